@@ -0,0 +1,81 @@
+//! Cost of a request from a fresh client, with and without the process-wide connection pool,
+//! and of cloning a client.
+//!
+//! Run with `cargo bench --bench client`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mlflow_rs::{MLFLowRestClient, MLFLowRestClientBuilder, MLFlowClient};
+use std::io::{BufRead, BufReader, Write};
+
+/// A keep-alive server answering every request with the default experiment.
+fn serve() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            // write! sends the answer in pieces, which Nagle's algorithm would hold back
+            stream.set_nodelay(true).unwrap();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                        return;
+                    }
+                    while line.trim() != "" {
+                        line.clear();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                    }
+                    let body = r#"{"experiment": {"experiment_id": "0", "name": "Default"}}"#;
+                    let answered = write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    if answered.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    host
+}
+
+fn requests(c: &mut Criterion) {
+    let host = serve();
+    let mut group = c.benchmark_group("request from a fresh client");
+    // reuses a pooled connection
+    group.bench_function("shared pool", |b| {
+        b.iter(|| {
+            MLFLowRestClient::new(&host)
+                .get_experiment_by_id("0")
+                .unwrap()
+        })
+    });
+    // builds a reqwest client and opens a connection every time
+    group.bench_function("own pool", |b| {
+        b.iter(|| {
+            MLFLowRestClientBuilder::new(&host)
+                .unwrap()
+                .build()
+                .unwrap()
+                .get_experiment_by_id("0")
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn clones(c: &mut Criterion) {
+    let client = MLFLowRestClient::new("http://localhost:5000").with_bearer_token("t0ken");
+    c.bench_function("clone", |b| b.iter(|| client.clone()));
+}
+
+criterion_group!(benches, requests, clones);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! Derive macros for [mlflow-rs](https://github.com/gavinmead/mlflow-rs). Use them through the
+//! `derive` feature of `mlflow-rs` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implements `mlflow_rs::MlflowParams` for a struct with named fields, turning every field
+/// into a param keyed by the field name.
+///
+/// Fields accept `#[mlflow(rename = "key")]` to log under another key, `#[mlflow(skip)]` to
+/// leave them out and `#[mlflow(flatten)]` to log the params of a nested `MlflowParams` struct
+/// with the field's key as a dotted prefix.
+#[proc_macro_derive(MlflowParams, attributes(mlflow))]
+pub fn derive_mlflow_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "MlflowParams can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "MlflowParams can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut pushes = vec![];
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named fields have identifiers");
+        let mut key = ident.to_string().trim_start_matches("r#").to_string();
+        let (mut skip, mut flatten) = (false, false);
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("mlflow")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    key = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    flatten = true;
+                } else {
+                    return Err(meta.error("expected `rename = \"...\"`, `skip` or `flatten`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+        pushes.push(if flatten {
+            quote! {
+                for param in ::mlflow_rs::MlflowParams::to_params(&self.#ident) {
+                    params.push(::mlflow_rs::Param {
+                        key: ::std::format!("{}.{}", #key, param.key),
+                        value: param.value,
+                    });
+                }
+            }
+        } else {
+            quote! {
+                if let ::std::option::Option::Some(value) =
+                    ::mlflow_rs::ParamValue::param_value(&self.#ident)
+                {
+                    params.push(::mlflow_rs::Param {
+                        key: ::std::string::ToString::to_string(#key),
+                        value,
+                    });
+                }
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::mlflow_rs::MlflowParams for #name #ty_generics #where_clause {
+            fn to_params(&self) -> ::std::vec::Vec<::mlflow_rs::Param> {
+                let mut params = ::std::vec::Vec::new();
+                #(#pushes)*
+                params
+            }
+        }
+    })
+}
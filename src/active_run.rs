@@ -0,0 +1,102 @@
+//! Scope guard that terminates a run when it goes out of scope.
+
+use crate::entities::RunStatus;
+use crate::heartbeat::Heartbeat;
+use crate::run::{Run, RunBuilder};
+use crate::termination::{deregister_run, register_run};
+use crate::MLFlowResult;
+use std::ops::{Deref, DerefMut};
+
+/// A [`Run`] that is ended automatically when dropped, the equivalent of Python's
+/// `with mlflow.start_run():`.
+///
+/// A run that is still `RUNNING` when the guard is dropped is marked `FINISHED`, or `FAILED` if
+/// the thread is unwinding from a panic. Use [`ActiveRun::end`] to choose the status explicitly
+/// and observe errors, which `Drop` can only log. Runs of guards that are never dropped, e.g.
+/// when the process is killed, can be ended with
+/// [`install_termination_hook`](crate::install_termination_hook).
+///
+/// ```no_run
+/// use mlflow_rs::RunBuilder;
+///
+/// let run = RunBuilder::new("1").unwrap().start().unwrap();
+/// run.log_metric("rmse", 0.4, None, None).unwrap();
+/// // run is marked FINISHED here
+/// ```
+#[derive(Debug)]
+pub struct ActiveRun {
+    run: Run,
+    ended: bool,
+    registration: u64,
+    pub(crate) heartbeat: Option<Heartbeat>,
+}
+
+impl ActiveRun {
+    /// Ends the run with `status` now instead of when the guard is dropped.
+    pub fn end(mut self, status: RunStatus) -> MLFlowResult<()> {
+        self.ended = true;
+        self.heartbeat.take();
+        deregister_run(self.registration);
+        self.run.finish(status)
+    }
+}
+
+impl From<Run> for ActiveRun {
+    fn from(run: Run) -> Self {
+        let registration = register_run(&run);
+        ActiveRun {
+            run,
+            ended: false,
+            registration,
+            heartbeat: None,
+        }
+    }
+}
+
+impl Deref for ActiveRun {
+    type Target = Run;
+
+    fn deref(&self) -> &Self::Target {
+        &self.run
+    }
+}
+
+impl DerefMut for ActiveRun {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.run
+    }
+}
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        self.heartbeat.take();
+        if self.ended {
+            return;
+        }
+        // a run no longer registered was ended by terminate_active_runs
+        if !deregister_run(self.registration) || self.run.status() != RunStatus::Running {
+            return;
+        }
+
+        let status = if std::thread::panicking() {
+            RunStatus::Failed
+        } else {
+            RunStatus::Finished
+        };
+        if let Err(e) = self.run.finish(status) {
+            log::warn!(
+                "could not end run {} with status {:?}: {}",
+                self.run.info.run_id,
+                status,
+                e
+            );
+        }
+    }
+}
+
+impl RunBuilder {
+    /// Creates the run and wraps it in an [`ActiveRun`] guard.
+    pub fn start(self) -> MLFlowResult<ActiveRun> {
+        self.build().map(ActiveRun::from)
+    }
+}
@@ -0,0 +1,603 @@
+//! Artifact storage for runs through the tracking server's proxied artifact API
+//! (`mlflow server --serve-artifacts`, the default since MLflow 2.0), or directly in the store
+//! the run's artifact location points at when the client can reach it.
+//!
+//! Stores are [`ArtifactRepository`] implementations chosen by the scheme of the run's artifact
+//! URI. Other schemes can be supported with [`register_artifact_repository`].
+
+use crate::client::SharedClient;
+use crate::local::LocalArtifactRepository;
+use crate::pagination::{Page, PageIterator};
+use crate::run::Run;
+use crate::transfer::ProgressCallback;
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+const MLFLOW_ARTIFACTS_SCHEME: &str = "mlflow-artifacts:";
+
+/// An entry in a run's artifact listing. `path` is relative to the run's artifact root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileInfo {
+    pub path: String,
+    #[serde(default)]
+    pub is_dir: bool,
+    #[serde(default)]
+    pub file_size: Option<i64>,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Resolves a `mlflow-artifacts:` URI to the path it refers to inside the proxied artifact store.
+///
+/// Both the short form (`mlflow-artifacts:/1/abc/artifacts`) and the form carrying an authority
+/// (`mlflow-artifacts://host:5000/1/abc/artifacts`) are accepted; requests are always routed
+/// through the tracking server the client is configured with.
+pub(crate) fn artifact_repository_path(artifact_uri: &str) -> MLFlowResult<String> {
+    let path = artifact_uri
+        .strip_prefix(MLFLOW_ARTIFACTS_SCHEME)
+        .ok_or_else(|| {
+            ArtifactError(format!(
+                "unsupported artifact location {}, only {} URIs are supported",
+                artifact_uri, MLFLOW_ARTIFACTS_SCHEME
+            ))
+        })?;
+
+    let path = match path.strip_prefix("//") {
+        Some(authority_and_path) => authority_and_path
+            .find('/')
+            .map(|i| &authority_and_path[i..])
+            .unwrap_or_default(),
+        None => path,
+    };
+
+    Ok(path.trim_matches('/').to_string())
+}
+
+/// Joins a run relative artifact path onto a repository path, rejecting paths that would escape
+/// the run's artifact root.
+pub(crate) fn join_artifact_path(root: &str, artifact_path: &str) -> MLFlowResult<String> {
+    let mut joined = root.trim_end_matches('/').to_string();
+    for segment in artifact_path.split('/').filter(|s| !s.is_empty()) {
+        if segment == "." || segment == ".." {
+            return Err(ArtifactError(format!(
+                "artifact path {} must not contain relative segments",
+                artifact_path
+            )));
+        }
+        if !joined.is_empty() {
+            joined.push('/');
+        }
+        joined.push_str(segment);
+    }
+    Ok(joined)
+}
+
+/// Maps a `/` separated artifact path onto a local destination directory.
+pub(crate) fn local_artifact_path(dest: &Path, artifact_path: &str) -> PathBuf {
+    artifact_path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .fold(dest.to_path_buf(), |path, segment| path.join(segment))
+}
+
+/// Lists every file below `dir` together with its `/` separated path relative to `dir`.
+pub(crate) fn collect_files(dir: &Path) -> MLFlowResult<Vec<(PathBuf, String)>> {
+    let mut files = vec![];
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current).map_err(|e| ArtifactError(e.to_string()))?;
+        for entry in entries {
+            let path = entry.map_err(|e| ArtifactError(e.to_string()))?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                let relative = path
+                    .strip_prefix(dir)
+                    .map_err(|e| ArtifactError(e.to_string()))?
+                    .components()
+                    .filter_map(|c| match c {
+                        Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+                        _ => None,
+                    })
+                    .collect::<Vec<String>>()
+                    .join("/");
+                files.push((path, relative));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Storage for the artifacts of one run, rooted at the run's artifact location. Paths are `/`
+/// separated and relative to that root.
+///
+/// Implement it and [`register_artifact_repository`] a factory for its URI scheme to store
+/// artifacts in a backend the crate doesn't support, e.g. MinIO or an internal object store.
+pub trait ArtifactRepository: Debug + Send + Sync {
+    /// Uploads `local_file` as the file `artifact_path`.
+    fn log_artifact(&self, local_file: &Path, artifact_path: &str) -> MLFlowResult<()>;
+
+    /// Lists the entries directly below `path`, or below the root when `path` is `None`. A file
+    /// or a missing path has no entries.
+    fn list_artifacts(&self, path: Option<&str>) -> MLFlowResult<Vec<FileInfo>>;
+
+    /// Downloads the file `artifact_path` to `local_file`, whose directory already exists.
+    fn download_artifact(&self, artifact_path: &str, local_file: &Path) -> MLFlowResult<()>;
+
+    /// Deletes the file or, recursively, the directory `artifact_path`. Deleting a missing path
+    /// succeeds. The default implementation reports that the store doesn't support deletion.
+    fn delete_artifacts(&self, artifact_path: &str) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "cannot delete {}, the artifact store does not support deleting artifacts",
+            artifact_path
+        )))
+    }
+
+    /// Like [`log_artifact`](Self::log_artifact), reporting the bytes sent to `progress`. The
+    /// default implementation reports the whole file once it has been uploaded.
+    fn log_artifact_with_progress(
+        &self,
+        local_file: &Path,
+        artifact_path: &str,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        self.log_artifact(local_file, artifact_path)?;
+        progress(file_size(local_file));
+        Ok(())
+    }
+
+    /// Like [`download_artifact`](Self::download_artifact), reporting the bytes received to
+    /// `progress`. The default implementation reports the whole file once it has been
+    /// downloaded.
+    fn download_artifact_with_progress(
+        &self,
+        artifact_path: &str,
+        local_file: &Path,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        self.download_artifact(artifact_path, local_file)?;
+        progress(file_size(local_file));
+        Ok(())
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or_default()
+}
+
+/// Creates the repository for a run from its artifact URI.
+pub type ArtifactRepositoryFactory =
+    Arc<dyn Fn(&str) -> MLFlowResult<Box<dyn ArtifactRepository>> + Send + Sync>;
+
+static REPOSITORIES: RwLock<Vec<(String, ArtifactRepositoryFactory)>> = RwLock::new(vec![]);
+
+/// Uses `factory` for runs whose artifact location has the URI `scheme`, e.g. `s3` for
+/// `s3://bucket/1/abc/artifacts`. Registered factories take precedence over the built in
+/// repositories, and registering a scheme again replaces its factory.
+pub fn register_artifact_repository<F>(scheme: impl AsRef<str>, factory: F)
+where
+    F: Fn(&str) -> MLFlowResult<Box<dyn ArtifactRepository>> + Send + Sync + 'static,
+{
+    let scheme = scheme.as_ref().to_ascii_lowercase();
+    let mut repositories = REPOSITORIES.write().unwrap_or_else(|e| e.into_inner());
+    repositories.retain(|(s, _)| *s != scheme);
+    repositories.push((scheme, Arc::new(factory)));
+}
+
+/// Whether a factory was registered for `scheme` with [`register_artifact_repository`].
+pub(crate) fn is_registered_scheme(scheme: &str) -> bool {
+    REPOSITORIES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|(s, _)| s == scheme)
+}
+
+/// The scheme of a URI, or `None` for plain paths.
+pub(crate) fn uri_scheme(uri: &str) -> Option<String> {
+    let (scheme, _) = uri.split_once(':')?;
+    let mut chars = scheme.chars();
+    // a single letter is a windows drive
+    let valid = scheme.len() > 1
+        && chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// The repository storing the artifacts under `artifact_uri`, chosen by its scheme. `run_id` is
+/// the run owning the artifacts, if any.
+pub(crate) fn artifact_repository(
+    artifact_uri: &str,
+    client: &SharedClient,
+    run_id: Option<&str>,
+) -> MLFlowResult<Box<dyn ArtifactRepository>> {
+    let scheme = uri_scheme(artifact_uri);
+
+    let factory = REPOSITORIES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(s, _)| Some(s) == scheme.as_ref())
+        .map(|(_, factory)| factory.clone());
+    if let Some(factory) = factory {
+        return factory(artifact_uri);
+    }
+
+    match scheme.as_deref() {
+        None | Some("file") => Ok(Box::new(LocalArtifactRepository::new(artifact_uri)?)),
+        #[cfg(feature = "azure")]
+        Some("wasbs" | "abfss") => Ok(Box::new(crate::azure::AzureBlobRepository::new(
+            artifact_uri,
+        )?)),
+        #[cfg(not(feature = "azure"))]
+        Some("wasbs" | "abfss") => Err(ArtifactError(format!(
+            "artifact location {} requires the azure feature",
+            artifact_uri
+        ))),
+        Some("mlflow-artifacts") => Ok(Box::new(ProxiedArtifactRepository {
+            client: client.clone(),
+            run_id: run_id.map(|r| r.to_string()),
+            root: artifact_repository_path(artifact_uri)?,
+        })),
+        Some(scheme) => Err(ArtifactError(format!(
+            "no artifact repository is registered for {} locations like {}",
+            scheme, artifact_uri
+        ))),
+    }
+}
+
+/// Artifacts uploaded through the tracking server the run's client talks to. Listing goes
+/// through the run's artifact listing, so it needs the run the artifacts belong to.
+#[derive(Debug)]
+struct ProxiedArtifactRepository {
+    client: SharedClient,
+    run_id: Option<String>,
+    root: String,
+}
+
+impl ArtifactRepository for ProxiedArtifactRepository {
+    fn log_artifact(&self, local_file: &Path, artifact_path: &str) -> MLFlowResult<()> {
+        self.client
+            .upload_artifact(&join_artifact_path(&self.root, artifact_path)?, local_file)
+    }
+
+    /// Follows the server's pagination until every entry has been fetched.
+    fn list_artifacts(&self, path: Option<&str>) -> MLFlowResult<Vec<FileInfo>> {
+        let run_id = self.run_id.as_deref().ok_or_else(|| {
+            ArtifactError(format!(
+                "cannot list artifacts of {} outside a run",
+                self.root
+            ))
+        })?;
+        let client = self.client.clone();
+        let (run_id, path) = (run_id.to_string(), path.map(str::to_string));
+        PageIterator::new(move |page_token| {
+            client
+                .list_artifacts(&run_id, path.as_deref(), page_token)
+                .map(|r| Page::new(r.files, r.next_page_token))
+        })
+        .collect()
+    }
+
+    fn download_artifact(&self, artifact_path: &str, local_file: &Path) -> MLFlowResult<()> {
+        self.client
+            .download_artifact(&join_artifact_path(&self.root, artifact_path)?, local_file)
+    }
+
+    fn delete_artifacts(&self, artifact_path: &str) -> MLFlowResult<()> {
+        self.client
+            .delete_artifact(&join_artifact_path(&self.root, artifact_path)?)
+    }
+
+    fn log_artifact_with_progress(
+        &self,
+        local_file: &Path,
+        artifact_path: &str,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        self.client.upload_artifact_with_progress(
+            &join_artifact_path(&self.root, artifact_path)?,
+            local_file,
+            progress,
+        )
+    }
+
+    fn download_artifact_with_progress(
+        &self,
+        artifact_path: &str,
+        local_file: &Path,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        self.client.download_artifact_with_progress(
+            &join_artifact_path(&self.root, artifact_path)?,
+            local_file,
+            progress,
+        )
+    }
+}
+
+impl Run {
+    /// The repository storing the run's artifacts, chosen by the scheme of its artifact URI.
+    pub(crate) fn artifact_repository(&self) -> MLFlowResult<Box<dyn ArtifactRepository>> {
+        let artifact_uri = self
+            .info
+            .artifact_uri
+            .as_deref()
+            .ok_or_else(|| ArtifactError("run has no artifact location".to_string()))?;
+        artifact_repository(artifact_uri, &self.client, Some(&self.info.run_id))
+    }
+
+    /// Uploads a local file into the run's artifacts, under the optional `artifact_path` directory.
+    pub fn log_artifact(
+        &self,
+        local_path: impl AsRef<Path>,
+        artifact_path: Option<&str>,
+    ) -> MLFlowResult<()> {
+        self.artifact_transfer()
+            .log_artifact(local_path, artifact_path)
+    }
+
+    /// Uploads the contents of a local directory into the run's artifacts, preserving the
+    /// directory structure, under the optional `artifact_path` directory.
+    pub fn log_artifacts(
+        &self,
+        local_dir: impl AsRef<Path>,
+        artifact_path: Option<&str>,
+    ) -> MLFlowResult<()> {
+        self.artifact_transfer()
+            .log_artifacts(local_dir, artifact_path)
+    }
+
+    /// Uploads `contents` as the artifact file `artifact_file`, e.g. `reports/summary.txt`.
+    pub(crate) fn log_bytes(&self, contents: &[u8], artifact_file: &str) -> MLFlowResult<()> {
+        if artifact_file.ends_with('/') {
+            return Err(ArtifactError(format!(
+                "artifact file {} must not be a directory",
+                artifact_file
+            )));
+        }
+        let artifact_file = join_artifact_path("", artifact_file)?;
+        let (directory, name) = artifact_file
+            .rsplit_once('/')
+            .unwrap_or(("", artifact_file.as_str()));
+        if name.is_empty() {
+            return Err(ArtifactError("artifact_file cannot be empty".to_string()));
+        }
+
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", Uuid::new_v4()));
+        let file = dir.join(name);
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(&file, contents))
+            .map_err(|e| ArtifactError(e.to_string()))
+            .and_then(|_| self.log_artifact(&file, Some(directory).filter(|d| !d.is_empty())));
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    /// Uploads `text` as the artifact file `artifact_file`, e.g. `reports/summary.txt`.
+    pub fn log_text(&self, text: impl AsRef<str>, artifact_file: &str) -> MLFlowResult<()> {
+        self.log_bytes(text.as_ref().as_bytes(), artifact_file)
+    }
+
+    /// Uploads `dict` as the artifact file `artifact_file`, serialized as YAML when it ends with
+    /// `.yaml` or `.yml` and as JSON otherwise.
+    pub fn log_dict(&self, dict: &impl Serialize, artifact_file: &str) -> MLFlowResult<()> {
+        let contents = match artifact_file.ends_with(".yaml") || artifact_file.ends_with(".yml") {
+            true => serde_yaml::to_string(dict).map_err(|e| ArtifactError(e.to_string()))?,
+            false => {
+                serde_json::to_string_pretty(dict).map_err(|e| ArtifactError(e.to_string()))?
+            }
+        };
+        self.log_bytes(contents.as_bytes(), artifact_file)
+    }
+
+    /// Uploads `value` serialized as JSON as the artifact file `artifact_file`.
+    pub fn log_json(&self, value: &impl Serialize, artifact_file: &str) -> MLFlowResult<()> {
+        let contents =
+            serde_json::to_string_pretty(value).map_err(|e| ArtifactError(e.to_string()))?;
+        self.log_bytes(contents.as_bytes(), artifact_file)
+    }
+
+    /// Lists the run's artifacts directly below `path`, or below the artifact root when `path`
+    /// is `None`.
+    pub fn list_artifacts(&self, path: Option<&str>) -> MLFlowResult<Vec<FileInfo>> {
+        self.artifact_repository()?.list_artifacts(path)
+    }
+
+    /// Deletes a file or, recursively, a directory of the run's artifacts.
+    pub fn delete_artifacts(&self, artifact_path: &str) -> MLFlowResult<()> {
+        self.artifact_repository()?.delete_artifacts(artifact_path)
+    }
+
+    /// Downloads a file or, recursively, a directory of the run's artifacts into `dest`,
+    /// preserving `artifact_path` below it. Returns the local path of the downloaded artifact.
+    pub fn download_artifact(
+        &self,
+        artifact_path: &str,
+        dest: impl AsRef<Path>,
+    ) -> MLFlowResult<PathBuf> {
+        self.artifact_transfer()
+            .download_artifact(artifact_path, dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repository_path_from_short_uri() {
+        assert_eq!(
+            artifact_repository_path("mlflow-artifacts:/1/abc/artifacts").unwrap(),
+            "1/abc/artifacts"
+        );
+    }
+
+    #[test]
+    fn repository_path_from_uri_with_authority() {
+        assert_eq!(
+            artifact_repository_path("mlflow-artifacts://localhost:5000/1/abc/artifacts").unwrap(),
+            "1/abc/artifacts"
+        );
+    }
+
+    #[test]
+    fn repository_path_unsupported_scheme() {
+        artifact_repository_path("s3://bucket/1/abc/artifacts")
+            .expect_err("only mlflow-artifacts URIs are supported");
+    }
+
+    #[test]
+    fn join_paths() {
+        assert_eq!(join_artifact_path("1/abc", "").unwrap(), "1/abc");
+        assert_eq!(
+            join_artifact_path("1/abc/", "model//weights.bin").unwrap(),
+            "1/abc/model/weights.bin"
+        );
+        join_artifact_path("1/abc", "../other").expect_err("relative segments are rejected");
+    }
+
+    #[test]
+    fn local_paths() {
+        let dest = Path::new("/tmp/downloads");
+        assert_eq!(local_artifact_path(dest, ""), dest);
+        assert_eq!(
+            local_artifact_path(dest, "model/weights.bin"),
+            dest.join("model").join("weights.bin")
+        );
+    }
+
+    #[test]
+    fn file_info_from_server_json() {
+        let info: FileInfo = serde_json::from_str(r#"{"path": "model"}"#).unwrap();
+        assert!(!info.is_dir);
+        assert_eq!(info.file_size, None);
+
+        let info: FileInfo = serde_json::from_str(
+            r#"{"path": "model/weights.bin", "is_dir": false, "file_size": 7}"#,
+        )
+        .unwrap();
+        assert_eq!(info.file_size, Some(7));
+    }
+
+    #[test]
+    fn collect_files_is_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("nested/deeper")).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("nested/b.txt"), "b").unwrap();
+        std::fs::write(dir.path().join("nested/deeper/c.txt"), "c").unwrap();
+
+        let relative: Vec<String> = collect_files(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|(_, r)| r)
+            .collect();
+        assert_eq!(
+            relative,
+            vec!["a.txt", "nested/b.txt", "nested/deeper/c.txt"]
+        );
+    }
+
+    #[test]
+    fn uri_schemes() {
+        assert_eq!(
+            uri_scheme("mlflow-artifacts:/1/abc").as_deref(),
+            Some("mlflow-artifacts")
+        );
+        assert_eq!(uri_scheme("S3://bucket/1").as_deref(), Some("s3"));
+        assert_eq!(uri_scheme("/mlruns/1/abc"), None);
+        assert_eq!(uri_scheme("C:\\mlruns\\1"), None);
+    }
+
+    /// Keeps artifacts in memory, keyed by their path below the artifact root.
+    #[derive(Debug, Default)]
+    struct MemoryRepository {
+        files: std::sync::Mutex<std::collections::BTreeMap<String, Vec<u8>>>,
+    }
+
+    impl ArtifactRepository for Arc<MemoryRepository> {
+        fn log_artifact(&self, local_file: &Path, artifact_path: &str) -> MLFlowResult<()> {
+            let contents = std::fs::read(local_file).map_err(|e| ArtifactError(e.to_string()))?;
+            self.files
+                .lock()
+                .unwrap()
+                .insert(artifact_path.to_string(), contents);
+            Ok(())
+        }
+
+        fn list_artifacts(&self, path: Option<&str>) -> MLFlowResult<Vec<FileInfo>> {
+            let prefix = path.map(|p| format!("{}/", p)).unwrap_or_default();
+            Ok(self
+                .files
+                .lock()
+                .unwrap()
+                .keys()
+                .filter_map(|k| k.strip_prefix(&prefix))
+                .map(|rest| FileInfo {
+                    path: format!("{}{}", prefix, rest),
+                    is_dir: false,
+                    file_size: None,
+                    extra: serde_json::Map::new(),
+                })
+                .collect())
+        }
+
+        fn download_artifact(&self, artifact_path: &str, local_file: &Path) -> MLFlowResult<()> {
+            let files = self.files.lock().unwrap();
+            let contents = files
+                .get(artifact_path)
+                .ok_or_else(|| ArtifactError(format!("{} not found", artifact_path)))?;
+            std::fs::write(local_file, contents).map_err(|e| ArtifactError(e.to_string()))
+        }
+    }
+
+    #[test]
+    fn registered_repositories() {
+        let repository = Arc::new(MemoryRepository::default());
+        let registered = repository.clone();
+        register_artifact_repository("memory", move |uri| {
+            assert_eq!(uri, "memory://bucket/1/abc/artifacts");
+            Ok(Box::new(registered.clone()))
+        });
+
+        let run = Run {
+            info: serde_json::from_str(
+                r#"{"run_id": "abc", "experiment_id": "1", "status": "RUNNING",
+                    "artifact_uri": "memory://bucket/1/abc/artifacts"}"#,
+            )
+            .unwrap(),
+            data: crate::RunData::default(),
+            client: SharedClient::default(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        run.log_artifact(dir.path().join("notes.txt"), Some("docs"))
+            .unwrap();
+        assert_eq!(
+            repository.files.lock().unwrap().get("docs/notes.txt"),
+            Some(&b"notes".to_vec())
+        );
+
+        let downloaded = run
+            .download_artifact("docs/notes.txt", dir.path().join("downloads"))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(downloaded).unwrap(), "notes");
+
+        let mut unsupported = run.clone();
+        unsupported.info.artifact_uri = Some("gs://bucket/1/abc/artifacts".to_string());
+        unsupported
+            .list_artifacts(None)
+            .expect_err("no repository is registered for gs");
+    }
+}
@@ -0,0 +1,627 @@
+//! Async counterpart of [`MLFLowRestClient`](crate::MLFLowRestClient) built on the non-blocking
+//! `reqwest` client, for use inside a tokio runtime where `reqwest::blocking` would panic.
+//!
+//! It is the only client available on `wasm32`, built with `default-features = false` and the
+//! `async` feature, where requests go through the browser's `fetch` and its futures are not
+//! `Send`.
+
+use crate::client::{
+    chunk_batch, param_value_changed, server_error, Auth, CreateExperimentRequest,
+    CreateExperimentResponse, CreateRunRequest, CreateRunResponse, EmptyResponse, ExperimentInfo,
+    GetExperimentResponse, GetRunResponse, ListArtifactsResponse, LogMetricRequest,
+    LogParamRequest, MetricHistoryResponse, RunResponse, SearchExperimentsRequest,
+    SearchExperimentsResponse, SearchRunsRequest, SearchRunsResponse, UpdateRunRequest,
+    UpdateRunResponse,
+};
+use crate::dataset::DatasetInput;
+use crate::run::current_time_millis;
+use crate::tags::with_default_tags;
+use crate::telemetry::{redact_body, send_async};
+use crate::version::ServerVersion;
+use crate::MLFlowError::{ExperimentNotFound, RunNotFound, UnknownError};
+use crate::{ExperimentTag, MLFlowError, MLFlowResult, Metric, Param, RunInfo, RunStatus, RunTag};
+use reqwest::{Client, IntoUrl, Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+
+/// `Send`, except on `wasm32` where futures are bound to the single thread of the page or worker.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+/// `Send`, except on `wasm32` where futures are bound to the single thread of the page or worker.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// The operations [`MLFlowAsyncRestClient`] performs against a tracking server, awaited rather
+/// than blocking.
+///
+/// It covers a subset of [`MLFlowClient`](crate::MLFlowClient): experiments, runs, metrics,
+/// params, tags, dataset inputs, listing artifacts and the server version. Uploading and
+/// downloading artifacts, the model registry, traces, webhooks and the auth and garbage
+/// collection endpoints are only available through the blocking client.
+pub trait AsyncMLFlowClient {
+    /// Creates an experiment and returns its id.
+    fn create_experiment(
+        &self,
+        name: &str,
+        artifact_location: Option<&str>,
+        tags: Vec<ExperimentTag>,
+    ) -> impl Future<Output = MLFlowResult<String>> + MaybeSend;
+
+    fn get_experiment_by_id(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = MLFlowResult<ExperimentInfo>> + MaybeSend;
+
+    fn get_experiment_by_name(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = MLFlowResult<ExperimentInfo>> + MaybeSend;
+
+    /// Fetches one page of an experiment search.
+    fn search_experiments(
+        &self,
+        request: &SearchExperimentsRequest,
+    ) -> impl Future<Output = MLFlowResult<SearchExperimentsResponse>> + MaybeSend;
+
+    fn update_experiment(
+        &self,
+        experiment_id: &str,
+        new_name: &str,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    fn delete_experiment(
+        &self,
+        experiment_id: &str,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    fn restore_experiment(
+        &self,
+        experiment_id: &str,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    /// Creates a run in the given experiment, starting it at the current time.
+    fn create_run(
+        &self,
+        experiment_id: &str,
+        run_name: Option<&str>,
+        tags: Vec<RunTag>,
+    ) -> impl Future<Output = MLFlowResult<RunInfo>> + MaybeSend;
+
+    /// Fetches a run with its latest metrics, params and tags.
+    fn get_run(&self, run_id: &str) -> impl Future<Output = MLFlowResult<RunResponse>> + MaybeSend;
+
+    fn update_run(
+        &self,
+        run_id: &str,
+        status: RunStatus,
+        end_time: Option<i64>,
+    ) -> impl Future<Output = MLFlowResult<RunInfo>> + MaybeSend;
+
+    /// Marks a run as deleted. Deleted runs are only returned by searches for deleted runs.
+    fn delete_run(&self, run_id: &str) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    fn restore_run(&self, run_id: &str) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    /// Fetches one page of a run search.
+    fn search_runs(
+        &self,
+        request: &SearchRunsRequest,
+    ) -> impl Future<Output = MLFlowResult<SearchRunsResponse>> + MaybeSend;
+
+    fn log_metric(
+        &self,
+        run_id: &str,
+        metric: Metric,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    fn log_param(
+        &self,
+        run_id: &str,
+        param: Param,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    /// Fetches one page of a metric's history.
+    fn get_metric_history(
+        &self,
+        run_id: &str,
+        key: &str,
+        page_token: Option<&str>,
+    ) -> impl Future<Output = MLFlowResult<MetricHistoryResponse>> + MaybeSend;
+
+    /// Logs metrics, params and tags in as few requests as the server limits allow.
+    fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    fn set_tag(
+        &self,
+        run_id: &str,
+        tag: RunTag,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    fn delete_tag(
+        &self,
+        run_id: &str,
+        key: &str,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    /// Records the datasets a run used.
+    fn log_inputs(
+        &self,
+        run_id: &str,
+        datasets: Vec<DatasetInput>,
+    ) -> impl Future<Output = MLFlowResult<()>> + MaybeSend;
+
+    /// Fetches one page of the artifacts of a run directly below `path`, or its artifact root.
+    fn list_artifacts(
+        &self,
+        run_id: &str,
+        path: Option<&str>,
+        page_token: Option<&str>,
+    ) -> impl Future<Output = MLFlowResult<ListArtifactsResponse>> + MaybeSend;
+
+    /// Asks the tracking server which MLflow release it runs.
+    fn server_version(&self) -> impl Future<Output = MLFlowResult<ServerVersion>> + MaybeSend;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MLFlowAsyncRestClient {
+    client: Client,
+    host: String,
+    auth: Option<Auth>,
+    verbose_http_logging: bool,
+}
+
+impl MLFlowAsyncRestClient {
+    pub fn new(host: impl AsRef<str>) -> Self {
+        MLFlowAsyncRestClient {
+            client: Client::new(),
+            host: host.as_ref().to_string(),
+            auth: None,
+            verbose_http_logging: false,
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn from_parts(
+        client: Client,
+        host: String,
+        auth: Option<Auth>,
+        verbose_http_logging: bool,
+    ) -> Self {
+        MLFlowAsyncRestClient {
+            client,
+            host,
+            auth,
+            verbose_http_logging,
+        }
+    }
+
+    /// See [`MLFLowRestClient::with_verbose_http_logging`](crate::MLFLowRestClient::with_verbose_http_logging).
+    pub fn with_verbose_http_logging(mut self) -> Self {
+        self.verbose_http_logging = true;
+        self
+    }
+
+    /// See [`MLFLowRestClient::with_bearer_token`](crate::MLFLowRestClient::with_bearer_token).
+    pub fn with_bearer_token(mut self, token: impl AsRef<str>) -> Self {
+        self.auth = Some(Auth::Bearer(token.as_ref().to_string()));
+        self
+    }
+
+    /// See [`MLFLowRestClient::with_basic_auth`](crate::MLFLowRestClient::with_basic_auth).
+    pub fn with_basic_auth(mut self, username: impl AsRef<str>, password: impl AsRef<str>) -> Self {
+        self.auth = Some(Auth::Basic {
+            username: username.as_ref().to_string(),
+            password: password.as_ref().to_string(),
+        });
+        self
+    }
+
+    async fn _send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        send_async(request, self.verbose_http_logging).await
+    }
+
+    /// Parses the body of a successful response, logging it first with verbose HTTP logging.
+    async fn _parse_json<T: DeserializeOwned>(&self, response: Response) -> MLFlowResult<T> {
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| UnknownError(e.to_string()))?;
+        if self.verbose_http_logging {
+            log::debug!("response body: {}", redact_body(&body));
+        }
+        serde_json::from_slice(&body).map_err(|e| {
+            log::debug!("could not parse response: {}", e);
+            UnknownError(e.to_string())
+        })
+    }
+
+    fn _request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        let builder = self.client.request(method, url);
+        match &self.auth {
+            Some(Auth::Bearer(token)) => builder.bearer_auth(token),
+            Some(Auth::Basic { username, password }) => {
+                builder.basic_auth(username, Some(password))
+            }
+            // rejected by MLFLowRestClientBuilder::build_async
+            #[cfg(feature = "blocking")]
+            Some(Auth::Provider(_)) => builder,
+            None => builder,
+        }
+    }
+
+    async fn _process_get<T: DeserializeOwned>(
+        &self,
+        result: Result<Response, reqwest::Error>,
+        not_found: MLFlowError,
+    ) -> MLFlowResult<T> {
+        match result {
+            Ok(r) => {
+                if r.status().is_success() {
+                    self._parse_json(r).await
+                } else if r.status() == StatusCode::NOT_FOUND {
+                    Err(not_found)
+                } else {
+                    let status = r.status();
+                    let body = r.text().await.unwrap_or_default();
+                    Err(server_error(status, &body, &not_found.to_string()))
+                }
+            }
+            Err(e) => Err(UnknownError(e.to_string())),
+        }
+    }
+
+    async fn _process_post<T: DeserializeOwned>(
+        &self,
+        result: Result<Response, reqwest::Error>,
+        error_message: &str,
+    ) -> MLFlowResult<T> {
+        match result {
+            Ok(result) => {
+                if result.status().is_success() {
+                    self._parse_json(result).await
+                } else {
+                    let status = result.status();
+                    let body = result.text().await.unwrap_or_default();
+                    Err(server_error(status, &body, error_message))
+                }
+            }
+            Err(result) => Err(UnknownError(result.to_string())),
+        }
+    }
+}
+
+impl AsyncMLFlowClient for MLFlowAsyncRestClient {
+    async fn create_experiment(
+        &self,
+        name: &str,
+        artifact_location: Option<&str>,
+        tags: Vec<ExperimentTag>,
+    ) -> MLFlowResult<String> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/create");
+        let request = CreateExperimentRequest {
+            name: name.to_string(),
+            artifact_location: artifact_location.map(|a| a.to_string()),
+            tags,
+        };
+        let result = self
+            ._send(self._request(Method::POST, url).json(&request))
+            .await;
+        self._process_post::<CreateExperimentResponse>(result, "Could not create experiment")
+            .await
+            .map(|r| r.experiment_id)
+    }
+
+    async fn get_experiment_by_id(&self, id: &str) -> MLFlowResult<ExperimentInfo> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/get");
+        let result = self
+            ._send(
+                self._request(Method::GET, url)
+                    .query(&[("experiment_id", id)]),
+            )
+            .await;
+        self._process_get::<GetExperimentResponse>(
+            result,
+            ExperimentNotFound("experiment was not found".to_string()),
+        )
+        .await
+        .map(|r| r.experiment)
+    }
+
+    async fn get_experiment_by_name(&self, name: &str) -> MLFlowResult<ExperimentInfo> {
+        let url = format!(
+            "{}{}",
+            &self.host, "/api/2.0/mlflow/experiments/get-by-name"
+        );
+        let result = self
+            ._send(
+                self._request(Method::GET, url)
+                    .query(&[("experiment_name", name)]),
+            )
+            .await;
+        self._process_get::<GetExperimentResponse>(
+            result,
+            ExperimentNotFound("experiment was not found".to_string()),
+        )
+        .await
+        .map(|r| r.experiment)
+    }
+
+    async fn search_experiments(
+        &self,
+        request: &SearchExperimentsRequest,
+    ) -> MLFlowResult<SearchExperimentsResponse> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/search");
+        let result = self
+            ._send(self._request(Method::POST, url).json(request))
+            .await;
+        self._process_post(result, "Could not search experiments")
+            .await
+    }
+
+    async fn update_experiment(&self, experiment_id: &str, new_name: &str) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/update");
+        let body = serde_json::json!({"experiment_id": experiment_id, "new_name": new_name});
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not update experiment")
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/delete");
+        let body = serde_json::json!({ "experiment_id": experiment_id });
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not delete experiment")
+            .await
+            .map(|_| ())
+    }
+
+    async fn restore_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/restore");
+        let body = serde_json::json!({ "experiment_id": experiment_id });
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not restore experiment")
+            .await
+            .map(|_| ())
+    }
+
+    async fn create_run(
+        &self,
+        experiment_id: &str,
+        run_name: Option<&str>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<RunInfo> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/create");
+        let request = CreateRunRequest {
+            experiment_id: experiment_id.to_string(),
+            run_name: run_name.map(|n| n.to_string()),
+            start_time: current_time_millis(),
+            tags: with_default_tags(tags),
+        };
+        let result = self
+            ._send(self._request(Method::POST, url).json(&request))
+            .await;
+        self._process_post::<CreateRunResponse>(result, "Could not create run")
+            .await
+            .map(|r| r.run.info)
+    }
+
+    async fn get_run(&self, run_id: &str) -> MLFlowResult<RunResponse> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/get");
+        let result = self
+            ._send(self._request(Method::GET, url).query(&[("run_id", run_id)]))
+            .await;
+        self._process_get::<GetRunResponse>(
+            result,
+            RunNotFound(format!("run {} was not found", run_id)),
+        )
+        .await
+        .map(|r| r.run)
+    }
+
+    async fn update_run(
+        &self,
+        run_id: &str,
+        status: RunStatus,
+        end_time: Option<i64>,
+    ) -> MLFlowResult<RunInfo> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/update");
+        let request = UpdateRunRequest {
+            run_id: run_id.to_string(),
+            status: Some(status),
+            end_time,
+            run_name: None,
+        };
+        let result = self
+            ._send(self._request(Method::POST, url).json(&request))
+            .await;
+        self._process_post::<UpdateRunResponse>(result, "Could not update run")
+            .await
+            .map(|r| r.run_info)
+    }
+
+    async fn delete_run(&self, run_id: &str) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/delete");
+        let body = serde_json::json!({ "run_id": run_id });
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not delete run")
+            .await
+            .map(|_| ())
+    }
+
+    async fn restore_run(&self, run_id: &str) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/restore");
+        let body = serde_json::json!({ "run_id": run_id });
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not restore run")
+            .await
+            .map(|_| ())
+    }
+
+    async fn search_runs(&self, request: &SearchRunsRequest) -> MLFlowResult<SearchRunsResponse> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/search");
+        let result = self
+            ._send(self._request(Method::POST, url).json(request))
+            .await;
+        self._process_post(result, "Could not search runs").await
+    }
+
+    async fn log_metric(&self, run_id: &str, metric: Metric) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/log-metric");
+        let request = LogMetricRequest {
+            run_id: run_id.to_string(),
+            metric,
+        };
+        let result = self
+            ._send(self._request(Method::POST, url).json(&request))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not log metric")
+            .await
+            .map(|_| ())
+    }
+
+    async fn log_param(&self, run_id: &str, param: Param) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/log-parameter");
+        let request = LogParamRequest {
+            run_id: run_id.to_string(),
+            param,
+        };
+        let result = self
+            ._send(self._request(Method::POST, url).json(&request))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not log param")
+            .await
+            .map(|_| ())
+            .map_err(param_value_changed)
+    }
+
+    async fn get_metric_history(
+        &self,
+        run_id: &str,
+        key: &str,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<MetricHistoryResponse> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/metrics/get-history");
+        let mut query = vec![("run_id", run_id), ("metric_key", key)];
+        if let Some(page_token) = page_token {
+            query.push(("page_token", page_token));
+        }
+        let result = self
+            ._send(self._request(Method::GET, url).query(&query))
+            .await;
+        self._process_post(result, "Could not get metric history")
+            .await
+    }
+
+    async fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/log-batch");
+        for request in chunk_batch(run_id, metrics, params, tags) {
+            let result = self
+                ._send(self._request(Method::POST, &url).json(&request))
+                .await;
+            self._process_post::<EmptyResponse>(result, "Could not log batch")
+                .await
+                .map_err(param_value_changed)?;
+        }
+        Ok(())
+    }
+
+    async fn set_tag(&self, run_id: &str, tag: RunTag) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/set-tag");
+        let body = serde_json::json!({"run_id": run_id, "key": tag.key, "value": tag.value});
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not set tag")
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete_tag(&self, run_id: &str, key: &str) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/delete-tag");
+        let body = serde_json::json!({"run_id": run_id, "key": key});
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not delete tag")
+            .await
+            .map(|_| ())
+    }
+
+    async fn log_inputs(&self, run_id: &str, datasets: Vec<DatasetInput>) -> MLFlowResult<()> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/runs/log-inputs");
+        let body = serde_json::json!({"run_id": run_id, "datasets": datasets});
+        let result = self
+            ._send(self._request(Method::POST, url).json(&body))
+            .await;
+        self._process_post::<EmptyResponse>(result, "Could not log inputs")
+            .await
+            .map(|_| ())
+    }
+
+    async fn list_artifacts(
+        &self,
+        run_id: &str,
+        path: Option<&str>,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<ListArtifactsResponse> {
+        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/artifacts/list");
+        let mut query = vec![("run_id", run_id)];
+        if let Some(path) = path {
+            query.push(("path", path));
+        }
+        if let Some(page_token) = page_token {
+            query.push(("page_token", page_token));
+        }
+        let result = self
+            ._send(self._request(Method::GET, url).query(&query))
+            .await;
+        self._process_post(result, "Could not list artifacts").await
+    }
+
+    async fn server_version(&self) -> MLFlowResult<ServerVersion> {
+        let url = format!("{}/version", &self.host);
+        let response = self
+            ._send(self._request(Method::GET, url))
+            .await
+            .map_err(|e| UnknownError(e.to_string()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| UnknownError(e.to_string()))?;
+        if !status.is_success() {
+            return Err(server_error(status, &body, "Could not get server version"));
+        }
+        body.parse()
+    }
+}
@@ -0,0 +1,431 @@
+//! Users and permissions of a tracking server running MLflow's basic-auth app
+//! (`mlflow server --app-name basic-auth`).
+//!
+//! A tracking server without the auth app answers these requests with an error.
+
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{
+    ExperimentPermissionRequest, MLFlowClient, SharedClient, UpdateUserAdminRequest,
+    UserPasswordRequest,
+};
+use crate::config::default_client;
+use crate::experiment::Experiment;
+use crate::MLFlowError::UserBuilderError;
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+/// What a user may do with an experiment or registered model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Permission {
+    Read,
+    /// Read, and log to or update the resource.
+    Edit,
+    /// Edit, and delete the resource and manage its permissions.
+    Manage,
+    NoPermissions,
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let permission = match self {
+            Permission::Read => "READ",
+            Permission::Edit => "EDIT",
+            Permission::Manage => "MANAGE",
+            Permission::NoPermissions => "NO_PERMISSIONS",
+        };
+        f.write_str(permission)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExperimentPermission {
+    pub experiment_id: String,
+    /// The id of the user the permission is granted to.
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    pub permission: Permission,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisteredModelPermission {
+    pub name: String,
+    /// The id of the user the permission is granted to.
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    pub permission: Permission,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A user of the auth app, with the permissions granted to them. Resources without an explicit
+/// permission fall back to the server's `default_permission`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    #[serde(default)]
+    pub is_admin: bool,
+    #[serde(default)]
+    pub experiment_permissions: Vec<ExperimentPermission>,
+    #[serde(default)]
+    pub registered_model_permissions: Vec<RegisteredModelPermission>,
+
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) client: SharedClient,
+}
+
+impl User {
+    pub(crate) fn with_client(mut self, client: SharedClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// The permission granted to the user on `experiment_id`, if any.
+    pub fn experiment_permission(&self, experiment_id: &str) -> Option<Permission> {
+        self.experiment_permissions
+            .iter()
+            .find(|p| p.experiment_id == experiment_id)
+            .map(|p| p.permission)
+    }
+
+    pub fn update_password(&self, password: impl AsRef<str>) -> MLFlowResult<()> {
+        self.client.update_user_password(&UserPasswordRequest {
+            username: self.username.clone(),
+            password: password.as_ref().to_string(),
+        })
+    }
+
+    /// Makes the user an admin, who may do anything regardless of permissions, or revokes it.
+    pub fn set_admin(&mut self, is_admin: bool) -> MLFlowResult<()> {
+        self.client.update_user_admin(&UpdateUserAdminRequest {
+            username: self.username.clone(),
+            is_admin,
+        })?;
+        self.is_admin = is_admin;
+        Ok(())
+    }
+
+    pub fn delete(self) -> MLFlowResult<()> {
+        self.client.delete_user(&self.username)
+    }
+}
+
+/// Creates a user of the auth app. Requires the credentials of an admin user.
+///
+/// ```no_run
+/// use mlflow_rs::UserBuilder;
+///
+/// let user = UserBuilder::new("alice", "correct-horse-battery-staple")
+///     .unwrap()
+///     .with_admin(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct UserBuilder {
+    username: String,
+    password: String,
+    is_admin: bool,
+    client: SharedClient,
+}
+
+impl Debug for UserBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserBuilder")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .field("is_admin", &self.is_admin)
+            .finish()
+    }
+}
+
+impl UserBuilder {
+    pub fn new(username: impl AsRef<str>, password: impl AsRef<str>) -> MLFlowResult<UserBuilder> {
+        if username.as_ref().is_empty() {
+            return Err(UserBuilderError("username cannot be empty".to_string()));
+        }
+        if password.as_ref().is_empty() {
+            return Err(UserBuilderError("password cannot be empty".to_string()));
+        }
+
+        Ok(UserBuilder {
+            username: username.as_ref().to_string(),
+            password: password.as_ref().to_string(),
+            is_admin: false,
+            client: default_client()?,
+        })
+    }
+
+    /// Makes the new user an admin.
+    pub fn with_admin(mut self, is_admin: bool) -> Self {
+        self.is_admin = is_admin;
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Creates the user. The auth API creates users without admin rights, so admins are
+    /// promoted with a second request.
+    fn request(&self) -> UserPasswordRequest {
+        UserPasswordRequest {
+            username: self.username.clone(),
+            password: self.password.clone(),
+        }
+    }
+
+    pub fn build(self) -> MLFlowResult<User> {
+        let resp = self.client.create_user(&self.request())?;
+        let mut user = resp.user.with_client(self.client);
+        if self.is_admin {
+            user.set_admin(true)?;
+        }
+        Ok(user)
+    }
+}
+
+/// Loads users by username.
+///
+/// ```no_run
+/// use mlflow_rs::UserLoader;
+///
+/// let user = UserLoader::default().load("alice").unwrap();
+/// println!("{:?}", user.experiment_permissions);
+/// ```
+#[derive(Default)]
+pub struct UserLoader {
+    client: Option<SharedClient>,
+}
+
+impl UserLoader {
+    #[cfg(feature = "blocking")]
+    pub fn with_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn load(self, username: impl AsRef<str>) -> MLFlowResult<User> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+        let resp = client.get_user(username.as_ref())?;
+        Ok(resp.user.with_client(client))
+    }
+}
+
+/// Permissions on an experiment. Managing them requires the
+/// [`Permission::Manage`] permission on the experiment, or an admin user.
+///
+/// ```no_run
+/// use mlflow_rs::{ExperimentIdentifierType, ExperimentLoader, Permission};
+///
+/// let experiment = ExperimentLoader::default()
+///     .load(ExperimentIdentifierType::ByName("fraud-detection".to_string()))
+///     .unwrap();
+/// experiment.grant_permission("alice", Permission::Edit).unwrap();
+/// ```
+impl Experiment {
+    /// Grants `username` a permission on the experiment. Fails if they already have one, see
+    /// [`Experiment::update_permission`].
+    pub fn grant_permission(
+        &self,
+        username: impl AsRef<str>,
+        permission: Permission,
+    ) -> MLFlowResult<ExperimentPermission> {
+        let request = self.permission_request(username.as_ref(), permission)?;
+        Ok(self
+            .client
+            .create_experiment_permission(&request)?
+            .experiment_permission)
+    }
+
+    /// The permission explicitly granted to `username` on the experiment.
+    pub fn permission(&self, username: impl AsRef<str>) -> MLFlowResult<ExperimentPermission> {
+        Ok(self
+            .client
+            .get_experiment_permission(self.id(), username.as_ref())?
+            .experiment_permission)
+    }
+
+    pub fn update_permission(
+        &self,
+        username: impl AsRef<str>,
+        permission: Permission,
+    ) -> MLFlowResult<()> {
+        let request = self.permission_request(username.as_ref(), permission)?;
+        self.client.update_experiment_permission(&request)
+    }
+
+    /// Removes the permission granted to `username`, who falls back to the server's default.
+    pub fn revoke_permission(&self, username: impl AsRef<str>) -> MLFlowResult<()> {
+        self.client
+            .delete_experiment_permission(self.id(), username.as_ref())
+    }
+
+    fn permission_request(
+        &self,
+        username: &str,
+        permission: Permission,
+    ) -> MLFlowResult<ExperimentPermissionRequest> {
+        Ok(ExperimentPermissionRequest {
+            experiment_id: self.id().to_string(),
+            username: username.to_string(),
+            permission,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_response() {
+        let user: User = serde_json::from_value(serde_json::json!({
+            "id": 2,
+            "username": "alice",
+            "is_admin": false,
+            "experiment_permissions": [
+                {"experiment_id": "1", "user_id": 2, "permission": "EDIT"},
+            ],
+            "registered_model_permissions": [
+                {"name": "fraud-detector", "user_id": 2, "permission": "NO_PERMISSIONS"},
+            ],
+        }))
+        .unwrap();
+        assert_eq!(user.experiment_permission("1"), Some(Permission::Edit));
+        assert_eq!(user.experiment_permission("2"), None);
+        assert_eq!(
+            user.registered_model_permissions[0].permission,
+            Permission::NoPermissions
+        );
+        assert_eq!(Permission::NoPermissions.to_string(), "NO_PERMISSIONS");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn manage_experiment_permissions() {
+        use crate::{ExperimentBuilder, MLFlowError, MockMLFlowClient};
+
+        let mock = MockMLFlowClient::default();
+        UserBuilder::new("alice", "password")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let experiment = ExperimentBuilder::new("fraud-detection")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+
+        experiment
+            .grant_permission("alice", Permission::Read)
+            .unwrap();
+        assert!(matches!(
+            experiment.grant_permission("alice", Permission::Edit),
+            Err(MLFlowError::ResourceAlreadyExists(_))
+        ));
+        experiment
+            .update_permission("alice", Permission::Manage)
+            .unwrap();
+        assert_eq!(
+            experiment.permission("alice").unwrap().permission,
+            Permission::Manage
+        );
+
+        let user = UserLoader::default()
+            .with_mock_client(mock.clone())
+            .load("alice")
+            .unwrap();
+        assert_eq!(
+            user.experiment_permission(experiment.id()),
+            Some(Permission::Manage)
+        );
+
+        experiment.revoke_permission("alice").unwrap();
+        experiment
+            .permission("alice")
+            .expect_err("the permission was revoked");
+        experiment
+            .grant_permission("bob", Permission::Read)
+            .expect_err("bob is not a user");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn manage_users() {
+        use crate::{MLFlowError, MockMLFlowClient};
+
+        UserBuilder::new("alice", "").expect_err("password cannot be empty");
+
+        let mock = MockMLFlowClient::default();
+        let builder = UserBuilder::new("alice", "password")
+            .unwrap()
+            .with_admin(true)
+            .with_mock_client(mock.clone());
+        assert!(!format!("{:?}", builder).contains("\"password\""));
+        assert!(!format!("{:?}", builder.request()).contains("\"password\""));
+        let mut user = builder.clone().build().unwrap();
+        assert!(user.is_admin);
+        assert!(matches!(
+            builder.build(),
+            Err(MLFlowError::ResourceAlreadyExists(_))
+        ));
+
+        user.update_password("new-password").unwrap();
+        user.set_admin(false).unwrap();
+        let load = || {
+            UserLoader::default()
+                .with_mock_client(mock.clone())
+                .load("alice")
+        };
+        assert!(!load().unwrap().is_admin);
+
+        user.delete().unwrap();
+        load().expect_err("the user was deleted");
+    }
+}
@@ -0,0 +1,536 @@
+//! Artifact storage in Azure Blob Storage, for runs whose artifact location is a `wasbs://` or
+//! `abfss://` URI.
+//!
+//! Credentials are resolved from the environment like the Python client does: a SAS token from
+//! `AZURE_STORAGE_SAS_TOKEN` or the `SharedAccessSignature` of `AZURE_STORAGE_CONNECTION_STRING`,
+//! falling back to the managed identity of the host (`AZURE_CLIENT_ID` selects a user assigned
+//! identity).
+//!
+//! `abfss://` locations are accessed through the Blob endpoint of the account, which serves
+//! accounts with a hierarchical namespace too.
+
+use crate::artifacts::{join_artifact_path, ArtifactRepository, FileInfo};
+use crate::client::shared_http_client;
+use crate::telemetry::InstrumentedSend;
+use crate::transfer::{ProgressCallback, ProgressReader};
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::blocking::{Body, Client, RequestBuilder, Response};
+use reqwest::{Method, Url};
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const AZURE_SCHEMES: [&str; 2] = ["wasbs://", "abfss://"];
+const STORAGE_VERSION: &str = "2021-08-06";
+const STORAGE_RESOURCE: &str = "https://storage.azure.com/";
+const IMDS_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+/// Files at least this large are uploaded in blocks rather than with a single request.
+const BLOCK_UPLOAD_THRESHOLD: u64 = 256 * 1024 * 1024;
+const BLOCK_SIZE: u64 = 100 * 1024 * 1024;
+/// Times a block is sent before the upload fails.
+const BLOCK_UPLOAD_ATTEMPTS: u32 = 3;
+/// Tokens are refreshed this many seconds before they expire.
+const TOKEN_EXPIRY_MARGIN: u64 = 300;
+
+/// Managed identity tokens shared by every repository, keyed by the identity's client id.
+static TOKENS: Mutex<Vec<(Option<String>, AccessToken)>> = Mutex::new(vec![]);
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum AzureCredential {
+    SasToken(String),
+    ManagedIdentity { client_id: Option<String> },
+}
+
+impl AzureCredential {
+    fn from_env(env: impl Fn(&str) -> Option<String>) -> AzureCredential {
+        let sas_token = env("AZURE_STORAGE_SAS_TOKEN").or_else(|| {
+            env("AZURE_STORAGE_CONNECTION_STRING").and_then(|connection_string| {
+                connection_string
+                    .split(';')
+                    .find_map(|part| part.strip_prefix("SharedAccessSignature="))
+                    .map(|sas| sas.to_string())
+            })
+        });
+        match sas_token {
+            Some(token) => AzureCredential::SasToken(token.trim_start_matches('?').to_string()),
+            None => AzureCredential::ManagedIdentity {
+                client_id: env("AZURE_CLIENT_ID"),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AccessToken {
+    access_token: String,
+    /// Seconds since the UNIX epoch, sent as a string by the identity endpoints.
+    #[serde(deserialize_with = "seconds")]
+    expires_on: u64,
+}
+
+fn seconds<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seconds {
+        Number(u64),
+        String(String),
+    }
+    match Seconds::deserialize(deserializer)? {
+        Seconds::Number(n) => Ok(n),
+        Seconds::String(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The run's artifact directory within a blob container.
+#[derive(Debug)]
+pub(crate) struct AzureBlobRepository {
+    container_url: Url,
+    root: String,
+    credential: AzureCredential,
+    client: Client,
+}
+
+impl AzureBlobRepository {
+    pub(crate) fn new(artifact_uri: &str) -> MLFlowResult<AzureBlobRepository> {
+        Self::with_credential(
+            artifact_uri,
+            AzureCredential::from_env(|name| std::env::var(name).ok()),
+        )
+    }
+
+    /// Parses `wasbs://<container>@<account>.blob.core.windows.net/<path>` or
+    /// `abfss://<filesystem>@<account>.dfs.core.windows.net/<path>`.
+    pub(crate) fn with_credential(
+        artifact_uri: &str,
+        credential: AzureCredential,
+    ) -> MLFlowResult<AzureBlobRepository> {
+        let invalid = || ArtifactError(format!("invalid Azure artifact location {}", artifact_uri));
+        let rest = AZURE_SCHEMES
+            .iter()
+            .find_map(|scheme| artifact_uri.strip_prefix(scheme))
+            .ok_or_else(invalid)?;
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (container, host) = authority.split_once('@').ok_or_else(invalid)?;
+        if container.is_empty() || !host.contains('.') {
+            return Err(invalid());
+        }
+        let host = host.replacen(".dfs.", ".blob.", 1);
+        let container_url =
+            Url::parse(&format!("https://{}/{}", host, container)).map_err(|_| invalid())?;
+
+        Ok(AzureBlobRepository {
+            container_url,
+            root: path.trim_matches('/').to_string(),
+            credential,
+            client: shared_http_client(),
+        })
+    }
+
+    fn blob_url(&self, artifact_path: &str) -> MLFlowResult<Url> {
+        let blob = join_artifact_path(&self.root, artifact_path)?;
+        let mut url = self.container_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| ArtifactError(format!("invalid container URL {}", self.container_url)))?
+            .extend(blob.split('/'));
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, mut url: Url) -> MLFlowResult<RequestBuilder> {
+        let request = match &self.credential {
+            AzureCredential::SasToken(token) => {
+                let query = match url.query() {
+                    Some(query) => format!("{}&{}", query, token),
+                    None => token.clone(),
+                };
+                url.set_query(Some(&query));
+                self.client.request(method, url)
+            }
+            AzureCredential::ManagedIdentity { client_id } => self
+                .client
+                .request(method, url)
+                .bearer_auth(self.managed_identity_token(client_id.as_deref())?),
+        };
+        Ok(request.header("x-ms-version", STORAGE_VERSION))
+    }
+
+    fn managed_identity_token(&self, client_id: Option<&str>) -> MLFlowResult<String> {
+        let mut tokens = TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = tokens.iter().find(|(id, token)| {
+            id.as_deref() == client_id && token.expires_on > now_seconds() + TOKEN_EXPIRY_MARGIN
+        });
+        if let Some((_, token)) = cached {
+            return Ok(token.access_token.clone());
+        }
+
+        let mut query = vec![("resource", STORAGE_RESOURCE)];
+        if let Some(client_id) = client_id {
+            query.push(("client_id", client_id));
+        }
+        // App Service and Functions expose their own endpoint instead of the VM metadata service
+        let request = match (
+            std::env::var("IDENTITY_ENDPOINT"),
+            std::env::var("IDENTITY_HEADER"),
+        ) {
+            (Ok(endpoint), Ok(header)) => self
+                .client
+                .get(endpoint)
+                .query(&[("api-version", "2019-08-01")])
+                .header("X-IDENTITY-HEADER", header),
+            _ => self
+                .client
+                .get(IMDS_ENDPOINT)
+                .query(&[("api-version", "2018-02-01")])
+                .header("Metadata", "true"),
+        };
+        let token = check(
+            request.query(&query).send_logged(false),
+            "Could not get a managed identity token",
+        )?
+        .json::<AccessToken>()
+        .map_err(|e| ArtifactError(e.to_string()))?;
+
+        tokens.retain(|(id, _)| id.as_deref() != client_id);
+        tokens.push((client_id.map(|id| id.to_string()), token.clone()));
+        Ok(token.access_token)
+    }
+}
+
+impl ArtifactRepository for AzureBlobRepository {
+    fn log_artifact(&self, local_file: &Path, artifact_path: &str) -> MLFlowResult<()> {
+        self.log_artifact_with_progress(local_file, artifact_path, Arc::new(|_| {}))
+    }
+
+    fn list_artifacts(&self, path: Option<&str>) -> MLFlowResult<Vec<FileInfo>> {
+        let directory = join_artifact_path(&self.root, path.unwrap_or_default())?;
+        let prefix = match directory.is_empty() {
+            true => String::new(),
+            false => format!("{}/", directory),
+        };
+
+        let mut files = vec![];
+        let mut marker: Option<String> = None;
+        loop {
+            let mut url = self.container_url.clone();
+            url.query_pairs_mut()
+                .append_pair("restype", "container")
+                .append_pair("comp", "list")
+                .append_pair("delimiter", "/")
+                .append_pair("prefix", &prefix);
+            if let Some(marker) = &marker {
+                url.query_pairs_mut().append_pair("marker", marker);
+            }
+            let body = check(
+                self.request(Method::GET, url)?.send_logged(false),
+                "Could not list artifacts",
+            )?
+            .text()
+            .map_err(|e| ArtifactError(e.to_string()))?;
+
+            let page = parse_blob_list(&body)?;
+            files.extend(page.files.into_iter().filter_map(|mut file| {
+                let relative = match self.root.is_empty() {
+                    true => file.path.as_str(),
+                    false => file.path.strip_prefix(&self.root)?.strip_prefix('/')?,
+                };
+                file.path = relative.trim_end_matches('/').to_string();
+                Some(file)
+            }));
+            match page.next_marker {
+                Some(next) if !next.is_empty() => marker = Some(next),
+                _ => break,
+            }
+        }
+        Ok(files)
+    }
+
+    fn download_artifact(&self, artifact_path: &str, local_file: &Path) -> MLFlowResult<()> {
+        self.download_artifact_with_progress(artifact_path, local_file, Arc::new(|_| {}))
+    }
+
+    /// Files of [`BLOCK_UPLOAD_THRESHOLD`] bytes or more are uploaded as blocks, each retried on
+    /// failure, and committed once all have been staged.
+    fn log_artifact_with_progress(
+        &self,
+        local_file: &Path,
+        artifact_path: &str,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        let url = self.blob_url(artifact_path)?;
+        let file = File::open(local_file).map_err(|e| ArtifactError(e.to_string()))?;
+        let size = file
+            .metadata()
+            .map_err(|e| ArtifactError(e.to_string()))?
+            .len();
+
+        if size < BLOCK_UPLOAD_THRESHOLD {
+            let request = self
+                .request(Method::PUT, url)?
+                .header("x-ms-blob-type", "BlockBlob")
+                .body(Body::sized(ProgressReader::new(file, progress), size));
+            return check(request.send_logged(false), "Could not upload artifact").map(|_| ());
+        }
+
+        let mut block_list = String::from(r#"<?xml version="1.0" encoding="utf-8"?><BlockList>"#);
+        for (index, offset) in (0..size).step_by(BLOCK_SIZE as usize).enumerate() {
+            let len = BLOCK_SIZE.min(size - offset);
+            let block_id = BASE64_STANDARD.encode(format!("block-{:08}", index));
+            let mut block_url = url.clone();
+            block_url
+                .query_pairs_mut()
+                .append_pair("comp", "block")
+                .append_pair("blockid", &block_id);
+
+            let mut attempt = 0;
+            loop {
+                attempt += 1;
+                let mut file = File::open(local_file).map_err(|e| ArtifactError(e.to_string()))?;
+                file.seek(SeekFrom::Start(offset))
+                    .map_err(|e| ArtifactError(e.to_string()))?;
+                let request = self
+                    .request(Method::PUT, block_url.clone())?
+                    .body(Body::sized(file.take(len), len));
+                match check(
+                    request.send_retry(attempt - 1),
+                    "Could not upload artifact block",
+                ) {
+                    Ok(_) => break,
+                    Err(_) if attempt < BLOCK_UPLOAD_ATTEMPTS => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            progress(len);
+            block_list.push_str(&format!("<Latest>{}</Latest>", block_id));
+        }
+        block_list.push_str("</BlockList>");
+
+        let mut commit_url = url;
+        commit_url
+            .query_pairs_mut()
+            .append_pair("comp", "blocklist");
+        let request = self.request(Method::PUT, commit_url)?.body(block_list);
+        check(
+            request.send_logged(false),
+            "Could not commit artifact blocks",
+        )
+        .map(|_| ())
+    }
+
+    fn download_artifact_with_progress(
+        &self,
+        artifact_path: &str,
+        local_file: &Path,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        let response = check(
+            self.request(Method::GET, self.blob_url(artifact_path)?)?
+                .send_logged(false),
+            "Could not download artifact",
+        )?;
+        let mut file = File::create(local_file).map_err(|e| ArtifactError(e.to_string()))?;
+        std::io::copy(&mut ProgressReader::new(response, progress), &mut file)
+            .map_err(|e| ArtifactError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn check(result: Result<Response, reqwest::Error>, context: &str) -> MLFlowResult<Response> {
+    let response = result.map_err(|e| ArtifactError(format!("{}: {}", context, e)))?;
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().unwrap_or_default();
+    Err(ArtifactError(format!("{}: {} {}", context, status, body)))
+}
+
+#[derive(Debug, Default)]
+struct BlobListPage {
+    /// Full blob names, and prefixes ending in `/` for directories.
+    files: Vec<FileInfo>,
+    next_marker: Option<String>,
+}
+
+/// Parses a `List Blobs` response, an `EnumerationResults` document.
+fn parse_blob_list(xml: &str) -> MLFlowResult<BlobListPage> {
+    let mut reader = Reader::from_str(xml);
+    let mut page = BlobListPage::default();
+    let mut elements: Vec<String> = vec![];
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match name.as_str() {
+                    "Blob" | "BlobPrefix" => page.files.push(FileInfo {
+                        path: String::new(),
+                        is_dir: name == "BlobPrefix",
+                        file_size: None,
+                        extra: serde_json::Map::new(),
+                    }),
+                    _ => {}
+                }
+                elements.push(name);
+            }
+            Ok(Event::End(_)) => {
+                elements.pop();
+            }
+            Ok(Event::Text(text)) => {
+                let text = text.unescape().map_err(|e| ArtifactError(e.to_string()))?;
+                let path: Vec<&str> = elements.iter().map(String::as_str).collect();
+                match path.as_slice() {
+                    [.., "Blob" | "BlobPrefix", "Name"] => {
+                        if let Some(file) = page.files.last_mut() {
+                            file.path = text.to_string();
+                        }
+                    }
+                    [.., "Blob", "Properties", "Content-Length"] => {
+                        if let Some(file) = page.files.last_mut() {
+                            file.file_size = text.parse().ok();
+                        }
+                    }
+                    [.., "NextMarker"] => page.next_marker = Some(text.to_string()),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(ArtifactError(format!("invalid blob listing: {}", e))),
+        }
+    }
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sas_repository(uri: &str) -> AzureBlobRepository {
+        AzureBlobRepository::with_credential(uri, AzureCredential::SasToken("sv=1&sig=x".into()))
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_locations() {
+        let repo = sas_repository("wasbs://models@acct.blob.core.windows.net/1/abc/artifacts");
+        assert_eq!(
+            repo.container_url.as_str(),
+            "https://acct.blob.core.windows.net/models"
+        );
+        assert_eq!(repo.root, "1/abc/artifacts");
+        assert_eq!(
+            repo.blob_url("model/weights v2.bin").unwrap().as_str(),
+            "https://acct.blob.core.windows.net/models/1/abc/artifacts/model/weights%20v2.bin"
+        );
+
+        let repo = sas_repository("abfss://fs@acct.dfs.core.windows.net/mlruns/1/abc/artifacts");
+        assert_eq!(
+            repo.container_url.as_str(),
+            "https://acct.blob.core.windows.net/fs"
+        );
+
+        for uri in [
+            "wasbs://acct.blob.core.windows.net/1",
+            "wasbs://@acct.blob.core.windows.net/1",
+            "s3://bucket/1",
+        ] {
+            AzureBlobRepository::with_credential(uri, AzureCredential::SasToken(String::new()))
+                .expect_err(uri);
+        }
+    }
+
+    #[test]
+    fn sas_token_is_appended_to_the_query() {
+        let repo = sas_repository("wasbs://models@acct.blob.core.windows.net/1");
+        let mut url = repo.container_url.clone();
+        url.set_query(Some("comp=list"));
+        let request = repo.request(Method::GET, url).unwrap().build().unwrap();
+        assert_eq!(request.url().query(), Some("comp=list&sv=1&sig=x"));
+        assert_eq!(request.headers()["x-ms-version"], STORAGE_VERSION);
+    }
+
+    #[test]
+    fn credentials_from_env() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert_eq!(
+            AzureCredential::from_env(env(&[("AZURE_STORAGE_SAS_TOKEN", "?sv=1")])),
+            AzureCredential::SasToken("sv=1".to_string())
+        );
+        assert_eq!(
+            AzureCredential::from_env(env(&[(
+                "AZURE_STORAGE_CONNECTION_STRING",
+                "BlobEndpoint=https://acct.blob.core.windows.net/;SharedAccessSignature=sv=2&sig=y"
+            )])),
+            AzureCredential::SasToken("sv=2&sig=y".to_string())
+        );
+        assert_eq!(
+            AzureCredential::from_env(env(&[("AZURE_CLIENT_ID", "abc")])),
+            AzureCredential::ManagedIdentity {
+                client_id: Some("abc".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn parses_blob_list() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+            <EnumerationResults ServiceEndpoint="https://acct.blob.core.windows.net/" ContainerName="models">
+              <Prefix>1/abc/artifacts/</Prefix>
+              <Delimiter>/</Delimiter>
+              <Blobs>
+                <Blob>
+                  <Name>1/abc/artifacts/MLmodel</Name>
+                  <Properties><Content-Length>312</Content-Length><BlobType>BlockBlob</BlobType></Properties>
+                </Blob>
+                <BlobPrefix><Name>1/abc/artifacts/model/</Name></BlobPrefix>
+              </Blobs>
+              <NextMarker>2!abc</NextMarker>
+            </EnumerationResults>"#;
+        let page = parse_blob_list(xml).unwrap();
+        assert_eq!(
+            page.files,
+            vec![
+                FileInfo {
+                    path: "1/abc/artifacts/MLmodel".to_string(),
+                    is_dir: false,
+                    file_size: Some(312),
+                    extra: serde_json::Map::new(),
+                },
+                FileInfo {
+                    path: "1/abc/artifacts/model/".to_string(),
+                    is_dir: true,
+                    file_size: None,
+                    extra: serde_json::Map::new(),
+                },
+            ]
+        );
+        assert_eq!(page.next_marker.as_deref(), Some("2!abc"));
+    }
+
+    #[test]
+    fn token_expiry_as_string() {
+        let token: AccessToken =
+            serde_json::from_str(r#"{"access_token": "t", "expires_on": "1700000000"}"#).unwrap();
+        assert_eq!(token.expires_on, 1_700_000_000);
+    }
+}
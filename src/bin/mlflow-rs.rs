@@ -0,0 +1,469 @@
+//! `mlflow-rs`, a command line client for the tracking operations CI jobs need, built with the
+//! `cli` feature.
+//!
+//! ```text
+//! mlflow-rs experiments create nlp --tag team=nlp
+//! mlflow-rs runs list --experiment-id 1 --filter "metrics.rmse < 0.5"
+//! mlflow-rs runs compare 2f0c... 9ab1...
+//! mlflow-rs artifacts download --run-id 2f0c... --artifact-path model --dst ./model
+//! mlflow-rs models promote classifier 3 --stage production --archive-existing
+//! ```
+//!
+//! The tracking server is resolved like in the library, from `MLFLOW_TRACKING_URI` and the
+//! config file profiles, unless `--tracking-uri` or `--profile` is given.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use mlflow_rs::{
+    Config, ExperimentBuilder, ExperimentIdentifier, ExperimentIdentifierType, ExperimentLoader,
+    ExperimentSearch, MLFlowResult, ModelVersionLoader, ModelVersionStage, Run, RunLoader,
+    RunSearch, RunViewType,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "mlflow-rs",
+    version,
+    about = "Manage MLflow experiments, runs and models"
+)]
+struct Cli {
+    /// Tracking URI, overriding MLFLOW_TRACKING_URI and the config file.
+    #[arg(long, global = true)]
+    tracking_uri: Option<String>,
+    /// Profile of the config file to use.
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create, list and delete experiments.
+    #[command(subcommand)]
+    Experiments(ExperimentsCommand),
+    /// List and compare runs.
+    #[command(subcommand)]
+    Runs(RunsCommand),
+    /// Download run artifacts.
+    #[command(subcommand)]
+    Artifacts(ArtifactsCommand),
+    /// Manage registered model versions.
+    #[command(subcommand)]
+    Models(ModelsCommand),
+}
+
+#[derive(Subcommand)]
+enum ExperimentsCommand {
+    /// Creates an experiment and prints its id.
+    Create {
+        name: String,
+        #[arg(long)]
+        artifact_location: Option<String>,
+        /// A tag as `key=value`, repeatable.
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+    },
+    /// Lists experiments.
+    List {
+        #[arg(long)]
+        filter: Option<String>,
+        #[arg(long, value_enum, default_value_t = ViewType::ActiveOnly)]
+        view: ViewType,
+    },
+    /// Marks an experiment deleted.
+    Delete { experiment_id: String },
+}
+
+#[derive(Subcommand)]
+enum RunsCommand {
+    /// Lists the runs of one or more experiments.
+    List {
+        #[arg(long = "experiment-id", required = true)]
+        experiment_ids: Vec<String>,
+        #[arg(long)]
+        filter: Option<String>,
+        /// An `ORDER BY` clause, e.g. `metrics.rmse ASC`, repeatable.
+        #[arg(long)]
+        order_by: Vec<String>,
+        #[arg(long, value_enum, default_value_t = ViewType::ActiveOnly)]
+        view: ViewType,
+        /// Stops after this many runs.
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Prints the params and latest metrics of runs side by side.
+    Compare {
+        #[arg(required = true, num_args = 2..)]
+        run_ids: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ArtifactsCommand {
+    /// Downloads a file or directory of a run's artifacts and prints its local path.
+    Download(DownloadArgs),
+}
+
+#[derive(Args)]
+struct DownloadArgs {
+    #[arg(long)]
+    run_id: String,
+    /// Path below the run's artifact root, everything when omitted.
+    #[arg(long, default_value = "")]
+    artifact_path: String,
+    #[arg(long, default_value = ".")]
+    dst: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// Moves a model version to a stage.
+    Promote {
+        name: String,
+        version: String,
+        #[arg(long, value_enum, default_value_t = Stage::Production)]
+        stage: Stage,
+        /// Archives the versions currently in the stage.
+        #[arg(long)]
+        archive_existing: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ViewType {
+    ActiveOnly,
+    DeletedOnly,
+    All,
+}
+
+impl From<ViewType> for RunViewType {
+    fn from(view: ViewType) -> Self {
+        match view {
+            ViewType::ActiveOnly => RunViewType::ActiveOnly,
+            ViewType::DeletedOnly => RunViewType::DeletedOnly,
+            ViewType::All => RunViewType::All,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Stage {
+    None,
+    Staging,
+    Production,
+    Archived,
+}
+
+impl From<Stage> for ModelVersionStage {
+    fn from(stage: Stage) -> Self {
+        match stage {
+            Stage::None => ModelVersionStage::None,
+            Stage::Staging => ModelVersionStage::Staging,
+            Stage::Production => ModelVersionStage::Production,
+            Stage::Archived => ModelVersionStage::Archived,
+        }
+    }
+}
+
+fn parse_tag(tag: &str) -> Result<(String, String), String> {
+    tag.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("{} is not a key=value pair", tag))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> MLFlowResult<()> {
+    if cli.tracking_uri.is_some() || cli.profile.is_some() {
+        let mut config = Config::default();
+        if let Some(profile) = &cli.profile {
+            config = config.with_profile(profile);
+        }
+        if let Some(tracking_uri) = &cli.tracking_uri {
+            config = config.with_tracking_uri(tracking_uri);
+        }
+        config.set_global()?;
+    }
+
+    match cli.command {
+        Command::Experiments(command) => experiments(command),
+        Command::Runs(command) => runs(command),
+        Command::Artifacts(ArtifactsCommand::Download(args)) => {
+            let run = RunLoader::default().load(&args.run_id)?;
+            let local = run.download_artifact(&args.artifact_path, &args.dst)?;
+            println!("{}", local.display());
+            Ok(())
+        }
+        Command::Models(ModelsCommand::Promote {
+            name,
+            version,
+            stage,
+            archive_existing,
+        }) => {
+            let mut model_version = ModelVersionLoader::default().load(&name, &version)?;
+            model_version.transition_stage(stage.into(), archive_existing)?;
+            println!(
+                "{} version {} is now in stage {}",
+                name,
+                version,
+                screaming(&model_version.current_stage)
+            );
+            Ok(())
+        }
+    }
+}
+
+fn experiments(command: ExperimentsCommand) -> MLFlowResult<()> {
+    match command {
+        ExperimentsCommand::Create {
+            name,
+            artifact_location,
+            tags,
+        } => {
+            let mut builder = ExperimentBuilder::new(name)?.with_tags(
+                tags.iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str()))
+                    .collect(),
+            );
+            if let Some(artifact_location) = artifact_location {
+                builder = builder.with_artifact_location(artifact_location);
+            }
+            let experiment = builder.build()?;
+            println!("{}", experiment.experiment_id().unwrap_or_default());
+        }
+        ExperimentsCommand::List { filter, view } => {
+            let mut search = ExperimentSearch::default().with_view_type(view.into());
+            if let Some(filter) = filter {
+                search = search.with_filter(filter);
+            }
+            let mut rows = vec![];
+            for experiment in search.search() {
+                let experiment = experiment?;
+                rows.push(vec![
+                    experiment.experiment_id().unwrap_or_default(),
+                    experiment.name.clone(),
+                    experiment
+                        .lifecycle_stage
+                        .as_ref()
+                        .map(screaming)
+                        .unwrap_or_default(),
+                    experiment.artifact_location.clone().unwrap_or_default(),
+                ]);
+            }
+            print_table(&["ID", "NAME", "STAGE", "ARTIFACT LOCATION"], rows);
+        }
+        ExperimentsCommand::Delete { experiment_id } => {
+            ExperimentLoader::default()
+                .load(ExperimentIdentifierType::ById(experiment_id))?
+                .delete()?;
+        }
+    }
+    Ok(())
+}
+
+fn runs(command: RunsCommand) -> MLFlowResult<()> {
+    match command {
+        RunsCommand::List {
+            experiment_ids,
+            filter,
+            order_by,
+            view,
+            limit,
+        } => {
+            let mut search = RunSearch::new(experiment_ids)?
+                .with_run_view_type(view.into())
+                .with_order_by(order_by);
+            if let Some(filter) = filter {
+                search = search.with_filter(filter);
+            }
+            let mut rows = vec![];
+            for run in search.search().take(limit.unwrap_or(usize::MAX)) {
+                let info = run?.info().clone();
+                rows.push(vec![
+                    info.run_id,
+                    info.run_name.unwrap_or_default(),
+                    info.experiment_id,
+                    screaming(&info.status),
+                    info.start_time.map(|t| t.to_string()).unwrap_or_default(),
+                ]);
+            }
+            print_table(
+                &["RUN ID", "NAME", "EXPERIMENT", "STATUS", "START TIME"],
+                rows,
+            );
+        }
+        RunsCommand::Compare { run_ids } => {
+            let runs = run_ids
+                .iter()
+                .map(|run_id| RunLoader::default().load(run_id))
+                .collect::<MLFlowResult<Vec<_>>>()?;
+            let (headers, rows) = comparison(&runs);
+            let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+            print_table(&headers, rows);
+        }
+    }
+    Ok(())
+}
+
+/// One row per param and metric of any of the runs, one column per run.
+fn comparison(runs: &[Run]) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut headers = vec!["KEY".to_string()];
+    headers.extend(runs.iter().map(|run| {
+        run.info()
+            .run_name
+            .clone()
+            .unwrap_or_else(|| run.info().run_id.clone())
+    }));
+
+    let params: BTreeSet<&str> = runs
+        .iter()
+        .flat_map(|run| run.data().params.iter().map(|p| p.key.as_str()))
+        .collect();
+    let metrics: BTreeSet<&str> = runs
+        .iter()
+        .flat_map(|run| run.data().metrics.iter().map(|m| m.key.as_str()))
+        .collect();
+
+    let mut rows = vec![];
+    for key in params {
+        let mut row = vec![format!("params.{}", key)];
+        row.extend(runs.iter().map(|run| {
+            run.data()
+                .params
+                .iter()
+                .find(|p| p.key == key)
+                .map(|p| p.value.clone())
+                .unwrap_or_else(|| "-".to_string())
+        }));
+        rows.push(row);
+    }
+    for key in metrics {
+        let mut row = vec![format!("metrics.{}", key)];
+        row.extend(runs.iter().map(|run| {
+            run.data()
+                .metric(key)
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        }));
+        rows.push(row);
+    }
+    (headers, rows)
+}
+
+/// The name an enum has in the REST API, e.g. `FINISHED`.
+fn screaming(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in &rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_key_value_pairs() {
+        assert_eq!(
+            parse_tag("team=nlp=core").unwrap(),
+            ("team".to_string(), "nlp=core".to_string())
+        );
+        parse_tag("team").expect_err("no value");
+    }
+
+    #[test]
+    fn commands_parse() {
+        Cli::try_parse_from(["mlflow-rs", "runs", "compare", "abc"])
+            .err()
+            .expect("compare needs two runs");
+        let cli = Cli::try_parse_from([
+            "mlflow-rs",
+            "models",
+            "promote",
+            "classifier",
+            "3",
+            "--stage",
+            "staging",
+            "--tracking-uri",
+            "http://mlflow:5000",
+        ])
+        .unwrap();
+        assert_eq!(cli.tracking_uri.as_deref(), Some("http://mlflow:5000"));
+        assert!(matches!(
+            cli.command,
+            Command::Models(ModelsCommand::Promote {
+                stage: Stage::Staging,
+                archive_existing: false,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn runs_are_compared_by_key() {
+        use mlflow_rs::{MockMLFlowClient, RunBuilder};
+
+        let mock = MockMLFlowClient::default();
+        let run = |name: &str, lr: &str, rmse: Option<f64>| {
+            let run = RunBuilder::new("0")
+                .unwrap()
+                .with_mock_client(mock.clone())
+                .with_run_name(name)
+                .build()
+                .unwrap();
+            run.log_param("lr", lr).unwrap();
+            if let Some(rmse) = rmse {
+                run.log_metric("rmse", rmse, None, None).unwrap();
+            }
+            RunLoader::default()
+                .with_mock_client(mock.clone())
+                .load(run.info().run_id.clone())
+                .unwrap()
+        };
+
+        let (headers, rows) = comparison(&[run("a", "0.1", Some(0.5)), run("b", "0.2", None)]);
+        assert_eq!(headers, vec!["KEY", "a", "b"]);
+        assert_eq!(
+            rows,
+            vec![
+                vec!["params.lr", "0.1", "0.2"],
+                vec!["metrics.rmse", "0.5", "-"]
+            ]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! Background batching of metric, param and tag writes.
+
+use crate::client::{SharedClient, MAX_ENTITIES_PER_BATCH};
+use crate::entities::{Metric, Param, RunTag};
+use crate::run::Run;
+use crate::termination::{deregister_logger, register_logger};
+use crate::validation::{validate_metric_key, validate_param, validate_run_tag};
+use crate::MLFlowError::{ParamValueChanged, UnknownError};
+use crate::{MLFlowError, MLFlowResult};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a flush on termination waits for the server before giving up on the buffer.
+const TERMINATION_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+enum Message {
+    Metric(Metric),
+    Param(Param),
+    Tag(RunTag),
+    Flush(Sender<MLFlowResult<()>>),
+}
+
+impl std::fmt::Debug for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Message::Metric(metric) => write!(f, "Metric({:?})", metric),
+            Message::Param(param) => write!(f, "Param({:?})", param),
+            Message::Tag(tag) => write!(f, "Tag({:?})", tag),
+            Message::Flush(_) => write!(f, "Flush"),
+        }
+    }
+}
+
+/// Configures a [`BufferedLogger`].
+#[derive(Clone, Debug)]
+pub struct BufferedLoggerBuilder {
+    run_id: String,
+    client: SharedClient,
+    flush_interval: Duration,
+    max_batch_size: usize,
+}
+
+impl BufferedLoggerBuilder {
+    pub fn new(run: &Run) -> BufferedLoggerBuilder {
+        BufferedLoggerBuilder {
+            run_id: run.info.run_id.clone(),
+            client: run.client.clone(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            max_batch_size: MAX_ENTITIES_PER_BATCH,
+        }
+    }
+
+    /// How long writes may sit in the buffer before being sent. Defaults to 2 seconds.
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Number of buffered metrics, params and tags that triggers a flush before the interval
+    /// elapses. Defaults to 1000, the most the server accepts in one `runs/log-batch` request.
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// Starts the background thread that sends the buffered writes.
+    pub fn build(self) -> MLFlowResult<BufferedLogger> {
+        let (sender, receiver) = channel::<Message>();
+        let mut buffer = Buffer {
+            run_id: self.run_id,
+            client: self.client,
+            metrics: vec![],
+            params: vec![],
+            tags: vec![],
+            error: None,
+        };
+        let flush_interval = self.flush_interval;
+        let max_batch_size = self.max_batch_size;
+
+        let handle = std::thread::Builder::new()
+            .name("mlflow-buffered-logger".to_string())
+            .spawn(move || {
+                let mut deadline = Instant::now() + flush_interval;
+                loop {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    match receiver.recv_timeout(timeout) {
+                        Ok(Message::Metric(metric)) => buffer.metrics.push(metric),
+                        Ok(Message::Param(param)) => buffer.push_param(param),
+                        Ok(Message::Tag(tag)) => buffer.push_tag(tag),
+                        Ok(Message::Flush(reply)) => {
+                            buffer.flush();
+                            deadline = Instant::now() + flush_interval;
+                            let _ = reply.send(buffer.take_error());
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Timeout) => {
+                            buffer.flush();
+                            deadline = Instant::now() + flush_interval;
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => {
+                            buffer.flush();
+                            return buffer.take_error();
+                        }
+                    }
+                    if buffer.len() >= max_batch_size {
+                        buffer.flush();
+                        deadline = Instant::now() + flush_interval;
+                    }
+                }
+            })
+            .map_err(|e| UnknownError(format!("could not start buffered logger: {}", e)))?;
+
+        let flush_sender = sender.clone();
+        let registration = register_logger(move || {
+            let (reply, result) = channel();
+            if flush_sender.send(Message::Flush(reply)).is_ok() {
+                let _ = result.recv_timeout(TERMINATION_FLUSH_TIMEOUT);
+            }
+        });
+
+        Ok(BufferedLogger {
+            sender: Some(sender),
+            handle: Some(handle),
+            registration,
+        })
+    }
+}
+
+/// Writes accumulated on the background thread until the next `runs/log-batch` request.
+struct Buffer {
+    run_id: String,
+    client: SharedClient,
+    metrics: Vec<Metric>,
+    params: Vec<Param>,
+    tags: Vec<RunTag>,
+    error: Option<MLFlowError>,
+}
+
+impl Buffer {
+    fn len(&self) -> usize {
+        self.metrics.len() + self.params.len() + self.tags.len()
+    }
+
+    /// The server rejects a batch containing the same param key twice, so repeats are dropped
+    /// here and changed values reported the way the server would.
+    fn push_param(&mut self, param: Param) {
+        match self.params.iter().find(|p| p.key == param.key) {
+            Some(existing) if existing.value != param.value => {
+                self.record_error(ParamValueChanged(format!(
+                    "param {} was already logged with value {}, not {}",
+                    param.key, existing.value, param.value
+                )));
+            }
+            Some(_) => {}
+            None => self.params.push(param),
+        }
+    }
+
+    fn push_tag(&mut self, tag: RunTag) {
+        self.tags.retain(|t| t.key != tag.key);
+        self.tags.push(tag);
+    }
+
+    fn flush(&mut self) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let metrics = std::mem::take(&mut self.metrics);
+        let params = std::mem::take(&mut self.params);
+        let tags = std::mem::take(&mut self.tags);
+        if let Err(e) = self.client.log_batch(&self.run_id, metrics, params, tags) {
+            self.record_error(e);
+        }
+    }
+
+    fn record_error(&mut self, error: MLFlowError) {
+        log::warn!("buffered logging to run {} failed: {}", self.run_id, error);
+        self.error.get_or_insert(error);
+    }
+
+    fn take_error(&mut self) -> MLFlowResult<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Logs to a run without waiting on the tracking server, for training loops where a request per
+/// step would dominate the step time.
+///
+/// Writes are queued to a background thread which sends them with `runs/log-batch` whenever the
+/// flush interval elapses or the batch size is reached. Failures are logged as they happen and
+/// the first one is returned by the next [`flush`](BufferedLogger::flush) or
+/// [`close`](BufferedLogger::close). Dropping the logger flushes whatever is still buffered.
+///
+/// ```no_run
+/// use mlflow_rs::{BufferedLoggerBuilder, RunBuilder};
+///
+/// let run = RunBuilder::new("1").unwrap().build().unwrap();
+/// let logger = BufferedLoggerBuilder::new(&run).build().unwrap();
+/// for step in 0..10_000 {
+///     logger.log_metric("loss", 1.0 / (step + 1) as f64, Some(step), None).unwrap();
+/// }
+/// logger.close().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct BufferedLogger {
+    sender: Option<Sender<Message>>,
+    handle: Option<JoinHandle<MLFlowResult<()>>>,
+    registration: u64,
+}
+
+impl BufferedLogger {
+    fn send(&self, message: Message) -> MLFlowResult<()> {
+        self.sender
+            .as_ref()
+            .and_then(|s| s.send(message).ok())
+            .ok_or_else(|| UnknownError("buffered logger has stopped".to_string()))
+    }
+
+    pub fn log_metric(
+        &self,
+        key: impl AsRef<str>,
+        value: f64,
+        step: Option<i64>,
+        timestamp: Option<i64>,
+    ) -> MLFlowResult<()> {
+        validate_metric_key(key.as_ref())?;
+
+        self.send(Message::Metric(Metric::new(key, value, step, timestamp)))
+    }
+
+    pub fn log_param(&self, key: impl AsRef<str>, value: impl ToString) -> MLFlowResult<()> {
+        let param = Param {
+            key: key.as_ref().to_string(),
+            value: value.to_string(),
+        };
+        validate_param(&param)?;
+
+        self.send(Message::Param(param))
+    }
+
+    pub fn set_tag(&self, key: impl AsRef<str>, value: impl ToString) -> MLFlowResult<()> {
+        let tag = RunTag {
+            key: key.as_ref().to_string(),
+            value: value.to_string(),
+        };
+        validate_run_tag(&tag)?;
+
+        self.send(Message::Tag(tag))
+    }
+
+    /// Sends everything buffered so far and waits for the server to accept it.
+    pub fn flush(&self) -> MLFlowResult<()> {
+        let (reply, result) = channel();
+        self.send(Message::Flush(reply))?;
+        result
+            .recv()
+            .map_err(|_| UnknownError("buffered logger has stopped".to_string()))?
+    }
+
+    /// Flushes the remaining writes and stops the background thread.
+    pub fn close(mut self) -> MLFlowResult<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> MLFlowResult<()> {
+        // the registered flush holds a sender too, which would keep the thread alive
+        deregister_logger(self.registration);
+        self.sender.take();
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .map_err(|_| UnknownError("buffered logger thread panicked".to_string()))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for BufferedLogger {
+    fn drop(&mut self) {
+        // failures were already logged by the background thread
+        let _ = self.shutdown();
+    }
+}
+
+impl Run {
+    /// A [`BufferedLogger`] for this run with the default flush interval and batch size.
+    pub fn buffered_logger(&self) -> MLFlowResult<BufferedLogger> {
+        BufferedLoggerBuilder::new(self).build()
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder, RunIdentifier};
+
+    fn run(mock: &MockMLFlowClient) -> Run {
+        RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn flushes_on_close() {
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+        let logger = BufferedLoggerBuilder::new(&run)
+            .with_flush_interval(Duration::from_secs(60))
+            .build()
+            .unwrap();
+
+        for step in 0..100 {
+            logger
+                .log_metric("loss", step as f64, Some(step), None)
+                .unwrap();
+        }
+        logger.log_param("lr", 0.1).unwrap();
+        logger.log_param("lr", 0.1).unwrap();
+        logger.set_tag("phase", "warmup").unwrap();
+        logger.set_tag("phase", "train").unwrap();
+        assert!(mock.metric_history(&run.run_id(), "loss").is_empty());
+
+        logger.close().unwrap();
+        assert_eq!(mock.metric_history(&run.run_id(), "loss").len(), 100);
+        let data = mock.run_data(&run.run_id()).unwrap();
+        assert_eq!(data.params.len(), 1);
+        assert!(data
+            .tags
+            .iter()
+            .any(|t| t.key == "phase" && t.value == "train"));
+    }
+
+    #[test]
+    fn flushes_on_batch_size_and_drop() {
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+        {
+            let logger = BufferedLoggerBuilder::new(&run)
+                .with_flush_interval(Duration::from_secs(60))
+                .with_max_batch_size(10)
+                .build()
+                .unwrap();
+            for step in 0..10 {
+                logger.log_metric("loss", 0.5, Some(step), None).unwrap();
+            }
+            logger.log_metric("loss", 0.5, Some(10), None).unwrap();
+            logger.flush().unwrap();
+            assert_eq!(mock.metric_history(&run.run_id(), "loss").len(), 11);
+            logger.log_metric("loss", 0.5, Some(11), None).unwrap();
+        }
+        assert_eq!(mock.metric_history(&run.run_id(), "loss").len(), 12);
+    }
+
+    #[test]
+    fn flushes_on_interval() {
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+        let logger = BufferedLoggerBuilder::new(&run)
+            .with_flush_interval(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        logger.log_metric("loss", 0.5, None, None).unwrap();
+
+        let started = Instant::now();
+        while mock.metric_history(&run.run_id(), "loss").is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(5), "never flushed");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn reports_errors_on_flush() {
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+        run.log_param("lr", 0.1).unwrap();
+
+        let logger = run.buffered_logger().unwrap();
+        logger.log_param("lr", 0.2).unwrap();
+        let err = logger.flush().expect_err("param values are immutable");
+        assert!(matches!(err, ParamValueChanged(_)));
+        logger.flush().expect("errors are only reported once");
+
+        logger
+            .log_metric("", 1.0, None, None)
+            .expect_err("empty key");
+    }
+}
@@ -0,0 +1,263 @@
+//! Periodic training checkpoints stored as run artifacts under `checkpoints/step=N/`.
+
+use crate::run::Run;
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use std::io::Read;
+use uuid::Uuid;
+
+/// The run artifact directory checkpoints are stored in.
+pub const CHECKPOINTS_DIR: &str = "checkpoints";
+/// Run tag holding the artifact directory of the checkpoint with the best metric value.
+pub const BEST_CHECKPOINT_TAG: &str = "checkpoint.best";
+const DEFAULT_FILE_NAME: &str = "checkpoint";
+
+/// Whether lower or higher values of a metric are better.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    Minimize,
+    Maximize,
+}
+
+/// What [`CheckpointLogger::log`] logged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub step: i64,
+    /// The checkpoint file, e.g. `checkpoints/step=100/checkpoint`.
+    pub artifact_path: String,
+    /// Whether the checkpoint has the best value of the metric set with
+    /// [`CheckpointLogger::with_best_metric`] so far.
+    pub is_best: bool,
+}
+
+/// Logs checkpoints of a run, pruning old ones and tracking the best one.
+///
+/// ```no_run
+/// use mlflow_rs::{CheckpointLogger, Objective, RunBuilder};
+///
+/// let run = RunBuilder::new("1").unwrap().build().unwrap();
+/// let checkpoints = CheckpointLogger::new(&run)
+///     .with_file_name("model.safetensors")
+///     .with_keep_latest(3)
+///     .with_best_metric("val_loss", Objective::Minimize);
+/// for step in 0..10 {
+///     run.log_metric("val_loss", 1.0 / (step + 1) as f64, Some(step), None).unwrap();
+///     let weights = std::fs::File::open("./target/model.safetensors").unwrap();
+///     checkpoints.log(step, weights).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CheckpointLogger<'a> {
+    run: &'a Run,
+    file_name: String,
+    keep_latest: Option<usize>,
+    best_metric: Option<(String, Objective)>,
+}
+
+impl<'a> CheckpointLogger<'a> {
+    /// Keeps every checkpoint in a file named `checkpoint` unless configured otherwise.
+    pub fn new(run: &'a Run) -> CheckpointLogger<'a> {
+        CheckpointLogger {
+            run,
+            file_name: DEFAULT_FILE_NAME.to_string(),
+            keep_latest: None,
+            best_metric: None,
+        }
+    }
+
+    /// The name of the file each checkpoint is stored in within its step directory.
+    pub fn with_file_name(mut self, file_name: impl AsRef<str>) -> Self {
+        self.file_name = file_name.as_ref().to_string();
+        self
+    }
+
+    /// Deletes all but the latest `keep` checkpoints after logging one. The best checkpoint is
+    /// never deleted, nor is the latest one, so a `keep` of 0 keeps 1.
+    pub fn with_keep_latest(mut self, keep: usize) -> Self {
+        self.keep_latest = Some(keep.max(1));
+        self
+    }
+
+    /// Ranks checkpoints by the value `key` was logged with at their step, recording the best
+    /// one in the [`BEST_CHECKPOINT_TAG`] run tag.
+    pub fn with_best_metric(mut self, key: impl AsRef<str>, objective: Objective) -> Self {
+        self.best_metric = Some((key.as_ref().to_string(), objective));
+        self
+    }
+
+    /// Uploads the contents of `reader` as the checkpoint of `step`, replacing an earlier
+    /// checkpoint of the same step.
+    pub fn log(&self, step: i64, mut reader: impl Read) -> MLFlowResult<Checkpoint> {
+        if self.file_name.is_empty() || self.file_name.contains('/') {
+            return Err(ArtifactError(format!(
+                "invalid checkpoint file name {:?}",
+                self.file_name
+            )));
+        }
+
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", Uuid::new_v4()));
+        let file = dir.join(&self.file_name);
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::File::create(&file))
+            .and_then(|mut f| std::io::copy(&mut reader, &mut f))
+            .map_err(|e| ArtifactError(e.to_string()))
+            .and_then(|_| self.run.log_artifact(&file, Some(&step_dir(step))));
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+
+        let steps = logged_steps(self.run)?;
+        let best = self.best_step(&steps)?;
+        if let Some(best) = best {
+            self.run.set_tag(BEST_CHECKPOINT_TAG, step_dir(best))?;
+        }
+        if let Some(keep) = self.keep_latest {
+            let stale = steps.len().saturating_sub(keep);
+            for old in steps[..stale].iter().filter(|s| Some(**s) != best) {
+                self.run.delete_artifacts(&step_dir(*old))?;
+            }
+        }
+
+        Ok(Checkpoint {
+            step,
+            artifact_path: format!("{}/{}", step_dir(step), self.file_name),
+            is_best: best == Some(step),
+        })
+    }
+
+    /// The step among `steps` with the best value of the metric, if one is configured and was
+    /// logged at any of them.
+    fn best_step(&self, steps: &[i64]) -> MLFlowResult<Option<i64>> {
+        let Some((key, objective)) = &self.best_metric else {
+            return Ok(None);
+        };
+
+        let history = self.run.get_metric_history(key)?;
+        // the latest value logged for a step wins, as in the UI
+        let value_at = |step: i64| {
+            history
+                .iter()
+                .filter(|m| m.step == step)
+                .max_by_key(|m| m.timestamp)
+                .map(|m| m.value)
+        };
+        let scored = steps
+            .iter()
+            .filter_map(|s| value_at(*s).filter(|v| !v.is_nan()).map(|v| (*s, v)));
+        let best = match objective {
+            Objective::Minimize => scored.min_by(|a, b| a.1.total_cmp(&b.1)),
+            Objective::Maximize => scored.max_by(|a, b| a.1.total_cmp(&b.1)),
+        };
+        Ok(best.map(|(step, _)| step))
+    }
+}
+
+/// The steps of the checkpoints stored for `run`, in ascending order.
+fn logged_steps(run: &Run) -> MLFlowResult<Vec<i64>> {
+    let mut steps: Vec<i64> = run
+        .list_artifacts(Some(CHECKPOINTS_DIR))?
+        .iter()
+        .filter(|f| f.is_dir)
+        .filter_map(|f| {
+            f.path
+                .rsplit('/')
+                .next()?
+                .strip_prefix("step=")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    steps.sort_unstable();
+    Ok(steps)
+}
+
+fn step_dir(step: i64) -> String {
+    format!("{}/step={}", CHECKPOINTS_DIR, step)
+}
+
+impl Run {
+    /// Uploads the contents of `reader` as the checkpoint of `step`, in
+    /// `checkpoints/step=N/checkpoint`. Use [`CheckpointLogger`] to prune old checkpoints or
+    /// track the best one.
+    pub fn log_checkpoint(&self, step: i64, reader: impl Read) -> MLFlowResult<Checkpoint> {
+        CheckpointLogger::new(self).log(step, reader)
+    }
+
+    /// The checkpoint with the highest step stored for the run, to restore a
+    /// [resumed](Run::resume) training job from. `None` if no checkpoint was logged.
+    pub fn latest_checkpoint(&self) -> MLFlowResult<Option<Checkpoint>> {
+        let Some(step) = logged_steps(self)?.pop() else {
+            return Ok(None);
+        };
+        let dir = step_dir(step);
+        let is_best = self.data.tag(BEST_CHECKPOINT_TAG) == Some(dir.as_str());
+        Ok(self
+            .list_artifacts(Some(&dir))?
+            .into_iter()
+            .find(|f| !f.is_dir)
+            .map(|f| Checkpoint {
+                step,
+                artifact_path: f.path,
+                is_best,
+            }))
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder};
+
+    #[test]
+    fn keeps_latest_and_best_checkpoints() {
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let checkpoints = CheckpointLogger::new(&run)
+            .with_file_name("weights.bin")
+            .with_keep_latest(2)
+            .with_best_metric("loss", Objective::Minimize);
+
+        for (step, loss) in [(0, 0.9), (1, 0.2), (2, 0.5), (3, 0.4)] {
+            run.log_metric("loss", loss, Some(step), None).unwrap();
+            let checkpoint = checkpoints
+                .log(step, format!("step {}", step).as_bytes())
+                .unwrap();
+            assert_eq!(checkpoint.is_best, step <= 1);
+        }
+
+        let run = crate::RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(&run.info().run_id)
+            .unwrap();
+        assert_eq!(
+            run.data().tag(BEST_CHECKPOINT_TAG),
+            Some("checkpoints/step=1")
+        );
+        assert_eq!(logged_steps(&run).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            run.list_artifacts(Some("checkpoints/step=3")).unwrap()[0].path,
+            "checkpoints/step=3/weights.bin"
+        );
+
+        let checkpoint = run.log_checkpoint(4, &b"last"[..]).unwrap();
+        assert_eq!(checkpoint.artifact_path, "checkpoints/step=4/checkpoint");
+        assert!(!checkpoint.is_best);
+    }
+
+    #[test]
+    fn keeps_the_latest_checkpoint() {
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let checkpoints = CheckpointLogger::new(&run).with_keep_latest(0);
+        for step in 0..3 {
+            checkpoints.log(step, &b"weights"[..]).unwrap();
+        }
+        assert_eq!(logged_steps(&run).unwrap(), vec![2]);
+    }
+}
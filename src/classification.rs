@@ -0,0 +1,296 @@
+//! Confusion matrices and classification reports of a classifier's predictions, logged as JSON
+//! artifacts along with their aggregate metrics.
+
+use crate::entities::Metric;
+use crate::run::{current_time_millis, Run};
+use crate::MLFlowError::InvalidMetric;
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// The artifact file [`Run::log_confusion_matrix`] stores the matrix in.
+pub const CONFUSION_MATRIX_FILE_NAME: &str = "confusion_matrix.json";
+/// The artifact file [`Run::log_classification_report`] stores the report in.
+pub const CLASSIFICATION_REPORT_FILE_NAME: &str = "classification_report.json";
+
+/// Counts of each true class, by row, predicted as each class, by column.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfusionMatrix {
+    pub class_names: Vec<String>,
+    pub matrix: Vec<Vec<u64>>,
+}
+
+impl ConfusionMatrix {
+    /// Tallies `predictions` against the true `labels`, both indices into `class_names`.
+    pub fn new(
+        labels: &[usize],
+        predictions: &[usize],
+        class_names: &[impl AsRef<str>],
+    ) -> MLFlowResult<ConfusionMatrix> {
+        if labels.len() != predictions.len() {
+            return Err(InvalidMetric(format!(
+                "{} labels but {} predictions",
+                labels.len(),
+                predictions.len()
+            )));
+        }
+        let classes = class_names.len();
+        let mut matrix = vec![vec![0; classes]; classes];
+        for (&label, &prediction) in labels.iter().zip(predictions) {
+            if label >= classes || prediction >= classes {
+                return Err(InvalidMetric(format!(
+                    "class {} is out of range for {} class names",
+                    label.max(prediction),
+                    classes
+                )));
+            }
+            matrix[label][prediction] += 1;
+        }
+
+        Ok(ConfusionMatrix {
+            class_names: class_names.iter().map(|c| c.as_ref().to_string()).collect(),
+            matrix,
+        })
+    }
+
+    /// The share of predictions that were right, `0.0` without any.
+    pub fn accuracy(&self) -> f64 {
+        let correct: u64 = (0..self.matrix.len()).map(|i| self.matrix[i][i]).sum();
+        ratio(correct, self.matrix.iter().flatten().sum())
+    }
+
+    /// Precision, recall and F1 score of each class and their averages.
+    pub fn report(&self) -> ClassificationReport {
+        let classes: Vec<ClassMetrics> = self
+            .class_names
+            .iter()
+            .enumerate()
+            .map(|(i, class_name)| {
+                let true_positives = self.matrix[i][i];
+                let predicted = self.matrix.iter().map(|row| row[i]).sum();
+                let support = self.matrix[i].iter().sum();
+                let precision = ratio(true_positives, predicted);
+                let recall = ratio(true_positives, support);
+                let f1_score = match precision + recall > 0.0 {
+                    true => 2.0 * precision * recall / (precision + recall),
+                    false => 0.0,
+                };
+                ClassMetrics {
+                    class_name: class_name.clone(),
+                    precision,
+                    recall,
+                    f1_score,
+                    support,
+                }
+            })
+            .collect();
+
+        let support: u64 = classes.iter().map(|c| c.support).sum();
+        let average = |weight: &dyn Fn(&ClassMetrics) -> f64, total: f64| AverageMetrics {
+            precision: classes.iter().map(|c| c.precision * weight(c)).sum::<f64>() / total,
+            recall: classes.iter().map(|c| c.recall * weight(c)).sum::<f64>() / total,
+            f1_score: classes.iter().map(|c| c.f1_score * weight(c)).sum::<f64>() / total,
+            support,
+        };
+        let macro_avg = average(&|_| 1.0, classes.len().max(1) as f64);
+        let weighted_avg = average(&|c| c.support as f64, support.max(1) as f64);
+
+        ClassificationReport {
+            accuracy: self.accuracy(),
+            classes,
+            macro_avg,
+            weighted_avg,
+        }
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    match denominator {
+        0 => 0.0,
+        _ => numerator as f64 / denominator as f64,
+    }
+}
+
+/// How well a classifier did on one class. Scores without any predictions or examples of the
+/// class to divide by are `0.0`, like scikit-learn's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassMetrics {
+    pub class_name: String,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1_score: f64,
+    /// The number of examples of the class.
+    pub support: u64,
+}
+
+/// Scores averaged over the classes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AverageMetrics {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1_score: f64,
+    pub support: u64,
+}
+
+/// The per-class and averaged scores of scikit-learn's `classification_report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassificationReport {
+    pub accuracy: f64,
+    pub classes: Vec<ClassMetrics>,
+    /// The unweighted mean over classes.
+    pub macro_avg: AverageMetrics,
+    /// The mean over classes weighted by their support.
+    pub weighted_avg: AverageMetrics,
+}
+
+impl ClassificationReport {
+    /// The report in the layout of `classification_report(..., output_dict=True)`, keyed by
+    /// class name, `accuracy`, `macro avg` and `weighted avg`.
+    pub fn to_json(&self) -> Value {
+        let scores = |precision: f64, recall: f64, f1_score: f64, support: u64| {
+            json!({
+                "precision": precision,
+                "recall": recall,
+                "f1-score": f1_score,
+                "support": support,
+            })
+        };
+        let mut report = Map::new();
+        for c in &self.classes {
+            report.insert(
+                c.class_name.clone(),
+                scores(c.precision, c.recall, c.f1_score, c.support),
+            );
+        }
+        report.insert("accuracy".to_string(), json!(self.accuracy));
+        for (name, avg) in [
+            ("macro avg", &self.macro_avg),
+            ("weighted avg", &self.weighted_avg),
+        ] {
+            report.insert(
+                name.to_string(),
+                scores(avg.precision, avg.recall, avg.f1_score, avg.support),
+            );
+        }
+        Value::Object(report)
+    }
+
+    /// The metrics [`Run::log_classification_report`] logs: `accuracy_score`, the weighted
+    /// `precision_score`, `recall_score` and `f1_score` as `mlflow.evaluate` names them, and
+    /// `f1_score/<class name>` for each class.
+    pub fn metrics(&self) -> Vec<Metric> {
+        let timestamp = current_time_millis();
+        let metric = |key: String, value: f64| Metric::new(key, value, None, Some(timestamp));
+        let mut metrics = vec![
+            metric("accuracy_score".to_string(), self.accuracy),
+            metric("precision_score".to_string(), self.weighted_avg.precision),
+            metric("recall_score".to_string(), self.weighted_avg.recall),
+            metric("f1_score".to_string(), self.weighted_avg.f1_score),
+        ];
+        metrics.extend(
+            self.classes
+                .iter()
+                .map(|c| metric(format!("f1_score/{}", c.class_name), c.f1_score)),
+        );
+        metrics
+    }
+}
+
+impl Run {
+    /// Logs the confusion matrix of `predictions` against the true `labels`, both indices into
+    /// `class_names`, as the [`CONFUSION_MATRIX_FILE_NAME`] artifact, and their accuracy as the
+    /// `accuracy_score` metric.
+    ///
+    /// ```no_run
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// let labels = [0, 1, 2, 2];
+    /// let predictions = [0, 2, 2, 2];
+    /// let classes = ["cat", "dog", "bird"];
+    /// run.log_confusion_matrix(&labels, &predictions, &classes).unwrap();
+    /// run.log_classification_report(&labels, &predictions, &classes).unwrap();
+    /// ```
+    pub fn log_confusion_matrix(
+        &self,
+        labels: &[usize],
+        predictions: &[usize],
+        class_names: &[impl AsRef<str>],
+    ) -> MLFlowResult<ConfusionMatrix> {
+        let matrix = ConfusionMatrix::new(labels, predictions, class_names)?;
+        self.log_json(&matrix, CONFUSION_MATRIX_FILE_NAME)?;
+        self.log_metric("accuracy_score", matrix.accuracy(), None, None)?;
+        Ok(matrix)
+    }
+
+    /// Logs the [`ClassificationReport`] of `predictions` against the true `labels`, both
+    /// indices into `class_names`, as the [`CLASSIFICATION_REPORT_FILE_NAME`] artifact, along
+    /// with its [metrics](ClassificationReport::metrics).
+    pub fn log_classification_report(
+        &self,
+        labels: &[usize],
+        predictions: &[usize],
+        class_names: &[impl AsRef<str>],
+    ) -> MLFlowResult<ClassificationReport> {
+        let report = ConfusionMatrix::new(labels, predictions, class_names)?.report();
+        self.log_json(&report.to_json(), CLASSIFICATION_REPORT_FILE_NAME)?;
+        self.log_batch(report.metrics(), vec![], vec![])?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_like_scikit_learn() {
+        let labels = [0, 0, 1, 1, 1, 2];
+        let predictions = [0, 1, 1, 1, 0, 1];
+        let matrix = ConfusionMatrix::new(&labels, &predictions, &["a", "b", "c"]).unwrap();
+        assert_eq!(
+            matrix.matrix,
+            vec![vec![1, 1, 0], vec![1, 2, 0], vec![0, 1, 0]]
+        );
+        assert_eq!(matrix.accuracy(), 0.5);
+
+        let report = matrix.report();
+        let b = &report.classes[1];
+        assert_eq!((b.precision, b.recall, b.support), (0.5, 2.0 / 3.0, 3));
+        assert!((b.f1_score - 4.0 / 7.0).abs() < 1e-12);
+        assert_eq!(report.classes[2].f1_score, 0.0);
+        assert!((report.macro_avg.f1_score - (0.5 + 4.0 / 7.0) / 3.0).abs() < 1e-12);
+        assert!((report.weighted_avg.recall - 0.5).abs() < 1e-12);
+
+        let json = report.to_json();
+        assert_eq!(json["a"]["f1-score"], 0.5);
+        assert_eq!(json["weighted avg"]["support"], 6);
+        assert!(ConfusionMatrix::new(&[0, 3], &[0, 0], &["a", "b"]).is_err());
+        assert!(ConfusionMatrix::new(&[0], &[0, 0], &["a"]).is_err());
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn logs_artifacts_and_metrics() {
+        let run = crate::RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(crate::MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let classes = ["cat", "dog"];
+        run.log_confusion_matrix(&[0, 1, 1], &[0, 1, 0], &classes)
+            .unwrap();
+        run.log_classification_report(&[0, 1, 1], &[0, 1, 0], &classes)
+            .unwrap();
+
+        let files: Vec<_> = run
+            .list_artifacts(None)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert!(files.contains(&CONFUSION_MATRIX_FILE_NAME.to_string()));
+        assert!(files.contains(&CLASSIFICATION_REPORT_FILE_NAME.to_string()));
+        let f1 = run.get_metric_history("f1_score/dog").unwrap();
+        assert!((f1[0].value - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(run.get_metric_history("accuracy_score").unwrap().len(), 2);
+    }
+}
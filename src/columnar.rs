@@ -0,0 +1,250 @@
+//! Tabular data logged as Arrow IPC or, with the `parquet` feature, Parquet artifacts and read
+//! back, for predictions too large for a JSON [`Table`](crate::Table).
+
+use crate::run::Run;
+use crate::MLFlowError::{ArtifactError, InvalidTable};
+use crate::MLFlowResult;
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use std::fs::File;
+
+/// Data that can be written to a Parquet file: Arrow record batches, and with the `polars`
+/// feature a data frame.
+#[cfg(feature = "parquet")]
+pub trait ParquetData {
+    fn to_parquet(&self) -> MLFlowResult<Vec<u8>>;
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetData for [RecordBatch] {
+    /// Writes the batches, which must share a schema, compressed with Snappy.
+    fn to_parquet(&self) -> MLFlowResult<Vec<u8>> {
+        use parquet::arrow::ArrowWriter;
+        use parquet::basic::Compression;
+        use parquet::file::properties::WriterProperties;
+
+        let schema = self
+            .first()
+            .ok_or_else(|| InvalidTable("no record batches to write".to_string()))?
+            .schema();
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut parquet = vec![];
+        let mut writer = ArrowWriter::try_new(&mut parquet, schema, Some(properties))
+            .map_err(|e| InvalidTable(e.to_string()))?;
+        for batch in self {
+            writer
+                .write(batch)
+                .map_err(|e| InvalidTable(e.to_string()))?;
+        }
+        writer.close().map_err(|e| InvalidTable(e.to_string()))?;
+        Ok(parquet)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetData for Vec<RecordBatch> {
+    fn to_parquet(&self) -> MLFlowResult<Vec<u8>> {
+        self.as_slice().to_parquet()
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetData for RecordBatch {
+    fn to_parquet(&self) -> MLFlowResult<Vec<u8>> {
+        std::slice::from_ref(self).to_parquet()
+    }
+}
+
+#[cfg(all(feature = "parquet", feature = "polars"))]
+impl ParquetData for polars::prelude::DataFrame {
+    /// Writes the data frame compressed with Snappy, like record batches.
+    fn to_parquet(&self) -> MLFlowResult<Vec<u8>> {
+        use polars::prelude::{ParquetCompression, ParquetWriter};
+
+        let mut parquet = vec![];
+        ParquetWriter::new(&mut parquet)
+            .with_compression(ParquetCompression::Snappy)
+            .finish(&mut self.clone())
+            .map_err(|e| InvalidTable(e.to_string()))?;
+        Ok(parquet)
+    }
+}
+
+impl Run {
+    /// Uploads `batches`, which must share a schema, as the Arrow IPC file `artifact_file`, e.g.
+    /// `predictions.arrow`.
+    pub fn log_arrow(&self, batches: &[RecordBatch], artifact_file: &str) -> MLFlowResult<()> {
+        let schema = batches
+            .first()
+            .ok_or_else(|| InvalidTable("no record batches to write".to_string()))?
+            .schema();
+        let mut ipc = vec![];
+        let mut writer =
+            FileWriter::try_new(&mut ipc, &schema).map_err(|e| InvalidTable(e.to_string()))?;
+        for batch in batches {
+            writer
+                .write(batch)
+                .map_err(|e| InvalidTable(e.to_string()))?;
+        }
+        writer.finish().map_err(|e| InvalidTable(e.to_string()))?;
+        drop(writer);
+        self.log_bytes(&ipc, artifact_file)
+    }
+
+    /// Downloads the Arrow IPC file `artifact_file` and reads its record batches.
+    pub fn read_arrow_artifact(&self, artifact_file: &str) -> MLFlowResult<Vec<RecordBatch>> {
+        self.read_artifact(artifact_file, |file| {
+            FileReader::try_new(file, None)
+                .and_then(|reader| reader.collect())
+                .map_err(|e| InvalidTable(e.to_string()))
+        })
+    }
+
+    /// Uploads `data`, record batches or a data frame, as the Parquet file `artifact_file`, e.g.
+    /// `predictions.parquet`, readable with `pandas.read_parquet`.
+    ///
+    /// ```no_run
+    /// use arrow::array::{Float64Array, Int64Array};
+    /// use arrow::record_batch::RecordBatch;
+    /// use std::sync::Arc;
+    ///
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// let predictions = RecordBatch::try_from_iter([
+    ///     ("id", Arc::new(Int64Array::from(vec![1, 2])) as _),
+    ///     ("score", Arc::new(Float64Array::from(vec![0.2, 0.9])) as _),
+    /// ])
+    /// .unwrap();
+    /// run.log_parquet(&predictions, "predictions.parquet").unwrap();
+    /// let batches = run.read_parquet_artifact("predictions.parquet").unwrap();
+    /// assert_eq!(batches[0].num_rows(), 2);
+    /// ```
+    #[cfg(feature = "parquet")]
+    pub fn log_parquet(
+        &self,
+        data: &(impl ParquetData + ?Sized),
+        artifact_file: &str,
+    ) -> MLFlowResult<()> {
+        self.log_bytes(&data.to_parquet()?, artifact_file)
+    }
+
+    /// Downloads the Parquet file `artifact_file` and reads its record batches.
+    #[cfg(feature = "parquet")]
+    pub fn read_parquet_artifact(&self, artifact_file: &str) -> MLFlowResult<Vec<RecordBatch>> {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        self.read_artifact(artifact_file, |file| {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+                .and_then(|builder| builder.build())
+                .map_err(|e| InvalidTable(e.to_string()))?;
+            reader
+                .collect::<Result<_, _>>()
+                .map_err(|e| InvalidTable(e.to_string()))
+        })
+    }
+
+    /// Downloads the Parquet file `artifact_file` and reads it into a data frame.
+    #[cfg(all(feature = "parquet", feature = "polars"))]
+    pub fn read_parquet_dataframe(
+        &self,
+        artifact_file: &str,
+    ) -> MLFlowResult<polars::prelude::DataFrame> {
+        use polars::prelude::SerReader;
+
+        self.read_artifact(artifact_file, |file| {
+            polars::prelude::ParquetReader::new(file)
+                .finish()
+                .map_err(|e| InvalidTable(e.to_string()))
+        })
+    }
+
+    /// Downloads `artifact_file` into a temporary directory and has `read` read it.
+    fn read_artifact<T>(
+        &self,
+        artifact_file: &str,
+        read: impl FnOnce(File) -> MLFlowResult<T>,
+    ) -> MLFlowResult<T> {
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", uuid::Uuid::new_v4()));
+        let result = self
+            .download_artifact(artifact_file, &dir)
+            .and_then(|path| File::open(path).map_err(|e| ArtifactError(e.to_string())))
+            .and_then(read);
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder};
+    use arrow::array::{Float64Array, Int64Array, StringArray};
+    use std::sync::Arc;
+
+    fn run() -> Run {
+        RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap()
+    }
+
+    fn batch(ids: Vec<i64>) -> RecordBatch {
+        let scores: Vec<f64> = ids.iter().map(|&id| id as f64 / 10.0).collect();
+        let labels: Vec<String> = ids.iter().map(|id| format!("label-{}", id)).collect();
+        RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(ids)) as _),
+            ("score", Arc::new(Float64Array::from(scores)) as _),
+            ("label", Arc::new(StringArray::from(labels)) as _),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trips_arrow_ipc() {
+        let run = run();
+        let batches = vec![batch(vec![1, 2]), batch(vec![3])];
+        run.log_arrow(&batches, "eval/predictions.arrow").unwrap();
+
+        assert_eq!(
+            run.read_arrow_artifact("eval/predictions.arrow").unwrap(),
+            batches
+        );
+        assert!(run.log_arrow(&[], "empty.arrow").is_err());
+        assert!(run.read_arrow_artifact("missing.arrow").is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn round_trips_parquet() {
+        let run = run();
+        let batches = vec![batch(vec![1, 2]), batch(vec![3])];
+        run.log_parquet(&batches, "predictions.parquet").unwrap();
+
+        let read = run.read_parquet_artifact("predictions.parquet").unwrap();
+        let rows: usize = read.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(rows, 3);
+        assert_eq!(read[0].schema(), batches[0].schema());
+        assert_eq!(read[0].slice(0, 2), batches[0]);
+        assert!(run.log_parquet(&Vec::new(), "empty.parquet").is_err());
+    }
+
+    #[cfg(all(feature = "parquet", feature = "polars"))]
+    #[test]
+    fn round_trips_dataframes() {
+        let run = run();
+        let df = polars::df!("id" => [1i64, 2], "label" => ["a", "b"]).unwrap();
+        run.log_parquet(&df, "predictions.parquet").unwrap();
+
+        assert_eq!(
+            run.read_parquet_dataframe("predictions.parquet").unwrap(),
+            df
+        );
+        assert_eq!(
+            run.read_parquet_artifact("predictions.parquet").unwrap()[0].num_rows(),
+            2
+        );
+    }
+}
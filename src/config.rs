@@ -0,0 +1,558 @@
+//! Resolution of the tracking server the builders, loaders and searches talk to when no client
+//! is passed to them explicitly, optionally from named profiles of a config file.
+//!
+//! ```toml
+//! # ~/.config/mlflow-rs/config.toml
+//! tracking_uri = "http://localhost:5000"
+//!
+//! [profiles.staging]
+//! tracking_uri = "https://mlflow.staging.example.com"
+//! token = "..."
+//! timeout_secs = 60
+//! max_requests_per_second = 20
+//! burst = 5
+//!
+//! [profiles.production]
+//! tracking_uri = "https://mlflow.example.com"
+//! username = "ci"
+//! password = "..."
+//! multipart_upload_threshold = 104857600
+//! ca_bundle = "/etc/pki/corp-root.pem"
+//! client_cert = "/etc/pki/ci.pem"
+//! client_key = "/etc/pki/ci.key"
+//! proxy = "http://proxy.example.com:3128"
+//! user_agent = "nightly-training/1.0"
+//!
+//! [profiles.production.headers]
+//! X-Org-Id = "research"
+//!
+//! [profiles.research]
+//! tracking_uri = "https://mlflow.research.example.com"
+//! oauth_token_url = "https://login.example.com/realms/ml/protocol/openid-connect/token"
+//! oauth_client_id = "training-jobs"
+//! oauth_client_secret = "..."
+//! oauth_scopes = ["mlflow"]
+//! ```
+
+#[cfg(feature = "blocking")]
+use crate::client::{MLFLowRestClient, MLFLowRestClientBuilder};
+use crate::client::{MLFlowClient, SharedClient};
+use crate::file_store::{is_file_store_uri, FileStoreClient};
+#[cfg(feature = "blocking")]
+use crate::oauth::ClientCredentialsProvider;
+#[cfg(feature = "blocking")]
+use crate::rate_limit::RateLimiter;
+use crate::MLFlowError::ConfigError;
+use crate::MLFlowResult;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+#[cfg(feature = "blocking")]
+use std::time::Duration;
+
+/// Tracking URI used when nothing else is configured.
+pub const DEFAULT_TRACKING_URI: &str = "http://localhost:5000";
+/// Environment variable pointing at a config file other than the default one.
+pub const CONFIG_FILE_ENV: &str = "MLFLOW_RS_CONFIG_FILE";
+/// Environment variable naming the profile of the config file to use.
+pub const PROFILE_ENV: &str = "MLFLOW_RS_PROFILE";
+
+static GLOBAL: OnceLock<Config> = OnceLock::new();
+
+/// The contents of a config file: top level settings used without a profile, and the named
+/// profiles.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(flatten)]
+    defaults: Profile,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// The settings of a profile.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(not(feature = "blocking"), allow(dead_code))]
+struct Profile {
+    tracking_uri: Option<String>,
+    /// Sent as `Authorization: Bearer <token>`.
+    token: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    timeout_secs: Option<u64>,
+    connect_timeout_secs: Option<u64>,
+    multipart_upload_threshold: Option<u64>,
+    multipart_upload_chunk_size: Option<u64>,
+    /// PEM bundle of extra root certificates to trust.
+    ca_bundle: Option<PathBuf>,
+    /// PEM client certificate and PKCS#8 key files for mutual TLS.
+    client_cert: Option<PathBuf>,
+    client_key: Option<PathBuf>,
+    #[serde(default)]
+    insecure_skip_verify: bool,
+    /// Proxy every request is sent through, instead of the one of `HTTPS_PROXY`.
+    proxy: Option<String>,
+    /// Hosts reached without the proxy, in the format of `NO_PROXY`.
+    no_proxy: Option<String>,
+    /// Average rate requests are throttled to, see [`RateLimiter`].
+    max_requests_per_second: Option<f64>,
+    /// Requests allowed at once after a quiet period, 1 by default.
+    burst: Option<u32>,
+    /// Sent instead of `mlflow-rs/<version>`.
+    user_agent: Option<String>,
+    /// Sent with every request, for gateways routing or authorizing on them.
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// Token endpoint of an OIDC provider, whose client credentials grant issues the bearer
+    /// tokens. See [`ClientCredentialsProvider`](crate::ClientCredentialsProvider).
+    oauth_token_url: Option<String>,
+    oauth_client_id: Option<String>,
+    oauth_client_secret: Option<String>,
+    #[serde(default)]
+    oauth_scopes: Vec<String>,
+}
+
+/// Where to find the tracking server, and how to talk to it.
+///
+/// The tracking URI is resolved in order from
+///
+/// 1. [`Config::with_tracking_uri`],
+/// 2. the profile chosen with [`Config::from_profile`],
+/// 3. the `MLFLOW_TRACKING_URI` environment variable,
+/// 4. the config file, `$MLFLOW_RS_CONFIG_FILE` or else `~/.config/mlflow-rs/config.toml`
+///    (honouring `XDG_CONFIG_HOME`): the profile named by `MLFLOW_RS_PROFILE`, or else the
+///    top level settings,
+/// 5. `http://localhost:5000`.
+///
+/// The credentials, TLS and proxy settings, timeouts, rate limit, multipart upload sizes, user
+/// agent and headers of the profile or top level settings are applied to the client unless the
+/// tracking URI came from the environment.
+///
+/// Builders, loaders and searches without an explicit client use the config registered with
+/// [`Config::set_global`], or `Config::default()` when none was.
+///
+/// ```no_run
+/// use mlflow_rs::{Config, ExperimentBuilder};
+///
+/// Config::default()
+///     .with_tracking_uri("https://mlflow.example.com")
+///     .set_global()
+///     .unwrap();
+/// // talks to https://mlflow.example.com
+/// let experiment = ExperimentBuilder::new("my-ml-experiment").unwrap().build().unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    tracking_uri: Option<String>,
+    profile: Option<String>,
+    config_file: Option<PathBuf>,
+}
+
+impl Config {
+    /// Uses the settings of the config file's profile `profile`, e.g. `staging`.
+    ///
+    /// ```no_run
+    /// use mlflow_rs::{Config, ExperimentSearch};
+    ///
+    /// let staging = Config::from_profile("staging").client().unwrap();
+    /// let experiments = ExperimentSearch::default()
+    ///     .with_rest_client(staging)
+    ///     .search()
+    ///     .collect::<Result<Vec<_>, _>>();
+    /// ```
+    pub fn from_profile(profile: impl AsRef<str>) -> Config {
+        Config::default().with_profile(profile)
+    }
+
+    /// Uses the settings of the config file's profile `profile`, which must exist.
+    pub fn with_profile(mut self, profile: impl AsRef<str>) -> Self {
+        self.profile = Some(profile.as_ref().to_string());
+        self
+    }
+
+    /// Uses `tracking_uri` regardless of the environment and config file.
+    pub fn with_tracking_uri(mut self, tracking_uri: impl AsRef<str>) -> Self {
+        self.tracking_uri = Some(tracking_uri.as_ref().to_string());
+        self
+    }
+
+    /// Reads `config_file` instead of the default config file, failing if it does not exist.
+    pub fn with_config_file(mut self, config_file: impl AsRef<Path>) -> Self {
+        self.config_file = Some(config_file.as_ref().to_path_buf());
+        self
+    }
+
+    /// Registers the config used by every builder, loader and search without an explicit
+    /// client. It can only be registered once per process, before the first one is used.
+    pub fn set_global(self) -> MLFlowResult<()> {
+        GLOBAL
+            .set(self)
+            .map_err(|_| ConfigError("a global config is already registered".to_string()))
+    }
+
+    /// The config registered with [`Config::set_global`], else the default one.
+    pub fn global() -> Config {
+        GLOBAL.get().cloned().unwrap_or_default()
+    }
+
+    /// The tracking URI, resolved from the process environment.
+    pub fn tracking_uri(&self) -> MLFlowResult<String> {
+        Ok(tracking_uri(
+            &self.resolve_with(|name| std::env::var(name).ok())?,
+        ))
+    }
+
+    /// A client for the resolved tracking URI with the profile's settings applied. See
+    /// [`MLFLowRestClient::from_tracking_uri`] for the supported URIs.
+    #[cfg(feature = "blocking")]
+    pub fn client(&self) -> MLFlowResult<MLFLowRestClient> {
+        let profile = self.resolve_with(|name| std::env::var(name).ok())?;
+        client_builder(&profile)?.build()
+    }
+
+    /// The settings the tracking URI and client are created from.
+    fn resolve_with(&self, env: impl Fn(&str) -> Option<String>) -> MLFlowResult<Profile> {
+        if self.profile.is_none() {
+            // nothing from the config file applies to an explicit or environment URI
+            let tracking_uri = self
+                .tracking_uri
+                .clone()
+                .or_else(|| env("MLFLOW_TRACKING_URI").filter(|uri| !uri.is_empty()));
+            if tracking_uri.is_some() {
+                return Ok(Profile {
+                    tracking_uri,
+                    ..Profile::default()
+                });
+            }
+        }
+
+        let mut file = self.read_config_file(&env)?;
+        let profile_name = self
+            .profile
+            .clone()
+            .or_else(|| env(PROFILE_ENV).filter(|p| !p.is_empty()));
+        let mut profile = match profile_name {
+            Some(name) => file
+                .profiles
+                .remove(&name)
+                .ok_or_else(|| ConfigError(format!("profile {} is not configured", name)))?,
+            None => file.defaults,
+        };
+        if let Some(tracking_uri) = &self.tracking_uri {
+            profile.tracking_uri = Some(tracking_uri.clone());
+        }
+        Ok(profile)
+    }
+
+    /// The explicitly configured file, which must exist, else the default file if there is one.
+    fn read_config_file(&self, env: impl Fn(&str) -> Option<String>) -> MLFlowResult<ConfigFile> {
+        let (path, required) = match &self.config_file {
+            Some(path) => (path.clone(), true),
+            None => match env(CONFIG_FILE_ENV) {
+                Some(path) => (PathBuf::from(path), true),
+                None => match default_config_file(&env) {
+                    Some(path) => (path, false),
+                    None => return Ok(ConfigFile::default()),
+                },
+            },
+        };
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => {
+                return Ok(ConfigFile::default())
+            }
+            Err(e) => {
+                return Err(ConfigError(format!(
+                    "could not read {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        toml::from_str(&contents)
+            .map_err(|e| ConfigError(format!("invalid config file {}: {}", path.display(), e)))
+    }
+}
+
+fn default_config_file(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let config_dir = env("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("mlflow-rs").join("config.toml"))
+}
+
+fn tracking_uri(profile: &Profile) -> String {
+    profile
+        .tracking_uri
+        .clone()
+        .unwrap_or_else(|| DEFAULT_TRACKING_URI.to_string())
+}
+
+#[cfg(feature = "blocking")]
+fn client_builder(profile: &Profile) -> MLFlowResult<MLFLowRestClientBuilder> {
+    let mut builder = MLFLowRestClientBuilder::from_tracking_uri(tracking_uri(profile))?;
+    if let Some(token) = &profile.token {
+        builder = builder.with_bearer_token(token);
+    }
+    match (&profile.username, &profile.password) {
+        (Some(username), Some(password)) => builder = builder.with_basic_auth(username, password),
+        (None, None) => {}
+        _ => {
+            return Err(ConfigError(
+                "username and password must be configured together".to_string(),
+            ))
+        }
+    }
+    if let Some(secs) = profile.timeout_secs {
+        builder = builder.with_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = profile.connect_timeout_secs {
+        builder = builder.with_connect_timeout(Duration::from_secs(secs));
+    }
+    if let Some(bytes) = profile.multipart_upload_threshold {
+        builder = builder.with_multipart_upload_threshold(bytes);
+    }
+    if let Some(bytes) = profile.multipart_upload_chunk_size {
+        builder = builder.with_multipart_upload_chunk_size(bytes);
+    }
+    if let Some(ca_bundle) = &profile.ca_bundle {
+        builder = builder.with_ca_bundle(ca_bundle);
+    }
+    match (&profile.client_cert, &profile.client_key) {
+        (Some(cert), Some(key)) => {
+            let read = |path: &PathBuf| {
+                std::fs::read(path)
+                    .map_err(|e| ConfigError(format!("could not read {}: {}", path.display(), e)))
+            };
+            builder = builder.with_client_identity_pem(read(cert)?, read(key)?);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(ConfigError(
+                "client_cert and client_key must be configured together".to_string(),
+            ))
+        }
+    }
+    if let Some(proxy) = &profile.proxy {
+        builder = builder.with_proxy(proxy);
+    }
+    if let Some(hosts) = &profile.no_proxy {
+        builder = builder.with_no_proxy(hosts);
+    }
+    if let Some(rate) = profile.max_requests_per_second {
+        let limiter = RateLimiter::new(rate, profile.burst.unwrap_or(1))
+            .map_err(|e| ConfigError(e.to_string()))?;
+        builder = builder.with_rate_limiter(limiter);
+    }
+    match (
+        &profile.oauth_token_url,
+        &profile.oauth_client_id,
+        &profile.oauth_client_secret,
+    ) {
+        (Some(url), Some(id), Some(secret)) => {
+            let provider = ClientCredentialsProvider::new(url, id, secret)
+                .with_scopes(profile.oauth_scopes.clone());
+            builder = builder.with_token_provider(provider);
+        }
+        (None, None, None) => {}
+        _ => return Err(ConfigError(
+            "oauth_token_url, oauth_client_id and oauth_client_secret must be configured together"
+                .to_string(),
+        )),
+    }
+    if let Some(user_agent) = &profile.user_agent {
+        builder = builder.with_user_agent(user_agent);
+    }
+    for (name, value) in &profile.headers {
+        builder = builder.with_default_header(name, value);
+    }
+    Ok(builder.with_insecure_skip_verify(profile.insecure_skip_verify))
+}
+
+/// The client of a tracking URI naming a local store rather than a tracking server: a
+/// [`FileStoreClient`] for `file:` URIs and plain paths, and with the `sqlite` feature a
+/// `SqliteStoreClient` for `sqlite:` URIs.
+pub(crate) fn local_store_client(
+    tracking_uri: &str,
+) -> Option<MLFlowResult<Arc<dyn MLFlowClient>>> {
+    if is_file_store_uri(tracking_uri) {
+        return Some(FileStoreClient::from_tracking_uri(tracking_uri).map(Into::into));
+    }
+    #[cfg(feature = "sqlite")]
+    if tracking_uri.starts_with("sqlite:") {
+        return Some(crate::SqliteStoreClient::from_tracking_uri(tracking_uri).map(Into::into));
+    }
+    None
+}
+
+/// The client of the global config, for builders and loaders without an explicit client. Local
+/// store URIs track to the store, see [`local_store_client`].
+pub(crate) fn default_client() -> MLFlowResult<SharedClient> {
+    let config = Config::global();
+    let tracking_uri = config.tracking_uri()?;
+    match local_store_client(&tracking_uri) {
+        Some(client) => Ok(client?.into()),
+        #[cfg(feature = "blocking")]
+        None => Ok(config.client()?.into()),
+        // builders are usually handed their client right after, e.g. an external transport
+        // through `with_dyn_client`, so a missing REST client only fails once it is used
+        #[cfg(not(feature = "blocking"))]
+        None => {
+            log::warn!("{}", tracking_server_unsupported(&tracking_uri));
+            Ok(SharedClient::default())
+        }
+    }
+}
+
+/// Without the `blocking` feature only local stores are tracked to synchronously.
+#[cfg(not(feature = "blocking"))]
+pub(crate) fn tracking_server_unsupported(tracking_uri: &str) -> crate::MLFlowError {
+    crate::MLFlowError::ClientError(format!(
+        "tracking to {} needs the blocking feature, or the async client",
+        tracking_uri
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: Vec<(&str, String)>) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> =
+            vars.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn resolved_uri(config: &Config, vars: Vec<(&str, String)>) -> String {
+        tracking_uri(&config.resolve_with(env(vars)).unwrap())
+    }
+
+    /// A config home with `config.toml` in it, and the variable pointing at it.
+    fn config_home(contents: &str) -> (tempfile::TempDir, (&'static str, String)) {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("mlflow-rs").join("config.toml");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, contents).unwrap();
+        let home = dir.path().to_string_lossy().to_string();
+        (dir, ("XDG_CONFIG_HOME", home))
+    }
+
+    #[test]
+    fn resolution_order() {
+        let (_dir, xdg) = config_home("tracking_uri = \"https://file.example.com\"\n");
+        let config = Config::default();
+        let from_env = ("MLFLOW_TRACKING_URI", "https://env.example.com".to_string());
+
+        assert_eq!(resolved_uri(&config, vec![]), DEFAULT_TRACKING_URI);
+        assert_eq!(
+            resolved_uri(&config, vec![xdg.clone()]),
+            "https://file.example.com"
+        );
+        assert_eq!(
+            resolved_uri(&config, vec![xdg.clone(), from_env.clone()]),
+            "https://env.example.com"
+        );
+        assert_eq!(
+            resolved_uri(
+                &config
+                    .clone()
+                    .with_tracking_uri("https://explicit.example.com"),
+                vec![xdg, from_env]
+            ),
+            "https://explicit.example.com"
+        );
+    }
+
+    #[test]
+    fn profiles() {
+        let (_dir, xdg) = config_home(
+            r#"
+tracking_uri = "https://default.example.com"
+
+[profiles.staging]
+tracking_uri = "https://staging.example.com"
+token = "t0ken"
+timeout_secs = 5
+multipart_upload_chunk_size = 1024
+
+[profiles.staging.headers]
+X-Org-Id = "research"
+
+[profiles.partial]
+username = "ci"
+
+[profiles.oauth]
+oauth_token_url = "https://login.example.com/token"
+oauth_client_id = "jobs"
+"#,
+        );
+        let from_env = ("MLFLOW_TRACKING_URI", "https://env.example.com".to_string());
+
+        let staging = Config::from_profile("staging");
+        assert_eq!(
+            resolved_uri(&staging, vec![xdg.clone(), from_env.clone()]),
+            "https://staging.example.com"
+        );
+        let profile = staging.resolve_with(env(vec![xdg.clone()])).unwrap();
+        assert_eq!(profile.token.as_deref(), Some("t0ken"));
+        assert_eq!(profile.multipart_upload_chunk_size, Some(1024));
+        assert_eq!(profile.headers["X-Org-Id"], "research");
+        #[cfg(feature = "blocking")]
+        client_builder(&profile).unwrap().build().unwrap();
+
+        assert_eq!(
+            resolved_uri(
+                &Config::default(),
+                vec![xdg.clone(), (PROFILE_ENV, "staging".to_string())]
+            ),
+            "https://staging.example.com"
+        );
+        // a profile chosen through the environment ranks below MLFLOW_TRACKING_URI
+        assert_eq!(
+            resolved_uri(
+                &Config::default(),
+                vec![xdg.clone(), (PROFILE_ENV, "staging".to_string()), from_env]
+            ),
+            "https://env.example.com"
+        );
+
+        Config::from_profile("missing")
+            .resolve_with(env(vec![xdg.clone()]))
+            .expect_err("profile missing is not configured");
+        let partial = Config::from_profile("partial")
+            .resolve_with(env(vec![xdg.clone()]))
+            .unwrap();
+        assert_eq!(tracking_uri(&partial), DEFAULT_TRACKING_URI);
+        #[cfg(feature = "blocking")]
+        client_builder(&partial).expect_err("username without a password");
+        #[cfg(feature = "blocking")]
+        client_builder(
+            &Config::from_profile("oauth")
+                .resolve_with(env(vec![xdg.clone()]))
+                .unwrap(),
+        )
+        .expect_err("client id without a secret");
+    }
+
+    #[test]
+    fn config_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.toml");
+
+        Config::default()
+            .with_config_file(&file)
+            .with_profile("staging")
+            .resolve_with(env(vec![]))
+            .expect_err("an explicit config file must exist");
+        std::fs::write(&file, "tracking_uri = ").unwrap();
+        Config::default()
+            .resolve_with(env(vec![(
+                CONFIG_FILE_ENV,
+                file.to_string_lossy().to_string(),
+            )]))
+            .expect_err("invalid TOML");
+    }
+}
@@ -0,0 +1,543 @@
+//! The entities of the tracking server and the request and response bodies of its REST API,
+//! independent of any transport.
+//!
+//! Everything here builds without `reqwest`: with `default-features = false` the crate is
+//! reduced to these types, the [`MLFlowClient`](crate::MLFlowClient) trait and the local stores.
+//! Targets bringing their own HTTP stack build a request, serialize it as JSON and send it to
+//! its [`Endpoint`]:
+//!
+//! ```
+//! use mlflow_rs::core::{CreateRunRequest, Endpoint};
+//!
+//! let request = CreateRunRequest {
+//!     experiment_id: "0".to_string(),
+//!     run_name: Some("on-device".to_string()),
+//!     start_time: 1_700_000_000_000,
+//!     tags: vec![],
+//! };
+//! assert_eq!(CreateRunRequest::PATH, "/api/2.0/mlflow/runs/create");
+//! let body = serde_json::to_string(&request).unwrap();
+//! # let _ = body;
+//! ```
+
+pub use crate::entities::*;
+
+use crate::artifacts::FileInfo;
+#[cfg(feature = "auth")]
+use crate::auth::{ExperimentPermission, Permission, User};
+use crate::dataset::RunInputs;
+use crate::registry::{ModelVersion, ModelVersionTag, RegisteredModel, RegisteredModelTag};
+use crate::search::RunViewType;
+#[cfg(feature = "webhooks")]
+use crate::webhooks::{
+    HttpUrlSpec, JobSpec, RegistryWebhook, RegistryWebhookEvent, RegistryWebhookStatus,
+    WebhookTestResult,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// A request sent as the JSON body of a `POST` to a fixed path of the tracking server.
+pub trait Endpoint: Serialize {
+    /// Path of the endpoint, relative to the tracking server's URL.
+    const PATH: &'static str;
+
+    /// What the server answers with on success. Failures carry an [`ErrorResponse`].
+    type Response: DeserializeOwned;
+}
+
+macro_rules! endpoint {
+    ($request:ty, $path:literal, $response:ty) => {
+        impl Endpoint for $request {
+            const PATH: &'static str = concat!("/api/2.0/mlflow/", $path);
+            type Response = $response;
+        }
+    };
+}
+
+endpoint!(
+    CreateExperimentRequest,
+    "experiments/create",
+    CreateExperimentResponse
+);
+endpoint!(
+    SearchExperimentsRequest,
+    "experiments/search",
+    SearchExperimentsResponse
+);
+endpoint!(CreateRunRequest, "runs/create", CreateRunResponse);
+endpoint!(UpdateRunRequest, "runs/update", UpdateRunResponse);
+endpoint!(SearchRunsRequest, "runs/search", SearchRunsResponse);
+endpoint!(LogMetricRequest, "runs/log-metric", EmptyResponse);
+endpoint!(LogParamRequest, "runs/log-parameter", EmptyResponse);
+endpoint!(LogBatchRequest, "runs/log-batch", EmptyResponse);
+endpoint!(
+    CreateRegisteredModelRequest,
+    "registered-models/create",
+    RegisteredModelResponse
+);
+endpoint!(
+    CreateModelVersionRequest,
+    "model-versions/create",
+    ModelVersionResponse
+);
+endpoint!(StartTraceRequest, "traces", TraceInfoResponse);
+
+/// The body of endpoints answering with nothing but a success status.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EmptyResponse {}
+
+/// Error body returned by the tracking server alongside non-success status codes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error_code: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateExperimentRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact_location: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<ExperimentTag>,
+}
+
+/// An experiment as the tracking server sends it. Converted into an
+/// [`Experiment`](crate::Experiment) bound to the client it was fetched with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExperimentInfo {
+    pub experiment_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub artifact_location: Option<String>,
+    #[serde(default)]
+    pub lifecycle_stage: Option<LifecycleStage>,
+    #[serde(default)]
+    pub tags: Vec<ExperimentTag>,
+    #[serde(default, alias = "creation_timestamp")]
+    pub creation_time: Option<i64>,
+    #[serde(default, alias = "last_updated_timestamp")]
+    pub last_update_time: Option<i64>,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateExperimentResponse {
+    pub experiment_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetExperimentResponse {
+    pub experiment: ExperimentInfo,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchExperimentsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    pub view_type: RunViewType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchExperimentsResponse {
+    #[serde(default)]
+    pub experiments: Vec<ExperimentInfo>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateRunRequest {
+    pub experiment_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_name: Option<String>,
+    pub start_time: i64,
+    pub tags: Vec<RunTag>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunResponse {
+    pub info: RunInfo,
+    #[serde(default)]
+    pub data: RunData,
+    #[serde(default)]
+    pub inputs: RunInputs,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateRunResponse {
+    pub run: RunResponse,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetRunResponse {
+    pub run: RunResponse,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchRunsRequest {
+    pub experiment_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    pub run_view_type: RunViewType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchRunsResponse {
+    #[serde(default)]
+    pub runs: Vec<RunResponse>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateRunRequest {
+    pub run_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<RunStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateRunResponse {
+    pub run_info: RunInfo,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogMetricRequest {
+    pub run_id: String,
+    #[serde(flatten)]
+    pub metric: Metric,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogParamRequest {
+    pub run_id: String,
+    #[serde(flatten)]
+    pub param: Param,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LogBatchRequest {
+    pub run_id: String,
+    pub metrics: Vec<Metric>,
+    pub params: Vec<Param>,
+    pub tags: Vec<RunTag>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MetricHistoryResponse {
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ListArtifactsResponse {
+    #[serde(default)]
+    pub files: Vec<FileInfo>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateRegisteredModelRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub tags: Vec<RegisteredModelTag>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegisteredModelResponse {
+    pub registered_model: RegisteredModel,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateModelVersionRequest {
+    pub name: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub tags: Vec<ModelVersionTag>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModelVersionResponse {
+    pub model_version: ModelVersion,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchRegisteredModelsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchRegisteredModelsResponse {
+    #[serde(default)]
+    pub registered_models: Vec<RegisteredModel>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchModelVersionsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchModelVersionsResponse {
+    #[serde(default)]
+    pub model_versions: Vec<ModelVersion>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CreateRegistryWebhookRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    pub events: Vec<RegistryWebhookEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<RegistryWebhookStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_url_spec: Option<HttpUrlSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_spec: Option<JobSpec>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UpdateRegistryWebhookRequest {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<RegistryWebhookEvent>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<RegistryWebhookStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_url_spec: Option<HttpUrlSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_spec: Option<JobSpec>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegistryWebhookResponse {
+    pub webhook: RegistryWebhook,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ListRegistryWebhooksRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<RegistryWebhookEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ListRegistryWebhooksResponse {
+    #[serde(default)]
+    pub webhooks: Vec<RegistryWebhook>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestRegistryWebhookRequest {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<RegistryWebhookEvent>,
+}
+
+#[cfg(feature = "webhooks")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestRegistryWebhookResponse {
+    pub webhook: WebhookTestResult,
+}
+
+#[cfg(feature = "auth")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExperimentPermissionRequest {
+    pub experiment_id: String,
+    pub username: String,
+    pub permission: Permission,
+}
+
+#[cfg(feature = "auth")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExperimentPermissionResponse {
+    pub experiment_permission: ExperimentPermission,
+}
+
+#[cfg(feature = "auth")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub user: User,
+}
+
+/// The body of the requests creating a user or changing their password.
+#[cfg(feature = "auth")]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserPasswordRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[cfg(feature = "auth")]
+impl Debug for UserPasswordRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserPasswordRequest")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+#[cfg(feature = "auth")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateUserAdminRequest {
+    pub username: String,
+    pub is_admin: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StartTraceRequest {
+    pub experiment_id: String,
+    pub timestamp_ms: i64,
+    pub request_metadata: Vec<TraceRequestMetadata>,
+    pub tags: Vec<TraceTag>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EndTraceRequest {
+    pub request_id: String,
+    pub timestamp_ms: i64,
+    pub status: TraceStatus,
+    pub request_metadata: Vec<TraceRequestMetadata>,
+    pub tags: Vec<TraceTag>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceInfoResponse {
+    pub trace_info: TraceInfo,
+}
+
+/// Maximum number of metrics the server accepts in a single `runs/log-batch` request.
+pub const MAX_METRICS_PER_BATCH: usize = 1000;
+/// Maximum number of params, and separately of tags, accepted in a single `runs/log-batch` request.
+pub const MAX_PARAMS_TAGS_PER_BATCH: usize = 100;
+/// Maximum number of metrics, params and tags combined accepted in a single `runs/log-batch` request.
+pub const MAX_ENTITIES_PER_BATCH: usize = 1000;
+
+/// Splits an arbitrarily large batch into requests that respect the server's per-request limits.
+///
+/// Params and tags are packed first since they have the tighter limits, and each request is then
+/// topped up with as many metrics as the entity limit allows. Entity order is preserved.
+pub fn chunk_batch(
+    run_id: &str,
+    metrics: Vec<Metric>,
+    params: Vec<Param>,
+    tags: Vec<RunTag>,
+) -> Vec<LogBatchRequest> {
+    let mut metrics = metrics.into_iter().peekable();
+    let mut params = params.into_iter().peekable();
+    let mut tags = tags.into_iter().peekable();
+    let mut requests = vec![];
+
+    while metrics.peek().is_some() || params.peek().is_some() || tags.peek().is_some() {
+        let params: Vec<Param> = params.by_ref().take(MAX_PARAMS_TAGS_PER_BATCH).collect();
+        let tags: Vec<RunTag> = tags.by_ref().take(MAX_PARAMS_TAGS_PER_BATCH).collect();
+        let metric_capacity =
+            MAX_METRICS_PER_BATCH.min(MAX_ENTITIES_PER_BATCH - params.len() - tags.len());
+        let metrics: Vec<Metric> = metrics.by_ref().take(metric_capacity).collect();
+
+        requests.push(LogBatchRequest {
+            run_id: run_id.to_string(),
+            metrics,
+            params,
+            tags,
+        });
+    }
+
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What an external transport does with a request: post it to its path and parse the answer.
+    fn exchange<R: Endpoint>(request: &R, answer: &str) -> (String, R::Response) {
+        let body = serde_json::to_string(request).unwrap();
+        (body, serde_json::from_str(answer).unwrap())
+    }
+
+    #[test]
+    fn endpoints() {
+        let request = LogParamRequest {
+            run_id: "abc".to_string(),
+            param: Param {
+                key: "lr".to_string(),
+                value: "0.1".to_string(),
+            },
+        };
+        let (body, _) = exchange(&request, "{}");
+        assert_eq!(body, r#"{"run_id":"abc","key":"lr","value":"0.1"}"#);
+        assert_eq!(LogParamRequest::PATH, "/api/2.0/mlflow/runs/log-parameter");
+
+        let (_, response) = exchange(
+            &CreateExperimentRequest {
+                name: "sweep".to_string(),
+                artifact_location: None,
+                tags: vec![],
+            },
+            r#"{"experiment_id": "7"}"#,
+        );
+        assert_eq!(response.experiment_id, "7");
+    }
+}
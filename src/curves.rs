@@ -0,0 +1,309 @@
+//! ROC and precision-recall curves of a binary classifier's scores, logged as JSON artifacts
+//! along with the area under them, and with the `plotters` feature as SVG plots.
+
+use crate::run::Run;
+use crate::MLFlowError::InvalidMetric;
+use crate::MLFlowResult;
+use serde_json::json;
+
+/// The artifact file [`Run::log_roc_curve`] stores the curve's points in.
+pub const ROC_CURVE_FILE_NAME: &str = "roc_curve.json";
+/// The artifact file [`Run::log_precision_recall_curve`] stores the curve's points in.
+pub const PRECISION_RECALL_CURVE_FILE_NAME: &str = "precision_recall_curve.json";
+
+/// Which curve a [`Curve`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveKind {
+    /// The true positive rate against the false positive rate.
+    Roc,
+    /// Precision against recall.
+    PrecisionRecall,
+}
+
+impl CurveKind {
+    /// The names of the x and y values, as stored in the artifact.
+    fn axes(self) -> (&'static str, &'static str) {
+        match self {
+            CurveKind::Roc => ("fpr", "tpr"),
+            CurveKind::PrecisionRecall => ("recall", "precision"),
+        }
+    }
+
+    /// The metric the area under the curve is logged as, named as `mlflow.evaluate` does.
+    fn metric_key(self) -> &'static str {
+        match self {
+            CurveKind::Roc => "roc_auc",
+            CurveKind::PrecisionRecall => "precision_recall_auc",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            CurveKind::Roc => ROC_CURVE_FILE_NAME,
+            CurveKind::PrecisionRecall => PRECISION_RECALL_CURVE_FILE_NAME,
+        }
+    }
+}
+
+/// The points of a curve over every distinct score used as the threshold for a positive
+/// prediction, from the highest down, like scikit-learn's `roc_curve` and
+/// `precision_recall_curve`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    pub kind: CurveKind,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    /// The threshold of each point. The first point, predicting nothing positive, has an
+    /// infinite one.
+    pub thresholds: Vec<f64>,
+    /// The area under the curve, by the trapezoidal rule.
+    pub auc: f64,
+}
+
+impl Curve {
+    /// The ROC curve of `scores` given whether each example is actually positive.
+    pub fn roc(scores: &[f64], labels: &[bool]) -> MLFlowResult<Curve> {
+        let counts = counts(scores, labels)?;
+        let (positives, negatives) = counts.last().map(|(_, tp, fp)| (*tp, *fp)).unwrap();
+        if negatives == 0 {
+            return Err(InvalidMetric(
+                "a ROC curve needs negative examples".to_string(),
+            ));
+        }
+        let points = counts.iter().map(|&(t, tp, fp)| {
+            (
+                t,
+                fp as f64 / negatives as f64,
+                tp as f64 / positives as f64,
+            )
+        });
+        Ok(Curve::new(CurveKind::Roc, (0.0, 0.0), points))
+    }
+
+    /// The precision-recall curve of `scores` given whether each example is actually positive.
+    pub fn precision_recall(scores: &[f64], labels: &[bool]) -> MLFlowResult<Curve> {
+        let counts = counts(scores, labels)?;
+        let positives = counts.last().map(|(_, tp, _)| *tp).unwrap();
+        let points = counts.iter().map(|&(t, tp, fp)| {
+            (
+                t,
+                tp as f64 / positives as f64,
+                tp as f64 / (tp + fp) as f64,
+            )
+        });
+        Ok(Curve::new(CurveKind::PrecisionRecall, (0.0, 1.0), points))
+    }
+
+    fn new(
+        kind: CurveKind,
+        start: (f64, f64),
+        points: impl Iterator<Item = (f64, f64, f64)>,
+    ) -> Curve {
+        let mut curve = Curve {
+            kind,
+            x: vec![start.0],
+            y: vec![start.1],
+            thresholds: vec![f64::INFINITY],
+            auc: 0.0,
+        };
+        for (threshold, x, y) in points {
+            let (last_x, last_y) = (curve.x[curve.x.len() - 1], curve.y[curve.y.len() - 1]);
+            curve.auc += (x - last_x) * (y + last_y) / 2.0;
+            curve.thresholds.push(threshold);
+            curve.x.push(x);
+            curve.y.push(y);
+        }
+        curve
+    }
+
+    /// Renders the curve as a 480x480 SVG plot, with the chance level for a ROC curve.
+    #[cfg(feature = "plotters")]
+    pub fn to_svg(&self) -> MLFlowResult<crate::Figure> {
+        use plotters::prelude::*;
+
+        let (x_desc, y_desc) = match self.kind {
+            CurveKind::Roc => ("False positive rate", "True positive rate"),
+            CurveKind::PrecisionRecall => ("Recall", "Precision"),
+        };
+        let failed = |e: String| crate::MLFlowError::ArtifactError(e);
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (480, 480)).into_drawing_area();
+            root.fill(&WHITE).map_err(|e| failed(e.to_string()))?;
+            let mut chart = ChartBuilder::on(&root)
+                .caption(
+                    format!("AUC = {:.4}", self.auc),
+                    ("sans-serif", 18).into_font(),
+                )
+                .margin(12)
+                .x_label_area_size(40)
+                .y_label_area_size(48)
+                .build_cartesian_2d(0f64..1f64, 0f64..1.02f64)
+                .map_err(|e| failed(e.to_string()))?;
+            chart
+                .configure_mesh()
+                .x_desc(x_desc)
+                .y_desc(y_desc)
+                .draw()
+                .map_err(|e| failed(e.to_string()))?;
+            if self.kind == CurveKind::Roc {
+                chart
+                    .draw_series(LineSeries::new([(0.0, 0.0), (1.0, 1.0)], &BLACK.mix(0.3)))
+                    .map_err(|e| failed(e.to_string()))?;
+            }
+            chart
+                .draw_series(LineSeries::new(
+                    self.x.iter().copied().zip(self.y.iter().copied()),
+                    BLUE.stroke_width(2),
+                ))
+                .map_err(|e| failed(e.to_string()))?;
+            root.present().map_err(|e| failed(e.to_string()))?;
+        }
+        Ok(crate::Figure::svg(svg))
+    }
+
+    fn log(&self, run: &Run) -> MLFlowResult<()> {
+        let (x, y) = self.kind.axes();
+        // the infinite first threshold is stored as null
+        let points = json!({
+            x: self.x,
+            y: self.y,
+            "thresholds": self.thresholds,
+            "auc": self.auc,
+        });
+        run.log_json(&points, self.kind.file_name())?;
+        #[cfg(feature = "plotters")]
+        run.log_figure(
+            &self.to_svg()?,
+            &self.kind.file_name().replace(".json", ".svg"),
+        )?;
+        run.log_metric(self.kind.metric_key(), self.auc, None, None)
+    }
+}
+
+/// For each distinct score from the highest down, the score and the number of positive and
+/// negative examples scoring at least that.
+fn counts(scores: &[f64], labels: &[bool]) -> MLFlowResult<Vec<(f64, u64, u64)>> {
+    if scores.len() != labels.len() {
+        return Err(InvalidMetric(format!(
+            "{} scores but {} labels",
+            scores.len(),
+            labels.len()
+        )));
+    }
+    if scores.iter().any(|s| s.is_nan()) {
+        return Err(InvalidMetric("scores cannot be NaN".to_string()));
+    }
+    let mut examples: Vec<(f64, bool)> =
+        scores.iter().copied().zip(labels.iter().copied()).collect();
+    examples.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut counts: Vec<(f64, u64, u64)> = vec![];
+    let (mut tp, mut fp) = (0, 0);
+    for (i, (score, positive)) in examples.iter().enumerate() {
+        match positive {
+            true => tp += 1,
+            false => fp += 1,
+        }
+        if examples.get(i + 1).is_none_or(|next| next.0 != *score) {
+            counts.push((*score, tp, fp));
+        }
+    }
+    if tp == 0 {
+        return Err(InvalidMetric(
+            "the curve needs positive examples".to_string(),
+        ));
+    }
+    Ok(counts)
+}
+
+impl Run {
+    /// Logs the ROC curve of a binary classifier's `scores`, given whether each example is
+    /// actually positive, as the [`ROC_CURVE_FILE_NAME`] artifact and the area under it as the
+    /// `roc_auc` metric. With the `plotters` feature the curve is also plotted in
+    /// `roc_curve.svg`.
+    ///
+    /// ```no_run
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// let scores = [0.1, 0.4, 0.35, 0.8];
+    /// let labels = [false, false, true, true];
+    /// let curve = run.log_roc_curve(&scores, &labels).unwrap();
+    /// assert_eq!(curve.auc, 0.75);
+    /// run.log_precision_recall_curve(&scores, &labels).unwrap();
+    /// ```
+    pub fn log_roc_curve(&self, scores: &[f64], labels: &[bool]) -> MLFlowResult<Curve> {
+        let curve = Curve::roc(scores, labels)?;
+        curve.log(self)?;
+        Ok(curve)
+    }
+
+    /// Logs the precision-recall curve of a binary classifier's `scores`, given whether each
+    /// example is actually positive, as the [`PRECISION_RECALL_CURVE_FILE_NAME`] artifact and
+    /// the area under it as the `precision_recall_auc` metric. With the `plotters` feature the
+    /// curve is also plotted in `precision_recall_curve.svg`.
+    pub fn log_precision_recall_curve(
+        &self,
+        scores: &[f64],
+        labels: &[bool],
+    ) -> MLFlowResult<Curve> {
+        let curve = Curve::precision_recall(scores, labels)?;
+        curve.log(self)?;
+        Ok(curve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCORES: [f64; 4] = [0.1, 0.4, 0.35, 0.8];
+    const LABELS: [bool; 4] = [false, false, true, true];
+
+    #[test]
+    fn roc_curve_like_scikit_learn() {
+        let curve = Curve::roc(&SCORES, &LABELS).unwrap();
+        assert_eq!(curve.x, vec![0.0, 0.0, 0.5, 0.5, 1.0]);
+        assert_eq!(curve.y, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(&curve.thresholds[1..], &[0.8, 0.4, 0.35, 0.1]);
+        assert_eq!(curve.auc, 0.75);
+
+        let tied = Curve::roc(&[0.5, 0.5], &[true, false]).unwrap();
+        assert_eq!((tied.x.len(), tied.auc), (2, 0.5));
+        assert!(Curve::roc(&[0.5], &[true]).is_err());
+        assert!(Curve::roc(&[0.5], &[false]).is_err());
+        assert!(Curve::roc(&[f64::NAN, 0.1], &[true, false]).is_err());
+    }
+
+    #[test]
+    fn precision_recall_curve() {
+        let curve = Curve::precision_recall(&SCORES, &LABELS).unwrap();
+        assert_eq!(curve.x, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(curve.y, vec![1.0, 1.0, 0.5, 2.0 / 3.0, 0.5]);
+        assert!((curve.auc - 0.5 - 0.5 * (0.5 + 2.0 / 3.0) / 2.0).abs() < 1e-12);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn logs_points_and_auc() {
+        let run = crate::RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(crate::MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        run.log_roc_curve(&SCORES, &LABELS).unwrap();
+
+        assert_eq!(run.get_metric_history("roc_auc").unwrap()[0].value, 0.75);
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", uuid::Uuid::new_v4()));
+        let path = run.download_artifact(ROC_CURVE_FILE_NAME, &dir).unwrap();
+        let points: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(points["tpr"][3], 1.0);
+        assert!(points["thresholds"][0].is_null());
+        #[cfg(feature = "plotters")]
+        {
+            let svg = run.download_artifact("roc_curve.svg", &dir).unwrap();
+            assert!(std::fs::read_to_string(svg).unwrap().starts_with("<svg"));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+//! Resolution of `databricks` tracking URIs to a workspace host and credentials.
+//!
+//! Databricks serves the MLflow REST API from the workspace host itself, so once resolved the
+//! client talks to it like any other tracking server.
+
+use crate::client::Auth;
+use crate::MLFlowError::ClientError;
+use crate::MLFlowResult;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub(crate) const DATABRICKS_SCHEME: &str = "databricks";
+const DEFAULT_PROFILE: &str = "DEFAULT";
+
+/// Workspace host and credentials a `databricks` URI resolved to.
+#[derive(Clone, Debug)]
+pub(crate) struct DatabricksWorkspace {
+    pub(crate) host: String,
+    pub(crate) auth: Auth,
+}
+
+/// Resolves `databricks` or `databricks://<profile>` using the process environment.
+pub(crate) fn resolve(tracking_uri: &str) -> MLFlowResult<DatabricksWorkspace> {
+    resolve_with(tracking_uri, |name| std::env::var(name).ok())
+}
+
+/// `databricks` uses `DATABRICKS_HOST` with `DATABRICKS_TOKEN` (or `DATABRICKS_USERNAME` and
+/// `DATABRICKS_PASSWORD`) when set, falling back to the `DATABRICKS_CONFIG_PROFILE` or
+/// `DEFAULT` profile of `~/.databrickscfg`. `databricks://<profile>` always reads the named
+/// profile. The config file location can be overridden with `DATABRICKS_CONFIG_FILE`.
+pub(crate) fn resolve_with(
+    tracking_uri: &str,
+    env: impl Fn(&str) -> Option<String>,
+) -> MLFlowResult<DatabricksWorkspace> {
+    let profile = match tracking_uri.strip_prefix(DATABRICKS_SCHEME) {
+        Some("") => None,
+        Some(rest) => match rest.strip_prefix("://") {
+            Some(profile) if profile.contains(':') => {
+                return Err(ClientError(format!(
+                    "{} uses secret scope credentials, which are not supported",
+                    tracking_uri
+                )))
+            }
+            Some(profile) if !profile.is_empty() => Some(profile.to_string()),
+            _ => {
+                return Err(ClientError(format!(
+                    "invalid tracking URI {}",
+                    tracking_uri
+                )))
+            }
+        },
+        None => {
+            return Err(ClientError(format!(
+                "{} is not a databricks URI",
+                tracking_uri
+            )))
+        }
+    };
+
+    if profile.is_none() {
+        if let Some(host) = env("DATABRICKS_HOST") {
+            let values = [
+                ("host", Some(host)),
+                ("token", env("DATABRICKS_TOKEN")),
+                ("username", env("DATABRICKS_USERNAME")),
+                ("password", env("DATABRICKS_PASSWORD")),
+            ]
+            .into_iter()
+            .filter_map(|(k, v)| v.map(|v| (k.to_string(), v)))
+            .collect();
+            return workspace(&values, "environment");
+        }
+    }
+
+    let profile = profile
+        .or_else(|| env("DATABRICKS_CONFIG_PROFILE"))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let path = env("DATABRICKS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".databrickscfg")))
+        .ok_or_else(|| ClientError("could not locate .databrickscfg".to_string()))?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| ClientError(format!("could not read {}: {}", path.to_string_lossy(), e)))?;
+
+    let values = parse_profile(&contents, &profile).ok_or_else(|| {
+        ClientError(format!(
+            "profile {} not found in {}",
+            profile,
+            path.to_string_lossy()
+        ))
+    })?;
+    workspace(&values, &profile)
+}
+
+fn workspace(values: &HashMap<String, String>, source: &str) -> MLFlowResult<DatabricksWorkspace> {
+    let host = values
+        .get("host")
+        .filter(|h| !h.is_empty())
+        .ok_or_else(|| ClientError(format!("no databricks host configured in {}", source)))?;
+    let host = if host.contains("://") {
+        host.trim_end_matches('/').to_string()
+    } else {
+        format!("https://{}", host.trim_end_matches('/'))
+    };
+
+    let auth = match (
+        values.get("token"),
+        values.get("username"),
+        values.get("password"),
+    ) {
+        (Some(token), _, _) => Auth::Bearer(token.clone()),
+        (None, Some(username), Some(password)) => Auth::Basic {
+            username: username.clone(),
+            password: password.clone(),
+        },
+        _ => {
+            return Err(ClientError(format!(
+                "no databricks token or username and password configured in {}",
+                source
+            )))
+        }
+    };
+
+    Ok(DatabricksWorkspace { host, auth })
+}
+
+/// Reads the `key = value` pairs of `[profile]` from a `.databrickscfg` INI file.
+fn parse_profile(contents: &str, profile: &str) -> Option<HashMap<String, String>> {
+    let mut values = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if values.is_some() {
+                break;
+            }
+            if section.trim() == profile {
+                values = Some(HashMap::new());
+            }
+            continue;
+        }
+        if let (Some(values), Some((key, value))) = (values.as_mut(), line.split_once('=')) {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[DEFAULT]
+host = https://default.cloud.databricks.com/
+token = dapi-default
+
+; a workspace using basic auth
+[staging]
+host = staging.cloud.databricks.com
+username = someone
+password = secret
+"#;
+
+    fn env_with_config(
+        config: &tempfile::NamedTempFile,
+        extra: Vec<(&'static str, &'static str)>,
+    ) -> impl Fn(&str) -> Option<String> {
+        let path = config.path().to_string_lossy().to_string();
+        move |name| match name {
+            "DATABRICKS_CONFIG_FILE" => Some(path.clone()),
+            _ => extra
+                .iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string()),
+        }
+    }
+
+    fn config_file() -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), CONFIG).unwrap();
+        file
+    }
+
+    #[test]
+    fn default_profile() {
+        let config = config_file();
+        let workspace = resolve_with("databricks", env_with_config(&config, vec![])).unwrap();
+        assert_eq!(workspace.host, "https://default.cloud.databricks.com");
+        assert!(matches!(workspace.auth, Auth::Bearer(ref t) if t == "dapi-default"));
+    }
+
+    #[test]
+    fn named_profile() {
+        let config = config_file();
+        let workspace =
+            resolve_with("databricks://staging", env_with_config(&config, vec![])).unwrap();
+        assert_eq!(workspace.host, "https://staging.cloud.databricks.com");
+        assert!(matches!(
+            workspace.auth,
+            Auth::Basic { ref username, ref password } if username == "someone" && password == "secret"
+        ));
+    }
+
+    #[test]
+    fn environment_takes_precedence_over_default_profile() {
+        let config = config_file();
+        let env = env_with_config(
+            &config,
+            vec![
+                ("DATABRICKS_HOST", "https://env.cloud.databricks.com"),
+                ("DATABRICKS_TOKEN", "dapi-env"),
+            ],
+        );
+        let workspace = resolve_with("databricks", &env).unwrap();
+        assert_eq!(workspace.host, "https://env.cloud.databricks.com");
+        assert!(matches!(workspace.auth, Auth::Bearer(ref t) if t == "dapi-env"));
+
+        // an explicit profile ignores the environment
+        let workspace = resolve_with("databricks://staging", &env).unwrap();
+        assert_eq!(workspace.host, "https://staging.cloud.databricks.com");
+    }
+
+    #[test]
+    fn invalid_uris() {
+        let config = config_file();
+        let env = env_with_config(&config, vec![]);
+        resolve_with("databricks://missing", &env).expect_err("unknown profile");
+        resolve_with("databricks://scope:prefix", &env).expect_err("secret scopes");
+        resolve_with("databricks:/", &env).expect_err("malformed");
+        resolve_with("http://localhost:5000", &env).expect_err("not databricks");
+    }
+}
@@ -0,0 +1,309 @@
+//! Datasets used by runs, recorded with `runs/log-inputs` so the lineage of a model can be traced
+//! back to the data it was trained and evaluated on.
+
+use crate::run::Run;
+use crate::tags::MLFLOW_DATASET_CONTEXT;
+use crate::MLFlowError::{ArtifactError, InvalidDataset};
+use crate::{MLFlowResult, RunIdentifier};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Number of hex characters MLflow keeps of a dataset digest.
+const DIGEST_LENGTH: usize = 8;
+
+/// Source type of datasets read from the local filesystem.
+const LOCAL_SOURCE_TYPE: &str = "local";
+
+/// A dataset, identified by its name and a digest of its contents. `source` is a JSON document
+/// describing where the data was read from, interpreted according to `source_type`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dataset {
+    pub name: String,
+    pub digest: String,
+    pub source_type: String,
+    pub source: String,
+    /// JSON encoded schema, e.g. `{"mlflow_colspec": [...]}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// JSON encoded summary statistics, e.g. `{"num_rows": 1000}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl Dataset {
+    pub fn new(
+        name: impl AsRef<str>,
+        digest: impl AsRef<str>,
+        source_type: impl AsRef<str>,
+        source: impl AsRef<str>,
+    ) -> Dataset {
+        Dataset {
+            name: name.as_ref().to_string(),
+            digest: digest.as_ref().to_string(),
+            source_type: source_type.as_ref().to_string(),
+            source: source.as_ref().to_string(),
+            schema: None,
+            profile: None,
+        }
+    }
+
+    /// A dataset stored in a local file or directory, with its digest computed from the
+    /// contents by [`file_digest`].
+    pub fn from_path(name: impl AsRef<str>, path: impl AsRef<Path>) -> MLFlowResult<Dataset> {
+        let path = path
+            .as_ref()
+            .canonicalize()
+            .map_err(|e| ArtifactError(e.to_string()))?;
+        let source = serde_json::json!({ "uri": path.to_string_lossy() }).to_string();
+        Ok(Dataset::new(
+            name,
+            file_digest(&path)?,
+            LOCAL_SOURCE_TYPE,
+            source,
+        ))
+    }
+
+    pub fn with_schema(mut self, schema: impl AsRef<str>) -> Self {
+        self.schema = Some(schema.as_ref().to_string());
+        self
+    }
+
+    pub fn with_profile(mut self, profile: impl AsRef<str>) -> Self {
+        self.profile = Some(profile.as_ref().to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputTag {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for InputTag {
+    fn from((k, v): (&str, &str)) -> Self {
+        InputTag {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+/// A dataset used by a run, with tags describing how it was used.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetInput {
+    #[serde(default)]
+    pub tags: Vec<InputTag>,
+    pub dataset: Dataset,
+}
+
+impl DatasetInput {
+    pub fn new(dataset: Dataset) -> DatasetInput {
+        DatasetInput {
+            tags: vec![],
+            dataset,
+        }
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<InputTag>) -> Self {
+        let tag = tag.into();
+        self.tags.retain(|t| t.key != tag.key);
+        self.tags.push(tag);
+        self
+    }
+
+    /// Records what the run used the dataset for, e.g. `training` or `evaluation`. The UI
+    /// groups a run's datasets by context.
+    pub fn with_context(self, context: impl AsRef<str>) -> Self {
+        self.with_tag((MLFLOW_DATASET_CONTEXT, context.as_ref()))
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.key == MLFLOW_DATASET_CONTEXT)
+            .map(|t| t.value.as_str())
+    }
+}
+
+impl From<Dataset> for DatasetInput {
+    fn from(dataset: Dataset) -> Self {
+        DatasetInput::new(dataset)
+    }
+}
+
+/// The datasets a run has logged, as returned by the tracking server with the run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunInputs {
+    #[serde(default)]
+    pub dataset_inputs: Vec<DatasetInput>,
+}
+
+/// A digest of in memory data, truncated to the length MLflow uses for dataset digests.
+pub fn compute_digest(data: impl AsRef<[u8]>) -> String {
+    truncate(Md5::digest(data.as_ref()))
+}
+
+/// A digest of the contents of a file, or of every file below a directory. Files are read in
+/// path order, and their relative paths are part of the digest so renames change it.
+pub fn file_digest(path: impl AsRef<Path>) -> MLFlowResult<String> {
+    let path = path.as_ref();
+    let mut hasher = Md5::new();
+    if path.is_dir() {
+        let mut files = vec![];
+        collect_files(path, &mut files)?;
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(path).unwrap_or(&file);
+            hasher.update(relative.to_string_lossy().as_bytes());
+            hash_file(&file, &mut hasher)?;
+        }
+    } else {
+        hash_file(path, &mut hasher)?;
+    }
+    Ok(truncate(hasher.finalize()))
+}
+
+fn truncate(digest: impl AsRef<[u8]>) -> String {
+    digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>()[..DIGEST_LENGTH]
+        .to_string()
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> MLFlowResult<()> {
+    for entry in std::fs::read_dir(dir).map_err(|e| ArtifactError(e.to_string()))? {
+        let path = entry.map_err(|e| ArtifactError(e.to_string()))?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn hash_file(path: &Path, hasher: &mut Md5) -> MLFlowResult<()> {
+    let mut file = File::open(path).map_err(|e| ArtifactError(e.to_string()))?;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| ArtifactError(e.to_string()))?;
+        if read == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+impl Run {
+    /// Records the datasets the run used. Logging the same dataset twice is a no-op on the
+    /// server.
+    pub fn log_inputs(&self, datasets: Vec<impl Into<DatasetInput>>) -> MLFlowResult<()> {
+        let datasets: Vec<DatasetInput> = datasets.into_iter().map(Into::into).collect();
+        for input in &datasets {
+            if input.dataset.name.is_empty() {
+                return Err(InvalidDataset("dataset name cannot be empty".to_string()));
+            }
+            if input.dataset.digest.is_empty() {
+                return Err(InvalidDataset(format!(
+                    "dataset {} has no digest",
+                    input.dataset.name
+                )));
+            }
+        }
+        if datasets.is_empty() {
+            return Ok(());
+        }
+        self.client.log_inputs(&self.run_id(), datasets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests() {
+        assert_eq!(compute_digest(""), "d41d8cd9");
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("train.csv"), "a,b\n1,2\n").unwrap();
+        let file_only = file_digest(dir.path().join("train.csv")).unwrap();
+        assert_eq!(file_only, compute_digest("a,b\n1,2\n"));
+
+        let before = file_digest(dir.path()).unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("nested/test.csv"), "a,b\n3,4\n").unwrap();
+        assert_ne!(file_digest(dir.path()).unwrap(), before);
+
+        file_digest(dir.path().join("missing.csv")).expect_err("missing file");
+    }
+
+    #[test]
+    fn dataset_input_json() {
+        let input = DatasetInput::new(
+            Dataset::new(
+                "iris",
+                "abcd1234",
+                "http",
+                r#"{"url": "https://x/iris.csv"}"#,
+            )
+            .with_profile(r#"{"num_rows": 150}"#),
+        )
+        .with_context("training")
+        .with_context("evaluation");
+        assert_eq!(input.context(), Some("evaluation"));
+        assert_eq!(
+            serde_json::to_value(&input).unwrap(),
+            serde_json::json!({
+                "tags": [{"key": "mlflow.data.context", "value": "evaluation"}],
+                "dataset": {
+                    "name": "iris",
+                    "digest": "abcd1234",
+                    "source_type": "http",
+                    "source": r#"{"url": "https://x/iris.csv"}"#,
+                    "profile": r#"{"num_rows": 150}"#,
+                },
+            })
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn log_inputs() {
+        use crate::{MockMLFlowClient, RunBuilder};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("train.csv");
+        std::fs::write(&path, "a,b\n1,2\n").unwrap();
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let dataset = Dataset::from_path("train", &path).unwrap();
+        assert_eq!(dataset.source_type, "local");
+        run.log_inputs(vec![
+            DatasetInput::new(dataset.clone()).with_context("training")
+        ])
+        .unwrap();
+        run.log_inputs(vec![dataset]).unwrap();
+
+        let inputs = mock.run_inputs(&run.run_id()).unwrap().dataset_inputs;
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].context(), Some("training"));
+
+        let unnamed = Dataset::new("", "abcd1234", "local", "{}");
+        run.log_inputs(vec![unnamed])
+            .expect_err("datasets must be named");
+    }
+}
@@ -0,0 +1,198 @@
+//! Summary statistics of many values logged as a family of metrics, since MLflow has no
+//! histogram type.
+
+use crate::entities::Metric;
+use crate::run::{current_time_millis, Run};
+use crate::MLFlowError::InvalidMetric;
+use crate::MLFlowResult;
+
+/// The mean, extremes and percentiles of a set of values, such as the gradient norms of a
+/// training step's layers. NaN values are left out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Summarizes `values`, or `None` if there are none besides NaN.
+    pub fn from_values<V: Into<f64>>(values: impl IntoIterator<Item = V>) -> Option<Distribution> {
+        let mut values: Vec<f64> = values
+            .into_iter()
+            .map(Into::into)
+            .filter(|v| !v.is_nan())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable_by(f64::total_cmp);
+
+        Some(Distribution {
+            count: values.len(),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            min: values[0],
+            p50: percentile(&values, 50.0),
+            p95: percentile(&values, 95.0),
+            max: values[values.len() - 1],
+        })
+    }
+
+    /// Summarizes the elements of an array or array view of any shape.
+    #[cfg(feature = "ndarray")]
+    pub fn from_ndarray<A, S, D>(array: &ndarray::ArrayBase<S, D>) -> Option<Distribution>
+    where
+        A: Copy + Into<f64>,
+        S: ndarray::Data<Elem = A>,
+        D: ndarray::Dimension,
+    {
+        Distribution::from_values(array.iter().copied())
+    }
+
+    /// The statistics as metrics named `<key>/<statistic>`, e.g. `grad_norm/p95`, which the UI
+    /// groups together.
+    pub fn metrics(&self, key: impl AsRef<str>, step: Option<i64>) -> Vec<Metric> {
+        let timestamp = current_time_millis();
+        [
+            ("mean", self.mean),
+            ("min", self.min),
+            ("p50", self.p50),
+            ("p95", self.p95),
+            ("max", self.max),
+        ]
+        .into_iter()
+        .map(|(statistic, value)| {
+            Metric::new(
+                format!("{}/{}", key.as_ref(), statistic),
+                value,
+                step,
+                Some(timestamp),
+            )
+        })
+        .collect()
+    }
+}
+
+/// The `p`th percentile of the ascending `sorted` values, interpolating linearly between the
+/// two closest ranks like NumPy's default.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+impl Run {
+    /// Logs the mean, minimum, median, 95th percentile and maximum of `values` as the metrics
+    /// `<key>/mean`, `<key>/min`, `<key>/p50`, `<key>/p95` and `<key>/max` in one request.
+    ///
+    /// Fails with [`InvalidMetric`](crate::MLFlowError::InvalidMetric) if there are no values
+    /// besides NaN.
+    ///
+    /// ```no_run
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// let grad_norms: Vec<f32> = vec![0.12, 0.4, 0.08, 1.3];
+    /// run.log_distribution("grad_norm", grad_norms, Some(100)).unwrap();
+    ///
+    /// // borrowed values are copied
+    /// let activations = [0.5, 0.25, 0.75];
+    /// run.log_distribution("activation", activations.iter().copied(), Some(100)).unwrap();
+    /// ```
+    pub fn log_distribution<V: Into<f64>>(
+        &self,
+        key: impl AsRef<str>,
+        values: impl IntoIterator<Item = V>,
+        step: Option<i64>,
+    ) -> MLFlowResult<Distribution> {
+        let distribution = Distribution::from_values(values).ok_or_else(|| {
+            InvalidMetric(format!(
+                "no values to log the distribution of {}",
+                key.as_ref()
+            ))
+        })?;
+        self.log_batch(distribution.metrics(key, step), vec![], vec![])?;
+        Ok(distribution)
+    }
+
+    /// Logs the distribution of the elements of an array or array view, such as a layer's
+    /// weights, like [`log_distribution`](Run::log_distribution).
+    ///
+    /// ```no_run
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// let weights = ndarray::Array2::<f32>::ones((64, 32));
+    /// run.log_ndarray_distribution("dense/weights", &weights.view(), Some(100)).unwrap();
+    /// ```
+    #[cfg(feature = "ndarray")]
+    pub fn log_ndarray_distribution<A, S, D>(
+        &self,
+        key: impl AsRef<str>,
+        array: &ndarray::ArrayBase<S, D>,
+        step: Option<i64>,
+    ) -> MLFlowResult<Distribution>
+    where
+        A: Copy + Into<f64>,
+        S: ndarray::Data<Elem = A>,
+        D: ndarray::Dimension,
+    {
+        self.log_distribution(key, array.iter().copied(), step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_values() {
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        let distribution = Distribution::from_values(values.iter().rev().copied()).unwrap();
+        assert_eq!(distribution.count, 100);
+        assert_eq!(distribution.mean, 50.5);
+        assert_eq!((distribution.min, distribution.max), (1.0, 100.0));
+        assert_eq!(distribution.p50, 50.5);
+        assert!((distribution.p95 - 95.05).abs() < 1e-9);
+
+        let single = Distribution::from_values([2.5f32, f32::NAN]).unwrap();
+        assert_eq!((single.count, single.p50, single.p95), (1, 2.5, 2.5));
+        assert!(Distribution::from_values([f64::NAN]).is_none());
+        assert!(Distribution::from_values(Vec::<f64>::new()).is_none());
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn summarizes_arrays() {
+        let array = ndarray::array![[1.0f32, 2.0], [3.0, 4.0]];
+        let distribution = Distribution::from_ndarray(&array.column(1)).unwrap();
+        assert_eq!((distribution.count, distribution.mean), (2, 3.0));
+        assert_eq!(Distribution::from_ndarray(&array).unwrap().max, 4.0);
+        assert!(Distribution::from_ndarray(&ndarray::Array1::<f64>::zeros(0)).is_none());
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn logs_statistics_as_metrics() {
+        let run = crate::RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(crate::MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        run.log_distribution("grad_norm", [1u8, 2, 3, 10], Some(7))
+            .unwrap();
+
+        let max = run.get_metric_history("grad_norm/max").unwrap();
+        assert_eq!((max[0].value, max[0].step), (10.0, 7));
+        assert_eq!(
+            run.get_metric_history("grad_norm/mean").unwrap()[0].value,
+            4.0
+        );
+        assert_eq!(
+            run.get_metric_history("grad_norm/p50").unwrap()[0].value,
+            2.5
+        );
+        assert!(run
+            .log_distribution("empty", Vec::<f64>::new(), None)
+            .is_err());
+    }
+}
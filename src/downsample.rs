@@ -0,0 +1,232 @@
+//! Reducing long metric histories to a bounded number of points, so plotting a run with millions
+//! of steps doesn't keep every one of them in memory.
+
+use crate::entities::Metric;
+use std::collections::BTreeMap;
+
+/// Points each [`StepBuckets`] bucket keeps: its first, last, lowest and highest.
+const POINTS_PER_BUCKET: usize = 4;
+
+/// How [`Run::get_metric_history_downsampled`](crate::Run::get_metric_history_downsampled)
+/// picks the points it keeps. Both keep the first and last point and use the step as the x axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Downsampling {
+    /// Evenly spaced points, the cheapest but blind to spikes between them.
+    Stride,
+    /// Largest-Triangle-Three-Buckets, keeping the points that shape the curve, like peaks and
+    /// dips, from each bucket.
+    #[default]
+    Lttb,
+}
+
+impl Downsampling {
+    /// Reduces `metrics`, ordered by step, to at most `max_points`. Histories already short enough
+    /// are returned unchanged.
+    pub fn apply(self, metrics: Vec<Metric>, max_points: usize) -> Vec<Metric> {
+        if metrics.len() <= max_points {
+            return metrics;
+        }
+        match self {
+            Downsampling::Lttb if max_points >= 3 => lttb(metrics, max_points),
+            _ => stride(metrics, max_points),
+        }
+    }
+}
+
+/// The points of a metric history gathered as its pages arrive, in buckets covering equal step
+/// ranges. Each bucket keeps its first, last, lowest and highest point, and the buckets double
+/// in width whenever there are more than `capacity` of them, so the points held stay spread
+/// evenly over the steps seen, and bounded however long the history is.
+#[derive(Debug)]
+pub(crate) struct StepBuckets {
+    capacity: usize,
+    width: i64,
+    buckets: BTreeMap<i64, Vec<Metric>>,
+}
+
+impl StepBuckets {
+    pub(crate) fn new(capacity: usize) -> StepBuckets {
+        StepBuckets {
+            // negative and positive steps never share a bucket
+            capacity: capacity.max(2),
+            width: 1,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, metric: Metric) {
+        let bucket = self
+            .buckets
+            .entry(metric.step.div_euclid(self.width))
+            .or_default();
+        bucket.push(metric);
+        summarize(bucket);
+        while self.buckets.len() > self.capacity {
+            self.widen();
+        }
+    }
+
+    /// Merges every two neighbouring buckets.
+    fn widen(&mut self) {
+        self.width = self.width.saturating_mul(2);
+        for (index, points) in std::mem::take(&mut self.buckets) {
+            let merged = self.buckets.entry(index.div_euclid(2)).or_default();
+            merged.extend(points);
+            summarize(merged);
+        }
+    }
+
+    /// The points kept, ordered by step.
+    pub(crate) fn into_sorted(self) -> Vec<Metric> {
+        let mut metrics: Vec<Metric> = self.buckets.into_values().flatten().collect();
+        metrics.sort_by_key(|m| (m.step, m.timestamp));
+        metrics
+    }
+}
+
+/// Reduces `points` to their first, last, lowest and highest.
+fn summarize(points: &mut Vec<Metric>) {
+    if points.len() <= POINTS_PER_BUCKET {
+        return;
+    }
+    points.sort_by_key(|m| (m.step, m.timestamp));
+    let by_value = |a: &(usize, &Metric), b: &(usize, &Metric)| a.1.value.total_cmp(&b.1.value);
+    let lowest = points.iter().enumerate().min_by(by_value).map(|(i, _)| i);
+    let highest = points.iter().enumerate().max_by(by_value).map(|(i, _)| i);
+    let mut keep: Vec<usize> = [Some(0), lowest, highest, Some(points.len() - 1)]
+        .into_iter()
+        .flatten()
+        .collect();
+    keep.sort_unstable();
+    keep.dedup();
+    *points = retain(std::mem::take(points), keep);
+}
+
+fn stride(metrics: Vec<Metric>, max_points: usize) -> Vec<Metric> {
+    let last = metrics.len() - 1;
+    let keep: Vec<usize> = match max_points {
+        0 => vec![],
+        1 => vec![last],
+        n => (0..n).map(|i| i * last / (n - 1)).collect(),
+    };
+    retain(metrics, keep)
+}
+
+/// Sveinn Steinarsson's algorithm: the points between the first and last are split into
+/// `max_points - 2` buckets, and each bucket keeps the point forming the largest triangle with
+/// the point kept before it and the average of the next bucket.
+fn lttb(metrics: Vec<Metric>, max_points: usize) -> Vec<Metric> {
+    let point = |m: &Metric| (m.step as f64, m.value);
+    let buckets = max_points - 2;
+    let width = (metrics.len() - 2) as f64 / buckets as f64;
+    let bucket = |b: usize| {
+        let start = 1 + (b as f64 * width) as usize;
+        let end = (1 + ((b + 1) as f64 * width) as usize).min(metrics.len() - 1);
+        start..end.max(start + 1)
+    };
+
+    let mut keep = vec![0];
+    for b in 0..buckets {
+        let next = match b + 1 < buckets {
+            true => bucket(b + 1),
+            false => metrics.len() - 1..metrics.len(),
+        };
+        let n = next.len() as f64;
+        let (avg_x, avg_y) = metrics[next]
+            .iter()
+            .map(point)
+            .fold((0.0, 0.0), |(x, y), (px, py)| (x + px / n, y + py / n));
+        let (ax, ay) = point(&metrics[keep[keep.len() - 1]]);
+
+        let mut best = (bucket(b).start, f64::NEG_INFINITY);
+        for i in bucket(b) {
+            let (bx, by) = point(&metrics[i]);
+            let area = ((ax - avg_x) * (by - ay) - (ax - bx) * (avg_y - ay)).abs();
+            if area > best.1 {
+                best = (i, area);
+            }
+        }
+        keep.push(best.0);
+    }
+    keep.push(metrics.len() - 1);
+    retain(metrics, keep)
+}
+
+/// The points of `metrics` at the ascending indices `keep`.
+fn retain(metrics: Vec<Metric>, keep: Vec<usize>) -> Vec<Metric> {
+    let mut keep = keep.into_iter().peekable();
+    metrics
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, metric)| match keep.peek() == Some(&i) {
+            true => {
+                keep.next();
+                Some(metric)
+            }
+            false => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(values: &[f64]) -> Vec<Metric> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(step, &value)| Metric {
+                key: "loss".to_string(),
+                value,
+                timestamp: step as i64,
+                step: step as i64,
+            })
+            .collect()
+    }
+
+    fn steps(metrics: &[Metric]) -> Vec<i64> {
+        metrics.iter().map(|m| m.step).collect()
+    }
+
+    #[test]
+    fn stride_keeps_evenly_spaced_points() {
+        let metrics = history(&[0.0; 10]);
+        assert_eq!(
+            steps(&Downsampling::Stride.apply(metrics.clone(), 4)),
+            vec![0, 3, 6, 9]
+        );
+        assert_eq!(
+            steps(&Downsampling::Stride.apply(metrics.clone(), 1)),
+            vec![9]
+        );
+        assert!(Downsampling::Stride.apply(metrics.clone(), 0).is_empty());
+        assert_eq!(Downsampling::Stride.apply(metrics, 20).len(), 10);
+    }
+
+    #[test]
+    fn lttb_keeps_spikes() {
+        let mut values = vec![1.0; 100];
+        values[37] = 50.0;
+        values[80] = -20.0;
+        let reduced = Downsampling::Lttb.apply(history(&values), 10);
+        assert_eq!(reduced.len(), 10);
+        let kept = steps(&reduced);
+        assert_eq!((kept[0], kept[9]), (0, 99));
+        assert!(kept.contains(&37) && kept.contains(&80), "{:?}", kept);
+        assert!(!steps(&Downsampling::Stride.apply(history(&values), 10)).contains(&37));
+    }
+
+    #[test]
+    fn step_buckets_stay_spread_over_the_steps() {
+        let mut buckets = StepBuckets::new(16);
+        for metric in history(&[1.0; 1000]) {
+            buckets.push(metric);
+        }
+        let kept = steps(&buckets.into_sorted());
+        assert!(kept.len() <= 16 * POINTS_PER_BUCKET, "{:?}", kept);
+        assert_eq!((kept[0], kept[kept.len() - 1]), (0, 999));
+        // no gap wider than two of the final buckets
+        assert!(kept.windows(2).all(|w| w[1] - w[0] <= 128), "{:?}", kept);
+    }
+}
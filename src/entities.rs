@@ -0,0 +1,389 @@
+//! The entities the tracking server exchanges, mirroring the messages in MLflow's
+//! `service.proto`. Field names match the REST schema so they can be (de)serialized as is.
+
+use crate::run::current_time_millis;
+use serde::{Deserialize, Serialize};
+
+/// Serializes metric values the way the MLflow REST API (protobuf JSON) expects them:
+/// finite values are plain numbers while NaN and the infinities are sent as the strings
+/// `"NaN"`, `"Infinity"` and `"-Infinity"`.
+pub(crate) mod metric_value {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_nan() {
+            serializer.serialize_str("NaN")
+        } else if *value == f64::INFINITY {
+            serializer.serialize_str("Infinity")
+        } else if *value == f64::NEG_INFINITY {
+            serializer.serialize_str("-Infinity")
+        } else {
+            serializer.serialize_f64(*value)
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Number(f64),
+            Text(String),
+        }
+
+        match Value::deserialize(deserializer)? {
+            Value::Number(v) => Ok(v),
+            Value::Text(s) => match s.as_str() {
+                "NaN" => Ok(f64::NAN),
+                "Infinity" => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                other => other.parse::<f64>().map_err(D::Error::custom),
+            },
+        }
+    }
+}
+
+/// Whether a run or experiment is live or has been soft deleted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleStage {
+    Active,
+    Deleted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RunStatus {
+    Running,
+    Scheduled,
+    Finished,
+    Failed,
+    Killed,
+}
+
+impl RunStatus {
+    /// Whether the run has ended, as `FINISHED`, `FAILED` or `KILLED`.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            RunStatus::Finished | RunStatus::Failed | RunStatus::Killed
+        )
+    }
+
+    /// Whether a run may go from this status to `next`: runs never go back to `SCHEDULED`, and
+    /// ended runs can only be reopened as `RUNNING` before ending again. Staying in the same
+    /// status is always allowed.
+    pub fn can_transition_to(self, next: RunStatus) -> bool {
+        match (self, next) {
+            (current, next) if current == next => true,
+            (_, RunStatus::Scheduled) => false,
+            (current, next) if current.is_terminal() => next == RunStatus::Running,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunTag {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for RunTag {
+    fn from((k, v): (&str, &str)) -> Self {
+        RunTag {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+/// A single metric measurement. `timestamp` is in milliseconds since the UNIX epoch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub key: String,
+    #[serde(with = "metric_value")]
+    pub value: f64,
+    #[serde(default)]
+    pub timestamp: i64,
+    #[serde(default)]
+    pub step: i64,
+}
+
+impl Metric {
+    /// Creates a metric, defaulting `step` to `0` and `timestamp` to the current time.
+    pub fn new(
+        key: impl AsRef<str>,
+        value: f64,
+        step: Option<i64>,
+        timestamp: Option<i64>,
+    ) -> Self {
+        Metric {
+            key: key.as_ref().to_string(),
+            value,
+            timestamp: timestamp.unwrap_or_else(current_time_millis),
+            step: step.unwrap_or_default(),
+        }
+    }
+}
+
+/// A key/value hyperparameter logged against a run. Params are immutable once logged.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Param {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for Param {
+    fn from((k, v): (&str, &str)) -> Self {
+        Param {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+/// Metadata describing a run as returned by the tracking server. Fields unknown to this crate are
+/// kept in `extra` rather than rejected.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunInfo {
+    pub run_id: String,
+    #[serde(default)]
+    pub run_name: Option<String>,
+    pub experiment_id: String,
+    pub status: RunStatus,
+    #[serde(default)]
+    pub start_time: Option<i64>,
+    #[serde(default)]
+    pub end_time: Option<i64>,
+    #[serde(default)]
+    pub artifact_uri: Option<String>,
+    #[serde(default)]
+    pub lifecycle_stage: Option<LifecycleStage>,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The metrics (latest value per key), params and tags recorded for a run.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RunData {
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub params: Vec<Param>,
+    #[serde(default)]
+    pub tags: Vec<RunTag>,
+}
+
+impl RunData {
+    pub fn metric(&self, key: impl AsRef<str>) -> Option<f64> {
+        self.metrics
+            .iter()
+            .find(|m| m.key == key.as_ref())
+            .map(|m| m.value)
+    }
+
+    pub fn param(&self, key: impl AsRef<str>) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|p| p.key == key.as_ref())
+            .map(|p| p.value.as_str())
+    }
+
+    pub fn tag(&self, key: impl AsRef<str>) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.key == key.as_ref())
+            .map(|t| t.value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExperimentTag {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for ExperimentTag {
+    fn from((k, v): (&str, &str)) -> Self {
+        ExperimentTag {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+/// The state of a GenAI trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TraceStatus {
+    #[serde(rename = "TRACE_STATUS_UNSPECIFIED")]
+    Unspecified,
+    Ok,
+    Error,
+    InProgress,
+}
+
+/// Metadata describing the request a trace recorded, e.g. previews of its inputs and outputs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceRequestMetadata {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for TraceRequestMetadata {
+    fn from((k, v): (&str, &str)) -> Self {
+        TraceRequestMetadata {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceTag {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for TraceTag {
+    fn from((k, v): (&str, &str)) -> Self {
+        TraceTag {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+/// A trace as stored by the tracking server. The spans themselves are kept as an artifact at
+/// the trace's `mlflow.artifactLocation`. Timestamps are in milliseconds since the UNIX epoch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceInfo {
+    pub request_id: String,
+    pub experiment_id: String,
+    pub timestamp_ms: i64,
+    #[serde(default)]
+    pub execution_time_ms: Option<i64>,
+    pub status: TraceStatus,
+    #[serde(default)]
+    pub request_metadata: Vec<TraceRequestMetadata>,
+    #[serde(default)]
+    pub tags: Vec<TraceTag>,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TraceInfo {
+    pub fn tag(&self, key: impl AsRef<str>) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.key == key.as_ref())
+            .map(|t| t.value.as_str())
+    }
+
+    pub fn request_metadata(&self, key: impl AsRef<str>) -> Option<&str> {
+        self.request_metadata
+            .iter()
+            .find(|m| m.key == key.as_ref())
+            .map(|m| m.value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metric_without_step_from_server_json() {
+        let metric: Metric = serde_json::from_str(r#"{"key": "rmse", "value": 0.5}"#).unwrap();
+        assert_eq!(metric.step, 0);
+        assert_eq!(metric.timestamp, 0);
+    }
+
+    #[test]
+    fn run_data_lookups() {
+        let data: RunData = serde_json::from_str(
+            r#"{
+                "metrics": [{"key": "rmse", "value": 0.5, "timestamp": 0, "step": 3}],
+                "params": [{"key": "lr", "value": "0.01"}],
+                "tags": [{"key": "team", "value": "nlp"}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.metric("rmse"), Some(0.5));
+        assert_eq!(data.param("lr"), Some("0.01"));
+        assert_eq!(data.tag("team"), Some("nlp"));
+        assert_eq!(data.metric("missing"), None);
+    }
+
+    #[test]
+    fn run_status_serializes_to_server_names() {
+        assert_eq!(
+            serde_json::to_string(&RunStatus::Finished).unwrap(),
+            "\"FINISHED\""
+        );
+        let status: RunStatus = serde_json::from_str("\"RUNNING\"").unwrap();
+        assert_eq!(status, RunStatus::Running);
+    }
+
+    #[test]
+    fn run_info_from_server_json() {
+        let json = r#"{
+            "run_id": "abc",
+            "run_uuid": "abc",
+            "run_name": "bold-owl-42",
+            "experiment_id": "1",
+            "user_id": "someone",
+            "status": "RUNNING",
+            "start_time": 1700000000000,
+            "artifact_uri": "mlflow-artifacts:/1/abc/artifacts",
+            "lifecycle_stage": "active"
+        }"#;
+        let info: RunInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.run_id, "abc");
+        assert_eq!(info.status, RunStatus::Running);
+        assert_eq!(info.start_time, Some(1700000000000));
+        assert_eq!(info.end_time, None);
+        assert_eq!(info.lifecycle_stage, Some(LifecycleStage::Active));
+    }
+
+    #[test]
+    fn tuple_to_experiment_tag() {
+        let tag = ExperimentTag::from(("name", "value"));
+        assert_eq!(tag.key, "name");
+        assert_eq!(tag.value, "value");
+    }
+
+    #[test]
+    fn trace_info_from_server_json() {
+        let json = r#"{
+            "request_id": "tr-abc",
+            "experiment_id": "0",
+            "timestamp_ms": 1700000000000,
+            "status": "IN_PROGRESS",
+            "tags": [{"key": "mlflow.artifactLocation", "value": "mlflow-artifacts:/0/traces/tr-abc/artifacts"}]
+        }"#;
+        let info: TraceInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.status, TraceStatus::InProgress);
+        assert_eq!(info.execution_time_ms, None);
+        assert_eq!(
+            info.tag("mlflow.artifactLocation"),
+            Some("mlflow-artifacts:/0/traces/tr-abc/artifacts")
+        );
+        assert_eq!(
+            serde_json::to_string(&TraceStatus::Unspecified).unwrap(),
+            "\"TRACE_STATUS_UNSPECIFIED\""
+        );
+    }
+
+    #[test]
+    fn lifecycle_stage_serializes_to_server_names() {
+        assert_eq!(
+            serde_json::to_string(&LifecycleStage::Deleted).unwrap(),
+            "\"deleted\""
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Per-example evaluation results logged the way `mlflow.evaluate` does: an
+//! `eval_results_table.json` table artifact the UI's evaluation view compares across runs, and
+//! aggregate metrics for every per-example score.
+
+use crate::entities::Metric;
+use crate::run::Run;
+use crate::table::Table;
+use crate::validation::validate_metric_key;
+use crate::MLFlowResult;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The artifact file `mlflow.evaluate` stores per-example results in.
+pub const EVAL_RESULTS_FILE_NAME: &str = "eval_results_table.json";
+
+/// The inputs, prediction, target and scores of one evaluated example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationExample {
+    inputs: Value,
+    prediction: Value,
+    target: Option<Value>,
+    scores: BTreeMap<String, f64>,
+}
+
+impl EvaluationExample {
+    /// An example the model predicted `prediction` for. When `inputs` is a JSON object each of
+    /// its fields becomes a column of the results table, otherwise it is stored in an `inputs`
+    /// column.
+    pub fn new(inputs: impl Into<Value>, prediction: impl Into<Value>) -> EvaluationExample {
+        EvaluationExample {
+            inputs: inputs.into(),
+            prediction: prediction.into(),
+            target: None,
+            scores: BTreeMap::new(),
+        }
+    }
+
+    /// The expected prediction, stored in the `targets` column.
+    pub fn with_target(mut self, target: impl Into<Value>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// A per-example score, stored in the `<name>/score` column and aggregated into the
+    /// `<name>/mean`, `<name>/variance` and `<name>/p90` metrics.
+    pub fn with_score(mut self, name: impl AsRef<str>, score: f64) -> Self {
+        self.scores.insert(name.as_ref().to_string(), score);
+        self
+    }
+}
+
+/// Logs per-example results and aggregate metrics of an evaluation in one call.
+///
+/// ```no_run
+/// use mlflow_rs::{EvaluationExample, EvaluationLogger, RunBuilder};
+///
+/// let run = RunBuilder::new("1").unwrap().build().unwrap();
+/// let metrics = EvaluationLogger::new(&run)
+///     .with_example(
+///         EvaluationExample::new(serde_json::json!({"question": "2 + 2?"}), "4")
+///             .with_target("4")
+///             .with_score("exact_match", 1.0),
+///     )
+///     .with_metric("accuracy", 1.0)
+///     .log()
+///     .unwrap();
+/// println!("{:?}", metrics);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EvaluationLogger<'a> {
+    run: &'a Run,
+    artifact_file: String,
+    examples: Vec<EvaluationExample>,
+    metrics: BTreeMap<String, f64>,
+}
+
+impl<'a> EvaluationLogger<'a> {
+    pub fn new(run: &'a Run) -> EvaluationLogger<'a> {
+        EvaluationLogger {
+            run,
+            artifact_file: EVAL_RESULTS_FILE_NAME.to_string(),
+            examples: vec![],
+            metrics: BTreeMap::new(),
+        }
+    }
+
+    /// Stores the results table in `artifact_file` instead of `eval_results_table.json`.
+    pub fn with_artifact_file(mut self, artifact_file: impl AsRef<str>) -> Self {
+        self.artifact_file = artifact_file.as_ref().to_string();
+        self
+    }
+
+    pub fn with_example(mut self, example: EvaluationExample) -> Self {
+        self.examples.push(example);
+        self
+    }
+
+    pub fn with_examples(mut self, examples: impl IntoIterator<Item = EvaluationExample>) -> Self {
+        self.examples.extend(examples);
+        self
+    }
+
+    /// An aggregate metric computed outside the per-example scores, e.g. an F1 score.
+    pub fn with_metric(mut self, key: impl AsRef<str>, value: f64) -> Self {
+        self.metrics.insert(key.as_ref().to_string(), value);
+        self
+    }
+
+    /// Logs the results table and the metrics, returning every metric that was logged.
+    pub fn log(self) -> MLFlowResult<BTreeMap<String, f64>> {
+        for key in self.metrics.keys() {
+            validate_metric_key(key)?;
+        }
+
+        let metrics = self.aggregate_metrics();
+        if !self.examples.is_empty() {
+            self.run.log_table(&self.table(), &self.artifact_file)?;
+        }
+        self.run.log_batch(
+            metrics
+                .iter()
+                .map(|(key, value)| Metric::new(key, *value, None, None))
+                .collect(),
+            vec![],
+            vec![],
+        )?;
+        Ok(metrics)
+    }
+
+    fn table(&self) -> Table {
+        let mut input_columns: Vec<String> = self
+            .examples
+            .iter()
+            .flat_map(|e| match &e.inputs {
+                Value::Object(inputs) => inputs.keys().cloned().collect(),
+                _ => vec!["inputs".to_string()],
+            })
+            .collect();
+        input_columns.sort();
+        input_columns.dedup();
+        let has_targets = self.examples.iter().any(|e| e.target.is_some());
+        let score_names = self.score_names();
+
+        let mut columns = input_columns.clone();
+        columns.push("outputs".to_string());
+        if has_targets {
+            columns.push("targets".to_string());
+        }
+        columns.extend(score_names.iter().map(|n| format!("{}/score", n)));
+
+        let data = self
+            .examples
+            .iter()
+            .map(|example| {
+                let mut row: Vec<Value> = input_columns
+                    .iter()
+                    .map(|column| match &example.inputs {
+                        Value::Object(inputs) => inputs.get(column).cloned().unwrap_or_default(),
+                        inputs => inputs.clone(),
+                    })
+                    .collect();
+                row.push(example.prediction.clone());
+                if has_targets {
+                    row.push(example.target.clone().unwrap_or_default());
+                }
+                row.extend(
+                    score_names
+                        .iter()
+                        .map(|n| example.scores.get(n).map_or(Value::Null, |s| (*s).into())),
+                );
+                row
+            })
+            .collect();
+
+        Table { columns, data }
+    }
+
+    fn score_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .examples
+            .iter()
+            .flat_map(|e| e.scores.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// The explicit metrics plus the mean, variance and 90th percentile of every score.
+    fn aggregate_metrics(&self) -> BTreeMap<String, f64> {
+        let mut metrics = self.metrics.clone();
+        for name in self.score_names() {
+            let mut scores: Vec<f64> = self
+                .examples
+                .iter()
+                .filter_map(|e| e.scores.get(&name).copied())
+                .filter(|s| !s.is_nan())
+                .collect();
+            if scores.is_empty() {
+                continue;
+            }
+            scores.sort_by(f64::total_cmp);
+
+            let n = scores.len() as f64;
+            let mean = scores.iter().sum::<f64>() / n;
+            let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+            metrics.insert(format!("{}/mean", name), mean);
+            metrics.insert(format!("{}/variance", name), variance);
+            metrics.insert(format!("{}/p90", name), percentile(&scores, 0.9));
+        }
+        metrics
+    }
+}
+
+/// The `q` quantile of sorted `values`, interpolating linearly between ranks like numpy.
+fn percentile(values: &[f64], q: f64) -> f64 {
+    let rank = q * (values.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    values[lower] + (values[upper] - values[lower]) * (rank - lower as f64)
+}
+
+impl Run {
+    /// Logs per-example evaluation results and the aggregates of their scores. Use
+    /// [`EvaluationLogger`] to add aggregate metrics or change the results file.
+    pub fn log_evaluation(
+        &self,
+        examples: impl IntoIterator<Item = EvaluationExample>,
+    ) -> MLFlowResult<BTreeMap<String, f64>> {
+        EvaluationLogger::new(self).with_examples(examples).log()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(percentile(&[1.0], 0.9), 1.0);
+        assert!((percentile(&[0.0, 1.0, 2.0, 3.0, 4.0], 0.9) - 3.6).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn log_evaluation() {
+        use crate::tags::MLFLOW_LOGGED_ARTIFACTS;
+        use crate::{MockMLFlowClient, RunBuilder, RunLoader};
+        use serde_json::json;
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let metrics = EvaluationLogger::new(&run)
+            .with_example(
+                EvaluationExample::new(json!({"question": "a", "context": "x"}), "yes")
+                    .with_target("yes")
+                    .with_score("exact_match", 1.0),
+            )
+            .with_example(
+                EvaluationExample::new(json!({"question": "b"}), "no")
+                    .with_score("exact_match", 0.0)
+                    .with_score("latency", 2.0),
+            )
+            .with_metric("f1", 0.5)
+            .log()
+            .unwrap();
+        assert_eq!(metrics["exact_match/mean"], 0.5);
+        assert_eq!(metrics["exact_match/variance"], 0.25);
+        assert_eq!(metrics["latency/p90"], 2.0);
+
+        let stored = mock
+            .artifact(&run.info().run_id, EVAL_RESULTS_FILE_NAME)
+            .unwrap();
+        let table: Table = serde_json::from_slice(&stored).unwrap();
+        assert_eq!(
+            table.columns,
+            vec![
+                "context",
+                "question",
+                "outputs",
+                "targets",
+                "exact_match/score",
+                "latency/score"
+            ]
+        );
+        assert_eq!(
+            table.data[1],
+            vec![
+                Value::Null,
+                json!("b"),
+                json!("no"),
+                Value::Null,
+                json!(0.0),
+                json!(2.0)
+            ]
+        );
+
+        let run = RunLoader::default()
+            .with_mock_client(mock)
+            .load(&run.info().run_id)
+            .unwrap();
+        assert!(run
+            .data()
+            .tag(MLFLOW_LOGGED_ARTIFACTS)
+            .unwrap()
+            .contains(EVAL_RESULTS_FILE_NAME));
+        assert_eq!(run.data().metric("f1"), Some(0.5));
+    }
+}
@@ -0,0 +1,461 @@
+use crate::active_run::ActiveRun;
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{CreateExperimentRequest, ExperimentInfo, MLFlowClient, SharedClient};
+use crate::config::default_client;
+use crate::entities::{ExperimentTag, LifecycleStage};
+use crate::run::{Run, RunBuilder};
+use crate::search::{RunSearch, RunSearchIter, RunViewType};
+use crate::validation::{
+    validate_artifact_location, validate_experiment_name, validate_experiment_tag,
+};
+use crate::MLFlowError::{ExperimentBuilderError, ResourceAlreadyExists};
+use crate::MLFlowResult;
+use std::sync::Arc;
+
+pub trait ExperimentIdentifier {
+    fn experiment_id(&self) -> Option<String>;
+}
+
+/// An experiment and the client used to manage it. The JSON exchanged with the tracking server is
+/// [`ExperimentInfo`](crate::client::ExperimentInfo).
+///
+/// Experiments are only obtained from the builder, loader and search, which attach the client
+/// they talked to, so operations like [`Experiment::rename`] go to the same server. Clones share
+/// the client; use `with_dyn_client` to manage the experiment through another one.
+#[derive(Clone, Debug)]
+pub struct Experiment {
+    pub(crate) experiment_id: String,
+    pub name: String,
+    pub artifact_location: Option<String>,
+    pub tags: Vec<ExperimentTag>,
+    pub lifecycle_stage: Option<LifecycleStage>,
+    pub(crate) client: SharedClient,
+}
+
+impl Experiment {
+    /// The experiment described by `info`, managed through `client`.
+    pub(crate) fn from_info(info: ExperimentInfo, client: SharedClient) -> Self {
+        Experiment {
+            experiment_id: info.experiment_id,
+            name: info.name,
+            artifact_location: info.artifact_location,
+            tags: info.tags,
+            lifecycle_stage: info.lifecycle_stage,
+            client,
+        }
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.experiment_id
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Manages the experiment through any [`MLFlowClient`](crate::MLFlowClient) implementation
+    /// instead of the client it was loaded with.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Manages the experiment through an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient).
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    pub fn rename(&mut self, new_name: impl AsRef<str>) -> MLFlowResult<()> {
+        if new_name.as_ref().is_empty() {
+            return Err(ExperimentBuilderError("name cannot be empty".to_string()));
+        }
+
+        self.client
+            .update_experiment(self.id(), new_name.as_ref())?;
+        self.name = new_name.as_ref().to_string();
+        Ok(())
+    }
+
+    /// Marks the experiment, and all of its runs, as deleted. Deleted experiments can be
+    /// brought back with [`Experiment::restore`] until the server garbage collects them.
+    pub fn delete(&mut self) -> MLFlowResult<()> {
+        self.client.delete_experiment(self.id())?;
+        self.lifecycle_stage = Some(LifecycleStage::Deleted);
+        Ok(())
+    }
+
+    pub fn restore(&mut self) -> MLFlowResult<()> {
+        self.client.restore_experiment(self.id())?;
+        self.lifecycle_stage = Some(LifecycleStage::Active);
+        Ok(())
+    }
+
+    /// A [`RunBuilder`] for a run in this experiment, sent through the experiment's client.
+    pub fn run_builder(&self) -> RunBuilder {
+        RunBuilder::with_shared_client(self.id(), self.client.clone())
+    }
+
+    /// Creates a run in this experiment. Use [`Experiment::run_builder`] to name or tag it.
+    pub fn create_run(&self) -> MLFlowResult<Run> {
+        self.run_builder().build()
+    }
+
+    /// Creates a run in this experiment wrapped in an [`ActiveRun`] guard that ends it when
+    /// dropped.
+    pub fn start_run(&self) -> MLFlowResult<ActiveRun> {
+        self.run_builder().start()
+    }
+
+    /// Every run of the experiment in `view_type`, fetched page by page as the iterator
+    /// advances. Use [`Experiment::run_search`] to filter or order them.
+    pub fn runs(&self, view_type: RunViewType) -> RunSearchIter {
+        self.run_search().with_run_view_type(view_type).search()
+    }
+
+    /// A [`RunSearch`] over the runs of this experiment, sent through the experiment's client.
+    pub fn run_search(&self) -> RunSearch {
+        RunSearch::with_shared_client(vec![self.experiment_id.clone()], self.client.clone())
+    }
+}
+
+impl ExperimentIdentifier for Experiment {
+    fn experiment_id(&self) -> Option<String> {
+        Some(self.experiment_id.clone())
+    }
+}
+
+impl ExperimentIdentifier for ExperimentInfo {
+    fn experiment_id(&self) -> Option<String> {
+        Some(self.experiment_id.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+
+pub struct ExperimentBuilder {
+    name: String,
+    artifact_location: Option<String>,
+    tags: Vec<ExperimentTag>,
+    client: SharedClient,
+}
+
+impl ExperimentBuilder {
+    pub fn new(name: impl AsRef<str>) -> MLFlowResult<ExperimentBuilder> {
+        if name.as_ref().is_empty() {
+            return Err(ExperimentBuilderError("name cannot be empty".to_string()));
+        }
+
+        Ok(ExperimentBuilder {
+            name: name.as_ref().to_string(),
+            artifact_location: None,
+            tags: vec![],
+            client: default_client()?,
+        })
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<ExperimentTag>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<impl Into<ExperimentTag>>) -> Self {
+        self.tags = tags.into_iter().map(|t| t.into()).collect();
+        self
+    }
+
+    /// Stores the experiment's artifacts under `uri` instead of the server's default artifact
+    /// root, e.g. `s3://bucket/mlruns` or `gs://bucket/mlruns`. The scheme is checked by
+    /// [`build`](ExperimentBuilder::build).
+    pub fn with_artifact_location(mut self, uri: impl AsRef<str>) -> Self {
+        self.artifact_location = Some(uri.as_ref().to_string());
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Creates the experiment. The name, tags and artifact location are validated first, failing
+    /// with a [`ValidationError`](crate::MLFlowError::ValidationError) the server would reject.
+    pub fn build(self) -> MLFlowResult<Experiment> {
+        validate_experiment_name(&self.name)?;
+        if let Some(location) = &self.artifact_location {
+            validate_artifact_location(location)?;
+        }
+        for tag in &self.tags {
+            validate_experiment_tag(tag)?;
+        }
+        let request = CreateExperimentRequest {
+            name: self.name,
+            artifact_location: self.artifact_location,
+            tags: self.tags,
+        };
+        let resp = self.client.create_experiment(&request)?;
+
+        Ok(Experiment {
+            experiment_id: resp.experiment_id,
+            name: request.name,
+            artifact_location: request.artifact_location,
+            tags: request.tags,
+            lifecycle_stage: Some(LifecycleStage::Active),
+            client: self.client,
+        })
+    }
+
+    /// Creates the experiment, or returns the existing experiment with the same name if the
+    /// server reports it already exists. Tags and artifact location are only applied when the
+    /// experiment is created. A deleted experiment still holds its name, and is returned as-is.
+    pub fn get_or_create(self) -> MLFlowResult<Experiment> {
+        let client = self.client.clone();
+        let name = self.name.clone();
+
+        match self.build() {
+            Err(ResourceAlreadyExists(_)) => client
+                .get_experiment_by_name(&name)
+                .map(|resp| Experiment::from_info(resp.experiment, client)),
+            result => result,
+        }
+    }
+}
+
+pub enum ExperimentIdentifierType {
+    ById(String),
+    ByName(String),
+}
+
+#[derive(Default)]
+pub struct ExperimentLoader {
+    client: Option<SharedClient>,
+}
+
+impl ExperimentLoader {
+    #[cfg(feature = "blocking")]
+    pub fn with_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn load(self, experiment_identifier: ExperimentIdentifierType) -> MLFlowResult<Experiment> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+
+        match experiment_identifier {
+            ExperimentIdentifierType::ById(id) => match client.get_experiment_by_id(&id) {
+                Ok(resp) => Ok(Experiment::from_info(resp.experiment, client)),
+                Err(e) => Err(e),
+            },
+            ExperimentIdentifierType::ByName(name) => match client.get_experiment_by_name(&name) {
+                Ok(resp) => Ok(Experiment::from_info(resp.experiment, client)),
+                Err(e) => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_builder_new_empty_name() {
+        ExperimentBuilder::new("").expect_err("ExperimentBuilderError: name cannot be empty");
+    }
+
+    #[test]
+    fn builder_with_tag() {
+        let builder = ExperimentBuilder::new("test_experiment")
+            .unwrap()
+            .with_tag(("key", "value"))
+            .with_tag(("key2", "value2"));
+
+        assert_eq!(builder.name, "test_experiment");
+        assert_eq!(builder.tags.len(), 2);
+    }
+
+    #[test]
+    fn experiment_without_tags_from_server_json() {
+        let json = r#"{
+            "experiment_id": "0",
+            "name": "Default",
+            "artifact_location": "mlflow-artifacts:/0",
+            "lifecycle_stage": "active"
+        }"#;
+        let info: ExperimentInfo = serde_json::from_str(json).unwrap();
+        let experiment = Experiment::from_info(info, SharedClient::default());
+        assert_eq!(experiment.experiment_id(), Some("0".to_string()));
+        assert_eq!(experiment.lifecycle_stage, Some(LifecycleStage::Active));
+        assert!(experiment.tags.is_empty());
+    }
+
+    #[test]
+    fn build_with_tags() {
+        let builder = ExperimentBuilder::new("test_experiment")
+            .unwrap()
+            .with_tags(vec![("key", "value"), ("key2", "value2")]);
+        assert_eq!(builder.name, "test_experiment");
+        assert_eq!(builder.tags.len(), 2);
+    }
+
+    #[test]
+    fn build_validates_tags() {
+        let result = ExperimentBuilder::new("test_experiment")
+            .unwrap()
+            .with_tag(("team name?", "fraud"))
+            .build();
+        assert!(matches!(
+            result,
+            Err(crate::MLFlowError::ValidationError(message)) if message.contains("'team name?'")
+        ));
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn build_with_artifact_location() {
+        let mock = crate::MockMLFlowClient::default();
+        let experiment = ExperimentBuilder::new("s3_experiment")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .with_artifact_location("s3://bucket/mlruns")
+            .build()
+            .unwrap();
+        let loaded = ExperimentLoader::default()
+            .with_mock_client(mock.clone())
+            .load(ExperimentIdentifierType::ById(
+                experiment.experiment_id().unwrap(),
+            ))
+            .unwrap();
+        assert_eq!(
+            loaded.artifact_location.as_deref(),
+            Some("s3://bucket/mlruns")
+        );
+
+        ExperimentBuilder::new("bad_location")
+            .unwrap()
+            .with_mock_client(mock)
+            .with_artifact_location("s3:/bucket")
+            .build()
+            .expect_err("bucket is missing");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn experiments_use_their_client() {
+        let mock = crate::MockMLFlowClient::default();
+        let mut experiment = ExperimentBuilder::new("attached")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        experiment.rename("attached_renamed").unwrap();
+        let loaded = ExperimentLoader::default()
+            .with_mock_client(mock)
+            .load(ExperimentIdentifierType::ById(experiment.id().to_string()))
+            .unwrap();
+        assert_eq!(loaded.name, "attached_renamed");
+
+        let mut detached = experiment.with_mock_client(crate::MockMLFlowClient::default());
+        detached
+            .delete()
+            .expect_err("the other client does not know the experiment");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn experiment_runs() {
+        let mock = crate::MockMLFlowClient::default();
+        let experiment = ExperimentBuilder::new("with_runs")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+
+        let run = experiment.create_run().unwrap();
+        assert_eq!(run.info().experiment_id, experiment.id());
+
+        let named = experiment
+            .run_builder()
+            .with_run_name("named")
+            .build()
+            .unwrap();
+        assert_eq!(named.info().run_name.as_deref(), Some("named"));
+
+        let run_id = {
+            let active = experiment.start_run().unwrap();
+            active.info().run_id.clone()
+        };
+        let ended = crate::RunLoader::default()
+            .with_mock_client(mock)
+            .load(run_id)
+            .unwrap();
+        assert_eq!(ended.status(), crate::RunStatus::Finished);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn experiment_run_listing() {
+        let mock = crate::MockMLFlowClient::default();
+        let experiment = ExperimentBuilder::new("listed")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let other = ExperimentBuilder::new("other")
+            .unwrap()
+            .with_mock_client(mock)
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            experiment.create_run().unwrap();
+        }
+        other.create_run().unwrap();
+        let mut deleted = experiment.create_run().unwrap();
+        deleted.delete().unwrap();
+
+        let active: Vec<Run> = experiment
+            .run_search()
+            .with_max_results(2)
+            .search()
+            .collect::<MLFlowResult<_>>()
+            .unwrap();
+        assert_eq!(active.len(), 3);
+        assert_eq!(experiment.runs(RunViewType::All).count(), 4);
+        assert_eq!(experiment.runs(RunViewType::DeletedOnly).count(), 1);
+    }
+}
@@ -0,0 +1,704 @@
+//! Exporting an experiment to a self-contained directory, and importing it back, in the layout
+//! of [mlflow-export-import](https://github.com/mlflow/mlflow-export-import) so experiments can be
+//! migrated between tracking servers by either tool. [`copy_runs`] copies runs between servers
+//! directly.
+//!
+//! ```text
+//! <path>/experiment.json           the experiment and the ids of its exported runs
+//! <path>/<run_id>/run.json         the run's info, params, tags and full metric histories
+//! <path>/<run_id>/artifacts/...    the run's artifacts
+//! ```
+
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{MLFlowClient, SharedClient};
+use crate::config::default_client;
+use crate::entities::{metric_value, ExperimentTag, LifecycleStage, Metric, Param, RunInfo};
+use crate::entities::{RunStatus, RunTag};
+use crate::experiment::{Experiment, ExperimentBuilder};
+use crate::run::{current_time_millis, Run, RunLoader};
+use crate::search::RunSearch;
+use crate::tags::{parent_run_id, parents_first, MLFLOW_PARENT_RUN_ID};
+use crate::MLFlowError::ExportError;
+use crate::MLFlowResult;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The file describing the exported experiment at the root of an export.
+pub const EXPERIMENT_FILE_NAME: &str = "experiment.json";
+/// The file describing an exported run, in the run's directory.
+pub const RUN_FILE_NAME: &str = "run.json";
+const ARTIFACTS_DIR: &str = "artifacts";
+
+/// The runs an [`ExperimentExporter`] wrote, and those it could not.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExperimentExport {
+    pub run_ids: Vec<String>,
+    /// Runs skipped after failing to export, logged as warnings.
+    pub failed_run_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExperimentFile {
+    #[serde(default)]
+    system: Map<String, Value>,
+    mlflow: ExportedExperiment,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedExperiment {
+    experiment: ExperimentDetails,
+    runs: Vec<String>,
+    #[serde(default)]
+    failed_runs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExperimentDetails {
+    experiment_id: String,
+    name: String,
+    #[serde(default)]
+    artifact_location: Option<String>,
+    #[serde(default)]
+    lifecycle_stage: Option<LifecycleStage>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    #[serde(default)]
+    creation_time: Option<i64>,
+    #[serde(default)]
+    last_update_time: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RunFile {
+    #[serde(default)]
+    system: Map<String, Value>,
+    mlflow: ExportedRun,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedRun {
+    info: RunInfo,
+    #[serde(default)]
+    params: BTreeMap<String, String>,
+    /// Every logged value of each metric, not just the latest.
+    #[serde(default)]
+    metrics: BTreeMap<String, Vec<MetricPoint>>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MetricPoint {
+    #[serde(with = "metric_value")]
+    value: f64,
+    timestamp: i64,
+    step: i64,
+}
+
+/// Writes an experiment's active runs, with their full metric histories and artifacts, to a
+/// directory [`ExperimentImporter`] or mlflow-export-import can import.
+///
+/// ```no_run
+/// use mlflow_rs::ExperimentExporter;
+///
+/// let export = ExperimentExporter::new("1").export("exports/churn").unwrap();
+/// println!("exported {} runs", export.run_ids.len());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExperimentExporter {
+    experiment_id: String,
+    client: Option<SharedClient>,
+}
+
+impl ExperimentExporter {
+    pub fn new(experiment_id: impl AsRef<str>) -> ExperimentExporter {
+        ExperimentExporter {
+            experiment_id: experiment_id.as_ref().to_string(),
+            client: None,
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Exports the experiment into `path`, which is created if needed.
+    pub fn export(self, path: impl AsRef<Path>) -> MLFlowResult<ExperimentExport> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+        let path = path.as_ref();
+        let experiment = client.get_experiment_by_id(&self.experiment_id)?.experiment;
+        create_dir(path)?;
+
+        let mut export = ExperimentExport::default();
+        let runs = RunSearch::with_shared_client(vec![self.experiment_id.clone()], client);
+        for run in runs.search() {
+            let run = run?;
+            let run_id = run.info.run_id.clone();
+            match export_run(&run, &path.join(&run_id)) {
+                Ok(()) => export.run_ids.push(run_id),
+                Err(e) => {
+                    log::warn!("failed to export run {}: {}", run_id, e);
+                    export.failed_run_ids.push(run_id);
+                }
+            }
+        }
+
+        let file = ExperimentFile {
+            system: system(),
+            mlflow: ExportedExperiment {
+                experiment: ExperimentDetails {
+                    experiment_id: experiment.experiment_id,
+                    name: experiment.name,
+                    artifact_location: experiment.artifact_location,
+                    lifecycle_stage: experiment.lifecycle_stage,
+                    tags: experiment
+                        .tags
+                        .into_iter()
+                        .map(|t| (t.key, t.value))
+                        .collect(),
+                    creation_time: experiment.creation_time,
+                    last_update_time: experiment.last_update_time,
+                },
+                runs: export.run_ids.clone(),
+                failed_runs: export.failed_run_ids.clone(),
+            },
+        };
+        write_json(&path.join(EXPERIMENT_FILE_NAME), &file)?;
+        Ok(export)
+    }
+}
+
+fn export_run(run: &Run, dir: &Path) -> MLFlowResult<()> {
+    create_dir(dir)?;
+    let exported = exported_run(run, dir)?;
+    let file = RunFile {
+        system: system(),
+        mlflow: exported,
+    };
+    write_json(&dir.join(RUN_FILE_NAME), &file)
+}
+
+/// Reads a run's params, tags and full metric histories, and downloads its artifacts into the
+/// `artifacts` directory below `dir`.
+fn exported_run(run: &Run, dir: &Path) -> MLFlowResult<ExportedRun> {
+    let mut metrics = BTreeMap::new();
+    for metric in &run.data.metrics {
+        let history = run
+            .get_metric_history(&metric.key)?
+            .into_iter()
+            .map(|m| MetricPoint {
+                value: m.value,
+                timestamp: m.timestamp,
+                step: m.step,
+            })
+            .collect();
+        metrics.insert(metric.key.clone(), history);
+    }
+    if run.info.artifact_uri.is_some() && !run.list_artifacts(None)?.is_empty() {
+        run.download_artifact("", dir.join(ARTIFACTS_DIR))?;
+    }
+
+    Ok(ExportedRun {
+        info: run.info.clone(),
+        params: run
+            .data
+            .params
+            .iter()
+            .map(|p| (p.key.clone(), p.value.clone()))
+            .collect(),
+        metrics,
+        tags: run
+            .data
+            .tags
+            .iter()
+            .map(|t| (t.key.clone(), t.value.clone()))
+            .collect(),
+    })
+}
+
+/// Recreates an experiment written by [`ExperimentExporter`] or mlflow-export-import.
+///
+/// Runs get new ids. They keep their name, start and end times, status, tags, params, full
+/// metric histories and artifacts, and parent runs are remapped to their imported ids.
+///
+/// ```no_run
+/// use mlflow_rs::ExperimentImporter;
+///
+/// let experiment = ExperimentImporter::default()
+///     .with_experiment_name("churn-migrated")
+///     .import("exports/churn")
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ExperimentImporter {
+    experiment_name: Option<String>,
+    client: Option<SharedClient>,
+}
+
+impl ExperimentImporter {
+    /// Creates the experiment under `name` instead of its exported name.
+    pub fn with_experiment_name(mut self, name: impl AsRef<str>) -> Self {
+        self.experiment_name = Some(name.as_ref().to_string());
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Imports the export at `path` into a new experiment, failing with
+    /// `ResourceAlreadyExists` if its name is taken.
+    pub fn import(self, path: impl AsRef<Path>) -> MLFlowResult<Experiment> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+        let path = path.as_ref();
+        let file: ExperimentFile = read_json(&path.join(EXPERIMENT_FILE_NAME))?;
+        let details = file.mlflow.experiment;
+
+        let mut runs = vec![];
+        for run_id in &file.mlflow.runs {
+            let dir = path.join(run_id);
+            let run: RunFile = read_json(&dir.join(RUN_FILE_NAME))?;
+            runs.push((dir, run.mlflow));
+        }
+        let runs = parents_first(
+            runs,
+            |(_, run)| &run.info.run_id,
+            |(_, run)| run.tags.get(MLFLOW_PARENT_RUN_ID).map(String::as_str),
+        );
+
+        let experiment = ExperimentBuilder::new(self.experiment_name.unwrap_or(details.name))?
+            .with_tags(
+                details
+                    .tags
+                    .into_iter()
+                    .map(|(key, value)| ExperimentTag { key, value })
+                    .collect(),
+            )
+            .with_dyn_client(client)
+            .build()?;
+
+        let mut run_ids = HashMap::new();
+        for (dir, run) in runs {
+            let exported_id = run.info.run_id.clone();
+            let imported = import_run(&experiment, &dir, run, &run_ids)?;
+            run_ids.insert(exported_id, imported.info.run_id);
+        }
+        Ok(experiment)
+    }
+}
+
+fn import_run(
+    experiment: &Experiment,
+    dir: &Path,
+    run: ExportedRun,
+    run_ids: &HashMap<String, String>,
+) -> MLFlowResult<Run> {
+    let tags: Vec<RunTag> = run
+        .tags
+        .into_iter()
+        .map(|(key, value)| match key == MLFLOW_PARENT_RUN_ID {
+            true => RunTag {
+                value: run_ids.get(&value).cloned().unwrap_or(value),
+                key,
+            },
+            false => RunTag { key, value },
+        })
+        .collect();
+
+    let mut builder = experiment
+        .run_builder()
+        .with_source_context(false)
+        .with_tags(tags);
+    if let Some(start_time) = run.info.start_time {
+        builder = builder.with_start_time(start_time);
+    }
+    if let Some(run_name) = &run.info.run_name {
+        builder = builder.with_run_name(run_name);
+    }
+    let mut imported = builder.build()?;
+
+    let metrics = run
+        .metrics
+        .into_iter()
+        .flat_map(|(key, history)| {
+            history.into_iter().map(move |m| Metric {
+                key: key.clone(),
+                value: m.value,
+                timestamp: m.timestamp,
+                step: m.step,
+            })
+        })
+        .collect();
+    let params = run
+        .params
+        .into_iter()
+        .map(|(key, value)| Param { key, value })
+        .collect();
+    imported.log_batch(metrics, params, vec![])?;
+    let artifacts = dir.join(ARTIFACTS_DIR);
+    if artifacts.is_dir() {
+        imported.log_artifacts(&artifacts, None)?;
+    }
+    if !matches!(run.info.status, RunStatus::Running | RunStatus::Scheduled) {
+        imported.update(None, Some(run.info.status), run.info.end_time)?;
+    }
+    Ok(imported)
+}
+
+/// Copies runs from the server behind `src_client` to the one behind `dst_client`, replaying
+/// their params, tags, metric histories with the original timestamps and steps, and artifacts.
+///
+/// Each run goes to the destination experiment named like its source experiment, created if
+/// needed. Copied runs get new ids, their name, start and end times and status are kept, and
+/// parents copied in the same call are remapped to their new ids, parents being copied and
+/// returned first. Artifacts are staged in a temporary directory, removed once the run is copied.
+///
+/// ```no_run
+/// use mlflow_rs::{copy_runs, MLFLowRestClient, MLFlowClient};
+/// use std::sync::Arc;
+///
+/// let src: Arc<dyn MLFlowClient> = Arc::new(MLFLowRestClient::new("http://team-a:5000"));
+/// let dst: Arc<dyn MLFlowClient> = Arc::new(MLFLowRestClient::new("http://shared:5000"));
+/// let copies = copy_runs(src, dst, vec!["a1b2c3", "d4e5f6"]).unwrap();
+/// println!("copied {} runs", copies.len());
+/// ```
+pub fn copy_runs(
+    src_client: impl Into<Arc<dyn MLFlowClient>>,
+    dst_client: impl Into<Arc<dyn MLFlowClient>>,
+    run_ids: Vec<impl AsRef<str>>,
+) -> MLFlowResult<Vec<Run>> {
+    let src: Arc<dyn MLFlowClient> = src_client.into();
+    let dst: Arc<dyn MLFlowClient> = dst_client.into();
+
+    let mut runs = vec![];
+    for run_id in &run_ids {
+        let run = RunLoader::default()
+            .with_dyn_client(src.clone())
+            .load(run_id)?;
+        runs.push(run);
+    }
+    let runs = parents_first(
+        runs,
+        |run| &run.info.run_id,
+        |run| parent_run_id(&run.data.tags),
+    );
+
+    let mut experiments: HashMap<String, Experiment> = HashMap::new();
+    let mut run_ids = HashMap::new();
+    let mut copies = vec![];
+    for run in runs {
+        let experiment_id = &run.info.experiment_id;
+        if !experiments.contains_key(experiment_id) {
+            let source = src.get_experiment_by_id(experiment_id)?.experiment;
+            let experiment = ExperimentBuilder::new(&source.name)?
+                .with_tags(source.tags)
+                .with_dyn_client(dst.clone())
+                .get_or_create()?;
+            experiments.insert(experiment_id.clone(), experiment);
+        }
+
+        let staging = std::env::temp_dir().join(format!("mlflow-rs-{}", Uuid::new_v4()));
+        let copy = exported_run(&run, &staging).and_then(|exported| {
+            import_run(&experiments[experiment_id], &staging, exported, &run_ids)
+        });
+        let _ = std::fs::remove_dir_all(&staging);
+        let copy = copy?;
+        run_ids.insert(run.info.run_id, copy.info.run_id.clone());
+        copies.push(copy);
+    }
+    Ok(copies)
+}
+
+/// Copies the run `run_id` from the server behind `src_client` to the one behind `dst_client`.
+/// See [`copy_runs`].
+pub fn copy_run(
+    src_client: impl Into<Arc<dyn MLFlowClient>>,
+    dst_client: impl Into<Arc<dyn MLFlowClient>>,
+    run_id: impl AsRef<str>,
+) -> MLFlowResult<Run> {
+    let mut copies = copy_runs(src_client, dst_client, vec![run_id])?;
+    Ok(copies.remove(0))
+}
+
+/// Describes the exporting tool, as mlflow-export-import does in every file it writes.
+fn system() -> Map<String, Value> {
+    let mut system = Map::new();
+    system.insert("package_version".into(), env!("CARGO_PKG_VERSION").into());
+    system.insert("script".into(), "mlflow-rs".into());
+    system.insert("export_time".into(), (current_time_millis() / 1000).into());
+    system.insert("platform".into(), std::env::consts::OS.into());
+    system
+}
+
+fn create_dir(dir: &Path) -> MLFlowResult<()> {
+    std::fs::create_dir_all(dir).map_err(|e| ExportError(format!("{}: {}", dir.display(), e)))
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> MLFlowResult<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| ExportError(e.to_string()))?;
+    std::fs::write(path, json).map_err(|e| ExportError(format!("{}: {}", path.display(), e)))
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> MLFlowResult<T> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| ExportError(format!("{}: {}", path.display(), e)))?;
+    serde_json::from_str(&json).map_err(|e| ExportError(format!("{}: {}", path.display(), e)))
+}
+
+/// Exports the experiment `experiment_id` into the directory `path`. See
+/// [`ExperimentExporter`].
+pub fn export_experiment(
+    experiment_id: impl AsRef<str>,
+    path: impl AsRef<Path>,
+) -> MLFlowResult<ExperimentExport> {
+    ExperimentExporter::new(experiment_id).export(path)
+}
+
+/// Imports the experiment exported into the directory `path` under its exported name. See
+/// [`ExperimentImporter`].
+pub fn import_experiment(path: impl AsRef<Path>) -> MLFlowResult<Experiment> {
+    ExperimentImporter::default().import(path)
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunLoader};
+
+    #[test]
+    fn exports_and_imports_experiment() {
+        let source = MockMLFlowClient::default();
+        let experiment = ExperimentBuilder::new("churn")
+            .unwrap()
+            .with_tag(ExperimentTag::from(("team", "growth")))
+            .with_mock_client(source.clone())
+            .build()
+            .unwrap();
+        let parent = experiment
+            .run_builder()
+            .with_run_name("sweep")
+            .build()
+            .unwrap();
+        let mut child = experiment
+            .run_builder()
+            .with_parent(&parent.info().run_id)
+            .build()
+            .unwrap();
+        child.log_param("lr", 0.1).unwrap();
+        for step in 0..3 {
+            child
+                .log_metric("loss", 1.0 / (step + 1) as f64, Some(step), None)
+                .unwrap();
+        }
+        child.log_text("weights", "model/weights.bin").unwrap();
+        child.finish(RunStatus::Finished).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let export = ExperimentExporter::new(experiment.id())
+            .with_mock_client(source)
+            .export(dir.path())
+            .unwrap();
+        assert_eq!(export.run_ids.len(), 2);
+        assert!(export.failed_run_ids.is_empty());
+        let child_dir = dir.path().join(&child.info().run_id);
+        assert!(child_dir.join(RUN_FILE_NAME).is_file());
+        assert!(child_dir.join("artifacts/model/weights.bin").is_file());
+
+        let target = MockMLFlowClient::default();
+        let imported = ExperimentImporter::default()
+            .with_mock_client(target.clone())
+            .import(dir.path())
+            .unwrap();
+        assert_eq!(imported.name, "churn");
+        assert!(imported.tags.iter().any(|t| t.key == "team"));
+
+        let runs: Vec<Run> = imported
+            .runs(Default::default())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(runs.len(), 2);
+        let sweep = runs
+            .iter()
+            .find(|r| r.info().run_name.as_deref() == Some("sweep"))
+            .unwrap();
+        let copy = runs
+            .iter()
+            .find(|r| r.info().run_id != sweep.info().run_id)
+            .unwrap();
+        let copy = RunLoader::default()
+            .with_mock_client(target)
+            .load(&copy.info().run_id)
+            .unwrap();
+        assert_eq!(copy.status(), RunStatus::Finished);
+        assert_eq!(copy.end_time(), child.end_time());
+        assert_eq!(copy.get_metric_history("loss").unwrap().len(), 3);
+        assert!(copy
+            .data()
+            .params
+            .iter()
+            .any(|p| p.key == "lr" && p.value == "0.1"));
+        assert!(copy
+            .data()
+            .tags
+            .iter()
+            .any(|t| t.key == MLFLOW_PARENT_RUN_ID && t.value == sweep.info().run_id));
+        assert_eq!(copy.list_artifacts(Some("model")).unwrap().len(), 1);
+
+        ExperimentImporter::default()
+            .with_mock_client(MockMLFlowClient::default())
+            .import(dir.path().join("missing"))
+            .expect_err("no export there");
+    }
+
+    #[test]
+    fn imports_parents_before_their_children() {
+        let source = MockMLFlowClient::default();
+        let experiment = ExperimentBuilder::new("nested")
+            .unwrap()
+            .with_mock_client(source.clone())
+            .build()
+            .unwrap();
+        let parent = experiment.create_run().unwrap();
+        let child = experiment
+            .run_builder()
+            .with_parent(&parent.info().run_id)
+            .with_start_time(parent.start_time().unwrap())
+            .build()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        ExperimentExporter::new(experiment.id())
+            .with_mock_client(source)
+            .export(dir.path())
+            .unwrap();
+        // list the child first, as a server may when both started in the same millisecond
+        let path = dir.path().join(EXPERIMENT_FILE_NAME);
+        let mut file: ExperimentFile = read_json(&path).unwrap();
+        file.mlflow.runs = vec![child.info().run_id.clone(), parent.info().run_id.clone()];
+        write_json(&path, &file).unwrap();
+
+        let target = MockMLFlowClient::default();
+        let imported = ExperimentImporter::default()
+            .with_mock_client(target)
+            .import(dir.path())
+            .unwrap();
+        let runs: Vec<Run> = imported
+            .runs(Default::default())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let imported_parent = runs
+            .iter()
+            .find(|r| parent_run_id(&r.data().tags).is_none())
+            .unwrap();
+        let imported_child = runs
+            .iter()
+            .find(|r| parent_run_id(&r.data().tags).is_some())
+            .unwrap();
+        assert_eq!(
+            parent_run_id(&imported_child.data().tags),
+            Some(imported_parent.info().run_id.as_str())
+        );
+    }
+
+    #[test]
+    fn copies_runs_between_servers() {
+        let source = MockMLFlowClient::default();
+        let experiment = ExperimentBuilder::new("team-a")
+            .unwrap()
+            .with_mock_client(source.clone())
+            .build()
+            .unwrap();
+        let parent = experiment.create_run().unwrap();
+        let child = experiment
+            .run_builder()
+            .with_parent(&parent.info().run_id)
+            .with_start_time(parent.start_time().unwrap())
+            .build()
+            .unwrap();
+        child.log_metric("loss", 0.5, Some(7), Some(1_000)).unwrap();
+        child
+            .log_metric("loss", 0.25, Some(8), Some(2_000))
+            .unwrap();
+        child.log_text("done", "notes.txt").unwrap();
+
+        let src: Arc<dyn MLFlowClient> = Arc::new(source);
+        let dst: Arc<dyn MLFlowClient> = Arc::new(MockMLFlowClient::default());
+        let copies = copy_runs(
+            src.clone(),
+            dst.clone(),
+            vec![&child.info().run_id, &parent.info().run_id],
+        )
+        .unwrap();
+        assert_eq!(copies.len(), 2);
+        let (parent_copy, child_copy) = (&copies[0], &copies[1]);
+        assert_eq!(child_copy.start_time(), child.start_time());
+        let history: Vec<(f64, i64, i64)> = child_copy
+            .get_metric_history("loss")
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.value, m.step, m.timestamp))
+            .collect();
+        assert_eq!(history, vec![(0.5, 7, 1_000), (0.25, 8, 2_000)]);
+        let child_copy = RunLoader::default()
+            .with_dyn_client(dst.clone())
+            .load(&child_copy.info().run_id)
+            .unwrap();
+        assert!(child_copy
+            .data()
+            .tags
+            .iter()
+            .any(|t| t.key == MLFLOW_PARENT_RUN_ID && t.value == parent_copy.info().run_id));
+        assert_eq!(child_copy.list_artifacts(None).unwrap().len(), 1);
+
+        let again = copy_run(src, dst, &parent.info().run_id).unwrap();
+        assert_eq!(
+            again.info().experiment_id,
+            parent_copy.info().experiment_id,
+            "the destination experiment is reused"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Plots logged as image artifacts of a run, e.g. rendered with `plotters`.
+
+use crate::run::Run;
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+
+/// A rendered plot.
+///
+/// `plotters` renders SVG into a string with `SVGBackend::with_string`, and bitmaps into an RGB
+/// buffer with `BitMapBackend::with_buffer`, which [`Figure::from_rgb`] encodes as PNG.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Figure {
+    Png(Vec<u8>),
+    Svg(String),
+}
+
+impl Figure {
+    pub fn svg(svg: impl Into<String>) -> Figure {
+        Figure::Svg(svg.into())
+    }
+
+    /// An already encoded PNG image.
+    pub fn png(png: impl Into<Vec<u8>>) -> Figure {
+        Figure::Png(png.into())
+    }
+
+    /// Encodes a `width` x `height` image of 8 bit RGB pixels, row by row, as PNG.
+    #[cfg(feature = "png")]
+    pub fn from_rgb(width: u32, height: u32, rgb: &[u8]) -> MLFlowResult<Figure> {
+        if rgb.len() != width as usize * height as usize * 3 {
+            return Err(ArtifactError(format!(
+                "a {}x{} RGB image has {} bytes, not {}",
+                width,
+                height,
+                width as usize * height as usize * 3,
+                rgb.len()
+            )));
+        }
+
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(rgb))
+            .map_err(|e| ArtifactError(e.to_string()))?;
+        Ok(Figure::Png(png))
+    }
+
+    /// The file extension the artifact file of the figure must have.
+    fn extension(&self) -> &'static str {
+        match self {
+            Figure::Png(_) => "png",
+            Figure::Svg(_) => "svg",
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Figure::Png(png) => png,
+            Figure::Svg(svg) => svg.as_bytes(),
+        }
+    }
+}
+
+impl Run {
+    /// Uploads `figure` as the artifact file `artifact_file`, e.g. `plots/loss.png`, whose
+    /// extension must match the figure's format so the UI can preview it.
+    pub fn log_figure(&self, figure: &Figure, artifact_file: &str) -> MLFlowResult<()> {
+        let extension = artifact_file
+            .rsplit_once('.')
+            .map(|(_, e)| e.to_ascii_lowercase());
+        if extension.as_deref() != Some(figure.extension()) {
+            return Err(ArtifactError(format!(
+                "artifact file {} of a figure must end with .{}",
+                artifact_file,
+                figure.extension()
+            )));
+        }
+        self.log_bytes(figure.bytes(), artifact_file)
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder};
+
+    fn run(mock: &MockMLFlowClient) -> Run {
+        RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn logs_files() {
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+        let run_id = &run.info().run_id;
+        let config = serde_json::json!({"lr": 0.1, "layers": [64, 32]});
+
+        run.log_text("done", "notes.txt").unwrap();
+        run.log_dict(&config, "config/params.yaml").unwrap();
+        run.log_dict(&config, "config/params.json").unwrap();
+        run.log_json(&[1, 2], "/values.json").unwrap();
+
+        assert_eq!(mock.artifact(run_id, "notes.txt").unwrap(), b"done");
+        let yaml = mock.artifact(run_id, "config/params.yaml").unwrap();
+        let yaml: serde_json::Value = serde_yaml::from_slice(&yaml).unwrap();
+        assert_eq!(yaml, config);
+        let json = mock.artifact(run_id, "config/params.json").unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&json).unwrap(),
+            config
+        );
+        assert!(mock.artifact(run_id, "values.json").is_some());
+        run.log_text("x", "config/")
+            .expect_err("artifact file config/ must not be a directory");
+        run.log_text("x", "")
+            .expect_err("artifact_file cannot be empty");
+    }
+
+    #[test]
+    fn logs_figures() {
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+
+        let svg = Figure::svg("<svg xmlns=\"http://www.w3.org/2000/svg\"/>");
+        run.log_figure(&svg, "plots/loss.svg").unwrap();
+        assert!(mock
+            .artifact(&run.info().run_id, "plots/loss.svg")
+            .is_some());
+        run.log_figure(&svg, "plots/loss.png")
+            .expect_err("the extension must match the format");
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn logs_plotters_bitmaps() {
+        use plotters::prelude::*;
+
+        let mock = MockMLFlowClient::default();
+        let run = run(&mock);
+        let (width, height) = (64, 48);
+        let mut buffer = vec![0u8; width as usize * height as usize * 3];
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            let mut chart = ChartBuilder::on(&root)
+                .build_cartesian_2d(0f32..1f32, 0f32..1f32)
+                .unwrap();
+            chart
+                .draw_series(LineSeries::new([(0.0, 0.0), (1.0, 1.0)], &RED))
+                .unwrap();
+            root.present().unwrap();
+        }
+
+        let figure = Figure::from_rgb(width, height, &buffer).unwrap();
+        run.log_figure(&figure, "plots/line.png").unwrap();
+        let png = mock.artifact(&run.info().run_id, "plots/line.png").unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        Figure::from_rgb(width, height, &buffer[1..]).expect_err("the buffer is too short");
+    }
+}
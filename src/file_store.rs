@@ -0,0 +1,1493 @@
+//! Tracking into a local `mlruns/` directory laid out the way MLflow's own file store lays it
+//! out, so runs can be tracked without any server and browsed with `mlflow ui`.
+
+use crate::artifacts::{artifact_repository, uri_scheme};
+use crate::client::{
+    CreateExperimentRequest, CreateExperimentResponse, CreateModelVersionRequest,
+    CreateRegisteredModelRequest, CreateRunRequest, CreateRunResponse, EndTraceRequest,
+    ExperimentInfo, GetExperimentResponse, GetRunResponse, ListArtifactsResponse, LogMetricRequest,
+    LogParamRequest, MLFlowClient, MetricHistoryResponse, ModelVersionResponse,
+    RegisteredModelResponse, RunResponse, SearchExperimentsRequest, SearchExperimentsResponse,
+    SearchModelVersionsRequest, SearchModelVersionsResponse, SearchRegisteredModelsRequest,
+    SearchRegisteredModelsResponse, SearchRunsRequest, SearchRunsResponse, SharedClient,
+    StartTraceRequest, TraceInfoResponse, UpdateRunRequest, UpdateRunResponse,
+};
+use crate::dataset::{Dataset, DatasetInput, InputTag, RunInputs};
+use crate::entities::{
+    ExperimentTag, LifecycleStage, Metric, Param, RunData, RunInfo, RunStatus, RunTag, TraceTag,
+};
+use crate::experiment::ExperimentBuilder;
+use crate::names::generate_run_name;
+use crate::query::{all_match, experiment_value, matches_view_type, page, parse_filter, run_value};
+use crate::registry::{ModelVersionStage, ModelVersionTag, RegisteredModelTag};
+use crate::run::{current_time_millis, RunBuilder};
+use crate::tags::{
+    parent_run_id, parents_first, MLFLOW_PARENT_RUN_ID, MLFLOW_RUN_NAME, MLFLOW_USER,
+};
+use crate::MLFlowError::{
+    ArtifactError, ExperimentNotFound, FileStoreError, InvalidParameterValue, ParamValueChanged,
+    ResourceAlreadyExists, ResourceDoesNotExist, RunNotFound,
+};
+use crate::{MLFlowError, MLFlowResult};
+use md5::{Digest, Md5};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use url::Url;
+
+/// Tag set on local runs once [`FileStoreClient::sync_to`] uploaded them, holding the id of the
+/// run on the tracking server.
+pub const SYNCED_RUN_ID_TAG: &str = "mlflow-rs.syncedRunId";
+
+const META_FILE_NAME: &str = "meta.yaml";
+const TRASH_DIR: &str = ".trash";
+const DEFAULT_EXPERIMENT_ID: &str = "0";
+const DEFAULT_EXPERIMENT_NAME: &str = "Default";
+
+/// `SourceType.LOCAL`, the only source MLflow still records in run metadata.
+const LOCAL_SOURCE_TYPE: i32 = 4;
+/// `InputVertexType.DATASET` and `InputVertexType.RUN` of a run's input edges.
+const DATASET_VERTEX_TYPE: i32 = 2;
+const RUN_VERTEX_TYPE: i32 = 1;
+
+/// Whether `tracking_uri` names a local directory, a `file:` URI or a plain path, rather than a
+/// tracking server. The bare `databricks` URI has no scheme but names a workspace.
+pub(crate) fn is_file_store_uri(tracking_uri: &str) -> bool {
+    tracking_uri != "databricks"
+        && matches!(uri_scheme(tracking_uri).as_deref(), None | Some("file"))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExperimentMeta {
+    artifact_location: String,
+    #[serde(default)]
+    creation_time: Option<i64>,
+    experiment_id: String,
+    #[serde(default)]
+    last_update_time: Option<i64>,
+    lifecycle_stage: LifecycleStage,
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RunMeta {
+    artifact_uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_time: Option<i64>,
+    #[serde(default)]
+    end_time: Option<i64>,
+    #[serde(default)]
+    entry_point_name: String,
+    experiment_id: String,
+    lifecycle_stage: LifecycleStage,
+    run_id: String,
+    #[serde(default)]
+    run_name: String,
+    run_uuid: String,
+    #[serde(default)]
+    source_name: String,
+    #[serde(default)]
+    source_type: i32,
+    #[serde(default)]
+    source_version: String,
+    #[serde(default)]
+    start_time: Option<i64>,
+    #[serde(with = "status_code")]
+    status: RunStatus,
+    #[serde(default)]
+    tags: Vec<RunTag>,
+    #[serde(default)]
+    user_id: String,
+}
+
+impl RunMeta {
+    fn info(&self) -> RunInfo {
+        RunInfo {
+            run_id: self.run_id.clone(),
+            run_name: (!self.run_name.is_empty()).then(|| self.run_name.clone()),
+            experiment_id: self.experiment_id.clone(),
+            status: self.status,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            artifact_uri: Some(self.artifact_uri.clone()),
+            lifecycle_stage: Some(self.lifecycle_stage),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+/// Run statuses are stored as the integers of MLflow's `RunStatus` proto enum.
+mod status_code {
+    use crate::entities::RunStatus;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(status: &RunStatus, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u8(match status {
+            RunStatus::Running => 1,
+            RunStatus::Scheduled => 2,
+            RunStatus::Finished => 3,
+            RunStatus::Failed => 4,
+            RunStatus::Killed => 5,
+        })
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<RunStatus, D::Error> {
+        match u8::deserialize(d)? {
+            1 => Ok(RunStatus::Running),
+            2 => Ok(RunStatus::Scheduled),
+            3 => Ok(RunStatus::Finished),
+            4 => Ok(RunStatus::Failed),
+            5 => Ok(RunStatus::Killed),
+            other => Err(D::Error::custom(format!("unknown run status {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InputMeta {
+    source_type: i32,
+    source_id: String,
+    destination_type: i32,
+    destination_id: String,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+}
+
+fn store_error(path: &Path, e: impl std::fmt::Display) -> MLFlowError {
+    FileStoreError(format!("{}: {}", path.display(), e))
+}
+
+fn read_yaml<T: DeserializeOwned>(path: &Path) -> MLFlowResult<T> {
+    let contents = std::fs::read_to_string(path).map_err(|e| store_error(path, e))?;
+    serde_yaml::from_str(&contents).map_err(|e| store_error(path, e))
+}
+
+fn write_yaml(path: &Path, value: &impl Serialize) -> MLFlowResult<()> {
+    let contents = serde_yaml::to_string(value).map_err(|e| store_error(path, e))?;
+    write_file(path, &contents)
+}
+
+/// Writes `contents` to `path`, creating its parent directories.
+fn write_file(path: &Path, contents: &str) -> MLFlowResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| store_error(parent, e))?;
+    }
+    std::fs::write(path, contents).map_err(|e| store_error(path, e))
+}
+
+/// The file of the metric, param or tag `key` below `dir`. Keys may contain `/`, which nest
+/// directories as MLflow does, but can't escape `dir`.
+fn key_path(dir: &Path, key: &str) -> MLFlowResult<PathBuf> {
+    let valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || "_-. /".contains(c))
+        && key
+            .split('/')
+            .all(|s| !s.is_empty() && s != "." && s != "..");
+    if !valid {
+        return Err(InvalidParameterValue(format!(
+            "invalid key '{}': keys may only contain alphanumerics, underscores, dashes, \
+             periods, spaces and slashes, and may not be a relative path",
+            key
+        )));
+    }
+    Ok(key
+        .split('/')
+        .fold(dir.to_path_buf(), |path, s| path.join(s)))
+}
+
+/// Every key below `dir` with the path of its file, nested directories joined with `/`.
+fn keys(dir: &Path) -> MLFlowResult<Vec<(String, PathBuf)>> {
+    let mut keys = vec![];
+    let mut pending = vec![(String::new(), dir.to_path_buf())];
+
+    while let Some((prefix, current)) = pending.pop() {
+        let entries = match std::fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(store_error(&current, e)),
+        };
+        for entry in entries {
+            let path = entry.map_err(|e| store_error(&current, e))?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let key = match prefix.is_empty() {
+                true => name.to_string(),
+                false => format!("{}/{}", prefix, name),
+            };
+            match path.is_dir() {
+                true => pending.push((key, path)),
+                false => keys.push((key, path)),
+            }
+        }
+    }
+
+    keys.sort();
+    Ok(keys)
+}
+
+/// The subdirectories of `dir` holding a `meta.yaml`.
+fn entity_dirs(dir: &Path) -> MLFlowResult<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(store_error(dir, e)),
+    };
+    let mut dirs = vec![];
+    for entry in entries {
+        let path = entry.map_err(|e| store_error(dir, e))?.path();
+        if path.join(META_FILE_NAME).is_file() {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+/// Metric files hold one `<timestamp> <value> <step>` line per logged value.
+fn read_metric_history(key: &str, path: &Path) -> MLFlowResult<Vec<Metric>> {
+    let contents = std::fs::read_to_string(path).map_err(|e| store_error(path, e))?;
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let invalid = || store_error(path, format!("invalid metric line '{}'", line));
+            let mut fields = line.split_whitespace();
+            let timestamp = fields
+                .next()
+                .and_then(|f| f.parse().ok())
+                .ok_or_else(invalid)?;
+            let value = fields
+                .next()
+                .and_then(|f| f.parse().ok())
+                .ok_or_else(invalid)?;
+            let step = match fields.next() {
+                Some(step) => step.parse().map_err(|_| invalid())?,
+                None => 0,
+            };
+            Ok(Metric {
+                key: key.to_string(),
+                value,
+                timestamp,
+                step,
+            })
+        })
+        .collect()
+}
+
+fn hex_digest(data: &str) -> String {
+    format!("{:x}", Md5::digest(data.as_bytes()))
+}
+
+/// A [`MLFlowClient`] keeping experiments and runs in a local directory instead of a tracking
+/// server, in the layout of MLflow's file store (`mlflow server --backend-store-uri ./mlruns`).
+///
+/// Runs store their artifacts in their `artifacts` directory. Runs tracked offline can be
+/// uploaded to a tracking server afterwards with [`sync_to`](Self::sync_to). The model
+/// registry and traces need a tracking server and are not supported.
+///
+/// Setting the tracking URI to a `file:` URI or a plain path, e.g. `MLFLOW_TRACKING_URI=./mlruns`,
+/// makes builders and the [`fluent`](crate::fluent) API use a file store.
+///
+/// ```no_run
+/// use mlflow_rs::{FileStoreClient, MLFLowRestClient, MLFlowClient, RunBuilder};
+/// use std::sync::Arc;
+///
+/// let store = FileStoreClient::new("./mlruns").unwrap();
+/// let mut run = RunBuilder::new("0")
+///     .unwrap()
+///     .with_dyn_client(store.clone())
+///     .build()
+///     .unwrap();
+/// run.log_metric("loss", 0.25, Some(1), None).unwrap();
+/// run.finish(mlflow_rs::RunStatus::Finished).unwrap();
+///
+/// // later, with a tracking server at hand
+/// let server: Arc<dyn MLFlowClient> = Arc::new(MLFLowRestClient::new("http://localhost:5000"));
+/// store.sync_to(server).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileStoreClient {
+    root: PathBuf,
+}
+
+impl FileStoreClient {
+    /// A client without a store, the [`Default`] of [`SharedClient`] without the `blocking`
+    /// feature.
+    #[cfg(not(feature = "blocking"))]
+    pub(crate) fn unrooted() -> FileStoreClient {
+        FileStoreClient {
+            root: PathBuf::new(),
+        }
+    }
+
+    /// Tracks into `root`, created together with the `Default` experiment if needed.
+    pub fn new(root: impl AsRef<Path>) -> MLFlowResult<FileStoreClient> {
+        let root = root.as_ref();
+        std::fs::create_dir_all(root).map_err(|e| store_error(root, e))?;
+        let root = root.canonicalize().map_err(|e| store_error(root, e))?;
+        let client = FileStoreClient { root };
+
+        if client.experiment_dir(DEFAULT_EXPERIMENT_ID).is_err() {
+            client.write_experiment(DEFAULT_EXPERIMENT_ID, DEFAULT_EXPERIMENT_NAME, None)?;
+        }
+        Ok(client)
+    }
+
+    /// Tracks into the directory of a `file:` tracking URI or plain path, e.g. `file:./mlruns`
+    /// or `/data/mlruns`.
+    pub fn from_tracking_uri(tracking_uri: impl AsRef<str>) -> MLFlowResult<FileStoreClient> {
+        let tracking_uri = tracking_uri.as_ref();
+        let invalid = || FileStoreError(format!("invalid file store URI {}", tracking_uri));
+        match uri_scheme(tracking_uri).as_deref() {
+            None => FileStoreClient::new(tracking_uri),
+            Some("file") if tracking_uri.starts_with("file://") => {
+                let url = Url::parse(tracking_uri).map_err(|_| invalid())?;
+                FileStoreClient::new(url.to_file_path().map_err(|_| invalid())?)
+            }
+            // file:relative/path and file:/absolute/path
+            Some("file") => FileStoreClient::new(&tracking_uri["file:".len()..]),
+            Some(_) => Err(invalid()),
+        }
+    }
+
+    /// The absolute path of the store's directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Uploads the runs that finished since the last sync to `client`, returning how many were
+    /// uploaded. Experiments are matched by name and created where missing.
+    ///
+    /// Each run is recreated with its start time, name, tags, params, full metric history,
+    /// datasets and artifacts, then terminated with its local status and end time. Uploaded
+    /// runs are tagged locally with [`SYNCED_RUN_ID_TAG`], so syncing again skips them. Runs
+    /// still running and deleted runs are skipped.
+    pub fn sync_to(&self, client: impl Into<Arc<dyn MLFlowClient>>) -> MLFlowResult<usize> {
+        let client: Arc<dyn MLFlowClient> = client.into();
+        let mut synced = 0;
+        let mut run_ids: HashMap<String, String> = HashMap::new();
+
+        for experiment_dir in self.experiment_dirs()? {
+            let experiment = self.read_experiment(&experiment_dir)?;
+            if experiment.lifecycle_stage == Some(LifecycleStage::Deleted) {
+                continue;
+            }
+
+            let mut runs = vec![];
+            for run_dir in entity_dirs(&experiment_dir)? {
+                let run = self.read_run(&run_dir)?;
+                let finished =
+                    !matches!(run.info.status, RunStatus::Running | RunStatus::Scheduled);
+                let active = run.info.lifecycle_stage != Some(LifecycleStage::Deleted);
+                let unsynced = !run.data.tags.iter().any(|t| t.key == SYNCED_RUN_ID_TAG);
+                if finished && active && unsynced {
+                    runs.push((run_dir, run));
+                }
+            }
+            if runs.is_empty() {
+                continue;
+            }
+            let runs = parents_first(
+                runs,
+                |(_, run)| &run.info.run_id,
+                |(_, run)| parent_run_id(&run.data.tags),
+            );
+
+            let target = ExperimentBuilder::new(&experiment.name)?
+                .with_tags(experiment.tags.clone())
+                .with_dyn_client(client.clone())
+                .get_or_create()?;
+            let target_id = target.experiment_id;
+
+            for (run_dir, run) in runs {
+                let remote = self.sync_run(&run_dir, run, &target_id, &client, &run_ids)?;
+                run_ids.insert(remote.0, remote.1);
+                synced += 1;
+            }
+        }
+        Ok(synced)
+    }
+
+    /// Uploads one run, returning its local and remote ids.
+    fn sync_run(
+        &self,
+        run_dir: &Path,
+        run: RunResponse,
+        experiment_id: &str,
+        client: &Arc<dyn MLFlowClient>,
+        run_ids: &HashMap<String, String>,
+    ) -> MLFlowResult<(String, String)> {
+        let local_id = run.info.run_id.clone();
+        let tags: Vec<RunTag> = run
+            .data
+            .tags
+            .into_iter()
+            .map(|mut tag| {
+                if tag.key == MLFLOW_PARENT_RUN_ID {
+                    if let Some(parent) = run_ids.get(&tag.value) {
+                        tag.value = parent.clone();
+                    } else if let Ok(Some(parent)) = self.synced_run_id(&tag.value) {
+                        tag.value = parent;
+                    }
+                }
+                tag
+            })
+            .collect();
+
+        let mut builder = RunBuilder::new(experiment_id)?
+            .with_dyn_client(client.clone())
+            .with_source_context(false)
+            .with_tags(tags);
+        if let Some(start_time) = run.info.start_time {
+            builder = builder.with_start_time(start_time);
+        }
+        if let Some(run_name) = &run.info.run_name {
+            builder = builder.with_run_name(run_name);
+        }
+        let mut remote = builder.build()?;
+
+        let mut history = vec![];
+        for (key, path) in keys(&run_dir.join("metrics"))? {
+            history.extend(read_metric_history(&key, &path)?);
+        }
+        remote.log_batch(history, run.data.params, vec![])?;
+        remote.log_inputs(run.inputs.dataset_inputs)?;
+        let artifacts = run_dir.join("artifacts");
+        if artifacts.is_dir() {
+            remote.log_artifacts(&artifacts, None)?;
+        }
+        remote.update(None, Some(run.info.status), run.info.end_time)?;
+
+        let remote_id = remote.info().run_id.clone();
+        self.set_tag(
+            &local_id,
+            &RunTag::from((SYNCED_RUN_ID_TAG, remote_id.as_str())),
+        )?;
+        Ok((local_id, remote_id))
+    }
+
+    fn synced_run_id(&self, run_id: &str) -> MLFlowResult<Option<String>> {
+        let path = key_path(&self.run_dir(run_id)?.join("tags"), SYNCED_RUN_ID_TAG)?;
+        Ok(std::fs::read_to_string(path).ok())
+    }
+
+    /// The directories of every experiment, deleted experiments included.
+    fn experiment_dirs(&self) -> MLFlowResult<Vec<PathBuf>> {
+        let mut dirs = entity_dirs(&self.root)?;
+        dirs.extend(entity_dirs(&self.root.join(TRASH_DIR))?);
+        dirs.retain(|d| {
+            let name = d.file_name().unwrap_or_default().to_string_lossy();
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_digit())
+        });
+        dirs.sort_by_key(|d| {
+            let name = d.file_name().unwrap_or_default().to_string_lossy();
+            name.parse::<u64>().unwrap_or_default()
+        });
+        Ok(dirs)
+    }
+
+    fn experiment_dir(&self, experiment_id: &str) -> MLFlowResult<PathBuf> {
+        let not_found =
+            || ExperimentNotFound(format!("experiment {} was not found", experiment_id));
+        if experiment_id.is_empty() || !experiment_id.chars().all(|c| c.is_ascii_digit()) {
+            return Err(not_found());
+        }
+        [self.root.clone(), self.root.join(TRASH_DIR)]
+            .into_iter()
+            .map(|dir| dir.join(experiment_id))
+            .find(|dir| dir.join(META_FILE_NAME).is_file())
+            .ok_or_else(not_found)
+    }
+
+    fn read_experiment(&self, dir: &Path) -> MLFlowResult<ExperimentInfo> {
+        let meta: ExperimentMeta = read_yaml(&dir.join(META_FILE_NAME))?;
+        let mut tags = vec![];
+        for (key, path) in keys(&dir.join("tags"))? {
+            let value = std::fs::read_to_string(&path).map_err(|e| store_error(&path, e))?;
+            tags.push(ExperimentTag { key, value });
+        }
+        Ok(ExperimentInfo {
+            experiment_id: meta.experiment_id,
+            name: meta.name,
+            artifact_location: Some(meta.artifact_location),
+            tags,
+            lifecycle_stage: Some(meta.lifecycle_stage),
+            creation_time: meta.creation_time,
+            last_update_time: meta.last_update_time,
+            extra: serde_json::Map::new(),
+        })
+    }
+
+    /// Creates the experiment `id`, failing with `ResourceAlreadyExists` if another process
+    /// took the id first.
+    fn write_experiment(
+        &self,
+        id: &str,
+        name: &str,
+        artifact_location: Option<String>,
+    ) -> MLFlowResult<PathBuf> {
+        let dir = self.root.join(id);
+        match std::fs::create_dir(&dir) {
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                return Err(ResourceAlreadyExists(format!(
+                    "experiment {} already exists",
+                    id
+                )))
+            }
+            result => result.map_err(|e| store_error(&dir, e))?,
+        }
+        let artifact_location = match artifact_location {
+            Some(location) => location,
+            None => Url::from_file_path(&dir)
+                .map_err(|_| store_error(&dir, "not an absolute path"))?
+                .to_string(),
+        };
+        let now = current_time_millis();
+        let meta = ExperimentMeta {
+            artifact_location,
+            creation_time: Some(now),
+            experiment_id: id.to_string(),
+            last_update_time: Some(now),
+            lifecycle_stage: LifecycleStage::Active,
+            name: name.to_string(),
+        };
+        write_yaml(&dir.join(META_FILE_NAME), &meta)?;
+        Ok(dir)
+    }
+
+    fn update_experiment_meta(
+        &self,
+        experiment_id: &str,
+        update: impl FnOnce(&mut ExperimentMeta),
+    ) -> MLFlowResult<PathBuf> {
+        let dir = self.experiment_dir(experiment_id)?;
+        let path = dir.join(META_FILE_NAME);
+        let mut meta: ExperimentMeta = read_yaml(&path)?;
+        update(&mut meta);
+        meta.last_update_time = Some(current_time_millis());
+        write_yaml(&path, &meta)?;
+        Ok(dir)
+    }
+
+    fn run_dir(&self, run_id: &str) -> MLFlowResult<PathBuf> {
+        let not_found = || RunNotFound(format!("run {} was not found", run_id));
+        if run_id.is_empty() || !run_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(not_found());
+        }
+        self.experiment_dirs()?
+            .into_iter()
+            .map(|dir| dir.join(run_id))
+            .find(|dir| dir.join(META_FILE_NAME).is_file())
+            .ok_or_else(not_found)
+    }
+
+    /// The directory of a run that may still be logged to.
+    fn active_run_dir(&self, run_id: &str) -> MLFlowResult<PathBuf> {
+        let dir = self.run_dir(run_id)?;
+        let meta: RunMeta = read_yaml(&dir.join(META_FILE_NAME))?;
+        if meta.lifecycle_stage != LifecycleStage::Active {
+            return Err(InvalidParameterValue(format!(
+                "The run {} must be in the 'active' state. Current state is {:?}.",
+                run_id, meta.lifecycle_stage
+            )));
+        }
+        Ok(dir)
+    }
+
+    fn update_run_meta(
+        &self,
+        run_id: &str,
+        update: impl FnOnce(&mut RunMeta),
+    ) -> MLFlowResult<RunMeta> {
+        let path = self.run_dir(run_id)?.join(META_FILE_NAME);
+        let mut meta: RunMeta = read_yaml(&path)?;
+        update(&mut meta);
+        write_yaml(&path, &meta)?;
+        Ok(meta)
+    }
+
+    /// Removes a run's directory, and its artifacts when they are stored elsewhere.
+    fn remove_run(&self, dir: &Path) -> MLFlowResult<()> {
+        let meta: RunMeta = read_yaml(&dir.join(META_FILE_NAME))?;
+        let artifacts =
+            artifact_repository(&meta.artifact_uri, &self.clone().into(), Some(&meta.run_id))
+                .and_then(|repository| repository.delete_artifacts(""));
+        if let Err(e) = artifacts {
+            log::warn!(
+                "could not delete the artifacts of run {}: {}",
+                meta.run_id,
+                e
+            );
+        }
+        std::fs::remove_dir_all(dir).map_err(|e| store_error(dir, e))
+    }
+
+    fn read_run(&self, dir: &Path) -> MLFlowResult<RunResponse> {
+        let meta: RunMeta = read_yaml(&dir.join(META_FILE_NAME))?;
+        let mut data = RunData::default();
+
+        for (key, path) in keys(&dir.join("metrics"))? {
+            let latest = read_metric_history(&key, &path)?
+                .into_iter()
+                .max_by_key(|m| (m.step, m.timestamp));
+            data.metrics.extend(latest);
+        }
+        for (key, path) in keys(&dir.join("params"))? {
+            let value = std::fs::read_to_string(&path).map_err(|e| store_error(&path, e))?;
+            data.params.push(Param { key, value });
+        }
+        for (key, path) in keys(&dir.join("tags"))? {
+            let value = std::fs::read_to_string(&path).map_err(|e| store_error(&path, e))?;
+            data.tags.push(RunTag { key, value });
+        }
+
+        let datasets = dir
+            .parent()
+            .map(|experiment| experiment.join("datasets"))
+            .unwrap_or_default();
+        let mut inputs = RunInputs::default();
+        for input_dir in entity_dirs(&dir.join("inputs"))? {
+            let input: InputMeta = read_yaml(&input_dir.join(META_FILE_NAME))?;
+            let dataset: Dataset =
+                read_yaml(&datasets.join(&input.source_id).join(META_FILE_NAME))?;
+            inputs.dataset_inputs.push(DatasetInput {
+                tags: input
+                    .tags
+                    .into_iter()
+                    .map(|(key, value)| InputTag { key, value })
+                    .collect(),
+                dataset,
+            });
+        }
+
+        Ok(RunResponse {
+            info: meta.info(),
+            data,
+            inputs,
+        })
+    }
+
+    fn log_metric_to(&self, dir: &Path, metric: &Metric) -> MLFlowResult<()> {
+        let path = key_path(&dir.join("metrics"), &metric.key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| store_error(parent, e))?;
+        }
+        let value = match metric.value.is_nan() {
+            true => "nan".to_string(),
+            false => metric.value.to_string(),
+        };
+        let line = format!("{} {} {}\n", metric.timestamp, value, metric.step);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| store_error(&path, e))
+    }
+
+    fn log_param_to(&self, dir: &Path, run_id: &str, param: &Param) -> MLFlowResult<()> {
+        let path = key_path(&dir.join("params"), &param.key)?;
+        match std::fs::read_to_string(&path) {
+            Ok(existing) if existing != param.value => Err(ParamValueChanged(format!(
+                "Changing param values is not allowed. Param with key='{}' was already logged \
+                 with value='{}' for run ID='{}'. Attempted logging new value '{}'.",
+                param.key, existing, run_id, param.value
+            ))),
+            Ok(_) => Ok(()),
+            Err(_) => write_file(&path, &param.value),
+        }
+    }
+
+    fn set_tag_to(&self, dir: &Path, run_id: &str, tag: &RunTag) -> MLFlowResult<()> {
+        write_file(&key_path(&dir.join("tags"), &tag.key)?, &tag.value)?;
+        if tag.key == MLFLOW_RUN_NAME {
+            self.update_run_meta(run_id, |meta| meta.run_name = tag.value.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl From<FileStoreClient> for SharedClient {
+    fn from(client: FileStoreClient) -> Self {
+        SharedClient::new(client)
+    }
+}
+
+impl From<FileStoreClient> for Arc<dyn MLFlowClient> {
+    fn from(client: FileStoreClient) -> Self {
+        Arc::new(client)
+    }
+}
+
+fn unsupported<T>(feature: &str) -> MLFlowResult<T> {
+    Err(FileStoreError(format!(
+        "{} requires a tracking server and is not supported by the file store",
+        feature
+    )))
+}
+
+impl MLFlowClient for FileStoreClient {
+    fn create_experiment(
+        &self,
+        request: &CreateExperimentRequest,
+    ) -> MLFlowResult<CreateExperimentResponse> {
+        let mut next_id = 0;
+        for dir in self.experiment_dirs()? {
+            let existing = self.read_experiment(&dir)?;
+            if existing.name == request.name {
+                return Err(ResourceAlreadyExists(format!(
+                    "Experiment '{}' already exists.",
+                    request.name
+                )));
+            }
+            let id: u64 = existing.experiment_id.parse().unwrap_or_default();
+            next_id = next_id.max(id + 1);
+        }
+
+        // concurrent processes may race for the next id
+        let dir = loop {
+            match self.write_experiment(
+                &next_id.to_string(),
+                &request.name,
+                request.artifact_location.clone(),
+            ) {
+                Err(ResourceAlreadyExists(_)) => next_id += 1,
+                result => break result?,
+            }
+        };
+        for tag in &request.tags {
+            write_file(&key_path(&dir.join("tags"), &tag.key)?, &tag.value)?;
+        }
+        Ok(CreateExperimentResponse {
+            experiment_id: next_id.to_string(),
+        })
+    }
+
+    fn get_experiment_by_id(&self, id: &str) -> MLFlowResult<GetExperimentResponse> {
+        let experiment = self.read_experiment(&self.experiment_dir(id)?)?;
+        Ok(GetExperimentResponse { experiment })
+    }
+
+    fn get_experiment_by_name(&self, name: &str) -> MLFlowResult<GetExperimentResponse> {
+        for dir in self.experiment_dirs()? {
+            let experiment = self.read_experiment(&dir)?;
+            if experiment.name == name {
+                return Ok(GetExperimentResponse { experiment });
+            }
+        }
+        Err(ExperimentNotFound("experiment was not found".to_string()))
+    }
+
+    fn search_experiments(
+        &self,
+        request: &SearchExperimentsRequest,
+    ) -> MLFlowResult<SearchExperimentsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut experiments = vec![];
+        for dir in self.experiment_dirs()? {
+            let experiment = self.read_experiment(&dir)?;
+            if !matches_view_type(experiment.lifecycle_stage, request.view_type) {
+                continue;
+            }
+            if all_match(&clauses, |clause| experiment_value(&experiment, clause))? {
+                experiments.push(experiment);
+            }
+        }
+
+        let (experiments, next_page_token) = page(
+            experiments,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchExperimentsResponse {
+            experiments,
+            next_page_token,
+        })
+    }
+
+    fn update_experiment(&self, experiment_id: &str, new_name: &str) -> MLFlowResult<()> {
+        if let Ok(existing) = self.get_experiment_by_name(new_name) {
+            if existing.experiment.experiment_id != experiment_id {
+                return Err(ResourceAlreadyExists(format!(
+                    "Experiment '{}' already exists.",
+                    new_name
+                )));
+            }
+        }
+        self.update_experiment_meta(experiment_id, |meta| meta.name = new_name.to_string())
+            .map(|_| ())
+    }
+
+    fn delete_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        if experiment_id == DEFAULT_EXPERIMENT_ID {
+            return Err(InvalidParameterValue(
+                "Cannot delete the default experiment '0'".to_string(),
+            ));
+        }
+        let dir = self.update_experiment_meta(experiment_id, |meta| {
+            meta.lifecycle_stage = LifecycleStage::Deleted
+        })?;
+        let trash = self.root.join(TRASH_DIR);
+        std::fs::create_dir_all(&trash).map_err(|e| store_error(&trash, e))?;
+        std::fs::rename(&dir, trash.join(experiment_id)).map_err(|e| store_error(&dir, e))
+    }
+
+    fn restore_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        let dir = self.update_experiment_meta(experiment_id, |meta| {
+            meta.lifecycle_stage = LifecycleStage::Active
+        })?;
+        let restored = self.root.join(experiment_id);
+        if dir != restored {
+            std::fs::rename(&dir, &restored).map_err(|e| store_error(&dir, e))?;
+        }
+        Ok(())
+    }
+
+    fn create_run(&self, request: CreateRunRequest) -> MLFlowResult<CreateRunResponse> {
+        let experiment_dir = self.experiment_dir(&request.experiment_id)?;
+        let experiment: ExperimentMeta = read_yaml(&experiment_dir.join(META_FILE_NAME))?;
+        if experiment.lifecycle_stage != LifecycleStage::Active {
+            return Err(InvalidParameterValue(format!(
+                "Could not create run under non-active experiment with ID {}.",
+                request.experiment_id
+            )));
+        }
+
+        let run_id = uuid::Uuid::new_v4().simple().to_string();
+        let tag = |key: &str| {
+            request
+                .tags
+                .iter()
+                .rev()
+                .find(|t| t.key == key)
+                .map(|t| t.value.clone())
+        };
+        let run_name = request
+            .run_name
+            .clone()
+            .or_else(|| tag(MLFLOW_RUN_NAME))
+            .unwrap_or_else(generate_run_name);
+        let meta = RunMeta {
+            artifact_uri: format!(
+                "{}/{}/artifacts",
+                experiment.artifact_location.trim_end_matches('/'),
+                run_id
+            ),
+            end_time: None,
+            entry_point_name: String::new(),
+            deleted_time: None,
+            experiment_id: request.experiment_id.clone(),
+            lifecycle_stage: LifecycleStage::Active,
+            run_id: run_id.clone(),
+            run_name: run_name.clone(),
+            run_uuid: run_id.clone(),
+            source_name: String::new(),
+            source_type: LOCAL_SOURCE_TYPE,
+            source_version: String::new(),
+            start_time: Some(request.start_time),
+            status: RunStatus::Running,
+            tags: vec![],
+            user_id: tag(MLFLOW_USER).unwrap_or_default(),
+        };
+
+        let dir = experiment_dir.join(&run_id);
+        for subdir in ["metrics", "params", "tags"] {
+            let path = dir.join(subdir);
+            std::fs::create_dir_all(&path).map_err(|e| store_error(&path, e))?;
+        }
+        write_yaml(&dir.join(META_FILE_NAME), &meta)?;
+        for tag in request
+            .tags
+            .iter()
+            .filter(|t| t.key != MLFLOW_RUN_NAME)
+            .chain([&RunTag::from((MLFLOW_RUN_NAME, run_name.as_str()))])
+        {
+            write_file(&key_path(&dir.join("tags"), &tag.key)?, &tag.value)?;
+        }
+
+        Ok(CreateRunResponse {
+            run: self.read_run(&dir)?,
+        })
+    }
+
+    fn get_run(&self, run_id: &str) -> MLFlowResult<GetRunResponse> {
+        Ok(GetRunResponse {
+            run: self.read_run(&self.run_dir(run_id)?)?,
+        })
+    }
+
+    fn update_run(&self, request: UpdateRunRequest) -> MLFlowResult<UpdateRunResponse> {
+        let dir = self.active_run_dir(&request.run_id)?;
+        if let Some(run_name) = &request.run_name {
+            let tag = RunTag::from((MLFLOW_RUN_NAME, run_name.as_str()));
+            self.set_tag_to(&dir, &request.run_id, &tag)?;
+        }
+        let meta = self.update_run_meta(&request.run_id, |meta| {
+            if let Some(status) = request.status {
+                meta.status = status;
+            }
+            if request.end_time.is_some() {
+                meta.end_time = request.end_time;
+            }
+        })?;
+        Ok(UpdateRunResponse {
+            run_info: meta.info(),
+        })
+    }
+
+    fn delete_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.update_run_meta(run_id, |meta| {
+            meta.lifecycle_stage = LifecycleStage::Deleted;
+            meta.deleted_time = Some(current_time_millis());
+        })
+        .map(|_| ())
+    }
+
+    fn restore_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.update_run_meta(run_id, |meta| {
+            meta.lifecycle_stage = LifecycleStage::Active;
+            meta.deleted_time = None;
+        })
+        .map(|_| ())
+    }
+
+    fn deleted_runs(&self, older_than_ms: i64) -> MLFlowResult<Vec<RunInfo>> {
+        let now = current_time_millis();
+        let mut runs = vec![];
+        for experiment_dir in self.experiment_dirs()? {
+            for dir in entity_dirs(&experiment_dir)? {
+                let path = dir.join(META_FILE_NAME);
+                if !path.is_file() {
+                    continue;
+                }
+                let meta: RunMeta = read_yaml(&path)?;
+                let old_enough = older_than_ms == 0
+                    || meta.deleted_time.is_some_and(|t| now - t >= older_than_ms);
+                if meta.lifecycle_stage == LifecycleStage::Deleted && old_enough {
+                    runs.push(meta.info());
+                }
+            }
+        }
+        Ok(runs)
+    }
+
+    fn purge_run(&self, run_id: &str) -> MLFlowResult<()> {
+        let dir = self.run_dir(run_id)?;
+        let meta: RunMeta = read_yaml(&dir.join(META_FILE_NAME))?;
+        if meta.lifecycle_stage != LifecycleStage::Deleted {
+            return Err(InvalidParameterValue(format!(
+                "run {} must be deleted before it is purged",
+                run_id
+            )));
+        }
+        self.remove_run(&dir)
+    }
+
+    fn purge_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        let dir = self.experiment_dir(experiment_id)?;
+        let meta: ExperimentMeta = read_yaml(&dir.join(META_FILE_NAME))?;
+        if meta.lifecycle_stage != LifecycleStage::Deleted {
+            return Err(InvalidParameterValue(format!(
+                "experiment {} must be deleted before it is purged",
+                experiment_id
+            )));
+        }
+        for run_dir in entity_dirs(&dir)? {
+            if run_dir.join(META_FILE_NAME).is_file() {
+                self.remove_run(&run_dir)?;
+            }
+        }
+        std::fs::remove_dir_all(&dir).map_err(|e| store_error(&dir, e))
+    }
+
+    fn search_runs(&self, request: &SearchRunsRequest) -> MLFlowResult<SearchRunsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut runs = vec![];
+        for experiment_id in &request.experiment_ids {
+            let Ok(experiment_dir) = self.experiment_dir(experiment_id) else {
+                continue;
+            };
+            for dir in entity_dirs(&experiment_dir)? {
+                let run = self.read_run(&dir)?;
+                if !matches_view_type(run.info.lifecycle_stage, request.run_view_type) {
+                    continue;
+                }
+                if all_match(&clauses, |clause| run_value(&run.info, &run.data, clause))? {
+                    runs.push(run);
+                }
+            }
+        }
+        runs.sort_by_key(|r| std::cmp::Reverse(r.info.start_time));
+
+        let (runs, next_page_token) = page(
+            runs,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchRunsResponse {
+            runs,
+            next_page_token,
+        })
+    }
+
+    fn log_metric(&self, request: LogMetricRequest) -> MLFlowResult<()> {
+        let dir = self.active_run_dir(&request.run_id)?;
+        self.log_metric_to(&dir, &request.metric)
+    }
+
+    fn log_param(&self, request: LogParamRequest) -> MLFlowResult<()> {
+        let dir = self.active_run_dir(&request.run_id)?;
+        self.log_param_to(&dir, &request.run_id, &request.param)
+    }
+
+    fn get_metric_history(
+        &self,
+        run_id: &str,
+        key: &str,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<MetricHistoryResponse> {
+        let path = key_path(&self.run_dir(run_id)?.join("metrics"), key)?;
+        let history = match path.is_file() {
+            true => read_metric_history(key, &path)?,
+            false => vec![],
+        };
+        let (metrics, next_page_token) = page(history, None, page_token)?;
+        Ok(MetricHistoryResponse {
+            metrics,
+            next_page_token,
+        })
+    }
+
+    fn set_tag(&self, run_id: &str, tag: &RunTag) -> MLFlowResult<()> {
+        let dir = self.active_run_dir(run_id)?;
+        self.set_tag_to(&dir, run_id, tag)
+    }
+
+    fn delete_tag(&self, run_id: &str, key: &str) -> MLFlowResult<()> {
+        let path = key_path(&self.active_run_dir(run_id)?.join("tags"), key)?;
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(ResourceDoesNotExist(format!(
+                "No tag with name: {} in run with id {}",
+                key, run_id
+            ))),
+            result => result.map_err(|e| store_error(&path, e)),
+        }
+    }
+
+    fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<()> {
+        let dir = self.active_run_dir(run_id)?;
+        for param in &params {
+            self.log_param_to(&dir, run_id, param)?;
+        }
+        for metric in &metrics {
+            self.log_metric_to(&dir, metric)?;
+        }
+        for tag in &tags {
+            self.set_tag_to(&dir, run_id, tag)?;
+        }
+        Ok(())
+    }
+
+    fn log_inputs(&self, run_id: &str, datasets: Vec<DatasetInput>) -> MLFlowResult<()> {
+        let dir = self.active_run_dir(run_id)?;
+        let datasets_dir = dir
+            .parent()
+            .map(|experiment| experiment.join("datasets"))
+            .unwrap_or_default();
+
+        for input in datasets {
+            let dataset = &input.dataset;
+            let dataset_id = hex_digest(&format!("{}{}", dataset.name, dataset.digest));
+            let dataset_meta = datasets_dir.join(&dataset_id).join(META_FILE_NAME);
+            if !dataset_meta.is_file() {
+                write_yaml(&dataset_meta, dataset)?;
+            }
+
+            let input_meta = dir
+                .join("inputs")
+                .join(hex_digest(&format!("{}{}", dataset_id, run_id)))
+                .join(META_FILE_NAME);
+            if !input_meta.is_file() {
+                let meta = InputMeta {
+                    source_type: DATASET_VERTEX_TYPE,
+                    source_id: dataset_id,
+                    destination_type: RUN_VERTEX_TYPE,
+                    destination_id: run_id.to_string(),
+                    tags: input.tags.into_iter().map(|t| (t.key, t.value)).collect(),
+                };
+                write_yaml(&input_meta, &meta)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn upload_artifact(&self, path: &str, _local_file: &Path) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "the file store does not proxy artifacts, could not upload {}",
+            path
+        )))
+    }
+
+    fn list_artifacts(
+        &self,
+        run_id: &str,
+        path: Option<&str>,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<ListArtifactsResponse> {
+        let run = self.read_run(&self.run_dir(run_id)?)?;
+        let repository = artifact_repository(
+            run.info.artifact_uri.as_deref().unwrap_or_default(),
+            &self.clone().into(),
+            Some(run_id),
+        )?;
+        let (files, next_page_token) = page(repository.list_artifacts(path)?, None, page_token)?;
+        Ok(ListArtifactsResponse {
+            files,
+            next_page_token,
+        })
+    }
+
+    fn download_artifact(&self, path: &str, _local_file: &Path) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "the file store does not proxy artifacts, could not download {}",
+            path
+        )))
+    }
+
+    fn delete_artifact(&self, path: &str) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "the file store does not proxy artifacts, could not delete {}",
+            path
+        )))
+    }
+
+    fn create_registered_model(
+        &self,
+        _request: CreateRegisteredModelRequest,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        unsupported("the model registry")
+    }
+
+    fn get_registered_model(&self, _name: &str) -> MLFlowResult<RegisteredModelResponse> {
+        unsupported("the model registry")
+    }
+
+    fn rename_registered_model(
+        &self,
+        _name: &str,
+        _new_name: &str,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        unsupported("the model registry")
+    }
+
+    fn delete_registered_model(&self, _name: &str) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn set_registered_model_tag(&self, _name: &str, _tag: &RegisteredModelTag) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn delete_registered_model_tag(&self, _name: &str, _key: &str) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn search_registered_models(
+        &self,
+        _request: &SearchRegisteredModelsRequest,
+    ) -> MLFlowResult<SearchRegisteredModelsResponse> {
+        unsupported("the model registry")
+    }
+
+    fn create_model_version(
+        &self,
+        _request: CreateModelVersionRequest,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        unsupported("the model registry")
+    }
+
+    fn get_model_version(&self, _name: &str, _version: &str) -> MLFlowResult<ModelVersionResponse> {
+        unsupported("the model registry")
+    }
+
+    fn transition_model_version_stage(
+        &self,
+        _name: &str,
+        _version: &str,
+        _stage: ModelVersionStage,
+        _archive_existing_versions: bool,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        unsupported("the model registry")
+    }
+
+    fn delete_model_version(&self, _name: &str, _version: &str) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn set_model_version_tag(
+        &self,
+        _name: &str,
+        _version: &str,
+        _tag: &ModelVersionTag,
+    ) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn delete_model_version_tag(
+        &self,
+        _name: &str,
+        _version: &str,
+        _key: &str,
+    ) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn search_model_versions(
+        &self,
+        _request: &SearchModelVersionsRequest,
+    ) -> MLFlowResult<SearchModelVersionsResponse> {
+        unsupported("the model registry")
+    }
+
+    fn start_trace(&self, _request: StartTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        unsupported("tracing")
+    }
+
+    fn end_trace(&self, _request: EndTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        unsupported("tracing")
+    }
+
+    fn get_trace_info(&self, _request_id: &str) -> MLFlowResult<TraceInfoResponse> {
+        unsupported("tracing")
+    }
+
+    fn set_trace_tag(&self, _request_id: &str, _tag: &TraceTag) -> MLFlowResult<()> {
+        unsupported("tracing")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Run, RunLoader, RunSearch};
+
+    fn store() -> (tempfile::TempDir, FileStoreClient) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStoreClient::new(dir.path().join("mlruns")).unwrap();
+        (dir, store)
+    }
+
+    fn run(store: &FileStoreClient) -> Run {
+        RunBuilder::new(DEFAULT_EXPERIMENT_ID)
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .with_source_context(false)
+            .with_run_name("training")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn tracks_runs_in_the_mlruns_layout() {
+        let (_dir, store) = store();
+        let mut run = run(&store);
+        run.log_metric("loss", 0.5, Some(1), Some(1000)).unwrap();
+        run.log_metric("loss", 0.25, Some(2), Some(2000)).unwrap();
+        run.log_param("optimizer/lr", 0.01).unwrap();
+        run.finish(RunStatus::Finished).unwrap();
+
+        let run_dir = store.root().join("0").join(run.info().run_id.as_str());
+        assert_eq!(
+            std::fs::read_to_string(run_dir.join("metrics/loss")).unwrap(),
+            "1000 0.5 1\n2000 0.25 2\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(run_dir.join("params/optimizer/lr")).unwrap(),
+            "0.01"
+        );
+        let meta = std::fs::read_to_string(run_dir.join(META_FILE_NAME)).unwrap();
+        assert!(meta.contains("status: 3"), "{}", meta);
+        assert!(meta.contains("run_name: training"), "{}", meta);
+        assert!(meta.contains("experiment_id: '0'"), "{}", meta);
+
+        let loaded = RunLoader::default()
+            .with_dyn_client(store.clone())
+            .load(run.info().run_id.as_str())
+            .unwrap();
+        assert_eq!(loaded.data().metric("loss"), Some(0.25));
+        assert_eq!(loaded.status(), RunStatus::Finished);
+        assert_eq!(loaded.get_metric_history("loss").unwrap().len(), 2);
+        assert!(run
+            .log_param("optimizer/lr", 0.1)
+            .unwrap_err()
+            .to_string()
+            .contains("Changing param values"));
+    }
+
+    #[test]
+    fn reads_experiments_written_by_mlflow() {
+        let (_dir, store) = store();
+        let experiment_dir = store.root().join("7");
+        let run_dir = experiment_dir.join("0123456789abcdef0123456789abcdef");
+        write_file(
+            &experiment_dir.join(META_FILE_NAME),
+            "artifact_location: file:///tmp/mlruns/7\ncreation_time: 1700000000000\n\
+             experiment_id: '7'\nlast_update_time: 1700000000000\nlifecycle_stage: active\n\
+             name: imported\n",
+        )
+        .unwrap();
+        write_file(
+            &run_dir.join(META_FILE_NAME),
+            "artifact_uri: file:///tmp/mlruns/7/0123456789abcdef0123456789abcdef/artifacts\n\
+             end_time: 1700000005000\nentry_point_name: ''\nexperiment_id: '7'\n\
+             lifecycle_stage: active\nrun_id: 0123456789abcdef0123456789abcdef\n\
+             run_name: python-run\nrun_uuid: 0123456789abcdef0123456789abcdef\n\
+             source_name: ''\nsource_type: 4\nsource_version: ''\n\
+             start_time: 1700000000000\nstatus: 3\ntags: []\nuser_id: alice\n",
+        )
+        .unwrap();
+        write_file(&run_dir.join("metrics/acc"), "1700000001000 0.9 0\n").unwrap();
+
+        let experiment = store.get_experiment_by_name("imported").unwrap().experiment;
+        assert_eq!(experiment.experiment_id, "7");
+        let runs: Vec<Run> = RunSearch::new(vec!["7"])
+            .unwrap()
+            .with_filter("metrics.acc > 0.5")
+            .with_dyn_client(store.clone())
+            .search()
+            .collect::<MLFlowResult<_>>()
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].info().run_name.as_deref(), Some("python-run"));
+
+        // new experiments take the next free id
+        let id = store.create_experiment(&CreateExperimentRequest {
+            name: "next".to_string(),
+            artifact_location: None,
+            tags: vec![],
+        });
+        assert_eq!(id.unwrap().experiment_id, "8");
+    }
+
+    #[test]
+    fn keys_cannot_escape_the_run() {
+        let (_dir, store) = store();
+        let run = run(&store);
+        run.log_metric("../escaped", 1.0, None, None).unwrap_err();
+        run.set_tag("/absolute", "x").unwrap_err();
+        run.log_param("a//b", "x").unwrap_err();
+    }
+
+    #[test]
+    fn purges_deleted_runs() {
+        let (_dir, store) = store();
+        let mut run = run(&store);
+        run.log_text("weights", "model.bin").unwrap();
+        let run_id = run.info().run_id.clone();
+        store
+            .purge_run(&run_id)
+            .expect_err("the run is not deleted");
+
+        run.delete().unwrap();
+        let deleted = store.deleted_runs(0).unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert!(store.deleted_runs(60_000).unwrap().is_empty());
+        store.purge_run(&run_id).unwrap();
+        assert!(!store.root().join("0").join(&run_id).exists());
+        store.get_run(&run_id).expect_err("purged");
+
+        store
+            .purge_experiment(DEFAULT_EXPERIMENT_ID)
+            .expect_err("the experiment is not deleted");
+    }
+
+    #[test]
+    fn tracking_uris() {
+        assert!(is_file_store_uri("./mlruns"));
+        assert!(is_file_store_uri("file:///tmp/mlruns"));
+        assert!(is_file_store_uri("C:\\mlruns"));
+        assert!(!is_file_store_uri("http://localhost:5000"));
+        assert!(!is_file_store_uri("databricks"));
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn syncs_finished_runs_once() {
+        let (_dir, store) = store();
+        let mut finished = run(&store);
+        finished.log_metric("loss", 0.5, Some(1), None).unwrap();
+        finished.log_metric("loss", 0.25, Some(2), None).unwrap();
+        finished.log_param("epochs", 2).unwrap();
+        finished.log_text("done", "notes/summary.txt").unwrap();
+        finished.finish(RunStatus::Finished).unwrap();
+        let _running = run(&store);
+
+        let mock = crate::MockMLFlowClient::default();
+        let server: Arc<dyn MLFlowClient> = Arc::new(mock.clone());
+        assert_eq!(store.sync_to(server.clone()).unwrap(), 1);
+        assert_eq!(store.sync_to(server).unwrap(), 0);
+
+        let synced = store
+            .synced_run_id(&finished.info().run_id)
+            .unwrap()
+            .unwrap();
+        let remote = RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(&synced)
+            .unwrap();
+        assert_eq!(remote.status(), RunStatus::Finished);
+        assert_eq!(remote.info().run_name.as_deref(), Some("training"));
+        assert_eq!(remote.data().params.len(), 1);
+        assert_eq!(mock.metric_history(&synced, "loss").len(), 2);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn syncs_parents_before_their_children() {
+        let (_dir, store) = store();
+        let mut parent = run(&store);
+        let mut children: Vec<Run> = (0..4)
+            .map(|_| {
+                RunBuilder::new(DEFAULT_EXPERIMENT_ID)
+                    .unwrap()
+                    .with_dyn_client(store.clone())
+                    .with_source_context(false)
+                    .with_parent(&parent.info().run_id)
+                    .with_start_time(parent.start_time().unwrap())
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        for child in &mut children {
+            child.finish(RunStatus::Finished).unwrap();
+        }
+        parent.finish(RunStatus::Finished).unwrap();
+
+        let mock = crate::MockMLFlowClient::default();
+        let server: Arc<dyn MLFlowClient> = Arc::new(mock.clone());
+        assert_eq!(store.sync_to(server).unwrap(), 5);
+        let remote_parent = store.synced_run_id(&parent.info().run_id).unwrap().unwrap();
+        for child in &children {
+            let synced = store.synced_run_id(&child.info().run_id).unwrap().unwrap();
+            let remote = RunLoader::default()
+                .with_mock_client(mock.clone())
+                .load(&synced)
+                .unwrap();
+            assert_eq!(
+                parent_run_id(&remote.data().tags),
+                Some(remote_parent.as_str())
+            );
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Typed builder for the filter expressions accepted by `runs/search` and `experiments/search`.
+//!
+//! ```
+//! use mlflow_rs::Filter;
+//!
+//! let filter = Filter::metric("rmse")
+//!     .lt(0.5)
+//!     .and(Filter::tag("team").eq("nlp"))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(filter, "metrics.rmse < 0.5 AND tags.team = 'nlp'");
+//! ```
+
+use crate::MLFlowError::SearchError;
+use crate::MLFlowResult;
+use std::fmt::{Display, Formatter};
+
+/// Attributes holding timestamps, compared numerically.
+const NUMERIC_ATTRIBUTES: [&str; 5] = [
+    "start_time",
+    "end_time",
+    "created",
+    "creation_time",
+    "last_update_time",
+];
+
+/// Attributes holding text, compared as strings.
+const STRING_ATTRIBUTES: [&str; 6] = [
+    "run_id",
+    "run_name",
+    "status",
+    "artifact_uri",
+    "user_id",
+    "name",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+    ILike,
+}
+
+impl Comparator {
+    fn is_string_comparator(&self) -> bool {
+        matches!(
+            self,
+            Comparator::Eq | Comparator::Ne | Comparator::Like | Comparator::ILike
+        )
+    }
+
+    fn is_numeric_comparator(&self) -> bool {
+        !matches!(self, Comparator::Like | Comparator::ILike)
+    }
+}
+
+impl Display for Comparator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Comparator::Eq => "=",
+            Comparator::Ne => "!=",
+            Comparator::Lt => "<",
+            Comparator::Le => "<=",
+            Comparator::Gt => ">",
+            Comparator::Ge => ">=",
+            Comparator::Like => "LIKE",
+            Comparator::ILike => "ILIKE",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterValue {
+    Number(f64),
+    Text(String),
+}
+
+impl From<f64> for FilterValue {
+    fn from(v: f64) -> Self {
+        FilterValue::Number(v)
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(v: i64) -> Self {
+        FilterValue::Number(v as f64)
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(v: i32) -> Self {
+        FilterValue::Number(v as f64)
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(v: &str) -> Self {
+        FilterValue::Text(v.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(v: String) -> Self {
+        FilterValue::Text(v)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Entity {
+    Metric,
+    Param,
+    Tag,
+    Attribute,
+}
+
+impl Entity {
+    fn prefix(&self) -> &'static str {
+        match self {
+            Entity::Metric => "metrics",
+            Entity::Param => "params",
+            Entity::Tag => "tags",
+            Entity::Attribute => "attributes",
+        }
+    }
+}
+
+/// The left hand side of a comparison, created by [`Filter::metric`], [`Filter::param`],
+/// [`Filter::tag`] or [`Filter::attribute`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterKey {
+    entity: Entity,
+    key: String,
+}
+
+impl FilterKey {
+    pub fn compare(self, comparator: Comparator, value: impl Into<FilterValue>) -> Filter {
+        Filter {
+            clauses: vec![Clause {
+                entity: self.entity,
+                key: self.key,
+                comparator,
+                value: value.into(),
+            }],
+        }
+    }
+
+    pub fn eq(self, value: impl Into<FilterValue>) -> Filter {
+        self.compare(Comparator::Eq, value)
+    }
+
+    pub fn ne(self, value: impl Into<FilterValue>) -> Filter {
+        self.compare(Comparator::Ne, value)
+    }
+
+    pub fn lt(self, value: impl Into<FilterValue>) -> Filter {
+        self.compare(Comparator::Lt, value)
+    }
+
+    pub fn le(self, value: impl Into<FilterValue>) -> Filter {
+        self.compare(Comparator::Le, value)
+    }
+
+    pub fn gt(self, value: impl Into<FilterValue>) -> Filter {
+        self.compare(Comparator::Gt, value)
+    }
+
+    pub fn ge(self, value: impl Into<FilterValue>) -> Filter {
+        self.compare(Comparator::Ge, value)
+    }
+
+    /// Case sensitive pattern match, using `%` as the wildcard.
+    pub fn like(self, pattern: impl AsRef<str>) -> Filter {
+        self.compare(Comparator::Like, pattern.as_ref())
+    }
+
+    /// Case insensitive pattern match, using `%` as the wildcard.
+    pub fn ilike(self, pattern: impl AsRef<str>) -> Filter {
+        self.compare(Comparator::ILike, pattern.as_ref())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Clause {
+    entity: Entity,
+    key: String,
+    comparator: Comparator,
+    value: FilterValue,
+}
+
+impl Clause {
+    fn render(&self) -> MLFlowResult<String> {
+        let numeric = match self.entity {
+            Entity::Metric => true,
+            Entity::Param | Entity::Tag => false,
+            Entity::Attribute if NUMERIC_ATTRIBUTES.contains(&self.key.as_str()) => true,
+            Entity::Attribute if STRING_ATTRIBUTES.contains(&self.key.as_str()) => false,
+            Entity::Attribute => {
+                return Err(SearchError(format!(
+                    "{} is not a searchable attribute",
+                    self.key
+                )))
+            }
+        };
+
+        let comparator_ok = if numeric {
+            self.comparator.is_numeric_comparator()
+        } else {
+            self.comparator.is_string_comparator()
+        };
+        if !comparator_ok {
+            return Err(SearchError(format!(
+                "{} cannot be used with {}.{}",
+                self.comparator,
+                self.entity.prefix(),
+                self.key
+            )));
+        }
+
+        let value = match (&self.value, numeric) {
+            (FilterValue::Number(v), true) if v.is_finite() => v.to_string(),
+            (FilterValue::Text(v), false) => quote_value(v)?,
+            _ => {
+                return Err(SearchError(format!(
+                    "{}.{} must be compared with a {} value",
+                    self.entity.prefix(),
+                    self.key,
+                    if numeric { "finite numeric" } else { "string" }
+                )))
+            }
+        };
+
+        Ok(format!(
+            "{}.{} {} {}",
+            self.entity.prefix(),
+            quote_key(&self.key)?,
+            self.comparator,
+            value
+        ))
+    }
+}
+
+/// Keys made only of letters, digits and underscores are written as-is, everything else is
+/// wrapped in backticks.
+fn quote_key(key: &str) -> MLFlowResult<String> {
+    if key.is_empty() {
+        return Err(SearchError("filter keys cannot be empty".to_string()));
+    }
+    if key.contains('`') {
+        return Err(SearchError(format!(
+            "filter key {} cannot contain backticks",
+            key
+        )));
+    }
+
+    if key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(key.to_string())
+    } else {
+        Ok(format!("`{}`", key))
+    }
+}
+
+/// The grammar has no escape sequences, so values are wrapped in whichever quote they don't contain.
+fn quote_value(value: &str) -> MLFlowResult<String> {
+    match (value.contains('\''), value.contains('"')) {
+        (false, _) => Ok(format!("'{}'", value)),
+        (true, false) => Ok(format!("\"{}\"", value)),
+        (true, true) => Err(SearchError(format!(
+            "filter value {} cannot contain both single and double quotes",
+            value
+        ))),
+    }
+}
+
+/// A conjunction of comparisons. MLflow's search grammar only supports `AND`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    clauses: Vec<Clause>,
+}
+
+impl Filter {
+    pub fn metric(key: impl AsRef<str>) -> FilterKey {
+        FilterKey {
+            entity: Entity::Metric,
+            key: key.as_ref().to_string(),
+        }
+    }
+
+    pub fn param(key: impl AsRef<str>) -> FilterKey {
+        FilterKey {
+            entity: Entity::Param,
+            key: key.as_ref().to_string(),
+        }
+    }
+
+    pub fn tag(key: impl AsRef<str>) -> FilterKey {
+        FilterKey {
+            entity: Entity::Tag,
+            key: key.as_ref().to_string(),
+        }
+    }
+
+    /// A run or experiment attribute such as `status`, `run_name`, `start_time` or `name`.
+    pub fn attribute(name: impl AsRef<str>) -> FilterKey {
+        FilterKey {
+            entity: Entity::Attribute,
+            key: name.as_ref().to_string(),
+        }
+    }
+
+    pub fn and(mut self, other: Filter) -> Filter {
+        self.clauses.extend(other.clauses);
+        self
+    }
+
+    /// Validates the expression and renders it in the MLflow filter grammar.
+    pub fn build(&self) -> MLFlowResult<String> {
+        let clauses = self
+            .clauses
+            .iter()
+            .map(|c| c.render())
+            .collect::<MLFlowResult<Vec<String>>>()?;
+        Ok(clauses.join(" AND "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_comparisons() {
+        assert_eq!(
+            Filter::metric("rmse").le(1).build().unwrap(),
+            "metrics.rmse <= 1"
+        );
+        assert_eq!(
+            Filter::param("optimizer").ne("adam").build().unwrap(),
+            "params.optimizer != 'adam'"
+        );
+        assert_eq!(
+            Filter::attribute("status").eq("FINISHED").build().unwrap(),
+            "attributes.status = 'FINISHED'"
+        );
+        assert_eq!(
+            Filter::attribute("start_time")
+                .gt(1700000000000_i64)
+                .build()
+                .unwrap(),
+            "attributes.start_time > 1700000000000"
+        );
+    }
+
+    #[test]
+    fn conjunction() {
+        let filter = Filter::metric("rmse")
+            .lt(0.5)
+            .and(Filter::tag("team").eq("nlp"))
+            .and(Filter::param("model").ilike("%bert%"));
+        assert_eq!(
+            filter.build().unwrap(),
+            "metrics.rmse < 0.5 AND tags.team = 'nlp' AND params.model ILIKE '%bert%'"
+        );
+    }
+
+    #[test]
+    fn special_keys_are_quoted() {
+        assert_eq!(
+            Filter::tag("mlflow.runName").eq("run").build().unwrap(),
+            "tags.`mlflow.runName` = 'run'"
+        );
+        assert_eq!(
+            Filter::metric("f1 score").gt(0.9).build().unwrap(),
+            "metrics.`f1 score` > 0.9"
+        );
+    }
+
+    #[test]
+    fn values_with_quotes() {
+        assert_eq!(
+            Filter::tag("note").eq("it's").build().unwrap(),
+            "tags.note = \"it's\""
+        );
+        Filter::tag("note")
+            .eq("it's \"quoted\"")
+            .build()
+            .expect_err("both quote styles cannot be rendered");
+    }
+
+    #[test]
+    fn invalid_filters() {
+        Filter::metric("").lt(1).build().expect_err("empty key");
+        Filter::metric("a`b")
+            .lt(1)
+            .build()
+            .expect_err("backtick in key");
+        Filter::metric("rmse")
+            .eq("low")
+            .build()
+            .expect_err("metric compared to text");
+        Filter::metric("rmse")
+            .lt(f64::NAN)
+            .build()
+            .expect_err("non finite value");
+        Filter::metric("rmse")
+            .like("%")
+            .build()
+            .expect_err("LIKE on a metric");
+        Filter::param("lr")
+            .lt(0.1)
+            .build()
+            .expect_err("param compared to a number");
+        Filter::tag("team")
+            .gt("a")
+            .build()
+            .expect_err("ordering on a tag");
+        Filter::attribute("colour")
+            .eq("red")
+            .build()
+            .expect_err("unknown attribute");
+    }
+}
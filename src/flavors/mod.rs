@@ -0,0 +1,33 @@
+//! Helpers that log models in the formats MLflow has flavors for, with the flavor sections and
+//! environment files `mlflow models serve` needs to load them.
+
+pub mod onnx;
+pub mod pytorch;
+
+use crate::model::ModelLogger;
+
+/// Adds the `requirements.txt`, `python_env.yaml` and `conda.yaml` files `mlflow models serve`
+/// creates the model's environment from.
+fn with_environment<'a>(
+    logger: ModelLogger<'a>,
+    python_version: &str,
+    requirements: &[String],
+) -> ModelLogger<'a> {
+    let python_env = format!(
+        "python: {}\nbuild_dependencies:\n- pip\n- setuptools\n- wheel\ndependencies:\n- -r requirements.txt\n",
+        python_version
+    );
+    let conda = format!(
+        "channels:\n- conda-forge\ndependencies:\n- python={}\n- pip\n- pip:\n{}",
+        python_version,
+        requirements
+            .iter()
+            .map(|r| format!("  - {}\n", r))
+            .collect::<String>()
+    );
+
+    logger
+        .with_metadata_file("requirements.txt", requirements.join("\n") + "\n")
+        .with_metadata_file("python_env.yaml", python_env)
+        .with_metadata_file("conda.yaml", conda)
+}
@@ -0,0 +1,395 @@
+//! The `onnx` flavor, for models exported to ONNX by Rust inference and training stacks.
+//!
+//! ```no_run
+//! use mlflow_rs::flavors::onnx::OnnxModel;
+//! use mlflow_rs::RunBuilder;
+//!
+//! let run = RunBuilder::new("1").unwrap().build().unwrap();
+//! let info = OnnxModel::load("model.onnx").unwrap().log(&run, "model").unwrap();
+//! println!("serve with: mlflow models serve -m {}", info.model_uri);
+//! ```
+
+use super::with_environment;
+use crate::model::{Flavor, ModelInfo, ModelLogger};
+use crate::run::Run;
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+
+pub const FLAVOR_NAME: &str = "onnx";
+const DEFAULT_PYTHON_VERSION: &str = "3.10";
+const DEFAULT_PROVIDERS: [&str; 2] = ["CUDAExecutionProvider", "CPUExecutionProvider"];
+
+/// An ONNX model file, with the metadata read from its header.
+#[derive(Clone, Debug)]
+pub struct OnnxModel {
+    path: PathBuf,
+    ir_version: i64,
+    opset_version: Option<i64>,
+    producer: Option<String>,
+    onnx_version: String,
+    providers: Vec<String>,
+    python_version: String,
+}
+
+impl OnnxModel {
+    /// Reads the header of the ONNX model at `path`, failing if it isn't an ONNX model.
+    pub fn load(path: impl AsRef<Path>) -> MLFlowResult<OnnxModel> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| ArtifactError(e.to_string()))?;
+        let header = read_header(BufReader::new(file)).map_err(|e| {
+            ArtifactError(format!(
+                "{} is not an ONNX model: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })?;
+        let ir_version = header.ir_version.ok_or_else(|| {
+            ArtifactError(format!(
+                "{} is not an ONNX model: no ir_version",
+                path.to_string_lossy()
+            ))
+        })?;
+
+        Ok(OnnxModel {
+            path: path.to_path_buf(),
+            ir_version,
+            opset_version: header.opset_version,
+            producer: header.producer,
+            onnx_version: minimum_onnx_version(ir_version).to_string(),
+            providers: DEFAULT_PROVIDERS.iter().map(|p| p.to_string()).collect(),
+            python_version: DEFAULT_PYTHON_VERSION.to_string(),
+        })
+    }
+
+    /// The version of the ONNX IR the model is serialized with.
+    pub fn ir_version(&self) -> i64 {
+        self.ir_version
+    }
+
+    /// The version of the default `ai.onnx` operator set the model uses.
+    pub fn opset_version(&self) -> Option<i64> {
+        self.opset_version
+    }
+
+    /// The tool that exported the model, e.g. `pytorch 2.1.0`.
+    pub fn producer(&self) -> Option<&str> {
+        self.producer.as_deref()
+    }
+
+    /// The `onnx` Python package version recorded in the flavor and pinned in the model's
+    /// environment. Defaults to the first release supporting the model's IR version.
+    pub fn with_onnx_version(mut self, onnx_version: impl AsRef<str>) -> Self {
+        self.onnx_version = onnx_version.as_ref().to_string();
+        self
+    }
+
+    /// The onnxruntime execution providers to try when serving, in order of preference.
+    pub fn with_providers(mut self, providers: Vec<impl AsRef<str>>) -> Self {
+        self.providers = providers.iter().map(|p| p.as_ref().to_string()).collect();
+        self
+    }
+
+    /// The Python version of the environment the model is served from. Defaults to 3.10.
+    pub fn with_python_version(mut self, python_version: impl AsRef<str>) -> Self {
+        self.python_version = python_version.as_ref().to_string();
+        self
+    }
+
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "model.onnx".to_string())
+    }
+
+    /// The `onnx` flavor and the `python_function` flavor that serves it.
+    pub fn flavors(&self) -> Vec<Flavor> {
+        vec![
+            Flavor::new(FLAVOR_NAME)
+                .with_config("onnx_version", self.onnx_version.as_str())
+                .with_config("data", self.file_name())
+                .with_config("providers", self.providers.clone())
+                .with_config("code", serde_json::Value::Null),
+            Flavor::new("python_function")
+                .with_config("loader_module", "mlflow.onnx")
+                .with_config("data", self.file_name())
+                .with_config("python_version", self.python_version.as_str())
+                .with_config(
+                    "env",
+                    serde_json::json!({"conda": "conda.yaml", "virtualenv": "python_env.yaml"}),
+                ),
+        ]
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        vec![
+            "mlflow".to_string(),
+            format!("onnx>={}", self.onnx_version),
+            "onnxruntime".to_string(),
+        ]
+    }
+
+    /// A [`ModelLogger`] set up with the flavors and environment files, to add a signature or
+    /// register the model before logging it.
+    pub fn logger<'a>(
+        &self,
+        run: &'a Run,
+        artifact_path: impl AsRef<str>,
+    ) -> MLFlowResult<ModelLogger<'a>> {
+        Ok(with_environment(
+            self.flavors().into_iter().fold(
+                ModelLogger::new(run, artifact_path, &self.path)?,
+                ModelLogger::with_flavor,
+            ),
+            &self.python_version,
+            &self.requirements(),
+        ))
+    }
+
+    /// Logs the model under the run's `artifact_path` artifact directory.
+    pub fn log(&self, run: &Run, artifact_path: impl AsRef<str>) -> MLFlowResult<ModelInfo> {
+        self.logger(run, artifact_path)?.log()
+    }
+}
+
+/// The first `onnx` release able to load models of the given IR version.
+fn minimum_onnx_version(ir_version: i64) -> &'static str {
+    match ir_version {
+        i64::MIN..=3 => "1.1.0",
+        4 => "1.3.0",
+        5 => "1.5.0",
+        6 => "1.6.0",
+        7 => "1.7.0",
+        8 => "1.10.0",
+        9 => "1.13.0",
+        10 => "1.15.0",
+        _ => "1.18.0",
+    }
+}
+
+#[derive(Debug, Default)]
+struct Header {
+    ir_version: Option<i64>,
+    producer: Option<String>,
+    opset_version: Option<i64>,
+}
+
+/// Reads the top level fields of a serialized `ModelProto`, seeking over the graph so large
+/// models aren't read into memory.
+fn read_header<R: Read + Seek>(mut reader: R) -> std::io::Result<Header> {
+    let mut header = Header::default();
+    let mut producer_name = None;
+    let mut producer_version = None;
+
+    while let Some(key) = read_varint_or_eof(&mut reader)? {
+        match (key >> 3, key & 7) {
+            (1, 0) => header.ir_version = Some(read_varint(&mut reader)? as i64),
+            (2, 2) => producer_name = Some(read_string(&mut reader)?),
+            (3, 2) => producer_version = Some(read_string(&mut reader)?),
+            (8, 2) => {
+                let opset = read_bytes(&mut reader)?;
+                let (domain, version) = read_opset(&opset)?;
+                if domain.is_empty() || domain == "ai.onnx" {
+                    header.opset_version = version;
+                }
+            }
+            (_, wire_type) => skip(&mut reader, wire_type)?,
+        }
+    }
+
+    header.producer = match (producer_name, producer_version) {
+        (Some(name), Some(version)) if !version.is_empty() => Some(format!("{} {}", name, version)),
+        (name, _) => name,
+    };
+    Ok(header)
+}
+
+fn read_opset(mut bytes: &[u8]) -> std::io::Result<(String, Option<i64>)> {
+    let mut domain = String::new();
+    let mut version = None;
+    while let Some(key) = read_varint_or_eof(&mut bytes)? {
+        match (key >> 3, key & 7) {
+            (1, 2) => domain = read_string(&mut bytes)?,
+            (2, 0) => version = Some(read_varint(&mut bytes)? as i64),
+            (_, wire_type) => {
+                let skipped = match wire_type {
+                    0 => read_varint(&mut bytes).map(|_| 0)?,
+                    1 => 8,
+                    2 => read_varint(&mut bytes)?,
+                    5 => 4,
+                    _ => return Err(invalid("unsupported wire type")),
+                } as usize;
+                bytes = bytes.get(skipped..).ok_or_else(|| invalid("truncated"))?;
+            }
+        }
+    }
+    Ok((domain, version))
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+fn read_varint_or_eof<R: Read>(reader: &mut R) -> std::io::Result<Option<u64>> {
+    let mut value = 0u64;
+    for (i, shift) in (0..64).step_by(7).enumerate() {
+        let mut byte = [0u8];
+        if reader.read(&mut byte)? == 0 {
+            return match i {
+                0 => Ok(None),
+                _ => Err(invalid("truncated varint")),
+            };
+        }
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    read_varint_or_eof(reader)?.ok_or_else(|| invalid("truncated varint"))
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let len = read_varint(reader)?;
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(invalid("truncated field"));
+    }
+    Ok(bytes)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> std::io::Result<String> {
+    String::from_utf8(read_bytes(reader)?).map_err(|_| invalid("invalid string"))
+}
+
+fn skip<R: Read + Seek>(reader: &mut R, wire_type: u64) -> std::io::Result<()> {
+    let len = match wire_type {
+        0 => return read_varint(reader).map(|_| ()),
+        1 => 8,
+        2 => read_varint(reader)?,
+        5 => 4,
+        _ => return Err(invalid("unsupported wire type")),
+    };
+    reader.seek_relative(len as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn varint(mut value: u64) -> Vec<u8> {
+        let mut bytes = vec![];
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    fn field(number: u64, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = varint(number << 3 | 2);
+        bytes.extend(varint(payload.len() as u64));
+        bytes.extend(payload);
+        bytes
+    }
+
+    /// A `ModelProto` with just enough fields set to exercise the header reader.
+    fn model_proto() -> Vec<u8> {
+        let mut bytes = varint(1 << 3);
+        bytes.extend(varint(9));
+        bytes.extend(field(2, b"pytorch"));
+        bytes.extend(field(3, b"2.1.0"));
+        bytes.extend(field(7, &[0u8; 300]));
+        let mut opset = field(1, b"ai.onnx.ml");
+        opset.extend(varint(2 << 3));
+        opset.extend(varint(3));
+        bytes.extend(field(8, &opset));
+        let mut opset = varint(2 << 3);
+        opset.extend(varint(17));
+        bytes.extend(field(8, &opset));
+        bytes
+    }
+
+    #[test]
+    fn reads_header() {
+        let header = read_header(Cursor::new(model_proto())).unwrap();
+        assert_eq!(header.ir_version, Some(9));
+        assert_eq!(header.producer.as_deref(), Some("pytorch 2.1.0"));
+        assert_eq!(header.opset_version, Some(17));
+
+        read_header(Cursor::new(b"\x0a\xff".to_vec())).expect_err("truncated");
+    }
+
+    #[test]
+    fn flavors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("classifier.onnx");
+        std::fs::write(&path, model_proto()).unwrap();
+
+        let model = OnnxModel::load(&path)
+            .unwrap()
+            .with_providers(vec!["CPUExecutionProvider"]);
+        assert_eq!(model.ir_version(), 9);
+        assert_eq!(model.opset_version(), Some(17));
+
+        let flavors = model.flavors();
+        assert_eq!(flavors[0].name(), FLAVOR_NAME);
+        assert_eq!(flavors[0].config["onnx_version"], "1.13.0");
+        assert_eq!(flavors[0].config["data"], "classifier.onnx");
+        assert_eq!(
+            flavors[0].config["providers"],
+            serde_json::json!(["CPUExecutionProvider"])
+        );
+        assert_eq!(flavors[1].config["loader_module"], "mlflow.onnx");
+
+        let text = dir.path().join("model.txt");
+        std::fs::write(&text, "not a model").unwrap();
+        OnnxModel::load(&text).expect_err("not an ONNX model");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn logs_model() {
+        use crate::{MockMLFlowClient, RunBuilder, RunIdentifier};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.onnx");
+        std::fs::write(&path, model_proto()).unwrap();
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let info = OnnxModel::load(&path).unwrap().log(&run, "model").unwrap();
+        assert_eq!(info.flavors, vec!["onnx", "python_function"]);
+
+        for file in [
+            "model.onnx",
+            "MLmodel",
+            "requirements.txt",
+            "python_env.yaml",
+            "conda.yaml",
+        ] {
+            assert!(
+                mock.artifact(&run.run_id(), &format!("model/{}", file))
+                    .is_some(),
+                "{} was not logged",
+                file
+            );
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! The `pytorch` flavor, for TorchScript modules saved by `tch`'s `CModule::save` or traced and
+//! scripted in Python, which `mlflow.pytorch.load_model` and `mlflow models serve` load with
+//! `torch.load`.
+//!
+//! ```no_run
+//! use mlflow_rs::flavors::pytorch::TorchScriptModel;
+//! use mlflow_rs::RunBuilder;
+//!
+//! // e.g. after `module.save("model.pt")` on a `tch::CModule`
+//! let run = RunBuilder::new("1").unwrap().build().unwrap();
+//! let info = TorchScriptModel::load("model.pt")
+//!     .unwrap()
+//!     .with_pytorch_version("2.9.0")
+//!     .log(&run, "model")
+//!     .unwrap();
+//! println!("serve with: mlflow models serve -m {}", info.model_uri);
+//! ```
+
+use super::with_environment;
+use crate::model::{Flavor, ModelInfo, ModelLogger};
+use crate::run::Run;
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+pub const FLAVOR_NAME: &str = "pytorch";
+/// The libtorch version `tch` 0.22 builds against.
+const DEFAULT_PYTORCH_VERSION: &str = "2.9.0";
+const DEFAULT_PYTHON_VERSION: &str = "3.10";
+/// TorchScript archives are zip files.
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+const END_OF_CENTRAL_DIRECTORY: [u8; 4] = *b"PK\x05\x06";
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR: [u8; 4] = *b"PK\x06\x07";
+const ZIP64_END_OF_CENTRAL_DIRECTORY: [u8; 4] = *b"PK\x06\x06";
+const CENTRAL_DIRECTORY_HEADER: [u8; 4] = *b"PK\x01\x02";
+/// Size of the end of central directory record without its trailing comment.
+const END_OF_CENTRAL_DIRECTORY_LEN: u64 = 22;
+const MAX_ZIP_COMMENT_LEN: u64 = u16::MAX as u64;
+
+/// A TorchScript module file.
+#[derive(Clone, Debug)]
+pub struct TorchScriptModel {
+    path: PathBuf,
+    pytorch_version: String,
+    python_version: String,
+}
+
+impl TorchScriptModel {
+    /// Checks that the file at `path` is a TorchScript archive, with the `code/` and
+    /// `constants.pkl` entries `torch.jit.save` writes. Checkpoints of `torch.save`, such as a
+    /// saved `state_dict`, are zip files too but are rejected, since they can't be served.
+    pub fn load(path: impl AsRef<Path>) -> MLFlowResult<TorchScriptModel> {
+        let path = path.as_ref();
+        let not_torchscript = || {
+            ArtifactError(format!(
+                "{} is not a TorchScript archive",
+                path.to_string_lossy()
+            ))
+        };
+        let mut file = File::open(path).map_err(|e| ArtifactError(e.to_string()))?;
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)
+            .map_err(|e| ArtifactError(e.to_string()))?;
+        if magic != ZIP_MAGIC {
+            return Err(not_torchscript());
+        }
+        let entries = zip_entries(&mut file)
+            .map_err(|e| ArtifactError(e.to_string()))?
+            .ok_or_else(not_torchscript)?;
+        let has_code = entries.iter().any(|e| e.contains("/code/"));
+        let has_constants = entries.iter().any(|e| e.ends_with("/constants.pkl"));
+        if !has_code || !has_constants {
+            return Err(not_torchscript());
+        }
+
+        Ok(TorchScriptModel {
+            path: path.to_path_buf(),
+            pytorch_version: DEFAULT_PYTORCH_VERSION.to_string(),
+            python_version: DEFAULT_PYTHON_VERSION.to_string(),
+        })
+    }
+
+    /// The `torch` Python package version recorded in the flavor and pinned in the model's
+    /// environment, usually the libtorch version the module was saved with. Defaults to 2.9.0,
+    /// the one of `tch` 0.22.
+    pub fn with_pytorch_version(mut self, pytorch_version: impl AsRef<str>) -> Self {
+        self.pytorch_version = pytorch_version.as_ref().to_string();
+        self
+    }
+
+    /// The Python version of the environment the model is served from. Defaults to 3.10.
+    pub fn with_python_version(mut self, python_version: impl AsRef<str>) -> Self {
+        self.python_version = python_version.as_ref().to_string();
+        self
+    }
+
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "model.pt".to_string())
+    }
+
+    /// The `pytorch` flavor and the `python_function` flavor that serves it. Both point at the
+    /// module file itself, which `mlflow.pytorch` passes straight to `torch.load`.
+    pub fn flavors(&self) -> Vec<Flavor> {
+        vec![
+            Flavor::new(FLAVOR_NAME)
+                .with_config("model_data", self.file_name())
+                .with_config("pytorch_version", self.pytorch_version.as_str())
+                .with_config("code", serde_json::Value::Null),
+            Flavor::new("python_function")
+                .with_config("loader_module", "mlflow.pytorch")
+                .with_config("data", self.file_name())
+                .with_config("pickle_module_name", "mlflow.pytorch.pickle_module")
+                .with_config("python_version", self.python_version.as_str())
+                .with_config(
+                    "env",
+                    serde_json::json!({"conda": "conda.yaml", "virtualenv": "python_env.yaml"}),
+                ),
+        ]
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        vec![
+            "mlflow".to_string(),
+            format!("torch>={}", self.pytorch_version),
+            "cloudpickle".to_string(),
+        ]
+    }
+
+    /// A [`ModelLogger`] set up with the flavors and environment files, to add a signature or
+    /// register the model before logging it.
+    pub fn logger<'a>(
+        &self,
+        run: &'a Run,
+        artifact_path: impl AsRef<str>,
+    ) -> MLFlowResult<ModelLogger<'a>> {
+        Ok(with_environment(
+            self.flavors().into_iter().fold(
+                ModelLogger::new(run, artifact_path, &self.path)?,
+                ModelLogger::with_flavor,
+            ),
+            &self.python_version,
+            &self.requirements(),
+        ))
+    }
+
+    /// Logs the model under the run's `artifact_path` artifact directory.
+    pub fn log(&self, run: &Run, artifact_path: impl AsRef<str>) -> MLFlowResult<ModelInfo> {
+        self.logger(run, artifact_path)?.log()
+    }
+}
+
+/// The names of the entries in the central directory of the zip `file`, or `None` if it has none.
+fn zip_entries(file: &mut File) -> std::io::Result<Option<Vec<String>>> {
+    // the end of central directory record is last, followed by a comment of up to 64 KiB
+    let len = file.metadata()?.len();
+    let tail_len = len.min(END_OF_CENTRAL_DIRECTORY_LEN + MAX_ZIP_COMMENT_LEN);
+    let mut tail = vec![0u8; tail_len as usize];
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    file.read_exact(&mut tail)?;
+    let last = tail
+        .len()
+        .saturating_sub(END_OF_CENTRAL_DIRECTORY_LEN as usize - 1);
+    let end = match (0..last)
+        .rev()
+        .find(|i| tail[*i..].starts_with(&END_OF_CENTRAL_DIRECTORY))
+    {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+    let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]) as u64;
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes(b[i..i + 4].try_into().unwrap()) as u64;
+    let u64_at = |b: &[u8], i: usize| u64::from_le_bytes(b[i..i + 8].try_into().unwrap());
+
+    let mut entries = u16_at(&tail, end + 10);
+    let mut size = u32_at(&tail, end + 12);
+    let mut offset = u32_at(&tail, end + 16);
+    // archives over 4 GiB, as large models are, keep the real values in the zip64 record
+    if offset == u32::MAX as u64
+        && end >= 20
+        && tail[end - 20..].starts_with(&ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR)
+    {
+        let mut record = [0u8; 56];
+        file.seek(SeekFrom::Start(u64_at(&tail, end - 12)))?;
+        file.read_exact(&mut record)?;
+        if !record.starts_with(&ZIP64_END_OF_CENTRAL_DIRECTORY) {
+            return Ok(None);
+        }
+        entries = u64_at(&record, 32);
+        size = u64_at(&record, 40);
+        offset = u64_at(&record, 48);
+    }
+    if offset.saturating_add(size) > len {
+        return Ok(None);
+    }
+
+    let mut directory = vec![0u8; size as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut directory)?;
+    let mut names = vec![];
+    let mut at = 0;
+    for _ in 0..entries {
+        if at + 46 > directory.len() || !directory[at..].starts_with(&CENTRAL_DIRECTORY_HEADER) {
+            return Ok(None);
+        }
+        let name_len = u16_at(&directory, at + 28) as usize;
+        let extra_len = u16_at(&directory, at + 30) as usize;
+        let comment_len = u16_at(&directory, at + 32) as usize;
+        let name = directory.get(at + 46..at + 46 + name_len);
+        match name {
+            Some(name) => names.push(String::from_utf8_lossy(name).to_string()),
+            None => return Ok(None),
+        }
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(Some(names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A zip archive of empty, stored entries named `names`.
+    fn archive(names: &[&str]) -> Vec<u8> {
+        let mut local = vec![];
+        let mut directory = vec![];
+        for name in names {
+            let offset = local.len() as u32;
+            local.extend_from_slice(&ZIP_MAGIC);
+            local.extend_from_slice(&[0; 22]);
+            local.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            local.extend_from_slice(&[0; 2]);
+            local.extend_from_slice(name.as_bytes());
+
+            directory.extend_from_slice(&CENTRAL_DIRECTORY_HEADER);
+            directory.extend_from_slice(&[0; 24]);
+            directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let mut zip = local.clone();
+        zip.extend_from_slice(&directory);
+        zip.extend_from_slice(&END_OF_CENTRAL_DIRECTORY);
+        zip.extend_from_slice(&[0; 4]);
+        zip.extend_from_slice(&(names.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&(names.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(local.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&[0; 2]);
+        zip
+    }
+
+    fn torchscript() -> Vec<u8> {
+        archive(&[
+            "classifier/data.pkl",
+            "classifier/code/__torch__/classifier.py",
+            "classifier/constants.pkl",
+            "classifier/version",
+        ])
+    }
+
+    #[test]
+    fn flavors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("classifier.pt");
+        std::fs::write(&path, torchscript()).unwrap();
+
+        let model = TorchScriptModel::load(&path)
+            .unwrap()
+            .with_pytorch_version("2.8.0");
+        let flavors = model.flavors();
+        assert_eq!(flavors[0].name(), FLAVOR_NAME);
+        assert_eq!(flavors[0].config["model_data"], "classifier.pt");
+        assert_eq!(flavors[0].config["pytorch_version"], "2.8.0");
+        assert_eq!(flavors[1].config["loader_module"], "mlflow.pytorch");
+        assert_eq!(flavors[1].config["data"], "classifier.pt");
+
+        let text = dir.path().join("model.txt");
+        std::fs::write(&text, "not a model").unwrap();
+        TorchScriptModel::load(&text).expect_err("not a TorchScript archive");
+
+        // what torch.save writes for a state_dict
+        let checkpoint = dir.path().join("checkpoint.pt");
+        let state_dict = archive(&[
+            "checkpoint/data.pkl",
+            "checkpoint/data/0",
+            "checkpoint/version",
+        ]);
+        std::fs::write(&checkpoint, state_dict).unwrap();
+        TorchScriptModel::load(&checkpoint).expect_err("a checkpoint is not a module");
+
+        let truncated = dir.path().join("truncated.pt");
+        std::fs::write(&truncated, &torchscript()[..40]).unwrap();
+        TorchScriptModel::load(&truncated).expect_err("no central directory");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn logs_model() {
+        use crate::{MockMLFlowClient, RunBuilder, RunIdentifier};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.pt");
+        std::fs::write(&path, torchscript()).unwrap();
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let info = TorchScriptModel::load(&path)
+            .unwrap()
+            .log(&run, "model")
+            .unwrap();
+        assert_eq!(info.flavors, vec!["pytorch", "python_function"]);
+
+        for file in ["model.pt", "MLmodel", "requirements.txt", "conda.yaml"] {
+            assert!(
+                mock.artifact(&run.run_id(), &format!("model/{}", file))
+                    .is_some(),
+                "{} was not logged",
+                file
+            );
+        }
+        let requirements = mock
+            .artifact(&run.run_id(), "model/requirements.txt")
+            .unwrap();
+        assert!(String::from_utf8_lossy(&requirements).contains("torch>=2.9.0"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Module level tracking API mirroring Python's `mlflow.*` functions, for scripts that would
+//! rather not thread clients and runs through every call.
+//!
+//! The tracking URI and experiment are process wide. Active runs are tracked per thread, and
+//! logging without an active run starts one, as the Python client does.
+//!
+//! ```no_run
+//! use mlflow_rs::fluent;
+//!
+//! fluent::set_tracking_uri("http://localhost:5000");
+//! fluent::set_experiment("my-ml-experiment").unwrap();
+//! fluent::start_run().unwrap();
+//! fluent::log_param("learning_rate", 0.01).unwrap();
+//! fluent::log_metric("rmse", 0.4, None, None).unwrap();
+//! fluent::end_run().unwrap();
+//! ```
+
+use crate::active_run::ActiveRun;
+use crate::config::{local_store_client, Config};
+use crate::entities::{Metric, Param, RunStatus, RunTag};
+use crate::experiment::{Experiment, ExperimentBuilder};
+use crate::run::{Run, RunBuilder, RunLoader};
+#[cfg(feature = "blocking")]
+use crate::MLFLowRestClient;
+use crate::MLFlowError::RunBuilderError;
+use crate::{ExperimentIdentifier, MLFlowClient, MLFlowResult, RunIdentifier};
+use std::cell::RefCell;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+/// Id of the experiment every tracking server creates on startup.
+const DEFAULT_EXPERIMENT_ID: &str = "0";
+
+static TRACKING_URI: RwLock<Option<String>> = RwLock::new(None);
+static EXPERIMENT_ID: RwLock<Option<String>> = RwLock::new(None);
+
+thread_local! {
+    static ACTIVE_RUNS: RefCell<Vec<ActiveRun>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sets the tracking server used by every function in this module. See
+/// [`MLFLowRestClient::from_tracking_uri`] for the supported URIs. A `file:` URI or plain path,
+/// e.g. `./mlruns`, tracks to a local [`FileStoreClient`](crate::FileStoreClient) instead, and
+/// with the `sqlite` feature a `sqlite:///mlflow.db` URI to a `SqliteStoreClient`.
+pub fn set_tracking_uri(uri: impl AsRef<str>) {
+    *TRACKING_URI.write().unwrap_or_else(|e| e.into_inner()) = Some(uri.as_ref().to_string());
+}
+
+/// The URI passed to [`set_tracking_uri`], else the one the global [`Config`] resolves.
+pub fn get_tracking_uri() -> MLFlowResult<String> {
+    let tracking_uri = TRACKING_URI
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    match tracking_uri {
+        Some(tracking_uri) => Ok(tracking_uri),
+        None => Config::global().tracking_uri(),
+    }
+}
+
+fn client() -> MLFlowResult<Arc<dyn MLFlowClient>> {
+    let tracking_uri = get_tracking_uri()?;
+    match local_store_client(&tracking_uri) {
+        Some(client) => client,
+        #[cfg(feature = "blocking")]
+        None => Ok(Arc::new(MLFLowRestClient::from_tracking_uri(tracking_uri)?)),
+        #[cfg(not(feature = "blocking"))]
+        None => Err(crate::config::tracking_server_unsupported(&tracking_uri)),
+    }
+}
+
+/// Finds the most recently started run named `run_name` in the experiment, see
+/// [`RunLoader::find_by_name`].
+pub fn find_run_by_name(
+    experiment_id: impl AsRef<str>,
+    run_name: impl AsRef<str>,
+) -> MLFlowResult<Option<Run>> {
+    RunLoader::default()
+        .with_dyn_client(client()?)
+        .find_by_name(experiment_id, run_name)
+}
+
+/// Makes `name` the experiment new runs are created in, creating it if it doesn't exist.
+pub fn set_experiment(name: impl AsRef<str>) -> MLFlowResult<Experiment> {
+    let experiment = ExperimentBuilder::new(name)?
+        .with_dyn_client(client()?)
+        .get_or_create()?;
+
+    *EXPERIMENT_ID.write().unwrap_or_else(|e| e.into_inner()) = experiment.experiment_id();
+    Ok(experiment)
+}
+
+/// The experiment chosen with [`set_experiment`], else `MLFLOW_EXPERIMENT_ID`, else the
+/// server's default experiment.
+fn experiment_id() -> String {
+    EXPERIMENT_ID
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(|| std::env::var("MLFLOW_EXPERIMENT_ID").ok())
+        .unwrap_or_else(|| DEFAULT_EXPERIMENT_ID.to_string())
+}
+
+fn push_run(parent_run_id: Option<String>) -> MLFlowResult<String> {
+    let mut builder = RunBuilder::new(experiment_id())?.with_dyn_client(client()?);
+    if let Some(parent) = parent_run_id {
+        builder = builder.with_parent(parent);
+    }
+    let run = builder.start()?;
+    let run_id = run.run_id();
+    ACTIVE_RUNS.with(|runs| runs.borrow_mut().push(run));
+    Ok(run_id)
+}
+
+/// Starts a run on the current thread and returns its id.
+///
+/// Fails if the thread already has an active run, use [`start_nested_run`] to create a child
+/// of it instead.
+pub fn start_run() -> MLFlowResult<String> {
+    if let Some(run_id) = active_run_id() {
+        return Err(RunBuilderError(format!(
+            "run {} is already active, end it or use start_nested_run",
+            run_id
+        )));
+    }
+    push_run(None)
+}
+
+/// Starts a run nested under the thread's active run, or a top level run if there is none.
+pub fn start_nested_run() -> MLFlowResult<String> {
+    push_run(active_run_id())
+}
+
+/// The id of the current thread's innermost active run.
+pub fn active_run_id() -> Option<String> {
+    ACTIVE_RUNS.with(|runs| runs.borrow().last().map(|r| r.run_id()))
+}
+
+/// Ends the innermost active run as `FINISHED`. Does nothing if there is no active run.
+pub fn end_run() -> MLFlowResult<()> {
+    end_run_with_status(RunStatus::Finished)
+}
+
+pub fn end_run_with_status(status: RunStatus) -> MLFlowResult<()> {
+    match ACTIVE_RUNS.with(|runs| runs.borrow_mut().pop()) {
+        Some(run) => run.end(status),
+        None => Ok(()),
+    }
+}
+
+fn with_active_run<T>(f: impl FnOnce(&Run) -> MLFlowResult<T>) -> MLFlowResult<T> {
+    if active_run_id().is_none() {
+        push_run(None)?;
+    }
+    ACTIVE_RUNS.with(|runs| match runs.borrow().last() {
+        Some(run) => f(run),
+        None => Err(RunBuilderError("no active run".to_string())),
+    })
+}
+
+pub fn log_metric(
+    key: impl AsRef<str>,
+    value: f64,
+    step: Option<i64>,
+    timestamp: Option<i64>,
+) -> MLFlowResult<()> {
+    with_active_run(|run| run.log_metric(key, value, step, timestamp))
+}
+
+pub fn log_param(key: impl AsRef<str>, value: impl ToString) -> MLFlowResult<()> {
+    with_active_run(|run| run.log_param(key, value))
+}
+
+/// Logs several metrics to the active run, see [`Run::log_metrics`].
+pub fn log_metrics<K: AsRef<str>>(
+    metrics: impl IntoIterator<Item = (K, f64)>,
+    step: Option<i64>,
+) -> MLFlowResult<()> {
+    with_active_run(|run| run.log_metrics(metrics, step))
+}
+
+/// Logs the fields of a config struct or map to the active run, see [`Run::log_params`].
+pub fn log_params(params: &impl serde::Serialize) -> MLFlowResult<()> {
+    with_active_run(|run| run.log_params(params))
+}
+
+pub fn set_tag(key: impl AsRef<str>, value: impl ToString) -> MLFlowResult<()> {
+    with_active_run(|run| run.set_tag(key, value))
+}
+
+pub fn log_batch(metrics: Vec<Metric>, params: Vec<Param>, tags: Vec<RunTag>) -> MLFlowResult<()> {
+    with_active_run(|run| run.log_batch(metrics, params, tags))
+}
+
+pub fn log_artifact(local_path: impl AsRef<Path>, artifact_path: Option<&str>) -> MLFlowResult<()> {
+    with_active_run(|run| run.log_artifact(local_path, artifact_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracking_uri_can_be_overridden() {
+        set_tracking_uri("http://tracking:5000");
+        assert_eq!(get_tracking_uri().unwrap(), "http://tracking:5000");
+    }
+
+    #[test]
+    fn no_active_run_by_default() {
+        assert_eq!(active_run_id(), None);
+        end_run().expect("ending without an active run is a no-op");
+    }
+}
@@ -0,0 +1,228 @@
+//! Permanently removing deleted runs and experiments, like `mlflow gc`, so that stores tracked
+//! to by long lived jobs don't grow without bounds.
+
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{ExperimentInfo, MLFlowClient, SearchExperimentsRequest, SharedClient};
+use crate::config::default_client;
+use crate::entities::LifecycleStage;
+use crate::run::current_time_millis;
+use crate::search::RunViewType;
+use crate::MLFlowError::InvalidParameterValue;
+use crate::MLFlowResult;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The runs and experiments a [`GarbageCollector`] removed for good.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GarbageCollection {
+    /// Deleted runs purged on their own, not counting those of purged experiments.
+    pub run_ids: Vec<String>,
+    pub experiment_ids: Vec<String>,
+}
+
+/// Permanently removes soft-deleted runs and experiments along with their artifacts.
+///
+/// Only backends storing runs themselves, the file and SQLite stores, support it. For a tracking
+/// server, run `mlflow gc` against its backend store instead.
+///
+/// ```no_run
+/// use mlflow_rs::GarbageCollector;
+/// use std::time::Duration;
+///
+/// let collected = GarbageCollector::default()
+///     .with_older_than(Duration::from_secs(30 * 24 * 3600))
+///     .collect()
+///     .unwrap();
+/// println!("purged {} runs", collected.run_ids.len());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GarbageCollector {
+    older_than: Option<Duration>,
+    experiment_ids: Vec<String>,
+    client: Option<SharedClient>,
+}
+
+impl GarbageCollector {
+    /// Only removes runs and experiments deleted at least `older_than` ago.
+    pub fn with_older_than(mut self, older_than: Duration) -> Self {
+        self.older_than = Some(older_than);
+        self
+    }
+
+    /// Only removes these experiments, which must be deleted, and the deleted runs within them.
+    /// By default every deleted experiment and run is removed.
+    pub fn with_experiment_ids(mut self, experiment_ids: Vec<impl AsRef<str>>) -> Self {
+        self.experiment_ids = experiment_ids
+            .iter()
+            .map(|id| id.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn collect(self) -> MLFlowResult<GarbageCollection> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+        let older_than_ms = self
+            .older_than
+            .map(|d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+            .unwrap_or_default();
+        let deleted_before = current_time_millis().saturating_sub(older_than_ms);
+
+        let experiments = match self.experiment_ids.is_empty() {
+            true => deleted_experiments(&client)?
+                .into_iter()
+                .filter(|e| older_than_ms == 0 || e.last_update_time <= Some(deleted_before))
+                .collect(),
+            false => {
+                let mut experiments = vec![];
+                for experiment_id in &self.experiment_ids {
+                    let experiment = client.get_experiment_by_id(experiment_id)?.experiment;
+                    if experiment.lifecycle_stage != Some(LifecycleStage::Deleted) {
+                        return Err(InvalidParameterValue(format!(
+                            "experiment {} is not deleted",
+                            experiment_id
+                        )));
+                    }
+                    if older_than_ms > 0 && experiment.last_update_time > Some(deleted_before) {
+                        return Err(InvalidParameterValue(format!(
+                            "experiment {} was deleted less than {:?} ago",
+                            experiment_id,
+                            self.older_than.unwrap_or_default()
+                        )));
+                    }
+                    experiments.push(experiment);
+                }
+                experiments
+            }
+        };
+
+        let mut collected = GarbageCollection::default();
+        for run in client.deleted_runs(older_than_ms)? {
+            let purged_with_experiment = experiments
+                .iter()
+                .any(|e| e.experiment_id == run.experiment_id);
+            let selected =
+                self.experiment_ids.is_empty() || self.experiment_ids.contains(&run.experiment_id);
+            if selected && !purged_with_experiment {
+                client.purge_run(&run.run_id)?;
+                collected.run_ids.push(run.run_id);
+            }
+        }
+        for experiment in experiments {
+            client.purge_experiment(&experiment.experiment_id)?;
+            collected.experiment_ids.push(experiment.experiment_id);
+        }
+        Ok(collected)
+    }
+}
+
+fn deleted_experiments(client: &SharedClient) -> MLFlowResult<Vec<ExperimentInfo>> {
+    let mut request = SearchExperimentsRequest {
+        filter: None,
+        view_type: RunViewType::DeletedOnly,
+        max_results: None,
+        order_by: vec![],
+        page_token: None,
+    };
+    let mut experiments = vec![];
+    loop {
+        let resp = client.search_experiments(&request)?;
+        experiments.extend(resp.experiments);
+        match resp.next_page_token {
+            Some(token) if !token.is_empty() => request.page_token = Some(token),
+            _ => return Ok(experiments),
+        }
+    }
+}
+
+/// Permanently removes the runs and experiments deleted at least `older_than` ago, by default
+/// all of them, restricted to `experiment_ids` unless it is empty. See [`GarbageCollector`].
+pub fn gc(
+    older_than: Option<Duration>,
+    experiment_ids: Vec<impl AsRef<str>>,
+) -> MLFlowResult<GarbageCollection> {
+    let mut collector = GarbageCollector::default().with_experiment_ids(experiment_ids);
+    if let Some(older_than) = older_than {
+        collector = collector.with_older_than(older_than);
+    }
+    collector.collect()
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{ExperimentBuilder, ExperimentIdentifier, MockMLFlowClient, RunBuilder, RunLoader};
+
+    #[test]
+    fn purges_deleted_runs_and_experiments() {
+        let mock = MockMLFlowClient::default();
+        let run = |experiment_id: &str| {
+            RunBuilder::new(experiment_id)
+                .unwrap()
+                .with_mock_client(mock.clone())
+                .build()
+                .unwrap()
+        };
+        let mut experiment = ExperimentBuilder::new("old")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let experiment_id = experiment.experiment_id().unwrap();
+        let in_experiment = run(&experiment_id);
+        let kept = run("0");
+        let mut deleted = run("0");
+        deleted.delete().unwrap();
+        experiment.delete().unwrap();
+
+        let collector = GarbageCollector::default().with_mock_client(mock.clone());
+        let collected = collector
+            .clone()
+            .with_older_than(Duration::from_secs(3600))
+            .collect()
+            .unwrap();
+        assert_eq!(
+            collected,
+            GarbageCollection::default(),
+            "nothing is old enough"
+        );
+
+        let collected = collector.clone().collect().unwrap();
+        assert_eq!(collected.run_ids, vec![deleted.info().run_id.clone()]);
+        assert_eq!(collected.experiment_ids, vec![experiment_id]);
+        let loader = || RunLoader::default().with_mock_client(mock.clone());
+        loader().load(&deleted.info().run_id).expect_err("purged");
+        loader()
+            .load(&in_experiment.info().run_id)
+            .expect_err("purged with its experiment");
+        loader().load(&kept.info().run_id).unwrap();
+
+        collector
+            .with_experiment_ids(vec!["0"])
+            .collect()
+            .expect_err("the default experiment is not deleted");
+    }
+}
@@ -0,0 +1,149 @@
+//! Periodic liveness tag for long-running runs.
+
+use crate::active_run::ActiveRun;
+use crate::entities::RunTag;
+use crate::run::{current_time_millis, Run};
+use crate::tags::MLFLOW_RS_LAST_HEARTBEAT;
+use crate::MLFlowError::UnknownError;
+use crate::MLFlowResult;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Sets the [`MLFLOW_RS_LAST_HEARTBEAT`] tag of a run to the current time in milliseconds every
+/// interval, from a background thread, until dropped.
+///
+/// A run still `RUNNING` whose heartbeat is older than a few intervals belongs to a worker that
+/// died without ending it, which orchestration can detect with [`Run::last_heartbeat`].
+/// Failures are logged and retried on the next beat.
+///
+/// ```no_run
+/// use mlflow_rs::RunBuilder;
+/// use std::time::Duration;
+///
+/// let run = RunBuilder::new("1")
+///     .unwrap()
+///     .start()
+///     .unwrap()
+///     .with_heartbeat(Duration::from_secs(30))
+///     .unwrap();
+/// // the heartbeat stops when the run ends
+/// ```
+#[derive(Debug)]
+pub struct Heartbeat {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Stops the heartbeat, waiting for a beat in flight.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl Run {
+    /// Starts a [`Heartbeat`] beating now and then every `interval`.
+    pub fn start_heartbeat(&self, interval: Duration) -> MLFlowResult<Heartbeat> {
+        let (stop, stopped) = channel::<()>();
+        let run_id = self.info.run_id.clone();
+        let client = self.client.clone();
+
+        let handle = std::thread::Builder::new()
+            .name("mlflow-heartbeat".to_string())
+            .spawn(move || loop {
+                let tag = RunTag {
+                    key: MLFLOW_RS_LAST_HEARTBEAT.to_string(),
+                    value: current_time_millis().to_string(),
+                };
+                if let Err(e) = client.set_tag(&run_id, &tag) {
+                    log::warn!("heartbeat of run {} failed: {}", run_id, e);
+                }
+                match stopped.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => return,
+                }
+            })
+            .map_err(|e| UnknownError(format!("could not start heartbeat: {}", e)))?;
+
+        Ok(Heartbeat {
+            stop: Some(stop),
+            handle: Some(handle),
+        })
+    }
+
+    /// When the run's last [`Heartbeat`] beat, in milliseconds since the UNIX epoch, as of when
+    /// the run was loaded. `None` for runs without a heartbeat.
+    pub fn last_heartbeat(&self) -> Option<i64> {
+        self.data
+            .tags
+            .iter()
+            .find(|t| t.key == MLFLOW_RS_LAST_HEARTBEAT)
+            .and_then(|t| t.value.parse().ok())
+    }
+}
+
+impl ActiveRun {
+    /// Keeps a [`Heartbeat`] beating every `interval` until the run ends.
+    pub fn with_heartbeat(mut self, interval: Duration) -> MLFlowResult<Self> {
+        self.heartbeat = Some(self.start_heartbeat(interval)?);
+        Ok(self)
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder, RunLoader, RunStatus};
+    use std::time::Instant;
+
+    #[test]
+    fn beats_until_the_run_ends() {
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .start()
+            .unwrap()
+            .with_heartbeat(Duration::from_millis(10))
+            .unwrap();
+        let run_id = run.info().run_id.clone();
+        let load = || {
+            RunLoader::default()
+                .with_mock_client(mock.clone())
+                .load(&run_id)
+                .unwrap()
+        };
+
+        let beat = |after: Option<i64>| {
+            let started = Instant::now();
+            loop {
+                match load().last_heartbeat() {
+                    Some(beat) if Some(beat) != after => return beat,
+                    _ => assert!(started.elapsed() < Duration::from_secs(5), "never beat"),
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        };
+        let first = beat(None);
+        beat(Some(first));
+
+        run.end(RunStatus::Finished).unwrap();
+        let last = load().last_heartbeat();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(load().last_heartbeat(), last, "stops with the run");
+    }
+}
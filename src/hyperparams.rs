@@ -0,0 +1,147 @@
+//! Hyperparameter structs logged as params, usually through `#[derive(MlflowParams)]` from the
+//! `derive` feature.
+//!
+#![cfg_attr(feature = "derive", doc = "```no_run")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use mlflow_rs::{MlflowParams, RunBuilder};
+//!
+//! #[derive(MlflowParams)]
+//! struct Optimizer {
+//!     #[mlflow(rename = "lr")]
+//!     learning_rate: f64,
+//!     momentum: Option<f64>,
+//! }
+//!
+//! #[derive(MlflowParams)]
+//! struct Config {
+//!     epochs: u32,
+//!     #[mlflow(flatten)]
+//!     optimizer: Optimizer,
+//!     #[mlflow(skip)]
+//!     data_dir: std::path::PathBuf,
+//! }
+//!
+//! let config = Config {
+//!     epochs: 10,
+//!     optimizer: Optimizer { learning_rate: 0.01, momentum: None },
+//!     data_dir: "/data".into(),
+//! };
+//! let run = RunBuilder::new("0").unwrap().build().unwrap();
+//! // logs epochs and optimizer.lr, and optimizer.momentum when it is set
+//! run.log_hyperparams(&config).unwrap();
+//! ```
+
+use crate::entities::Param;
+use std::path::{Path, PathBuf};
+
+/// A value that can be turned into a list of params, such as a struct of hyperparameters.
+pub trait MlflowParams {
+    fn to_params(&self) -> Vec<Param>;
+}
+
+/// The param value of a field of a [`MlflowParams`] struct, or `None` to leave the field out.
+/// Implement it for custom field types to use them in derived structs.
+pub trait ParamValue {
+    fn param_value(&self) -> Option<String>;
+}
+
+macro_rules! display_param_value {
+    ($($t:ty),*) => {
+        $(
+            impl ParamValue for $t {
+                fn param_value(&self) -> Option<String> {
+                    Some(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+display_param_value!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, char, str,
+    String
+);
+
+impl ParamValue for Path {
+    fn param_value(&self) -> Option<String> {
+        Some(self.display().to_string())
+    }
+}
+
+impl ParamValue for PathBuf {
+    fn param_value(&self) -> Option<String> {
+        self.as_path().param_value()
+    }
+}
+
+impl<T: ParamValue + ?Sized> ParamValue for &T {
+    fn param_value(&self) -> Option<String> {
+        (**self).param_value()
+    }
+}
+
+impl<T: ParamValue> ParamValue for Option<T> {
+    fn param_value(&self) -> Option<String> {
+        self.as_ref().and_then(ParamValue::param_value)
+    }
+}
+
+/// Sequences are logged the way Python prints lists, e.g. `[64, 32]`.
+impl<T: ParamValue> ParamValue for Vec<T> {
+    fn param_value(&self) -> Option<String> {
+        let values: Vec<String> = self.iter().filter_map(ParamValue::param_value).collect();
+        Some(format!("[{}]", values.join(", ")))
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::{MlflowParams, Param};
+
+    #[derive(MlflowParams)]
+    struct Optimizer {
+        #[mlflow(rename = "lr")]
+        learning_rate: f64,
+        momentum: Option<f64>,
+    }
+
+    #[derive(MlflowParams)]
+    struct Config<'a> {
+        epochs: u32,
+        r#type: &'a str,
+        hidden_layers: Vec<usize>,
+        #[mlflow(flatten)]
+        optimizer: Optimizer,
+        #[mlflow(skip)]
+        #[allow(dead_code)]
+        data_dir: std::path::PathBuf,
+    }
+
+    #[test]
+    fn derived_params() {
+        let config = Config {
+            epochs: 10,
+            r#type: "mlp",
+            hidden_layers: vec![64, 32],
+            optimizer: Optimizer {
+                learning_rate: 0.01,
+                momentum: None,
+            },
+            data_dir: "/data".into(),
+        };
+        let params: Vec<(String, String)> = config
+            .to_params()
+            .into_iter()
+            .map(|Param { key, value }| (key, value))
+            .collect();
+        assert_eq!(
+            params,
+            vec![
+                ("epochs".to_string(), "10".to_string()),
+                ("type".to_string(), "mlp".to_string()),
+                ("hidden_layers".to_string(), "[64, 32]".to_string()),
+                ("optimizer.lr".to_string(), "0.01".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Logging for `burn`'s training loop: [`BurnMetricLogger`] is a metric logger the learner
+//! reports every metric to, alongside or instead of its default file logger.
+//!
+//! ```ignore
+//! // with burn_train's `LearnerBuilder` and metrics, and a model and optimizer
+//! use mlflow_rs::integrations::burn::BurnMetricLogger;
+//! use mlflow_rs::RunBuilder;
+//!
+//! let run = RunBuilder::new("1").unwrap().start().unwrap();
+//! let learner = LearnerBuilder::new("./artifacts")
+//!     .metric_train_numeric(LossMetric::new())
+//!     .metric_valid_numeric(AccuracyMetric::new())
+//!     .with_metric_logger(BurnMetricLogger::new(&run).unwrap())
+//!     .build(model, optimizer, learning_rate);
+//! ```
+
+use crate::buffered::{BufferedLogger, BufferedLoggerBuilder};
+use crate::run::Run;
+use crate::MLFlowResult;
+use burn_train::logger::{InMemoryMetricLogger, MetricLogger};
+use burn_train::metric::store::{EpochSummary, MetricsUpdate, Split};
+use burn_train::metric::{MetricDefinition, MetricId, NumericEntry};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A `burn_train` metric logger sending the learner's numeric metrics to a run through a
+/// [`BufferedLogger`], so training doesn't wait on the tracking server.
+///
+/// Each metric is logged as `<split>/<name>`, e.g. `train/loss` or `valid/accuracy`, with the
+/// name lowercased and spaces replaced by underscores, at the split's iteration as its step.
+/// At the end of an epoch its running value, the mean over the epoch, is logged as
+/// `<split>/epoch_<name>` at the epoch's number.
+///
+/// Values are also kept in memory for the learner's early stopping and checkpointing
+/// strategies to read.
+#[derive(Debug)]
+pub struct BurnMetricLogger {
+    logger: BufferedLogger,
+    names: HashMap<MetricId, String>,
+    iterations: HashMap<Split, i64>,
+    /// The running value of each metric of the epoch in progress, by key.
+    running: HashMap<String, f64>,
+    in_memory: InMemory,
+}
+
+/// The [`InMemoryMetricLogger`], which doesn't implement `Debug`.
+struct InMemory(InMemoryMetricLogger);
+
+impl std::fmt::Debug for InMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InMemoryMetricLogger")
+    }
+}
+
+impl BurnMetricLogger {
+    /// Starts a [`BufferedLogger`] for `run` with the default flush interval and batch size.
+    pub fn new(run: &Run) -> MLFlowResult<BurnMetricLogger> {
+        Ok(BurnMetricLogger::with_logger(
+            BufferedLoggerBuilder::new(run).build()?,
+        ))
+    }
+
+    /// Logs through `logger`, e.g. one with a shorter flush interval.
+    pub fn with_logger(logger: BufferedLogger) -> BurnMetricLogger {
+        BurnMetricLogger {
+            logger,
+            names: HashMap::new(),
+            iterations: HashMap::new(),
+            running: HashMap::new(),
+            in_memory: InMemory(InMemoryMetricLogger::new()),
+        }
+    }
+
+    fn key(&self, metric_id: &MetricId, split: Split, tag: Option<&str>) -> Option<String> {
+        let name = self.names.get(metric_id)?.to_lowercase().replace(' ', "_");
+        Some(match tag {
+            Some(tag) => format!("{}/{}/{}", split, tag.trim().replace(' ', "_"), name),
+            None => format!("{}/{}", split, name),
+        })
+    }
+}
+
+impl MetricLogger for BurnMetricLogger {
+    fn log(&mut self, update: MetricsUpdate, epoch: usize, split: Split, tag: Option<Arc<String>>) {
+        let iteration = self.iterations.entry(split).or_default();
+        let step = *iteration;
+        *iteration += 1;
+
+        let tag_name = tag.as_deref().map(String::as_str);
+        for numeric in &update.entries_numeric {
+            let Some(key) = self.key(&numeric.entry.metric_id, split, tag_name) else {
+                continue;
+            };
+            let value = numeric.numeric_entry.current();
+            if let Err(e) = self.logger.log_metric(&key, value, Some(step), None) {
+                log::warn!("could not log {}: {}", key, e);
+            }
+            self.running.insert(key, numeric.running_entry.current());
+        }
+        self.in_memory.0.log(update, epoch, split, tag);
+    }
+
+    fn read_numeric(
+        &mut self,
+        name: &str,
+        epoch: usize,
+        split: Split,
+    ) -> Result<Vec<NumericEntry>, String> {
+        self.in_memory.0.read_numeric(name, epoch, split)
+    }
+
+    fn log_metric_definition(&mut self, definition: MetricDefinition) {
+        self.names
+            .insert(definition.metric_id.clone(), definition.name.clone());
+        self.in_memory.0.log_metric_definition(definition);
+    }
+
+    fn log_epoch_summary(&mut self, summary: EpochSummary) {
+        let prefix = format!("{}/", summary.split);
+        let epoch_keys: Vec<String> = self
+            .running
+            .keys()
+            .filter(|key| key.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for key in epoch_keys {
+            let value = self.running.remove(&key).unwrap_or_default();
+            let (split, name) = key.rsplit_once('/').unwrap_or(("", &key));
+            let epoch_key = format!("{}/epoch_{}", split, name);
+            let step = Some(summary.epoch_number as i64);
+            if let Err(e) = self.logger.log_metric(&epoch_key, value, step, None) {
+                log::warn!("could not log {}: {}", epoch_key, e);
+            }
+        }
+        if let Err(e) = self.logger.flush() {
+            log::warn!(
+                "could not log the metrics of epoch {}: {}",
+                summary.epoch_number,
+                e
+            );
+        }
+        self.in_memory.0.log_epoch_summary(summary);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder};
+    use burn_train::metric::store::NumericMetricUpdate;
+    use burn_train::metric::{MetricAttributes, MetricEntry, SerializedEntry};
+
+    fn update(id: &MetricId, value: f64, running: f64) -> MetricsUpdate {
+        let entry = MetricEntry::new(
+            id.clone(),
+            SerializedEntry::new(value.to_string(), value.to_string()),
+        );
+        MetricsUpdate::new(
+            vec![],
+            vec![NumericMetricUpdate::new(
+                entry,
+                NumericEntry::Value(value),
+                NumericEntry::Value(running),
+            )],
+        )
+    }
+
+    #[test]
+    fn logs_learner_metrics() {
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let mut logger = BurnMetricLogger::new(&run).unwrap();
+        let loss = MetricId::new(Arc::new("Loss".to_string()));
+        logger.log_metric_definition(MetricDefinition {
+            metric_id: loss.clone(),
+            name: "Loss".to_string(),
+            description: None,
+            attributes: MetricAttributes::None,
+        });
+
+        logger.log(update(&loss, 0.9, 0.9), 1, Split::Train, None);
+        logger.log(update(&loss, 0.5, 0.7), 1, Split::Train, None);
+        logger.log(update(&loss, 0.6, 0.6), 1, Split::Valid, None);
+        logger.log_epoch_summary(EpochSummary::new(1, Split::Train));
+
+        let steps = |key: &str| {
+            run.get_metric_history(key)
+                .unwrap()
+                .iter()
+                .map(|m| (m.step, m.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(steps("train/loss"), vec![(0, 0.9), (1, 0.5)]);
+        assert_eq!(steps("train/epoch_loss"), vec![(1, 0.7)]);
+        assert_eq!(steps("valid/loss"), vec![(0, 0.6)]);
+        assert!(
+            steps("valid/epoch_loss").is_empty(),
+            "its epoch hasn't ended"
+        );
+        assert_eq!(
+            logger.read_numeric("Loss", 1, Split::Train).unwrap().len(),
+            2
+        );
+    }
+}
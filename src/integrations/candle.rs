@@ -0,0 +1,201 @@
+//! Logging for training loops written with `candle`, which has no callback system of its own:
+//! [`CandleAutolog`] takes the place of the calls a loop already makes.
+//!
+//! ```no_run
+//! use candle_core::{DType, Device, Tensor};
+//! use candle_nn::{linear, loss, Module, Optimizer, VarBuilder, VarMap, SGD};
+//! use mlflow_rs::integrations::candle::CandleAutolog;
+//! use mlflow_rs::RunBuilder;
+//!
+//! let run = RunBuilder::new("1").unwrap().start().unwrap();
+//! let varmap = VarMap::new();
+//! let vb = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+//! let model = linear(4, 1, vb).unwrap();
+//! let mut sgd = SGD::new(varmap.all_vars(), 0.01).unwrap();
+//! let autolog = CandleAutolog::new(&run).with_log_every(10);
+//!
+//! let xs = Tensor::zeros((8, 4), DType::F32, &Device::Cpu).unwrap();
+//! let ys = Tensor::ones((8, 1), DType::F32, &Device::Cpu).unwrap();
+//! for _ in 0..5 {
+//!     autolog.start_epoch();
+//!     let loss = loss::mse(&model.forward(&xs).unwrap(), &ys).unwrap();
+//!     autolog.backward_step(&mut sgd, &loss).unwrap();
+//!     autolog.end_epoch().unwrap();
+//! }
+//! autolog.log_weights(&varmap).unwrap();
+//! ```
+
+use crate::integrations::log_saved_file;
+use crate::metric_logger::{MetricLogger, StepMode};
+use crate::run::Run;
+use crate::MLFlowError::IntegrationError;
+use crate::MLFlowResult;
+use candle_core::{DType, Tensor};
+use candle_nn::{Optimizer, VarMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The artifact directory [`CandleAutolog::log_weights`] uploads the weights to by default.
+pub const DEFAULT_WEIGHTS_PATH: &str = "model";
+/// The file the weights are stored in, in the safetensors format.
+pub const WEIGHTS_FILE_NAME: &str = "model.safetensors";
+
+#[derive(Debug, Default)]
+struct Epoch {
+    number: i64,
+    started: Option<Instant>,
+}
+
+/// Logs a candle training loop to a run: the `loss` and `learning_rate` metrics at each
+/// optimizer step, `epoch_time_s` for each epoch and the trained weights as an artifact.
+///
+/// Metrics are logged at the optimizer step count, continuing after the run's latest step when
+/// it was [resumed](Run::resume).
+#[derive(Debug)]
+pub struct CandleAutolog<'a> {
+    run: &'a Run,
+    metrics: MetricLogger<'a>,
+    log_every: i64,
+    weights_path: String,
+    epoch: Mutex<Epoch>,
+}
+
+impl<'a> CandleAutolog<'a> {
+    pub fn new(run: &'a Run) -> CandleAutolog<'a> {
+        CandleAutolog {
+            run,
+            metrics: MetricLogger::new(run).with_mode(StepMode::Shared),
+            log_every: 1,
+            weights_path: DEFAULT_WEIGHTS_PATH.to_string(),
+            epoch: Mutex::new(Epoch::default()),
+        }
+    }
+
+    /// Logs the loss and learning rate of every `steps`th optimizer step only, to keep tight
+    /// loops from waiting on the tracking server. Defaults to every step.
+    pub fn with_log_every(mut self, steps: usize) -> Self {
+        self.log_every = steps.max(1) as i64;
+        self
+    }
+
+    /// The artifact directory [`log_weights`](Self::log_weights) uploads to.
+    pub fn with_weights_path(mut self, artifact_path: impl AsRef<str>) -> Self {
+        self.weights_path = artifact_path.as_ref().to_string();
+        self
+    }
+
+    /// Runs `optimizer.backward_step(loss)` and logs the loss, a scalar tensor, and the
+    /// optimizer's learning rate. Returns the loss.
+    pub fn backward_step(
+        &self,
+        optimizer: &mut impl Optimizer,
+        loss: &Tensor,
+    ) -> MLFlowResult<f64> {
+        let value = loss
+            .to_dtype(DType::F64)
+            .and_then(|loss| loss.to_scalar::<f64>())
+            .map_err(|e| IntegrationError(format!("loss is not a scalar: {}", e)))?;
+        optimizer
+            .backward_step(loss)
+            .map_err(|e| IntegrationError(e.to_string()))?;
+
+        if self.metrics.step() % self.log_every == 0 {
+            self.metrics.log_all([
+                ("loss", value),
+                ("learning_rate", optimizer.learning_rate()),
+            ])?;
+        }
+        self.metrics.advance();
+        Ok(value)
+    }
+
+    /// The number of optimizer steps taken so far.
+    pub fn step(&self) -> i64 {
+        self.metrics.step()
+    }
+
+    /// Starts timing an epoch.
+    pub fn start_epoch(&self) {
+        self.lock().started = Some(Instant::now());
+    }
+
+    /// Logs how long the epoch started with [`start_epoch`](Self::start_epoch) took as the
+    /// `epoch_time_s` metric, at the epoch's number as its step.
+    pub fn end_epoch(&self) -> MLFlowResult<Duration> {
+        let mut epoch = self.lock();
+        let elapsed = epoch
+            .started
+            .take()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        self.run.log_metric(
+            "epoch_time_s",
+            elapsed.as_secs_f64(),
+            Some(epoch.number),
+            None,
+        )?;
+        epoch.number += 1;
+        Ok(elapsed)
+    }
+
+    /// Uploads the variables of `varmap` as `model.safetensors` into the weights path, `model`
+    /// by default, loadable with `VarMap::load` or the `safetensors` Python package.
+    pub fn log_weights(&self, varmap: &VarMap) -> MLFlowResult<()> {
+        log_saved_file(self.run, &self.weights_path, WEIGHTS_FILE_NAME, |file| {
+            varmap.save(file)
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Epoch> {
+        self.epoch.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder};
+    use candle_core::Device;
+    use candle_nn::{linear, loss, Module, VarBuilder, SGD};
+
+    #[test]
+    fn logs_a_training_loop() {
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+        let model = linear(2, 1, vb).unwrap();
+        let mut sgd = SGD::new(varmap.all_vars(), 0.1).unwrap();
+        let xs = Tensor::new(&[[1f32, 0.0], [0.0, 1.0], [1.0, 1.0]], &Device::Cpu).unwrap();
+        let ys = Tensor::new(&[[1f32], [2.0], [3.0]], &Device::Cpu).unwrap();
+
+        let autolog = CandleAutolog::new(&run).with_log_every(2);
+        for _ in 0..2 {
+            autolog.start_epoch();
+            for _ in 0..3 {
+                let loss = loss::mse(&model.forward(&xs).unwrap(), &ys).unwrap();
+                autolog.backward_step(&mut sgd, &loss).unwrap();
+            }
+            autolog.end_epoch().unwrap();
+        }
+        autolog.log_weights(&varmap).unwrap();
+
+        let losses = run.get_metric_history("loss").unwrap();
+        let steps: Vec<i64> = losses.iter().map(|m| m.step).collect();
+        assert_eq!(steps, vec![0, 2, 4]);
+        assert!(losses[2].value < losses[0].value, "the model trains");
+        assert_eq!(
+            run.get_metric_history("learning_rate").unwrap()[0].value,
+            0.1
+        );
+        assert_eq!(run.get_metric_history("epoch_time_s").unwrap().len(), 2);
+        assert_eq!(autolog.step(), 6);
+        assert_eq!(
+            run.list_artifacts(Some("model")).unwrap()[0].path,
+            "model/model.safetensors"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Logging for classical ML experiments with `linfa`: [`LinfaAutolog`] logs a model's
+//! hyperparameters, its cross-validation scores and the fitted model in one call each.
+//!
+//! linfa's hyperparameter and model types implement `serde`'s traits with the `serde` feature of
+//! its algorithm crates, and cross-validation scores are plain arrays, so this feature doesn't
+//! depend on linfa itself and works with any of its versions.
+//!
+//! ```ignore
+//! // with linfa, linfa-trees (with its `serde` feature) and a `dataset`
+//! use linfa::prelude::*;
+//! use linfa_trees::DecisionTree;
+//! use mlflow_rs::integrations::linfa::LinfaAutolog;
+//! use mlflow_rs::RunBuilder;
+//!
+//! let run = RunBuilder::new("1").unwrap().start().unwrap();
+//! let autolog = LinfaAutolog::new(&run);
+//! let params = DecisionTree::params().max_depth(Some(4));
+//! autolog.log_params(&params).unwrap();
+//!
+//! let scores = dataset
+//!     .cross_validate_single(5, &[params.clone()], |model, valid| {
+//!         Ok(model.predict(valid).confusion_matrix(valid)?.accuracy())
+//!     })
+//!     .unwrap();
+//! autolog.log_cross_validation("accuracy", scores.iter().copied()).unwrap();
+//!
+//! let model = params.fit(&dataset).unwrap();
+//! autolog.log_model(&model).unwrap();
+//! ```
+
+use crate::entities::Metric;
+use crate::model::{Flavor, ModelInfo};
+use crate::run::{current_time_millis, Run};
+use crate::MLFlowError::{IntegrationError, InvalidMetric};
+use crate::MLFlowResult;
+use serde::Serialize;
+
+/// The flavor name the `MLmodel` file of a model logged with [`LinfaAutolog::log_model`] lists.
+pub const FLAVOR_NAME: &str = "linfa";
+/// The artifact directory [`LinfaAutolog::log_model`] logs the model to by default.
+pub const DEFAULT_MODEL_PATH: &str = "model";
+/// The file the model is stored in, as JSON.
+pub const MODEL_FILE_NAME: &str = "model.json";
+
+/// The scores of each fold of a cross-validation, with their mean and standard deviation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossValidation {
+    pub scores: Vec<f64>,
+    pub mean: f64,
+    /// The population standard deviation, like scikit-learn's `std_test_score`.
+    pub std: f64,
+}
+
+/// Logs a linfa experiment to a run: the hyperparameters as params, cross-validation scores as
+/// metrics and the fitted model as a model artifact.
+#[derive(Clone, Debug)]
+pub struct LinfaAutolog<'a> {
+    run: &'a Run,
+    model_path: String,
+}
+
+impl<'a> LinfaAutolog<'a> {
+    pub fn new(run: &'a Run) -> LinfaAutolog<'a> {
+        LinfaAutolog {
+            run,
+            model_path: DEFAULT_MODEL_PATH.to_string(),
+        }
+    }
+
+    /// The artifact directory [`log_model`](Self::log_model) logs to.
+    pub fn with_model_path(mut self, artifact_path: impl AsRef<str>) -> Self {
+        self.model_path = artifact_path.as_ref().to_string();
+        self
+    }
+
+    /// Logs the fields of a hyperparameter struct, such as `DecisionTree::params()`, as params,
+    /// nested fields keyed by their dotted path. Unset optional fields are left out.
+    pub fn log_params(&self, params: &impl Serialize) -> MLFlowResult<()> {
+        self.run.log_params(params)
+    }
+
+    /// Logs the score of each cross-validation fold as the `cv/<metric>` metric, at the fold's
+    /// index as its step, and their mean and standard deviation as `cv/<metric>/mean` and
+    /// `cv/<metric>/std`, in one request.
+    pub fn log_cross_validation<V: Into<f64>>(
+        &self,
+        metric: impl AsRef<str>,
+        scores: impl IntoIterator<Item = V>,
+    ) -> MLFlowResult<CrossValidation> {
+        let scores: Vec<f64> = scores.into_iter().map(Into::into).collect();
+        if scores.is_empty() {
+            return Err(InvalidMetric(format!(
+                "no cross-validation scores for {}",
+                metric.as_ref()
+            )));
+        }
+        let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+        let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / scores.len() as f64;
+        let cv = CrossValidation {
+            scores,
+            mean,
+            std: variance.sqrt(),
+        };
+
+        let key = format!("cv/{}", metric.as_ref());
+        let timestamp = current_time_millis();
+        let mut metrics: Vec<Metric> = cv
+            .scores
+            .iter()
+            .enumerate()
+            .map(|(fold, &score)| Metric::new(&key, score, Some(fold as i64), Some(timestamp)))
+            .collect();
+        metrics.push(Metric::new(
+            format!("{}/mean", key),
+            cv.mean,
+            None,
+            Some(timestamp),
+        ));
+        metrics.push(Metric::new(
+            format!("{}/std", key),
+            cv.std,
+            None,
+            Some(timestamp),
+        ));
+        self.run.log_batch(metrics, vec![], vec![])?;
+        Ok(cv)
+    }
+
+    /// Logs a fitted model, serialized as [`MODEL_FILE_NAME`], as a model with the `linfa`
+    /// flavor in the model path, `model` by default. It loads back with `serde_json` into the
+    /// same linfa type.
+    pub fn log_model(&self, model: &impl Serialize) -> MLFlowResult<ModelInfo> {
+        let json = serde_json::to_vec(model).map_err(|e| IntegrationError(e.to_string()))?;
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", uuid::Uuid::new_v4()));
+        let file = dir.join(MODEL_FILE_NAME);
+        let flavor = Flavor::new(FLAVOR_NAME)
+            .with_config("data", MODEL_FILE_NAME)
+            .with_config("serialization_format", "json");
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(&file, json))
+            .map_err(|e| IntegrationError(e.to_string()))
+            .and_then(|_| self.run.log_model(&self.model_path, flavor, &file));
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder};
+
+    #[derive(Serialize)]
+    enum SplitQuality {
+        Gini,
+    }
+
+    #[derive(Serialize)]
+    struct TreeParams {
+        split_quality: SplitQuality,
+        max_depth: Option<usize>,
+        min_weight_split: f32,
+    }
+
+    #[derive(Serialize)]
+    struct Tree {
+        feature: usize,
+        threshold: f64,
+    }
+
+    #[test]
+    fn logs_params_scores_and_model() {
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let autolog = LinfaAutolog::new(&run);
+        autolog
+            .log_params(&TreeParams {
+                split_quality: SplitQuality::Gini,
+                max_depth: None,
+                min_weight_split: 2.0,
+            })
+            .unwrap();
+        let cv = autolog
+            .log_cross_validation("accuracy", [0.75f32, 1.0, 0.5])
+            .unwrap();
+        let info = autolog
+            .log_model(&Tree {
+                feature: 1,
+                threshold: 0.5,
+            })
+            .unwrap();
+
+        let run = crate::RunLoader::default()
+            .with_dyn_client(run.client.clone())
+            .load(run.info().run_id.clone())
+            .unwrap();
+        let params = &run.data().params;
+        assert!(params
+            .iter()
+            .any(|p| p.key == "split_quality" && p.value == "Gini"));
+        assert!(params.iter().all(|p| p.key != "max_depth"));
+        assert_eq!((cv.mean, cv.std), (0.75, (0.125f64 / 3.0).sqrt()));
+        let folds = run.get_metric_history("cv/accuracy").unwrap();
+        assert_eq!(
+            folds.iter().map(|m| m.step).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            run.get_metric_history("cv/accuracy/mean").unwrap()[0].value,
+            0.75
+        );
+        assert!(autolog
+            .log_cross_validation("f1", Vec::<f64>::new())
+            .is_err());
+
+        assert_eq!(info.flavors, vec![FLAVOR_NAME.to_string()]);
+        let files: Vec<_> = run
+            .list_artifacts(Some("model"))
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert!(files.contains(&"model/model.json".to_string()));
+        assert!(files.contains(&"model/MLmodel".to_string()));
+    }
+}
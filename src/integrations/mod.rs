@@ -0,0 +1,31 @@
+//! Hooks for the training loops of Rust ML frameworks that log their losses, learning rates,
+//! timings and trained weights, like MLflow's autologging does for Python frameworks. Each
+//! framework's hook is behind a feature of the same name.
+
+#[cfg(feature = "burn")]
+pub mod burn;
+#[cfg(feature = "candle")]
+pub mod candle;
+#[cfg(feature = "linfa")]
+pub mod linfa;
+
+/// Has `save` write the file `file_name` into a temporary directory and uploads it into the
+/// run's `artifact_path` directory.
+#[cfg(feature = "candle")]
+pub(crate) fn log_saved_file<E: std::fmt::Display>(
+    run: &crate::Run,
+    artifact_path: &str,
+    file_name: &str,
+    save: impl FnOnce(&std::path::Path) -> Result<(), E>,
+) -> crate::MLFlowResult<()> {
+    use crate::MLFlowError::IntegrationError;
+
+    let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", uuid::Uuid::new_v4()));
+    let file = dir.join(file_name);
+    let result = std::fs::create_dir_all(&dir)
+        .map_err(|e| IntegrationError(e.to_string()))
+        .and_then(|_| save(&file).map_err(|e| IntegrationError(e.to_string())))
+        .and_then(|_| run.log_artifact(&file, Some(artifact_path)));
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
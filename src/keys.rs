@@ -0,0 +1,105 @@
+//! Compile-time checked metric and param names.
+//!
+//! The [`metrics!`](crate::metrics) and [`params!`](crate::params) macros declare one unit
+//! struct per key. The structs can be passed wherever a key is expected, so a typo in a name
+//! is a compile error instead of a new, silently diverging metric.
+//!
+//! ```no_run
+//! use mlflow_rs::{metrics, params, MetricKey, RunBuilder};
+//!
+//! metrics! {
+//!     Loss => "loss",
+//!     /// Top-1 accuracy on the validation set.
+//!     pub Accuracy => "val_accuracy",
+//! }
+//! params! {
+//!     LearningRate => "lr",
+//! }
+//!
+//! let run = RunBuilder::new("0").unwrap().build().unwrap();
+//! run.log_param(LearningRate, 0.01).unwrap();
+//! run.log_metric(Loss, 0.25, Some(1), None).unwrap();
+//! assert_eq!(Accuracy::KEY, "val_accuracy");
+//! ```
+
+/// A metric name declared with [`metrics!`](crate::metrics).
+pub trait MetricKey: AsRef<str> + Copy {
+    const KEY: &'static str;
+}
+
+/// A param name declared with [`params!`](crate::params).
+pub trait ParamKey: AsRef<str> + Copy {
+    const KEY: &'static str;
+}
+
+/// Declares unit structs implementing [`MetricKey`](crate::MetricKey), one per
+/// `Name => "key"` entry. Entries may carry doc comments and a visibility.
+#[macro_export]
+macro_rules! metrics {
+    ($($(#[$meta:meta])* $vis:vis $name:ident => $key:literal),* $(,)?) => {
+        $(
+            $crate::__declare_key!($(#[$meta])* $vis $name => $key, $crate::MetricKey);
+        )*
+    };
+}
+
+/// Declares unit structs implementing [`ParamKey`](crate::ParamKey), one per
+/// `Name => "key"` entry. Entries may carry doc comments and a visibility.
+#[macro_export]
+macro_rules! params {
+    ($($(#[$meta:meta])* $vis:vis $name:ident => $key:literal),* $(,)?) => {
+        $(
+            $crate::__declare_key!($(#[$meta])* $vis $name => $key, $crate::ParamKey);
+        )*
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_key {
+    ($(#[$meta:meta])* $vis:vis $name:ident => $key:literal, $trait:path) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        $vis struct $name;
+
+        impl $trait for $name {
+            const KEY: &'static str = $key;
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                $key
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str($key)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MetricKey, ParamKey};
+
+    metrics! {
+        Loss => "loss",
+        /// Documented keys keep their docs.
+        pub(crate) Accuracy => "accuracy",
+    }
+    params! { Epochs => "epochs" }
+
+    #[test]
+    fn declared_keys() {
+        assert_eq!(Loss::KEY, "loss");
+        assert_eq!(Accuracy.as_ref(), "accuracy");
+        assert_eq!(Epochs::KEY, "epochs");
+        assert_eq!(format!("{}/{}", Loss, Epochs), "loss/epochs");
+        assert_eq!(
+            crate::Filter::metric(Loss).lt(0.5).build().unwrap(),
+            "metrics.loss < 0.5"
+        );
+    }
+}
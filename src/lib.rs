@@ -11,10 +11,175 @@
 //! ```
 //!
 
-use crate::MLFlowError::{ExperimentBuilderError, ExperimentNotFound, UnknownError};
-use reqwest::blocking::{Client, Response};
-use reqwest::StatusCode;
-use serde::{Deserialize, Serialize};
+#[cfg(all(feature = "blocking", target_arch = "wasm32"))]
+compile_error!("reqwest has no blocking client on wasm32, build with default-features = false");
+
+// lets the code generated by mlflow-rs-derive name the crate in its own tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as mlflow_rs;
+
+mod active_run;
+mod artifacts;
+#[cfg(feature = "async")]
+mod async_client;
+#[cfg(feature = "auth")]
+mod auth;
+#[cfg(feature = "azure")]
+mod azure;
+mod buffered;
+mod checkpoint;
+mod classification;
+pub mod client;
+#[cfg(feature = "arrow")]
+mod columnar;
+mod config;
+pub mod core;
+mod curves;
+#[cfg(feature = "blocking")]
+mod databricks;
+mod dataset;
+mod distribution;
+mod downsample;
+pub mod entities;
+mod evaluation;
+mod experiment;
+mod export;
+mod figure;
+mod file_store;
+mod filter;
+pub mod flavors;
+pub mod fluent;
+mod gc;
+mod heartbeat;
+mod hyperparams;
+pub mod integrations;
+mod keys;
+mod local;
+mod metric_logger;
+#[cfg(feature = "mock")]
+mod mock;
+mod model;
+mod names;
+#[cfg(feature = "blocking")]
+mod oauth;
+mod offline;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+mod pagination;
+mod query;
+mod rate_limit;
+mod registry;
+mod run;
+mod search;
+mod signature;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod sweep;
+mod table;
+pub mod tags;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod telemetry;
+mod termination;
+#[cfg(all(test, feature = "blocking"))]
+mod test_server;
+mod trace;
+mod transfer;
+mod validation;
+mod version;
+#[cfg(feature = "webhooks")]
+mod webhooks;
+
+pub use active_run::ActiveRun;
+pub use artifacts::{
+    register_artifact_repository, ArtifactRepository, ArtifactRepositoryFactory, FileInfo,
+};
+#[cfg(feature = "async")]
+pub use async_client::{AsyncMLFlowClient, MLFlowAsyncRestClient, MaybeSend};
+#[cfg(feature = "auth")]
+pub use auth::{
+    ExperimentPermission, Permission, RegisteredModelPermission, User, UserBuilder, UserLoader,
+};
+pub use buffered::{BufferedLogger, BufferedLoggerBuilder};
+pub use checkpoint::{
+    Checkpoint, CheckpointLogger, Objective, BEST_CHECKPOINT_TAG, CHECKPOINTS_DIR,
+};
+pub use classification::{
+    AverageMetrics, ClassMetrics, ClassificationReport, ConfusionMatrix,
+    CLASSIFICATION_REPORT_FILE_NAME, CONFUSION_MATRIX_FILE_NAME,
+};
+pub use client::MLFlowClient;
+#[cfg(feature = "blocking")]
+pub use client::{MLFLowRestClient, MLFLowRestClientBuilder, DEFAULT_USER_AGENT};
+#[cfg(feature = "parquet")]
+pub use columnar::ParquetData;
+pub use config::{Config, CONFIG_FILE_ENV, DEFAULT_TRACKING_URI, PROFILE_ENV};
+pub use curves::{Curve, CurveKind, PRECISION_RECALL_CURVE_FILE_NAME, ROC_CURVE_FILE_NAME};
+pub use dataset::{compute_digest, file_digest, Dataset, DatasetInput, InputTag, RunInputs};
+pub use distribution::Distribution;
+pub use downsample::Downsampling;
+pub use entities::{
+    ExperimentTag, LifecycleStage, Metric, Param, RunData, RunInfo, RunStatus, RunTag, TraceInfo,
+    TraceRequestMetadata, TraceStatus, TraceTag,
+};
+pub use evaluation::{EvaluationExample, EvaluationLogger, EVAL_RESULTS_FILE_NAME};
+pub use experiment::{
+    Experiment, ExperimentBuilder, ExperimentIdentifier, ExperimentIdentifierType, ExperimentLoader,
+};
+pub use export::{
+    copy_run, copy_runs, export_experiment, import_experiment, ExperimentExport,
+    ExperimentExporter, ExperimentImporter, EXPERIMENT_FILE_NAME, RUN_FILE_NAME,
+};
+pub use figure::Figure;
+pub use file_store::{FileStoreClient, SYNCED_RUN_ID_TAG};
+pub use filter::{Comparator, Filter, FilterKey, FilterValue};
+pub use gc::{gc, GarbageCollection, GarbageCollector};
+pub use heartbeat::Heartbeat;
+pub use hyperparams::{MlflowParams, ParamValue};
+pub use keys::{MetricKey, ParamKey};
+pub use metric_logger::{MetricLogger, StepMode};
+#[cfg(feature = "derive")]
+pub use mlflow_rs_derive::MlflowParams;
+#[cfg(feature = "mock")]
+pub use mock::MockMLFlowClient;
+pub use model::{Flavor, MLModel, ModelInfo, ModelLogger, MLMODEL_FILE_NAME};
+pub use names::generate_run_name;
+#[cfg(feature = "blocking")]
+pub use oauth::{ClientCredentialsProvider, TokenProvider};
+pub use offline::{OfflineClient, DEAD_LETTER_FILE_NAME, SPOOL_FILE_NAME};
+#[cfg(feature = "opentelemetry")]
+pub use otel::{MLFlowSpanExporter, MLFlowSpanExporterBuilder};
+#[cfg(feature = "async")]
+pub use pagination::PageStream;
+pub use pagination::{Page, PageIterator};
+pub use rate_limit::RateLimiter;
+pub use registry::{
+    ModelVersion, ModelVersionBuilder, ModelVersionLoader, ModelVersionStage, ModelVersionStatus,
+    ModelVersionTag, RegisteredModel, RegisteredModelBuilder, RegisteredModelLoader,
+    RegisteredModelTag,
+};
+pub use run::{Run, RunBuilder, RunIdentifier, RunLoader};
+pub use search::{
+    ExperimentSearch, ExperimentSearchIter, ModelVersionSearch, RegisteredModelSearch, RunSearch,
+    RunSearchIter, RunViewType,
+};
+#[cfg(feature = "ndarray")]
+pub use signature::TensorElement;
+pub use signature::{
+    ColSpec, DataType, InputExample, ModelSignature, Schema, TensorSpec, INPUT_EXAMPLE_FILE_NAME,
+};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStoreClient;
+pub use sweep::{Sweep, SweepResult, Trial, BEST_RUN_TAG};
+pub use table::Table;
+pub use termination::{install_termination_hook, terminate_active_runs};
+pub use trace::{Span, SpanEvent, SpanStatus, SpanType, Trace, TraceBuilder};
+pub use transfer::{ArtifactTransfer, ProgressCallback, TransferProgress};
+pub use version::ServerVersion;
+#[cfg(feature = "webhooks")]
+pub use webhooks::{
+    HttpUrlSpec, JobSpec, RegistryWebhook, RegistryWebhookBuilder, RegistryWebhookEvent,
+    RegistryWebhookSearch, RegistryWebhookStatus, RegistryWebhookUpdate, WebhookTestResult,
+};
 
 pub type MLFlowResult<T> = Result<T, MLFlowError>;
 #[derive(thiserror::Error, Debug, Clone)]
@@ -25,294 +190,109 @@ pub enum MLFlowError {
     #[error("{0}")]
     ExperimentNotFound(String),
 
-    #[error("ClientError: {0}")]
-    ClientError(String),
+    #[error("{0}")]
+    RunNotFound(String),
 
-    #[error("ResourceAlreadyExists: {0}")]
-    ResourceAlreadyExists(String),
+    #[error("RunBuilderError: {0}")]
+    RunBuilderError(String),
 
-    #[error("UnknownError: {0}")]
-    UnknownError(String),
-}
+    #[error("TraceBuilderError: {0}")]
+    TraceBuilderError(String),
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ExperimentTag {
-    key: String,
-    value: String,
-}
+    #[error("InvalidMetric: {0}")]
+    InvalidMetric(String),
 
-impl From<(&str, &str)> for ExperimentTag {
-    fn from((k, v): (&str, &str)) -> Self {
-        ExperimentTag {
-            key: k.to_string(),
-            value: v.to_string(),
-        }
-    }
-}
+    #[error("InvalidParam: {0}")]
+    InvalidParam(String),
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct CreateExperimentResponse {
-    experiment_id: String,
-}
+    #[error("InvalidTag: {0}")]
+    InvalidTag(String),
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct GetExperimentResponse {
-    experiment: Experiment,
-}
+    #[error("InvalidDataset: {0}")]
+    InvalidDataset(String),
 
-trait MLFlowClient {
-    fn create_experiment(&self, experiment: Experiment) -> MLFlowResult<CreateExperimentResponse>;
+    #[error("InvalidTable: {0}")]
+    InvalidTable(String),
 
-    fn get_experiment_by_id(&self, id: impl AsRef<str>) -> MLFlowResult<GetExperimentResponse>;
+    #[error("ValidationError: {0}")]
+    ValidationError(String),
 
-    fn get_experiment_by_name(&self, name: impl AsRef<str>) -> MLFlowResult<GetExperimentResponse>;
-}
+    #[error("ParamValueChanged: {0}")]
+    ParamValueChanged(String),
 
-#[derive(Clone, Debug, Default)]
-pub struct MLFLowRestClient {
-    client: Client,
-    host: String,
-}
+    #[error("RegisteredModelBuilderError: {0}")]
+    RegisteredModelBuilderError(String),
 
-impl MLFLowRestClient {
-    pub fn new(host: impl AsRef<str>) -> Self {
-        //TODO support resolvers for host
-
-        MLFLowRestClient {
-            client: Client::new(),
-            host: host.as_ref().to_string(),
-        }
-    }
-
-    fn _process_get(
-        &self,
-        result: Result<Response, reqwest::Error>,
-    ) -> MLFlowResult<GetExperimentResponse> {
-        match result {
-            Ok(r) => {
-                if r.status().is_success() {
-                    let e = r.json::<GetExperimentResponse>();
-                    match e {
-                        Ok(result) => Ok(result),
-                        Err(e) => {
-                            println!("{}", e);
-                            Err(UnknownError(e.to_string()))
-                        }
-                    }
-                } else if r.status() == StatusCode::NOT_FOUND {
-                    Err(ExperimentNotFound("experiment was not found".to_string()))
-                } else {
-                    println!("experiment not found server message: {}", r.status());
-                    Err(UnknownError("error finding experiment".to_string()))
-                }
-            }
-            Err(e) => {
-                println!("{}", e);
-                Err(UnknownError(e.to_string()))
-            }
-        }
-    }
-}
+    #[error("{0}")]
+    RegisteredModelNotFound(String),
 
-impl MLFlowClient for MLFLowRestClient {
-    fn create_experiment(&self, experiment: Experiment) -> MLFlowResult<CreateExperimentResponse> {
-        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/create");
-        let result = self.client.post(url).json(&experiment).send();
-
-        match result {
-            Ok(result) => {
-                if result.status().is_success() {
-                    let e = result.json::<CreateExperimentResponse>();
-                    match e {
-                        Ok(result) => Ok(result),
-                        Err(e) => Err(UnknownError(e.to_string())),
-                    }
-                } else {
-                    println!("{:?}", result.error_for_status());
-                    Err(UnknownError("Could not create experiment".to_string()))
-                }
-            }
-            Err(result) => {
-                println!("{}", result);
-                Err(UnknownError(result.to_string()))
-            }
-        }
-    }
-
-    fn get_experiment_by_id(&self, id: impl AsRef<str>) -> MLFlowResult<GetExperimentResponse> {
-        let url = format!("{}{}", &self.host, "/api/2.0/mlflow/experiments/get");
-        let result = self
-            .client
-            .get(url)
-            .query(&[("experiment_id", id.as_ref())])
-            .send();
-        self._process_get(result)
-    }
-
-    fn get_experiment_by_name(&self, name: impl AsRef<str>) -> MLFlowResult<GetExperimentResponse> {
-        let url = format!(
-            "{}{}",
-            &self.host, "/api/2.0/mlflow/experiments/get-by-name"
-        );
-        let result = self
-            .client
-            .get(url)
-            .query(&[("experiment_name", name.as_ref())])
-            .send();
-        self._process_get(result)
-    }
-}
+    #[error("ModelVersionBuilderError: {0}")]
+    ModelVersionBuilderError(String),
 
-pub trait ExperimentIdentifier {
-    fn experiment_id(&self) -> Option<String>;
-}
+    #[error("{0}")]
+    ModelVersionNotFound(String),
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Experiment {
-    experiment_id: Option<String>,
-    pub name: String,
-    pub artifact_location: Option<String>,
-    pub tags: Vec<ExperimentTag>,
+    #[error("WebhookBuilderError: {0}")]
+    WebhookBuilderError(String),
 
-    #[serde(skip_serializing, skip_deserializing)]
-    #[allow(dead_code)]
-    client: MLFLowRestClient,
-}
+    #[error("UserBuilderError: {0}")]
+    UserBuilderError(String),
 
-impl ExperimentIdentifier for Experiment {
-    fn experiment_id(&self) -> Option<String> {
-        self.experiment_id.clone()
-    }
-}
+    #[error("SearchError: {0}")]
+    SearchError(String),
 
-#[derive(Clone, Debug)]
+    #[error("ArtifactError: {0}")]
+    ArtifactError(String),
 
-pub struct ExperimentBuilder {
-    name: String,
-    artifact_location: Option<String>,
-    tags: Vec<ExperimentTag>,
-    client: MLFLowRestClient,
-}
+    #[error("SignatureError: {0}")]
+    SignatureError(String),
 
-impl ExperimentBuilder {
-    pub fn new(name: impl AsRef<str>) -> MLFlowResult<ExperimentBuilder> {
-        if name.as_ref().is_empty() {
-            return Err(ExperimentBuilderError("name cannot be empty".to_string()));
-        }
-
-        Ok(ExperimentBuilder {
-            name: name.as_ref().to_string(),
-            artifact_location: None,
-            tags: vec![],
-            client: MLFLowRestClient::new("http://localhost:5000"),
-        })
-    }
-
-    pub fn with_tag(mut self, tag: impl Into<ExperimentTag>) -> Self {
-        self.tags.push(tag.into());
-        self
-    }
-
-    pub fn with_tags(mut self, tags: Vec<impl Into<ExperimentTag>>) -> Self {
-        self.tags = tags.into_iter().map(|t| t.into()).collect();
-        self
-    }
-
-    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
-        self.client = client;
-        self
-    }
-
-    pub fn build(self) -> MLFlowResult<Experiment> {
-        //TODO Get a count of tags that have either an empty key or empty value
-        let client = self.client.clone();
-        let mut e = Experiment {
-            experiment_id: None,
-            name: self.name,
-            artifact_location: self.artifact_location.clone(),
-            tags: self.tags.clone(),
-            client: self.client.clone(),
-        };
-
-        let result = client.create_experiment(e.clone());
-
-        match result {
-            Ok(resp) => {
-                e.experiment_id = Some(resp.experiment_id);
-                Ok(e)
-            }
-            Err(e) => Err(e),
-        }
-    }
-}
+    #[error("SpoolError: {0}")]
+    SpoolError(String),
 
-pub enum ExperimentIdentifierType {
-    ById(String),
-    ByName(String),
-}
+    #[error("FileStoreError: {0}")]
+    FileStoreError(String),
 
-#[derive(Default)]
-pub struct ExperimentLoader {
-    client: Option<MLFLowRestClient>,
-}
+    #[error("SqliteError: {0}")]
+    SqliteError(String),
 
-impl ExperimentLoader {
-    pub fn with_client(mut self, client: MLFLowRestClient) -> Self {
-        self.client = Some(client);
-        self
-    }
-
-    pub fn load(self, experiment_identifier: ExperimentIdentifierType) -> MLFlowResult<Experiment> {
-        let client: MLFLowRestClient = self
-            .client
-            .unwrap_or_else(|| MLFLowRestClient::new("http://localhost:5000"));
-
-        match experiment_identifier {
-            ExperimentIdentifierType::ById(id) => match client.get_experiment_by_id(id) {
-                Ok(resp) => Ok(resp.experiment),
-                Err(e) => Err(UnknownError(e.to_string())),
-            },
-            ExperimentIdentifierType::ByName(name) => match client.get_experiment_by_name(name) {
-                Ok(resp) => Ok(resp.experiment),
-                Err(e) => Err(e),
-            },
-        }
-    }
-}
+    #[error("ExportError: {0}")]
+    ExportError(String),
+
+    /// An error of the ML framework behind an [`integrations`] hook.
+    #[error("IntegrationError: {0}")]
+    IntegrationError(String),
+
+    #[error("ConfigError: {0}")]
+    ConfigError(String),
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn experiment_builder_new_empty_name() {
-        ExperimentBuilder::new("").expect_err("ExperimentBuilderError: name cannot be empty");
-    }
-
-    #[test]
-    fn builder_with_tag() {
-        let builder = ExperimentBuilder::new("test_experiment")
-            .unwrap()
-            .with_tag(("key", "value"))
-            .with_tag(("key2", "value2"));
-
-        assert_eq!(builder.name, "test_experiment");
-        assert_eq!(builder.tags.len(), 2);
-    }
-
-    #[test]
-    fn tuple_to_experiment_tag() {
-        let tag = ExperimentTag::from(("name", "value"));
-        assert_eq!(tag.key, "name");
-        assert_eq!(tag.value, "value");
-    }
-
-    #[test]
-    fn build_with_tags() {
-        let builder = ExperimentBuilder::new("test_experiment")
-            .unwrap()
-            .with_tags(vec![("key", "value"), ("key2", "value2")]);
-        assert_eq!(builder.name, "test_experiment");
-        assert_eq!(builder.tags.len(), 2);
-    }
+    #[error("ClientError: {0}")]
+    ClientError(String),
+
+    #[error("ResourceAlreadyExists: {0}")]
+    ResourceAlreadyExists(String),
+
+    #[error("ResourceDoesNotExist: {0}")]
+    ResourceDoesNotExist(String),
+
+    #[error("InvalidParameterValue: {0}")]
+    InvalidParameterValue(String),
+
+    #[error("InvalidState: {0}")]
+    InvalidState(String),
+
+    #[error("PermissionDenied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Unauthenticated: {0}")]
+    Unauthenticated(String),
+
+    #[error("RequestLimitExceeded: {0}")]
+    RequestLimitExceeded(String),
+
+    #[error("InternalError: {0}")]
+    InternalError(String),
+
+    #[error("UnknownError: {0}")]
+    UnknownError(String),
 }
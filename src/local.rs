@@ -0,0 +1,199 @@
+//! Artifact storage on a filesystem the client shares with the tracking server, for runs whose
+//! artifact location is a `file://` URI or a plain path, as with
+//! `mlflow server --default-artifact-root ./mlruns`.
+
+use crate::artifacts::{join_artifact_path, local_artifact_path, ArtifactRepository, FileInfo};
+use crate::transfer::{ProgressCallback, ProgressReader};
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use url::Url;
+
+const FILE_SCHEME: &str = "file:";
+
+/// The run's artifact directory on the local filesystem.
+#[derive(Debug)]
+pub(crate) struct LocalArtifactRepository {
+    root: PathBuf,
+}
+
+impl LocalArtifactRepository {
+    /// Accepts `file:///path`, `file:/path` and absolute paths.
+    pub(crate) fn new(artifact_uri: &str) -> MLFlowResult<LocalArtifactRepository> {
+        let invalid = || ArtifactError(format!("invalid local artifact location {}", artifact_uri));
+        let root = match artifact_uri.starts_with(FILE_SCHEME) {
+            true => Url::parse(artifact_uri)
+                .map_err(|_| invalid())?
+                .to_file_path()
+                .map_err(|_| invalid())?,
+            false => PathBuf::from(artifact_uri),
+        };
+        if !root.is_absolute() {
+            return Err(invalid());
+        }
+        Ok(LocalArtifactRepository { root })
+    }
+
+    fn path(&self, artifact_path: &str) -> MLFlowResult<PathBuf> {
+        // validates the path so it can't escape the artifact root
+        join_artifact_path("", artifact_path)?;
+        Ok(local_artifact_path(&self.root, artifact_path))
+    }
+}
+
+impl ArtifactRepository for LocalArtifactRepository {
+    fn log_artifact(&self, local_file: &Path, artifact_path: &str) -> MLFlowResult<()> {
+        self.log_artifact_with_progress(local_file, artifact_path, Arc::new(|_| {}))
+    }
+
+    fn list_artifacts(&self, path: Option<&str>) -> MLFlowResult<Vec<FileInfo>> {
+        let directory = path.unwrap_or_default();
+        let local = self.path(directory)?;
+        if !local.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut files = vec![];
+        for entry in std::fs::read_dir(&local).map_err(|e| ArtifactError(e.to_string()))? {
+            let entry = entry.map_err(|e| ArtifactError(e.to_string()))?;
+            let metadata = entry.metadata().map_err(|e| ArtifactError(e.to_string()))?;
+            files.push(FileInfo {
+                path: join_artifact_path(directory, &entry.file_name().to_string_lossy())?,
+                is_dir: metadata.is_dir(),
+                file_size: match metadata.is_dir() {
+                    true => None,
+                    false => Some(metadata.len() as i64),
+                },
+                extra: serde_json::Map::new(),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    fn download_artifact(&self, artifact_path: &str, local_file: &Path) -> MLFlowResult<()> {
+        copy(&self.path(artifact_path)?, local_file, Arc::new(|_| {}))
+    }
+
+    fn delete_artifacts(&self, artifact_path: &str) -> MLFlowResult<()> {
+        let local = self.path(artifact_path)?;
+        let result = match local.is_dir() {
+            true => std::fs::remove_dir_all(&local),
+            false if local.exists() => std::fs::remove_file(&local),
+            false => Ok(()),
+        };
+        result.map_err(|e| ArtifactError(e.to_string()))
+    }
+
+    fn log_artifact_with_progress(
+        &self,
+        local_file: &Path,
+        artifact_path: &str,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        let destination = self.path(artifact_path)?;
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ArtifactError(e.to_string()))?;
+        }
+        copy(local_file, &destination, progress)
+    }
+
+    fn download_artifact_with_progress(
+        &self,
+        artifact_path: &str,
+        local_file: &Path,
+        progress: ProgressCallback,
+    ) -> MLFlowResult<()> {
+        copy(&self.path(artifact_path)?, local_file, progress)
+    }
+}
+
+fn copy(from: &Path, to: &Path, progress: ProgressCallback) -> MLFlowResult<()> {
+    let source = File::open(from).map_err(|e| ArtifactError(e.to_string()))?;
+    let mut destination = File::create(to).map_err(|e| ArtifactError(e.to_string()))?;
+    std::io::copy(&mut ProgressReader::new(source, progress), &mut destination)
+        .map(|_| ())
+        .map_err(|e| ArtifactError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::SharedClient;
+    use crate::{Run, RunData};
+
+    #[test]
+    fn parses_locations() {
+        assert_eq!(
+            LocalArtifactRepository::new("file:///mlruns/1/abc/artifacts")
+                .unwrap()
+                .root,
+            PathBuf::from("/mlruns/1/abc/artifacts")
+        );
+        assert_eq!(
+            LocalArtifactRepository::new("/mlruns/1/abc/artifacts")
+                .unwrap()
+                .root,
+            PathBuf::from("/mlruns/1/abc/artifacts")
+        );
+        LocalArtifactRepository::new("file://fileserver/mlruns")
+            .expect_err("remote hosts are not supported");
+    }
+
+    #[test]
+    fn logs_and_downloads_artifacts() {
+        let store = tempfile::tempdir().unwrap();
+        let artifact_uri = Url::from_directory_path(store.path().join("1/abc/artifacts")).unwrap();
+        let run = Run {
+            info: serde_json::from_value(serde_json::json!({
+                "run_id": "abc",
+                "experiment_id": "1",
+                "status": "RUNNING",
+                "artifact_uri": artifact_uri.as_str(),
+            }))
+            .unwrap(),
+            data: RunData::default(),
+            client: SharedClient::default(),
+        };
+
+        let local = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(local.path().join("weights")).unwrap();
+        std::fs::write(local.path().join("MLmodel"), "flavors: {}").unwrap();
+        std::fs::write(local.path().join("weights/model.bin"), "0101").unwrap();
+        run.log_artifacts(local.path(), Some("model")).unwrap();
+        assert!(store
+            .path()
+            .join("1/abc/artifacts/model/weights/model.bin")
+            .is_file());
+
+        let listing = run.list_artifacts(Some("model")).unwrap();
+        assert_eq!(
+            listing,
+            vec![
+                FileInfo {
+                    path: "model/MLmodel".to_string(),
+                    is_dir: false,
+                    file_size: Some(11),
+                    extra: serde_json::Map::new(),
+                },
+                FileInfo {
+                    path: "model/weights".to_string(),
+                    is_dir: true,
+                    file_size: None,
+                    extra: serde_json::Map::new(),
+                },
+            ]
+        );
+
+        let dest = tempfile::tempdir().unwrap();
+        let downloaded = run.download_artifact("model", dest.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(downloaded.join("weights/model.bin")).unwrap(),
+            "0101"
+        );
+        run.log_artifact(local.path().join("MLmodel"), Some("../escape"))
+            .expect_err("relative segments are rejected");
+    }
+}
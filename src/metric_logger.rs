@@ -0,0 +1,264 @@
+//! Step and timestamp bookkeeping for metric logging.
+
+use crate::entities::Metric;
+use crate::run::{current_time_millis, Run};
+use crate::MLFlowError::InvalidMetric;
+use crate::MLFlowResult;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Which step a [`MetricLogger`] logs a value at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepMode {
+    /// Each key counts its own steps, the next one being used every time the key is logged.
+    #[default]
+    PerKey,
+    /// All keys are logged at one global step, moved on with [`MetricLogger::advance`], like a
+    /// training loop's iteration counter.
+    Shared,
+}
+
+#[derive(Debug, Default)]
+struct Steps {
+    global: i64,
+    /// The step each key was last logged at.
+    last: HashMap<String, i64>,
+}
+
+impl Steps {
+    fn next(&self, mode: StepMode, key: &str) -> i64 {
+        match mode {
+            StepMode::PerKey => self.last.get(key).map_or(0, |step| step + 1),
+            StepMode::Shared => self.global,
+        }
+    }
+}
+
+/// Logs metrics of a run without step bookkeeping in user code: steps are chosen according to
+/// the [`StepMode`] and timestamps are the current time.
+///
+/// Keys the run already has metrics for continue after their latest step, and the shared step
+/// after the highest one, so a [resumed](Run::resume) run's curves continue where they stopped.
+///
+/// ```no_run
+/// use mlflow_rs::{MetricLogger, RunBuilder, StepMode};
+///
+/// let run = RunBuilder::new("1").unwrap().build().unwrap();
+/// let metrics = MetricLogger::new(&run).with_mode(StepMode::Shared);
+/// for _ in 0..10 {
+///     metrics.log_all([("loss", 0.5), ("accuracy", 0.8)]).unwrap();
+///     metrics.advance();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MetricLogger<'a> {
+    run: &'a Run,
+    mode: StepMode,
+    strict: bool,
+    steps: Mutex<Steps>,
+}
+
+impl<'a> MetricLogger<'a> {
+    pub fn new(run: &'a Run) -> MetricLogger<'a> {
+        let steps = Steps {
+            global: run.last_step().map_or(0, |step| step + 1),
+            last: run
+                .data()
+                .metrics
+                .iter()
+                .map(|m| (m.key.clone(), m.step))
+                .collect(),
+        };
+        MetricLogger {
+            run,
+            mode: StepMode::default(),
+            strict: false,
+            steps: Mutex::new(steps),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: StepMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Rejects values logged at a lower step than their key was last logged at with
+    /// [`InvalidMetric`](crate::MLFlowError::InvalidMetric), instead of sending them. Disabled
+    /// by default.
+    pub fn with_strict_order(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Logs `value` at the key's next step, or the shared step, returning the step used.
+    pub fn log(&self, key: impl AsRef<str>, value: f64) -> MLFlowResult<i64> {
+        let mut steps = self.lock();
+        let step = steps.next(self.mode, key.as_ref());
+        self.send(&mut steps, key.as_ref(), value, step)?;
+        Ok(step)
+    }
+
+    /// Logs `value` at an explicit `step`, which later values of the key continue after.
+    pub fn log_at(&self, key: impl AsRef<str>, value: f64, step: i64) -> MLFlowResult<()> {
+        let mut steps = self.lock();
+        self.send(&mut steps, key.as_ref(), value, step)
+    }
+
+    /// Logs several metrics, each at its next step or all at the shared step, in one
+    /// `runs/log-batch` request.
+    pub fn log_all<K: AsRef<str>>(
+        &self,
+        metrics: impl IntoIterator<Item = (K, f64)>,
+    ) -> MLFlowResult<()> {
+        let mut steps = self.lock();
+        let timestamp = current_time_millis();
+        let mut next: HashMap<String, i64> = HashMap::new();
+        let metrics = metrics
+            .into_iter()
+            .map(|(key, value)| {
+                let key = key.as_ref();
+                // a key given twice takes two steps of its own
+                let step = match next.get(key) {
+                    Some(step) if self.mode == StepMode::PerKey => step + 1,
+                    _ => steps.next(self.mode, key),
+                };
+                self.check(&steps, key, step)?;
+                next.insert(key.to_string(), step);
+                Ok(Metric::new(key, value, Some(step), Some(timestamp)))
+            })
+            .collect::<MLFlowResult<_>>()?;
+
+        self.run.log_batch(metrics, vec![], vec![])?;
+        steps.last.extend(next);
+        Ok(())
+    }
+
+    /// Moves the shared step on by one, returning the new step.
+    pub fn advance(&self) -> i64 {
+        let mut steps = self.lock();
+        steps.global += 1;
+        steps.global
+    }
+
+    /// The shared step values are logged at in [`StepMode::Shared`].
+    pub fn step(&self) -> i64 {
+        self.lock().global
+    }
+
+    pub fn set_step(&self, step: i64) {
+        self.lock().global = step;
+    }
+
+    /// The step `key` was last logged at, through this logger or before it was created.
+    pub fn last_step(&self, key: impl AsRef<str>) -> Option<i64> {
+        self.lock().last.get(key.as_ref()).copied()
+    }
+
+    fn send(&self, steps: &mut Steps, key: &str, value: f64, step: i64) -> MLFlowResult<()> {
+        self.check(steps, key, step)?;
+        self.run
+            .log_metric(key, value, Some(step), Some(current_time_millis()))?;
+        steps.last.insert(key.to_string(), step);
+        Ok(())
+    }
+
+    fn check(&self, steps: &Steps, key: &str, step: i64) -> MLFlowResult<()> {
+        match steps.last.get(key) {
+            Some(last) if self.strict && *last > step => Err(InvalidMetric(format!(
+                "{} was already logged at step {}, cannot log step {}",
+                key, last, step
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Steps> {
+        self.steps.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MLFlowError, MockMLFlowClient, RunBuilder};
+
+    fn run() -> Run {
+        RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap()
+    }
+
+    fn steps(run: &Run, key: &str) -> Vec<i64> {
+        run.get_metric_history(key)
+            .unwrap()
+            .iter()
+            .map(|m| m.step)
+            .collect()
+    }
+
+    #[test]
+    fn counts_steps_per_key() {
+        let run = run();
+        run.log_metric("loss", 0.9, Some(4), None).unwrap();
+        let run = crate::RunLoader::default()
+            .with_dyn_client(run.client.clone())
+            .load(run.info().run_id.clone())
+            .unwrap();
+
+        let metrics = MetricLogger::new(&run);
+        assert_eq!(metrics.log("loss", 0.8).unwrap(), 5);
+        assert_eq!(metrics.log("acc", 0.5).unwrap(), 0);
+        metrics
+            .log_all([("loss", 0.7), ("acc", 0.6), ("acc", 0.7)])
+            .unwrap();
+        metrics.log_at("acc", 0.8, 10).unwrap();
+        assert_eq!(metrics.log("acc", 0.9).unwrap(), 11);
+
+        assert_eq!(steps(&run, "loss"), vec![4, 5, 6]);
+        assert_eq!(steps(&run, "acc"), vec![0, 1, 2, 10, 11]);
+        assert!(run
+            .get_metric_history("acc")
+            .unwrap()
+            .iter()
+            .all(|m| m.timestamp > 0));
+    }
+
+    #[test]
+    fn shares_one_step() {
+        let run = run();
+        let metrics = MetricLogger::new(&run).with_mode(StepMode::Shared);
+        metrics.log_all([("loss", 0.5), ("acc", 0.5)]).unwrap();
+        assert_eq!(metrics.advance(), 1);
+        metrics.log("loss", 0.4).unwrap();
+        metrics.set_step(7);
+        metrics.log("acc", 0.6).unwrap();
+
+        assert_eq!(steps(&run, "loss"), vec![0, 1]);
+        assert_eq!(steps(&run, "acc"), vec![0, 7]);
+        assert_eq!(metrics.last_step("acc"), Some(7));
+    }
+
+    #[test]
+    fn rejects_out_of_order_steps_when_strict() {
+        let run = run();
+        let metrics = MetricLogger::new(&run).with_strict_order(true);
+        metrics.log_at("loss", 0.5, 3).unwrap();
+        metrics.log_at("loss", 0.4, 3).unwrap();
+        assert!(matches!(
+            metrics.log_at("loss", 0.6, 2),
+            Err(MLFlowError::InvalidMetric(_))
+        ));
+        assert_eq!(steps(&run, "loss"), vec![3, 3]);
+
+        let shared = MetricLogger::new(&run)
+            .with_mode(StepMode::Shared)
+            .with_strict_order(true);
+        shared.set_step(5);
+        shared.log("loss", 0.3).unwrap();
+        shared.set_step(4);
+        assert!(shared.log_all([("acc", 0.9), ("loss", 0.2)]).is_err());
+        assert!(steps(&run, "acc").is_empty(), "the whole batch is rejected");
+    }
+}
@@ -0,0 +1,1785 @@
+//! In-memory stand-in for a tracking server, for unit testing code that tracks experiments
+//! without running MLflow.
+//!
+//! ```
+//! use mlflow_rs::{ExperimentBuilder, ExperimentIdentifier, MockMLFlowClient, RunBuilder, RunIdentifier};
+//!
+//! let mock = MockMLFlowClient::default();
+//! let experiment = ExperimentBuilder::new("my-ml-experiment")
+//!     .unwrap()
+//!     .with_mock_client(mock.clone())
+//!     .build()
+//!     .unwrap();
+//! let run = RunBuilder::new(experiment.experiment_id().unwrap())
+//!     .unwrap()
+//!     .with_mock_client(mock.clone())
+//!     .build()
+//!     .unwrap();
+//! run.log_metric("rmse", 0.4, Some(1), None).unwrap();
+//!
+//! assert_eq!(mock.metric_history(&run.run_id(), "rmse").len(), 1);
+//! ```
+
+use crate::artifacts::{artifact_repository_path, join_artifact_path, FileInfo};
+#[cfg(feature = "auth")]
+use crate::auth::{ExperimentPermission, User};
+use crate::client::{
+    CreateExperimentRequest, CreateExperimentResponse, CreateModelVersionRequest,
+    CreateRegisteredModelRequest, CreateRunRequest, CreateRunResponse, EndTraceRequest,
+    ExperimentInfo, GetExperimentResponse, GetRunResponse, ListArtifactsResponse, LogMetricRequest,
+    LogParamRequest, MLFlowClient, MetricHistoryResponse, ModelVersionResponse,
+    RegisteredModelResponse, RunResponse, SearchExperimentsRequest, SearchExperimentsResponse,
+    SearchModelVersionsRequest, SearchModelVersionsResponse, SearchRegisteredModelsRequest,
+    SearchRegisteredModelsResponse, SearchRunsRequest, SearchRunsResponse, SharedClient,
+    StartTraceRequest, TraceInfoResponse, UpdateRunRequest, UpdateRunResponse,
+};
+#[cfg(feature = "webhooks")]
+use crate::client::{
+    CreateRegistryWebhookRequest, ListRegistryWebhooksRequest, ListRegistryWebhooksResponse,
+    RegistryWebhookResponse, TestRegistryWebhookRequest, TestRegistryWebhookResponse,
+    UpdateRegistryWebhookRequest,
+};
+#[cfg(feature = "auth")]
+use crate::client::{
+    ExperimentPermissionRequest, ExperimentPermissionResponse, UpdateUserAdminRequest,
+    UserPasswordRequest, UserResponse,
+};
+use crate::dataset::{DatasetInput, RunInputs};
+use crate::entities::{
+    ExperimentTag, LifecycleStage, Metric, Param, RunData, RunInfo, RunStatus, RunTag, TraceInfo,
+    TraceStatus, TraceTag,
+};
+use crate::names::generate_run_name;
+use crate::query::{
+    all_match, experiment_value, matches_view_type, page, parse_filter, run_value, Clause, Value,
+};
+use crate::registry::{
+    ModelVersion, ModelVersionStage, ModelVersionStatus, ModelVersionTag, RegisteredModel,
+    RegisteredModelTag,
+};
+use crate::run::current_time_millis;
+use crate::tags::{MLFLOW_ARTIFACT_LOCATION, MLFLOW_RUN_NAME};
+use crate::trace::TRACE_DATA_FILE_NAME;
+#[cfg(feature = "webhooks")]
+use crate::webhooks::{RegistryWebhook, WebhookTestResult};
+use crate::MLFlowError::{
+    ArtifactError, ExperimentNotFound, InvalidParameterValue, ModelVersionNotFound,
+    ParamValueChanged, RegisteredModelNotFound, ResourceAlreadyExists, ResourceDoesNotExist,
+    RunNotFound, SearchError,
+};
+use crate::MLFlowResult;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A tracking client that keeps experiments, runs, artifacts and registered models in memory.
+///
+/// Clones share the same state, so keep a clone around to inspect what the code under test
+/// logged. Like a fresh tracking server it starts with the `Default` experiment, id `0`.
+///
+/// Searches support filters made of `AND`ed comparisons on metrics, params, tags and run or
+/// experiment attributes, as produced by [`Filter`](crate::Filter). `order_by` is ignored:
+/// runs are returned newest first and experiments in creation order.
+#[derive(Clone, Debug, Default)]
+pub struct MockMLFlowClient {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    next_id: u64,
+    experiments: Vec<ExperimentInfo>,
+    runs: Vec<MockRun>,
+    artifacts: BTreeMap<String, Vec<u8>>,
+    registered_models: Vec<RegisteredModel>,
+    model_versions: Vec<ModelVersion>,
+    traces: Vec<TraceInfo>,
+    #[cfg(feature = "webhooks")]
+    webhooks: Vec<RegistryWebhook>,
+    #[cfg(feature = "auth")]
+    users: Vec<User>,
+}
+
+#[derive(Debug)]
+struct MockRun {
+    info: RunInfo,
+    data: RunData,
+    inputs: RunInputs,
+    metric_history: Vec<Metric>,
+    deleted_time: Option<i64>,
+}
+
+impl MockRun {
+    fn set_lifecycle(&mut self, stage: LifecycleStage, now: i64) {
+        self.info.lifecycle_stage = Some(stage);
+        self.deleted_time = (stage == LifecycleStage::Deleted).then_some(now);
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            next_id: 1,
+            experiments: vec![new_experiment("0", "Default", None, vec![])],
+            runs: vec![],
+            artifacts: BTreeMap::new(),
+            registered_models: vec![],
+            model_versions: vec![],
+            traces: vec![],
+            #[cfg(feature = "webhooks")]
+            webhooks: vec![],
+            #[cfg(feature = "auth")]
+            users: vec![],
+        }
+    }
+}
+
+fn new_experiment(
+    id: &str,
+    name: &str,
+    artifact_location: Option<String>,
+    tags: Vec<ExperimentTag>,
+) -> ExperimentInfo {
+    ExperimentInfo {
+        experiment_id: id.to_string(),
+        name: name.to_string(),
+        artifact_location: Some(
+            artifact_location.unwrap_or_else(|| format!("mlflow-artifacts:/{}", id)),
+        ),
+        tags,
+        lifecycle_stage: Some(LifecycleStage::Active),
+        creation_time: None,
+        last_update_time: None,
+        extra: serde_json::Map::new(),
+    }
+}
+
+impl MockMLFlowClient {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The run's info as currently stored, if it exists.
+    pub fn run_info(&self, run_id: &str) -> Option<RunInfo> {
+        self.state().run(run_id).ok().map(|r| r.info.clone())
+    }
+
+    /// The latest value of each metric and the params and tags logged to the run.
+    pub fn run_data(&self, run_id: &str) -> Option<RunData> {
+        self.state().run(run_id).ok().map(|r| r.data.clone())
+    }
+
+    /// The datasets logged to the run.
+    pub fn run_inputs(&self, run_id: &str) -> Option<RunInputs> {
+        self.state().run(run_id).ok().map(|r| r.inputs.clone())
+    }
+
+    /// Every value logged for `key`, in the order it was logged.
+    pub fn metric_history(&self, run_id: &str, key: &str) -> Vec<Metric> {
+        self.state()
+            .run(run_id)
+            .map(|r| {
+                r.metric_history
+                    .iter()
+                    .filter(|m| m.key == key)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The trace's info as currently stored, if it exists.
+    pub fn trace_info(&self, request_id: &str) -> Option<TraceInfo> {
+        self.state().trace(request_id).ok().cloned()
+    }
+
+    /// The `traces.json` uploaded when the trace ended, holding its spans.
+    pub fn trace_data(&self, request_id: &str) -> Option<Vec<u8>> {
+        let mut state = self.state();
+        let location = state
+            .trace(request_id)
+            .ok()?
+            .tag(MLFLOW_ARTIFACT_LOCATION)?;
+        let path = join_artifact_path(
+            &artifact_repository_path(location).ok()?,
+            TRACE_DATA_FILE_NAME,
+        )
+        .ok()?;
+        state.artifacts.get(&path).cloned()
+    }
+
+    /// The contents of an uploaded artifact, by its path relative to the run's artifact root.
+    pub fn artifact(&self, run_id: &str, path: &str) -> Option<Vec<u8>> {
+        let state = self.state();
+        let root = state.run_artifact_root(run_id).ok()?;
+        let path = join_artifact_path(&root, path).ok()?;
+        state.artifacts.get(&path).cloned()
+    }
+}
+
+impl From<MockMLFlowClient> for SharedClient {
+    fn from(client: MockMLFlowClient) -> Self {
+        SharedClient::new(client)
+    }
+}
+
+impl State {
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn experiment(&mut self, id: &str) -> MLFlowResult<&mut ExperimentInfo> {
+        self.experiments
+            .iter_mut()
+            .find(|e| e.experiment_id == id)
+            .ok_or_else(|| ResourceDoesNotExist(format!("No Experiment with id={} exists", id)))
+    }
+
+    fn set_experiment_lifecycle(&mut self, id: &str, stage: LifecycleStage) -> MLFlowResult<()> {
+        let now = current_time_millis();
+        let experiment = self.experiment(id)?;
+        experiment.lifecycle_stage = Some(stage);
+        experiment.last_update_time = Some(now);
+        for run in self.runs.iter_mut().filter(|r| r.info.experiment_id == id) {
+            run.set_lifecycle(stage, now);
+        }
+        Ok(())
+    }
+
+    /// Forgets a run and the artifacts kept for it.
+    fn remove_run(&mut self, run_id: &str) {
+        if let Ok(root) = self.run_artifact_root(run_id) {
+            let root = format!("{}/", root);
+            self.artifacts
+                .retain(|artifact, _| !artifact.starts_with(&root));
+        }
+        self.runs.retain(|r| r.info.run_id != run_id);
+    }
+
+    fn trace(&mut self, request_id: &str) -> MLFlowResult<&mut TraceInfo> {
+        self.traces
+            .iter_mut()
+            .find(|t| t.request_id == request_id)
+            .ok_or_else(|| ResourceDoesNotExist(format!("Trace '{}' not found", request_id)))
+    }
+
+    fn run(&self, run_id: &str) -> MLFlowResult<&MockRun> {
+        self.runs
+            .iter()
+            .find(|r| r.info.run_id == run_id)
+            .ok_or_else(|| ResourceDoesNotExist(format!("Run '{}' not found", run_id)))
+    }
+
+    fn run_mut(&mut self, run_id: &str) -> MLFlowResult<&mut MockRun> {
+        self.runs
+            .iter_mut()
+            .find(|r| r.info.run_id == run_id)
+            .ok_or_else(|| ResourceDoesNotExist(format!("Run '{}' not found", run_id)))
+    }
+
+    fn run_artifact_root(&self, run_id: &str) -> MLFlowResult<String> {
+        let run = self.run(run_id)?;
+        let artifact_uri = run
+            .info
+            .artifact_uri
+            .as_deref()
+            .ok_or_else(|| ArtifactError("run has no artifact location".to_string()))?;
+        artifact_repository_path(artifact_uri)
+    }
+
+    fn registered_model(&mut self, name: &str) -> MLFlowResult<&mut RegisteredModel> {
+        self.registered_models
+            .iter_mut()
+            .find(|m| m.name == name)
+            .ok_or_else(|| {
+                RegisteredModelNotFound(format!("registered model {} was not found", name))
+            })
+    }
+
+    fn model_version(&mut self, name: &str, version: &str) -> MLFlowResult<&mut ModelVersion> {
+        self.model_versions
+            .iter_mut()
+            .find(|v| v.name == name && v.version == version)
+            .ok_or_else(|| {
+                ModelVersionNotFound(format!(
+                    "version {} of registered model {} was not found",
+                    version, name
+                ))
+            })
+    }
+
+    #[cfg(feature = "webhooks")]
+    fn webhook(&mut self, id: &str) -> MLFlowResult<&mut RegistryWebhook> {
+        self.webhooks
+            .iter_mut()
+            .find(|w| w.id == id)
+            .ok_or_else(|| ResourceDoesNotExist(format!("webhook {} was not found", id)))
+    }
+
+    #[cfg(feature = "auth")]
+    fn user(&mut self, username: &str) -> MLFlowResult<&mut User> {
+        self.users
+            .iter_mut()
+            .find(|u| u.username == username)
+            .ok_or_else(|| {
+                ResourceDoesNotExist(format!("User with username={} not found", username))
+            })
+    }
+
+    #[cfg(feature = "auth")]
+    fn experiment_permission(
+        &mut self,
+        experiment_id: &str,
+        username: &str,
+    ) -> MLFlowResult<&mut ExperimentPermission> {
+        self.users
+            .iter_mut()
+            .filter(|u| u.username == username)
+            .flat_map(|u| u.experiment_permissions.iter_mut())
+            .find(|p| p.experiment_id == experiment_id)
+            .ok_or_else(|| {
+                ResourceDoesNotExist(format!(
+                    "Experiment permission with experiment_id={} and username={} not found",
+                    experiment_id, username
+                ))
+            })
+    }
+
+    /// The model with the latest version in each stage filled in, as the server returns it.
+    fn registered_model_response(&mut self, name: &str) -> MLFlowResult<RegisteredModelResponse> {
+        let mut model = self.registered_model(name)?.clone();
+        let mut latest: BTreeMap<String, ModelVersion> = BTreeMap::new();
+        for version in self.model_versions.iter().filter(|v| v.name == name) {
+            let stage = format!("{:?}", version.current_stage);
+            let newer = latest
+                .get(&stage)
+                .map(|v| version_number(version) > version_number(v))
+                .unwrap_or(true);
+            if newer {
+                latest.insert(stage, version.clone());
+            }
+        }
+        model.latest_versions = latest.into_values().collect();
+        Ok(RegisteredModelResponse {
+            registered_model: model,
+        })
+    }
+
+    fn log_metric(&mut self, run_id: &str, metric: Metric) -> MLFlowResult<()> {
+        let run = self.run_mut(run_id)?;
+        match run.data.metrics.iter_mut().find(|m| m.key == metric.key) {
+            Some(latest) => {
+                if (metric.step, metric.timestamp) >= (latest.step, latest.timestamp) {
+                    *latest = metric.clone();
+                }
+            }
+            None => run.data.metrics.push(metric.clone()),
+        }
+        run.metric_history.push(metric);
+        Ok(())
+    }
+
+    fn log_param(&mut self, run_id: &str, param: Param) -> MLFlowResult<()> {
+        let run = self.run_mut(run_id)?;
+        match run.data.params.iter().find(|p| p.key == param.key) {
+            Some(existing) if existing.value != param.value => Err(ParamValueChanged(format!(
+                "Changing param values is not allowed. Param with key='{}' was already logged \
+                 with value='{}' for run ID='{}'. Attempted logging new value '{}'.",
+                param.key, existing.value, run_id, param.value
+            ))),
+            Some(_) => Ok(()),
+            None => {
+                run.data.params.push(param);
+                Ok(())
+            }
+        }
+    }
+
+    fn set_tag(&mut self, run_id: &str, tag: RunTag) -> MLFlowResult<()> {
+        let run = self.run_mut(run_id)?;
+        if tag.key == MLFLOW_RUN_NAME {
+            run.info.run_name = Some(tag.value.clone());
+        }
+        run.data.tags.retain(|t| t.key != tag.key);
+        run.data.tags.push(tag);
+        Ok(())
+    }
+}
+
+fn version_number(version: &ModelVersion) -> u64 {
+    version.version.parse().unwrap_or_default()
+}
+
+impl MLFlowClient for MockMLFlowClient {
+    fn create_experiment(
+        &self,
+        request: &CreateExperimentRequest,
+    ) -> MLFlowResult<CreateExperimentResponse> {
+        let mut state = self.state();
+        if state.experiments.iter().any(|e| e.name == request.name) {
+            return Err(ResourceAlreadyExists(format!(
+                "Experiment '{}' already exists.",
+                request.name
+            )));
+        }
+
+        let id = state.next_id().to_string();
+        state.experiments.push(new_experiment(
+            &id,
+            &request.name,
+            request.artifact_location.clone(),
+            request.tags.clone(),
+        ));
+        Ok(CreateExperimentResponse { experiment_id: id })
+    }
+
+    fn get_experiment_by_id(&self, id: &str) -> MLFlowResult<GetExperimentResponse> {
+        self.state()
+            .experiments
+            .iter()
+            .find(|e| e.experiment_id == id)
+            .map(|e| GetExperimentResponse {
+                experiment: e.clone(),
+            })
+            .ok_or_else(|| ExperimentNotFound("experiment was not found".to_string()))
+    }
+
+    fn get_experiment_by_name(&self, name: &str) -> MLFlowResult<GetExperimentResponse> {
+        self.state()
+            .experiments
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| GetExperimentResponse {
+                experiment: e.clone(),
+            })
+            .ok_or_else(|| ExperimentNotFound("experiment was not found".to_string()))
+    }
+
+    fn search_experiments(
+        &self,
+        request: &SearchExperimentsRequest,
+    ) -> MLFlowResult<SearchExperimentsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut experiments = vec![];
+        for experiment in self.state().experiments.iter() {
+            if !matches_view_type(experiment.lifecycle_stage, request.view_type) {
+                continue;
+            }
+            if all_match(&clauses, |clause| experiment_value(experiment, clause))? {
+                experiments.push(experiment.clone());
+            }
+        }
+
+        let (experiments, next_page_token) = page(
+            experiments,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchExperimentsResponse {
+            experiments,
+            next_page_token,
+        })
+    }
+
+    fn update_experiment(&self, experiment_id: &str, new_name: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        if state
+            .experiments
+            .iter()
+            .any(|e| e.name == new_name && e.experiment_id != experiment_id)
+        {
+            return Err(ResourceAlreadyExists(format!(
+                "Experiment '{}' already exists.",
+                new_name
+            )));
+        }
+        state.experiment(experiment_id)?.name = new_name.to_string();
+        Ok(())
+    }
+
+    fn delete_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        self.state()
+            .set_experiment_lifecycle(experiment_id, LifecycleStage::Deleted)
+    }
+
+    fn restore_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        self.state()
+            .set_experiment_lifecycle(experiment_id, LifecycleStage::Active)
+    }
+
+    fn create_run(&self, request: CreateRunRequest) -> MLFlowResult<CreateRunResponse> {
+        let mut state = self.state();
+        let artifact_location = state
+            .experiment(&request.experiment_id)?
+            .artifact_location
+            .clone()
+            .unwrap_or_default();
+
+        let id = state.next_id();
+        let run_id = format!("{:032x}", id);
+        let run_name = request
+            .run_name
+            .or_else(|| {
+                request
+                    .tags
+                    .iter()
+                    .find(|t| t.key == MLFLOW_RUN_NAME)
+                    .map(|t| t.value.clone())
+            })
+            .unwrap_or_else(generate_run_name);
+        let mut run = MockRun {
+            info: RunInfo {
+                run_id: run_id.clone(),
+                run_name: Some(run_name.clone()),
+                experiment_id: request.experiment_id,
+                status: RunStatus::Running,
+                start_time: Some(request.start_time),
+                end_time: None,
+                artifact_uri: Some(format!(
+                    "{}/{}/artifacts",
+                    artifact_location.trim_end_matches('/'),
+                    run_id
+                )),
+                lifecycle_stage: Some(LifecycleStage::Active),
+                extra: serde_json::Map::new(),
+            },
+            data: RunData::default(),
+            inputs: RunInputs::default(),
+            metric_history: vec![],
+            deleted_time: None,
+        };
+        for tag in request.tags {
+            run.data.tags.retain(|t| t.key != tag.key);
+            run.data.tags.push(tag);
+        }
+        run.data.tags.retain(|t| t.key != MLFLOW_RUN_NAME);
+        run.data
+            .tags
+            .push(RunTag::from((MLFLOW_RUN_NAME, run_name.as_str())));
+
+        let response = CreateRunResponse {
+            run: RunResponse {
+                info: run.info.clone(),
+                data: run.data.clone(),
+                inputs: run.inputs.clone(),
+            },
+        };
+        state.runs.push(run);
+        Ok(response)
+    }
+
+    fn get_run(&self, run_id: &str) -> MLFlowResult<GetRunResponse> {
+        let state = self.state();
+        let run = state
+            .run(run_id)
+            .map_err(|_| RunNotFound(format!("run {} was not found", run_id)))?;
+        Ok(GetRunResponse {
+            run: RunResponse {
+                info: run.info.clone(),
+                data: run.data.clone(),
+                inputs: run.inputs.clone(),
+            },
+        })
+    }
+
+    fn update_run(&self, request: UpdateRunRequest) -> MLFlowResult<UpdateRunResponse> {
+        let mut state = self.state();
+        if let Some(run_name) = &request.run_name {
+            state.set_tag(
+                &request.run_id,
+                RunTag::from((MLFLOW_RUN_NAME, run_name.as_str())),
+            )?;
+        }
+        let run = state.run_mut(&request.run_id)?;
+        if let Some(status) = request.status {
+            run.info.status = status;
+        }
+        if request.end_time.is_some() {
+            run.info.end_time = request.end_time;
+        }
+        Ok(UpdateRunResponse {
+            run_info: run.info.clone(),
+        })
+    }
+
+    fn delete_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.state()
+            .run_mut(run_id)?
+            .set_lifecycle(LifecycleStage::Deleted, current_time_millis());
+        Ok(())
+    }
+
+    fn restore_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.state()
+            .run_mut(run_id)?
+            .set_lifecycle(LifecycleStage::Active, current_time_millis());
+        Ok(())
+    }
+
+    fn deleted_runs(&self, older_than_ms: i64) -> MLFlowResult<Vec<RunInfo>> {
+        let now = current_time_millis();
+        Ok(self
+            .state()
+            .runs
+            .iter()
+            .filter(|r| r.info.lifecycle_stage == Some(LifecycleStage::Deleted))
+            .filter(|r| {
+                older_than_ms == 0 || r.deleted_time.is_some_and(|t| now - t >= older_than_ms)
+            })
+            .map(|r| r.info.clone())
+            .collect())
+    }
+
+    fn purge_run(&self, run_id: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        if state.run(run_id)?.info.lifecycle_stage != Some(LifecycleStage::Deleted) {
+            return Err(InvalidParameterValue(format!(
+                "Run '{}' must be deleted before it is purged",
+                run_id
+            )));
+        }
+        state.remove_run(run_id);
+        Ok(())
+    }
+
+    fn purge_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        if state.experiment(experiment_id)?.lifecycle_stage != Some(LifecycleStage::Deleted) {
+            return Err(InvalidParameterValue(format!(
+                "Experiment '{}' must be deleted before it is purged",
+                experiment_id
+            )));
+        }
+        let run_ids: Vec<String> = state
+            .runs
+            .iter()
+            .filter(|r| r.info.experiment_id == experiment_id)
+            .map(|r| r.info.run_id.clone())
+            .collect();
+        for run_id in run_ids {
+            state.remove_run(&run_id);
+        }
+        state
+            .experiments
+            .retain(|e| e.experiment_id != experiment_id);
+        Ok(())
+    }
+
+    fn search_runs(&self, request: &SearchRunsRequest) -> MLFlowResult<SearchRunsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut runs = vec![];
+        for run in self.state().runs.iter().rev() {
+            if !request.experiment_ids.contains(&run.info.experiment_id)
+                || !matches_view_type(run.info.lifecycle_stage, request.run_view_type)
+            {
+                continue;
+            }
+            if all_match(&clauses, |clause| run_value(&run.info, &run.data, clause))? {
+                runs.push(RunResponse {
+                    info: run.info.clone(),
+                    data: run.data.clone(),
+                    inputs: run.inputs.clone(),
+                });
+            }
+        }
+        runs.sort_by_key(|r| std::cmp::Reverse(r.info.start_time));
+
+        let (runs, next_page_token) = page(
+            runs,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchRunsResponse {
+            runs,
+            next_page_token,
+        })
+    }
+
+    fn log_metric(&self, request: LogMetricRequest) -> MLFlowResult<()> {
+        self.state().log_metric(&request.run_id, request.metric)
+    }
+
+    fn log_param(&self, request: LogParamRequest) -> MLFlowResult<()> {
+        self.state().log_param(&request.run_id, request.param)
+    }
+
+    fn get_metric_history(
+        &self,
+        run_id: &str,
+        key: &str,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<MetricHistoryResponse> {
+        let history = self
+            .state()
+            .run(run_id)?
+            .metric_history
+            .iter()
+            .filter(|m| m.key == key)
+            .cloned()
+            .collect();
+        let (metrics, next_page_token) = page(history, None, page_token)?;
+        Ok(MetricHistoryResponse {
+            metrics,
+            next_page_token,
+        })
+    }
+
+    fn set_tag(&self, run_id: &str, tag: &RunTag) -> MLFlowResult<()> {
+        self.state().set_tag(run_id, tag.clone())
+    }
+
+    fn delete_tag(&self, run_id: &str, key: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        let run = state.run_mut(run_id)?;
+        if !run.data.tags.iter().any(|t| t.key == key) {
+            return Err(ResourceDoesNotExist(format!(
+                "No tag with name: {} in run with id {}",
+                key, run_id
+            )));
+        }
+        run.data.tags.retain(|t| t.key != key);
+        Ok(())
+    }
+
+    fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.run(run_id)?;
+        for param in params {
+            state.log_param(run_id, param)?;
+        }
+        for metric in metrics {
+            state.log_metric(run_id, metric)?;
+        }
+        for tag in tags {
+            state.set_tag(run_id, tag)?;
+        }
+        Ok(())
+    }
+
+    fn log_inputs(&self, run_id: &str, datasets: Vec<DatasetInput>) -> MLFlowResult<()> {
+        let mut state = self.state();
+        let inputs = &mut state.run_mut(run_id)?.inputs.dataset_inputs;
+        for input in datasets {
+            let logged = inputs.iter().any(|i| {
+                i.dataset.name == input.dataset.name && i.dataset.digest == input.dataset.digest
+            });
+            if !logged {
+                inputs.push(input);
+            }
+        }
+        Ok(())
+    }
+
+    fn upload_artifact(&self, path: &str, local_file: &Path) -> MLFlowResult<()> {
+        let contents = std::fs::read(local_file).map_err(|e| ArtifactError(e.to_string()))?;
+        self.state()
+            .artifacts
+            .insert(path.trim_matches('/').to_string(), contents);
+        Ok(())
+    }
+
+    fn list_artifacts(
+        &self,
+        run_id: &str,
+        path: Option<&str>,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<ListArtifactsResponse> {
+        let state = self.state();
+        let root = state.run_artifact_root(run_id)?;
+        let relative = path.unwrap_or_default().trim_matches('/');
+        let directory = join_artifact_path(&root, relative)?;
+
+        let mut files: Vec<FileInfo> = vec![];
+        for (artifact, contents) in state.artifacts.range(format!("{}/", directory)..) {
+            let Some(rest) = artifact.strip_prefix(&format!("{}/", directory)) else {
+                break;
+            };
+            let (name, is_dir) = match rest.split_once('/') {
+                Some((name, _)) => (name, true),
+                None => (rest, false),
+            };
+            let path = if relative.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            if files.last().map(|f| f.path == path).unwrap_or(false) {
+                continue;
+            }
+            files.push(FileInfo {
+                path,
+                is_dir,
+                file_size: (!is_dir).then_some(contents.len() as i64),
+                extra: serde_json::Map::new(),
+            });
+        }
+
+        let (files, next_page_token) = page(files, None, page_token)?;
+        Ok(ListArtifactsResponse {
+            files,
+            next_page_token,
+        })
+    }
+
+    fn download_artifact(&self, path: &str, local_file: &Path) -> MLFlowResult<()> {
+        let state = self.state();
+        let contents = state
+            .artifacts
+            .get(path.trim_matches('/'))
+            .ok_or_else(|| ArtifactError(format!("Could not download artifact {}", path)))?;
+        std::fs::write(local_file, contents).map_err(|e| ArtifactError(e.to_string()))
+    }
+
+    fn delete_artifact(&self, path: &str) -> MLFlowResult<()> {
+        let path = path.trim_matches('/');
+        let directory = format!("{}/", path);
+        self.state()
+            .artifacts
+            .retain(|artifact, _| artifact != path && !artifact.starts_with(&directory));
+        Ok(())
+    }
+
+    fn create_registered_model(
+        &self,
+        request: CreateRegisteredModelRequest,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        let mut state = self.state();
+        if state
+            .registered_models
+            .iter()
+            .any(|m| m.name == request.name)
+        {
+            return Err(ResourceAlreadyExists(format!(
+                "Registered Model (name={}) already exists.",
+                request.name
+            )));
+        }
+
+        let now = current_time_millis();
+        state.registered_models.push(RegisteredModel {
+            name: request.name.clone(),
+            description: request.description,
+            creation_timestamp: Some(now),
+            last_updated_timestamp: Some(now),
+            tags: request.tags,
+            latest_versions: vec![],
+            extra: serde_json::Map::new(),
+            client: SharedClient::default(),
+        });
+        state.registered_model_response(&request.name)
+    }
+
+    fn get_registered_model(&self, name: &str) -> MLFlowResult<RegisteredModelResponse> {
+        self.state().registered_model_response(name)
+    }
+
+    fn rename_registered_model(
+        &self,
+        name: &str,
+        new_name: &str,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        let mut state = self.state();
+        if state.registered_models.iter().any(|m| m.name == new_name) {
+            return Err(ResourceAlreadyExists(format!(
+                "Registered Model (name={}) already exists.",
+                new_name
+            )));
+        }
+
+        let model = state.registered_model(name)?;
+        model.name = new_name.to_string();
+        model.last_updated_timestamp = Some(current_time_millis());
+        for version in state.model_versions.iter_mut().filter(|v| v.name == name) {
+            version.name = new_name.to_string();
+        }
+        state.registered_model_response(new_name)
+    }
+
+    fn delete_registered_model(&self, name: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.registered_model(name)?;
+        state.registered_models.retain(|m| m.name != name);
+        state.model_versions.retain(|v| v.name != name);
+        Ok(())
+    }
+
+    fn set_registered_model_tag(&self, name: &str, tag: &RegisteredModelTag) -> MLFlowResult<()> {
+        let mut state = self.state();
+        let model = state.registered_model(name)?;
+        model.tags.retain(|t| t.key != tag.key);
+        model.tags.push(tag.clone());
+        Ok(())
+    }
+
+    fn delete_registered_model_tag(&self, name: &str, key: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.registered_model(name)?.tags.retain(|t| t.key != key);
+        Ok(())
+    }
+
+    fn search_registered_models(
+        &self,
+        request: &SearchRegisteredModelsRequest,
+    ) -> MLFlowResult<SearchRegisteredModelsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut state = self.state();
+        let mut names = vec![];
+        for model in state.registered_models.iter() {
+            if all_match(&clauses, |clause| registered_model_value(model, clause))? {
+                names.push(model.name.clone());
+            }
+        }
+        names.sort();
+
+        let (names, next_page_token) = page(
+            names,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        let registered_models = names
+            .iter()
+            .map(|name| Ok(state.registered_model_response(name)?.registered_model))
+            .collect::<MLFlowResult<_>>()?;
+        Ok(SearchRegisteredModelsResponse {
+            registered_models,
+            next_page_token,
+        })
+    }
+
+    fn create_model_version(
+        &self,
+        request: CreateModelVersionRequest,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        let mut state = self.state();
+        state.registered_model(&request.name)?;
+        let version = state
+            .model_versions
+            .iter()
+            .filter(|v| v.name == request.name)
+            .map(version_number)
+            .max()
+            .unwrap_or_default()
+            + 1;
+
+        let now = current_time_millis();
+        let model_version = ModelVersion {
+            name: request.name,
+            version: version.to_string(),
+            creation_timestamp: Some(now),
+            last_updated_timestamp: Some(now),
+            current_stage: ModelVersionStage::None,
+            description: request.description,
+            source: Some(request.source),
+            run_id: request.run_id,
+            status: Some(ModelVersionStatus::Ready),
+            status_message: None,
+            tags: request.tags,
+            extra: serde_json::Map::new(),
+            client: SharedClient::default(),
+        };
+        state.model_versions.push(model_version.clone());
+        Ok(ModelVersionResponse { model_version })
+    }
+
+    fn get_model_version(&self, name: &str, version: &str) -> MLFlowResult<ModelVersionResponse> {
+        let mut state = self.state();
+        let model_version = state.model_version(name, version)?.clone();
+        Ok(ModelVersionResponse { model_version })
+    }
+
+    fn transition_model_version_stage(
+        &self,
+        name: &str,
+        version: &str,
+        stage: ModelVersionStage,
+        archive_existing_versions: bool,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        let mut state = self.state();
+        state.model_version(name, version)?;
+
+        let now = current_time_millis();
+        let archive = archive_existing_versions
+            && matches!(
+                stage,
+                ModelVersionStage::Staging | ModelVersionStage::Production
+            );
+        for other in state
+            .model_versions
+            .iter_mut()
+            .filter(|v| v.name == name && v.version != version && v.current_stage == stage)
+        {
+            if archive {
+                other.current_stage = ModelVersionStage::Archived;
+                other.last_updated_timestamp = Some(now);
+            }
+        }
+
+        let model_version = state.model_version(name, version)?;
+        model_version.current_stage = stage;
+        model_version.last_updated_timestamp = Some(now);
+        Ok(ModelVersionResponse {
+            model_version: model_version.clone(),
+        })
+    }
+
+    fn delete_model_version(&self, name: &str, version: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.model_version(name, version)?;
+        state
+            .model_versions
+            .retain(|v| !(v.name == name && v.version == version));
+        Ok(())
+    }
+
+    fn set_model_version_tag(
+        &self,
+        name: &str,
+        version: &str,
+        tag: &ModelVersionTag,
+    ) -> MLFlowResult<()> {
+        let mut state = self.state();
+        let model_version = state.model_version(name, version)?;
+        model_version.tags.retain(|t| t.key != tag.key);
+        model_version.tags.push(tag.clone());
+        Ok(())
+    }
+
+    fn delete_model_version_tag(&self, name: &str, version: &str, key: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state
+            .model_version(name, version)?
+            .tags
+            .retain(|t| t.key != key);
+        Ok(())
+    }
+
+    fn search_model_versions(
+        &self,
+        request: &SearchModelVersionsRequest,
+    ) -> MLFlowResult<SearchModelVersionsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut model_versions = vec![];
+        for version in self.state().model_versions.iter() {
+            if all_match(&clauses, |clause| model_version_value(version, clause))? {
+                model_versions.push(version.clone());
+            }
+        }
+        // newest version first within each model, as the server orders them
+        model_versions.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then(version_number(b).cmp(&version_number(a)))
+        });
+
+        let (model_versions, next_page_token) = page(
+            model_versions,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchModelVersionsResponse {
+            model_versions,
+            next_page_token,
+        })
+    }
+
+    fn start_trace(&self, request: StartTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        let mut state = self.state();
+        let artifact_location = state
+            .experiment(&request.experiment_id)?
+            .artifact_location
+            .clone()
+            .unwrap_or_default();
+
+        let request_id = format!("tr-{:032x}", state.next_id());
+        let mut trace = TraceInfo {
+            request_id: request_id.clone(),
+            experiment_id: request.experiment_id,
+            timestamp_ms: request.timestamp_ms,
+            execution_time_ms: None,
+            status: TraceStatus::InProgress,
+            request_metadata: request.request_metadata,
+            tags: request.tags,
+            extra: serde_json::Map::new(),
+        };
+        let artifact_location = format!(
+            "{}/traces/{}/artifacts",
+            artifact_location.trim_end_matches('/'),
+            request_id
+        );
+        set_trace_tag(
+            &mut trace,
+            TraceTag::from((MLFLOW_ARTIFACT_LOCATION, artifact_location.as_str())),
+        );
+        state.traces.push(trace.clone());
+        Ok(TraceInfoResponse { trace_info: trace })
+    }
+
+    fn end_trace(&self, request: EndTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        let mut state = self.state();
+        let trace = state.trace(&request.request_id)?;
+        trace.status = request.status;
+        trace.execution_time_ms = Some(request.timestamp_ms - trace.timestamp_ms);
+        for metadata in request.request_metadata {
+            trace.request_metadata.retain(|m| m.key != metadata.key);
+            trace.request_metadata.push(metadata);
+        }
+        for tag in request.tags {
+            set_trace_tag(trace, tag);
+        }
+        Ok(TraceInfoResponse {
+            trace_info: trace.clone(),
+        })
+    }
+
+    fn get_trace_info(&self, request_id: &str) -> MLFlowResult<TraceInfoResponse> {
+        Ok(TraceInfoResponse {
+            trace_info: self.state().trace(request_id)?.clone(),
+        })
+    }
+
+    fn set_trace_tag(&self, request_id: &str, tag: &TraceTag) -> MLFlowResult<()> {
+        set_trace_tag(self.state().trace(request_id)?, tag.clone());
+        Ok(())
+    }
+
+    #[cfg(feature = "webhooks")]
+    fn create_registry_webhook(
+        &self,
+        request: &CreateRegistryWebhookRequest,
+    ) -> MLFlowResult<RegistryWebhookResponse> {
+        let mut state = self.state();
+        if let Some(model_name) = &request.model_name {
+            state.registered_model(model_name)?;
+        }
+        let now = current_time_millis();
+        let webhook = RegistryWebhook {
+            id: format!("{:032x}", state.next_id()),
+            creation_timestamp: Some(now),
+            last_updated_timestamp: Some(now),
+            description: request.description.clone(),
+            status: request.status.unwrap_or_default(),
+            events: request.events.clone(),
+            http_url_spec: request.http_url_spec.clone(),
+            job_spec: request.job_spec.clone(),
+            model_name: request.model_name.clone(),
+            extra: serde_json::Map::new(),
+            client: SharedClient::default(),
+        };
+        state.webhooks.push(webhook.clone());
+        Ok(RegistryWebhookResponse { webhook })
+    }
+
+    #[cfg(feature = "webhooks")]
+    fn list_registry_webhooks(
+        &self,
+        request: &ListRegistryWebhooksRequest,
+    ) -> MLFlowResult<ListRegistryWebhooksResponse> {
+        let webhooks: Vec<RegistryWebhook> = self
+            .state()
+            .webhooks
+            .iter()
+            .filter(|w| request.model_name.is_none() || w.model_name == request.model_name)
+            .filter(|w| {
+                request.events.is_empty() || w.events.iter().any(|e| request.events.contains(e))
+            })
+            .cloned()
+            .collect();
+        let (webhooks, next_page_token) = page(webhooks, None, request.page_token.as_deref())?;
+        Ok(ListRegistryWebhooksResponse {
+            webhooks,
+            next_page_token,
+        })
+    }
+
+    #[cfg(feature = "webhooks")]
+    fn update_registry_webhook(
+        &self,
+        request: &UpdateRegistryWebhookRequest,
+    ) -> MLFlowResult<RegistryWebhookResponse> {
+        let mut state = self.state();
+        let webhook = state.webhook(&request.id)?;
+        if let Some(description) = &request.description {
+            webhook.description = Some(description.clone());
+        }
+        if let Some(events) = &request.events {
+            webhook.events = events.clone();
+        }
+        if let Some(status) = request.status {
+            webhook.status = status;
+        }
+        if let Some(http_url_spec) = &request.http_url_spec {
+            webhook.http_url_spec = Some(http_url_spec.clone());
+            webhook.job_spec = None;
+        }
+        if let Some(job_spec) = &request.job_spec {
+            webhook.job_spec = Some(job_spec.clone());
+            webhook.http_url_spec = None;
+        }
+        webhook.last_updated_timestamp = Some(current_time_millis());
+        Ok(RegistryWebhookResponse {
+            webhook: webhook.clone(),
+        })
+    }
+
+    #[cfg(feature = "webhooks")]
+    fn delete_registry_webhook(&self, id: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.webhook(id)?;
+        state.webhooks.retain(|w| w.id != id);
+        Ok(())
+    }
+
+    /// Nothing is sent; every test is answered as if the endpoint accepted it.
+    #[cfg(feature = "webhooks")]
+    fn test_registry_webhook(
+        &self,
+        request: &TestRegistryWebhookRequest,
+    ) -> MLFlowResult<TestRegistryWebhookResponse> {
+        let mut state = self.state();
+        let webhook = state.webhook(&request.id)?;
+        if let Some(event) = request.event {
+            if !webhook.events.contains(&event) {
+                return Err(InvalidParameterValue(format!(
+                    "webhook {} does not fire on {}",
+                    request.id, event
+                )));
+            }
+        }
+        Ok(TestRegistryWebhookResponse {
+            webhook: WebhookTestResult {
+                status_code: 200,
+                body: None,
+                extra: serde_json::Map::new(),
+            },
+        })
+    }
+
+    #[cfg(feature = "auth")]
+    fn create_experiment_permission(
+        &self,
+        request: &ExperimentPermissionRequest,
+    ) -> MLFlowResult<ExperimentPermissionResponse> {
+        let mut state = self.state();
+        state.experiment(&request.experiment_id)?;
+        if state
+            .experiment_permission(&request.experiment_id, &request.username)
+            .is_ok()
+        {
+            return Err(ResourceAlreadyExists(format!(
+                "Experiment permission (experiment_id={}, username={}) already exists.",
+                request.experiment_id, request.username
+            )));
+        }
+        let user = state.user(&request.username)?;
+        let experiment_permission = ExperimentPermission {
+            experiment_id: request.experiment_id.clone(),
+            user_id: Some(user.id),
+            permission: request.permission,
+            extra: serde_json::Map::new(),
+        };
+        user.experiment_permissions
+            .push(experiment_permission.clone());
+        Ok(ExperimentPermissionResponse {
+            experiment_permission,
+        })
+    }
+
+    #[cfg(feature = "auth")]
+    fn get_experiment_permission(
+        &self,
+        experiment_id: &str,
+        username: &str,
+    ) -> MLFlowResult<ExperimentPermissionResponse> {
+        let experiment_permission = self
+            .state()
+            .experiment_permission(experiment_id, username)?
+            .clone();
+        Ok(ExperimentPermissionResponse {
+            experiment_permission,
+        })
+    }
+
+    #[cfg(feature = "auth")]
+    fn update_experiment_permission(
+        &self,
+        request: &ExperimentPermissionRequest,
+    ) -> MLFlowResult<()> {
+        self.state()
+            .experiment_permission(&request.experiment_id, &request.username)?
+            .permission = request.permission;
+        Ok(())
+    }
+
+    #[cfg(feature = "auth")]
+    fn delete_experiment_permission(
+        &self,
+        experiment_id: &str,
+        username: &str,
+    ) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.experiment_permission(experiment_id, username)?;
+        state
+            .user(username)?
+            .experiment_permissions
+            .retain(|p| p.experiment_id != experiment_id);
+        Ok(())
+    }
+
+    #[cfg(feature = "auth")]
+    fn get_user(&self, username: &str) -> MLFlowResult<UserResponse> {
+        let user = self.state().user(username)?.clone();
+        Ok(UserResponse { user })
+    }
+
+    #[cfg(feature = "auth")]
+    fn create_user(&self, request: &UserPasswordRequest) -> MLFlowResult<UserResponse> {
+        let mut state = self.state();
+        if state.user(&request.username).is_ok() {
+            return Err(ResourceAlreadyExists(format!(
+                "User (username={}) already exists.",
+                request.username
+            )));
+        }
+        let user = User {
+            id: state.next_id() as i64,
+            username: request.username.clone(),
+            is_admin: false,
+            experiment_permissions: vec![],
+            registered_model_permissions: vec![],
+            extra: serde_json::Map::new(),
+            client: SharedClient::default(),
+        };
+        state.users.push(user.clone());
+        Ok(UserResponse { user })
+    }
+
+    #[cfg(feature = "auth")]
+    fn update_user_password(&self, request: &UserPasswordRequest) -> MLFlowResult<()> {
+        self.state().user(&request.username).map(|_| ())
+    }
+
+    #[cfg(feature = "auth")]
+    fn update_user_admin(&self, request: &UpdateUserAdminRequest) -> MLFlowResult<()> {
+        self.state().user(&request.username)?.is_admin = request.is_admin;
+        Ok(())
+    }
+
+    #[cfg(feature = "auth")]
+    fn delete_user(&self, username: &str) -> MLFlowResult<()> {
+        let mut state = self.state();
+        state.user(username)?;
+        state.users.retain(|u| u.username != username);
+        Ok(())
+    }
+}
+
+fn set_trace_tag(trace: &mut TraceInfo, tag: TraceTag) {
+    trace.tags.retain(|t| t.key != tag.key);
+    trace.tags.push(tag);
+}
+
+fn registered_model_value(model: &RegisteredModel, clause: &Clause) -> MLFlowResult<Option<Value>> {
+    match (clause.entity.as_str(), clause.key.as_str()) {
+        ("tags", key) => Ok(model
+            .tags
+            .iter()
+            .find(|t| t.key == key)
+            .map(|t| Value::String(t.value.clone()))),
+        ("attributes", "name") => Ok(Some(Value::String(model.name.clone()))),
+        (entity, key) => Err(SearchError(format!(
+            "unsupported registered model filter key {}.{}",
+            entity, key
+        ))),
+    }
+}
+
+fn model_version_value(version: &ModelVersion, clause: &Clause) -> MLFlowResult<Option<Value>> {
+    match (clause.entity.as_str(), clause.key.as_str()) {
+        ("tags", key) => Ok(version
+            .tags
+            .iter()
+            .find(|t| t.key == key)
+            .map(|t| Value::String(t.value.clone()))),
+        ("attributes", "name") => Ok(Some(Value::String(version.name.clone()))),
+        ("attributes", "run_id") => Ok(version.run_id.clone().map(Value::String)),
+        ("attributes", "source_path") => Ok(version.source.clone().map(Value::String)),
+        ("attributes", "version_number") => Ok(Some(Value::Number(version_number(version) as f64))),
+        (entity, key) => Err(SearchError(format!(
+            "unsupported model version filter key {}.{}",
+            entity, key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::ExperimentIdentifierType;
+    use crate::search::RunViewType;
+    use crate::MLFlowError;
+    use crate::{
+        ExperimentBuilder, ExperimentIdentifier, ExperimentLoader, ExperimentSearch, Filter,
+        ModelVersionBuilder, RegisteredModelBuilder, RegisteredModelLoader, RunBuilder,
+        RunIdentifier, RunLoader, RunSearch,
+    };
+
+    fn experiment(mock: &MockMLFlowClient, name: &str) -> String {
+        ExperimentBuilder::new(name)
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap()
+            .experiment_id()
+            .unwrap()
+    }
+
+    #[test]
+    fn experiments() {
+        let mock = MockMLFlowClient::default();
+        let id = experiment(&mock, "mock-experiment");
+
+        let err = ExperimentBuilder::new("mock-experiment")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .expect_err("names are unique");
+        assert!(matches!(err, MLFlowError::ResourceAlreadyExists(_)));
+
+        let mut loaded = ExperimentLoader::default()
+            .with_mock_client(mock.clone())
+            .load(ExperimentIdentifierType::ByName(
+                "mock-experiment".to_string(),
+            ))
+            .unwrap();
+        assert_eq!(loaded.experiment_id(), Some(id.clone()));
+        assert_eq!(
+            loaded.artifact_location,
+            Some(format!("mlflow-artifacts:/{}", id))
+        );
+
+        loaded.delete().unwrap();
+        let active: Vec<_> = ExperimentSearch::default()
+            .with_mock_client(mock.clone())
+            .search()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(active.len(), 1, "only the default experiment is active");
+        loaded.restore().unwrap();
+
+        let found: Vec<_> = ExperimentSearch::default()
+            .with_filter("name LIKE 'mock-%'")
+            .with_mock_client(mock.clone())
+            .search()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn runs() {
+        let mock = MockMLFlowClient::default();
+        let experiment_id = experiment(&mock, "runs");
+
+        let mut parent = RunBuilder::new(&experiment_id)
+            .unwrap()
+            .with_run_name("parent")
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        parent.log_metric("rmse", 0.5, Some(1), None).unwrap();
+        parent.log_metric("rmse", 0.3, Some(2), None).unwrap();
+        parent.log_param("learning_rate", 0.01).unwrap();
+        parent.log_param("learning_rate", 0.01).unwrap();
+        let err = parent
+            .log_param("learning_rate", 0.02)
+            .expect_err("params are immutable");
+        assert!(matches!(err, MLFlowError::ParamValueChanged(_)));
+        parent.set_tag("team", "nlp").unwrap();
+
+        let child = RunBuilder::new(&experiment_id)
+            .unwrap()
+            .with_parent(parent.run_id())
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        child.log_metric("rmse", 0.9, None, None).unwrap();
+
+        assert_eq!(parent.get_metric_history("rmse").unwrap().len(), 2);
+        let data = mock.run_data(&parent.run_id()).unwrap();
+        assert_eq!(data.metrics.len(), 1);
+        assert_eq!(data.metrics[0].value, 0.3);
+        assert_eq!(data.params.len(), 1);
+
+        let children = parent.children().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].run_id(), child.run_id());
+
+        let good: Vec<_> = RunSearch::new(vec![experiment_id.as_str()])
+            .unwrap()
+            .with_filter(
+                Filter::metric("rmse")
+                    .lt(0.5)
+                    .and(Filter::tag("team").eq("nlp"))
+                    .build()
+                    .unwrap(),
+            )
+            .with_mock_client(mock.clone())
+            .search()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(good.len(), 1);
+        assert_eq!(good[0].run_id(), parent.run_id());
+
+        parent.finish(RunStatus::Finished).unwrap();
+        assert_eq!(
+            mock.run_info(&parent.run_id()).unwrap().status,
+            RunStatus::Finished
+        );
+        assert_eq!(parent.info().run_name.as_deref(), Some("parent"));
+
+        parent.reopen().unwrap();
+        parent
+            .update(Some("renamed"), Some(RunStatus::Killed), None)
+            .unwrap();
+        let info = mock.run_info(&parent.run_id()).unwrap();
+        assert_eq!(info.run_name.as_deref(), Some("renamed"));
+        assert_eq!(info.status, RunStatus::Killed);
+        assert_eq!(info.end_time, parent.end_time());
+        assert_eq!(parent.info().run_name.as_deref(), Some("renamed"));
+
+        let loaded = RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(parent.run_id())
+            .unwrap();
+        assert_eq!(loaded.info().run_name.as_deref(), Some("renamed"));
+        assert_eq!(loaded.data().metric("rmse"), Some(0.3));
+        assert_eq!(loaded.data().param("learning_rate"), Some("0.01"));
+        assert_eq!(loaded.data().tag("team"), Some("nlp"));
+        assert!(matches!(
+            RunLoader::default()
+                .with_mock_client(mock.clone())
+                .load("missing"),
+            Err(MLFlowError::RunNotFound(_))
+        ));
+
+        let search = |view_type| {
+            RunSearch::new(vec![experiment_id.as_str()])
+                .unwrap()
+                .with_run_view_type(view_type)
+                .with_mock_client(mock.clone())
+                .search()
+                .map(|r| r.map(|r| r.run_id()))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        let mut child = child;
+        child.delete().unwrap();
+        assert_eq!(child.info().lifecycle_stage, Some(LifecycleStage::Deleted));
+        assert_eq!(search(RunViewType::ActiveOnly), vec![parent.run_id()]);
+        assert_eq!(search(RunViewType::DeletedOnly), vec![child.run_id()]);
+        assert_eq!(search(RunViewType::All).len(), 2);
+        child.restore().unwrap();
+        assert_eq!(search(RunViewType::ActiveOnly).len(), 2);
+    }
+
+    #[test]
+    fn injected_as_trait_object() {
+        let mock = MockMLFlowClient::default();
+        let client: Box<dyn MLFlowClient> = Box::new(mock.clone());
+        let client: Arc<dyn MLFlowClient> = client.into();
+
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_dyn_client(client.clone())
+            .build()
+            .unwrap();
+        run.log_param("optimizer", "adam").unwrap();
+
+        assert_eq!(mock.run_data(&run.run_id()).unwrap().params.len(), 1);
+        let runs: Vec<_> = RunSearch::new(vec!["0"])
+            .unwrap()
+            .with_dyn_client(client)
+            .search()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+    }
+
+    #[test]
+    fn search_pagination() {
+        let mock = MockMLFlowClient::default();
+        let experiment_id = experiment(&mock, "pages");
+        for _ in 0..5 {
+            RunBuilder::new(&experiment_id)
+                .unwrap()
+                .with_mock_client(mock.clone())
+                .build()
+                .unwrap();
+        }
+
+        let runs: Vec<_> = RunSearch::new(vec![experiment_id.as_str()])
+            .unwrap()
+            .with_max_results(2)
+            .with_mock_client(mock.clone())
+            .search()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(runs.len(), 5);
+    }
+
+    #[test]
+    fn artifacts() {
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new(experiment(&mock, "artifacts"))
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("weights.bin");
+        std::fs::write(&file, b"weights").unwrap();
+        run.log_artifact(&file, Some("model")).unwrap();
+        run.log_artifact(&file, None).unwrap();
+
+        assert_eq!(
+            mock.artifact(&run.run_id(), "model/weights.bin"),
+            Some(b"weights".to_vec())
+        );
+        let root = run.list_artifacts(None).unwrap();
+        assert_eq!(root.len(), 2);
+        assert!(root.iter().any(|f| f.path == "model" && f.is_dir));
+        let model = run.list_artifacts(Some("model")).unwrap();
+        assert_eq!(model[0].path, "model/weights.bin");
+        assert_eq!(model[0].file_size, Some(7));
+
+        let dest = tempfile::tempdir().unwrap();
+        let local = run.download_artifact("model", dest.path()).unwrap();
+        assert_eq!(
+            std::fs::read(local.join("weights.bin")).unwrap(),
+            b"weights"
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let mock = MockMLFlowClient::default();
+        RegisteredModelBuilder::new("classifier")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+
+        let mut first = ModelVersionBuilder::new("classifier", "runs:/abc/model")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let mut second = ModelVersionBuilder::new("classifier", "runs:/def/model")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        assert_eq!(second.version, "2");
+
+        first
+            .transition_stage(ModelVersionStage::Production, false)
+            .unwrap();
+        second
+            .transition_stage(ModelVersionStage::Production, true)
+            .unwrap();
+
+        let model = RegisteredModelLoader::default()
+            .with_mock_client(mock.clone())
+            .load("classifier")
+            .unwrap();
+        let stages: Vec<_> = model
+            .latest_versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.current_stage))
+            .collect();
+        assert!(stages.contains(&("1", ModelVersionStage::Archived)));
+        assert!(stages.contains(&("2", ModelVersionStage::Production)));
+
+        RegisteredModelLoader::default()
+            .with_mock_client(mock.clone())
+            .load("missing")
+            .expect_err("unknown model");
+    }
+
+    #[test]
+    fn registry_search() {
+        let mock = MockMLFlowClient::default();
+        for (name, team) in [
+            ("fraud-b", "fraud"),
+            ("fraud-a", "fraud"),
+            ("churn", "growth"),
+        ] {
+            RegisteredModelBuilder::new(name)
+                .unwrap()
+                .with_tag(("team", team))
+                .with_mock_client(mock.clone())
+                .build()
+                .unwrap();
+            for run_id in ["abc", "def"] {
+                ModelVersionBuilder::new(name, format!("runs:/{}/model", run_id))
+                    .unwrap()
+                    .with_run_id(run_id)
+                    .with_mock_client(mock.clone())
+                    .build()
+                    .unwrap();
+            }
+        }
+
+        let models: Vec<_> = crate::RegisteredModelSearch::default()
+            .with_filter("tags.team = 'fraud'")
+            .with_max_results(1)
+            .with_mock_client(mock.clone())
+            .search()
+            .map(|m| m.unwrap().name)
+            .collect();
+        assert_eq!(models, vec!["fraud-a", "fraud-b"]);
+
+        let mut version = crate::ModelVersionSearch::default()
+            .with_filter("name = 'fraud-a' AND run_id = 'def'")
+            .with_mock_client(mock.clone())
+            .search()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.version, "2");
+        version
+            .transition_stage(ModelVersionStage::Production, false)
+            .unwrap();
+
+        let versions: Vec<_> = crate::ModelVersionSearch::default()
+            .with_filter("version_number >= 1")
+            .with_max_results(4)
+            .with_mock_client(mock.clone())
+            .search()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(versions.len(), 6);
+        assert_eq!(
+            (versions[0].name.as_str(), versions[0].version.as_str()),
+            ("churn", "2")
+        );
+        assert_eq!(versions[2].current_stage, ModelVersionStage::Production);
+
+        crate::ModelVersionSearch::default()
+            .with_filter("metrics.rmse < 1")
+            .with_mock_client(mock)
+            .search()
+            .next()
+            .unwrap()
+            .expect_err("unsupported filter key");
+    }
+}
@@ -0,0 +1,518 @@
+//! Logging models in the MLflow Model format, a directory of files described by an `MLmodel`
+//! YAML document, so they can be loaded, served and registered by the rest of the MLflow stack.
+
+use crate::client::{CreateModelVersionRequest, CreateRegisteredModelRequest, SearchRunsRequest};
+use crate::registry::ModelVersion;
+use crate::run::{current_time_millis, Run, RunIdentifier};
+use crate::search::RunViewType;
+use crate::signature::{InputExample, ModelSignature, INPUT_EXAMPLE_FILE_NAME};
+use crate::tags::MLFLOW_LOGGED_MODELS;
+use crate::MLFlowError::{
+    ArtifactError, ModelVersionBuilderError, RegisteredModelNotFound, ResourceAlreadyExists,
+    ResourceDoesNotExist,
+};
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the file describing a model, stored at the root of the model directory.
+pub const MLMODEL_FILE_NAME: &str = "MLmodel";
+
+/// One way of loading a model, e.g. `python_function` or `onnx`, and the configuration its
+/// loader needs. A model may be logged with several flavors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Flavor {
+    pub(crate) name: String,
+    pub(crate) config: Map<String, Value>,
+}
+
+impl Flavor {
+    pub fn new(name: impl AsRef<str>) -> Flavor {
+        Flavor {
+            name: name.as_ref().to_string(),
+            config: Map::new(),
+        }
+    }
+
+    /// Adds a key to the flavor's section of the `MLmodel` file.
+    pub fn with_config(mut self, key: impl AsRef<str>, value: impl Into<Value>) -> Self {
+        self.config.insert(key.as_ref().to_string(), value.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Contents of an `MLmodel` file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MLModel {
+    pub artifact_path: String,
+    pub flavors: BTreeMap<String, Value>,
+    pub model_uuid: String,
+    pub run_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_input_example_info: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ModelSignature>,
+    pub utc_time_created: String,
+}
+
+impl MLModel {
+    pub fn to_yaml(&self) -> MLFlowResult<String> {
+        serde_yaml::to_string(self).map_err(|e| ArtifactError(e.to_string()))
+    }
+
+    pub fn from_yaml(yaml: &str) -> MLFlowResult<MLModel> {
+        serde_yaml::from_str(yaml).map_err(|e| ArtifactError(e.to_string()))
+    }
+}
+
+/// What [`ModelLogger::log`] logged.
+#[derive(Clone, Debug)]
+pub struct ModelInfo {
+    pub artifact_path: String,
+    /// `runs:/<run_id>/<artifact_path>`, the URI MLflow tools load the model from.
+    pub model_uri: String,
+    pub model_uuid: String,
+    pub run_id: String,
+    pub utc_time_created: String,
+    pub flavors: Vec<String>,
+    /// Set when the model was registered with
+    /// [`ModelLogger::with_registered_model_name`].
+    pub registered_model_version: Option<ModelVersion>,
+}
+
+/// Logs a directory of model files as an MLflow Model of a run.
+///
+/// ```no_run
+/// use mlflow_rs::{Flavor, ModelLogger, RunBuilder};
+///
+/// let run = RunBuilder::new("1").unwrap().build().unwrap();
+/// let info = ModelLogger::new(&run, "model", "./target/model")
+///     .unwrap()
+///     .with_flavor(Flavor::new("onnx").with_config("data", "model.onnx"))
+///     .with_registered_model_name("classifier")
+///     .log()
+///     .unwrap();
+/// println!("logged {}", info.model_uri);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ModelLogger<'a> {
+    run: &'a Run,
+    artifact_path: String,
+    local_path: PathBuf,
+    flavors: Vec<Flavor>,
+    metadata_files: Vec<(String, String)>,
+    signature: Option<ModelSignature>,
+    input_example: Option<InputExample>,
+    registered_model_name: Option<String>,
+}
+
+impl<'a> ModelLogger<'a> {
+    /// Logs the files in `local_path` under the run's `artifact_path` artifact directory.
+    /// `local_path` may also be a single file, for models serialized to one file.
+    pub fn new(
+        run: &'a Run,
+        artifact_path: impl AsRef<str>,
+        local_path: impl AsRef<Path>,
+    ) -> MLFlowResult<ModelLogger<'a>> {
+        let artifact_path = artifact_path.as_ref().trim_matches('/');
+        if artifact_path.is_empty() {
+            return Err(ArtifactError("artifact_path cannot be empty".to_string()));
+        }
+        if !local_path.as_ref().exists() {
+            return Err(ArtifactError(format!(
+                "{} does not exist",
+                local_path.as_ref().to_string_lossy()
+            )));
+        }
+
+        Ok(ModelLogger {
+            run,
+            artifact_path: artifact_path.to_string(),
+            local_path: local_path.as_ref().to_path_buf(),
+            flavors: vec![],
+            metadata_files: vec![],
+            signature: None,
+            input_example: None,
+            registered_model_name: None,
+        })
+    }
+
+    pub fn with_flavor(mut self, flavor: Flavor) -> Self {
+        self.flavors.retain(|f| f.name != flavor.name);
+        self.flavors.push(flavor);
+        self
+    }
+
+    /// Records the schema of the model's inputs and outputs, which model serving validates
+    /// requests against.
+    pub fn with_signature(mut self, signature: ModelSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    /// Records the tensor signature of a model taking and returning arrays like `inputs` and
+    /// `outputs`, with their element types and shapes, the first axis being the batch size.
+    #[cfg(feature = "ndarray")]
+    pub fn with_ndarray_signature<A, S, D, B, T, E>(
+        self,
+        inputs: &ndarray::ArrayBase<S, D>,
+        outputs: Option<&ndarray::ArrayBase<T, E>>,
+    ) -> Self
+    where
+        A: crate::signature::TensorElement,
+        S: ndarray::Data<Elem = A>,
+        D: ndarray::Dimension,
+        B: crate::signature::TensorElement,
+        T: ndarray::Data<Elem = B>,
+        E: ndarray::Dimension,
+    {
+        self.with_signature(ModelSignature::from_ndarray(inputs, outputs))
+    }
+
+    /// Saves `example` as `input_example.json` alongside the model.
+    pub fn with_input_example(mut self, example: InputExample) -> Self {
+        self.input_example = Some(example);
+        self
+    }
+
+    /// Adds a generated file, such as `requirements.txt`, next to the `MLmodel` file.
+    pub fn with_metadata_file(mut self, name: impl AsRef<str>, contents: impl AsRef<str>) -> Self {
+        self.metadata_files
+            .retain(|(n, _)| n.as_str() != name.as_ref());
+        self.metadata_files
+            .push((name.as_ref().to_string(), contents.as_ref().to_string()));
+        self
+    }
+
+    /// Registers the logged model as a new version of `name`, creating the registered model if
+    /// it doesn't exist yet.
+    pub fn with_registered_model_name(mut self, name: impl AsRef<str>) -> Self {
+        self.registered_model_name = Some(name.as_ref().to_string());
+        self
+    }
+
+    /// Uploads the model directory with a generated `MLmodel` file, records the model in the
+    /// run's `mlflow.log-model.history` tag and registers it if requested.
+    pub fn log(self) -> MLFlowResult<ModelInfo> {
+        if self.flavors.is_empty() {
+            return Err(ArtifactError(
+                "a model needs at least one flavor".to_string(),
+            ));
+        }
+
+        let mlmodel = MLModel {
+            artifact_path: self.artifact_path.clone(),
+            flavors: self
+                .flavors
+                .iter()
+                .map(|f| (f.name.clone(), Value::Object(f.config.clone())))
+                .collect(),
+            model_uuid: uuid::Uuid::new_v4().simple().to_string(),
+            run_id: self.run.run_id(),
+            saved_input_example_info: self.input_example.as_ref().map(InputExample::info),
+            signature: self.signature.clone(),
+            utc_time_created: utc_time(current_time_millis()),
+        };
+
+        if self.local_path.is_dir() {
+            self.run
+                .log_artifacts(&self.local_path, Some(&self.artifact_path))?;
+        } else {
+            self.run
+                .log_artifact(&self.local_path, Some(&self.artifact_path))?;
+        }
+        self.log_mlmodel(&mlmodel)?;
+        self.record_history(&mlmodel)?;
+
+        let registered_model_version = match &self.registered_model_name {
+            Some(name) => Some(self.register(name)?),
+            None => None,
+        };
+
+        Ok(ModelInfo {
+            model_uri: format!("runs:/{}/{}", mlmodel.run_id, mlmodel.artifact_path),
+            artifact_path: mlmodel.artifact_path,
+            model_uuid: mlmodel.model_uuid,
+            run_id: mlmodel.run_id,
+            utc_time_created: mlmodel.utc_time_created,
+            flavors: self.flavors.into_iter().map(|f| f.name).collect(),
+            registered_model_version,
+        })
+    }
+
+    fn log_mlmodel(&self, mlmodel: &MLModel) -> MLFlowResult<()> {
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", mlmodel.model_uuid));
+        std::fs::create_dir_all(&dir).map_err(|e| ArtifactError(e.to_string()))?;
+
+        let result = self
+            .write_metadata(&dir, mlmodel)
+            .and_then(|_| self.run.log_artifacts(&dir, Some(&self.artifact_path)));
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn write_metadata(&self, dir: &Path, mlmodel: &MLModel) -> MLFlowResult<()> {
+        std::fs::write(dir.join(MLMODEL_FILE_NAME), mlmodel.to_yaml()?)
+            .map_err(|e| ArtifactError(e.to_string()))?;
+        for (name, contents) in &self.metadata_files {
+            std::fs::write(dir.join(name), contents).map_err(|e| ArtifactError(e.to_string()))?;
+        }
+        if let Some(example) = &self.input_example {
+            std::fs::write(dir.join(INPUT_EXAMPLE_FILE_NAME), example.to_json()?)
+                .map_err(|e| ArtifactError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Appends the model to the `mlflow.log-model.history` tag the UI lists a run's models from.
+    fn record_history(&self, mlmodel: &MLModel) -> MLFlowResult<()> {
+        // the run's own copy of its tags may predate models logged since it was fetched
+        let request = SearchRunsRequest {
+            experiment_ids: vec![self.run.info.experiment_id.clone()],
+            filter: Some(format!("attributes.run_id = '{}'", self.run.info.run_id)),
+            run_view_type: RunViewType::All,
+            max_results: Some(1),
+            order_by: vec![],
+            page_token: None,
+        };
+        let tags = match self.run.client.search_runs(&request)?.runs.pop() {
+            Some(run) => run.data.tags,
+            None => self.run.data.tags.clone(),
+        };
+        let mut history: Vec<Value> = tags
+            .iter()
+            .find(|t| t.key == MLFLOW_LOGGED_MODELS)
+            .and_then(|t| serde_json::from_str(&t.value).ok())
+            .unwrap_or_default();
+        history.push(serde_json::to_value(mlmodel).map_err(|e| ArtifactError(e.to_string()))?);
+
+        let history = serde_json::to_string(&history).map_err(|e| ArtifactError(e.to_string()))?;
+        self.run.set_tag(MLFLOW_LOGGED_MODELS, history)
+    }
+
+    fn register(&self, name: &str) -> MLFlowResult<ModelVersion> {
+        let client = &self.run.client;
+        match client.get_registered_model(name) {
+            Ok(_) => {}
+            Err(RegisteredModelNotFound(_)) | Err(ResourceDoesNotExist(_)) => {
+                let request = CreateRegisteredModelRequest {
+                    name: name.to_string(),
+                    description: None,
+                    tags: vec![],
+                };
+                match client.create_registered_model(request) {
+                    Ok(_) | Err(ResourceAlreadyExists(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        let artifact_uri =
+            self.run.info.artifact_uri.as_deref().ok_or_else(|| {
+                ModelVersionBuilderError("run has no artifact location".to_string())
+            })?;
+        let request = CreateModelVersionRequest {
+            name: name.to_string(),
+            source: format!(
+                "{}/{}",
+                artifact_uri.trim_end_matches('/'),
+                self.artifact_path
+            ),
+            run_id: Some(self.run.run_id()),
+            description: None,
+            tags: vec![],
+        };
+        let resp = client.create_model_version(request)?;
+        Ok(resp.model_version.with_client(client.clone()))
+    }
+}
+
+impl Run {
+    /// Logs the files in `local_path` as a model with a single flavor. Use [`ModelLogger`] to log
+    /// several flavors or register the model.
+    pub fn log_model(
+        &self,
+        artifact_path: impl AsRef<str>,
+        flavor: Flavor,
+        local_path: impl AsRef<Path>,
+    ) -> MLFlowResult<ModelInfo> {
+        ModelLogger::new(self, artifact_path, local_path)?
+            .with_flavor(flavor)
+            .log()
+    }
+}
+
+/// Formats milliseconds since the UNIX epoch the way MLflow writes `utc_time_created`,
+/// `YYYY-MM-DD HH:MM:SS.ffffff`.
+fn utc_time(millis: i64) -> String {
+    let seconds = millis.div_euclid(1000);
+    let (days, seconds_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    // civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+        millis.rem_euclid(1000) * 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_time() {
+        assert_eq!(utc_time(0), "1970-01-01 00:00:00.000000");
+        assert_eq!(utc_time(1_709_251_199_123), "2024-02-29 23:59:59.123000");
+        assert_eq!(utc_time(951_782_400_000), "2000-02-29 00:00:00.000000");
+    }
+
+    #[test]
+    fn mlmodel_yaml() {
+        let mlmodel = MLModel {
+            artifact_path: "model".to_string(),
+            flavors: [(
+                "onnx".to_string(),
+                Value::Object(Flavor::new("onnx").with_config("data", "model.onnx").config),
+            )]
+            .into_iter()
+            .collect(),
+            model_uuid: "abc".to_string(),
+            run_id: "123".to_string(),
+            saved_input_example_info: None,
+            signature: None,
+            utc_time_created: "2024-02-29 23:59:59.123000".to_string(),
+        };
+
+        let yaml = mlmodel.to_yaml().unwrap();
+        assert_eq!(
+            yaml,
+            "artifact_path: model\nflavors:\n  onnx:\n    data: model.onnx\nmodel_uuid: abc\nrun_id: '123'\nutc_time_created: 2024-02-29 23:59:59.123000\n"
+        );
+        let parsed = MLModel::from_yaml(&yaml).unwrap();
+        assert_eq!(parsed.run_id, "123");
+        assert_eq!(parsed.flavors["onnx"]["data"], "model.onnx");
+    }
+
+    #[cfg(all(feature = "ndarray", feature = "mock"))]
+    #[test]
+    fn infers_signature_from_ndarray() {
+        use crate::{MockMLFlowClient, RunBuilder, Schema, TensorSpec};
+
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let inputs = ndarray::Array3::<f32>::zeros((2, 3, 4));
+        let logger = ModelLogger::new(&run, "model", dir.path())
+            .unwrap()
+            .with_ndarray_signature(&inputs.view(), Some(&ndarray::array![1u8, 0]));
+
+        let signature = logger.signature.unwrap();
+        assert_eq!(
+            signature.inputs,
+            Schema::Tensors(vec![TensorSpec::new("float32", vec![-1, 3, 4])])
+        );
+        assert_eq!(
+            signature.outputs,
+            Some(Schema::Tensors(vec![TensorSpec::new("uint8", vec![-1])]))
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn logs_and_registers_model() {
+        use crate::{MockMLFlowClient, RunBuilder, Schema, TensorSpec};
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("model.onnx"), b"onnx").unwrap();
+
+        let info = ModelLogger::new(&run, "model", dir.path())
+            .unwrap()
+            .with_flavor(Flavor::new("onnx").with_config("data", "model.onnx"))
+            .with_signature(ModelSignature::new(Schema::Tensors(vec![TensorSpec::new(
+                "float32",
+                vec![-1, 4],
+            )])))
+            .with_input_example(InputExample::Tensor(serde_json::json!([[
+                1.0, 2.0, 3.0, 4.0
+            ]])))
+            .with_registered_model_name("classifier")
+            .log()
+            .unwrap();
+        assert_eq!(info.model_uri, format!("runs:/{}/model", run.run_id()));
+        assert_eq!(info.registered_model_version.unwrap().version, "1");
+
+        assert_eq!(
+            mock.artifact(&run.run_id(), "model/model.onnx"),
+            Some(b"onnx".to_vec())
+        );
+        let mlmodel = mock.artifact(&run.run_id(), "model/MLmodel").unwrap();
+        let mlmodel = MLModel::from_yaml(&String::from_utf8(mlmodel).unwrap()).unwrap();
+        assert_eq!(mlmodel.model_uuid, info.model_uuid);
+        assert!(mlmodel.signature.is_some());
+        assert_eq!(
+            mlmodel.saved_input_example_info.unwrap()["artifact_path"],
+            INPUT_EXAMPLE_FILE_NAME
+        );
+        assert!(mock
+            .artifact(&run.run_id(), "model/input_example.json")
+            .is_some());
+
+        let history = mock
+            .run_data(&run.run_id())
+            .unwrap()
+            .tags
+            .into_iter()
+            .find(|t| t.key == MLFLOW_LOGGED_MODELS)
+            .unwrap();
+        assert!(history.value.contains(&info.model_uuid));
+
+        run.log_model("other", Flavor::new("onnx"), dir.path())
+            .unwrap();
+        let history = mock
+            .run_data(&run.run_id())
+            .unwrap()
+            .tags
+            .into_iter()
+            .find(|t| t.key == MLFLOW_LOGGED_MODELS)
+            .unwrap();
+        let history: Vec<MLModel> = serde_json::from_str(&history.value).unwrap();
+        assert_eq!(history.len(), 2);
+        ModelLogger::new(&run, "model", dir.path())
+            .unwrap()
+            .log()
+            .expect_err("a flavor is required");
+    }
+}
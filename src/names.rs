@@ -0,0 +1,198 @@
+//! Memorable run names in the style of the ones MLflow generates, such as `dashing-owl-123`.
+
+const PREDICATES: &[&str] = &[
+    "able",
+    "abundant",
+    "adaptable",
+    "adorable",
+    "adventurous",
+    "agreeable",
+    "ambitious",
+    "amazing",
+    "amusing",
+    "auspicious",
+    "awesome",
+    "bald",
+    "beautiful",
+    "bedecked",
+    "bemused",
+    "big",
+    "bittersweet",
+    "blushing",
+    "bold",
+    "bouncy",
+    "brawny",
+    "bright",
+    "burly",
+    "bustling",
+    "calm",
+    "capable",
+    "capricious",
+    "carefree",
+    "caring",
+    "casual",
+    "charming",
+    "chill",
+    "classy",
+    "clean",
+    "clumsy",
+    "colorful",
+    "crawling",
+    "dapper",
+    "dashing",
+    "dazzling",
+    "debonair",
+    "defiant",
+    "delicate",
+    "delightful",
+    "efficient",
+    "enchanting",
+    "entertaining",
+    "enthused",
+    "exultant",
+    "fearless",
+    "flawless",
+    "fortunate",
+    "fun",
+    "funny",
+    "gaudy",
+    "gentle",
+    "gifted",
+    "glamorous",
+    "grandiose",
+    "gregarious",
+    "handsome",
+    "hilarious",
+    "honorable",
+    "illustrious",
+    "incongruous",
+    "indecisive",
+    "industrious",
+    "inquisitive",
+    "intelligent",
+    "intrigued",
+    "invincible",
+    "judicious",
+    "kindly",
+    "languid",
+    "learned",
+    "legendary",
+    "likeable",
+    "loud",
+    "luminous",
+    "luxuriant",
+    "lyrical",
+    "magnificent",
+    "marvelous",
+    "masked",
+    "melodic",
+    "merciful",
+    "mercurial",
+    "monumental",
+    "mysterious",
+    "nebulous",
+    "nervous",
+    "nimble",
+    "nosy",
+    "omniscient",
+    "orderly",
+    "overjoyed",
+    "painted",
+    "peaceful",
+    "persistent",
+    "placid",
+    "polite",
+    "popular",
+    "powerful",
+    "puzzled",
+    "rambunctious",
+    "rare",
+    "rebellious",
+    "receptive",
+    "redolent",
+    "resilient",
+    "respected",
+    "righteous",
+    "rogue",
+    "rumbling",
+    "salty",
+    "sassy",
+    "secretive",
+    "sedate",
+    "selective",
+    "serious",
+    "shivering",
+    "silent",
+    "sincere",
+    "skillful",
+    "skittish",
+    "smiling",
+    "sneaky",
+    "sophisticated",
+    "spiffy",
+    "stately",
+    "stylish",
+    "suave",
+    "tasteful",
+    "thoughtful",
+    "thundering",
+    "traveling",
+    "treasured",
+    "trusting",
+    "unequaled",
+    "unique",
+    "unleashed",
+    "unruly",
+    "upbeat",
+    "upset",
+    "useful",
+    "valuable",
+    "vaunted",
+    "victorious",
+    "welcoming",
+    "whimsical",
+    "wise",
+    "wistful",
+    "worried",
+    "youthful",
+    "zealous",
+];
+
+const NOUNS: &[&str] = &[
+    "ant", "ape", "asp", "auk", "bass", "bat", "bear", "bee", "bird", "boar", "bug", "calf",
+    "carp", "cat", "chimp", "cod", "colt", "conch", "cow", "crab", "crane", "croc", "crow", "cub",
+    "deer", "doe", "dog", "dolphin", "donkey", "dove", "duck", "eel", "elk", "fawn", "finch",
+    "fish", "flea", "fly", "foal", "fowl", "fox", "frog", "gnat", "gnu", "goat", "goose", "grouse",
+    "grub", "gull", "hare", "hawk", "hen", "hog", "horse", "hound", "jay", "kit", "kite", "koi",
+    "lamb", "lark", "loon", "lynx", "mare", "midge", "mink", "mole", "moose", "moth", "mouse",
+    "mule", "newt", "owl", "ox", "panda", "penguin", "perch", "pig", "pug", "quail", "ram", "rat",
+    "ray", "robin", "roo", "rook", "seal", "shad", "shark", "sheep", "shoat", "shrew", "shrike",
+    "shrimp", "skink", "skunk", "sloth", "slug", "smelt", "snail", "snake", "snipe", "sow",
+    "sponge", "squid", "squirrel", "stag", "steed", "stoat", "stork", "swan", "tern", "toad",
+    "trout", "turtle", "vole", "wasp", "whale", "wolf", "worm", "wren", "yak", "zebra",
+];
+
+/// A random run name made of an adjective, an animal and a number below 1000, e.g.
+/// `dashing-owl-123`. [`RunBuilder`](crate::RunBuilder) names runs this way when no name is
+/// given.
+pub fn generate_run_name() -> String {
+    let random = uuid::Uuid::new_v4().as_u128();
+    let predicate = PREDICATES[(random % PREDICATES.len() as u128) as usize];
+    let noun = NOUNS[(random >> 32) as usize % NOUNS.len()];
+    format!("{}-{}-{}", predicate, noun, (random >> 64) % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_names() {
+        let name = generate_run_name();
+        let parts: Vec<&str> = name.split('-').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(PREDICATES.contains(&parts[0]));
+        assert!(NOUNS.contains(&parts[1]));
+        assert!(parts[2].parse::<u32>().unwrap() < 1000);
+    }
+}
@@ -0,0 +1,332 @@
+//! Tokens obtained at request time, for tracking servers behind an OIDC provider such as
+//! Keycloak or Azure AD.
+
+use crate::client::shared_http_client;
+use crate::MLFlowError::Unauthenticated;
+use crate::MLFlowResult;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long before expiry [`ClientCredentialsProvider`] refreshes its token by default.
+const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Supplies the token sent as `Authorization: Bearer <token>` with each request of an
+/// [`MLFLowRestClient`](crate::MLFLowRestClient) configured with
+/// [`with_token_provider`](crate::MLFLowRestClient::with_token_provider).
+///
+/// `token` is called before every request, so implementations should cache the token and only
+/// fetch a new one when it is about to expire. When the server still rejects a request with a
+/// 401, the client calls `invalidate` and retries the request once with a new token.
+pub trait TokenProvider: Debug + Send + Sync {
+    fn token(&self) -> MLFlowResult<String>;
+
+    /// Drops the cached token, which the server rejected. Does nothing by default.
+    fn invalidate(&self) {}
+}
+
+#[derive(Debug)]
+struct CachedToken {
+    access_token: String,
+    /// When the token is refreshed, or `None` for a token kept until the server rejects it.
+    refresh_at: Option<Instant>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// Seconds until the token expires.
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// A [`TokenProvider`] using the OAuth2 client credentials grant, fetching a token from the
+/// identity provider's token endpoint on first use and again shortly before it expires.
+///
+/// ```no_run
+/// use mlflow_rs::{ClientCredentialsProvider, MLFLowRestClient};
+///
+/// let provider = ClientCredentialsProvider::new(
+///     "https://login.example.com/realms/ml/protocol/openid-connect/token",
+///     "training-jobs",
+///     "s3cr3t",
+/// )
+/// .with_scopes(vec!["mlflow"]);
+/// let client = MLFLowRestClient::new("https://mlflow.example.com").with_token_provider(provider);
+/// ```
+pub struct ClientCredentialsProvider {
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scopes: Vec<String>,
+    audience: Option<String>,
+    refresh_margin: Duration,
+    client: Client,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+impl Debug for ClientCredentialsProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientCredentialsProvider")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"***")
+            .field("scopes", &self.scopes)
+            .field("audience", &self.audience)
+            .finish()
+    }
+}
+
+impl ClientCredentialsProvider {
+    pub fn new(
+        token_url: impl AsRef<str>,
+        client_id: impl AsRef<str>,
+        client_secret: impl AsRef<str>,
+    ) -> ClientCredentialsProvider {
+        ClientCredentialsProvider {
+            token_url: token_url.as_ref().to_string(),
+            client_id: client_id.as_ref().to_string(),
+            client_secret: client_secret.as_ref().to_string(),
+            scopes: vec![],
+            audience: None,
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+            client: shared_http_client(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Scopes requested with the token, sent space separated.
+    pub fn with_scopes(mut self, scopes: Vec<impl AsRef<str>>) -> Self {
+        self.scopes = scopes.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    /// The `audience` parameter some providers, like Auth0, require.
+    pub fn with_audience(mut self, audience: impl AsRef<str>) -> Self {
+        self.audience = Some(audience.as_ref().to_string());
+        self
+    }
+
+    /// How long before expiry the token is refreshed. Defaults to 60 seconds, and is at most half
+    /// the lifetime of the token, so that short-lived tokens are still used.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    fn fetch(&self) -> MLFlowResult<CachedToken> {
+        let mut form = vec![
+            ("grant_type", "client_credentials".to_string()),
+            ("client_id", self.client_id.clone()),
+            ("client_secret", self.client_secret.clone()),
+        ];
+        if !self.scopes.is_empty() {
+            form.push(("scope", self.scopes.join(" ")));
+        }
+        if let Some(audience) = &self.audience {
+            form.push(("audience", audience.clone()));
+        }
+
+        let fetched_at = Instant::now();
+        let failed = |e: String| Unauthenticated(format!("could not fetch a token: {}", e));
+        let response = self
+            .client
+            .post(&self.token_url)
+            .form(&form)
+            .send()
+            .map_err(|e| failed(e.to_string()))?;
+        let status = response.status();
+        let body = response.bytes().map_err(|e| failed(e.to_string()))?;
+        if !status.is_success() {
+            return Err(failed(match serde_json::from_slice::<TokenError>(&body) {
+                Ok(e) => match e.error_description {
+                    Some(description) => format!("{}: {}", e.error, description),
+                    None => e.error,
+                },
+                Err(_) => format!("token endpoint returned {}", status),
+            }));
+        }
+        let token: TokenResponse =
+            serde_json::from_slice(&body).map_err(|e| failed(e.to_string()))?;
+        Ok(CachedToken {
+            access_token: token.access_token,
+            refresh_at: token.expires_in.map(|secs| {
+                let lifetime = Duration::from_secs(secs);
+                fetched_at + lifetime - self.refresh_margin.min(lifetime / 2)
+            }),
+        })
+    }
+}
+
+impl TokenProvider for ClientCredentialsProvider {
+    fn token(&self) -> MLFlowResult<String> {
+        // held while fetching, so concurrent requests wait for one refresh
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        let fresh = cached
+            .as_ref()
+            .is_some_and(|token| token.refresh_at.is_none_or(|at| Instant::now() < at));
+        if !fresh {
+            *cached = Some(self.fetch()?);
+        }
+        Ok(cached
+            .as_ref()
+            .map(|token| token.access_token.clone())
+            .unwrap_or_default())
+    }
+
+    fn invalidate(&self) {
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Request};
+    use crate::{MLFLowRestClient, MLFlowClient};
+
+    /// A server answering each request with the next of `responses`, a status and JSON body,
+    /// at `/token`.
+    fn serve(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, std::thread::JoinHandle<Vec<Request>>) {
+        let (host, handle) = test_server::serve(
+            responses
+                .into_iter()
+                .map(|(status, body)| Some((status, body.to_string())))
+                .collect(),
+        );
+        (format!("{}/token", host), handle)
+    }
+
+    #[test]
+    fn refreshes_tokens_before_expiry() {
+        let (url, server) = serve(vec![
+            (200, r#"{"access_token": "first", "expires_in": 0}"#),
+            (200, r#"{"access_token": "second", "expires_in": 3600}"#),
+        ]);
+        let provider = ClientCredentialsProvider::new(url, "jobs", "s3cr3t")
+            .with_scopes(vec!["mlflow", "openid"]);
+        assert!(!format!("{:?}", provider).contains("s3cr3t"));
+
+        assert_eq!(provider.token().unwrap(), "first");
+        // already expired, so is refreshed
+        assert_eq!(provider.token().unwrap(), "second");
+        assert_eq!(provider.token().unwrap(), "second");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].body.contains("grant_type=client_credentials"));
+        assert!(requests[0].body.contains("client_id=jobs"));
+        assert!(requests[0].body.contains("scope=mlflow+openid"));
+    }
+
+    #[test]
+    fn short_lived_tokens_are_reused() {
+        let (url, server) = serve(vec![(
+            200,
+            r#"{"access_token": "short", "expires_in": 30}"#,
+        )]);
+        // the 60 second margin is capped at half the 30 second lifetime
+        let provider = ClientCredentialsProvider::new(url, "jobs", "s3cr3t");
+        assert_eq!(provider.token().unwrap(), "short");
+        assert_eq!(provider.token().unwrap(), "short");
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn reports_token_endpoint_errors() {
+        let (url, server) = serve(vec![(
+            401,
+            r#"{"error": "invalid_client", "error_description": "bad secret"}"#,
+        )]);
+        let err = ClientCredentialsProvider::new(url, "jobs", "wrong")
+            .token()
+            .expect_err("rejected credentials");
+        assert!(
+            err.to_string().contains("invalid_client: bad secret"),
+            "{}",
+            err
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn client_sends_provided_token() {
+        #[derive(Debug)]
+        struct Fixed;
+
+        impl TokenProvider for Fixed {
+            fn token(&self) -> MLFlowResult<String> {
+                Ok("abc".to_string())
+            }
+        }
+
+        let experiment = r#"{"experiment": {"experiment_id": "0", "name": "Default"}}"#;
+        let (url, server) = serve(vec![(200, experiment)]);
+        let host = url.trim_end_matches("/token");
+        MLFLowRestClient::new(host)
+            .with_token_provider(Fixed)
+            .get_experiment_by_id("0")
+            .unwrap();
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .headers
+            .contains(&"authorization: bearer abc".to_string()));
+    }
+
+    #[test]
+    fn client_retries_with_a_new_token_when_rejected() {
+        // tokens without expiry are kept until the server rejects them
+        let (token_url, token_server) = serve(vec![
+            (200, r#"{"access_token": "revoked"}"#),
+            (200, r#"{"access_token": "renewed"}"#),
+        ]);
+        let experiment = r#"{"experiment": {"experiment_id": "0", "name": "Default"}}"#;
+        let (host, server) = test_server::serve(vec![
+            Some((401, r#"{"error_code": "UNAUTHENTICATED"}"#.to_string())),
+            Some((200, experiment.to_string())),
+        ]);
+        let client = MLFLowRestClient::new(host)
+            .with_token_provider(ClientCredentialsProvider::new(token_url, "jobs", "s3cr3t"));
+        client.get_experiment_by_id("0").unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .headers
+            .contains(&"authorization: bearer revoked".to_string()));
+        assert!(requests[1]
+            .headers
+            .contains(&"authorization: bearer renewed".to_string()));
+        assert_eq!(token_server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn client_fails_when_token_cannot_be_fetched() {
+        #[derive(Debug)]
+        struct Rejected;
+
+        impl TokenProvider for Rejected {
+            fn token(&self) -> MLFlowResult<String> {
+                Err(Unauthenticated("invalid_client: bad secret".to_string()))
+            }
+        }
+
+        // no request is sent, so nothing needs to listen
+        let err = MLFLowRestClient::new("http://127.0.0.1:9")
+            .with_token_provider(Rejected)
+            .get_experiment_by_id("0")
+            .expect_err("no token");
+        assert!(matches!(err, Unauthenticated(_)), "{}", err);
+        assert!(err.to_string().contains("bad secret"), "{}", err);
+    }
+}
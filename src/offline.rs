@@ -0,0 +1,1081 @@
+//! Durable spooling of tracking calls while the tracking server cannot be reached, for edge and
+//! air-gapped jobs whose network comes and goes.
+
+use crate::artifacts::{artifact_repository_path, join_artifact_path};
+use crate::client::{
+    is_transient, CreateExperimentRequest, CreateExperimentResponse, CreateModelVersionRequest,
+    CreateRegisteredModelRequest, CreateRunRequest, CreateRunResponse, EndTraceRequest,
+    GetExperimentResponse, GetRunResponse, ListArtifactsResponse, LogMetricRequest,
+    LogParamRequest, MLFlowClient, MetricHistoryResponse, ModelVersionResponse,
+    RegisteredModelResponse, RunResponse, SearchExperimentsRequest, SearchExperimentsResponse,
+    SearchModelVersionsRequest, SearchModelVersionsResponse, SearchRegisteredModelsRequest,
+    SearchRegisteredModelsResponse, SearchRunsRequest, SearchRunsResponse, SharedClient,
+    StartTraceRequest, TraceInfoResponse, UpdateRunRequest, UpdateRunResponse,
+};
+use crate::config::default_client;
+use crate::dataset::DatasetInput;
+use crate::entities::{
+    LifecycleStage, Metric, Param, RunData, RunInfo, RunStatus, RunTag, TraceTag,
+};
+use crate::registry::{ModelVersionStage, ModelVersionTag, RegisteredModelTag};
+use crate::tags::MLFLOW_RUN_NAME;
+use crate::version::ServerVersion;
+#[cfg(feature = "blocking")]
+use crate::MLFLowRestClient;
+use crate::MLFlowError::SpoolError;
+use crate::{MLFlowError, MLFlowResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The append-only log of spooled calls within the spool directory, one JSON object per line.
+pub const SPOOL_FILE_NAME: &str = "spool.jsonl";
+/// The log of spooled calls the server rejected on sync within the spool directory, each with
+/// the error it failed with, one JSON object per line.
+pub const DEAD_LETTER_FILE_NAME: &str = "dead_letter.jsonl";
+const SPOOLED_ARTIFACTS_DIR: &str = "artifacts";
+/// Prefix of the ids of runs created while offline, replaced by the server's id on sync.
+const OFFLINE_RUN_ID_PREFIX: &str = "offline-";
+/// Directory of the proxied artifact store the runs created while offline get their artifact
+/// root in, as `mlflow-artifacts:/offline/<run_id>`, replaced by the server's root on sync.
+const OFFLINE_ARTIFACTS_DIR: &str = "offline";
+
+/// A write recorded in the spool, with everything needed to send it later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "call", rename_all = "snake_case")]
+enum SpooledCall {
+    CreateRun {
+        /// The placeholder id the run was handed out with.
+        run_id: String,
+        request: CreateRunRequest,
+    },
+    UpdateRun {
+        request: UpdateRunRequest,
+    },
+    LogMetric {
+        request: LogMetricRequest,
+    },
+    LogParam {
+        request: LogParamRequest,
+    },
+    SetTag {
+        run_id: String,
+        tag: RunTag,
+    },
+    DeleteTag {
+        run_id: String,
+        key: String,
+    },
+    LogBatch {
+        run_id: String,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    },
+    LogInputs {
+        run_id: String,
+        datasets: Vec<DatasetInput>,
+    },
+    UploadArtifact {
+        path: String,
+        /// Copy of the uploaded file within the spool directory.
+        file: PathBuf,
+    },
+}
+
+/// A spooled call the server rejected, as recorded in the dead letter file.
+#[derive(Serialize)]
+struct DeadLetter<'a> {
+    error: String,
+    call: &'a SpooledCall,
+}
+
+/// What sending a [`SpooledCall`] returned.
+enum Sent {
+    Created(RunResponse),
+    Updated(RunInfo),
+    Done,
+}
+
+impl SpooledCall {
+    fn send(&self, client: &dyn MLFlowClient) -> MLFlowResult<Sent> {
+        match self.clone() {
+            SpooledCall::CreateRun { request, .. } => {
+                client.create_run(request).map(|r| Sent::Created(r.run))
+            }
+            SpooledCall::UpdateRun { request } => client
+                .update_run(request)
+                .map(|r| Sent::Updated(r.run_info)),
+            SpooledCall::LogMetric { request } => client.log_metric(request).map(|_| Sent::Done),
+            SpooledCall::LogParam { request } => client.log_param(request).map(|_| Sent::Done),
+            SpooledCall::SetTag { run_id, tag } => {
+                client.set_tag(&run_id, &tag).map(|_| Sent::Done)
+            }
+            SpooledCall::DeleteTag { run_id, key } => {
+                client.delete_tag(&run_id, &key).map(|_| Sent::Done)
+            }
+            SpooledCall::LogBatch {
+                run_id,
+                metrics,
+                params,
+                tags,
+            } => client
+                .log_batch(&run_id, metrics, params, tags)
+                .map(|_| Sent::Done),
+            SpooledCall::LogInputs { run_id, datasets } => {
+                client.log_inputs(&run_id, datasets).map(|_| Sent::Done)
+            }
+            SpooledCall::UploadArtifact { path, file } => {
+                client.upload_artifact(&path, &file).map(|_| Sent::Done)
+            }
+        }
+    }
+
+    /// Replaces the placeholder ids and artifact paths of runs that have since been created on
+    /// the server.
+    fn map_run_ids(
+        &mut self,
+        run_ids: &HashMap<String, String>,
+        artifact_roots: &HashMap<String, String>,
+    ) {
+        let run_id = match self {
+            SpooledCall::CreateRun { .. } => return,
+            SpooledCall::UploadArtifact { path, .. } => {
+                *path = map_artifact_path(path, artifact_roots);
+                return;
+            }
+            SpooledCall::UpdateRun { request } => &mut request.run_id,
+            SpooledCall::LogMetric { request } => &mut request.run_id,
+            SpooledCall::LogParam { request } => &mut request.run_id,
+            SpooledCall::SetTag { run_id, .. }
+            | SpooledCall::DeleteTag { run_id, .. }
+            | SpooledCall::LogBatch { run_id, .. }
+            | SpooledCall::LogInputs { run_id, .. } => run_id,
+        };
+        if let Some(mapped) = run_ids.get(run_id.as_str()) {
+            *run_id = mapped.clone();
+        }
+    }
+}
+
+/// Moves `path` from the placeholder artifact root of a run created offline to the run's
+/// artifact root on the server, once known.
+fn map_artifact_path(path: &str, artifact_roots: &HashMap<String, String>) -> String {
+    let mapped = path
+        .strip_prefix(OFFLINE_ARTIFACTS_DIR)
+        .and_then(|p| p.strip_prefix('/'))
+        .and_then(|p| {
+            let (run_id, artifact_path) = p.split_once('/').unwrap_or((p, ""));
+            join_artifact_path(artifact_roots.get(run_id)?, artifact_path).ok()
+        });
+    mapped.unwrap_or_else(|| path.to_string())
+}
+
+#[derive(Debug)]
+struct Spool {
+    dir: PathBuf,
+    /// Number of calls in the spool file.
+    pending: usize,
+    /// Server ids of the runs created offline and since synced, by placeholder id.
+    run_ids: HashMap<String, String>,
+    /// Artifact roots within the proxied artifact store of the runs created offline and since
+    /// synced, by placeholder id.
+    artifact_roots: HashMap<String, String>,
+    /// The latest known info of the runs created through the client, to answer updates with
+    /// while offline.
+    runs: HashMap<String, RunInfo>,
+}
+
+impl Spool {
+    fn file(&self) -> PathBuf {
+        self.dir.join(SPOOL_FILE_NAME)
+    }
+
+    fn run_id(&self, run_id: &str) -> String {
+        self.run_ids
+            .get(run_id)
+            .cloned()
+            .unwrap_or_else(|| run_id.to_string())
+    }
+
+    /// Appends `call` to the spool file, syncing it to disk before returning.
+    fn append(&mut self, call: &SpooledCall) -> MLFlowResult<()> {
+        let line = serde_json::to_string(call).map_err(|e| SpoolError(e.to_string()))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file())
+            .and_then(|mut file| {
+                writeln!(file, "{}", line)?;
+                file.sync_data()
+            })
+            .map_err(|e| spool_error(&self.file(), e))?;
+        self.pending += 1;
+        Ok(())
+    }
+
+    /// Appends `call` to the dead letter file with the `error` the server rejected it with.
+    fn dead_letter(&self, call: &SpooledCall, error: &MLFlowError) -> MLFlowResult<()> {
+        let file = self.dir.join(DEAD_LETTER_FILE_NAME);
+        let letter = DeadLetter {
+            error: error.to_string(),
+            call,
+        };
+        let line = serde_json::to_string(&letter).map_err(|e| SpoolError(e.to_string()))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file)
+            .and_then(|mut file| {
+                writeln!(file, "{}", line)?;
+                file.sync_data()
+            })
+            .map_err(|e| spool_error(&file, e))
+    }
+
+    /// Ends a line cut short by a crash while it was appended, so it doesn't swallow the next
+    /// call appended to the spool.
+    fn terminate_last_line(&mut self) -> MLFlowResult<()> {
+        let contents = match std::fs::read(self.file()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(spool_error(&self.file(), e)),
+        };
+        if contents.last().is_some_and(|b| *b != b'\n') {
+            OpenOptions::new()
+                .append(true)
+                .open(self.file())
+                .and_then(|mut file| writeln!(file))
+                .map_err(|e| spool_error(&self.file(), e))?;
+        }
+        Ok(())
+    }
+
+    /// The spooled calls, in the order they were made.
+    fn read(&self) -> MLFlowResult<Vec<SpooledCall>> {
+        let file = match File::open(self.file()) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(spool_error(&self.file(), e)),
+        };
+
+        let mut calls = vec![];
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| spool_error(&self.file(), e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(call) => calls.push(call),
+                // cut short by a crash while it was appended
+                Err(e) => log::warn!("skipping unreadable spooled call: {}", e),
+            }
+        }
+        Ok(calls)
+    }
+
+    /// Replaces the spool file's contents with `calls`.
+    fn rewrite(&mut self, calls: &[SpooledCall]) -> MLFlowResult<()> {
+        let tmp = self.dir.join(format!("{}.tmp", SPOOL_FILE_NAME));
+        let mut contents = String::new();
+        for call in calls {
+            contents += &serde_json::to_string(call).map_err(|e| SpoolError(e.to_string()))?;
+            contents.push('\n');
+        }
+        File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(contents.as_bytes())?;
+                file.sync_data()
+            })
+            .and_then(|_| std::fs::rename(&tmp, self.file()))
+            .map_err(|e| spool_error(&self.file(), e))?;
+        self.pending = calls.len();
+        Ok(())
+    }
+}
+
+fn spool_error(path: &Path, e: std::io::Error) -> MLFlowError {
+    SpoolError(format!("{}: {}", path.display(), e))
+}
+
+/// A client that keeps tracking while the tracking server is unreachable.
+///
+/// Creating and updating runs, logging metrics, params, tags and inputs, and uploading artifacts
+/// are sent to the wrapped client as long as it answers. Once a call fails because the server
+/// cannot be reached, or fails transiently with a `5xx` or `429` response, it and every call
+/// after it are appended to `spool.jsonl` in the spool directory instead, to keep their order,
+/// until [`sync`](Self::sync) replays them. Runs created while offline get an `offline-` id and
+/// an artifact root of `mlflow-artifacts:/offline/<run_id>`, replaced by the server's on sync.
+///
+/// Reads, searches, deletes and the model registry are always sent to the wrapped client.
+/// The spool survives restarts: a client opened on the same directory replays what an earlier
+/// process left in it.
+///
+/// ```no_run
+/// use mlflow_rs::{OfflineClient, RunBuilder};
+///
+/// let client = OfflineClient::new("/var/spool/mlflow-rs").unwrap();
+/// let run = RunBuilder::new("1")
+///     .unwrap()
+///     .with_dyn_client(client.clone())
+///     .build()
+///     .unwrap();
+/// run.log_metric("loss", 0.25, Some(1), None).unwrap();
+///
+/// // later, once the network is back
+/// client.sync().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OfflineClient {
+    client: SharedClient,
+    spool: Arc<Mutex<Spool>>,
+}
+
+impl OfflineClient {
+    /// Spools to `spool_dir`, created if needed, in front of the client of the global
+    /// [`Config`](crate::Config).
+    pub fn new(spool_dir: impl AsRef<Path>) -> MLFlowResult<OfflineClient> {
+        let dir = spool_dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(dir.join(SPOOLED_ARTIFACTS_DIR))
+            .map_err(|e| spool_error(&dir, e))?;
+        let mut spool = Spool {
+            dir,
+            pending: 0,
+            run_ids: HashMap::new(),
+            artifact_roots: HashMap::new(),
+            runs: HashMap::new(),
+        };
+        spool.pending = spool.read()?.len();
+        spool.terminate_last_line()?;
+
+        Ok(OfflineClient {
+            client: default_client()?,
+            spool: Arc::new(Mutex::new(spool)),
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Wraps any [`MLFlowClient`] implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Wraps an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a tracking
+    /// server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Number of calls waiting in the spool.
+    pub fn pending(&self) -> usize {
+        self.spool().pending
+    }
+
+    /// Replays the spooled calls in order, returning how many were sent. Replaying stops at the
+    /// first call that fails because the server cannot be reached, or fails transiently, which
+    /// stays in the spool with every call after it.
+    ///
+    /// Calls the server rejects for good, such as a changed param or a call for a run it doesn't
+    /// know, would otherwise block the spool forever. They are moved to
+    /// [`DEAD_LETTER_FILE_NAME`] in the spool directory with their error instead, and replaying
+    /// goes on with the next call.
+    ///
+    /// If the process dies while syncing, the calls sent since the spool was last written are
+    /// replayed again by the next sync.
+    pub fn sync(&self) -> MLFlowResult<usize> {
+        let mut spool = self.spool();
+        let mut calls = spool.read()?.into_iter();
+        let mut sent = 0;
+        let mut result = Ok(());
+
+        while let Some(mut call) = calls.next() {
+            call.map_run_ids(&spool.run_ids, &spool.artifact_roots);
+            match call.send(&*self.client) {
+                Ok(Sent::Created(run)) => {
+                    if let SpooledCall::CreateRun { run_id, .. } = &call {
+                        spool
+                            .run_ids
+                            .insert(run_id.clone(), run.info.run_id.clone());
+                        spool.runs.remove(run_id);
+                        match run
+                            .info
+                            .artifact_uri
+                            .as_deref()
+                            .map(artifact_repository_path)
+                        {
+                            Some(Ok(root)) => {
+                                spool.artifact_roots.insert(run_id.clone(), root);
+                            }
+                            _ => log::warn!(
+                                "artifacts spooled for run {} cannot be uploaded to {:?}",
+                                run.info.run_id,
+                                run.info.artifact_uri
+                            ),
+                        }
+                    }
+                    spool.runs.insert(run.info.run_id.clone(), run.info);
+                }
+                Ok(Sent::Updated(info)) => {
+                    spool.runs.insert(info.run_id.clone(), info);
+                }
+                Ok(Sent::Done) => {
+                    if let SpooledCall::UploadArtifact { file, .. } = &call {
+                        let _ = std::fs::remove_file(file);
+                    }
+                }
+                Err(e) if !is_transient(&e) => {
+                    log::warn!("moving spooled call rejected by the server aside: {}", e);
+                    spool.dead_letter(&call, &e)?;
+                    continue;
+                }
+                Err(e) => {
+                    let run_ids = spool.run_ids.clone();
+                    let artifact_roots = spool.artifact_roots.clone();
+                    let remaining: Vec<SpooledCall> = std::iter::once(call)
+                        .chain(calls.by_ref().map(|mut call| {
+                            call.map_run_ids(&run_ids, &artifact_roots);
+                            call
+                        }))
+                        .collect();
+                    spool.rewrite(&remaining)?;
+                    result = Err(e);
+                    break;
+                }
+            }
+            sent += 1;
+        }
+
+        if result.is_ok() {
+            spool.rewrite(&[])?;
+        }
+        result.map(|_| sent)
+    }
+
+    fn spool(&self) -> MutexGuard<'_, Spool> {
+        self.spool.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sends `call` unless calls are already waiting in the spool, spooling it if the server
+    /// cannot be reached. `offline` answers in place of the server for a spooled call.
+    fn write<T>(
+        &self,
+        call: SpooledCall,
+        online: impl FnOnce(&mut Spool, Sent) -> T,
+        offline: impl FnOnce(&mut Spool) -> T,
+    ) -> MLFlowResult<T> {
+        let mut spool = self.spool();
+        if spool.pending == 0 {
+            match call.send(&*self.client) {
+                Ok(sent) => return Ok(online(&mut spool, sent)),
+                Err(e) if is_transient(&e) => log::warn!("spooling call while offline: {}", e),
+                Err(e) => return Err(e),
+            }
+        }
+        spool.append(&call)?;
+        Ok(offline(&mut spool))
+    }
+
+    fn run_id(&self, run_id: &str) -> String {
+        self.spool().run_id(run_id)
+    }
+}
+
+impl From<OfflineClient> for SharedClient {
+    fn from(client: OfflineClient) -> Self {
+        SharedClient::new(client)
+    }
+}
+
+impl From<OfflineClient> for Arc<dyn MLFlowClient> {
+    fn from(client: OfflineClient) -> Self {
+        Arc::new(client)
+    }
+}
+
+impl MLFlowClient for OfflineClient {
+    fn create_experiment(
+        &self,
+        request: &CreateExperimentRequest,
+    ) -> MLFlowResult<CreateExperimentResponse> {
+        self.client.create_experiment(request)
+    }
+
+    fn get_experiment_by_id(&self, id: &str) -> MLFlowResult<GetExperimentResponse> {
+        self.client.get_experiment_by_id(id)
+    }
+
+    fn get_experiment_by_name(&self, name: &str) -> MLFlowResult<GetExperimentResponse> {
+        self.client.get_experiment_by_name(name)
+    }
+
+    fn search_experiments(
+        &self,
+        request: &SearchExperimentsRequest,
+    ) -> MLFlowResult<SearchExperimentsResponse> {
+        self.client.search_experiments(request)
+    }
+
+    fn update_experiment(&self, experiment_id: &str, new_name: &str) -> MLFlowResult<()> {
+        self.client.update_experiment(experiment_id, new_name)
+    }
+
+    fn delete_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        self.client.delete_experiment(experiment_id)
+    }
+
+    fn restore_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        self.client.restore_experiment(experiment_id)
+    }
+
+    fn create_run(&self, request: CreateRunRequest) -> MLFlowResult<CreateRunResponse> {
+        let run_id = format!("{}{}", OFFLINE_RUN_ID_PREFIX, uuid::Uuid::new_v4().simple());
+        let run = RunResponse {
+            info: RunInfo {
+                run_id: run_id.clone(),
+                run_name: request.run_name.clone().or_else(|| {
+                    request
+                        .tags
+                        .iter()
+                        .find(|t| t.key == MLFLOW_RUN_NAME)
+                        .map(|t| t.value.clone())
+                }),
+                experiment_id: request.experiment_id.clone(),
+                status: RunStatus::Running,
+                start_time: Some(request.start_time),
+                end_time: None,
+                artifact_uri: Some(format!(
+                    "mlflow-artifacts:/{}/{}",
+                    OFFLINE_ARTIFACTS_DIR, run_id
+                )),
+                lifecycle_stage: Some(LifecycleStage::Active),
+                extra: serde_json::Map::new(),
+            },
+            data: RunData {
+                tags: request.tags.clone(),
+                ..RunData::default()
+            },
+            inputs: Default::default(),
+        };
+
+        let run = self.write(
+            SpooledCall::CreateRun { run_id, request },
+            |spool, sent| match sent {
+                Sent::Created(run) => {
+                    spool.runs.insert(run.info.run_id.clone(), run.info.clone());
+                    run
+                }
+                _ => unreachable!("creating a run returns it"),
+            },
+            |spool| {
+                spool.runs.insert(run.info.run_id.clone(), run.info.clone());
+                run
+            },
+        )?;
+        Ok(CreateRunResponse { run })
+    }
+
+    fn get_run(&self, run_id: &str) -> MLFlowResult<GetRunResponse> {
+        self.client.get_run(&self.run_id(run_id))
+    }
+
+    fn update_run(&self, mut request: UpdateRunRequest) -> MLFlowResult<UpdateRunResponse> {
+        request.run_id = self.run_id(&request.run_id);
+        let update = request.clone();
+        let run_info = self.write(
+            SpooledCall::UpdateRun { request },
+            |spool, sent| match sent {
+                Sent::Updated(info) => {
+                    spool.runs.insert(info.run_id.clone(), info.clone());
+                    info
+                }
+                _ => unreachable!("updating a run returns it"),
+            },
+            |spool| {
+                let info = spool.runs.entry(update.run_id.clone()).or_insert(RunInfo {
+                    run_id: update.run_id.clone(),
+                    run_name: None,
+                    experiment_id: String::new(),
+                    status: RunStatus::Running,
+                    start_time: None,
+                    end_time: None,
+                    artifact_uri: None,
+                    lifecycle_stage: Some(LifecycleStage::Active),
+                    extra: serde_json::Map::new(),
+                });
+                if let Some(status) = update.status {
+                    info.status = status;
+                }
+                if update.end_time.is_some() {
+                    info.end_time = update.end_time;
+                }
+                if update.run_name.is_some() {
+                    info.run_name = update.run_name;
+                }
+                info.clone()
+            },
+        )?;
+        Ok(UpdateRunResponse { run_info })
+    }
+
+    fn delete_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.client.delete_run(&self.run_id(run_id))
+    }
+
+    fn restore_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.client.restore_run(&self.run_id(run_id))
+    }
+
+    fn search_runs(&self, request: &SearchRunsRequest) -> MLFlowResult<SearchRunsResponse> {
+        self.client.search_runs(request)
+    }
+
+    fn log_metric(&self, mut request: LogMetricRequest) -> MLFlowResult<()> {
+        request.run_id = self.run_id(&request.run_id);
+        self.write(SpooledCall::LogMetric { request }, |_, _| (), |_| ())
+    }
+
+    fn log_param(&self, mut request: LogParamRequest) -> MLFlowResult<()> {
+        request.run_id = self.run_id(&request.run_id);
+        self.write(SpooledCall::LogParam { request }, |_, _| (), |_| ())
+    }
+
+    fn get_metric_history(
+        &self,
+        run_id: &str,
+        key: &str,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<MetricHistoryResponse> {
+        self.client
+            .get_metric_history(&self.run_id(run_id), key, page_token)
+    }
+
+    fn set_tag(&self, run_id: &str, tag: &RunTag) -> MLFlowResult<()> {
+        let call = SpooledCall::SetTag {
+            run_id: self.run_id(run_id),
+            tag: tag.clone(),
+        };
+        self.write(call, |_, _| (), |_| ())
+    }
+
+    fn delete_tag(&self, run_id: &str, key: &str) -> MLFlowResult<()> {
+        let call = SpooledCall::DeleteTag {
+            run_id: self.run_id(run_id),
+            key: key.to_string(),
+        };
+        self.write(call, |_, _| (), |_| ())
+    }
+
+    fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<()> {
+        let call = SpooledCall::LogBatch {
+            run_id: self.run_id(run_id),
+            metrics,
+            params,
+            tags,
+        };
+        self.write(call, |_, _| (), |_| ())
+    }
+
+    fn log_inputs(&self, run_id: &str, datasets: Vec<DatasetInput>) -> MLFlowResult<()> {
+        let call = SpooledCall::LogInputs {
+            run_id: self.run_id(run_id),
+            datasets,
+        };
+        self.write(call, |_, _| (), |_| ())
+    }
+
+    fn upload_artifact(&self, path: &str, local_file: &Path) -> MLFlowResult<()> {
+        let path = map_artifact_path(path, &self.spool().artifact_roots);
+        {
+            let spool = self.spool();
+            if spool.pending == 0 {
+                match self.client.upload_artifact(&path, local_file) {
+                    Err(e) if is_transient(&e) => {
+                        log::warn!("spooling artifact upload while offline: {}", e)
+                    }
+                    result => return result,
+                }
+            }
+        }
+
+        // the file may change or disappear before the spool is synced
+        let file = self
+            .spool()
+            .dir
+            .join(SPOOLED_ARTIFACTS_DIR)
+            .join(uuid::Uuid::new_v4().simple().to_string());
+        std::fs::copy(local_file, &file).map_err(|e| spool_error(local_file, e))?;
+        let call = SpooledCall::UploadArtifact { path, file };
+        self.spool().append(&call)
+    }
+
+    fn list_artifacts(
+        &self,
+        run_id: &str,
+        path: Option<&str>,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<ListArtifactsResponse> {
+        self.client
+            .list_artifacts(&self.run_id(run_id), path, page_token)
+    }
+
+    fn download_artifact(&self, path: &str, local_file: &Path) -> MLFlowResult<()> {
+        let path = map_artifact_path(path, &self.spool().artifact_roots);
+        self.client.download_artifact(&path, local_file)
+    }
+
+    fn delete_artifact(&self, path: &str) -> MLFlowResult<()> {
+        let path = map_artifact_path(path, &self.spool().artifact_roots);
+        self.client.delete_artifact(&path)
+    }
+
+    fn create_registered_model(
+        &self,
+        request: CreateRegisteredModelRequest,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        self.client.create_registered_model(request)
+    }
+
+    fn get_registered_model(&self, name: &str) -> MLFlowResult<RegisteredModelResponse> {
+        self.client.get_registered_model(name)
+    }
+
+    fn rename_registered_model(
+        &self,
+        name: &str,
+        new_name: &str,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        self.client.rename_registered_model(name, new_name)
+    }
+
+    fn delete_registered_model(&self, name: &str) -> MLFlowResult<()> {
+        self.client.delete_registered_model(name)
+    }
+
+    fn set_registered_model_tag(&self, name: &str, tag: &RegisteredModelTag) -> MLFlowResult<()> {
+        self.client.set_registered_model_tag(name, tag)
+    }
+
+    fn delete_registered_model_tag(&self, name: &str, key: &str) -> MLFlowResult<()> {
+        self.client.delete_registered_model_tag(name, key)
+    }
+
+    fn search_registered_models(
+        &self,
+        request: &SearchRegisteredModelsRequest,
+    ) -> MLFlowResult<SearchRegisteredModelsResponse> {
+        self.client.search_registered_models(request)
+    }
+
+    fn create_model_version(
+        &self,
+        request: CreateModelVersionRequest,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        self.client.create_model_version(request)
+    }
+
+    fn get_model_version(&self, name: &str, version: &str) -> MLFlowResult<ModelVersionResponse> {
+        self.client.get_model_version(name, version)
+    }
+
+    fn transition_model_version_stage(
+        &self,
+        name: &str,
+        version: &str,
+        stage: ModelVersionStage,
+        archive_existing_versions: bool,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        self.client
+            .transition_model_version_stage(name, version, stage, archive_existing_versions)
+    }
+
+    fn delete_model_version(&self, name: &str, version: &str) -> MLFlowResult<()> {
+        self.client.delete_model_version(name, version)
+    }
+
+    fn set_model_version_tag(
+        &self,
+        name: &str,
+        version: &str,
+        tag: &ModelVersionTag,
+    ) -> MLFlowResult<()> {
+        self.client.set_model_version_tag(name, version, tag)
+    }
+
+    fn delete_model_version_tag(&self, name: &str, version: &str, key: &str) -> MLFlowResult<()> {
+        self.client.delete_model_version_tag(name, version, key)
+    }
+
+    fn search_model_versions(
+        &self,
+        request: &SearchModelVersionsRequest,
+    ) -> MLFlowResult<SearchModelVersionsResponse> {
+        self.client.search_model_versions(request)
+    }
+
+    fn start_trace(&self, request: StartTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        self.client.start_trace(request)
+    }
+
+    fn end_trace(&self, request: EndTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        self.client.end_trace(request)
+    }
+
+    fn get_trace_info(&self, request_id: &str) -> MLFlowResult<TraceInfoResponse> {
+        self.client.get_trace_info(request_id)
+    }
+
+    fn set_trace_tag(&self, request_id: &str, tag: &TraceTag) -> MLFlowResult<()> {
+        self.client.set_trace_tag(request_id, tag)
+    }
+
+    fn server_version(&self) -> MLFlowResult<ServerVersion> {
+        self.client.server_version()
+    }
+}
+
+#[cfg(all(test, feature = "mock", feature = "blocking"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder, RunLoader};
+
+    /// A client whose server refuses every connection.
+    fn unreachable() -> MLFLowRestClient {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        MLFLowRestClient::new(host)
+    }
+
+    #[test]
+    fn spools_while_offline_and_replays_on_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let offline = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_rest_client(unreachable());
+
+        let mut run = RunBuilder::new("0")
+            .unwrap()
+            .with_dyn_client(offline.clone())
+            .with_run_name("edge")
+            .with_source_context(false)
+            .build()
+            .unwrap();
+        assert!(run.info().run_id.starts_with(OFFLINE_RUN_ID_PREFIX));
+        run.log_metric("loss", 0.5, Some(1), None).unwrap();
+        run.log_param("lr", "0.1").unwrap();
+        run.finish(RunStatus::Finished).unwrap();
+        assert_eq!(run.info().status, RunStatus::Finished);
+        assert_eq!(offline.pending(), 4);
+        offline.sync().expect_err("the server is still unreachable");
+        assert_eq!(offline.pending(), 4);
+
+        // a new process replays the spool once the server is back
+        let mock = MockMLFlowClient::default();
+        let online = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_mock_client(mock.clone());
+        assert_eq!(online.pending(), 4);
+        assert_eq!(online.sync().unwrap(), 4);
+        assert_eq!(online.pending(), 0);
+
+        let runs = mock
+            .search_runs(&SearchRunsRequest {
+                experiment_ids: vec!["0".to_string()],
+                filter: None,
+                run_view_type: Default::default(),
+                max_results: None,
+                order_by: vec![],
+                page_token: None,
+            })
+            .unwrap()
+            .runs;
+        assert_eq!(runs.len(), 1);
+        let synced = RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(&runs[0].info.run_id)
+            .unwrap();
+        assert_eq!(synced.info().run_name.as_deref(), Some("edge"));
+        assert_eq!(synced.info().status, RunStatus::Finished);
+        assert_eq!(synced.data().metric("loss"), Some(0.5));
+        assert_eq!(synced.data().param("lr"), Some("0.1"));
+    }
+
+    #[test]
+    fn uploads_artifacts_of_runs_created_offline_on_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let offline = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_rest_client(unreachable());
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_dyn_client(offline.clone())
+            .with_source_context(false)
+            .build()
+            .unwrap();
+        run.log_text("converged", "notes/summary.txt").unwrap();
+        assert_eq!(offline.pending(), 2);
+
+        let mock = MockMLFlowClient::default();
+        let online = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_mock_client(mock.clone());
+        assert_eq!(online.sync().unwrap(), 2);
+        let synced = online.run_id(&run.info().run_id);
+        assert!(!synced.starts_with(OFFLINE_RUN_ID_PREFIX));
+        let loaded = RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(&synced)
+            .unwrap();
+        let listed = loaded.list_artifacts(Some("notes")).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].path, "notes/summary.txt");
+
+        // uploads to the placeholder root after the sync go to the synced run
+        let local = dir.path().join("later.txt");
+        std::fs::write(&local, b"later").unwrap();
+        let placeholder = format!("{}/{}/later.txt", OFFLINE_ARTIFACTS_DIR, run.info().run_id);
+        online.upload_artifact(&placeholder, &local).unwrap();
+        assert_eq!(loaded.list_artifacts(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn sends_directly_while_online() {
+        let dir = tempfile::tempdir().unwrap();
+        let mock = MockMLFlowClient::default();
+        let offline = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_mock_client(mock.clone());
+
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_dyn_client(offline.clone())
+            .build()
+            .unwrap();
+        run.log_metric("loss", 0.5, None, None).unwrap();
+        assert_eq!(offline.pending(), 0);
+        assert_eq!(mock.metric_history(&run.info().run_id, "loss").len(), 1);
+
+        // errors the server answered with are not spooled
+        run.log_param("lr", "0.1").unwrap();
+        run.log_param("lr", "0.2")
+            .expect_err("param values cannot change");
+        assert_eq!(offline.pending(), 0);
+    }
+
+    #[test]
+    fn spools_artifacts_and_skips_truncated_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let offline = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_rest_client(unreachable());
+        let local = dir.path().join("model.bin");
+        std::fs::write(&local, b"weights").unwrap();
+        offline
+            .upload_artifact("0/abc/artifacts/model.bin", &local)
+            .unwrap();
+        std::fs::remove_file(&local).unwrap();
+
+        let mut spool = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(SPOOL_FILE_NAME))
+            .unwrap();
+        write!(spool, "{{\"call\":\"log_met").unwrap();
+        let reopened = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_rest_client(unreachable());
+        reopened
+            .set_tag("abc", &RunTag::from(("stage", "edge")))
+            .unwrap();
+        assert_eq!(reopened.pending(), 2);
+
+        let mock = MockMLFlowClient::default();
+        let online = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_mock_client(mock.clone());
+        // the upload is replayed, and the tag of a run the server doesn't know is moved aside
+        assert_eq!(online.sync().unwrap(), 1);
+        assert_eq!(online.pending(), 0);
+        let dead_letters = std::fs::read_to_string(dir.path().join(DEAD_LETTER_FILE_NAME)).unwrap();
+        assert!(
+            dead_letters.contains("\"run_id\":\"abc\""),
+            "{}",
+            dead_letters
+        );
+        let downloaded = dir.path().join("downloaded.bin");
+        mock.download_artifact("0/abc/artifacts/model.bin", &downloaded)
+            .unwrap();
+        assert_eq!(std::fs::read(downloaded).unwrap(), b"weights");
+    }
+
+    #[test]
+    fn rejected_calls_do_not_block_the_spool() {
+        let dir = tempfile::tempdir().unwrap();
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        run.log_param("lr", "0.1").unwrap();
+        let run_id = run.info().run_id.clone();
+
+        let offline = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_rest_client(unreachable());
+        offline
+            .log_param(LogParamRequest {
+                run_id: run_id.clone(),
+                param: Param {
+                    key: "lr".to_string(),
+                    value: "0.2".to_string(),
+                },
+            })
+            .unwrap();
+        offline
+            .set_tag(&run_id, &RunTag::from(("stage", "edge")))
+            .unwrap();
+        assert_eq!(offline.pending(), 2);
+
+        let online = OfflineClient::new(dir.path())
+            .unwrap()
+            .with_mock_client(mock.clone());
+        assert_eq!(online.sync().unwrap(), 1);
+        assert_eq!(online.pending(), 0);
+        let dead_letters = std::fs::read_to_string(dir.path().join(DEAD_LETTER_FILE_NAME)).unwrap();
+        assert_eq!(dead_letters.lines().count(), 1);
+        assert!(
+            dead_letters.contains("\"value\":\"0.2\""),
+            "{}",
+            dead_letters
+        );
+
+        // later calls are sent again rather than spooled behind the rejected one
+        online
+            .set_tag(&run_id, &RunTag::from(("stage", "cloud")))
+            .unwrap();
+        assert_eq!(online.pending(), 0);
+        let synced = RunLoader::default()
+            .with_mock_client(mock)
+            .load(&run_id)
+            .unwrap();
+        assert_eq!(synced.data().param("lr"), Some("0.1"));
+        assert_eq!(synced.data().tag("stage"), Some("cloud"));
+    }
+}
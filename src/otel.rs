@@ -0,0 +1,361 @@
+//! An OpenTelemetry [`SpanExporter`] recording OTel traces as MLflow GenAI traces.
+//!
+//! Spans are buffered per OTel trace until its root span ends, then the whole trace is sent as
+//! one MLflow [`Trace`](crate::Trace), named after its root span. Spans whose parent lives in
+//! another service are treated as roots. Traces whose root never ends locally are sent when
+//! the exporter is flushed or shut down.
+//!
+//! MLflow specific span data can be set with the attributes the MLflow SDKs use:
+//! `mlflow.spanType` (e.g. `LLM`), and `mlflow.spanInputs`/`mlflow.spanOutputs` holding JSON.
+//!
+//! The exporter talks to the tracking server with blocking requests, so it should be installed
+//! with a batch span processor, which exports from its own thread.
+//!
+//! ```no_run
+//! use mlflow_rs::MLFlowSpanExporterBuilder;
+//! use opentelemetry_sdk::trace::SdkTracerProvider;
+//!
+//! let exporter = MLFlowSpanExporterBuilder::new("0").unwrap().build();
+//! let provider = SdkTracerProvider::builder()
+//!     .with_batch_exporter(exporter)
+//!     .build();
+//! ```
+
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{MLFlowClient, SharedClient};
+use crate::config::default_client;
+use crate::entities::TraceStatus;
+use crate::trace::{Span, SpanEvent, SpanStatus, SpanType, TraceBuilder};
+use crate::MLFlowError::TraceBuilderError;
+use crate::MLFlowResult;
+use opentelemetry::trace::{SpanId, Status, TraceId};
+use opentelemetry::{Array, KeyValue};
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SPAN_TYPE: &str = "mlflow.spanType";
+const SPAN_INPUTS: &str = "mlflow.spanInputs";
+const SPAN_OUTPUTS: &str = "mlflow.spanOutputs";
+const SPAN_REQUEST_ID: &str = "mlflow.traceRequestId";
+
+/// Exports OpenTelemetry spans to an MLflow experiment as GenAI traces.
+#[derive(Debug)]
+pub struct MLFlowSpanExporter {
+    experiment_id: String,
+    pending: Mutex<HashMap<TraceId, Vec<SpanData>>>,
+    client: SharedClient,
+}
+
+impl MLFlowSpanExporter {
+    /// Buffers `batch` and sends the traces whose root span is part of it.
+    fn export_spans(&self, batch: Vec<SpanData>) -> MLFlowResult<()> {
+        let mut completed = vec![];
+        {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            for span in batch {
+                let trace_id = span.span_context.trace_id();
+                let is_root = is_root(&span);
+                pending.entry(trace_id).or_default().push(span);
+                if is_root {
+                    completed.extend(pending.remove(&trace_id));
+                }
+            }
+        }
+
+        self.send_traces(completed)
+    }
+
+    /// Sends every buffered trace, including those whose root span hasn't been exported.
+    fn flush(&self) -> MLFlowResult<()> {
+        let pending: Vec<Vec<SpanData>> = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain()
+            .map(|(_, spans)| spans)
+            .collect();
+        self.send_traces(pending)
+    }
+
+    /// Sends every trace, returning the first error once all of them were attempted.
+    fn send_traces(&self, traces: Vec<Vec<SpanData>>) -> MLFlowResult<()> {
+        let mut result = Ok(());
+        for spans in traces {
+            let sent = self.send_trace(spans);
+            if result.is_ok() {
+                result = sent;
+            }
+        }
+        result
+    }
+
+    fn send_trace(&self, spans: Vec<SpanData>) -> MLFlowResult<()> {
+        let span_ids: Vec<SpanId> = spans.iter().map(|s| s.span_context.span_id()).collect();
+        let is_local_root =
+            |span: &SpanData| is_root(span) || !span_ids.contains(&span.parent_span_id);
+        let Some(root) = spans
+            .iter()
+            .filter(|s| is_local_root(s))
+            .min_by_key(|s| s.start_time)
+        else {
+            return Ok(());
+        };
+        let start_time = spans.iter().map(|s| s.start_time).min();
+        let end_time = spans.iter().map(|s| s.end_time).max();
+        let status = match root.status {
+            Status::Error { .. } => TraceStatus::Error,
+            _ => TraceStatus::Ok,
+        };
+
+        let mut trace = TraceBuilder::new(&self.experiment_id)?
+            .with_name(root.name.as_ref())
+            .with_start_time(start_time.map(millis).unwrap_or_default())
+            .with_client(self.client.clone())
+            .start()?;
+        trace.trace_id = format!("0x{}", root.span_context.trace_id());
+        for span in &spans {
+            trace.log_span(convert(span, is_local_root(span)));
+        }
+        trace.end_at(status, end_time.map(millis).unwrap_or_default())
+    }
+}
+
+impl SpanExporter for MLFlowSpanExporter {
+    fn export(
+        &self,
+        batch: Vec<SpanData>,
+    ) -> impl std::future::Future<Output = OTelSdkResult> + Send {
+        let result = self
+            .export_spans(batch)
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()));
+        std::future::ready(result)
+    }
+
+    fn shutdown_with_timeout(&mut self, _timeout: Duration) -> OTelSdkResult {
+        self.force_flush()
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.flush()
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+}
+
+fn is_root(span: &SpanData) -> bool {
+    span.parent_span_id == SpanId::INVALID || span.parent_span_is_remote
+}
+
+fn nanos(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or_default()
+}
+
+fn millis(time: SystemTime) -> i64 {
+    nanos(time) / 1_000_000
+}
+
+fn convert(span: &SpanData, is_root: bool) -> Span {
+    let mut converted = Span {
+        name: span.name.to_string(),
+        span_id: format!("0x{}", span.span_context.span_id()),
+        parent_id: (!is_root).then(|| format!("0x{}", span.parent_span_id)),
+        span_type: SpanType::Unknown,
+        start_time_ns: nanos(span.start_time),
+        end_time_ns: Some(nanos(span.end_time)),
+        status: match &span.status {
+            Status::Unset => SpanStatus::Unset,
+            Status::Ok => SpanStatus::Ok,
+            Status::Error { description } => SpanStatus::Error(description.to_string()),
+        },
+        inputs: None,
+        outputs: None,
+        attributes: BTreeMap::new(),
+        events: span
+            .events
+            .iter()
+            .map(|event| SpanEvent {
+                name: event.name.to_string(),
+                timestamp: nanos(event.timestamp),
+                attributes: attributes(&event.attributes),
+            })
+            .collect(),
+    };
+
+    for (key, value) in attributes(&span.attributes) {
+        match key.as_str() {
+            SPAN_TYPE => converted.span_type = span_type(&value),
+            SPAN_INPUTS => converted.inputs = Some(decode(value)),
+            SPAN_OUTPUTS => converted.outputs = Some(decode(value)),
+            SPAN_REQUEST_ID => {}
+            _ => {
+                converted.attributes.insert(key, value);
+            }
+        }
+    }
+    converted
+}
+
+fn attributes(attributes: &[KeyValue]) -> BTreeMap<String, Value> {
+    attributes
+        .iter()
+        .map(|kv| (kv.key.to_string(), json(&kv.value)))
+        .collect()
+}
+
+fn json(value: &opentelemetry::Value) -> Value {
+    match value {
+        opentelemetry::Value::Bool(b) => Value::from(*b),
+        opentelemetry::Value::I64(i) => Value::from(*i),
+        opentelemetry::Value::F64(f) => Value::from(*f),
+        opentelemetry::Value::String(s) => Value::from(s.as_str()),
+        opentelemetry::Value::Array(Array::Bool(values)) => Value::from(values.clone()),
+        opentelemetry::Value::Array(Array::I64(values)) => Value::from(values.clone()),
+        opentelemetry::Value::Array(Array::F64(values)) => Value::from(values.clone()),
+        opentelemetry::Value::Array(Array::String(values)) => {
+            values.iter().map(|s| Value::from(s.as_str())).collect()
+        }
+        other => Value::from(other.to_string()),
+    }
+}
+
+/// MLflow SDKs store inputs and outputs as JSON encoded strings.
+fn decode(value: Value) -> Value {
+    match &value {
+        Value::String(s) => serde_json::from_str(s).unwrap_or(value),
+        _ => value,
+    }
+}
+
+/// Accepts both `LLM` and the JSON encoded `"LLM"` MLflow SDKs set.
+fn span_type(value: &Value) -> SpanType {
+    serde_json::from_value(decode(value.clone())).unwrap_or(SpanType::Unknown)
+}
+
+#[derive(Clone, Debug)]
+pub struct MLFlowSpanExporterBuilder {
+    experiment_id: String,
+    client: SharedClient,
+}
+
+impl MLFlowSpanExporterBuilder {
+    pub fn new(experiment_id: impl AsRef<str>) -> MLFlowResult<MLFlowSpanExporterBuilder> {
+        if experiment_id.as_ref().is_empty() {
+            return Err(TraceBuilderError(
+                "experiment_id cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(MLFlowSpanExporterBuilder {
+            experiment_id: experiment_id.as_ref().to_string(),
+            client: default_client()?,
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    pub fn build(self) -> MLFlowSpanExporter {
+        MLFlowSpanExporter {
+            experiment_id: self.experiment_id,
+            pending: Mutex::new(HashMap::new()),
+            client: self.client,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_attributes() {
+        assert_eq!(span_type(&Value::from("LLM")), SpanType::Llm);
+        assert_eq!(
+            span_type(&Value::from("\"RETRIEVER\"")),
+            SpanType::Retriever
+        );
+        assert_eq!(span_type(&Value::from("SOMETHING")), SpanType::Unknown);
+        assert_eq!(
+            decode(Value::from(r#"{"q": 1}"#)),
+            serde_json::json!({"q": 1})
+        );
+        assert_eq!(decode(Value::from("plain")), Value::from("plain"));
+        assert_eq!(
+            json(&opentelemetry::Value::Array(Array::I64(vec![1, 2]))),
+            serde_json::json!([1, 2])
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn exports_traces() {
+        use crate::MockMLFlowClient;
+        use opentelemetry::trace::{Span as _, TraceContextExt, Tracer, TracerProvider};
+        use opentelemetry::Context;
+        use opentelemetry_sdk::trace::SdkTracerProvider;
+
+        let mock = MockMLFlowClient::default();
+        let exporter = MLFlowSpanExporterBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter)
+            .build();
+        let tracer = provider.tracer("test");
+
+        let root = tracer.start("qa");
+        let cx = Context::current_with_span(root);
+        let mut llm = tracer.start_with_context("chat", &cx);
+        llm.set_attribute(KeyValue::new(SPAN_TYPE, "LLM"));
+        llm.set_attribute(KeyValue::new(SPAN_INPUTS, r#"{"prompt": "hi"}"#));
+        llm.set_attribute(KeyValue::new("model", "gpt-4o"));
+        llm.end();
+        let trace_id = cx.span().span_context().trace_id();
+        cx.span().end();
+        provider.shutdown().unwrap();
+
+        let request_id = "tr-00000000000000000000000000000001";
+        let info = mock.trace_info(request_id).unwrap();
+        assert_eq!(info.status, TraceStatus::Ok);
+        assert_eq!(info.tag(crate::tags::MLFLOW_TRACE_NAME), Some("qa"));
+
+        let data: Value = serde_json::from_slice(&mock.trace_data(request_id).unwrap()).unwrap();
+        let spans = data["spans"].as_array().unwrap();
+        assert_eq!(spans.len(), 2);
+        let chat = spans.iter().find(|s| s["name"] == "chat").unwrap();
+        let qa = spans.iter().find(|s| s["name"] == "qa").unwrap();
+        assert_eq!(chat["parent_id"], qa["context"]["span_id"]);
+        assert_eq!(qa["parent_id"], Value::Null);
+        assert_eq!(qa["context"]["trace_id"], format!("0x{}", trace_id));
+        assert_eq!(chat["attributes"]["mlflow.spanType"], "\"LLM\"");
+        assert_eq!(
+            chat["attributes"]["mlflow.spanInputs"],
+            r#"{"prompt":"hi"}"#
+        );
+        assert_eq!(chat["attributes"]["model"], "\"gpt-4o\"");
+    }
+}
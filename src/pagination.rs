@@ -0,0 +1,235 @@
+//! Lazily following the `page_token`s of the server's paginated list and search endpoints.
+
+use crate::MLFlowResult;
+use std::collections::VecDeque;
+
+/// One page of results, and the token to request the page after it with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_page_token: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, next_page_token: Option<String>) -> Page<T> {
+        Page {
+            items,
+            next_page_token,
+        }
+    }
+
+    /// The token of the next page, treating the empty token some endpoints return as the end.
+    fn next_token(&mut self) -> Option<String> {
+        self.next_page_token.take().filter(|t| !t.is_empty())
+    }
+}
+
+type FetchPage<T> = Box<dyn FnMut(Option<&str>) -> MLFlowResult<Page<T>> + Send>;
+
+/// Iterator over the items of a paginated endpoint, fetching a page whenever the previous one
+/// has been consumed.
+///
+/// A failed request is yielded as an error and ends the iteration.
+pub struct PageIterator<T> {
+    fetch: FetchPage<T>,
+    page_token: Option<String>,
+    buffer: VecDeque<T>,
+    finished: bool,
+}
+
+impl<T> PageIterator<T> {
+    /// Iterates the pages `fetch` returns for a page token, starting without one.
+    pub fn new(
+        fetch: impl FnMut(Option<&str>) -> MLFlowResult<Page<T>> + Send + 'static,
+    ) -> PageIterator<T> {
+        PageIterator {
+            fetch: Box::new(fetch),
+            page_token: None,
+            buffer: VecDeque::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<T> Iterator for PageIterator<T> {
+    type Item = MLFlowResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.finished {
+                return None;
+            }
+
+            match (self.fetch)(self.page_token.as_deref()) {
+                Ok(mut page) => {
+                    self.page_token = page.next_token();
+                    self.finished = self.page_token.is_none();
+                    self.buffer.extend(page.items);
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use stream::PageStream;
+
+#[cfg(feature = "async")]
+mod stream {
+    use super::Page;
+    use crate::async_client::MaybeSend;
+    use crate::MLFlowResult;
+    use futures_core::Stream;
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    #[cfg(not(target_arch = "wasm32"))]
+    type PageFuture<T> = Pin<Box<dyn Future<Output = MLFlowResult<Page<T>>> + Send>>;
+    #[cfg(not(target_arch = "wasm32"))]
+    type FetchPage<T> = Box<dyn FnMut(Option<String>) -> PageFuture<T> + Send>;
+    #[cfg(target_arch = "wasm32")]
+    type PageFuture<T> = Pin<Box<dyn Future<Output = MLFlowResult<Page<T>>>>>;
+    #[cfg(target_arch = "wasm32")]
+    type FetchPage<T> = Box<dyn FnMut(Option<String>) -> PageFuture<T>>;
+
+    /// [`Stream`] over the items of a paginated endpoint for the async client, the counterpart
+    /// of [`PageIterator`](super::PageIterator).
+    ///
+    /// A failed request is yielded as an error and ends the stream.
+    pub struct PageStream<T> {
+        fetch: FetchPage<T>,
+        page_token: Option<String>,
+        pending: Option<PageFuture<T>>,
+        buffer: VecDeque<T>,
+        finished: bool,
+    }
+
+    impl<T> PageStream<T> {
+        /// Streams the pages `fetch` resolves for a page token, starting without one.
+        pub fn new<F>(
+            mut fetch: impl FnMut(Option<String>) -> F + MaybeSend + 'static,
+        ) -> PageStream<T>
+        where
+            F: Future<Output = MLFlowResult<Page<T>>> + MaybeSend + 'static,
+        {
+            PageStream {
+                fetch: Box::new(move |token| Box::pin(fetch(token))),
+                page_token: None,
+                pending: None,
+                buffer: VecDeque::new(),
+                finished: false,
+            }
+        }
+
+        /// Fetches every remaining item, stopping at the first failed request.
+        pub async fn collect(mut self) -> MLFlowResult<Vec<T>> {
+            let mut items = vec![];
+            while let Some(item) =
+                std::future::poll_fn(|cx| Pin::new(&mut self).poll_next(cx)).await
+            {
+                items.push(item?);
+            }
+            Ok(items)
+        }
+    }
+
+    // nothing is pinned in place, the pending request is boxed
+    impl<T> Unpin for PageStream<T> {}
+
+    impl<T> Stream for PageStream<T> {
+        type Item = MLFlowResult<T>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            loop {
+                if let Some(item) = self.buffer.pop_front() {
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                if self.finished {
+                    return Poll::Ready(None);
+                }
+
+                if self.pending.is_none() {
+                    let token = self.page_token.take();
+                    self.pending = Some((self.fetch)(token));
+                }
+                let result = match self.pending.as_mut() {
+                    Some(pending) => std::task::ready!(pending.as_mut().poll(cx)),
+                    None => unreachable!("a request is pending"),
+                };
+                self.pending = None;
+
+                match result {
+                    Ok(mut page) => {
+                        self.page_token = page.next_token();
+                        self.finished = self.page_token.is_none();
+                        self.buffer.extend(page.items);
+                    }
+                    Err(e) => {
+                        self.finished = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MLFlowError::UnknownError;
+
+    /// Pages of two items out of `0..5`, failing the request for `fail_at`.
+    fn pages(token: Option<&str>, fail_at: Option<usize>) -> MLFlowResult<Page<usize>> {
+        let start: usize = token.map(|t| t.parse().unwrap()).unwrap_or_default();
+        if Some(start) == fail_at {
+            return Err(UnknownError("page failed".to_string()));
+        }
+        let end = (start + 2).min(5);
+        // the last page carries an empty token, as some endpoints return
+        let next = match end < 5 {
+            true => end.to_string(),
+            false => String::new(),
+        };
+        Ok(Page::new((start..end).collect(), Some(next)))
+    }
+
+    #[test]
+    fn iterates_every_page() {
+        let items: Vec<usize> = PageIterator::new(|token| pages(token, None))
+            .collect::<MLFlowResult<_>>()
+            .unwrap();
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+
+        let mut failing = PageIterator::new(|token| pages(token, Some(2)));
+        assert_eq!(failing.next().unwrap().unwrap(), 0);
+        assert_eq!(failing.next().unwrap().unwrap(), 1);
+        failing.next().unwrap().expect_err("page failed");
+        assert!(failing.next().is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn streams_every_page() {
+        let stream = PageStream::new(|token: Option<String>| {
+            let page = pages(token.as_deref(), None);
+            async move { page }
+        });
+        assert_eq!(stream.collect().await.unwrap(), vec![0, 1, 2, 3, 4]);
+
+        let stream = PageStream::new(|token: Option<String>| {
+            let page = pages(token.as_deref(), Some(4));
+            async move { page }
+        });
+        stream.collect().await.expect_err("page failed");
+    }
+}
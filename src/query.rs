@@ -0,0 +1,340 @@
+//! Evaluation of search filters and pagination against entities held by the client itself, for
+//! the clients that answer searches without a tracking server.
+
+use crate::client::ExperimentInfo;
+use crate::entities::{LifecycleStage, RunData, RunInfo};
+use crate::search::RunViewType;
+use crate::tags::MLFLOW_USER;
+use crate::MLFlowError::SearchError;
+use crate::MLFlowResult;
+
+const DEFAULT_MAX_RESULTS: usize = 1000;
+
+/// The page of `items` starting at the offset `page_token` encodes.
+pub(crate) fn page<T: Clone>(
+    items: Vec<T>,
+    max_results: Option<usize>,
+    page_token: Option<&str>,
+) -> MLFlowResult<(Vec<T>, Option<String>)> {
+    let offset = match page_token {
+        Some(token) => token
+            .parse::<usize>()
+            .map_err(|_| SearchError(format!("invalid page token {}", token)))?,
+        None => 0,
+    };
+    let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    let end = items.len().min(offset + max_results);
+    let next_page_token = (end < items.len()).then(|| end.to_string());
+    Ok((
+        items.get(offset..end).unwrap_or_default().to_vec(),
+        next_page_token,
+    ))
+}
+
+pub(crate) fn matches_view_type(
+    lifecycle_stage: Option<LifecycleStage>,
+    view_type: RunViewType,
+) -> bool {
+    let deleted = lifecycle_stage == Some(LifecycleStage::Deleted);
+    match view_type {
+        RunViewType::ActiveOnly => !deleted,
+        RunViewType::DeletedOnly => deleted,
+        RunViewType::All => true,
+    }
+}
+
+/// A single `<entity>.<key> <op> <value>` comparison of a search filter.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Clause {
+    pub(crate) entity: String,
+    pub(crate) key: String,
+    pub(crate) op: String,
+    pub(crate) value: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Number(f64),
+    String(String),
+}
+
+/// A lexical token of a search filter.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Comparator(String),
+    Quoted(String),
+}
+
+/// Parses the subset of the MLflow search grammar [`Filter`](crate::Filter) produces: comparisons
+/// joined by `AND`.
+pub(crate) fn parse_filter(filter: &str) -> MLFlowResult<Vec<Clause>> {
+    let invalid = || SearchError(format!("invalid filter {}", filter));
+    let mut tokens = tokenize(filter)?.into_iter();
+    let mut clauses = vec![];
+
+    while let Some(key) = tokens.next() {
+        let Token::Word(key) = key else {
+            return Err(invalid());
+        };
+        let op = match tokens.next() {
+            Some(Token::Comparator(op)) => op,
+            Some(Token::Word(op)) => op.to_uppercase(),
+            _ => return Err(invalid()),
+        };
+        if !["=", "!=", "<", "<=", ">", ">=", "LIKE", "ILIKE"].contains(&op.as_str()) {
+            return Err(SearchError(format!(
+                "unsupported comparator {} in filter {}",
+                op, filter
+            )));
+        }
+        let value = match tokens.next() {
+            Some(Token::Quoted(value)) => Value::String(value),
+            Some(Token::Word(value)) => Value::Number(value.parse().map_err(|_| {
+                SearchError(format!("invalid value {} in filter {}", value, filter))
+            })?),
+            _ => return Err(invalid()),
+        };
+
+        let (entity, key) = match key.split_once('.') {
+            Some((entity, key)) if !key.is_empty() => (entity.to_string(), key.to_string()),
+            _ => ("attributes".to_string(), key),
+        };
+        let entity = match entity.as_str() {
+            "metric" | "metrics" => "metrics",
+            "param" | "params" | "parameter" | "parameters" => "params",
+            "tag" | "tags" => "tags",
+            "attribute" | "attributes" | "attr" | "run" => "attributes",
+            other => {
+                return Err(SearchError(format!(
+                    "unsupported entity {} in filter {}",
+                    other, filter
+                )))
+            }
+        };
+
+        clauses.push(Clause {
+            entity: entity.to_string(),
+            key: key.replace('`', ""),
+            op,
+            value,
+        });
+
+        match tokens.next() {
+            Some(Token::Word(and)) if and.eq_ignore_ascii_case("AND") => {}
+            Some(_) => return Err(invalid()),
+            None => {}
+        }
+    }
+
+    Ok(clauses)
+}
+
+fn tokenize(filter: &str) -> MLFlowResult<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = filter.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some(q) if q == c => break,
+                    Some(other) => value.push(other),
+                    None => {
+                        return Err(SearchError(format!(
+                            "unterminated string in filter {}",
+                            filter
+                        )))
+                    }
+                }
+            }
+            tokens.push(Token::Quoted(value));
+        } else if "=!<>".contains(c) {
+            let mut op = String::new();
+            while let Some(&o) = chars.peek().filter(|o| "=!<>".contains(**o)) {
+                op.push(o);
+                chars.next();
+            }
+            tokens.push(Token::Comparator(op));
+        } else {
+            let mut word = String::new();
+            let mut quoted = false;
+            while let Some(&w) = chars.peek() {
+                if w == '`' {
+                    quoted = !quoted;
+                } else if !quoted && (w.is_whitespace() || "=!<>'\"".contains(w)) {
+                    break;
+                }
+                word.push(w);
+                chars.next();
+            }
+            if quoted {
+                return Err(SearchError(format!(
+                    "unterminated identifier in filter {}",
+                    filter
+                )));
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    Ok(tokens)
+}
+
+pub(crate) fn all_match(
+    clauses: &[Clause],
+    value_of: impl Fn(&Clause) -> MLFlowResult<Option<Value>>,
+) -> MLFlowResult<bool> {
+    for clause in clauses {
+        let matched = match value_of(clause)? {
+            Some(actual) => compare(&actual, &clause.op, &clause.value)?,
+            None => false,
+        };
+        if !matched {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn compare(actual: &Value, op: &str, expected: &Value) -> MLFlowResult<bool> {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => Ok(match op {
+            "=" => a == b,
+            "!=" => a != b,
+            "<" => a < b,
+            "<=" => a <= b,
+            ">" => a > b,
+            ">=" => a >= b,
+            _ => return Err(SearchError(format!("{} cannot compare numbers", op))),
+        }),
+        (Value::String(a), Value::String(b)) => Ok(match op {
+            "=" => a == b,
+            "!=" => a != b,
+            "LIKE" => like(a, b),
+            "ILIKE" => like(&a.to_lowercase(), &b.to_lowercase()),
+            _ => return Err(SearchError(format!("{} cannot compare strings", op))),
+        }),
+        _ => Err(SearchError(format!(
+            "cannot compare {:?} with {:?}",
+            actual, expected
+        ))),
+    }
+}
+
+/// SQL `LIKE` matching, where `%` matches any sequence and `_` any single character.
+fn like(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    fn matches(value: &[char], pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => value.is_empty(),
+            Some(('%', rest)) => (0..=value.len()).any(|i| matches(&value[i..], rest)),
+            Some(('_', rest)) => !value.is_empty() && matches(&value[1..], rest),
+            Some((c, rest)) => value.first() == Some(c) && matches(&value[1..], rest),
+        }
+    }
+
+    matches(&value, &pattern)
+}
+
+pub(crate) fn run_value(
+    info: &RunInfo,
+    data: &RunData,
+    clause: &Clause,
+) -> MLFlowResult<Option<Value>> {
+    let key = clause.key.as_str();
+    Ok(match clause.entity.as_str() {
+        "metrics" => data
+            .metrics
+            .iter()
+            .find(|m| m.key == key)
+            .map(|m| Value::Number(m.value)),
+        "params" => data
+            .params
+            .iter()
+            .find(|p| p.key == key)
+            .map(|p| Value::String(p.value.clone())),
+        "tags" => data
+            .tags
+            .iter()
+            .find(|t| t.key == key)
+            .map(|t| Value::String(t.value.clone())),
+        _ => match key {
+            "run_id" => Some(Value::String(info.run_id.clone())),
+            "run_name" => info.run_name.clone().map(Value::String),
+            "status" => Some(Value::String(
+                serde_json::to_value(info.status)
+                    .ok()
+                    .and_then(|s| s.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+            )),
+            "artifact_uri" => info.artifact_uri.clone().map(Value::String),
+            "user_id" => data
+                .tags
+                .iter()
+                .find(|t| t.key == MLFLOW_USER)
+                .map(|t| Value::String(t.value.clone())),
+            "start_time" | "created" => info.start_time.map(|t| Value::Number(t as f64)),
+            "end_time" => info.end_time.map(|t| Value::Number(t as f64)),
+            other => return Err(SearchError(format!("unsupported run attribute {}", other))),
+        },
+    })
+}
+
+pub(crate) fn experiment_value(
+    experiment: &ExperimentInfo,
+    clause: &Clause,
+) -> MLFlowResult<Option<Value>> {
+    match (clause.entity.as_str(), clause.key.as_str()) {
+        ("tags", key) => Ok(experiment
+            .tags
+            .iter()
+            .find(|t| t.key == key)
+            .map(|t| Value::String(t.value.clone()))),
+        ("attributes", "name") => Ok(Some(Value::String(experiment.name.clone()))),
+        (entity, key) => Err(SearchError(format!(
+            "unsupported experiment filter key {}.{}",
+            entity, key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_filters() {
+        let clauses = parse_filter(
+            "metrics.rmse < 0.5 AND params.`learning rate` = '0.01' and attributes.run_name LIKE \"a'%\"",
+        )
+        .unwrap();
+        assert_eq!(clauses.len(), 3);
+        assert_eq!(clauses[0].entity, "metrics");
+        assert_eq!(clauses[0].value, Value::Number(0.5));
+        assert_eq!(clauses[1].key, "learning rate");
+        assert_eq!(clauses[1].value, Value::String("0.01".to_string()));
+        assert_eq!(clauses[2].op, "LIKE");
+        assert_eq!(clauses[2].value, Value::String("a'%".to_string()));
+
+        assert!(parse_filter("").unwrap().is_empty());
+        parse_filter("metrics.rmse <").expect_err("missing value");
+        parse_filter("metrics.rmse ~ 1").expect_err("unknown comparator");
+        parse_filter("params.a = 'b' OR params.c = 'd'").expect_err("OR is not supported");
+    }
+
+    #[test]
+    fn like_patterns() {
+        assert!(like("nlp-bert", "nlp-%"));
+        assert!(like("nlp-bert", "%bert"));
+        assert!(like("abc", "a_c"));
+        assert!(!like("abc", "a_"));
+        assert!(!like("other", "nlp-%"));
+    }
+}
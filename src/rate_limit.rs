@@ -0,0 +1,113 @@
+//! Client-side throttling of the requests sent to the tracking server.
+
+use crate::MLFlowError::ClientError;
+use crate::MLFlowResult;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting the rate of requests, so that many workers sharing a small tracking
+/// server stay below the rate at which it starts answering `429 Too Many Requests`.
+///
+/// The bucket holds up to `burst` tokens and refills at `requests_per_second`. Each request takes
+/// a token, waiting for one to be refilled when the bucket is empty. Clones share the bucket, so
+/// one limiter can throttle several clients together.
+///
+/// ```
+/// use mlflow_rs::{MLFLowRestClientBuilder, RateLimiter};
+///
+/// let limiter = RateLimiter::new(20.0, 5).unwrap();
+/// let client = MLFLowRestClientBuilder::new("http://localhost:5000")
+///     .unwrap()
+///     .with_rate_limiter(limiter.clone())
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests are waiting for tokens reserved ahead of the refill.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Allows `requests_per_second` on average, and bursts of up to `burst` requests after a
+    /// quiet period.
+    pub fn new(requests_per_second: f64, burst: u32) -> MLFlowResult<RateLimiter> {
+        if !requests_per_second.is_finite() || requests_per_second <= 0.0 {
+            return Err(ClientError(format!(
+                "rate limit must be a positive number of requests per second, got {}",
+                requests_per_second
+            )));
+        }
+        let burst = burst.max(1) as f64;
+        Ok(RateLimiter {
+            requests_per_second,
+            burst,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            })),
+        })
+    }
+
+    /// Takes a token, blocking until one is available.
+    pub fn acquire(&self) {
+        let delay = self.reserve(Instant::now());
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// Takes a token at `now`, returning how long to wait before it may be used.
+    fn reserve(&self, now: Instant) -> Duration {
+        // a panic while holding the lock leaves the bucket consistent
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.requests_per_second).min(self.burst);
+        bucket.refilled_at = bucket.refilled_at.max(now);
+        bucket.tokens -= 1.0;
+
+        match bucket.tokens < 0.0 {
+            true => Duration::from_secs_f64(-bucket.tokens / self.requests_per_second),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_then_throttles() {
+        let limiter = RateLimiter::new(10.0, 2).unwrap();
+        let start = limiter.bucket.lock().unwrap().refilled_at;
+
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.clone().reserve(start), Duration::ZERO);
+        // later requests queue up behind each other
+        assert_eq!(limiter.reserve(start), Duration::from_millis(100));
+        assert_eq!(limiter.reserve(start), Duration::from_millis(200));
+
+        // the bucket refills, but never beyond the burst
+        let later = start + Duration::from_secs(10);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn rate_must_be_positive() {
+        RateLimiter::new(0.0, 1).expect_err("zero requests per second");
+        RateLimiter::new(f64::NAN, 1).expect_err("not a number");
+        assert_eq!(RateLimiter::new(1.0, 0).unwrap().burst, 1.0);
+    }
+}
@@ -0,0 +1,512 @@
+//! Model Registry support: registered models, their versions and tags.
+
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{
+    CreateModelVersionRequest, CreateRegisteredModelRequest, MLFlowClient, SharedClient,
+};
+use crate::config::default_client;
+use crate::MLFlowError::{ModelVersionBuilderError, RegisteredModelBuilderError};
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisteredModelTag {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for RegisteredModelTag {
+    fn from((k, v): (&str, &str)) -> Self {
+        RegisteredModelTag {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelVersionTag {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<(&str, &str)> for ModelVersionTag {
+    fn from((k, v): (&str, &str)) -> Self {
+        ModelVersionTag {
+            key: k.to_string(),
+            value: v.to_string(),
+        }
+    }
+}
+
+/// Deployment stage of a model version.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModelVersionStage {
+    #[default]
+    None,
+    Staging,
+    Production,
+    Archived,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ModelVersionStatus {
+    PendingRegistration,
+    FailedRegistration,
+    Ready,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModelVersion {
+    pub name: String,
+    pub version: String,
+    #[serde(default, alias = "creation_time")]
+    pub creation_timestamp: Option<i64>,
+    #[serde(default, alias = "last_update_time")]
+    pub last_updated_timestamp: Option<i64>,
+    #[serde(default)]
+    pub current_stage: ModelVersionStage,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub status: Option<ModelVersionStatus>,
+    #[serde(default)]
+    pub status_message: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<ModelVersionTag>,
+
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) client: SharedClient,
+}
+
+impl ModelVersion {
+    pub(crate) fn with_client(mut self, client: SharedClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Moves the version to `stage`. When `archive_existing_versions` is set, versions currently
+    /// in that stage are archived.
+    pub fn transition_stage(
+        &mut self,
+        stage: ModelVersionStage,
+        archive_existing_versions: bool,
+    ) -> MLFlowResult<()> {
+        let resp = self.client.transition_model_version_stage(
+            &self.name,
+            &self.version,
+            stage,
+            archive_existing_versions,
+        )?;
+        *self = resp.model_version.with_client(self.client.clone());
+        Ok(())
+    }
+
+    pub fn delete(self) -> MLFlowResult<()> {
+        self.client.delete_model_version(&self.name, &self.version)
+    }
+
+    /// Sets a tag on the version, replacing any existing tag with the same key.
+    pub fn set_tag(&mut self, tag: impl Into<ModelVersionTag>) -> MLFlowResult<()> {
+        let tag = tag.into();
+        self.client
+            .set_model_version_tag(&self.name, &self.version, &tag)?;
+        self.tags.retain(|t| t.key != tag.key);
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    pub fn delete_tag(&mut self, key: impl AsRef<str>) -> MLFlowResult<()> {
+        self.client
+            .delete_model_version_tag(&self.name, &self.version, key.as_ref())?;
+        self.tags.retain(|t| t.key != key.as_ref());
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegisteredModel {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, alias = "creation_time")]
+    pub creation_timestamp: Option<i64>,
+    #[serde(default, alias = "last_update_time")]
+    pub last_updated_timestamp: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<RegisteredModelTag>,
+    #[serde(default)]
+    pub latest_versions: Vec<ModelVersion>,
+
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) client: SharedClient,
+}
+
+impl RegisteredModel {
+    pub(crate) fn with_client(mut self, client: SharedClient) -> Self {
+        self.latest_versions = self
+            .latest_versions
+            .into_iter()
+            .map(|v| v.with_client(client.clone()))
+            .collect();
+        self.client = client;
+        self
+    }
+
+    /// Renames the model, updating this instance with the server's response.
+    pub fn rename(&mut self, new_name: impl AsRef<str>) -> MLFlowResult<()> {
+        let resp = self
+            .client
+            .rename_registered_model(&self.name, new_name.as_ref())?;
+        *self = resp.registered_model.with_client(self.client.clone());
+        Ok(())
+    }
+
+    /// Deletes the model and all of its versions.
+    pub fn delete(self) -> MLFlowResult<()> {
+        self.client.delete_registered_model(&self.name)
+    }
+
+    /// Sets a tag on the model, replacing any existing tag with the same key.
+    pub fn set_tag(&mut self, tag: impl Into<RegisteredModelTag>) -> MLFlowResult<()> {
+        let tag = tag.into();
+        self.client.set_registered_model_tag(&self.name, &tag)?;
+        self.tags.retain(|t| t.key != tag.key);
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    pub fn delete_tag(&mut self, key: impl AsRef<str>) -> MLFlowResult<()> {
+        self.client
+            .delete_registered_model_tag(&self.name, key.as_ref())?;
+        self.tags.retain(|t| t.key != key.as_ref());
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RegisteredModelBuilder {
+    name: String,
+    description: Option<String>,
+    tags: Vec<RegisteredModelTag>,
+    client: SharedClient,
+}
+
+impl RegisteredModelBuilder {
+    pub fn new(name: impl AsRef<str>) -> MLFlowResult<RegisteredModelBuilder> {
+        if name.as_ref().is_empty() {
+            return Err(RegisteredModelBuilderError(
+                "name cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(RegisteredModelBuilder {
+            name: name.as_ref().to_string(),
+            description: None,
+            tags: vec![],
+            client: default_client()?,
+        })
+    }
+
+    pub fn with_description(mut self, description: impl AsRef<str>) -> Self {
+        self.description = Some(description.as_ref().to_string());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<RegisteredModelTag>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<impl Into<RegisteredModelTag>>) -> Self {
+        self.tags = tags.into_iter().map(|t| t.into()).collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    pub fn build(self) -> MLFlowResult<RegisteredModel> {
+        let request = CreateRegisteredModelRequest {
+            name: self.name,
+            description: self.description,
+            tags: self.tags,
+        };
+
+        let resp = self.client.create_registered_model(request)?;
+        Ok(resp.registered_model.with_client(self.client))
+    }
+}
+
+#[derive(Default)]
+pub struct RegisteredModelLoader {
+    client: Option<SharedClient>,
+}
+
+impl RegisteredModelLoader {
+    #[cfg(feature = "blocking")]
+    pub fn with_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn load(self, name: impl AsRef<str>) -> MLFlowResult<RegisteredModel> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+
+        let resp = client.get_registered_model(name.as_ref())?;
+        Ok(resp.registered_model.with_client(client))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModelVersionBuilder {
+    name: String,
+    source: String,
+    run_id: Option<String>,
+    description: Option<String>,
+    tags: Vec<ModelVersionTag>,
+    client: SharedClient,
+}
+
+impl ModelVersionBuilder {
+    /// Starts a new version of the registered model `name` whose files live at `source`,
+    /// typically a run artifact URI such as `runs:/<run_id>/model`.
+    pub fn new(
+        name: impl AsRef<str>,
+        source: impl AsRef<str>,
+    ) -> MLFlowResult<ModelVersionBuilder> {
+        if name.as_ref().is_empty() {
+            return Err(ModelVersionBuilderError("name cannot be empty".to_string()));
+        }
+        if source.as_ref().is_empty() {
+            return Err(ModelVersionBuilderError(
+                "source cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(ModelVersionBuilder {
+            name: name.as_ref().to_string(),
+            source: source.as_ref().to_string(),
+            run_id: None,
+            description: None,
+            tags: vec![],
+            client: default_client()?,
+        })
+    }
+
+    pub fn with_run_id(mut self, run_id: impl AsRef<str>) -> Self {
+        self.run_id = Some(run_id.as_ref().to_string());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl AsRef<str>) -> Self {
+        self.description = Some(description.as_ref().to_string());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<ModelVersionTag>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<impl Into<ModelVersionTag>>) -> Self {
+        self.tags = tags.into_iter().map(|t| t.into()).collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    pub fn build(self) -> MLFlowResult<ModelVersion> {
+        let request = CreateModelVersionRequest {
+            name: self.name,
+            source: self.source,
+            run_id: self.run_id,
+            description: self.description,
+            tags: self.tags,
+        };
+
+        let resp = self.client.create_model_version(request)?;
+        Ok(resp.model_version.with_client(self.client))
+    }
+}
+
+#[derive(Default)]
+pub struct ModelVersionLoader {
+    client: Option<SharedClient>,
+}
+
+impl ModelVersionLoader {
+    #[cfg(feature = "blocking")]
+    pub fn with_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn load(
+        self,
+        name: impl AsRef<str>,
+        version: impl AsRef<str>,
+    ) -> MLFlowResult<ModelVersion> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+
+        let resp = client.get_model_version(name.as_ref(), version.as_ref())?;
+        Ok(resp.model_version.with_client(client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_model_builder_new_empty_name() {
+        RegisteredModelBuilder::new("")
+            .expect_err("RegisteredModelBuilderError: name cannot be empty");
+    }
+
+    #[test]
+    fn registered_model_builder_with_tags() {
+        let builder = RegisteredModelBuilder::new("model")
+            .unwrap()
+            .with_description("a model")
+            .with_tags(vec![("key", "value"), ("key2", "value2")]);
+
+        assert_eq!(builder.name, "model");
+        assert_eq!(builder.description, Some("a model".to_string()));
+        assert_eq!(builder.tags.len(), 2);
+    }
+
+    #[test]
+    fn registered_model_from_server_json() {
+        let json = r#"{
+            "name": "model",
+            "creation_timestamp": 1700000000000,
+            "last_updated_timestamp": 1700000000000,
+            "latest_versions": []
+        }"#;
+        let model: RegisteredModel = serde_json::from_str(json).unwrap();
+        assert_eq!(model.name, "model");
+        assert!(model.tags.is_empty());
+        assert_eq!(model.description, None);
+    }
+
+    #[test]
+    fn model_version_builder_validation() {
+        ModelVersionBuilder::new("", "runs:/abc/model")
+            .expect_err("ModelVersionBuilderError: name cannot be empty");
+        ModelVersionBuilder::new("model", "")
+            .expect_err("ModelVersionBuilderError: source cannot be empty");
+    }
+
+    #[test]
+    fn model_version_stage_serializes_to_server_names() {
+        assert_eq!(
+            serde_json::to_string(&ModelVersionStage::Production).unwrap(),
+            "\"Production\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ModelVersionStage::None).unwrap(),
+            "\"None\""
+        );
+    }
+
+    #[test]
+    fn model_version_from_server_json() {
+        let json = r#"{
+            "name": "model",
+            "version": "3",
+            "creation_timestamp": 1700000000000,
+            "current_stage": "Staging",
+            "source": "runs:/abc/model",
+            "run_id": "abc",
+            "status": "READY"
+        }"#;
+        let version: ModelVersion = serde_json::from_str(json).unwrap();
+        assert_eq!(version.version, "3");
+        assert_eq!(version.current_stage, ModelVersionStage::Staging);
+        assert_eq!(version.status, Some(ModelVersionStatus::Ready));
+        assert!(version.tags.is_empty());
+    }
+}
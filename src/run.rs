@@ -0,0 +1,1030 @@
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{
+    CreateRunRequest, LogMetricRequest, LogParamRequest, MLFlowClient, SearchRunsRequest,
+    SharedClient, UpdateRunRequest,
+};
+use crate::config::default_client;
+use crate::downsample::{Downsampling, StepBuckets};
+use crate::entities::{LifecycleStage, Metric, Param, RunData, RunInfo, RunStatus, RunTag};
+use crate::filter::Filter;
+use crate::hyperparams::MlflowParams;
+use crate::names::generate_run_name;
+use crate::pagination::{Page, PageIterator};
+use crate::search::RunViewType;
+use crate::tags::{with_default_tags, MLFLOW_PARENT_RUN_ID, MLFLOW_RUN_NAME};
+use crate::termination::update_registered_run;
+use crate::validation::{validate_metric_key, validate_param, validate_run_tag, validate_tag_key};
+use crate::MLFlowError::{InvalidParam, InvalidState, RunBuilderError};
+use crate::MLFlowResult;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Milliseconds since the UNIX epoch, the unit MLflow uses for all timestamps.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn current_time_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Milliseconds since the UNIX epoch, from the JavaScript clock since `SystemTime` panics on
+/// `wasm32`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn current_time_millis() -> i64 {
+    js_sys::Date::now() as i64
+}
+
+/// How many times `max_points` step buckets a downsampled metric history is gathered in while
+/// its pages are still arriving.
+const DOWNSAMPLING_SLACK: usize = 4;
+
+pub trait RunIdentifier {
+    fn run_id(&self) -> String;
+}
+
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub(crate) info: RunInfo,
+    pub(crate) data: RunData,
+    pub(crate) client: SharedClient,
+}
+
+impl RunIdentifier for Run {
+    fn run_id(&self) -> String {
+        self.info.run_id.clone()
+    }
+}
+
+impl Run {
+    pub fn info(&self) -> &RunInfo {
+        &self.info
+    }
+
+    /// The run's data as of the last time it was fetched from the server.
+    pub fn data(&self) -> &RunData {
+        &self.data
+    }
+
+    pub fn status(&self) -> RunStatus {
+        self.info.status
+    }
+
+    pub fn start_time(&self) -> Option<i64> {
+        self.info.start_time
+    }
+
+    pub fn end_time(&self) -> Option<i64> {
+        self.info.end_time
+    }
+
+    /// The step `key` was last logged at, as of the last time the run was fetched.
+    pub fn latest_step(&self, key: impl AsRef<str>) -> Option<i64> {
+        self.data
+            .metrics
+            .iter()
+            .find(|m| m.key == key.as_ref())
+            .map(|m| m.step)
+    }
+
+    /// The highest step any metric was logged at, as of the last time the run was fetched. A
+    /// [resumed](Self::resume) training job continues after it.
+    pub fn last_step(&self) -> Option<i64> {
+        self.data.metrics.iter().map(|m| m.step).max()
+    }
+
+    /// Loads the run `run_id` and [reopens](Self::reopen) it if it ended, so a training job
+    /// restarted after preemption keeps logging to the same run and its metric curves continue
+    /// where they stopped. Use [`RunBuilder::resume_from`] to load it through another client.
+    ///
+    /// The run comes with its params and the latest value of each metric, while
+    /// [`last_step`](Self::last_step) and [`latest_checkpoint`](Self::latest_checkpoint) tell
+    /// where to pick up from.
+    ///
+    /// ```no_run
+    /// use mlflow_rs::Run;
+    ///
+    /// let run = Run::resume("1e8d8a6ab8c84b2e9f0e8b7c6d5a4f3e").unwrap();
+    /// let learning_rate: f64 = run.data().param("learning_rate").unwrap().parse().unwrap();
+    /// let first_step = run.last_step().map_or(0, |step| step + 1);
+    /// if let Some(checkpoint) = run.latest_checkpoint().unwrap() {
+    ///     run.download_artifact(&checkpoint.artifact_path, "./target/resume").unwrap();
+    /// }
+    /// for step in first_step..1000 {
+    ///     run.log_metric("loss", learning_rate / (step + 1) as f64, Some(step), None).unwrap();
+    /// }
+    /// ```
+    pub fn resume(run_id: impl AsRef<str>) -> MLFlowResult<Run> {
+        let mut run = RunLoader::default().load(run_id)?;
+        if run.info.status != RunStatus::Running {
+            run.reopen()?;
+        }
+        Ok(run)
+    }
+
+    /// Terminates the run with the given status, recording the current time as its end time.
+    pub fn finish(&mut self, status: RunStatus) -> MLFlowResult<()> {
+        self.update(None, Some(status), Some(current_time_millis()))
+    }
+
+    /// Terminates the run as `KILLED`, e.g. when a training job is cancelled.
+    pub fn kill(&mut self) -> MLFlowResult<()> {
+        self.finish(RunStatus::Killed)
+    }
+
+    /// Sets an ended run back to `RUNNING`, so a resumed training job can keep logging to it.
+    /// Its end time is replaced when it ends again.
+    pub fn reopen(&mut self) -> MLFlowResult<()> {
+        self.update(None, Some(RunStatus::Running), None)
+    }
+
+    /// Renames the run and/or changes its status and end time. Fields left as `None` keep
+    /// their current value on the server.
+    ///
+    /// Fails with [`InvalidState`](crate::MLFlowError::InvalidState) if the run can't go from
+    /// its current status to `status`, see [`RunStatus::can_transition_to`].
+    pub fn update(
+        &mut self,
+        name: Option<&str>,
+        status: Option<RunStatus>,
+        end_time: Option<i64>,
+    ) -> MLFlowResult<()> {
+        if let Some(status) = status {
+            if !self.info.status.can_transition_to(status) {
+                return Err(InvalidState(format!(
+                    "run {} cannot go from {:?} to {:?}",
+                    self.info.run_id, self.info.status, status
+                )));
+            }
+        }
+
+        let request = UpdateRunRequest {
+            run_id: self.info.run_id.clone(),
+            status,
+            end_time,
+            run_name: name.map(|n| n.to_string()),
+        };
+
+        let resp = self.client.update_run(request)?;
+        self.info = resp.run_info;
+        update_registered_run(&self.info);
+        if let Some(name) = name {
+            self.data.tags.retain(|t| t.key != MLFLOW_RUN_NAME);
+            self.data.tags.push(RunTag::from((MLFLOW_RUN_NAME, name)));
+        }
+        Ok(())
+    }
+
+    /// Marks the run as deleted. Deleted runs can be brought back with [`Run::restore`] until
+    /// the server garbage collects them.
+    pub fn delete(&mut self) -> MLFlowResult<()> {
+        self.client.delete_run(&self.info.run_id)?;
+        self.info.lifecycle_stage = Some(LifecycleStage::Deleted);
+        Ok(())
+    }
+
+    pub fn restore(&mut self) -> MLFlowResult<()> {
+        self.client.restore_run(&self.info.run_id)?;
+        self.info.lifecycle_stage = Some(LifecycleStage::Active);
+        Ok(())
+    }
+
+    /// Logs a single metric value against the run.
+    ///
+    /// `step` defaults to `0` and `timestamp` to the current time. NaN and infinite values are
+    /// accepted and sent using the encoding the tracking server understands.
+    pub fn log_metric(
+        &self,
+        key: impl AsRef<str>,
+        value: f64,
+        step: Option<i64>,
+        timestamp: Option<i64>,
+    ) -> MLFlowResult<()> {
+        validate_metric_key(key.as_ref())?;
+
+        let request = LogMetricRequest {
+            run_id: self.info.run_id.clone(),
+            metric: Metric::new(key, value, step, timestamp),
+        };
+
+        self.client.log_metric(request)
+    }
+
+    /// Logs a param against the run.
+    ///
+    /// Logging the same key again with a different value fails with
+    /// [`MLFlowError::ParamValueChanged`](crate::MLFlowError::ParamValueChanged).
+    pub fn log_param(&self, key: impl AsRef<str>, value: impl ToString) -> MLFlowResult<()> {
+        let param = Param {
+            key: key.as_ref().to_string(),
+            value: value.to_string(),
+        };
+        validate_param(&param)?;
+
+        let request = LogParamRequest {
+            run_id: self.info.run_id.clone(),
+            param,
+        };
+
+        self.client.log_param(request)
+    }
+
+    /// Logs metrics, params and tags in bulk through `runs/log-batch`.
+    ///
+    /// Batches larger than the server's per-request limits (1000 metrics, 100 params, 100 tags)
+    /// are split into several requests automatically.
+    pub fn log_batch(
+        &self,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<()> {
+        for metric in &metrics {
+            validate_metric_key(&metric.key)?;
+        }
+        for param in &params {
+            validate_param(param)?;
+        }
+        for tag in &tags {
+            validate_run_tag(tag)?;
+        }
+
+        self.client
+            .log_batch(&self.info.run_id, metrics, params, tags)
+    }
+
+    /// Logs several metrics at the same `step` and timestamp through `runs/log-batch`.
+    ///
+    /// ```no_run
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// run.log_metrics([("loss", 0.25), ("accuracy", 0.91)], Some(3)).unwrap();
+    /// ```
+    pub fn log_metrics<K: AsRef<str>>(
+        &self,
+        metrics: impl IntoIterator<Item = (K, f64)>,
+        step: Option<i64>,
+    ) -> MLFlowResult<()> {
+        let timestamp = current_time_millis();
+        let metrics = metrics
+            .into_iter()
+            .map(|(key, value)| Metric::new(key, value, step, Some(timestamp)))
+            .collect();
+        self.log_batch(metrics, vec![], vec![])
+    }
+
+    /// Logs the fields of `params`, any value serializing to a map such as a config struct or
+    /// a `HashMap`, through `runs/log-batch`. Nested maps are flattened into dotted keys, e.g.
+    /// `optimizer.lr`; sequences are logged as their JSON text and `None` fields are skipped.
+    ///
+    /// ```no_run
+    /// #[derive(serde::Serialize)]
+    /// struct Optimizer {
+    ///     name: String,
+    ///     lr: f64,
+    /// }
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct Config {
+    ///     epochs: u32,
+    ///     optimizer: Optimizer,
+    /// }
+    ///
+    /// # let run = mlflow_rs::RunBuilder::new("0").unwrap().build().unwrap();
+    /// let config = Config {
+    ///     epochs: 10,
+    ///     optimizer: Optimizer { name: "adam".to_string(), lr: 0.001 },
+    /// };
+    /// // logs epochs, optimizer.name and optimizer.lr
+    /// run.log_params(&config).unwrap();
+    /// ```
+    pub fn log_params(&self, params: &impl Serialize) -> MLFlowResult<()> {
+        let value = serde_json::to_value(params).map_err(|e| InvalidParam(e.to_string()))?;
+        let Value::Object(fields) = value else {
+            return Err(InvalidParam(
+                "params must serialize to a map of keys to values".to_string(),
+            ));
+        };
+        let mut params = vec![];
+        flatten_params("", fields, &mut params);
+        self.log_batch(vec![], params, vec![])
+    }
+
+    /// Logs the params of a hyperparameter struct, usually one deriving
+    /// [`MlflowParams`](crate::MlflowParams), through `runs/log-batch`.
+    pub fn log_hyperparams(&self, params: &impl MlflowParams) -> MLFlowResult<()> {
+        self.log_batch(vec![], params.to_params(), vec![])
+    }
+
+    /// Every value logged for the metric `key`, following the server's pagination for long
+    /// histories. [`RunData`] only holds the latest value of each metric.
+    pub fn get_metric_history(&self, key: impl AsRef<str>) -> MLFlowResult<Vec<Metric>> {
+        validate_metric_key(key.as_ref())?;
+
+        let client = self.client.clone();
+        let (run_id, key) = (self.info.run_id.clone(), key.as_ref().to_string());
+        PageIterator::new(move |page_token| {
+            client
+                .get_metric_history(&run_id, &key, page_token)
+                .map(|r| Page::new(r.metrics, r.next_page_token))
+        })
+        .collect()
+    }
+
+    /// The history of the metric `key` reduced to at most `max_points` values with `method`, for
+    /// plotting runs too long to hold every value. The server's pages are still all fetched, but
+    /// only a few values per step range are held as they arrive, so memory stays proportional to
+    /// `max_points`.
+    pub fn get_metric_history_downsampled(
+        &self,
+        key: impl AsRef<str>,
+        max_points: usize,
+        method: Downsampling,
+    ) -> MLFlowResult<Vec<Metric>> {
+        validate_metric_key(key.as_ref())?;
+
+        let client = self.client.clone();
+        let (run_id, key) = (self.info.run_id.clone(), key.as_ref().to_string());
+        let pages = PageIterator::new(move |page_token| {
+            client
+                .get_metric_history(&run_id, &key, page_token)
+                .map(|r| Page::new(r.metrics, r.next_page_token))
+        });
+        // gathering a few times the target lets the method still choose between neighbouring
+        // values, and it only ever runs once, on points spread evenly over the steps
+        let mut buckets = StepBuckets::new(max_points.saturating_mul(DOWNSAMPLING_SLACK));
+        for metric in pages {
+            buckets.push(metric?);
+        }
+        Ok(method.apply(buckets.into_sorted(), max_points))
+    }
+
+    /// Sets a tag on the run, replacing any existing value. See [`crate::tags`] for the keys
+    /// MLflow reserves.
+    pub fn set_tag(&self, key: impl AsRef<str>, value: impl ToString) -> MLFlowResult<()> {
+        let tag = RunTag {
+            key: key.as_ref().to_string(),
+            value: value.to_string(),
+        };
+        validate_run_tag(&tag)?;
+        self.client.set_tag(&self.info.run_id, &tag)
+    }
+
+    pub fn delete_tag(&self, key: impl AsRef<str>) -> MLFlowResult<()> {
+        validate_tag_key(key.as_ref())?;
+
+        self.client.delete_tag(&self.info.run_id, key.as_ref())
+    }
+}
+
+/// Appends the leaves of `fields` to `params`, keyed by their dotted path below `prefix`.
+fn flatten_params(prefix: &str, fields: Map<String, Value>, params: &mut Vec<Param>) {
+    for (key, value) in fields {
+        let key = match prefix {
+            "" => key,
+            prefix => format!("{}.{}", prefix, key),
+        };
+        let value = match value {
+            Value::Null => continue,
+            Value::Object(fields) => {
+                flatten_params(&key, fields, params);
+                continue;
+            }
+            Value::String(value) => value,
+            value => value.to_string(),
+        };
+        params.push(Param { key, value });
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RunBuilder {
+    experiment_id: String,
+    run_name: Option<String>,
+    start_time: Option<i64>,
+    tags: Vec<RunTag>,
+    source_context: bool,
+    resume_from: Option<String>,
+    client: SharedClient,
+}
+
+impl RunBuilder {
+    pub fn new(experiment_id: impl AsRef<str>) -> MLFlowResult<RunBuilder> {
+        if experiment_id.as_ref().is_empty() {
+            return Err(RunBuilderError("experiment_id cannot be empty".to_string()));
+        }
+
+        Ok(RunBuilder::with_shared_client(
+            experiment_id.as_ref(),
+            default_client()?,
+        ))
+    }
+
+    /// A builder for a run in `experiment_id`, created through `client`.
+    pub(crate) fn with_shared_client(experiment_id: &str, client: SharedClient) -> RunBuilder {
+        RunBuilder {
+            experiment_id: experiment_id.to_string(),
+            run_name: None,
+            start_time: None,
+            tags: vec![],
+            source_context: true,
+            resume_from: None,
+            client,
+        }
+    }
+
+    pub fn with_run_name(mut self, run_name: impl AsRef<str>) -> Self {
+        self.run_name = Some(run_name.as_ref().to_string());
+        self
+    }
+
+    pub fn with_start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<RunTag>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<impl Into<RunTag>>) -> Self {
+        self.tags = tags.into_iter().map(|t| t.into()).collect();
+        self
+    }
+
+    /// Nests the run under `parent_run_id` by setting the `mlflow.parentRunId` tag.
+    pub fn with_parent(mut self, parent_run_id: impl AsRef<str>) -> Self {
+        self.tags.retain(|t| t.key != MLFLOW_PARENT_RUN_ID);
+        self.tags
+            .push(RunTag::from((MLFLOW_PARENT_RUN_ID, parent_run_id.as_ref())));
+        self
+    }
+
+    /// Whether [`build`](Self::build) captures the source context of the process as tags.
+    /// Enabled by default.
+    pub fn with_source_context(mut self, enabled: bool) -> Self {
+        self.source_context = enabled;
+        self
+    }
+
+    /// Makes [`build`](Self::build) resume the existing run `run_id` instead of creating one,
+    /// like [`Run::resume`]. The run must belong to the builder's experiment. A run name and
+    /// tags given to the builder are applied to it, while the start time and source context
+    /// are left as they were.
+    pub fn resume_from(mut self, run_id: impl AsRef<str>) -> Self {
+        self.resume_from = Some(run_id.as_ref().to_string());
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Creates the run. Unless disabled with [`with_source_context`](Self::with_source_context),
+    /// the `mlflow.source.*` tags (executable, OS, git commit, branch, dirty flag and remote) and
+    /// `mlflow.user` are filled in from the environment where they weren't set explicitly.
+    ///
+    /// A run without a name, given here or as the `mlflow.runName` tag, gets a generated one
+    /// such as `dashing-owl-123`.
+    pub fn build(mut self) -> MLFlowResult<Run> {
+        if let Some(run_id) = self.resume_from.take() {
+            return self.resume(&run_id);
+        }
+
+        let mut tags = if self.source_context {
+            with_default_tags(self.tags)
+        } else {
+            self.tags
+        };
+        let run_name = match self.run_name {
+            Some(run_name) => run_name,
+            None => tags
+                .iter()
+                .rev()
+                .find(|t| t.key == MLFLOW_RUN_NAME)
+                .map(|t| t.value.clone())
+                .unwrap_or_else(generate_run_name),
+        };
+        tags.retain(|t| t.key != MLFLOW_RUN_NAME);
+        tags.push(RunTag::from((MLFLOW_RUN_NAME, run_name.as_str())));
+        for tag in &tags {
+            validate_run_tag(tag)?;
+        }
+        let request = CreateRunRequest {
+            experiment_id: self.experiment_id,
+            run_name: Some(run_name),
+            start_time: self.start_time.unwrap_or_else(current_time_millis),
+            tags,
+        };
+
+        let resp = self.client.create_run(request)?;
+        Ok(Run {
+            info: resp.run.info,
+            data: resp.run.data,
+            client: self.client,
+        })
+    }
+
+    fn resume(self, run_id: &str) -> MLFlowResult<Run> {
+        let resp = self.client.get_run(run_id)?;
+        let mut run = Run {
+            info: resp.run.info,
+            data: resp.run.data,
+            client: self.client,
+        };
+        if run.info.experiment_id != self.experiment_id {
+            return Err(RunBuilderError(format!(
+                "run {} belongs to experiment {}, not {}",
+                run_id, run.info.experiment_id, self.experiment_id
+            )));
+        }
+
+        if !self.tags.is_empty() {
+            run.log_batch(vec![], vec![], self.tags.clone())?;
+            for tag in self.tags {
+                run.data.tags.retain(|t| t.key != tag.key);
+                run.data.tags.push(tag);
+            }
+        }
+        let status = (run.info.status != RunStatus::Running).then_some(RunStatus::Running);
+        if self.run_name.is_some() || status.is_some() {
+            run.update(self.run_name.as_deref(), status, None)?;
+        }
+        Ok(run)
+    }
+}
+
+/// Fetches an existing run, e.g. to read the params and latest metrics of a previous run
+/// before resuming training.
+#[derive(Default)]
+pub struct RunLoader {
+    client: Option<SharedClient>,
+}
+
+impl RunLoader {
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn load(self, run_id: impl AsRef<str>) -> MLFlowResult<Run> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+
+        let resp = client.get_run(run_id.as_ref())?;
+        Ok(Run {
+            info: resp.run.info,
+            data: resp.run.data,
+            client,
+        })
+    }
+
+    /// Finds the most recently started run named `run_name` in the experiment. Run names aren't
+    /// unique, so earlier runs with the same name are ignored.
+    pub fn find_by_name(
+        self,
+        experiment_id: impl AsRef<str>,
+        run_name: impl AsRef<str>,
+    ) -> MLFlowResult<Option<Run>> {
+        let client = match self.client {
+            Some(client) => client,
+            None => default_client()?,
+        };
+
+        let request = SearchRunsRequest {
+            experiment_ids: vec![experiment_id.as_ref().to_string()],
+            filter: Some(
+                Filter::attribute("run_name")
+                    .eq(run_name.as_ref())
+                    .build()?,
+            ),
+            run_view_type: RunViewType::ActiveOnly,
+            max_results: Some(1),
+            order_by: vec!["attributes.start_time DESC".to_string()],
+            page_token: None,
+        };
+        let resp = client.search_runs(&request)?;
+        Ok(resp.runs.into_iter().next().map(|r| Run {
+            info: r.info,
+            data: r.data,
+            client,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_builder_new_empty_experiment_id() {
+        RunBuilder::new("").expect_err("RunBuilderError: experiment_id cannot be empty");
+    }
+
+    #[test]
+    fn run_builder_with_tags() {
+        let builder = RunBuilder::new("1")
+            .unwrap()
+            .with_run_name("test_run")
+            .with_tag(("key", "value"))
+            .with_tag(("key2", "value2"));
+
+        assert_eq!(builder.experiment_id, "1");
+        assert_eq!(builder.run_name, Some("test_run".to_string()));
+        assert_eq!(builder.tags.len(), 2);
+    }
+
+    #[test]
+    fn run_builder_with_parent() {
+        let builder = RunBuilder::new("1")
+            .unwrap()
+            .with_parent("first")
+            .with_parent("second");
+
+        assert_eq!(builder.tags.len(), 1);
+        assert_eq!(builder.tags[0].key, MLFLOW_PARENT_RUN_ID);
+        assert_eq!(builder.tags[0].value, "second");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn run_builder_source_context() {
+        use crate::tags::{MLFLOW_SOURCE_NAME, MLFLOW_SOURCE_OS};
+        use crate::MockMLFlowClient;
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_tag((MLFLOW_SOURCE_NAME, "train"))
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        assert_eq!(run.data().tag(MLFLOW_SOURCE_NAME), Some("train"));
+        assert_eq!(run.data().tag(MLFLOW_SOURCE_OS), Some(std::env::consts::OS));
+
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_source_context(false)
+            .with_mock_client(mock)
+            .build()
+            .unwrap();
+        assert_eq!(run.data().tag(MLFLOW_SOURCE_NAME), None);
+        assert_eq!(run.data().tag(MLFLOW_SOURCE_OS), None);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn run_names() {
+        use crate::MockMLFlowClient;
+
+        let mock = MockMLFlowClient::default();
+        let build = |builder: RunBuilder| {
+            builder
+                .with_source_context(false)
+                .with_mock_client(mock.clone())
+                .build()
+                .unwrap()
+        };
+        let generated = build(RunBuilder::new("0").unwrap());
+        let name = generated.info().run_name.clone().unwrap();
+        assert_eq!(name.split('-').count(), 3);
+        assert_eq!(generated.data().tag(MLFLOW_RUN_NAME), Some(name.as_str()));
+
+        let tagged = build(
+            RunBuilder::new("0")
+                .unwrap()
+                .with_tag((MLFLOW_RUN_NAME, "baseline")),
+        );
+        assert_eq!(tagged.info().run_name.as_deref(), Some("baseline"));
+        let rerun = build(
+            RunBuilder::new("0")
+                .unwrap()
+                .with_run_name("baseline")
+                .with_start_time(current_time_millis() + 1000),
+        );
+
+        let find = |name: &str| {
+            RunLoader::default()
+                .with_mock_client(mock.clone())
+                .find_by_name("0", name)
+                .unwrap()
+                .map(|r| r.run_id())
+        };
+        assert_eq!(find("baseline"), Some(rerun.run_id()));
+        assert_eq!(find(&name), Some(generated.run_id()));
+        assert_eq!(find("missing"), None);
+    }
+
+    fn test_run() -> Run {
+        Run {
+            info: serde_json::from_str(
+                r#"{"run_id": "abc", "experiment_id": "1", "status": "RUNNING"}"#,
+            )
+            .unwrap(),
+            data: RunData::default(),
+            client: SharedClient::default(),
+        }
+    }
+
+    #[test]
+    fn log_metric_empty_key() {
+        test_run()
+            .log_metric("", 1.0, None, None)
+            .expect_err("ValidationError: metric key cannot be empty");
+    }
+
+    #[test]
+    fn log_param_empty_key() {
+        test_run()
+            .log_param("", 1)
+            .expect_err("ValidationError: param key cannot be empty");
+    }
+
+    #[test]
+    fn log_batch_empty_keys() {
+        test_run()
+            .log_batch(vec![Metric::new("", 1.0, None, None)], vec![], vec![])
+            .expect_err("ValidationError: metric key cannot be empty");
+        test_run()
+            .log_batch(vec![], vec![Param::from(("", "v"))], vec![])
+            .expect_err("ValidationError: param key cannot be empty");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn log_metric_and_param_maps() {
+        use crate::MockMLFlowClient;
+        use std::collections::HashMap;
+
+        #[derive(Serialize)]
+        struct Optimizer {
+            name: &'static str,
+            lr: f64,
+            betas: Vec<f64>,
+            weight_decay: Option<f64>,
+        }
+
+        #[derive(Serialize)]
+        struct Config {
+            epochs: u32,
+            optimizer: Optimizer,
+        }
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        run.log_metrics([("loss", 0.25), ("accuracy", 0.9)], Some(2))
+            .unwrap();
+        run.log_params(&Config {
+            epochs: 10,
+            optimizer: Optimizer {
+                name: "adam",
+                lr: 0.001,
+                betas: vec![0.9, 0.999],
+                weight_decay: None,
+            },
+        })
+        .unwrap();
+        run.log_params(&HashMap::from([("seed", 42)])).unwrap();
+        run.log_params(&0.5).expect_err("params must be a map");
+
+        let data = RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(run.run_id())
+            .unwrap()
+            .data;
+        assert_eq!(data.metric("accuracy"), Some(0.9));
+        assert_eq!(mock.metric_history(&run.run_id(), "loss")[0].step, 2);
+        let mut params: Vec<(String, String)> =
+            data.params.into_iter().map(|p| (p.key, p.value)).collect();
+        params.sort();
+        assert_eq!(
+            params,
+            vec![
+                ("epochs".to_string(), "10".to_string()),
+                ("optimizer.betas".to_string(), "[0.9,0.999]".to_string()),
+                ("optimizer.lr".to_string(), "0.001".to_string()),
+                ("optimizer.name".to_string(), "adam".to_string()),
+                ("seed".to_string(), "42".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn metric_history_empty_key() {
+        test_run()
+            .get_metric_history("")
+            .expect_err("ValidationError: metric key cannot be empty");
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn downsampled_metric_history() {
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(crate::MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let metrics: Vec<Metric> = (0..5000)
+            .map(|step| Metric {
+                key: "loss".to_string(),
+                value: if step == 2500 { 100.0 } else { 1.0 },
+                timestamp: step,
+                step,
+            })
+            .collect();
+        for chunk in metrics.chunks(1000) {
+            run.log_batch(chunk.to_vec(), vec![], vec![]).unwrap();
+        }
+
+        let history = run
+            .get_metric_history_downsampled("loss", 50, Downsampling::Lttb)
+            .unwrap();
+        assert_eq!(history.len(), 50);
+        assert_eq!(history[0].step, 0);
+        assert_eq!(history[49].step, 4999);
+        assert!(history.iter().any(|m| m.value == 100.0), "keeps the spike");
+        let strided = run
+            .get_metric_history_downsampled("loss", 50, Downsampling::Stride)
+            .unwrap();
+        assert_eq!(strided.len(), 50);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn downsampled_metric_history_is_spread_over_the_steps() {
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(crate::MockMLFlowClient::default())
+            .build()
+            .unwrap();
+        let metrics: Vec<Metric> = (0..20_000)
+            .map(|step| Metric {
+                key: "loss".to_string(),
+                value: (step as f64 / 100.0).sin(),
+                timestamp: step,
+                step,
+            })
+            .collect();
+        for chunk in metrics.chunks(1000) {
+            run.log_batch(chunk.to_vec(), vec![], vec![]).unwrap();
+        }
+
+        for method in [Downsampling::Lttb, Downsampling::Stride] {
+            let history = run
+                .get_metric_history_downsampled("loss", 100, method)
+                .unwrap();
+            assert_eq!(history.len(), 100);
+            // about ten points in each tenth of the run
+            for tenth in 0..10 {
+                let steps = tenth * 2000..(tenth + 1) * 2000;
+                let points = history.iter().filter(|m| steps.contains(&m.step)).count();
+                assert!(
+                    (7..=13).contains(&points),
+                    "{:?}: {} points in steps {:?}",
+                    method,
+                    points,
+                    steps
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn status_transitions() {
+        use RunStatus::*;
+
+        assert!(Scheduled.can_transition_to(Running));
+        assert!(Running.can_transition_to(Killed));
+        assert!(Finished.can_transition_to(Running));
+        assert!(Finished.can_transition_to(Finished));
+        assert!(!Finished.can_transition_to(Failed));
+        assert!(!Running.can_transition_to(Scheduled));
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn kill_and_reopen() {
+        let mock = crate::MockMLFlowClient::default();
+        let mut run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .start()
+            .unwrap();
+        run.kill().unwrap();
+        assert_eq!(run.status(), RunStatus::Killed);
+        let err = run
+            .finish(RunStatus::Finished)
+            .expect_err("a killed run must be reopened first");
+        assert!(matches!(err, InvalidState(_)));
+
+        run.reopen().unwrap();
+        assert_eq!(run.status(), RunStatus::Running);
+        run.log_metric("loss", 0.1, Some(10), None).unwrap();
+        let run_id = run.run_id();
+        drop(run);
+        let loaded = RunLoader::default()
+            .with_mock_client(mock)
+            .load(&run_id)
+            .unwrap();
+        assert_eq!(
+            loaded.status(),
+            RunStatus::Finished,
+            "the guard ends the reopened run"
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn resumes_from_the_latest_step_and_checkpoint() {
+        let mock = crate::MockMLFlowClient::default();
+        let mut run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        run.log_param("lr", 0.01).unwrap();
+        for step in 0..5 {
+            run.log_metric("loss", 1.0 / (step + 1) as f64, Some(step), None)
+                .unwrap();
+        }
+        run.log_metric("val_loss", 0.3, Some(2), None).unwrap();
+        run.log_checkpoint(2, &b"weights"[..]).unwrap();
+        run.log_checkpoint(4, &b"weights"[..]).unwrap();
+        run.kill().unwrap();
+
+        let err = RunBuilder::new("1")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .resume_from(run.run_id())
+            .build()
+            .expect_err("the run is in experiment 0");
+        assert!(matches!(err, RunBuilderError(_)));
+
+        let resumed = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .with_tag(("attempt", "2"))
+            .resume_from(run.run_id())
+            .start()
+            .unwrap();
+        assert_eq!(resumed.run_id(), run.run_id());
+        assert_eq!(resumed.status(), RunStatus::Running);
+        assert_eq!(resumed.data().param("lr"), Some("0.01"));
+        assert_eq!(resumed.data().tag("attempt"), Some("2"));
+        assert_eq!(resumed.latest_step("val_loss"), Some(2));
+        assert_eq!(resumed.last_step(), Some(4));
+        assert_eq!(
+            resumed.latest_checkpoint().unwrap().unwrap().artifact_path,
+            "checkpoints/step=4/checkpoint"
+        );
+
+        resumed.log_metric("loss", 0.1, Some(5), None).unwrap();
+        drop(resumed);
+        assert_eq!(run.get_metric_history("loss").unwrap().len(), 6);
+        assert_eq!(
+            mock.run_info(&run.run_id()).unwrap().status,
+            RunStatus::Finished
+        );
+    }
+}
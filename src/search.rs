@@ -0,0 +1,601 @@
+//! Searching the tracking server for experiments and runs, and the model registry for
+//! registered models and model versions.
+
+#[cfg(feature = "async")]
+use crate::async_client::{AsyncMLFlowClient, MLFlowAsyncRestClient};
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+#[cfg(feature = "async")]
+use crate::client::{ExperimentInfo, RunResponse};
+use crate::client::{
+    MLFlowClient, SearchExperimentsRequest, SearchModelVersionsRequest,
+    SearchRegisteredModelsRequest, SearchRunsRequest, SharedClient,
+};
+use crate::config::default_client;
+use crate::experiment::Experiment;
+use crate::filter::Filter;
+#[cfg(feature = "async")]
+use crate::pagination::PageStream;
+use crate::pagination::{Page, PageIterator};
+use crate::registry::{ModelVersion, RegisteredModel};
+use crate::run::Run;
+use crate::tags::MLFLOW_PARENT_RUN_ID;
+use crate::MLFlowError::SearchError;
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Which experiments or runs a search considers, based on their lifecycle stage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RunViewType {
+    #[default]
+    ActiveOnly,
+    DeletedOnly,
+    All,
+}
+
+/// Builds an `experiments/search` query.
+///
+/// ```no_run
+/// use mlflow_rs::ExperimentSearch;
+///
+/// let experiments = ExperimentSearch::default()
+///     .with_filter("name LIKE 'nlp-%'")
+///     .with_order_by(vec!["last_update_time DESC"])
+///     .search()
+///     .collect::<Result<Vec<_>, _>>();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ExperimentSearch {
+    filter: Option<String>,
+    view_type: RunViewType,
+    max_results: Option<i64>,
+    order_by: Vec<String>,
+    client: Option<SharedClient>,
+}
+
+impl ExperimentSearch {
+    /// Restricts results using the MLflow search grammar, e.g. `tags.team = 'nlp'`.
+    pub fn with_filter(mut self, filter: impl AsRef<str>) -> Self {
+        self.filter = Some(filter.as_ref().to_string());
+        self
+    }
+
+    pub fn with_view_type(mut self, view_type: RunViewType) -> Self {
+        self.view_type = view_type;
+        self
+    }
+
+    /// Number of experiments fetched per page. The iterator keeps requesting pages until the
+    /// server reports no more results, so use [`Iterator::take`] to bound the total.
+    pub fn with_max_results(mut self, max_results: i64) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Orders results, e.g. `name ASC` or `creation_time DESC`.
+    pub fn with_order_by(mut self, order_by: Vec<impl AsRef<str>>) -> Self {
+        self.order_by = order_by.iter().map(|o| o.as_ref().to_string()).collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    fn request(&self) -> SearchExperimentsRequest {
+        SearchExperimentsRequest {
+            filter: self.filter.clone(),
+            view_type: self.view_type,
+            max_results: self.max_results,
+            order_by: self.order_by.clone(),
+            page_token: None,
+        }
+    }
+
+    /// Returns a lazy iterator over every matching experiment. Pages are fetched on demand.
+    pub fn search(self) -> ExperimentSearchIter {
+        let mut request = self.request();
+        let client = match self.client.map(Ok).unwrap_or_else(default_client) {
+            Ok(client) => client,
+            Err(e) => return PageIterator::new(move |_| Err(e.clone())),
+        };
+        PageIterator::new(move |page_token| {
+            request.page_token = page_token.map(str::to_string);
+            client.search_experiments(&request).map(|resp| {
+                Page::new(
+                    resp.experiments
+                        .into_iter()
+                        .map(|e| Experiment::from_info(e, client.clone()))
+                        .collect(),
+                    resp.next_page_token,
+                )
+            })
+        })
+    }
+
+    /// Streams every matching experiment from the async client, fetching pages on demand.
+    #[cfg(feature = "async")]
+    pub fn search_stream(self, client: MLFlowAsyncRestClient) -> PageStream<ExperimentInfo> {
+        let request = self.request();
+        PageStream::new(move |page_token| {
+            let request = SearchExperimentsRequest {
+                page_token,
+                ..request.clone()
+            };
+            let client = client.clone();
+            async move {
+                client
+                    .search_experiments(&request)
+                    .await
+                    .map(|resp| Page::new(resp.experiments, resp.next_page_token))
+            }
+        })
+    }
+}
+
+/// Iterator over the results of an [`ExperimentSearch`], transparently following `page_token`s.
+///
+/// A failed request is yielded as an error and ends the iteration.
+pub type ExperimentSearchIter = PageIterator<Experiment>;
+
+/// Builds a `runs/search` query.
+///
+/// ```no_run
+/// use mlflow_rs::RunSearch;
+///
+/// let best = RunSearch::new(vec!["1"])
+///     .unwrap()
+///     .with_filter("metrics.rmse < 0.5")
+///     .with_order_by(vec!["metrics.rmse ASC"])
+///     .search()
+///     .next();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RunSearch {
+    experiment_ids: Vec<String>,
+    filter: Option<String>,
+    run_view_type: RunViewType,
+    max_results: Option<i32>,
+    order_by: Vec<String>,
+    client: SharedClient,
+}
+
+impl RunSearch {
+    pub fn new(experiment_ids: Vec<impl AsRef<str>>) -> MLFlowResult<RunSearch> {
+        if experiment_ids.is_empty() {
+            return Err(SearchError(
+                "at least one experiment_id is required".to_string(),
+            ));
+        }
+
+        Ok(RunSearch::with_shared_client(
+            experiment_ids
+                .iter()
+                .map(|id| id.as_ref().to_string())
+                .collect(),
+            default_client()?,
+        ))
+    }
+
+    /// A search of `experiment_ids` sent through `client`.
+    pub(crate) fn with_shared_client(
+        experiment_ids: Vec<String>,
+        client: SharedClient,
+    ) -> RunSearch {
+        RunSearch {
+            experiment_ids,
+            filter: None,
+            run_view_type: RunViewType::default(),
+            max_results: None,
+            order_by: vec![],
+            client,
+        }
+    }
+
+    /// Restricts results using the MLflow search grammar, e.g. `metrics.rmse < 0.5`.
+    /// [`Filter`](crate::Filter) builds and validates these expressions.
+    pub fn with_filter(mut self, filter: impl AsRef<str>) -> Self {
+        self.filter = Some(filter.as_ref().to_string());
+        self
+    }
+
+    pub fn with_run_view_type(mut self, run_view_type: RunViewType) -> Self {
+        self.run_view_type = run_view_type;
+        self
+    }
+
+    /// Number of runs fetched per page. The iterator keeps requesting pages until the server
+    /// reports no more results, so use [`Iterator::take`] to bound the total.
+    pub fn with_max_results(mut self, max_results: i32) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Orders results, e.g. `metrics.rmse ASC` or `attributes.start_time DESC`.
+    pub fn with_order_by(mut self, order_by: Vec<impl AsRef<str>>) -> Self {
+        self.order_by = order_by.iter().map(|o| o.as_ref().to_string()).collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    fn request(&self) -> SearchRunsRequest {
+        SearchRunsRequest {
+            experiment_ids: self.experiment_ids.clone(),
+            filter: self.filter.clone(),
+            run_view_type: self.run_view_type,
+            max_results: self.max_results,
+            order_by: self.order_by.clone(),
+            page_token: None,
+        }
+    }
+
+    /// Returns a lazy iterator over every matching run. Pages are fetched on demand.
+    pub fn search(self) -> RunSearchIter {
+        let mut request = self.request();
+        let client = self.client;
+        PageIterator::new(move |page_token| {
+            request.page_token = page_token.map(str::to_string);
+            client.search_runs(&request).map(|resp| {
+                Page::new(
+                    resp.runs
+                        .into_iter()
+                        .map(|r| Run {
+                            info: r.info,
+                            data: r.data,
+                            client: client.clone(),
+                        })
+                        .collect(),
+                    resp.next_page_token,
+                )
+            })
+        })
+    }
+
+    /// Streams every matching run from the async client, fetching pages on demand.
+    #[cfg(feature = "async")]
+    pub fn search_stream(self, client: MLFlowAsyncRestClient) -> PageStream<RunResponse> {
+        let request = self.request();
+        PageStream::new(move |page_token| {
+            let request = SearchRunsRequest {
+                page_token,
+                ..request.clone()
+            };
+            let client = client.clone();
+            async move {
+                client
+                    .search_runs(&request)
+                    .await
+                    .map(|resp| Page::new(resp.runs, resp.next_page_token))
+            }
+        })
+    }
+}
+
+/// Iterator over the results of a [`RunSearch`], transparently following `page_token`s.
+///
+/// A failed request is yielded as an error and ends the iteration.
+pub type RunSearchIter = PageIterator<Run>;
+
+/// Builds a `registered-models/search` query.
+///
+/// ```no_run
+/// use mlflow_rs::{ModelVersionSearch, ModelVersionStage, RegisteredModelSearch};
+///
+/// // every Production version of the models tagged team=fraud
+/// for model in RegisteredModelSearch::default()
+///     .with_filter("tags.team = 'fraud'")
+///     .search()
+/// {
+///     let production = ModelVersionSearch::default()
+///         .with_filter(format!("name = '{}'", model.unwrap().name))
+///         .search()
+///         .filter(|v| {
+///             v.as_ref()
+///                 .map_or(true, |v| v.current_stage == ModelVersionStage::Production)
+///         })
+///         .collect::<Result<Vec<_>, _>>();
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RegisteredModelSearch {
+    filter: Option<String>,
+    max_results: Option<i64>,
+    order_by: Vec<String>,
+    client: Option<SharedClient>,
+}
+
+impl RegisteredModelSearch {
+    /// Restricts results by `name` or tags, e.g. `name LIKE 'fraud-%'` or `tags.team = 'fraud'`.
+    pub fn with_filter(mut self, filter: impl AsRef<str>) -> Self {
+        self.filter = Some(filter.as_ref().to_string());
+        self
+    }
+
+    /// Number of models fetched per page. The iterator keeps requesting pages until the server
+    /// reports no more results, so use [`Iterator::take`] to bound the total.
+    pub fn with_max_results(mut self, max_results: i64) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Orders results, e.g. `name ASC` or `last_updated_timestamp DESC`.
+    pub fn with_order_by(mut self, order_by: Vec<impl AsRef<str>>) -> Self {
+        self.order_by = order_by.iter().map(|o| o.as_ref().to_string()).collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    fn request(&self) -> SearchRegisteredModelsRequest {
+        SearchRegisteredModelsRequest {
+            filter: self.filter.clone(),
+            max_results: self.max_results,
+            order_by: self.order_by.clone(),
+            page_token: None,
+        }
+    }
+
+    /// Returns a lazy iterator over every matching registered model. Pages are fetched on
+    /// demand.
+    pub fn search(self) -> PageIterator<RegisteredModel> {
+        let mut request = self.request();
+        let client = match self.client.map(Ok).unwrap_or_else(default_client) {
+            Ok(client) => client,
+            Err(e) => return PageIterator::new(move |_| Err(e.clone())),
+        };
+        PageIterator::new(move |page_token| {
+            request.page_token = page_token.map(str::to_string);
+            client.search_registered_models(&request).map(|resp| {
+                Page::new(
+                    resp.registered_models
+                        .into_iter()
+                        .map(|m| m.with_client(client.clone()))
+                        .collect(),
+                    resp.next_page_token,
+                )
+            })
+        })
+    }
+}
+
+/// Builds a `model-versions/search` query.
+///
+/// ```no_run
+/// use mlflow_rs::ModelVersionSearch;
+///
+/// let versions = ModelVersionSearch::default()
+///     .with_filter("name = 'fraud-detector' AND tags.validated = 'true'")
+///     .search()
+///     .collect::<Result<Vec<_>, _>>();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ModelVersionSearch {
+    filter: Option<String>,
+    max_results: Option<i64>,
+    order_by: Vec<String>,
+    client: Option<SharedClient>,
+}
+
+impl ModelVersionSearch {
+    /// Restricts results by `name`, `run_id`, `source_path` or tags, e.g.
+    /// `name = 'fraud-detector'` or `run_id = '<run id>'`.
+    pub fn with_filter(mut self, filter: impl AsRef<str>) -> Self {
+        self.filter = Some(filter.as_ref().to_string());
+        self
+    }
+
+    /// Number of versions fetched per page. The iterator keeps requesting pages until the
+    /// server reports no more results, so use [`Iterator::take`] to bound the total.
+    pub fn with_max_results(mut self, max_results: i64) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Orders results, e.g. `version_number DESC`. Newer servers only support ordering model
+    /// versions.
+    pub fn with_order_by(mut self, order_by: Vec<impl AsRef<str>>) -> Self {
+        self.order_by = order_by.iter().map(|o| o.as_ref().to_string()).collect();
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    fn request(&self) -> SearchModelVersionsRequest {
+        SearchModelVersionsRequest {
+            filter: self.filter.clone(),
+            max_results: self.max_results,
+            order_by: self.order_by.clone(),
+            page_token: None,
+        }
+    }
+
+    /// Returns a lazy iterator over every matching model version. Pages are fetched on demand.
+    pub fn search(self) -> PageIterator<ModelVersion> {
+        let mut request = self.request();
+        let client = match self.client.map(Ok).unwrap_or_else(default_client) {
+            Ok(client) => client,
+            Err(e) => return PageIterator::new(move |_| Err(e.clone())),
+        };
+        PageIterator::new(move |page_token| {
+            request.page_token = page_token.map(str::to_string);
+            client.search_model_versions(&request).map(|resp| {
+                Page::new(
+                    resp.model_versions
+                        .into_iter()
+                        .map(|v| v.with_client(client.clone()))
+                        .collect(),
+                    resp.next_page_token,
+                )
+            })
+        })
+    }
+}
+
+impl Run {
+    /// Runs created with [`RunBuilder::with_parent`](crate::RunBuilder::with_parent) pointing at
+    /// this run, in the order the server returns them.
+    pub fn children(&self) -> MLFlowResult<Vec<Run>> {
+        let search = RunSearch {
+            experiment_ids: vec![self.info.experiment_id.clone()],
+            filter: None,
+            run_view_type: RunViewType::default(),
+            max_results: None,
+            order_by: vec![],
+            client: self.client.clone(),
+        };
+        search
+            .with_filter(
+                Filter::tag(MLFLOW_PARENT_RUN_ID)
+                    .eq(self.info.run_id.as_str())
+                    .build()?,
+            )
+            .search()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_search_request() {
+        let search = ExperimentSearch::default()
+            .with_filter("tags.team = 'nlp'")
+            .with_view_type(RunViewType::DeletedOnly)
+            .with_max_results(5)
+            .with_order_by(vec!["name ASC"]);
+
+        assert_eq!(
+            serde_json::to_value(search.request()).unwrap(),
+            serde_json::json!({
+                "filter": "tags.team = 'nlp'",
+                "view_type": "DELETED_ONLY",
+                "max_results": 5,
+                "order_by": ["name ASC"],
+            })
+        );
+    }
+
+    #[test]
+    fn registry_search_requests() {
+        let search = RegisteredModelSearch::default()
+            .with_filter("tags.team = 'fraud'")
+            .with_max_results(20)
+            .with_order_by(vec!["name ASC"]);
+        assert_eq!(
+            serde_json::to_value(search.request()).unwrap(),
+            serde_json::json!({
+                "filter": "tags.team = 'fraud'",
+                "max_results": 20,
+                "order_by": ["name ASC"],
+            })
+        );
+
+        let search = ModelVersionSearch::default().with_filter("name = 'fraud-detector'");
+        assert_eq!(
+            serde_json::to_value(search.request()).unwrap(),
+            serde_json::json!({"filter": "name = 'fraud-detector'"})
+        );
+    }
+
+    #[test]
+    fn run_search_requires_experiment_ids() {
+        RunSearch::new(Vec::<String>::new())
+            .expect_err("SearchError: at least one experiment_id is required");
+    }
+
+    #[test]
+    fn run_search_request() {
+        let search = RunSearch::new(vec!["1", "2"])
+            .unwrap()
+            .with_filter("metrics.rmse < 0.5")
+            .with_run_view_type(RunViewType::All)
+            .with_max_results(10)
+            .with_order_by(vec!["metrics.rmse ASC"]);
+
+        assert_eq!(
+            serde_json::to_value(search.request()).unwrap(),
+            serde_json::json!({
+                "experiment_ids": ["1", "2"],
+                "filter": "metrics.rmse < 0.5",
+                "run_view_type": "ALL",
+                "max_results": 10,
+                "order_by": ["metrics.rmse ASC"],
+            })
+        );
+    }
+}
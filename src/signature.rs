@@ -0,0 +1,564 @@
+//! Model signatures and input examples, describing the data a logged model accepts and returns.
+//!
+//! These follow MLflow's schema format so the serving layer can validate requests against them.
+
+use crate::MLFlowError::SignatureError;
+use crate::MLFlowResult;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+
+/// Name of the artifact input examples are saved as, next to the `MLmodel` file.
+pub const INPUT_EXAMPLE_FILE_NAME: &str = "input_example.json";
+
+/// Type of a column in a column based [`Schema`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataType {
+    Boolean,
+    /// 32 bit signed integer.
+    Integer,
+    /// 64 bit signed integer.
+    Long,
+    Float,
+    Double,
+    String,
+    Binary,
+    Datetime,
+}
+
+/// A named or positional column of a column based [`Schema`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColSpec {
+    #[serde(rename = "type")]
+    pub data_type: DataType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "required")]
+    pub required: bool,
+}
+
+fn required() -> bool {
+    true
+}
+
+impl ColSpec {
+    pub fn new(data_type: DataType, name: impl AsRef<str>) -> ColSpec {
+        ColSpec {
+            data_type,
+            name: Some(name.as_ref().to_string()),
+            required: true,
+        }
+    }
+
+    /// A column the model can be called without.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+}
+
+/// A tensor input or output, described by its numpy `dtype` and shape. `-1` marks a variable
+/// dimension, usually the batch size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorSpec {
+    pub dtype: String,
+    pub shape: Vec<i64>,
+    pub name: Option<String>,
+}
+
+impl TensorSpec {
+    pub fn new(dtype: impl AsRef<str>, shape: Vec<i64>) -> TensorSpec {
+        TensorSpec {
+            dtype: dtype.as_ref().to_string(),
+            shape,
+            name: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl AsRef<str>) -> Self {
+        self.name = Some(name.as_ref().to_string());
+        self
+    }
+}
+
+impl Serialize for TensorSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut spec = json!({
+            "type": "tensor",
+            "tensor-spec": {"dtype": self.dtype, "shape": self.shape},
+        });
+        if let Some(name) = &self.name {
+            spec["name"] = json!(name);
+        }
+        spec.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TensorSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Inner {
+            dtype: String,
+            shape: Vec<i64>,
+        }
+        #[derive(Deserialize)]
+        struct Outer {
+            #[serde(rename = "tensor-spec")]
+            tensor_spec: Inner,
+            #[serde(default)]
+            name: Option<String>,
+        }
+
+        let outer = Outer::deserialize(deserializer)?;
+        Ok(TensorSpec {
+            dtype: outer.tensor_spec.dtype,
+            shape: outer.tensor_spec.shape,
+            name: outer.name,
+        })
+    }
+}
+
+/// The inputs or outputs of a model, either columns or tensors. MLflow doesn't allow mixing the
+/// two in one schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Schema {
+    Columns(Vec<ColSpec>),
+    Tensors(Vec<TensorSpec>),
+}
+
+impl Schema {
+    /// The schema as the JSON array MLflow embeds in `MLmodel` files.
+    pub fn to_json(&self) -> MLFlowResult<String> {
+        match self {
+            Schema::Columns(columns) => serde_json::to_string(columns),
+            Schema::Tensors(tensors) => serde_json::to_string(tensors),
+        }
+        .map_err(|e| SignatureError(e.to_string()))
+    }
+
+    pub fn from_json(schema: &str) -> MLFlowResult<Schema> {
+        let specs: Vec<Value> =
+            serde_json::from_str(schema).map_err(|e| SignatureError(e.to_string()))?;
+        let is_tensor = |spec: &Value| spec.get("type") == Some(&json!("tensor"));
+
+        let schema = if !specs.is_empty() && specs.iter().all(is_tensor) {
+            serde_json::from_value(Value::Array(specs)).map(Schema::Tensors)
+        } else if specs.iter().any(is_tensor) {
+            return Err(SignatureError(
+                "a schema cannot mix columns and tensors".to_string(),
+            ));
+        } else {
+            serde_json::from_value(Value::Array(specs)).map(Schema::Columns)
+        };
+        schema.map_err(|e| SignatureError(e.to_string()))
+    }
+}
+
+/// The schema of a model's inputs and, optionally, outputs.
+///
+/// In an `MLmodel` file each schema is stored as a JSON encoded string, which is how this type
+/// serializes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelSignature {
+    pub inputs: Schema,
+    pub outputs: Option<Schema>,
+}
+
+impl ModelSignature {
+    pub fn new(inputs: Schema) -> ModelSignature {
+        ModelSignature {
+            inputs,
+            outputs: None,
+        }
+    }
+
+    pub fn with_outputs(mut self, outputs: Schema) -> Self {
+        self.outputs = Some(outputs);
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedSignature {
+    inputs: String,
+    #[serde(default)]
+    outputs: Option<String>,
+    #[serde(default)]
+    params: Option<String>,
+}
+
+impl Serialize for ModelSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EncodedSignature {
+            inputs: self.inputs.to_json().map_err(S::Error::custom)?,
+            outputs: match &self.outputs {
+                Some(outputs) => Some(outputs.to_json().map_err(S::Error::custom)?),
+                None => None,
+            },
+            params: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelSignature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = EncodedSignature::deserialize(deserializer)?;
+        Ok(ModelSignature {
+            inputs: Schema::from_json(&encoded.inputs).map_err(D::Error::custom)?,
+            outputs: match encoded.outputs {
+                Some(outputs) => Some(Schema::from_json(&outputs).map_err(D::Error::custom)?),
+                None => None,
+            },
+        })
+    }
+}
+
+/// Sample input saved with a model, shown in the UI and used to test serving.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputExample {
+    /// Rows of a table, saved in pandas' `split` orientation.
+    Columns {
+        columns: Vec<String>,
+        data: Vec<Vec<Value>>,
+    },
+    /// A (possibly nested) array for tensor based models.
+    Tensor(Value),
+}
+
+impl InputExample {
+    /// The contents of `input_example.json`.
+    pub fn to_json(&self) -> MLFlowResult<String> {
+        let example = match self {
+            InputExample::Columns { columns, data } => json!({"columns": columns, "data": data}),
+            InputExample::Tensor(inputs) => json!({ "inputs": inputs }),
+        };
+        serde_json::to_string(&example).map_err(|e| SignatureError(e.to_string()))
+    }
+
+    /// The `saved_input_example_info` section of the `MLmodel` file.
+    pub fn info(&self) -> Value {
+        match self {
+            InputExample::Columns { .. } => json!({
+                "artifact_path": INPUT_EXAMPLE_FILE_NAME,
+                "type": "dataframe",
+                "pandas_orient": "split",
+            }),
+            InputExample::Tensor(_) => json!({
+                "artifact_path": INPUT_EXAMPLE_FILE_NAME,
+                "type": "ndarray",
+                "format": "tf-serving",
+            }),
+        }
+    }
+}
+
+/// Element types of tensors whose numpy `dtype` is known.
+#[cfg(feature = "ndarray")]
+pub trait TensorElement {
+    const DTYPE: &'static str;
+}
+
+#[cfg(feature = "ndarray")]
+macro_rules! tensor_element {
+    ($($t:ty => $dtype:literal),* $(,)?) => {
+        $(impl TensorElement for $t {
+            const DTYPE: &'static str = $dtype;
+        })*
+    };
+}
+
+#[cfg(feature = "ndarray")]
+tensor_element!(
+    bool => "bool",
+    i8 => "int8",
+    i16 => "int16",
+    i32 => "int32",
+    i64 => "int64",
+    u8 => "uint8",
+    u16 => "uint16",
+    u32 => "uint32",
+    u64 => "uint64",
+    f32 => "float32",
+    f64 => "float64",
+);
+
+#[cfg(feature = "ndarray")]
+impl TensorSpec {
+    /// Infers the spec of a batch of inputs, treating the first axis as the variable batch
+    /// dimension.
+    pub fn from_ndarray<A, S, D>(array: &ndarray::ArrayBase<S, D>) -> TensorSpec
+    where
+        A: TensorElement,
+        S: ndarray::Data<Elem = A>,
+        D: ndarray::Dimension,
+    {
+        let shape = array
+            .shape()
+            .iter()
+            .enumerate()
+            .map(|(axis, len)| if axis == 0 { -1 } else { *len as i64 })
+            .collect();
+        TensorSpec::new(A::DTYPE, shape)
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl ModelSignature {
+    /// A tensor signature inferred from example model inputs and outputs.
+    pub fn from_ndarray<A, S, D, B, T, E>(
+        inputs: &ndarray::ArrayBase<S, D>,
+        outputs: Option<&ndarray::ArrayBase<T, E>>,
+    ) -> ModelSignature
+    where
+        A: TensorElement,
+        S: ndarray::Data<Elem = A>,
+        D: ndarray::Dimension,
+        B: TensorElement,
+        T: ndarray::Data<Elem = B>,
+        E: ndarray::Dimension,
+    {
+        ModelSignature {
+            inputs: Schema::Tensors(vec![TensorSpec::from_ndarray(inputs)]),
+            outputs: outputs.map(|o| Schema::Tensors(vec![TensorSpec::from_ndarray(o)])),
+        }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl InputExample {
+    pub fn from_ndarray<A, S, D>(array: &ndarray::ArrayBase<S, D>) -> MLFlowResult<InputExample>
+    where
+        A: Serialize,
+        S: ndarray::Data<Elem = A>,
+        D: ndarray::Dimension,
+    {
+        fn nest<A: Serialize>(values: &[A], shape: &[usize]) -> MLFlowResult<Value> {
+            match shape.split_first() {
+                Some((_, rest)) if !rest.is_empty() => {
+                    let stride = rest.iter().product::<usize>().max(1);
+                    values
+                        .chunks(stride)
+                        .map(|chunk| nest(chunk, rest))
+                        .collect::<MLFlowResult<Vec<_>>>()
+                        .map(Value::Array)
+                }
+                _ => serde_json::to_value(values).map_err(|e| SignatureError(e.to_string())),
+            }
+        }
+
+        let values: Vec<&A> = array.iter().collect();
+        nest(&values, array.shape()).map(InputExample::Tensor)
+    }
+}
+
+#[cfg(feature = "polars")]
+impl Schema {
+    /// A column schema with one column per data frame column.
+    pub fn from_dataframe(df: &polars::prelude::DataFrame) -> MLFlowResult<Schema> {
+        use polars::prelude::DataType as PolarsType;
+
+        df.get_columns()
+            .iter()
+            .map(|column| {
+                let data_type = match column.dtype() {
+                    PolarsType::Boolean => DataType::Boolean,
+                    PolarsType::Int8 | PolarsType::Int16 | PolarsType::Int32 => DataType::Integer,
+                    PolarsType::UInt8 | PolarsType::UInt16 => DataType::Integer,
+                    PolarsType::Int64 | PolarsType::UInt32 | PolarsType::UInt64 => DataType::Long,
+                    PolarsType::Float32 => DataType::Float,
+                    PolarsType::Float64 => DataType::Double,
+                    PolarsType::String => DataType::String,
+                    PolarsType::Binary => DataType::Binary,
+                    other => {
+                        return Err(SignatureError(format!(
+                            "column {} has unsupported type {}",
+                            column.name(),
+                            other
+                        )))
+                    }
+                };
+                Ok(ColSpec::new(data_type, column.name().as_str()))
+            })
+            .collect::<MLFlowResult<Vec<_>>>()
+            .map(Schema::Columns)
+    }
+}
+
+#[cfg(feature = "polars")]
+impl InputExample {
+    /// Uses the first `rows` rows of the data frame as the example.
+    pub fn from_dataframe(
+        df: &polars::prelude::DataFrame,
+        rows: usize,
+    ) -> MLFlowResult<InputExample> {
+        Ok(InputExample::Columns {
+            columns: dataframe_columns(df),
+            data: dataframe_rows(df, rows)?,
+        })
+    }
+}
+
+#[cfg(feature = "polars")]
+pub(crate) fn dataframe_columns(df: &polars::prelude::DataFrame) -> Vec<String> {
+    df.get_column_names()
+        .iter()
+        .map(|c| c.to_string())
+        .collect()
+}
+
+/// The first `rows` rows of the data frame as JSON values.
+#[cfg(feature = "polars")]
+pub(crate) fn dataframe_rows(
+    df: &polars::prelude::DataFrame,
+    rows: usize,
+) -> MLFlowResult<Vec<Vec<Value>>> {
+    use polars::prelude::AnyValue;
+
+    (0..rows.min(df.height()))
+        .map(|i| {
+            df.get(i)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|value| match value {
+                    AnyValue::Null => Ok(Value::Null),
+                    AnyValue::Boolean(b) => Ok(json!(b)),
+                    AnyValue::String(s) => Ok(json!(s)),
+                    AnyValue::StringOwned(s) => Ok(json!(s.as_str())),
+                    AnyValue::Int8(v) => Ok(json!(v)),
+                    AnyValue::Int16(v) => Ok(json!(v)),
+                    AnyValue::Int32(v) => Ok(json!(v)),
+                    AnyValue::Int64(v) => Ok(json!(v)),
+                    AnyValue::UInt8(v) => Ok(json!(v)),
+                    AnyValue::UInt16(v) => Ok(json!(v)),
+                    AnyValue::UInt32(v) => Ok(json!(v)),
+                    AnyValue::UInt64(v) => Ok(json!(v)),
+                    AnyValue::Float32(v) => Ok(json!(v)),
+                    AnyValue::Float64(v) => Ok(json!(v)),
+                    other => Err(SignatureError(format!(
+                        "unsupported value {} in data frame",
+                        other
+                    ))),
+                })
+                .collect::<MLFlowResult<Vec<_>>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_signature_round_trip() {
+        let signature = ModelSignature::new(Schema::Columns(vec![
+            ColSpec::new(DataType::Double, "sepal_length"),
+            ColSpec::new(DataType::String, "species").optional(),
+        ]))
+        .with_outputs(Schema::Tensors(vec![TensorSpec::new(
+            "float32",
+            vec![-1, 3],
+        )]));
+
+        let encoded = serde_json::to_value(&signature).unwrap();
+        assert_eq!(
+            encoded["inputs"],
+            r#"[{"type":"double","name":"sepal_length","required":true},{"type":"string","name":"species","required":false}]"#
+        );
+        assert_eq!(
+            encoded["outputs"],
+            r#"[{"tensor-spec":{"dtype":"float32","shape":[-1,3]},"type":"tensor"}]"#
+        );
+        assert_eq!(
+            serde_json::from_value::<ModelSignature>(encoded).unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn reads_mlflow_schemas() {
+        let schema =
+            Schema::from_json(r#"[{"type": "long", "name": "age"}, {"type": "boolean"}]"#).unwrap();
+        assert_eq!(
+            schema,
+            Schema::Columns(vec![
+                ColSpec::new(DataType::Long, "age"),
+                ColSpec {
+                    data_type: DataType::Boolean,
+                    name: None,
+                    required: true
+                },
+            ])
+        );
+
+        Schema::from_json(
+            r#"[{"type": "long"}, {"type": "tensor", "tensor-spec": {"dtype": "int8", "shape": [1]}}]"#,
+        )
+        .expect_err("columns and tensors cannot be mixed");
+    }
+
+    #[test]
+    fn input_examples() {
+        let example = InputExample::Columns {
+            columns: vec!["a".to_string(), "b".to_string()],
+            data: vec![vec![json!(1), json!("x")]],
+        };
+        assert_eq!(
+            example.to_json().unwrap(),
+            r#"{"columns":["a","b"],"data":[[1,"x"]]}"#
+        );
+        assert_eq!(example.info()["type"], "dataframe");
+        assert_eq!(
+            InputExample::Tensor(json!([[1.0, 2.0]])).to_json().unwrap(),
+            r#"{"inputs":[[1.0,2.0]]}"#
+        );
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn infers_from_ndarray() {
+        let inputs = ndarray::Array2::<f32>::zeros((8, 4));
+        let outputs = ndarray::Array1::<i64>::zeros(8);
+        let signature = ModelSignature::from_ndarray(&inputs, Some(&outputs));
+        assert_eq!(
+            signature.inputs,
+            Schema::Tensors(vec![TensorSpec::new("float32", vec![-1, 4])])
+        );
+        assert_eq!(
+            signature.outputs,
+            Some(Schema::Tensors(vec![TensorSpec::new("int64", vec![-1])]))
+        );
+
+        let example = InputExample::from_ndarray(&ndarray::array![[1.0, 2.0], [3.0, 4.0]]).unwrap();
+        assert_eq!(
+            example,
+            InputExample::Tensor(json!([[1.0, 2.0], [3.0, 4.0]]))
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn infers_from_dataframe() {
+        let df = polars::df!("age" => [31i64, 42], "name" => ["a", "b"], "score" => [0.5f64, 0.7])
+            .unwrap();
+        assert_eq!(
+            Schema::from_dataframe(&df).unwrap(),
+            Schema::Columns(vec![
+                ColSpec::new(DataType::Long, "age"),
+                ColSpec::new(DataType::String, "name"),
+                ColSpec::new(DataType::Double, "score"),
+            ])
+        );
+
+        let example = InputExample::from_dataframe(&df, 1).unwrap();
+        assert_eq!(
+            example,
+            InputExample::Columns {
+                columns: vec!["age".to_string(), "name".to_string(), "score".to_string()],
+                data: vec![vec![json!(31), json!("a"), json!(0.5)]],
+            }
+        );
+    }
+}
@@ -0,0 +1,1567 @@
+//! Tracking into a SQLite database with the schema of MLflow's SQLAlchemy store, for single
+//! binary deployments where the application is its own tracking store.
+
+use crate::artifacts::artifact_repository;
+use crate::client::{
+    CreateExperimentRequest, CreateExperimentResponse, CreateModelVersionRequest,
+    CreateRegisteredModelRequest, CreateRunRequest, CreateRunResponse, EndTraceRequest,
+    ExperimentInfo, GetExperimentResponse, GetRunResponse, ListArtifactsResponse, LogMetricRequest,
+    LogParamRequest, MLFlowClient, MetricHistoryResponse, ModelVersionResponse,
+    RegisteredModelResponse, RunResponse, SearchExperimentsRequest, SearchExperimentsResponse,
+    SearchModelVersionsRequest, SearchModelVersionsResponse, SearchRegisteredModelsRequest,
+    SearchRegisteredModelsResponse, SearchRunsRequest, SearchRunsResponse, SharedClient,
+    StartTraceRequest, TraceInfoResponse, UpdateRunRequest, UpdateRunResponse,
+};
+use crate::dataset::{Dataset, DatasetInput, InputTag, RunInputs};
+use crate::entities::{
+    ExperimentTag, LifecycleStage, Metric, Param, RunData, RunInfo, RunStatus, RunTag, TraceTag,
+};
+use crate::names::generate_run_name;
+use crate::query::{all_match, experiment_value, matches_view_type, page, parse_filter, run_value};
+use crate::registry::{ModelVersionStage, ModelVersionTag, RegisteredModelTag};
+use crate::run::current_time_millis;
+use crate::tags::{MLFLOW_RUN_NAME, MLFLOW_USER};
+use crate::MLFlowError::{
+    ArtifactError, ExperimentNotFound, InvalidParameterValue, ParamValueChanged,
+    ResourceAlreadyExists, ResourceDoesNotExist, RunNotFound, SqliteError,
+};
+use crate::{MLFlowError, MLFlowResult};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use url::Url;
+
+/// The alembic revision of the tables created in new databases, the schema of MLflow 2.4.
+/// `mlflow db upgrade` migrates them to the schema of the installed MLflow.
+const SCHEMA_REVISION: &str = "7f2a7d5fae7d";
+
+const SCHEMA: &str = "
+CREATE TABLE alembic_version (
+    version_num VARCHAR(32) NOT NULL,
+    CONSTRAINT alembic_version_pkc PRIMARY KEY (version_num)
+);
+CREATE TABLE experiments (
+    experiment_id INTEGER NOT NULL,
+    name VARCHAR(256) NOT NULL,
+    artifact_location VARCHAR(256),
+    lifecycle_stage VARCHAR(32),
+    creation_time BIGINT,
+    last_update_time BIGINT,
+    CONSTRAINT experiment_pk PRIMARY KEY (experiment_id),
+    UNIQUE (name),
+    CONSTRAINT experiments_lifecycle_stage CHECK (lifecycle_stage IN ('active', 'deleted'))
+);
+CREATE TABLE experiment_tags (
+    key VARCHAR(250) NOT NULL,
+    value VARCHAR(5000),
+    experiment_id INTEGER NOT NULL,
+    CONSTRAINT experiment_tag_pk PRIMARY KEY (key, experiment_id),
+    FOREIGN KEY(experiment_id) REFERENCES experiments (experiment_id)
+);
+CREATE TABLE runs (
+    run_uuid VARCHAR(32) NOT NULL,
+    name VARCHAR(250),
+    source_type VARCHAR(20),
+    source_name VARCHAR(500),
+    entry_point_name VARCHAR(50),
+    user_id VARCHAR(256),
+    status VARCHAR(9),
+    start_time BIGINT,
+    end_time BIGINT,
+    deleted_time BIGINT,
+    source_version VARCHAR(50),
+    lifecycle_stage VARCHAR(20),
+    artifact_uri VARCHAR(200),
+    experiment_id INTEGER,
+    CONSTRAINT run_pk PRIMARY KEY (run_uuid),
+    FOREIGN KEY(experiment_id) REFERENCES experiments (experiment_id),
+    CONSTRAINT source_type CHECK (source_type IN ('NOTEBOOK', 'JOB', 'LOCAL', 'UNKNOWN', 'PROJECT')),
+    CONSTRAINT runs_lifecycle_stage CHECK (lifecycle_stage IN ('active', 'deleted')),
+    CONSTRAINT runs_status_check CHECK (status IN ('SCHEDULED', 'FAILED', 'FINISHED', 'RUNNING', 'KILLED'))
+);
+CREATE TABLE tags (
+    key VARCHAR(250) NOT NULL,
+    value VARCHAR(5000),
+    run_uuid VARCHAR(32) NOT NULL,
+    CONSTRAINT tag_pk PRIMARY KEY (key, run_uuid),
+    FOREIGN KEY(run_uuid) REFERENCES runs (run_uuid)
+);
+CREATE TABLE params (
+    key VARCHAR(250) NOT NULL,
+    value VARCHAR(500) NOT NULL,
+    run_uuid VARCHAR(32) NOT NULL,
+    CONSTRAINT param_pk PRIMARY KEY (key, run_uuid),
+    FOREIGN KEY(run_uuid) REFERENCES runs (run_uuid)
+);
+CREATE TABLE metrics (
+    key VARCHAR(250) NOT NULL,
+    value FLOAT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    run_uuid VARCHAR(32) NOT NULL,
+    step BIGINT DEFAULT '0' NOT NULL,
+    is_nan BOOLEAN DEFAULT '0' NOT NULL,
+    CONSTRAINT metric_pk PRIMARY KEY (key, timestamp, step, run_uuid, value, is_nan),
+    FOREIGN KEY(run_uuid) REFERENCES runs (run_uuid)
+);
+CREATE TABLE latest_metrics (
+    key VARCHAR(250) NOT NULL,
+    value FLOAT NOT NULL,
+    timestamp BIGINT,
+    step BIGINT NOT NULL,
+    is_nan BOOLEAN NOT NULL,
+    run_uuid VARCHAR(32) NOT NULL,
+    CONSTRAINT latest_metric_pk PRIMARY KEY (key, run_uuid),
+    FOREIGN KEY(run_uuid) REFERENCES runs (run_uuid)
+);
+CREATE TABLE datasets (
+    dataset_uuid VARCHAR(36) NOT NULL,
+    experiment_id INTEGER NOT NULL,
+    name VARCHAR(500) NOT NULL,
+    digest VARCHAR(36) NOT NULL,
+    dataset_source_type VARCHAR(36) NOT NULL,
+    dataset_source TEXT NOT NULL,
+    dataset_schema TEXT,
+    dataset_profile TEXT,
+    CONSTRAINT dataset_pk PRIMARY KEY (experiment_id, name, digest),
+    FOREIGN KEY(experiment_id) REFERENCES experiments (experiment_id)
+);
+CREATE INDEX index_datasets_dataset_uuid ON datasets (dataset_uuid);
+CREATE INDEX index_datasets_experiment_id_dataset_source_type ON datasets (experiment_id, dataset_source_type);
+CREATE TABLE inputs (
+    input_uuid VARCHAR(36) NOT NULL,
+    source_type VARCHAR(36) NOT NULL,
+    source_id VARCHAR(36) NOT NULL,
+    destination_type VARCHAR(36) NOT NULL,
+    destination_id VARCHAR(36) NOT NULL,
+    CONSTRAINT inputs_pk PRIMARY KEY (source_type, source_id, destination_type, destination_id)
+);
+CREATE INDEX index_inputs_input_uuid ON inputs (input_uuid);
+CREATE INDEX index_inputs_destination_type_destination_id_source_type ON inputs (destination_type, destination_id, source_type);
+CREATE TABLE input_tags (
+    input_uuid VARCHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    value VARCHAR(500) NOT NULL,
+    CONSTRAINT input_tags_pk PRIMARY KEY (input_uuid, name)
+);
+CREATE TABLE registered_models (
+    name VARCHAR(256) NOT NULL,
+    creation_time BIGINT,
+    last_updated_time BIGINT,
+    description VARCHAR(5000),
+    CONSTRAINT registered_model_pk PRIMARY KEY (name),
+    UNIQUE (name)
+);
+CREATE TABLE model_versions (
+    name VARCHAR(256) NOT NULL,
+    version INTEGER NOT NULL,
+    creation_time BIGINT,
+    last_updated_time BIGINT,
+    description VARCHAR(5000),
+    user_id VARCHAR(256),
+    current_stage VARCHAR(20),
+    source VARCHAR(500),
+    run_id VARCHAR(32),
+    status VARCHAR(20),
+    status_message VARCHAR(500),
+    run_link VARCHAR(500),
+    CONSTRAINT model_version_pk PRIMARY KEY (name, version),
+    FOREIGN KEY(name) REFERENCES registered_models (name) ON UPDATE CASCADE
+);
+CREATE TABLE registered_model_tags (
+    key VARCHAR(250) NOT NULL,
+    value VARCHAR(5000),
+    name VARCHAR(256) NOT NULL,
+    CONSTRAINT registered_model_tag_pk PRIMARY KEY (key, name),
+    FOREIGN KEY(name) REFERENCES registered_models (name) ON UPDATE CASCADE
+);
+CREATE TABLE model_version_tags (
+    key VARCHAR(250) NOT NULL,
+    value VARCHAR(5000),
+    name VARCHAR(256) NOT NULL,
+    version INTEGER NOT NULL,
+    CONSTRAINT model_version_tag_pk PRIMARY KEY (key, name, version),
+    FOREIGN KEY(name, version) REFERENCES model_versions (name, version) ON UPDATE CASCADE
+);
+CREATE TABLE registered_model_aliases (
+    alias VARCHAR(256) NOT NULL,
+    version INTEGER NOT NULL,
+    name VARCHAR(256) NOT NULL,
+    CONSTRAINT registered_model_alias_pk PRIMARY KEY (name, alias),
+    CONSTRAINT registered_model_alias_name_fkey FOREIGN KEY(name) REFERENCES registered_models (name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+";
+
+const DEFAULT_EXPERIMENT_NAME: &str = "Default";
+const DATASET_SOURCE_TYPE: &str = "DATASET";
+const RUN_DESTINATION_TYPE: &str = "RUN";
+
+fn sql_error(e: rusqlite::Error) -> MLFlowError {
+    SqliteError(e.to_string())
+}
+
+fn lifecycle_stage(stage: &str) -> LifecycleStage {
+    match stage {
+        "deleted" => LifecycleStage::Deleted,
+        _ => LifecycleStage::Active,
+    }
+}
+
+fn status_name(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Running => "RUNNING",
+        RunStatus::Scheduled => "SCHEDULED",
+        RunStatus::Finished => "FINISHED",
+        RunStatus::Failed => "FAILED",
+        RunStatus::Killed => "KILLED",
+    }
+}
+
+fn parse_status(status: &str) -> MLFlowResult<RunStatus> {
+    match status {
+        "RUNNING" => Ok(RunStatus::Running),
+        "SCHEDULED" => Ok(RunStatus::Scheduled),
+        "FINISHED" => Ok(RunStatus::Finished),
+        "FAILED" => Ok(RunStatus::Failed),
+        "KILLED" => Ok(RunStatus::Killed),
+        other => Err(SqliteError(format!("unknown run status {}", other))),
+    }
+}
+
+/// Experiment ids are integers in the database, anything else can't name an experiment.
+fn experiment_key(experiment_id: &str) -> MLFlowResult<i64> {
+    experiment_id
+        .parse()
+        .map_err(|_| ExperimentNotFound(format!("experiment {} was not found", experiment_id)))
+}
+
+fn read_experiments(
+    db: &Connection,
+    condition: &str,
+    param: impl rusqlite::ToSql,
+) -> MLFlowResult<Vec<ExperimentInfo>> {
+    let sql = format!(
+        "SELECT experiment_id, name, artifact_location, lifecycle_stage, creation_time, \
+         last_update_time FROM experiments WHERE {} ORDER BY experiment_id",
+        condition
+    );
+    let mut statement = db.prepare(&sql).map_err(sql_error)?;
+    let rows = statement
+        .query_map([param], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+            ))
+        })
+        .map_err(sql_error)?;
+
+    let mut experiments = vec![];
+    for row in rows {
+        let (id, name, artifact_location, stage, creation_time, last_update_time) =
+            row.map_err(sql_error)?;
+        let mut statement = db
+            .prepare("SELECT key, value FROM experiment_tags WHERE experiment_id = ?1 ORDER BY key")
+            .map_err(sql_error)?;
+        let tags = statement
+            .query_map([id], |row| {
+                Ok(ExperimentTag {
+                    key: row.get(0)?,
+                    value: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(sql_error)?;
+
+        experiments.push(ExperimentInfo {
+            experiment_id: id.to_string(),
+            name,
+            artifact_location,
+            tags,
+            lifecycle_stage: Some(lifecycle_stage(stage.as_deref().unwrap_or_default())),
+            creation_time,
+            last_update_time,
+            extra: serde_json::Map::new(),
+        });
+    }
+    Ok(experiments)
+}
+
+/// Deletes a run and everything logged to it, except its artifacts.
+fn delete_run_rows(db: &Connection, run_id: &str) -> MLFlowResult<()> {
+    db.execute(
+        "DELETE FROM input_tags WHERE input_uuid IN (SELECT input_uuid FROM inputs \
+         WHERE destination_type = ?1 AND destination_id = ?2)",
+        params![RUN_DESTINATION_TYPE, run_id],
+    )
+    .map_err(sql_error)?;
+    db.execute(
+        "DELETE FROM inputs WHERE destination_type = ?1 AND destination_id = ?2",
+        params![RUN_DESTINATION_TYPE, run_id],
+    )
+    .map_err(sql_error)?;
+    for table in ["tags", "params", "metrics", "latest_metrics", "runs"] {
+        db.execute(
+            &format!("DELETE FROM {} WHERE run_uuid = ?1", table),
+            [run_id],
+        )
+        .map_err(sql_error)?;
+    }
+    Ok(())
+}
+
+fn read_run(db: &Connection, run_id: &str) -> MLFlowResult<RunResponse> {
+    let info = db
+        .query_row(
+            "SELECT run_uuid, name, experiment_id, status, start_time, end_time, artifact_uri, \
+             lifecycle_stage FROM runs WHERE run_uuid = ?1",
+            [run_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                    row.get::<_, Option<i64>>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(sql_error)?
+        .ok_or_else(|| RunNotFound(format!("run {} was not found", run_id)))?;
+    let info = RunInfo {
+        run_id: info.0,
+        run_name: info.1,
+        experiment_id: info.2.to_string(),
+        status: parse_status(&info.3)?,
+        start_time: info.4,
+        end_time: info.5,
+        artifact_uri: info.6,
+        lifecycle_stage: Some(lifecycle_stage(info.7.as_deref().unwrap_or_default())),
+        extra: serde_json::Map::new(),
+    };
+
+    let mut data = RunData::default();
+    let mut statement = db
+        .prepare(
+            "SELECT key, value, timestamp, step, is_nan FROM latest_metrics WHERE run_uuid = ?1 \
+             ORDER BY key",
+        )
+        .map_err(sql_error)?;
+    data.metrics = statement
+        .query_map([run_id], metric)
+        .map_err(sql_error)?
+        .collect::<Result<_, _>>()
+        .map_err(sql_error)?;
+    for (table, values) in [("params", &mut data.params), ("tags", &mut vec![])] {
+        let sql = format!(
+            "SELECT key, value FROM {} WHERE run_uuid = ?1 ORDER BY key",
+            table
+        );
+        let mut statement = db.prepare(&sql).map_err(sql_error)?;
+        let rows = statement
+            .query_map([run_id], |row| {
+                Ok(Param {
+                    key: row.get(0)?,
+                    value: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                })
+            })
+            .map_err(sql_error)?;
+        for row in rows {
+            let param = row.map_err(sql_error)?;
+            match table {
+                "tags" => data.tags.push(RunTag {
+                    key: param.key,
+                    value: param.value,
+                }),
+                _ => values.push(param),
+            }
+        }
+    }
+
+    let mut inputs = RunInputs::default();
+    let mut statement = db
+        .prepare(
+            "SELECT i.input_uuid, d.name, d.digest, d.dataset_source_type, d.dataset_source, \
+             d.dataset_schema, d.dataset_profile FROM inputs i \
+             JOIN datasets d ON d.dataset_uuid = i.source_id \
+             WHERE i.source_type = ?1 AND i.destination_type = ?2 AND i.destination_id = ?3",
+        )
+        .map_err(sql_error)?;
+    let rows = statement
+        .query_map(
+            params![DATASET_SOURCE_TYPE, RUN_DESTINATION_TYPE, run_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    Dataset {
+                        name: row.get(1)?,
+                        digest: row.get(2)?,
+                        source_type: row.get(3)?,
+                        source: row.get(4)?,
+                        schema: row.get(5)?,
+                        profile: row.get(6)?,
+                    },
+                ))
+            },
+        )
+        .map_err(sql_error)?;
+    for row in rows {
+        let (input_uuid, dataset) = row.map_err(sql_error)?;
+        let mut statement = db
+            .prepare("SELECT name, value FROM input_tags WHERE input_uuid = ?1 ORDER BY name")
+            .map_err(sql_error)?;
+        let tags = statement
+            .query_map([input_uuid], |row| {
+                Ok(InputTag {
+                    key: row.get(0)?,
+                    value: row.get(1)?,
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<_, _>>()
+            .map_err(sql_error)?;
+        inputs.dataset_inputs.push(DatasetInput { tags, dataset });
+    }
+
+    Ok(RunResponse { info, data, inputs })
+}
+
+/// A metric from a `key, value, timestamp, step, is_nan` row.
+fn metric(row: &rusqlite::Row) -> rusqlite::Result<Metric> {
+    let is_nan: bool = row.get(4)?;
+    Ok(Metric {
+        key: row.get(0)?,
+        value: match is_nan {
+            true => f64::NAN,
+            false => row.get(1)?,
+        },
+        timestamp: row.get::<_, Option<i64>>(2)?.unwrap_or_default(),
+        step: row.get(3)?,
+    })
+}
+
+/// Fails unless the run exists and isn't deleted.
+fn check_run_is_active(db: &Connection, run_id: &str) -> MLFlowResult<()> {
+    let stage: Option<String> = db
+        .query_row(
+            "SELECT lifecycle_stage FROM runs WHERE run_uuid = ?1",
+            [run_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(sql_error)?
+        .ok_or_else(|| RunNotFound(format!("run {} was not found", run_id)))?;
+    match stage.as_deref() {
+        Some("deleted") => Err(InvalidParameterValue(format!(
+            "The run {} must be in the 'active' state. Current state is deleted.",
+            run_id
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn insert_metric(db: &Connection, run_id: &str, metric: &Metric) -> MLFlowResult<()> {
+    // NaN can't be stored in a FLOAT column, MLflow stores 0 with the is_nan flag instead
+    let (value, is_nan) = match metric.value.is_nan() {
+        true => (0.0, true),
+        false => (metric.value, false),
+    };
+    db.execute(
+        "INSERT OR IGNORE INTO metrics (key, value, timestamp, step, is_nan, run_uuid) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            metric.key,
+            value,
+            metric.timestamp,
+            metric.step,
+            is_nan,
+            run_id
+        ],
+    )
+    .map_err(sql_error)?;
+    db.execute(
+        "INSERT INTO latest_metrics (key, value, timestamp, step, is_nan, run_uuid) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+         ON CONFLICT (key, run_uuid) DO UPDATE SET value = excluded.value, \
+         timestamp = excluded.timestamp, step = excluded.step, is_nan = excluded.is_nan \
+         WHERE (excluded.step, excluded.timestamp, excluded.value) > \
+         (latest_metrics.step, latest_metrics.timestamp, latest_metrics.value)",
+        params![
+            metric.key,
+            value,
+            metric.timestamp,
+            metric.step,
+            is_nan,
+            run_id
+        ],
+    )
+    .map_err(sql_error)?;
+    Ok(())
+}
+
+fn insert_param(db: &Connection, run_id: &str, param: &Param) -> MLFlowResult<()> {
+    let existing: Option<String> = db
+        .query_row(
+            "SELECT value FROM params WHERE key = ?1 AND run_uuid = ?2",
+            [&param.key, run_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    match existing {
+        Some(existing) if existing != param.value => Err(ParamValueChanged(format!(
+            "Changing param values is not allowed. Param with key='{}' was already logged \
+             with value='{}' for run ID='{}'. Attempted logging new value '{}'.",
+            param.key, existing, run_id, param.value
+        ))),
+        Some(_) => Ok(()),
+        None => db
+            .execute(
+                "INSERT INTO params (key, value, run_uuid) VALUES (?1, ?2, ?3)",
+                [&param.key, &param.value, run_id],
+            )
+            .map(|_| ())
+            .map_err(sql_error),
+    }
+}
+
+fn insert_tag(db: &Connection, run_id: &str, tag: &RunTag) -> MLFlowResult<()> {
+    db.execute(
+        "INSERT OR REPLACE INTO tags (key, value, run_uuid) VALUES (?1, ?2, ?3)",
+        [&tag.key, &tag.value, run_id],
+    )
+    .map_err(sql_error)?;
+    if tag.key == MLFLOW_RUN_NAME {
+        db.execute(
+            "UPDATE runs SET name = ?1 WHERE run_uuid = ?2",
+            [&tag.value, run_id],
+        )
+        .map_err(sql_error)?;
+    }
+    Ok(())
+}
+
+/// A [`MLFlowClient`] keeping experiments and runs in a SQLite database, in the schema of the
+/// store behind `mlflow server --backend-store-uri sqlite:///mlflow.db`. Requires the `sqlite`
+/// feature.
+///
+/// A new database gets MLflow's tables and the `Default` experiment. Databases created by MLflow
+/// are used as they are, so the same file can be served with `mlflow server` or `mlflow ui`.
+/// Runs store their artifacts below the artifact root given when opening the store. The model
+/// registry and traces are not supported.
+///
+/// Setting the tracking URI to a `sqlite:` URI makes builders and the
+/// [`fluent`](crate::fluent) API use a SQLite store.
+///
+/// ```no_run
+/// use mlflow_rs::{RunBuilder, SqliteStoreClient};
+///
+/// let store = SqliteStoreClient::new("mlflow.db", "file:///var/lib/mlflow/artifacts").unwrap();
+/// let run = RunBuilder::new("0")
+///     .unwrap()
+///     .with_dyn_client(store)
+///     .build()
+///     .unwrap();
+/// run.log_metric("loss", 0.25, Some(1), None).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct SqliteStoreClient {
+    db: Arc<Mutex<Connection>>,
+    artifact_root: String,
+}
+
+impl SqliteStoreClient {
+    /// Opens the database at `database`, creating it if needed. Experiments created by this
+    /// client store their artifacts below `artifact_root`, e.g. `file:///data/artifacts` or
+    /// `s3://bucket/mlflow`.
+    pub fn new(
+        database: impl AsRef<Path>,
+        artifact_root: impl AsRef<str>,
+    ) -> MLFlowResult<SqliteStoreClient> {
+        let db = Connection::open(database).map_err(sql_error)?;
+        // other processes, e.g. `mlflow ui`, may hold the lock for a moment
+        db.busy_timeout(Duration::from_secs(5)).map_err(sql_error)?;
+        db.pragma_update(None, "foreign_keys", true)
+            .map_err(sql_error)?;
+
+        let client = SqliteStoreClient {
+            db: Arc::new(Mutex::new(db)),
+            artifact_root: artifact_root.as_ref().trim_end_matches('/').to_string(),
+        };
+        client.create_schema()?;
+        Ok(client)
+    }
+
+    /// Opens the database of a `sqlite:///relative.db` or `sqlite:////absolute.db` tracking
+    /// URI. Artifacts are stored in the `mlruns` directory next to the database.
+    pub fn from_tracking_uri(tracking_uri: impl AsRef<str>) -> MLFlowResult<SqliteStoreClient> {
+        let tracking_uri = tracking_uri.as_ref();
+        let invalid = || SqliteError(format!("invalid SQLite tracking URI {}", tracking_uri));
+        let database = Path::new(
+            tracking_uri
+                .strip_prefix("sqlite:///")
+                .ok_or_else(invalid)?,
+        );
+
+        let directory = match database.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => Path::new(".").to_path_buf(),
+        };
+        let artifact_root = directory
+            .canonicalize()
+            .ok()
+            .and_then(|directory| Url::from_file_path(directory.join("mlruns")).ok())
+            .ok_or_else(invalid)?;
+        SqliteStoreClient::new(database, artifact_root.as_str())
+    }
+
+    fn db(&self) -> MutexGuard<'_, Connection> {
+        self.db.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Creates MLflow's tables and the default experiment in a new database.
+    fn create_schema(&self) -> MLFlowResult<()> {
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' \
+                 AND name = 'alembic_version')",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?;
+        if exists {
+            return Ok(());
+        }
+
+        tx.execute_batch(SCHEMA).map_err(sql_error)?;
+        tx.execute(
+            "INSERT INTO alembic_version (version_num) VALUES (?1)",
+            [SCHEMA_REVISION],
+        )
+        .map_err(sql_error)?;
+        let now = current_time_millis();
+        tx.execute(
+            "INSERT INTO experiments (experiment_id, name, artifact_location, lifecycle_stage, \
+             creation_time, last_update_time) VALUES (0, ?1, ?2, 'active', ?3, ?3)",
+            params![
+                DEFAULT_EXPERIMENT_NAME,
+                format!("{}/0", self.artifact_root),
+                now
+            ],
+        )
+        .map_err(sql_error)?;
+        tx.commit().map_err(sql_error)
+    }
+
+    fn set_experiment_lifecycle(
+        &self,
+        experiment_id: &str,
+        stage: LifecycleStage,
+    ) -> MLFlowResult<()> {
+        let id = experiment_key(experiment_id)?;
+        let (stage, deleted_time) = match stage {
+            LifecycleStage::Active => ("active", None),
+            LifecycleStage::Deleted => ("deleted", Some(current_time_millis())),
+        };
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        let updated = tx
+            .execute(
+                "UPDATE experiments SET lifecycle_stage = ?1, last_update_time = ?2 \
+                 WHERE experiment_id = ?3",
+                params![stage, current_time_millis(), id],
+            )
+            .map_err(sql_error)?;
+        if updated == 0 {
+            return Err(ExperimentNotFound(format!(
+                "experiment {} was not found",
+                experiment_id
+            )));
+        }
+        // the runs of a deleted experiment are deleted with it
+        tx.execute(
+            "UPDATE runs SET lifecycle_stage = ?1, deleted_time = ?2 WHERE experiment_id = ?3",
+            params![stage, deleted_time, id],
+        )
+        .map_err(sql_error)?;
+        tx.commit().map_err(sql_error)
+    }
+
+    fn set_run_lifecycle(&self, run_id: &str, stage: LifecycleStage) -> MLFlowResult<()> {
+        let (stage, deleted_time) = match stage {
+            LifecycleStage::Active => ("active", None),
+            LifecycleStage::Deleted => ("deleted", Some(current_time_millis())),
+        };
+        let updated = self
+            .db()
+            .execute(
+                "UPDATE runs SET lifecycle_stage = ?1, deleted_time = ?2 WHERE run_uuid = ?3",
+                params![stage, deleted_time, run_id],
+            )
+            .map_err(sql_error)?;
+        match updated {
+            0 => Err(RunNotFound(format!("run {} was not found", run_id))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deletes the artifacts of a run about to be purged, if its artifact store allows it.
+    fn delete_run_artifacts(&self, run: &RunInfo) {
+        let deleted = artifact_repository(
+            run.artifact_uri.as_deref().unwrap_or_default(),
+            &self.clone().into(),
+            Some(&run.run_id),
+        )
+        .and_then(|repository| repository.delete_artifacts(""));
+        if let Err(e) = deleted {
+            log::warn!(
+                "could not delete the artifacts of run {}: {}",
+                run.run_id,
+                e
+            );
+        }
+    }
+}
+
+impl From<SqliteStoreClient> for SharedClient {
+    fn from(client: SqliteStoreClient) -> Self {
+        SharedClient::new(client)
+    }
+}
+
+impl From<SqliteStoreClient> for Arc<dyn MLFlowClient> {
+    fn from(client: SqliteStoreClient) -> Self {
+        Arc::new(client)
+    }
+}
+
+fn unsupported<T>(feature: &str) -> MLFlowResult<T> {
+    Err(SqliteError(format!(
+        "{} is not supported by the SQLite store",
+        feature
+    )))
+}
+
+impl MLFlowClient for SqliteStoreClient {
+    fn create_experiment(
+        &self,
+        request: &CreateExperimentRequest,
+    ) -> MLFlowResult<CreateExperimentResponse> {
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM experiments WHERE name = ?1)",
+                [&request.name],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?;
+        if exists {
+            return Err(ResourceAlreadyExists(format!(
+                "Experiment '{}' already exists.",
+                request.name
+            )));
+        }
+
+        let now = current_time_millis();
+        tx.execute(
+            "INSERT INTO experiments (name, artifact_location, lifecycle_stage, creation_time, \
+             last_update_time) VALUES (?1, ?2, 'active', ?3, ?3)",
+            params![request.name, request.artifact_location, now],
+        )
+        .map_err(sql_error)?;
+        let id = tx.last_insert_rowid();
+        if request.artifact_location.is_none() {
+            tx.execute(
+                "UPDATE experiments SET artifact_location = ?1 WHERE experiment_id = ?2",
+                params![format!("{}/{}", self.artifact_root, id), id],
+            )
+            .map_err(sql_error)?;
+        }
+        for tag in &request.tags {
+            tx.execute(
+                "INSERT OR REPLACE INTO experiment_tags (key, value, experiment_id) \
+                 VALUES (?1, ?2, ?3)",
+                params![tag.key, tag.value, id],
+            )
+            .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(CreateExperimentResponse {
+            experiment_id: id.to_string(),
+        })
+    }
+
+    fn get_experiment_by_id(&self, id: &str) -> MLFlowResult<GetExperimentResponse> {
+        read_experiments(&self.db(), "experiment_id = ?1", experiment_key(id)?)?
+            .pop()
+            .map(|experiment| GetExperimentResponse { experiment })
+            .ok_or_else(|| ExperimentNotFound(format!("experiment {} was not found", id)))
+    }
+
+    fn get_experiment_by_name(&self, name: &str) -> MLFlowResult<GetExperimentResponse> {
+        read_experiments(&self.db(), "name = ?1", name)?
+            .pop()
+            .map(|experiment| GetExperimentResponse { experiment })
+            .ok_or_else(|| ExperimentNotFound("experiment was not found".to_string()))
+    }
+
+    fn search_experiments(
+        &self,
+        request: &SearchExperimentsRequest,
+    ) -> MLFlowResult<SearchExperimentsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let mut experiments = vec![];
+        for experiment in read_experiments(&self.db(), "?1", true)? {
+            if !matches_view_type(experiment.lifecycle_stage, request.view_type) {
+                continue;
+            }
+            if all_match(&clauses, |clause| experiment_value(&experiment, clause))? {
+                experiments.push(experiment);
+            }
+        }
+
+        let (experiments, next_page_token) = page(
+            experiments,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchExperimentsResponse {
+            experiments,
+            next_page_token,
+        })
+    }
+
+    fn update_experiment(&self, experiment_id: &str, new_name: &str) -> MLFlowResult<()> {
+        let id = experiment_key(experiment_id)?;
+        let db = self.db();
+        let taken: bool = db
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM experiments WHERE name = ?1 AND experiment_id != ?2)",
+                params![new_name, id],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?;
+        if taken {
+            return Err(ResourceAlreadyExists(format!(
+                "Experiment '{}' already exists.",
+                new_name
+            )));
+        }
+        let updated = db
+            .execute(
+                "UPDATE experiments SET name = ?1, last_update_time = ?2 WHERE experiment_id = ?3",
+                params![new_name, current_time_millis(), id],
+            )
+            .map_err(sql_error)?;
+        match updated {
+            0 => Err(ExperimentNotFound(format!(
+                "experiment {} was not found",
+                experiment_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn delete_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        self.set_experiment_lifecycle(experiment_id, LifecycleStage::Deleted)
+    }
+
+    fn restore_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        self.set_experiment_lifecycle(experiment_id, LifecycleStage::Active)
+    }
+
+    fn create_run(&self, request: CreateRunRequest) -> MLFlowResult<CreateRunResponse> {
+        let experiment = self
+            .get_experiment_by_id(&request.experiment_id)?
+            .experiment;
+        if experiment.lifecycle_stage != Some(LifecycleStage::Active) {
+            return Err(InvalidParameterValue(format!(
+                "Could not create run under non-active experiment with ID {}.",
+                request.experiment_id
+            )));
+        }
+
+        let run_id = uuid::Uuid::new_v4().simple().to_string();
+        let tag = |key: &str| {
+            request
+                .tags
+                .iter()
+                .rev()
+                .find(|t| t.key == key)
+                .map(|t| t.value.clone())
+        };
+        let run_name = request
+            .run_name
+            .clone()
+            .or_else(|| tag(MLFLOW_RUN_NAME))
+            .unwrap_or_else(generate_run_name);
+        let artifact_uri = format!(
+            "{}/{}/artifacts",
+            experiment
+                .artifact_location
+                .unwrap_or_default()
+                .trim_end_matches('/'),
+            run_id
+        );
+
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        tx.execute(
+            "INSERT INTO runs (run_uuid, name, source_type, source_name, entry_point_name, \
+             user_id, status, start_time, end_time, deleted_time, source_version, \
+             lifecycle_stage, artifact_uri, experiment_id) \
+             VALUES (?1, ?2, 'LOCAL', '', '', ?3, 'RUNNING', ?4, NULL, NULL, '', 'active', ?5, ?6)",
+            params![
+                run_id,
+                run_name,
+                tag(MLFLOW_USER).unwrap_or_default(),
+                request.start_time,
+                artifact_uri,
+                experiment_key(&request.experiment_id)?
+            ],
+        )
+        .map_err(sql_error)?;
+        for tag in request
+            .tags
+            .iter()
+            .filter(|t| t.key != MLFLOW_RUN_NAME)
+            .chain([&RunTag::from((MLFLOW_RUN_NAME, run_name.as_str()))])
+        {
+            insert_tag(&tx, &run_id, tag)?;
+        }
+        tx.commit().map_err(sql_error)?;
+
+        Ok(CreateRunResponse {
+            run: read_run(&db, &run_id)?,
+        })
+    }
+
+    fn get_run(&self, run_id: &str) -> MLFlowResult<GetRunResponse> {
+        Ok(GetRunResponse {
+            run: read_run(&self.db(), run_id)?,
+        })
+    }
+
+    fn update_run(&self, request: UpdateRunRequest) -> MLFlowResult<UpdateRunResponse> {
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        check_run_is_active(&tx, &request.run_id)?;
+        if let Some(run_name) = &request.run_name {
+            let tag = RunTag::from((MLFLOW_RUN_NAME, run_name.as_str()));
+            insert_tag(&tx, &request.run_id, &tag)?;
+        }
+        tx.execute(
+            "UPDATE runs SET status = COALESCE(?1, status), end_time = COALESCE(?2, end_time) \
+             WHERE run_uuid = ?3",
+            params![
+                request.status.map(status_name),
+                request.end_time,
+                request.run_id
+            ],
+        )
+        .map_err(sql_error)?;
+        tx.commit().map_err(sql_error)?;
+
+        Ok(UpdateRunResponse {
+            run_info: read_run(&db, &request.run_id)?.info,
+        })
+    }
+
+    fn delete_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.set_run_lifecycle(run_id, LifecycleStage::Deleted)
+    }
+
+    fn restore_run(&self, run_id: &str) -> MLFlowResult<()> {
+        self.set_run_lifecycle(run_id, LifecycleStage::Active)
+    }
+
+    fn deleted_runs(&self, older_than_ms: i64) -> MLFlowResult<Vec<RunInfo>> {
+        let db = self.db();
+        let run_ids: Vec<String> = db
+            .prepare(
+                "SELECT run_uuid FROM runs WHERE lifecycle_stage = 'deleted' \
+                 AND (?1 = 0 OR deleted_time <= ?2) ORDER BY run_uuid",
+            )
+            .map_err(sql_error)?
+            .query_map(
+                params![older_than_ms, current_time_millis() - older_than_ms],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?
+            .collect::<Result<_, _>>()
+            .map_err(sql_error)?;
+        run_ids
+            .iter()
+            .map(|run_id| read_run(&db, run_id).map(|run| run.info))
+            .collect()
+    }
+
+    fn purge_run(&self, run_id: &str) -> MLFlowResult<()> {
+        let run = read_run(&self.db(), run_id)?.info;
+        if run.lifecycle_stage != Some(LifecycleStage::Deleted) {
+            return Err(InvalidParameterValue(format!(
+                "run {} must be deleted before it is purged",
+                run_id
+            )));
+        }
+        self.delete_run_artifacts(&run);
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        delete_run_rows(&tx, run_id)?;
+        tx.commit().map_err(sql_error)
+    }
+
+    fn purge_experiment(&self, experiment_id: &str) -> MLFlowResult<()> {
+        let id = experiment_key(experiment_id)?;
+        let experiment = read_experiments(&self.db(), "experiment_id = ?1", id)?
+            .pop()
+            .ok_or_else(|| {
+                ExperimentNotFound(format!("experiment {} was not found", experiment_id))
+            })?;
+        if experiment.lifecycle_stage != Some(LifecycleStage::Deleted) {
+            return Err(InvalidParameterValue(format!(
+                "experiment {} must be deleted before it is purged",
+                experiment_id
+            )));
+        }
+
+        let runs: Vec<RunInfo> = {
+            let db = self.db();
+            let run_ids: Vec<String> = db
+                .prepare("SELECT run_uuid FROM runs WHERE experiment_id = ?1")
+                .map_err(sql_error)?
+                .query_map([id], |row| row.get(0))
+                .map_err(sql_error)?
+                .collect::<Result<_, _>>()
+                .map_err(sql_error)?;
+            run_ids
+                .iter()
+                .map(|run_id| read_run(&db, run_id).map(|run| run.info))
+                .collect::<MLFlowResult<_>>()?
+        };
+        for run in &runs {
+            self.delete_run_artifacts(run);
+        }
+
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        for run in &runs {
+            delete_run_rows(&tx, &run.run_id)?;
+        }
+        for table in ["experiment_tags", "datasets", "experiments"] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE experiment_id = ?1", table),
+                [id],
+            )
+            .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)
+    }
+
+    fn search_runs(&self, request: &SearchRunsRequest) -> MLFlowResult<SearchRunsResponse> {
+        let clauses = parse_filter(request.filter.as_deref().unwrap_or_default())?;
+        let db = self.db();
+        let mut statement = db
+            .prepare("SELECT run_uuid FROM runs WHERE experiment_id = ?1")
+            .map_err(sql_error)?;
+        let mut runs = vec![];
+        for experiment_id in &request.experiment_ids {
+            let Ok(id) = experiment_key(experiment_id) else {
+                continue;
+            };
+            let run_ids: Vec<String> = statement
+                .query_map([id], |row| row.get(0))
+                .map_err(sql_error)?
+                .collect::<Result<_, _>>()
+                .map_err(sql_error)?;
+            for run_id in run_ids {
+                let run = read_run(&db, &run_id)?;
+                if !matches_view_type(run.info.lifecycle_stage, request.run_view_type) {
+                    continue;
+                }
+                if all_match(&clauses, |clause| run_value(&run.info, &run.data, clause))? {
+                    runs.push(run);
+                }
+            }
+        }
+        runs.sort_by_key(|r| std::cmp::Reverse(r.info.start_time));
+
+        let (runs, next_page_token) = page(
+            runs,
+            request.max_results.map(|m| m as usize),
+            request.page_token.as_deref(),
+        )?;
+        Ok(SearchRunsResponse {
+            runs,
+            next_page_token,
+        })
+    }
+
+    fn log_metric(&self, request: LogMetricRequest) -> MLFlowResult<()> {
+        self.log_batch(&request.run_id, vec![request.metric], vec![], vec![])
+    }
+
+    fn log_param(&self, request: LogParamRequest) -> MLFlowResult<()> {
+        self.log_batch(&request.run_id, vec![], vec![request.param], vec![])
+    }
+
+    fn get_metric_history(
+        &self,
+        run_id: &str,
+        key: &str,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<MetricHistoryResponse> {
+        let db = self.db();
+        read_run(&db, run_id)?;
+        let mut statement = db
+            .prepare(
+                "SELECT key, value, timestamp, step, is_nan FROM metrics \
+                 WHERE run_uuid = ?1 AND key = ?2 ORDER BY timestamp, step",
+            )
+            .map_err(sql_error)?;
+        let history: Vec<Metric> = statement
+            .query_map([run_id, key], metric)
+            .map_err(sql_error)?
+            .collect::<Result<_, _>>()
+            .map_err(sql_error)?;
+
+        let (metrics, next_page_token) = page(history, None, page_token)?;
+        Ok(MetricHistoryResponse {
+            metrics,
+            next_page_token,
+        })
+    }
+
+    fn set_tag(&self, run_id: &str, tag: &RunTag) -> MLFlowResult<()> {
+        self.log_batch(run_id, vec![], vec![], vec![tag.clone()])
+    }
+
+    fn delete_tag(&self, run_id: &str, key: &str) -> MLFlowResult<()> {
+        let db = self.db();
+        check_run_is_active(&db, run_id)?;
+        let deleted = db
+            .execute(
+                "DELETE FROM tags WHERE key = ?1 AND run_uuid = ?2",
+                [key, run_id],
+            )
+            .map_err(sql_error)?;
+        match deleted {
+            0 => Err(ResourceDoesNotExist(format!(
+                "No tag with name: {} in run with id {}",
+                key, run_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn log_batch(
+        &self,
+        run_id: &str,
+        metrics: Vec<Metric>,
+        params: Vec<Param>,
+        tags: Vec<RunTag>,
+    ) -> MLFlowResult<()> {
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        check_run_is_active(&tx, run_id)?;
+        for param in &params {
+            insert_param(&tx, run_id, param)?;
+        }
+        for metric in &metrics {
+            insert_metric(&tx, run_id, metric)?;
+        }
+        for tag in &tags {
+            insert_tag(&tx, run_id, tag)?;
+        }
+        tx.commit().map_err(sql_error)
+    }
+
+    fn log_inputs(&self, run_id: &str, datasets: Vec<DatasetInput>) -> MLFlowResult<()> {
+        let mut db = self.db();
+        let tx = db.transaction().map_err(sql_error)?;
+        check_run_is_active(&tx, run_id)?;
+        let experiment_id: i64 = tx
+            .query_row(
+                "SELECT experiment_id FROM runs WHERE run_uuid = ?1",
+                [run_id],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?;
+
+        for input in datasets {
+            let dataset = &input.dataset;
+            let existing: Option<String> = tx
+                .query_row(
+                    "SELECT dataset_uuid FROM datasets WHERE experiment_id = ?1 AND name = ?2 \
+                     AND digest = ?3",
+                    params![experiment_id, dataset.name, dataset.digest],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(sql_error)?;
+            let dataset_uuid = match existing {
+                Some(dataset_uuid) => dataset_uuid,
+                None => {
+                    let dataset_uuid = uuid::Uuid::new_v4().simple().to_string();
+                    tx.execute(
+                        "INSERT INTO datasets (dataset_uuid, experiment_id, name, digest, \
+                         dataset_source_type, dataset_source, dataset_schema, dataset_profile) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            dataset_uuid,
+                            experiment_id,
+                            dataset.name,
+                            dataset.digest,
+                            dataset.source_type,
+                            dataset.source,
+                            dataset.schema,
+                            dataset.profile
+                        ],
+                    )
+                    .map_err(sql_error)?;
+                    dataset_uuid
+                }
+            };
+
+            let input_uuid = uuid::Uuid::new_v4().simple().to_string();
+            let inserted = tx
+                .execute(
+                    "INSERT OR IGNORE INTO inputs (input_uuid, source_type, source_id, \
+                     destination_type, destination_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        input_uuid,
+                        DATASET_SOURCE_TYPE,
+                        dataset_uuid,
+                        RUN_DESTINATION_TYPE,
+                        run_id
+                    ],
+                )
+                .map_err(sql_error)?;
+            // logging the same dataset again keeps the tags it was first logged with
+            if inserted == 0 {
+                continue;
+            }
+            for tag in &input.tags {
+                tx.execute(
+                    "INSERT OR REPLACE INTO input_tags (input_uuid, name, value) \
+                     VALUES (?1, ?2, ?3)",
+                    [&input_uuid, &tag.key, &tag.value],
+                )
+                .map_err(sql_error)?;
+            }
+        }
+        tx.commit().map_err(sql_error)
+    }
+
+    fn upload_artifact(&self, path: &str, _local_file: &Path) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "the SQLite store does not proxy artifacts, could not upload {}",
+            path
+        )))
+    }
+
+    fn list_artifacts(
+        &self,
+        run_id: &str,
+        path: Option<&str>,
+        page_token: Option<&str>,
+    ) -> MLFlowResult<ListArtifactsResponse> {
+        let run = read_run(&self.db(), run_id)?;
+        let repository = artifact_repository(
+            run.info.artifact_uri.as_deref().unwrap_or_default(),
+            &self.clone().into(),
+            Some(run_id),
+        )?;
+        let (files, next_page_token) = page(repository.list_artifacts(path)?, None, page_token)?;
+        Ok(ListArtifactsResponse {
+            files,
+            next_page_token,
+        })
+    }
+
+    fn download_artifact(&self, path: &str, _local_file: &Path) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "the SQLite store does not proxy artifacts, could not download {}",
+            path
+        )))
+    }
+
+    fn delete_artifact(&self, path: &str) -> MLFlowResult<()> {
+        Err(ArtifactError(format!(
+            "the SQLite store does not proxy artifacts, could not delete {}",
+            path
+        )))
+    }
+
+    fn create_registered_model(
+        &self,
+        _request: CreateRegisteredModelRequest,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        unsupported("the model registry")
+    }
+
+    fn get_registered_model(&self, _name: &str) -> MLFlowResult<RegisteredModelResponse> {
+        unsupported("the model registry")
+    }
+
+    fn rename_registered_model(
+        &self,
+        _name: &str,
+        _new_name: &str,
+    ) -> MLFlowResult<RegisteredModelResponse> {
+        unsupported("the model registry")
+    }
+
+    fn delete_registered_model(&self, _name: &str) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn set_registered_model_tag(&self, _name: &str, _tag: &RegisteredModelTag) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn delete_registered_model_tag(&self, _name: &str, _key: &str) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn search_registered_models(
+        &self,
+        _request: &SearchRegisteredModelsRequest,
+    ) -> MLFlowResult<SearchRegisteredModelsResponse> {
+        unsupported("the model registry")
+    }
+
+    fn create_model_version(
+        &self,
+        _request: CreateModelVersionRequest,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        unsupported("the model registry")
+    }
+
+    fn get_model_version(&self, _name: &str, _version: &str) -> MLFlowResult<ModelVersionResponse> {
+        unsupported("the model registry")
+    }
+
+    fn transition_model_version_stage(
+        &self,
+        _name: &str,
+        _version: &str,
+        _stage: ModelVersionStage,
+        _archive_existing_versions: bool,
+    ) -> MLFlowResult<ModelVersionResponse> {
+        unsupported("the model registry")
+    }
+
+    fn delete_model_version(&self, _name: &str, _version: &str) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn set_model_version_tag(
+        &self,
+        _name: &str,
+        _version: &str,
+        _tag: &ModelVersionTag,
+    ) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn delete_model_version_tag(
+        &self,
+        _name: &str,
+        _version: &str,
+        _key: &str,
+    ) -> MLFlowResult<()> {
+        unsupported("the model registry")
+    }
+
+    fn search_model_versions(
+        &self,
+        _request: &SearchModelVersionsRequest,
+    ) -> MLFlowResult<SearchModelVersionsResponse> {
+        unsupported("the model registry")
+    }
+
+    fn start_trace(&self, _request: StartTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        unsupported("tracing")
+    }
+
+    fn end_trace(&self, _request: EndTraceRequest) -> MLFlowResult<TraceInfoResponse> {
+        unsupported("tracing")
+    }
+
+    fn get_trace_info(&self, _request_id: &str) -> MLFlowResult<TraceInfoResponse> {
+        unsupported("tracing")
+    }
+
+    fn set_trace_tag(&self, _request_id: &str, _tag: &TraceTag) -> MLFlowResult<()> {
+        unsupported("tracing")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExperimentBuilder, Run, RunBuilder, RunLoader, RunSearch};
+
+    fn store() -> (tempfile::TempDir, SqliteStoreClient) {
+        let dir = tempfile::tempdir().unwrap();
+        let store =
+            SqliteStoreClient::new(dir.path().join("mlflow.db"), "file:///artifacts").unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn creates_the_mlflow_schema_once() {
+        let (dir, store) = store();
+        let version: String = store
+            .db()
+            .query_row("SELECT version_num FROM alembic_version", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(version, SCHEMA_REVISION);
+        let default = store.get_experiment_by_id("0").unwrap().experiment;
+        assert_eq!(default.name, DEFAULT_EXPERIMENT_NAME);
+        assert_eq!(
+            default.artifact_location.as_deref(),
+            Some("file:///artifacts/0")
+        );
+
+        let experiment = ExperimentBuilder::new("reopened")
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .build()
+            .unwrap();
+        drop(store);
+        let reopened =
+            SqliteStoreClient::new(dir.path().join("mlflow.db"), "file:///artifacts").unwrap();
+        let loaded = reopened.get_experiment_by_name("reopened").unwrap();
+        assert_eq!(loaded.experiment.experiment_id, experiment.experiment_id);
+        assert_eq!(
+            loaded.experiment.artifact_location.as_deref(),
+            Some("file:///artifacts/1")
+        );
+    }
+
+    #[test]
+    fn tracks_runs() {
+        let (_dir, store) = store();
+        let mut run = RunBuilder::new("0")
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .with_source_context(false)
+            .with_run_name("training")
+            .build()
+            .unwrap();
+        run.log_metric("loss", 0.5, Some(1), Some(1000)).unwrap();
+        run.log_metric("loss", 0.25, Some(2), Some(2000)).unwrap();
+        run.log_metric("loss", f64::NAN, Some(0), Some(3000))
+            .unwrap();
+        run.log_param("lr", 0.01).unwrap();
+        run.set_tag("team", "nlp").unwrap();
+        run.log_inputs(vec![Dataset::new("train", "abc", "local", "/data/train")])
+            .unwrap();
+        run.finish(RunStatus::Finished).unwrap();
+
+        let loaded = RunLoader::default()
+            .with_dyn_client(store.clone())
+            .load(run.info().run_id.as_str())
+            .unwrap();
+        assert_eq!(loaded.status(), RunStatus::Finished);
+        assert_eq!(loaded.info().run_name.as_deref(), Some("training"));
+        assert_eq!(loaded.data().metric("loss"), Some(0.25));
+        let history = loaded.get_metric_history("loss").unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history[2].value.is_nan());
+        run.log_param("lr", 0.1).unwrap_err();
+
+        let inputs = store
+            .get_run(&run.info().run_id)
+            .unwrap()
+            .run
+            .inputs
+            .dataset_inputs;
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].dataset.name, "train");
+
+        let found: Vec<Run> = RunSearch::new(vec!["0"])
+            .unwrap()
+            .with_filter("tags.team = 'nlp' AND metrics.loss < 0.5")
+            .with_dyn_client(store.clone())
+            .search()
+            .collect::<MLFlowResult<_>>()
+            .unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn deleting_an_experiment_deletes_its_runs() {
+        let (_dir, store) = store();
+        let experiment = ExperimentBuilder::new("doomed")
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .build()
+            .unwrap();
+        let id = experiment.experiment_id.clone();
+        let run = RunBuilder::new(&id)
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .build()
+            .unwrap();
+
+        store.delete_experiment(&id).unwrap();
+        let info = store.get_run(&run.info().run_id).unwrap().run.info;
+        assert_eq!(info.lifecycle_stage, Some(LifecycleStage::Deleted));
+        run.log_metric("loss", 1.0, None, None).unwrap_err();
+
+        store.restore_experiment(&id).unwrap();
+        run.log_metric("loss", 1.0, None, None).unwrap();
+    }
+
+    #[test]
+    fn purges_deleted_experiments_and_runs() {
+        let (_dir, store) = store();
+        let experiment = ExperimentBuilder::new("doomed")
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .build()
+            .unwrap();
+        let id = experiment.experiment_id.clone();
+        let run = RunBuilder::new(&id)
+            .unwrap()
+            .with_dyn_client(store.clone())
+            .build()
+            .unwrap();
+        run.log_metric("loss", 1.0, None, None).unwrap();
+        let run_id = run.info().run_id.clone();
+
+        store.purge_experiment(&id).expect_err("not deleted");
+        store.delete_experiment(&id).unwrap();
+        assert_eq!(store.deleted_runs(0).unwrap().len(), 1);
+        assert!(store.deleted_runs(60_000).unwrap().is_empty());
+        let experiment = store.get_experiment_by_id(&id).unwrap().experiment;
+        assert!(experiment.last_update_time.is_some());
+
+        store.purge_experiment(&id).unwrap();
+        store.get_experiment_by_id(&id).expect_err("purged");
+        store
+            .get_run(&run_id)
+            .expect_err("purged with its experiment");
+        let metrics: i64 = store
+            .db()
+            .query_row("SELECT COUNT(*) FROM metrics", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(metrics, 0);
+    }
+}
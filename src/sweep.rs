@@ -0,0 +1,393 @@
+//! Hyperparameter sweeps: one child run per configuration, nested under a parent run that
+//! records the best of them.
+
+use crate::active_run::ActiveRun;
+use crate::checkpoint::Objective;
+use crate::entities::RunStatus;
+use crate::run::{Run, RunBuilder};
+use crate::transfer::for_each_concurrent;
+use crate::MLFlowResult;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// The tag of the parent run holding the ID of the child run with the best score.
+pub const BEST_RUN_TAG: &str = "sweep.best_run_id";
+
+/// Runs a training function once per configuration, each in a child run of one parent run,
+/// with a bounded number running at once.
+///
+/// Each child run gets the configuration as params and the score the function returns as a
+/// metric, `score` by default, and ends `FINISHED`, or `FAILED` if the function failed. The
+/// parent run gets the best score as `best_<metric>` and the best child run in the
+/// [`BEST_RUN_TAG`] tag.
+///
+/// ```no_run
+/// use mlflow_rs::{Objective, RunBuilder, Sweep};
+///
+/// #[derive(serde::Serialize)]
+/// struct Config {
+///     learning_rate: f64,
+///     batch_size: usize,
+/// }
+///
+/// let configs = [1e-3, 1e-2, 1e-1].into_iter().flat_map(|learning_rate| {
+///     [32, 64].map(|batch_size| Config { learning_rate, batch_size })
+/// });
+/// let sweep = Sweep::new(RunBuilder::new("1").unwrap().with_run_name("lr-sweep"))
+///     .with_objective("val_loss", Objective::Minimize)
+///     .with_concurrency(4)
+///     .run(configs, |run, config| {
+///         // train, logging to `run`, and return the validation loss
+///         run.log_metric("train_loss", config.learning_rate, Some(0), None)?;
+///         Ok(config.learning_rate * config.batch_size as f64)
+///     })
+///     .unwrap();
+/// println!("best run: {}", sweep.best().unwrap().run_id);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sweep {
+    builder: RunBuilder,
+    metric: String,
+    objective: Objective,
+    concurrency: usize,
+}
+
+/// One configuration of a sweep and how it scored.
+#[derive(Clone, Debug)]
+pub struct Trial<P> {
+    pub run_id: String,
+    pub params: P,
+    /// The score the function returned, or its error.
+    pub score: MLFlowResult<f64>,
+}
+
+/// The trials of a sweep, in the order of their configurations.
+#[derive(Clone, Debug)]
+pub struct SweepResult<P> {
+    pub parent_run_id: String,
+    pub trials: Vec<Trial<P>>,
+    objective: Objective,
+}
+
+impl<P> SweepResult<P> {
+    /// The trial with the best score, the first of equally good ones. Failed trials and NaN
+    /// scores are left out.
+    pub fn best(&self) -> Option<&Trial<P>> {
+        let scored = self.trials.iter().filter_map(|trial| match trial.score {
+            Ok(score) if !score.is_nan() => Some((trial, score)),
+            _ => None,
+        });
+        scored
+            .min_by(|a, b| match self.objective {
+                Objective::Minimize => a.1.total_cmp(&b.1),
+                Objective::Maximize => b.1.total_cmp(&a.1),
+            })
+            .map(|(trial, _)| trial)
+    }
+
+    /// The score of the [best](Self::best) trial.
+    pub fn best_score(&self) -> Option<f64> {
+        self.best().and_then(|trial| trial.score.clone().ok())
+    }
+}
+
+impl Sweep {
+    /// A sweep whose parent run is created by `builder`. The child runs are created in the same
+    /// experiment, through the same client.
+    pub fn new(builder: RunBuilder) -> Sweep {
+        Sweep {
+            builder,
+            metric: "score".to_string(),
+            objective: Objective::Maximize,
+            concurrency: 1,
+        }
+    }
+
+    /// The metric the scores are logged as and whether lower or higher scores are better.
+    /// Defaults to maximizing `score`.
+    pub fn with_objective(mut self, metric: impl AsRef<str>, objective: Objective) -> Self {
+        self.metric = metric.as_ref().to_string();
+        self.objective = objective;
+        self
+    }
+
+    /// Runs up to `concurrency` trials at once, each on a thread of its own. Defaults to one
+    /// trial at a time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Creates the parent run and calls `trial` with a child run for each of `configs`, which
+    /// are logged as its params.
+    ///
+    /// An error returned by `trial` fails its child run only. A failure to track the sweep
+    /// itself, e.g. to create a child run or log its params, fails the child run if there is
+    /// one, stops the sweep, fails the parent run and is returned once the trials in progress
+    /// have ended. A panic in `trial` fails its child run and the parent run, and is resumed
+    /// once the other trials in progress have ended.
+    pub fn run<P, F>(
+        &self,
+        configs: impl IntoIterator<Item = P>,
+        trial: F,
+    ) -> MLFlowResult<SweepResult<P>>
+    where
+        P: Serialize + Send,
+        F: Fn(&Run, &P) -> MLFlowResult<f64> + Sync,
+    {
+        // held as guards, so that a panicking trial leaves no run RUNNING
+        let parent = self.builder.clone().start()?;
+        let configs: Vec<(usize, P)> = configs.into_iter().enumerate().collect();
+        let trials = Mutex::new(Vec::with_capacity(configs.len()));
+        let outcome = for_each_concurrent(self.concurrency, configs, |(index, params)| {
+            let result = self.run_trial(&parent, params, &trial)?;
+            trials
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((index, result));
+            Ok(())
+        });
+
+        let mut trials = trials.into_inner().unwrap_or_else(|e| e.into_inner());
+        trials.sort_by_key(|(index, _)| *index);
+        let sweep = SweepResult {
+            parent_run_id: parent.info.run_id.clone(),
+            trials: trials.into_iter().map(|(_, trial)| trial).collect(),
+            objective: self.objective,
+        };
+        if let Err(e) = outcome.and_then(|_| self.log_best(&parent, &sweep)) {
+            if let Err(finish) = parent.end(RunStatus::Failed) {
+                log::warn!(
+                    "could not fail sweep run {}: {}",
+                    sweep.parent_run_id,
+                    finish
+                );
+            }
+            return Err(e);
+        }
+        parent.end(RunStatus::Finished)?;
+        Ok(sweep)
+    }
+
+    fn run_trial<P: Serialize>(
+        &self,
+        parent: &Run,
+        params: P,
+        trial: &impl Fn(&Run, &P) -> MLFlowResult<f64>,
+    ) -> MLFlowResult<Trial<P>> {
+        let run: ActiveRun =
+            RunBuilder::with_shared_client(&parent.info.experiment_id, parent.client.clone())
+                .with_parent(&parent.info.run_id)
+                .start()?;
+        let run_id = run.info.run_id.clone();
+        let score = match self.track_trial(&run, &params, trial) {
+            Ok(score) => score,
+            Err(e) => {
+                if let Err(finish) = run.end(RunStatus::Failed) {
+                    log::warn!("could not fail trial run {}: {}", run_id, finish);
+                }
+                return Err(e);
+            }
+        };
+        let status = match &score {
+            Ok(_) => RunStatus::Finished,
+            Err(e) => {
+                log::warn!(
+                    "trial run {} of sweep {} failed: {}",
+                    run_id,
+                    parent.info.run_id,
+                    e
+                );
+                RunStatus::Failed
+            }
+        };
+        run.end(status)?;
+        Ok(Trial {
+            run_id,
+            params,
+            score,
+        })
+    }
+
+    /// Logs the params of a trial, runs it and logs its score. The outer error is a tracking
+    /// failure, the inner one the trial's own.
+    fn track_trial<P: Serialize>(
+        &self,
+        run: &Run,
+        params: &P,
+        trial: &impl Fn(&Run, &P) -> MLFlowResult<f64>,
+    ) -> MLFlowResult<MLFlowResult<f64>> {
+        run.log_params(params)?;
+        let score = trial(run, params);
+        if let Ok(score) = &score {
+            run.log_metric(&self.metric, *score, None, None)?;
+        }
+        Ok(score)
+    }
+
+    fn log_best<P>(&self, parent: &Run, sweep: &SweepResult<P>) -> MLFlowResult<()> {
+        let (Some(best), Some(score)) = (sweep.best(), sweep.best_score()) else {
+            return Ok(());
+        };
+        parent.log_metric(format!("best_{}", self.metric), score, None, None)?;
+        parent.set_tag(BEST_RUN_TAG, &best.run_id)
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::MLFlowError::InvalidMetric;
+    use crate::{MLFlowError, MockMLFlowClient, RunLoader, RunSearch};
+
+    #[derive(Debug, Serialize)]
+    struct Config {
+        learning_rate: f64,
+    }
+
+    #[test]
+    fn runs_trials_as_child_runs() {
+        let mock = MockMLFlowClient::default();
+        let builder = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .with_run_name("sweep");
+        let configs = [0.3, 0.1, 0.2, 0.5, 0.1].map(|learning_rate| Config { learning_rate });
+
+        let sweep = Sweep::new(builder)
+            .with_objective("val_loss", Objective::Minimize)
+            .with_concurrency(3)
+            .run(configs, |_, config| match config.learning_rate {
+                lr if lr > 0.4 => Err(InvalidMetric("diverged".to_string())),
+                lr => Ok(lr * 2.0),
+            })
+            .unwrap();
+
+        let learning_rates: Vec<f64> = sweep
+            .trials
+            .iter()
+            .map(|t| t.params.learning_rate)
+            .collect();
+        assert_eq!(learning_rates, vec![0.3, 0.1, 0.2, 0.5, 0.1]);
+        assert!(sweep.trials[3].score.is_err());
+        let best = sweep.best().unwrap();
+        assert_eq!(
+            best.run_id, sweep.trials[1].run_id,
+            "the first of equal scores"
+        );
+        assert_eq!(sweep.best_score(), Some(0.2));
+
+        let parent = RunLoader::default()
+            .with_mock_client(mock.clone())
+            .load(&sweep.parent_run_id)
+            .unwrap();
+        assert_eq!(parent.info().status, RunStatus::Finished);
+        assert_eq!(parent.data().tag(BEST_RUN_TAG), Some(best.run_id.as_str()));
+        assert_eq!(
+            parent.get_metric_history("best_val_loss").unwrap()[0].value,
+            0.2
+        );
+
+        let children = parent.children().unwrap();
+        assert_eq!(children.len(), 5);
+        let failed = children
+            .iter()
+            .find(|c| c.info().run_id == sweep.trials[3].run_id)
+            .unwrap();
+        assert_eq!(failed.info().status, RunStatus::Failed);
+        assert_eq!(failed.data().param("learning_rate"), Some("0.5"));
+        let finished = children
+            .iter()
+            .find(|c| c.info().run_id == best.run_id)
+            .unwrap();
+        assert_eq!(finished.info().status, RunStatus::Finished);
+        assert_eq!(
+            finished.get_metric_history("val_loss").unwrap()[0].value,
+            0.2
+        );
+    }
+
+    #[test]
+    fn maximizes_by_default() {
+        let sweep = Sweep::new(
+            RunBuilder::new("0")
+                .unwrap()
+                .with_mock_client(MockMLFlowClient::default()),
+        );
+        let configs = [1.0, f64::NAN, 3.0, 2.0, 3.0].map(|learning_rate| Config { learning_rate });
+        let result = sweep
+            .run(configs, |_, config| Ok(config.learning_rate))
+            .unwrap();
+        assert_eq!(result.best().unwrap().run_id, result.trials[2].run_id);
+        assert_eq!(result.best_score(), Some(3.0));
+
+        let empty = sweep
+            .run(Vec::<Config>::new(), |_, config| Ok(config.learning_rate))
+            .unwrap();
+        assert!(empty.best().is_none());
+    }
+
+    #[test]
+    fn fails_the_child_run_when_tracking_fails() {
+        #[derive(Serialize)]
+        struct Invalid {
+            #[serde(rename = "learning rate!")]
+            learning_rate: f64,
+        }
+
+        let mock = MockMLFlowClient::default();
+        let builder = RunBuilder::new("0").unwrap().with_mock_client(mock.clone());
+        let result = Sweep::new(builder).run([Invalid { learning_rate: 0.1 }], |_, _| {
+            panic!("the trial must not run when its params cannot be logged")
+        });
+        assert!(matches!(result, Err(MLFlowError::ValidationError(_))));
+
+        let runs: Vec<Run> = RunSearch::new(vec!["0"])
+            .unwrap()
+            .with_mock_client(mock)
+            .search()
+            .collect::<MLFlowResult<_>>()
+            .unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(|r| r.info().status == RunStatus::Failed));
+        assert!(runs.iter().all(|r| r.info().end_time.is_some()));
+    }
+
+    #[test]
+    fn fails_the_runs_when_a_trial_panics() {
+        let mock = MockMLFlowClient::default();
+        let builder = RunBuilder::new("0").unwrap().with_mock_client(mock.clone());
+        let configs = [0.1, 0.2, 0.3].map(|learning_rate| Config { learning_rate });
+        let sweep = Sweep::new(builder).with_concurrency(2);
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            sweep.run(configs, |_, config| {
+                if config.learning_rate == 0.2 {
+                    panic!("out of memory");
+                }
+                Ok(config.learning_rate)
+            })
+        }));
+        assert!(panicked.is_err());
+
+        let runs: Vec<Run> = RunSearch::new(vec!["0"])
+            .unwrap()
+            .with_mock_client(mock)
+            .search()
+            .collect::<MLFlowResult<_>>()
+            .unwrap();
+        assert_eq!(runs.len(), 4);
+        let failed = |run: &&Run| run.info().status == RunStatus::Failed;
+        let panicking = runs
+            .iter()
+            .find(|r| r.data().param("learning_rate") == Some("0.2"))
+            .unwrap();
+        assert!(failed(&panicking));
+        let parent = runs
+            .iter()
+            .find(|r| r.data().param("learning_rate").is_none())
+            .unwrap();
+        assert!(failed(&parent));
+        assert_eq!(runs.iter().filter(failed).count(), 2);
+        assert!(runs.iter().all(|r| r.info().end_time.is_some()));
+    }
+}
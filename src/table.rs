@@ -0,0 +1,213 @@
+//! Tables logged as run artifacts in the format `mlflow.log_table` writes, which the UI renders
+//! in its artifact and evaluation views.
+
+use crate::run::Run;
+use crate::tags::MLFLOW_LOGGED_ARTIFACTS;
+use crate::MLFlowError::InvalidTable;
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A table of named columns, stored row by row. Serializes to the pandas `split` orientation
+/// without an index, `{"columns": [...], "data": [[...], ...]}`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub data: Vec<Vec<Value>>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
+struct LoggedArtifact {
+    path: String,
+    #[serde(rename = "type")]
+    artifact_type: String,
+}
+
+impl Table {
+    /// An empty table with the given columns.
+    pub fn new(columns: Vec<impl Into<String>>) -> Table {
+        Table {
+            columns: columns.into_iter().map(Into::into).collect(),
+            data: vec![],
+        }
+    }
+
+    /// Appends a row, which must have a value for every column.
+    pub fn with_row(mut self, row: Vec<impl Into<Value>>) -> MLFlowResult<Self> {
+        if row.len() != self.columns.len() {
+            return Err(InvalidTable(format!(
+                "row has {} values but the table has {} columns",
+                row.len(),
+                self.columns.len()
+            )));
+        }
+        self.data.push(row.into_iter().map(Into::into).collect());
+        Ok(self)
+    }
+
+    /// A table with a row per record, e.g. a slice of structs deriving `Serialize`. Records
+    /// serialize to JSON objects whose keys become the columns, in alphabetical order; values
+    /// missing from a record are `null`.
+    pub fn from_records<T: Serialize>(records: &[T]) -> MLFlowResult<Table> {
+        let records = records
+            .iter()
+            .map(|r| match serde_json::to_value(r) {
+                Ok(Value::Object(record)) => Ok(record),
+                Ok(other) => Err(InvalidTable(format!("{} is not a record", other))),
+                Err(e) => Err(InvalidTable(e.to_string())),
+            })
+            .collect::<MLFlowResult<Vec<_>>>()?;
+
+        let mut columns: Vec<String> = records.iter().flat_map(|r| r.keys().cloned()).collect();
+        columns.sort();
+        columns.dedup();
+        let data = records
+            .iter()
+            .map(|r| {
+                columns
+                    .iter()
+                    .map(|c| r.get(c).cloned().unwrap_or(Value::Null))
+                    .collect()
+            })
+            .collect();
+        Ok(Table { columns, data })
+    }
+
+    /// A table with the columns and rows of the data frame.
+    #[cfg(feature = "polars")]
+    pub fn from_dataframe(df: &polars::prelude::DataFrame) -> MLFlowResult<Table> {
+        Ok(Table {
+            columns: crate::signature::dataframe_columns(df),
+            data: crate::signature::dataframe_rows(df, df.height())
+                .map_err(|e| InvalidTable(e.to_string()))?,
+        })
+    }
+}
+
+impl Run {
+    /// Uploads `table` as the JSON artifact file `artifact_file`, e.g. `eval/results.json`,
+    /// and lists it in the run's `mlflow.loggedArtifacts` tag so the UI shows it as a table.
+    /// An existing file is replaced.
+    pub fn log_table(&self, table: &Table, artifact_file: &str) -> MLFlowResult<()> {
+        if !artifact_file.ends_with(".json") {
+            return Err(InvalidTable(format!(
+                "artifact file {} of a table must end with .json",
+                artifact_file
+            )));
+        }
+        if let Some(row) = table.data.iter().find(|r| r.len() != table.columns.len()) {
+            return Err(InvalidTable(format!(
+                "row has {} values but the table has {} columns",
+                row.len(),
+                table.columns.len()
+            )));
+        }
+
+        let contents = serde_json::to_vec(table).map_err(|e| InvalidTable(e.to_string()))?;
+        self.log_bytes(&contents, artifact_file)?;
+
+        // read the tag from the server, other tables may have been logged since the run was loaded
+        let run = self.client.get_run(&self.info.run_id)?.run;
+        let mut logged: Vec<LoggedArtifact> = run
+            .data
+            .tag(MLFLOW_LOGGED_ARTIFACTS)
+            .and_then(|t| serde_json::from_str(t).ok())
+            .unwrap_or_default();
+        let artifact = LoggedArtifact {
+            path: artifact_file.trim_start_matches('/').to_string(),
+            artifact_type: "table".to_string(),
+        };
+        if !logged.contains(&artifact) {
+            logged.push(artifact);
+            let tag = serde_json::to_string(&logged).map_err(|e| InvalidTable(e.to_string()))?;
+            self.set_tag(MLFLOW_LOGGED_ARTIFACTS, tag)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_tables() {
+        let table = Table::new(vec!["question", "score"])
+            .with_row(vec![json!("why?"), json!(0.5)])
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&table).unwrap(),
+            json!({"columns": ["question", "score"], "data": [["why?", 0.5]]})
+        );
+        table
+            .with_row(vec![1])
+            .expect_err("InvalidTable: row has 1 values but the table has 2 columns");
+
+        #[derive(Serialize)]
+        struct Row {
+            score: f64,
+            answer: Option<&'static str>,
+        }
+        let table = Table::from_records(&[
+            Row {
+                score: 1.0,
+                answer: Some("yes"),
+            },
+            Row {
+                score: 0.0,
+                answer: None,
+            },
+        ])
+        .unwrap();
+        assert_eq!(table.columns, vec!["answer", "score"]);
+        assert_eq!(table.data[1], vec![Value::Null, json!(0.0)]);
+        Table::from_records(&[1, 2]).expect_err("InvalidTable: 1 is not a record");
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn from_dataframe() {
+        let df = polars::df!("id" => [1i64, 2], "label" => ["a", "b"]).unwrap();
+        let table = Table::from_dataframe(&df).unwrap();
+        assert_eq!(table.columns, vec!["id", "label"]);
+        assert_eq!(
+            table.data,
+            vec![vec![json!(1), json!("a")], vec![json!(2), json!("b")]]
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn log_table() {
+        use crate::{MockMLFlowClient, RunBuilder};
+
+        let mock = MockMLFlowClient::default();
+        let run = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let table = Table::new(vec!["x"]).with_row(vec![1]).unwrap();
+        run.log_table(&table, "eval/a.json").unwrap();
+        run.log_table(&table, "eval/b.json").unwrap();
+        run.log_table(&table, "eval/a.json").unwrap();
+        run.log_table(&table, "eval/a.csv")
+            .expect_err("InvalidTable: artifact file eval/a.csv of a table must end with .json");
+
+        let stored = mock.artifact(&run.info().run_id, "eval/a.json").unwrap();
+        assert_eq!(serde_json::from_slice::<Table>(&stored).unwrap(), table);
+        let run = crate::RunLoader::default()
+            .with_mock_client(mock)
+            .load(&run.info().run_id)
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(run.data().tag(MLFLOW_LOGGED_ARTIFACTS).unwrap())
+                .unwrap(),
+            json!([
+                {"path": "eval/a.json", "type": "table"},
+                {"path": "eval/b.json", "type": "table"}
+            ])
+        );
+    }
+}
@@ -0,0 +1,394 @@
+//! Instrumentation of the HTTP requests the client sends. With the `tracing` feature every request
+//! is recorded as an `mlflow.request` span with its method, endpoint, status code, latency and
+//! retry count; without it the helpers below are a plain `send`.
+//!
+//! Only the URL path is recorded as the endpoint, since the query string of presigned and SAS
+//! URLs carries credentials.
+//!
+//! Requests are also logged at debug level through the `log` facade: the method, the URL with
+//! secret query parameters redacted, the status and the latency. Clients built with verbose HTTP
+//! logging add the headers and JSON bodies, with credentials redacted.
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+
+/// Names, or parts of names, of headers, query parameters and JSON fields holding credentials.
+const SECRET_NAMES: &[&str] = &[
+    "authorization",
+    "cookie",
+    "password",
+    "secret",
+    "token",
+    "signature",
+    "credential",
+    "api-key",
+    "api_key",
+];
+
+/// Longest body logged, in characters.
+const MAX_LOGGED_BODY: usize = 4096;
+
+const REDACTED: &str = "[REDACTED]";
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    if name.ends_with("page_token") {
+        return false;
+    }
+    // `sig` is the signature of Azure SAS URLs
+    name == "sig" || SECRET_NAMES.iter().any(|secret| name.contains(secret))
+}
+
+/// `url` with the values of credential query parameters replaced by `[REDACTED]`.
+pub(crate) fn redact_url(url: &Url) -> String {
+    if url.query().is_none() {
+        return url.to_string();
+    }
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| match is_secret(&name) {
+            true => (name.into_owned(), REDACTED.to_string()),
+            false => (name.into_owned(), value.into_owned()),
+        })
+        .collect();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+fn redact_headers(headers: &HeaderMap) -> String {
+    let headers: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| match is_secret(name.as_str()) {
+            true => format!("{}: {}", name, REDACTED),
+            false => format!("{}: {}", name, value.to_str().unwrap_or("<binary>")),
+        })
+        .collect();
+    headers.join(", ")
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                match is_secret(name) && !value.is_object() && !value.is_array() {
+                    true => *value = serde_json::Value::String(REDACTED.to_string()),
+                    false => redact_json(value),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// A body for the log: JSON with credential fields redacted, other text as-is and binary
+/// content by its size, truncated to 4096 characters.
+pub(crate) fn redact_body(body: &[u8]) -> String {
+    let text = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => match std::str::from_utf8(body) {
+            Ok(text) => text.to_string(),
+            Err(_) => return format!("<{} bytes>", body.len()),
+        },
+    };
+    match text.char_indices().nth(MAX_LOGGED_BODY) {
+        Some((end, _)) => format!("{}... ({} bytes)", &text[..end], body.len()),
+        None => text,
+    }
+}
+
+fn log_request(
+    method: &Method,
+    url: &Url,
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+    verbose: bool,
+) {
+    log::debug!("{} {}", method, redact_url(url));
+    if verbose {
+        log::debug!("request headers: {}", redact_headers(headers));
+        if let Some(body) = body {
+            log::debug!("request body: {}", redact_body(body));
+        }
+    }
+}
+
+/// Measures the latency of a request. `Instant` panics on `wasm32`, where the JavaScript clock is
+/// read instead.
+#[derive(Clone, Copy)]
+struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    start: f64,
+}
+
+impl Stopwatch {
+    fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+            #[cfg(target_arch = "wasm32")]
+            start: js_sys::Date::now(),
+        }
+    }
+
+    fn elapsed_millis(&self) -> u64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed().as_millis() as u64;
+        #[cfg(target_arch = "wasm32")]
+        return (js_sys::Date::now() - self.start).max(0.0) as u64;
+    }
+}
+
+fn log_response(
+    method: &Method,
+    url: &str,
+    start: Stopwatch,
+    status: Result<StatusCode, &reqwest::Error>,
+) {
+    let elapsed = start.elapsed_millis();
+    match status {
+        Ok(status) => log::debug!("{} {} returned {} in {}ms", method, url, status, elapsed),
+        Err(e) => log::debug!("{} {} failed after {}ms: {}", method, url, elapsed, e),
+    }
+}
+
+/// Sends a blocking request inside an `mlflow.request` span.
+#[cfg(feature = "blocking")]
+pub(crate) trait InstrumentedSend {
+    /// Sends the request, logging its headers and body when `verbose`.
+    fn send_logged(self, verbose: bool) -> reqwest::Result<reqwest::blocking::Response>;
+
+    /// Sends the request as a retry of `retries` earlier failed attempts.
+    fn send_retry(self, retries: u32) -> reqwest::Result<reqwest::blocking::Response>;
+}
+
+#[cfg(feature = "blocking")]
+impl InstrumentedSend for reqwest::blocking::RequestBuilder {
+    fn send_logged(self, verbose: bool) -> reqwest::Result<reqwest::blocking::Response> {
+        send_blocking(self, 0, verbose)
+    }
+
+    fn send_retry(self, retries: u32) -> reqwest::Result<reqwest::blocking::Response> {
+        send_blocking(self, retries, false)
+    }
+}
+
+#[cfg(feature = "blocking")]
+fn send_blocking(
+    builder: reqwest::blocking::RequestBuilder,
+    retries: u32,
+    verbose: bool,
+) -> reqwest::Result<reqwest::blocking::Response> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+    let method = request.method().clone();
+    let url = redact_url(request.url());
+    log_request(
+        &method,
+        request.url(),
+        request.headers(),
+        request.body().and_then(|b| b.as_bytes()),
+        verbose,
+    );
+    let start = Stopwatch::start();
+    let span = RequestSpan::start(request.method(), request.url(), retries);
+    let result = span.in_scope(|| client.execute(request));
+    let status = result.as_ref().map(|r| r.status());
+    log_response(&method, &url, start, status);
+    span.finish(status);
+    result
+}
+
+/// Sends an async request inside an `mlflow.request` span, logging its headers and body when
+/// `verbose`.
+#[cfg(feature = "async")]
+pub(crate) async fn send_async(
+    request: reqwest::RequestBuilder,
+    verbose: bool,
+) -> reqwest::Result<reqwest::Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let method = request.method().clone();
+    let url = redact_url(request.url());
+    log_request(
+        &method,
+        request.url(),
+        request.headers(),
+        request.body().and_then(|b| b.as_bytes()),
+        verbose,
+    );
+    let start = Stopwatch::start();
+    let span = RequestSpan::start(request.method(), request.url(), 0);
+    let result = span.instrument(client.execute(request)).await;
+    let status = result.as_ref().map(|r| r.status());
+    log_response(&method, &url, start, status);
+    span.finish(status);
+    result
+}
+
+struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: Stopwatch,
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+impl RequestSpan {
+    fn start(method: &Method, url: &Url, retries: u32) -> RequestSpan {
+        RequestSpan {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "mlflow.request",
+                http.method = %method,
+                endpoint = url.path(),
+                retries,
+                status_code = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+            #[cfg(feature = "tracing")]
+            start: Stopwatch::start(),
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "tracing")]
+        return self.span.in_scope(f);
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+
+    #[cfg(feature = "async")]
+    fn instrument<F: std::future::Future>(
+        &self,
+        future: F,
+    ) -> impl std::future::Future<Output = F::Output> {
+        #[cfg(feature = "tracing")]
+        return tracing::Instrument::instrument(future, self.span.clone());
+        #[cfg(not(feature = "tracing"))]
+        future
+    }
+
+    fn finish(self, status: Result<StatusCode, &reqwest::Error>) {
+        #[cfg(feature = "tracing")]
+        {
+            let _entered = self.span.enter();
+            self.span.record("latency_ms", self.start.elapsed_millis());
+            match status {
+                Ok(status) => {
+                    self.span.record("status_code", status.as_u16());
+                    if !status.is_success() {
+                        tracing::debug!(%status, "request was not successful");
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "request failed"),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Collects the fields recorded on spans as `name=value` strings.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn records_request_fields() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let url = Url::parse("http://localhost:5000/api/2.0/mlflow/runs/get?run_id=x").unwrap();
+            let span = RequestSpan::start(&Method::GET, &url, 2);
+            span.finish(Ok(StatusCode::NOT_FOUND));
+        });
+
+        let fields = recorder.0.lock().unwrap().clone();
+        assert!(fields.contains(&"http.method=GET".to_string()));
+        assert!(fields.contains(&"endpoint=\"/api/2.0/mlflow/runs/get\"".to_string()));
+        assert!(fields.contains(&"retries=2".to_string()));
+        assert!(fields.contains(&"status_code=404".to_string()));
+        assert!(fields.iter().any(|f| f.starts_with("latency_ms=")));
+    }
+}
+
+#[cfg(test)]
+mod redaction_tests {
+    use super::*;
+
+    #[test]
+    fn redacted_urls() {
+        let url = Url::parse(
+            "https://bucket.s3.amazonaws.com/a?X-Amz-Signature=abc&X-Amz-Credential=key&part=1",
+        )
+        .unwrap();
+        assert_eq!(
+            redact_url(&url),
+            "https://bucket.s3.amazonaws.com/a?X-Amz-Signature=%5BREDACTED%5D&\
+             X-Amz-Credential=%5BREDACTED%5D&part=1"
+        );
+        let url = Url::parse("http://localhost:5000/api/2.0/mlflow/runs/get?run_id=1").unwrap();
+        assert_eq!(redact_url(&url), url.as_str());
+    }
+
+    #[test]
+    fn redacted_bodies() {
+        let body = br#"{"username":"alice","password":"hunter2","page_token":"p2",
+            "auth":{"access_token":"t"}}"#;
+        let redacted = redact_body(body);
+        assert!(!redacted.contains("hunter2"), "{}", redacted);
+        assert!(redacted.contains(r#""username":"alice""#), "{}", redacted);
+        assert!(redacted.contains(r#""page_token":"p2""#), "{}", redacted);
+        assert!(
+            redacted.contains(r#""access_token":"[REDACTED]""#),
+            "{}",
+            redacted
+        );
+        assert_eq!(redact_body(&[0xff, 0xfe]), "<2 bytes>");
+        assert!(redact_body("x".repeat(MAX_LOGGED_BODY + 1).as_bytes()).ends_with("(4097 bytes)"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Ending runs when the process dies before their [`ActiveRun`](crate::ActiveRun) guards are
+//! dropped, so preempted jobs don't leave runs `RUNNING` forever.
+//!
+//! [`ActiveRun`](crate::ActiveRun) guards and [`BufferedLogger`](crate::BufferedLogger)s register
+//! themselves here while they are alive. [`install_termination_hook`] flushes the loggers and
+//! ends the runs when the process is interrupted or panics.
+
+use crate::entities::{RunInfo, RunStatus};
+use crate::run::Run;
+use crate::MLFlowResult;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, Once};
+
+type FlushFn = Box<dyn Fn() + Send>;
+
+static NEXT_REGISTRATION: AtomicU64 = AtomicU64::new(0);
+static ACTIVE_RUNS: Mutex<Vec<(u64, Run)>> = Mutex::new(Vec::new());
+static LOGGERS: Mutex<Vec<(u64, FlushFn)>> = Mutex::new(Vec::new());
+static INSTALL: Once = Once::new();
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn next_registration() -> u64 {
+    NEXT_REGISTRATION.fetch_add(1, Ordering::Relaxed)
+}
+
+pub(crate) fn register_run(run: &Run) -> u64 {
+    let registration = next_registration();
+    lock(&ACTIVE_RUNS).push((registration, run.clone()));
+    registration
+}
+
+/// Keeps the registered copies of the run `info` belongs to up to date, so a run its guard's
+/// holder already ended isn't ended again with another status.
+pub(crate) fn update_registered_run(info: &RunInfo) {
+    for (_, run) in lock(&ACTIVE_RUNS).iter_mut() {
+        if run.info.run_id == info.run_id {
+            run.info = info.clone();
+        }
+    }
+}
+
+/// Forgets a run once its guard ends it. Returns `false` if the run was already ended by
+/// [`terminate_active_runs`].
+pub(crate) fn deregister_run(registration: u64) -> bool {
+    let mut runs = lock(&ACTIVE_RUNS);
+    let before = runs.len();
+    runs.retain(|(r, _)| *r != registration);
+    runs.len() != before
+}
+
+pub(crate) fn register_logger(flush: impl Fn() + Send + 'static) -> u64 {
+    let registration = next_registration();
+    lock(&LOGGERS).push((registration, Box::new(flush)));
+    registration
+}
+
+pub(crate) fn deregister_logger(registration: u64) {
+    lock(&LOGGERS).retain(|(r, _)| *r != registration);
+}
+
+/// Flushes every live [`BufferedLogger`](crate::BufferedLogger), then ends every run held by an
+/// [`ActiveRun`](crate::ActiveRun) guard with `status`. Returns the number of runs ended.
+///
+/// The guards are left in place but won't end their runs again when dropped.
+pub fn terminate_active_runs(status: RunStatus) -> usize {
+    terminate_runs(status, |_| true)
+}
+
+pub(crate) fn terminate_runs(status: RunStatus, filter: impl Fn(&Run) -> bool) -> usize {
+    for (_, flush) in lock(&LOGGERS).iter() {
+        flush();
+    }
+
+    let runs: Vec<Run> = {
+        let mut registered = lock(&ACTIVE_RUNS);
+        let (ended, kept) = std::mem::take(&mut *registered)
+            .into_iter()
+            .partition(|(_, run)| filter(run));
+        *registered = kept;
+        ended.into_iter().map(|(_, run)| run).collect()
+    };
+    let mut ended = 0;
+    for mut run in runs
+        .into_iter()
+        .filter(|run| !run.info.status.is_terminal())
+    {
+        match run.finish(status) {
+            Ok(()) => ended += 1,
+            Err(e) => log::warn!(
+                "could not end run {} with status {:?}: {}",
+                run.info.run_id,
+                status,
+                e
+            ),
+        }
+    }
+    ended
+}
+
+/// Installs a panic hook, and with the `signals` feature a SIGINT/SIGTERM/SIGHUP handler, that
+/// call [`terminate_active_runs`] before the process goes away.
+///
+/// - On a signal the runs are marked `KILLED` and the process exits with status 130.
+/// - On a panic of the main thread, or any panic when built with `panic = "abort"`, the runs are
+///   marked `FAILED`, then the previous panic hook runs. Panics of other threads only flush the
+///   buffered loggers, since the process may outlive them.
+///
+/// Installing the hook more than once has no effect.
+///
+/// ```no_run
+/// use mlflow_rs::{install_termination_hook, RunBuilder};
+///
+/// install_termination_hook().unwrap();
+/// let run = RunBuilder::new("1").unwrap().start().unwrap();
+/// // a Ctrl-C from here on marks the run KILLED instead of leaving it RUNNING
+/// ```
+pub fn install_termination_hook() -> MLFlowResult<()> {
+    let mut result = Ok(());
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let main_thread = std::thread::current().name() == Some("main");
+            if cfg!(panic = "abort") || main_thread {
+                terminate_active_runs(RunStatus::Failed);
+            } else {
+                for (_, flush) in lock(&LOGGERS).iter() {
+                    flush();
+                }
+            }
+            previous(info);
+        }));
+        result = install_signal_handler();
+    });
+    result
+}
+
+#[cfg(feature = "signals")]
+fn install_signal_handler() -> MLFlowResult<()> {
+    ctrlc::set_handler(|| {
+        terminate_active_runs(RunStatus::Killed);
+        std::process::exit(130);
+    })
+    .map_err(|e| {
+        crate::MLFlowError::UnknownError(format!("could not install signal handler: {}", e))
+    })
+}
+
+#[cfg(not(feature = "signals"))]
+fn install_signal_handler() -> MLFlowResult<()> {
+    Ok(())
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{MockMLFlowClient, RunBuilder, RunIdentifier, RunLoader};
+
+    #[test]
+    fn terminated_runs() {
+        let mock = MockMLFlowClient::default();
+        let active = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .start()
+            .unwrap();
+        let logger = active.buffered_logger().unwrap();
+        logger.log_metric("loss", 0.5, Some(1), None).unwrap();
+        let run_id = active.run_id();
+
+        let ended = terminate_runs(RunStatus::Killed, |run| run.run_id() == run_id);
+        assert_eq!(ended, 1);
+        drop(active);
+
+        let run = RunLoader::default()
+            .with_mock_client(mock)
+            .load(&run_id)
+            .unwrap();
+        assert_eq!(run.status(), RunStatus::Killed, "the guard kept the status");
+        assert_eq!(run.data().metrics.len(), 1, "buffered metrics were flushed");
+    }
+
+    #[test]
+    fn runs_ended_through_their_guard_are_not_ended_again() {
+        let mock = MockMLFlowClient::default();
+        let mut active = RunBuilder::new("0")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .start()
+            .unwrap();
+        active.finish(RunStatus::Finished).unwrap();
+        let run_id = active.run_id();
+
+        let ended = terminate_runs(RunStatus::Killed, |run| run.run_id() == run_id);
+        assert_eq!(ended, 0);
+        drop(active);
+
+        let run = RunLoader::default()
+            .with_mock_client(mock)
+            .load(&run_id)
+            .unwrap();
+        assert_eq!(run.status(), RunStatus::Finished);
+    }
+}
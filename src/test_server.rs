@@ -0,0 +1,64 @@
+//! A raw TCP server answering with canned JSON, to test the REST client against.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::thread::JoinHandle;
+
+/// A request received by [`serve`].
+pub(crate) struct Request {
+    /// The request line, e.g. `GET /version HTTP/1.1`.
+    pub line: String,
+    /// The headers, lowercased.
+    pub headers: Vec<String>,
+    pub body: String,
+}
+
+/// A server answering one request per connection with each of `responses` in turn, as a status
+/// and JSON body, closing the connection without an answer for `None`. Yields the requests.
+pub(crate) fn serve(responses: Vec<Option<(u16, String)>>) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = vec![];
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut content_length = 0;
+            let mut headers = vec![];
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                let header = header.trim().to_lowercase();
+                if let Some(len) = header.strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+                headers.push(header);
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            requests.push(Request {
+                line: line.trim().to_string(),
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            });
+
+            if let Some((status, body)) = response {
+                write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        }
+        requests
+    });
+    (host, handle)
+}
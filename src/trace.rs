@@ -0,0 +1,542 @@
+//! GenAI traces (MLflow 2.14+), shown in the Traces tab of an experiment.
+//!
+//! A trace is created on the tracking server when it starts. Its spans are collected
+//! client side and, when the trace ends, uploaded as `traces.json` to the trace's
+//! `mlflow.artifactLocation` in the format the MLflow UI reads.
+//!
+//! ```no_run
+//! use mlflow_rs::{SpanType, TraceBuilder, TraceStatus};
+//! use serde_json::json;
+//!
+//! let mut trace = TraceBuilder::new("0").unwrap().with_name("qa").start().unwrap();
+//! let root = trace
+//!     .start_span("qa", SpanType::Chain, None)
+//!     .with_inputs(json!({"question": "What is MLflow?"}));
+//! let llm = trace
+//!     .start_span("chat", SpanType::ChatModel, Some(&root))
+//!     .with_attribute("model", "gpt-4o")
+//!     .with_outputs("An ML lifecycle platform");
+//! trace.log_span(llm);
+//! trace.log_span(root.with_outputs(json!({"answer": "An ML lifecycle platform"})));
+//! trace.end(TraceStatus::Ok).unwrap();
+//! ```
+
+use crate::artifacts::artifact_repository;
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{EndTraceRequest, MLFlowClient, SharedClient, StartTraceRequest};
+use crate::config::default_client;
+use crate::entities::{TraceInfo, TraceRequestMetadata, TraceStatus, TraceTag};
+use crate::run::current_time_millis;
+use crate::tags::{MLFLOW_ARTIFACT_LOCATION, MLFLOW_TRACE_NAME};
+use crate::MLFlowError::{ArtifactError, InvalidState, InvalidTag, TraceBuilderError};
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the artifact holding a trace's spans.
+pub(crate) const TRACE_DATA_FILE_NAME: &str = "traces.json";
+
+/// Previews of a trace's inputs and outputs are truncated to this many characters.
+const MAX_PREVIEW_LENGTH: usize = 250;
+
+const TRACE_INPUTS: &str = "mlflow.traceInputs";
+const TRACE_OUTPUTS: &str = "mlflow.traceOutputs";
+const TRACE_SCHEMA_VERSION: &str = "mlflow.trace_schema.version";
+const SPAN_REQUEST_ID: &str = "mlflow.traceRequestId";
+const SPAN_TYPE: &str = "mlflow.spanType";
+const SPAN_INPUTS: &str = "mlflow.spanInputs";
+const SPAN_OUTPUTS: &str = "mlflow.spanOutputs";
+
+fn current_time_nanos() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or_default()
+}
+
+/// What a span represents, used by the UI to pick its icon and how to render its inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SpanType {
+    Llm,
+    ChatModel,
+    Chain,
+    Agent,
+    Tool,
+    Retriever,
+    Embedding,
+    Parser,
+    Reranker,
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpanStatus {
+    Unset,
+    Ok,
+    Error(String),
+}
+
+/// Something that happened during a span, e.g. an exception. `timestamp` is in nanoseconds
+/// since the UNIX epoch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpanEvent {
+    pub name: String,
+    pub timestamp: i64,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, Value>,
+}
+
+impl SpanEvent {
+    pub fn new(name: impl AsRef<str>) -> SpanEvent {
+        SpanEvent {
+            name: name.as_ref().to_string(),
+            timestamp: current_time_nanos(),
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_attribute(mut self, key: impl AsRef<str>, value: impl Into<Value>) -> Self {
+        self.attributes
+            .insert(key.as_ref().to_string(), value.into());
+        self
+    }
+}
+
+/// An operation within a trace, e.g. a call to a model or a retriever. Spans are created with
+/// [`Trace::start_span`] and recorded with [`Trace::log_span`].
+#[derive(Clone, Debug)]
+pub struct Span {
+    pub(crate) name: String,
+    pub(crate) span_id: String,
+    pub(crate) parent_id: Option<String>,
+    pub(crate) span_type: SpanType,
+    pub(crate) start_time_ns: i64,
+    pub(crate) end_time_ns: Option<i64>,
+    pub(crate) status: SpanStatus,
+    pub(crate) inputs: Option<Value>,
+    pub(crate) outputs: Option<Value>,
+    pub(crate) attributes: BTreeMap<String, Value>,
+    pub(crate) events: Vec<SpanEvent>,
+}
+
+impl Span {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    pub fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+
+    pub fn span_type(&self) -> SpanType {
+        self.span_type
+    }
+
+    pub fn inputs(&self) -> Option<&Value> {
+        self.inputs.as_ref()
+    }
+
+    pub fn outputs(&self) -> Option<&Value> {
+        self.outputs.as_ref()
+    }
+
+    pub fn status(&self) -> &SpanStatus {
+        &self.status
+    }
+
+    pub fn with_inputs(mut self, inputs: impl Into<Value>) -> Self {
+        self.inputs = Some(inputs.into());
+        self
+    }
+
+    pub fn with_outputs(mut self, outputs: impl Into<Value>) -> Self {
+        self.outputs = Some(outputs.into());
+        self
+    }
+
+    pub fn with_attribute(mut self, key: impl AsRef<str>, value: impl Into<Value>) -> Self {
+        self.attributes
+            .insert(key.as_ref().to_string(), value.into());
+        self
+    }
+
+    pub fn with_event(mut self, event: SpanEvent) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn with_status(mut self, status: SpanStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Records the current time as the end of the span. Spans logged without an end time are
+    /// ended when they are logged.
+    pub fn end(mut self) -> Self {
+        self.end_time_ns = Some(current_time_nanos());
+        self
+    }
+
+    /// The span as serialized in `traces.json`. Attribute values are JSON encoded strings.
+    fn to_json(&self, trace_id: &str, request_id: &str) -> Value {
+        let mut attributes = Map::new();
+        let mut attribute = |key: &str, value: &Value| {
+            attributes.insert(key.to_string(), Value::String(value.to_string()));
+        };
+        attribute(SPAN_REQUEST_ID, &Value::from(request_id));
+        attribute(
+            SPAN_TYPE,
+            &serde_json::to_value(self.span_type).unwrap_or_default(),
+        );
+        if let Some(inputs) = &self.inputs {
+            attribute(SPAN_INPUTS, inputs);
+        }
+        if let Some(outputs) = &self.outputs {
+            attribute(SPAN_OUTPUTS, outputs);
+        }
+        for (key, value) in &self.attributes {
+            attribute(key, value);
+        }
+
+        let (status_code, status_message) = match &self.status {
+            SpanStatus::Unset => ("UNSET", ""),
+            SpanStatus::Ok => ("OK", ""),
+            SpanStatus::Error(message) => ("ERROR", message.as_str()),
+        };
+        serde_json::json!({
+            "name": self.name,
+            "context": {"span_id": self.span_id, "trace_id": trace_id},
+            "parent_id": self.parent_id,
+            "start_time": self.start_time_ns,
+            "end_time": self.end_time_ns,
+            "status_code": status_code,
+            "status_message": status_message,
+            "attributes": attributes,
+            "events": self.events,
+        })
+    }
+}
+
+/// A trace in progress. End it with [`Trace::end`] to upload its spans and mark it finished.
+#[derive(Debug)]
+pub struct Trace {
+    info: TraceInfo,
+    pub(crate) trace_id: String,
+    spans: Vec<Span>,
+    client: SharedClient,
+}
+
+impl Trace {
+    pub fn info(&self) -> &TraceInfo {
+        &self.info
+    }
+
+    /// The id the tracking server identifies the trace by.
+    pub fn request_id(&self) -> &str {
+        &self.info.request_id
+    }
+
+    /// The spans logged so far, in the order they were logged.
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Starts a span at the current time, nested under `parent` or at the root of the trace.
+    pub fn start_span(
+        &self,
+        name: impl AsRef<str>,
+        span_type: SpanType,
+        parent: Option<&Span>,
+    ) -> Span {
+        Span {
+            name: name.as_ref().to_string(),
+            span_id: format!("0x{}", &uuid::Uuid::new_v4().simple().to_string()[..16]),
+            parent_id: parent.map(|p| p.span_id.clone()),
+            span_type,
+            start_time_ns: current_time_nanos(),
+            end_time_ns: None,
+            status: SpanStatus::Unset,
+            inputs: None,
+            outputs: None,
+            attributes: BTreeMap::new(),
+            events: vec![],
+        }
+    }
+
+    /// Records a finished span, ending it now unless [`Span::end`] was called.
+    pub fn log_span(&mut self, mut span: Span) {
+        if span.end_time_ns.is_none() {
+            span.end_time_ns = Some(current_time_nanos());
+        }
+        self.spans.push(span);
+    }
+
+    pub fn set_tag(&mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> MLFlowResult<()> {
+        if key.as_ref().is_empty() {
+            return Err(InvalidTag("tag key cannot be empty".to_string()));
+        }
+
+        let tag = TraceTag::from((key.as_ref(), value.as_ref()));
+        self.client.set_trace_tag(&self.info.request_id, &tag)?;
+        self.info.tags.retain(|t| t.key != tag.key);
+        self.info.tags.push(tag);
+        Ok(())
+    }
+
+    /// Uploads the logged spans and finishes the trace with `status`. The inputs and outputs of
+    /// the root span are kept as previews in the trace's request metadata.
+    pub fn end(&mut self, status: TraceStatus) -> MLFlowResult<()> {
+        self.end_at(status, current_time_millis())
+    }
+
+    pub(crate) fn end_at(&mut self, status: TraceStatus, timestamp_ms: i64) -> MLFlowResult<()> {
+        if self.info.status != TraceStatus::InProgress {
+            return Err(InvalidState(format!(
+                "trace {} has already ended",
+                self.info.request_id
+            )));
+        }
+
+        self.upload_spans()?;
+
+        let root = self.spans.iter().find(|s| s.parent_id.is_none());
+        let mut request_metadata = vec![];
+        if let Some(inputs) = root.and_then(|r| r.inputs.as_ref()) {
+            request_metadata.push(TraceRequestMetadata::from((
+                TRACE_INPUTS,
+                preview(inputs).as_str(),
+            )));
+        }
+        if let Some(outputs) = root.and_then(|r| r.outputs.as_ref()) {
+            request_metadata.push(TraceRequestMetadata::from((
+                TRACE_OUTPUTS,
+                preview(outputs).as_str(),
+            )));
+        }
+
+        let request = EndTraceRequest {
+            request_id: self.info.request_id.clone(),
+            timestamp_ms,
+            status,
+            request_metadata,
+            tags: vec![],
+        };
+        self.info = self.client.end_trace(request)?.trace_info;
+        Ok(())
+    }
+
+    fn upload_spans(&self) -> MLFlowResult<()> {
+        let artifact_location = self.info.tag(MLFLOW_ARTIFACT_LOCATION).ok_or_else(|| {
+            ArtifactError(format!(
+                "trace {} has no artifact location",
+                self.info.request_id
+            ))
+        })?;
+        let spans: Vec<Value> = self
+            .spans
+            .iter()
+            .map(|s| s.to_json(&self.trace_id, &self.info.request_id))
+            .collect();
+        let data = serde_json::json!({ "spans": spans }).to_string();
+
+        let dir = std::env::temp_dir().join(format!("mlflow-rs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).map_err(|e| ArtifactError(e.to_string()))?;
+        let file = dir.join(TRACE_DATA_FILE_NAME);
+        let result = std::fs::write(&file, data)
+            .map_err(|e| ArtifactError(e.to_string()))
+            .and_then(|_| artifact_repository(artifact_location, &self.client, None))
+            .and_then(|repository| repository.log_artifact(&file, TRACE_DATA_FILE_NAME));
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+}
+
+/// The JSON encoding of `value`, cut down to fit a request metadata value.
+fn preview(value: &Value) -> String {
+    value.to_string().chars().take(MAX_PREVIEW_LENGTH).collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceBuilder {
+    experiment_id: String,
+    start_time: Option<i64>,
+    tags: Vec<TraceTag>,
+    client: SharedClient,
+}
+
+impl TraceBuilder {
+    pub fn new(experiment_id: impl AsRef<str>) -> MLFlowResult<TraceBuilder> {
+        if experiment_id.as_ref().is_empty() {
+            return Err(TraceBuilderError(
+                "experiment_id cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(TraceBuilder {
+            experiment_id: experiment_id.as_ref().to_string(),
+            start_time: None,
+            tags: vec![],
+            client: default_client()?,
+        })
+    }
+
+    /// Names the trace in the Traces UI by setting the `mlflow.traceName` tag.
+    pub fn with_name(self, name: impl AsRef<str>) -> Self {
+        self.with_tag((MLFLOW_TRACE_NAME, name.as_ref()))
+    }
+
+    /// Starts the trace at `start_time` milliseconds since the UNIX epoch instead of now.
+    pub fn with_start_time(mut self, start_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<TraceTag>) -> Self {
+        let tag = tag.into();
+        self.tags.retain(|t| t.key != tag.key);
+        self.tags.push(tag);
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    pub(crate) fn with_client(mut self, client: SharedClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Creates the trace on the tracking server, starting it at the current time.
+    pub fn start(self) -> MLFlowResult<Trace> {
+        let request = StartTraceRequest {
+            experiment_id: self.experiment_id,
+            timestamp_ms: self.start_time.unwrap_or_else(current_time_millis),
+            request_metadata: vec![TraceRequestMetadata::from((TRACE_SCHEMA_VERSION, "2"))],
+            tags: self.tags,
+        };
+
+        let resp = self.client.start_trace(request)?;
+        Ok(Trace {
+            info: resp.trace_info,
+            trace_id: format!("0x{}", uuid::Uuid::new_v4().simple()),
+            spans: vec![],
+            client: self.client,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_json() {
+        let mut trace = Trace {
+            info: serde_json::from_value(serde_json::json!({
+                "request_id": "tr-1",
+                "experiment_id": "0",
+                "timestamp_ms": 0,
+                "status": "IN_PROGRESS",
+            }))
+            .unwrap(),
+            trace_id: "0x00".to_string(),
+            spans: vec![],
+            client: SharedClient::default(),
+        };
+        let root = trace.start_span("qa", SpanType::Chain, None);
+        let llm = trace
+            .start_span("chat", SpanType::Llm, Some(&root))
+            .with_inputs(serde_json::json!({"prompt": "hi"}))
+            .with_attribute("temperature", 0.5)
+            .with_status(SpanStatus::Error("rate limited".to_string()));
+        trace.log_span(llm);
+
+        let json = trace.spans()[0].to_json("0x00", "tr-1");
+        assert_eq!(json["parent_id"], Value::from(root.span_id()));
+        assert_eq!(json["context"]["trace_id"], "0x00");
+        assert_eq!(json["status_code"], "ERROR");
+        assert_eq!(json["status_message"], "rate limited");
+        assert_eq!(json["attributes"]["mlflow.spanType"], "\"LLM\"");
+        assert_eq!(json["attributes"]["mlflow.traceRequestId"], "\"tr-1\"");
+        assert_eq!(
+            json["attributes"]["mlflow.spanInputs"],
+            r#"{"prompt":"hi"}"#
+        );
+        assert_eq!(json["attributes"]["temperature"], "0.5");
+        assert!(json["end_time"].as_i64().unwrap() >= json["start_time"].as_i64().unwrap());
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn trace_lifecycle() {
+        use crate::MockMLFlowClient;
+
+        let mock = MockMLFlowClient::default();
+        let mut trace = TraceBuilder::new("0")
+            .unwrap()
+            .with_name("qa")
+            .with_mock_client(mock.clone())
+            .start()
+            .unwrap();
+        assert_eq!(trace.info().status, TraceStatus::InProgress);
+        assert_eq!(trace.info().tag(MLFLOW_TRACE_NAME), Some("qa"));
+
+        let root = trace
+            .start_span("qa", SpanType::Chain, None)
+            .with_inputs(serde_json::json!({"question": "x".repeat(300)}));
+        let retriever = trace
+            .start_span("retrieve", SpanType::Retriever, Some(&root))
+            .with_outputs(serde_json::json!(["doc"]));
+        trace.log_span(retriever);
+        trace.log_span(root.with_outputs("answer"));
+        trace.set_tag("user", "someone").unwrap();
+        trace.end(TraceStatus::Ok).unwrap();
+
+        let info = mock.trace_info(trace.request_id()).unwrap();
+        assert_eq!(info.status, TraceStatus::Ok);
+        assert!(info.execution_time_ms.is_some());
+        assert_eq!(info.tag("user"), Some("someone"));
+        assert_eq!(
+            info.request_metadata(TRACE_INPUTS).map(|v| v.len()),
+            Some(MAX_PREVIEW_LENGTH)
+        );
+        assert_eq!(info.request_metadata(TRACE_OUTPUTS), Some("\"answer\""));
+
+        let location = info.tag(MLFLOW_ARTIFACT_LOCATION).unwrap();
+        assert!(location.ends_with(&format!("/traces/{}/artifacts", trace.request_id())));
+        let data = mock.trace_data(trace.request_id()).unwrap();
+        let data: Value = serde_json::from_slice(&data).unwrap();
+        assert_eq!(data["spans"].as_array().unwrap().len(), 2);
+
+        trace
+            .end(TraceStatus::Ok)
+            .expect_err("a trace can only end once");
+    }
+}
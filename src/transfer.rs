@@ -0,0 +1,387 @@
+//! Artifact uploads and downloads with progress reporting and concurrent transfers.
+//!
+//! Files are streamed from and to disk, so artifacts of any size can be transferred without
+//! holding them in memory.
+//!
+//! ```no_run
+//! use mlflow_rs::RunBuilder;
+//!
+//! let run = RunBuilder::new("1").unwrap().build().unwrap();
+//! run.artifact_transfer()
+//!     .with_progress(|p| {
+//!         println!("{}: {} of {:?} bytes", p.artifact_path, p.bytes_transferred, p.total_bytes)
+//!     })
+//!     .with_concurrency(8)
+//!     .log_artifacts("checkpoints", Some("checkpoints"))
+//!     .unwrap();
+//! ```
+
+use crate::artifacts::{collect_files, join_artifact_path, local_artifact_path};
+use crate::run::Run;
+use crate::MLFlowError::ArtifactError;
+use crate::MLFlowResult;
+use std::fmt::{Debug, Formatter};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Called with the number of bytes just transferred, as a transfer makes progress.
+pub type ProgressCallback = Arc<dyn Fn(u64) + Send + Sync>;
+
+type FileProgressCallback = Arc<dyn Fn(&TransferProgress) + Send + Sync>;
+
+/// How far the transfer of one file has got.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Path of the file relative to the run's artifact root.
+    pub artifact_path: String,
+    pub bytes_transferred: u64,
+    /// Size of the file, when known before the transfer starts.
+    pub total_bytes: Option<u64>,
+}
+
+/// Reports the bytes read through it to a [`ProgressCallback`].
+pub(crate) struct ProgressReader<R> {
+    inner: R,
+    progress: ProgressCallback,
+}
+
+impl<R: Read> ProgressReader<R> {
+    pub(crate) fn new(inner: R, progress: ProgressCallback) -> Self {
+        ProgressReader { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            (self.progress)(read as u64);
+        }
+        Ok(read)
+    }
+}
+
+/// Uploads and downloads of a run's artifacts, built from [`Run::artifact_transfer`].
+#[derive(Clone)]
+pub struct ArtifactTransfer<'a> {
+    run: &'a Run,
+    progress: Option<FileProgressCallback>,
+    concurrency: usize,
+}
+
+impl Debug for ArtifactTransfer<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArtifactTransfer")
+            .field("run", &self.run.info.run_id)
+            .field("progress", &self.progress.is_some())
+            .field("concurrency", &self.concurrency)
+            .finish()
+    }
+}
+
+impl<'a> ArtifactTransfer<'a> {
+    /// Calls `progress` as each file is transferred. It may be called from several threads and
+    /// should return quickly.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&TransferProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Transfers up to `concurrency` files at once when uploading or downloading a directory.
+    /// Defaults to one file at a time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Calls `transfer` for each item on up to `concurrency` threads, stopping at the first
+    /// error.
+    fn for_each_concurrent<T: Send>(
+        &self,
+        items: Vec<T>,
+        transfer: impl Fn(T) -> MLFlowResult<()> + Sync,
+    ) -> MLFlowResult<()> {
+        for_each_concurrent(self.concurrency, items, transfer)
+    }
+
+    fn file_progress(&self, artifact_path: &str, total_bytes: Option<u64>) -> ProgressCallback {
+        let Some(progress) = self.progress.clone() else {
+            return Arc::new(|_| {});
+        };
+        let artifact_path = artifact_path.to_string();
+        let transferred = AtomicU64::new(0);
+        Arc::new(move |bytes| {
+            let bytes_transferred = transferred.fetch_add(bytes, Ordering::Relaxed) + bytes;
+            progress(&TransferProgress {
+                artifact_path: artifact_path.clone(),
+                bytes_transferred,
+                total_bytes,
+            })
+        })
+    }
+
+    /// Uploads a local file into the run's artifacts, under the optional `artifact_path`
+    /// directory.
+    pub fn log_artifact(
+        &self,
+        local_path: impl AsRef<Path>,
+        artifact_path: Option<&str>,
+    ) -> MLFlowResult<()> {
+        let local_path = local_path.as_ref();
+        let file_name = local_path
+            .file_name()
+            .ok_or_else(|| {
+                ArtifactError(format!("{} is not a file", local_path.to_string_lossy()))
+            })?
+            .to_string_lossy();
+
+        let destination = join_artifact_path(artifact_path.unwrap_or(""), &file_name)?;
+        let size = std::fs::metadata(local_path).ok().map(|m| m.len());
+        self.run.artifact_repository()?.log_artifact_with_progress(
+            local_path,
+            &destination,
+            self.file_progress(&destination, size),
+        )
+    }
+
+    /// Uploads the contents of a local directory into the run's artifacts, preserving the
+    /// directory structure, under the optional `artifact_path` directory.
+    pub fn log_artifacts(
+        &self,
+        local_dir: impl AsRef<Path>,
+        artifact_path: Option<&str>,
+    ) -> MLFlowResult<()> {
+        let local_dir = local_dir.as_ref();
+        if !local_dir.is_dir() {
+            return Err(ArtifactError(format!(
+                "{} is not a directory",
+                local_dir.to_string_lossy()
+            )));
+        }
+
+        let repository = self.run.artifact_repository()?;
+        // validates the path before anything is uploaded
+        join_artifact_path("", artifact_path.unwrap_or(""))?;
+        self.for_each_concurrent(collect_files(local_dir)?, |(file, relative)| {
+            let destination = join_artifact_path(artifact_path.unwrap_or(""), &relative)?;
+            let size = std::fs::metadata(&file).ok().map(|m| m.len());
+            repository.log_artifact_with_progress(
+                &file,
+                &destination,
+                self.file_progress(&destination, size),
+            )
+        })
+    }
+
+    /// Downloads a file or, recursively, a directory of the run's artifacts into `dest`,
+    /// preserving `artifact_path` below it. Returns the local path of the downloaded artifact.
+    pub fn download_artifact(
+        &self,
+        artifact_path: &str,
+        dest: impl AsRef<Path>,
+    ) -> MLFlowResult<PathBuf> {
+        let repository = self.run.artifact_repository()?;
+        // validates the path before anything touches the local filesystem
+        join_artifact_path("", artifact_path)?;
+
+        let local_path = local_artifact_path(dest.as_ref(), artifact_path);
+        let mut pending = vec![(artifact_path.to_string(), None)];
+        let mut files = vec![];
+
+        // lists the whole tree first so the files can be downloaded concurrently
+        while let Some((current, size)) = pending.pop() {
+            let listing = repository.list_artifacts(Some(&current))?;
+            let local = local_artifact_path(dest.as_ref(), &current);
+
+            if listing.is_empty() && !current.is_empty() {
+                if let Some(parent) = local.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| ArtifactError(e.to_string()))?;
+                }
+                files.push((current, local, size));
+            } else {
+                std::fs::create_dir_all(&local).map_err(|e| ArtifactError(e.to_string()))?;
+                pending.extend(
+                    listing
+                        .into_iter()
+                        .map(|f| (f.path, f.file_size.map(|s| s as u64))),
+                );
+            }
+        }
+
+        self.for_each_concurrent(files, |(artifact_path, local, size)| {
+            repository.download_artifact_with_progress(
+                &artifact_path,
+                &local,
+                self.file_progress(&artifact_path, size),
+            )
+        })?;
+        Ok(local_path)
+    }
+}
+
+impl Run {
+    /// Uploads and downloads of the run's artifacts, configurable beyond what
+    /// [`Run::log_artifacts`] and [`Run::download_artifact`] offer.
+    pub fn artifact_transfer(&self) -> ArtifactTransfer<'_> {
+        ArtifactTransfer {
+            run: self,
+            progress: None,
+            concurrency: 1,
+        }
+    }
+}
+
+/// Calls `f` for each item on up to `concurrency` scoped threads taking items from a shared
+/// queue, stopping at the first error.
+pub(crate) fn for_each_concurrent<T: Send>(
+    concurrency: usize,
+    items: Vec<T>,
+    f: impl Fn(T) -> MLFlowResult<()> + Sync,
+) -> MLFlowResult<()> {
+    let workers = concurrency.min(items.len());
+    if workers <= 1 {
+        return items.into_iter().try_for_each(f);
+    }
+
+    let queue = Mutex::new(items.into_iter());
+    let error = Mutex::new(None);
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                if error.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+                    return;
+                }
+                let Some(item) = queue.lock().unwrap_or_else(|e| e.into_inner()).next() else {
+                    return;
+                };
+                if let Err(e) = f(item) {
+                    error
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .get_or_insert(e);
+                    return;
+                }
+            });
+        }
+    });
+
+    match error.into_inner().unwrap_or_else(|e| e.into_inner()) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::SharedClient;
+    use crate::RunData;
+
+    #[test]
+    fn progress_reader_counts_bytes() {
+        let counted = Arc::new(AtomicU64::new(0));
+        let total = counted.clone();
+        let mut reader = ProgressReader::new(
+            std::io::Cursor::new(vec![7u8; 10_000]),
+            Arc::new(move |bytes| {
+                total.fetch_add(bytes, Ordering::Relaxed);
+            }),
+        );
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+        assert_eq!(counted.load(Ordering::Relaxed), 10_000);
+    }
+
+    fn local_run(store: &Path) -> Run {
+        Run {
+            info: serde_json::from_value(serde_json::json!({
+                "run_id": "abc",
+                "experiment_id": "1",
+                "status": "RUNNING",
+                "artifact_uri": store.to_string_lossy(),
+            }))
+            .unwrap(),
+            data: RunData::default(),
+            client: SharedClient::default(),
+        }
+    }
+
+    #[test]
+    fn reports_progress_per_file() {
+        let store = tempfile::tempdir().unwrap();
+        let run = local_run(store.path());
+
+        let local = tempfile::tempdir().unwrap();
+        std::fs::write(local.path().join("a.bin"), vec![0u8; 100_000]).unwrap();
+        std::fs::write(local.path().join("b.bin"), vec![0u8; 10]).unwrap();
+
+        let reports = Arc::new(Mutex::new(vec![]));
+        let recorded = reports.clone();
+        let transfer = run
+            .artifact_transfer()
+            .with_progress(move |p| recorded.lock().unwrap().push(p.clone()));
+        transfer.log_artifacts(local.path(), Some("ckpt")).unwrap();
+
+        let last = |path: &str| {
+            reports
+                .lock()
+                .unwrap()
+                .iter()
+                .rfind(|p| p.artifact_path == path)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(last("ckpt/a.bin").bytes_transferred, 100_000);
+        assert_eq!(last("ckpt/a.bin").total_bytes, Some(100_000));
+        assert_eq!(last("ckpt/b.bin").bytes_transferred, 10);
+
+        reports.lock().unwrap().clear();
+        let dest = tempfile::tempdir().unwrap();
+        transfer.download_artifact("ckpt", dest.path()).unwrap();
+        assert_eq!(last("ckpt/a.bin").bytes_transferred, 100_000);
+        assert_eq!(last("ckpt/a.bin").total_bytes, Some(100_000));
+    }
+
+    #[test]
+    fn concurrent_transfers() {
+        let store = tempfile::tempdir().unwrap();
+        let run = local_run(store.path());
+
+        let local = tempfile::tempdir().unwrap();
+        for i in 0..20 {
+            let dir = local.path().join(format!("epoch-{}", i % 3));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(format!("{}.bin", i)), vec![i as u8; 1000 + i]).unwrap();
+        }
+        let transfer = run.artifact_transfer().with_concurrency(4);
+        transfer.log_artifacts(local.path(), Some("ckpt")).unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let downloaded = transfer.download_artifact("ckpt", dest.path()).unwrap();
+        let uploaded: Vec<String> = collect_files(local.path())
+            .unwrap()
+            .into_iter()
+            .map(|(_, r)| r)
+            .collect();
+        for relative in &uploaded {
+            assert_eq!(
+                std::fs::read(downloaded.join(relative)).unwrap(),
+                std::fs::read(local.path().join(relative)).unwrap()
+            );
+        }
+        assert_eq!(uploaded.len(), 20);
+
+        let failing: Vec<usize> = (0..10).collect();
+        let error = transfer
+            .for_each_concurrent(failing, |i| match i {
+                7 => Err(ArtifactError("boom".to_string())),
+                _ => Ok(()),
+            })
+            .expect_err("the first error is returned");
+        assert_eq!(error.to_string(), "ArtifactError: boom");
+    }
+}
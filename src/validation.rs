@@ -0,0 +1,204 @@
+//! Client-side checks of the constraints the tracking server puts on keys and values, so invalid
+//! input fails with a [`ValidationError`](crate::MLFlowError::ValidationError) naming the
+//! offending key instead of a rejected request.
+
+use crate::artifacts::{is_registered_scheme, uri_scheme};
+use crate::entities::{ExperimentTag, Param, RunTag};
+use crate::MLFlowError::ValidationError;
+use crate::MLFlowResult;
+
+pub(crate) const MAX_KEY_LENGTH: usize = 250;
+pub(crate) const MAX_PARAM_VALUE_LENGTH: usize = 6000;
+pub(crate) const MAX_TAG_VALUE_LENGTH: usize = 8000;
+pub(crate) const MAX_EXPERIMENT_TAG_VALUE_LENGTH: usize = 5000;
+
+/// Schemes of artifact roots the tracking server knows how to store artifacts under.
+const ARTIFACT_LOCATION_SCHEMES: &[&str] = &[
+    "file",
+    "mlflow-artifacts",
+    "http",
+    "https",
+    "s3",
+    "gs",
+    "wasbs",
+    "abfss",
+    "hdfs",
+    "viewfs",
+    "dbfs",
+    "ftp",
+    "sftp",
+];
+
+/// Schemes whose locations name a bucket or container, e.g. `s3://bucket/path`.
+const BUCKET_SCHEMES: &[&str] = &["s3", "gs", "wasbs", "abfss"];
+
+/// Checks a metric, param or tag key: non-empty, at most 250 characters of alphanumerics,
+/// `_`, `-`, `.`, ` `, `:` and `/`, and not resolving to another key when read as a path,
+/// since some stores keep each key in a file of that name.
+fn validate_key(kind: &str, key: &str) -> MLFlowResult<()> {
+    if key.is_empty() {
+        return Err(ValidationError(format!("{} key cannot be empty", kind)));
+    }
+    if key.chars().count() > MAX_KEY_LENGTH {
+        return Err(ValidationError(format!(
+            "{} key '{}' is longer than {} characters",
+            kind, key, MAX_KEY_LENGTH
+        )));
+    }
+    if !key
+        .chars()
+        .all(|c| c.is_alphanumeric() || "_-. :/".contains(c))
+    {
+        return Err(ValidationError(format!(
+            "{} key '{}' may only contain alphanumerics, underscores (_), dashes (-), \
+             periods (.), spaces ( ), colons (:) and slashes (/)",
+            kind, key
+        )));
+    }
+    if key
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(ValidationError(format!(
+            "{} key '{}' must not resolve to another key when treated as a path",
+            kind, key
+        )));
+    }
+    Ok(())
+}
+
+fn validate_value_length(kind: &str, key: &str, value: &str, limit: usize) -> MLFlowResult<()> {
+    match value.chars().count() > limit {
+        true => Err(ValidationError(format!(
+            "value of {} '{}' is longer than {} characters",
+            kind, key, limit
+        ))),
+        false => Ok(()),
+    }
+}
+
+pub(crate) fn validate_metric_key(key: &str) -> MLFlowResult<()> {
+    validate_key("metric", key)
+}
+
+pub(crate) fn validate_param(param: &Param) -> MLFlowResult<()> {
+    validate_key("param", &param.key)?;
+    validate_value_length("param", &param.key, &param.value, MAX_PARAM_VALUE_LENGTH)
+}
+
+pub(crate) fn validate_tag_key(key: &str) -> MLFlowResult<()> {
+    validate_key("tag", key)
+}
+
+pub(crate) fn validate_run_tag(tag: &RunTag) -> MLFlowResult<()> {
+    validate_tag_key(&tag.key)?;
+    validate_value_length("tag", &tag.key, &tag.value, MAX_TAG_VALUE_LENGTH)
+}
+
+pub(crate) fn validate_experiment_tag(tag: &ExperimentTag) -> MLFlowResult<()> {
+    validate_tag_key(&tag.key)?;
+    validate_value_length("tag", &tag.key, &tag.value, MAX_EXPERIMENT_TAG_VALUE_LENGTH)
+}
+
+pub(crate) fn validate_experiment_name(name: &str) -> MLFlowResult<()> {
+    match name.trim().is_empty() {
+        true => Err(ValidationError(
+            "experiment name cannot be empty".to_string(),
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Checks an experiment's artifact root: a plain path, or a URI with a scheme the server supports
+/// or a repository was registered for. Bucket stores like `s3` and `gs` must name the bucket.
+pub(crate) fn validate_artifact_location(location: &str) -> MLFlowResult<()> {
+    if location.trim().is_empty() {
+        return Err(ValidationError(
+            "artifact location cannot be empty".to_string(),
+        ));
+    }
+    let scheme = match uri_scheme(location) {
+        Some(scheme) => scheme,
+        None => return Ok(()),
+    };
+    if !ARTIFACT_LOCATION_SCHEMES.contains(&scheme.as_str()) && !is_registered_scheme(&scheme) {
+        return Err(ValidationError(format!(
+            "artifact location '{}' has the unsupported scheme {}",
+            location, scheme
+        )));
+    }
+    if BUCKET_SCHEMES.contains(&scheme.as_str()) {
+        let bucket = location[scheme.len() + 1..]
+            .strip_prefix("//")
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or_default();
+        if bucket.is_empty() {
+            return Err(ValidationError(format!(
+                "artifact location '{}' must look like {}://bucket/path",
+                location, scheme
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        for key in ["loss", "val/loss", "Genauigkeit 1:2", "a.b-c_d", "größe"] {
+            validate_metric_key(key).unwrap();
+        }
+        for key in [
+            "",
+            "loss!",
+            "/loss",
+            "val//loss",
+            "val/",
+            "./loss",
+            "a/../b",
+            &"k".repeat(MAX_KEY_LENGTH + 1),
+        ] {
+            validate_metric_key(key).expect_err(key);
+        }
+        let error = validate_metric_key("loss!").unwrap_err().to_string();
+        assert!(error.starts_with("ValidationError: metric key 'loss!'"));
+    }
+
+    #[test]
+    fn values() {
+        validate_param(&Param::from(("lr", "0.01"))).unwrap();
+        let long = "v".repeat(MAX_PARAM_VALUE_LENGTH + 1);
+        validate_param(&Param::from(("lr", long.as_str()))).expect_err("param value too long");
+        validate_run_tag(&RunTag::from(("note", long.as_str()))).unwrap();
+        validate_experiment_tag(&ExperimentTag::from(("note", long.as_str())))
+            .expect_err("experiment tag value too long");
+        validate_experiment_name(" ").expect_err("blank name");
+    }
+
+    #[test]
+    fn artifact_locations() {
+        for location in [
+            "/mnt/mlruns",
+            "C:\\mlruns",
+            "file:///mnt/mlruns",
+            "s3://bucket/mlruns",
+            "GS://bucket",
+            "wasbs://container@account.blob.core.windows.net/mlruns",
+            "mlflow-artifacts:/",
+        ] {
+            validate_artifact_location(location).unwrap();
+        }
+        for location in [
+            "",
+            "s3:/bucket",
+            "s3:///mlruns",
+            "gs:",
+            "bucket-store://a/b",
+        ] {
+            validate_artifact_location(location).expect_err(location);
+        }
+    }
+}
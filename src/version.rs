@@ -0,0 +1,120 @@
+//! Versions of the tracking server, used to fail fast on endpoints it doesn't serve yet.
+
+#[cfg(feature = "blocking")]
+use crate::MLFlowError::ClientError;
+use crate::MLFlowError::UnknownError;
+use crate::MLFlowResult;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The MLflow release a tracking server runs, as reported by its `/version` endpoint.
+///
+/// Pre-release and development suffixes are ignored, so `2.10.0rc0` compares equal to `2.10.0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        ServerVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl Display for ServerVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ServerVersion {
+    type Err = crate::MLFlowError;
+
+    fn from_str(version: &str) -> MLFlowResult<Self> {
+        let version = version.trim().trim_matches('"');
+        let mut components = version.split('.').map(|component| {
+            let digits: String = component
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            digits.parse::<u32>().ok()
+        });
+        let invalid = || UnknownError(format!("{} is not an MLflow version", version));
+        let major = components.next().flatten().ok_or_else(invalid)?;
+        let minor = components.next().flatten().ok_or_else(invalid)?;
+        let patch = components.next().flatten().unwrap_or_default();
+        Ok(ServerVersion::new(major, minor, patch))
+    }
+}
+
+/// The first releases serving endpoints that older servers answer with a 404.
+#[cfg(feature = "blocking")]
+pub(crate) const LOG_INPUTS_VERSION: ServerVersion = ServerVersion::new(2, 4, 0);
+#[cfg(feature = "blocking")]
+pub(crate) const TRACES_VERSION: ServerVersion = ServerVersion::new(2, 14, 0);
+
+/// Fails if `version` is known to be older than `minimum`, the first release serving
+/// `capability`. An unknown version is given the benefit of the doubt.
+#[cfg(feature = "blocking")]
+pub(crate) fn require_version(
+    version: Option<ServerVersion>,
+    capability: &str,
+    minimum: ServerVersion,
+) -> MLFlowResult<()> {
+    match version {
+        Some(version) if version < minimum => Err(ClientError(format!(
+            "{} requires MLflow {} or newer, the tracking server runs {}",
+            capability, minimum, version
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "2.9.2".parse::<ServerVersion>().unwrap(),
+            ServerVersion::new(2, 9, 2)
+        );
+        assert_eq!(
+            "2.10.0rc0\n".parse::<ServerVersion>().unwrap(),
+            ServerVersion::new(2, 10, 0)
+        );
+        assert_eq!(
+            "3.1".parse::<ServerVersion>().unwrap(),
+            ServerVersion::new(3, 1, 0)
+        );
+        "<html>"
+            .parse::<ServerVersion>()
+            .expect_err("not a version");
+        assert!(ServerVersion::new(2, 9, 2) < ServerVersion::new(2, 14, 0));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn required_versions() {
+        require_version(None, "tracing", TRACES_VERSION).unwrap();
+        require_version(
+            Some(ServerVersion::new(2, 14, 1)),
+            "tracing",
+            TRACES_VERSION,
+        )
+        .unwrap();
+        let error = require_version(Some(ServerVersion::new(2, 9, 2)), "tracing", TRACES_VERSION)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "ClientError: tracing requires MLflow 2.14.0 or newer, the tracking server runs 2.9.2"
+        );
+    }
+}
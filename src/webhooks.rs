@@ -0,0 +1,537 @@
+//! Model registry webhooks, which notify an HTTP endpoint or trigger a job when registry events
+//! such as stage transitions happen.
+//!
+//! Only some backends, such as Databricks workspaces, serve the webhooks API; an open source
+//! tracking server answers these requests with an error.
+
+#[cfg(feature = "blocking")]
+use crate::client::MLFLowRestClient;
+use crate::client::{
+    CreateRegistryWebhookRequest, ListRegistryWebhooksRequest, MLFlowClient, SharedClient,
+    TestRegistryWebhookRequest, UpdateRegistryWebhookRequest,
+};
+use crate::config::default_client;
+use crate::pagination::{Page, PageIterator};
+use crate::MLFlowError::WebhookBuilderError;
+use crate::MLFlowResult;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// Registry event a webhook fires on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RegistryWebhookEvent {
+    RegisteredModelCreated,
+    ModelVersionCreated,
+    ModelVersionTagSet,
+    ModelVersionTransitionedStage,
+    ModelVersionTransitionedToStaging,
+    ModelVersionTransitionedToProduction,
+    ModelVersionTransitionedToArchived,
+    TransitionRequestCreated,
+    TransitionRequestToStagingCreated,
+    TransitionRequestToProductionCreated,
+    TransitionRequestToArchivedCreated,
+    CommentCreated,
+}
+
+impl Display for RegistryWebhookEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let event = match self {
+            RegistryWebhookEvent::RegisteredModelCreated => "REGISTERED_MODEL_CREATED",
+            RegistryWebhookEvent::ModelVersionCreated => "MODEL_VERSION_CREATED",
+            RegistryWebhookEvent::ModelVersionTagSet => "MODEL_VERSION_TAG_SET",
+            RegistryWebhookEvent::ModelVersionTransitionedStage => {
+                "MODEL_VERSION_TRANSITIONED_STAGE"
+            }
+            RegistryWebhookEvent::ModelVersionTransitionedToStaging => {
+                "MODEL_VERSION_TRANSITIONED_TO_STAGING"
+            }
+            RegistryWebhookEvent::ModelVersionTransitionedToProduction => {
+                "MODEL_VERSION_TRANSITIONED_TO_PRODUCTION"
+            }
+            RegistryWebhookEvent::ModelVersionTransitionedToArchived => {
+                "MODEL_VERSION_TRANSITIONED_TO_ARCHIVED"
+            }
+            RegistryWebhookEvent::TransitionRequestCreated => "TRANSITION_REQUEST_CREATED",
+            RegistryWebhookEvent::TransitionRequestToStagingCreated => {
+                "TRANSITION_REQUEST_TO_STAGING_CREATED"
+            }
+            RegistryWebhookEvent::TransitionRequestToProductionCreated => {
+                "TRANSITION_REQUEST_TO_PRODUCTION_CREATED"
+            }
+            RegistryWebhookEvent::TransitionRequestToArchivedCreated => {
+                "TRANSITION_REQUEST_TO_ARCHIVED_CREATED"
+            }
+            RegistryWebhookEvent::CommentCreated => "COMMENT_CREATED",
+        };
+        f.write_str(event)
+    }
+}
+
+/// Whether a webhook fires on real events, only on [`RegistryWebhook::test`], or not at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RegistryWebhookStatus {
+    #[default]
+    Active,
+    TestMode,
+    Disabled,
+}
+
+/// The HTTP endpoint a webhook posts event payloads to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpUrlSpec {
+    pub url: String,
+    /// Shared secret the payload is signed with, in the `X-Databricks-Signature` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Value of the `Authorization` header sent with the payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_ssl_verification: Option<bool>,
+}
+
+impl HttpUrlSpec {
+    pub fn new(url: impl AsRef<str>) -> HttpUrlSpec {
+        HttpUrlSpec {
+            url: url.as_ref().to_string(),
+            secret: None,
+            authorization: None,
+            enable_ssl_verification: None,
+        }
+    }
+
+    pub fn with_secret(mut self, secret: impl AsRef<str>) -> Self {
+        self.secret = Some(secret.as_ref().to_string());
+        self
+    }
+
+    pub fn with_authorization(mut self, authorization: impl AsRef<str>) -> Self {
+        self.authorization = Some(authorization.as_ref().to_string());
+        self
+    }
+
+    pub fn with_ssl_verification(mut self, enable: bool) -> Self {
+        self.enable_ssl_verification = Some(enable);
+        self
+    }
+}
+
+/// The Databricks job a webhook triggers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobSpec {
+    pub job_id: String,
+    /// The workspace the job lives in, when it is not the workspace of the registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_url: Option<String>,
+    /// Token used to trigger the job. The server never returns it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+}
+
+impl JobSpec {
+    pub fn new(job_id: impl AsRef<str>, access_token: impl AsRef<str>) -> JobSpec {
+        JobSpec {
+            job_id: job_id.as_ref().to_string(),
+            workspace_url: None,
+            access_token: Some(access_token.as_ref().to_string()),
+        }
+    }
+
+    pub fn with_workspace_url(mut self, workspace_url: impl AsRef<str>) -> Self {
+        self.workspace_url = Some(workspace_url.as_ref().to_string());
+        self
+    }
+}
+
+/// The response of the endpoint a test payload was sent to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookTestResult {
+    pub status_code: i32,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegistryWebhook {
+    pub id: String,
+    #[serde(default, alias = "creation_time")]
+    pub creation_timestamp: Option<i64>,
+    #[serde(default, alias = "last_update_time")]
+    pub last_updated_timestamp: Option<i64>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub status: RegistryWebhookStatus,
+    #[serde(default)]
+    pub events: Vec<RegistryWebhookEvent>,
+    #[serde(default)]
+    pub http_url_spec: Option<HttpUrlSpec>,
+    #[serde(default)]
+    pub job_spec: Option<JobSpec>,
+    /// The model whose events the webhook fires on, or `None` for every model in the registry.
+    #[serde(default)]
+    pub model_name: Option<String>,
+
+    /// Fields this crate doesn't know about yet, as sent by newer servers.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) client: SharedClient,
+}
+
+impl RegistryWebhook {
+    pub(crate) fn with_client(mut self, client: SharedClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Applies `update`, updating this instance with the server's response.
+    pub fn update(&mut self, update: RegistryWebhookUpdate) -> MLFlowResult<()> {
+        let request = UpdateRegistryWebhookRequest {
+            id: self.id.clone(),
+            description: update.description,
+            events: update.events,
+            status: update.status,
+            http_url_spec: update.http_url_spec,
+            job_spec: update.job_spec,
+        };
+        let resp = self.client.update_registry_webhook(&request)?;
+        *self = resp.webhook.with_client(self.client.clone());
+        Ok(())
+    }
+
+    pub fn delete(self) -> MLFlowResult<()> {
+        self.client.delete_registry_webhook(&self.id)
+    }
+
+    /// Sends a test payload for `event`, or the first of the webhook's events, and returns the
+    /// endpoint's response.
+    pub fn test(&self, event: Option<RegistryWebhookEvent>) -> MLFlowResult<WebhookTestResult> {
+        let request = TestRegistryWebhookRequest {
+            id: self.id.clone(),
+            event,
+        };
+        Ok(self.client.test_registry_webhook(&request)?.webhook)
+    }
+}
+
+/// The fields of a webhook to change with [`RegistryWebhook::update`]. Unset fields are left as
+/// they are.
+#[derive(Clone, Debug, Default)]
+pub struct RegistryWebhookUpdate {
+    description: Option<String>,
+    events: Option<Vec<RegistryWebhookEvent>>,
+    status: Option<RegistryWebhookStatus>,
+    http_url_spec: Option<HttpUrlSpec>,
+    job_spec: Option<JobSpec>,
+}
+
+impl RegistryWebhookUpdate {
+    pub fn with_description(mut self, description: impl AsRef<str>) -> Self {
+        self.description = Some(description.as_ref().to_string());
+        self
+    }
+
+    pub fn with_events(mut self, events: Vec<RegistryWebhookEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_status(mut self, status: RegistryWebhookStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn with_http_url_spec(mut self, http_url_spec: HttpUrlSpec) -> Self {
+        self.http_url_spec = Some(http_url_spec);
+        self
+    }
+
+    pub fn with_job_spec(mut self, job_spec: JobSpec) -> Self {
+        self.job_spec = Some(job_spec);
+        self
+    }
+}
+
+/// Creates a registry webhook.
+///
+/// ```no_run
+/// use mlflow_rs::{HttpUrlSpec, RegistryWebhookBuilder, RegistryWebhookEvent};
+///
+/// let webhook = RegistryWebhookBuilder::new(vec![
+///     RegistryWebhookEvent::ModelVersionTransitionedToProduction,
+/// ])
+/// .unwrap()
+/// .with_model_name("fraud-detector")
+/// .with_http_url_spec(HttpUrlSpec::new("https://ci.example.com/hooks/mlflow").with_secret("s3cr3t"))
+/// .build()
+/// .unwrap();
+/// println!("{:?}", webhook.test(None).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct RegistryWebhookBuilder {
+    events: Vec<RegistryWebhookEvent>,
+    model_name: Option<String>,
+    description: Option<String>,
+    status: Option<RegistryWebhookStatus>,
+    http_url_spec: Option<HttpUrlSpec>,
+    job_spec: Option<JobSpec>,
+    client: SharedClient,
+}
+
+impl RegistryWebhookBuilder {
+    pub fn new(events: Vec<RegistryWebhookEvent>) -> MLFlowResult<RegistryWebhookBuilder> {
+        if events.is_empty() {
+            return Err(WebhookBuilderError(
+                "at least one event is required".to_string(),
+            ));
+        }
+
+        Ok(RegistryWebhookBuilder {
+            events,
+            model_name: None,
+            description: None,
+            status: None,
+            http_url_spec: None,
+            job_spec: None,
+            client: default_client()?,
+        })
+    }
+
+    /// Fires only on events of the registered model `model_name` instead of every model.
+    pub fn with_model_name(mut self, model_name: impl AsRef<str>) -> Self {
+        self.model_name = Some(model_name.as_ref().to_string());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl AsRef<str>) -> Self {
+        self.description = Some(description.as_ref().to_string());
+        self
+    }
+
+    /// Creates the webhook in `status` instead of [`RegistryWebhookStatus::Active`].
+    pub fn with_status(mut self, status: RegistryWebhookStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn with_http_url_spec(mut self, http_url_spec: HttpUrlSpec) -> Self {
+        self.http_url_spec = Some(http_url_spec);
+        self
+    }
+
+    pub fn with_job_spec(mut self, job_spec: JobSpec) -> Self {
+        self.job_spec = Some(job_spec);
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = client.into().into();
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    fn request(&self) -> MLFlowResult<CreateRegistryWebhookRequest> {
+        if self.http_url_spec.is_some() == self.job_spec.is_some() {
+            return Err(WebhookBuilderError(
+                "exactly one of http_url_spec and job_spec is required".to_string(),
+            ));
+        }
+
+        Ok(CreateRegistryWebhookRequest {
+            model_name: self.model_name.clone(),
+            events: self.events.clone(),
+            description: self.description.clone(),
+            status: self.status,
+            http_url_spec: self.http_url_spec.clone(),
+            job_spec: self.job_spec.clone(),
+        })
+    }
+
+    pub fn build(self) -> MLFlowResult<RegistryWebhook> {
+        let resp = self.client.create_registry_webhook(&self.request()?)?;
+        Ok(resp.webhook.with_client(self.client))
+    }
+}
+
+/// Lists registry webhooks, optionally only those of one model or firing on some events.
+///
+/// ```no_run
+/// use mlflow_rs::{RegistryWebhookEvent, RegistryWebhookSearch};
+///
+/// let webhooks = RegistryWebhookSearch::default()
+///     .with_model_name("fraud-detector")
+///     .with_events(vec![RegistryWebhookEvent::ModelVersionTransitionedStage])
+///     .search()
+///     .collect::<Result<Vec<_>, _>>();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RegistryWebhookSearch {
+    model_name: Option<String>,
+    events: Vec<RegistryWebhookEvent>,
+    client: Option<SharedClient>,
+}
+
+impl RegistryWebhookSearch {
+    pub fn with_model_name(mut self, model_name: impl AsRef<str>) -> Self {
+        self.model_name = Some(model_name.as_ref().to_string());
+        self
+    }
+
+    /// Only lists webhooks firing on at least one of `events`.
+    pub fn with_events(mut self, events: Vec<RegistryWebhookEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn with_rest_client(mut self, client: MLFLowRestClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Sends requests through any [`MLFlowClient`](crate::MLFlowClient) implementation.
+    pub fn with_dyn_client(mut self, client: impl Into<Arc<dyn MLFlowClient>>) -> Self {
+        self.client = Some(client.into().into());
+        self
+    }
+
+    /// Sends requests to an in-memory [`MockMLFlowClient`](crate::MockMLFlowClient) instead of a
+    /// tracking server.
+    #[cfg(feature = "mock")]
+    pub fn with_mock_client(mut self, client: crate::MockMLFlowClient) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Returns a lazy iterator over every matching webhook. Pages are fetched on demand.
+    pub fn search(self) -> PageIterator<RegistryWebhook> {
+        let mut request = ListRegistryWebhooksRequest {
+            model_name: self.model_name,
+            events: self.events,
+            page_token: None,
+        };
+        let client = match self.client.map(Ok).unwrap_or_else(default_client) {
+            Ok(client) => client,
+            Err(e) => return PageIterator::new(move |_| Err(e.clone())),
+        };
+        PageIterator::new(move |page_token| {
+            request.page_token = page_token.map(str::to_string);
+            client.list_registry_webhooks(&request).map(|resp| {
+                Page::new(
+                    resp.webhooks
+                        .into_iter()
+                        .map(|w| w.with_client(client.clone()))
+                        .collect(),
+                    resp.next_page_token,
+                )
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_request() {
+        RegistryWebhookBuilder::new(vec![]).expect_err("at least one event is required");
+
+        let builder = RegistryWebhookBuilder::new(vec![RegistryWebhookEvent::ModelVersionCreated])
+            .unwrap()
+            .with_model_name("fraud-detector");
+        builder
+            .request()
+            .expect_err("exactly one of http_url_spec and job_spec is required");
+
+        let builder = builder
+            .with_status(RegistryWebhookStatus::TestMode)
+            .with_http_url_spec(HttpUrlSpec::new("https://ci.example.com").with_secret("s"));
+        assert_eq!(
+            serde_json::to_value(builder.request().unwrap()).unwrap(),
+            serde_json::json!({
+                "model_name": "fraud-detector",
+                "events": ["MODEL_VERSION_CREATED"],
+                "status": "TEST_MODE",
+                "http_url_spec": {"url": "https://ci.example.com", "secret": "s"},
+            })
+        );
+        assert_eq!(
+            RegistryWebhookEvent::TransitionRequestToProductionCreated.to_string(),
+            "TRANSITION_REQUEST_TO_PRODUCTION_CREATED"
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn manage_webhooks() {
+        use crate::{MockMLFlowClient, RegisteredModelBuilder};
+
+        let mock = MockMLFlowClient::default();
+        RegisteredModelBuilder::new("fraud-detector")
+            .unwrap()
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+        let mut webhook =
+            RegistryWebhookBuilder::new(vec![RegistryWebhookEvent::ModelVersionTransitionedStage])
+                .unwrap()
+                .with_model_name("fraud-detector")
+                .with_job_spec(JobSpec::new("42", "token"))
+                .with_mock_client(mock.clone())
+                .build()
+                .unwrap();
+        RegistryWebhookBuilder::new(vec![RegistryWebhookEvent::RegisteredModelCreated])
+            .unwrap()
+            .with_http_url_spec(HttpUrlSpec::new("https://ci.example.com"))
+            .with_mock_client(mock.clone())
+            .build()
+            .unwrap();
+
+        webhook
+            .update(RegistryWebhookUpdate::default().with_status(RegistryWebhookStatus::Disabled))
+            .unwrap();
+        assert_eq!(webhook.status, RegistryWebhookStatus::Disabled);
+        assert_eq!(webhook.test(None).unwrap().status_code, 200);
+        webhook
+            .test(Some(RegistryWebhookEvent::CommentCreated))
+            .expect_err("the webhook does not fire on comments");
+
+        let search = || RegistryWebhookSearch::default().with_mock_client(mock.clone());
+        assert_eq!(search().search().count(), 2);
+        let found: Vec<_> = search()
+            .with_events(vec![RegistryWebhookEvent::ModelVersionTransitionedStage])
+            .search()
+            .map(|w| w.unwrap().id)
+            .collect();
+        assert_eq!(found, vec![webhook.id.clone()]);
+
+        webhook.delete().unwrap();
+        assert_eq!(
+            search().with_model_name("fraud-detector").search().count(),
+            0
+        );
+    }
+}
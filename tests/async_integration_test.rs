@@ -0,0 +1,208 @@
+#![cfg(feature = "async")]
+
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use rstest::*;
+use testcontainers::core::ContainerPort::Tcp;
+use testcontainers::{
+    core::WaitFor, runners::AsyncRunner, ContainerRequest, GenericImage, ImageExt,
+};
+
+use mlflow_rs::{
+    AsyncMLFlowClient, Dataset, DatasetInput, ExperimentIdentifier, LifecycleStage,
+    MLFlowAsyncRestClient, MLFlowError, Metric, Param, RunSearch, RunStatus,
+};
+
+const MLFLOW_VERSION: &str = "2.18.0";
+const MLFLOW_DOCKER_IMAGE: &str = "ghcr.io/mlflow/mlflow";
+const MLFLOW_ENTRY_POINT: &str = "mlflow";
+
+#[fixture]
+fn mlflow_server_container() -> ContainerRequest<GenericImage> {
+    let docker_version = format!("v{}", MLFLOW_VERSION);
+    GenericImage::new(MLFLOW_DOCKER_IMAGE, docker_version.as_str())
+        .with_wait_for(WaitFor::seconds(5))
+        .with_entrypoint(MLFLOW_ENTRY_POINT)
+        .with_exposed_port(Tcp(5000))
+        .with_cmd(["server", "--host", "0.0.0.0", "--port", "5000"])
+}
+
+#[fixture]
+fn experiment_name() -> String {
+    let rand_string: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(5)
+        .map(char::from)
+        .collect();
+
+    format!("experiment-{}", rand_string)
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_async_experiment_lifecycle(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().await.unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).await.unwrap();
+    let client = MLFlowAsyncRestClient::new(format!("http://localhost:{}", host_port));
+
+    let id = client
+        .create_experiment(&experiment_name, None, vec![("tag1", "value1").into()])
+        .await
+        .unwrap();
+
+    let by_id = client.get_experiment_by_id(&id).await.unwrap();
+    assert_eq!(by_id.experiment_id().unwrap(), id);
+
+    let by_name = client
+        .get_experiment_by_name(&experiment_name)
+        .await
+        .unwrap();
+    assert_eq!(by_name.experiment_id().unwrap(), id);
+
+    assert!(matches!(
+        client.get_experiment_by_name("does-not-exist").await,
+        Err(MLFlowError::ExperimentNotFound(_))
+    ));
+
+    let renamed = format!("{}-renamed", experiment_name);
+    client.update_experiment(&id, &renamed).await.unwrap();
+    assert_eq!(
+        client.get_experiment_by_id(&id).await.unwrap().name,
+        renamed
+    );
+
+    client.delete_experiment(&id).await.unwrap();
+    assert_eq!(
+        client
+            .get_experiment_by_id(&id)
+            .await
+            .unwrap()
+            .lifecycle_stage,
+        Some(LifecycleStage::Deleted)
+    );
+    client.restore_experiment(&id).await.unwrap();
+    assert_eq!(
+        client
+            .get_experiment_by_id(&id)
+            .await
+            .unwrap()
+            .lifecycle_stage,
+        Some(LifecycleStage::Active)
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_async_run_logging(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().await.unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).await.unwrap();
+    let client = MLFlowAsyncRestClient::new(format!("http://localhost:{}", host_port));
+
+    let experiment_id = client
+        .create_experiment(&experiment_name, None, vec![])
+        .await
+        .unwrap();
+    let run = client
+        .create_run(&experiment_id, Some("async-run"), vec![])
+        .await
+        .unwrap();
+    assert_eq!(run.status, RunStatus::Running);
+
+    client
+        .log_metric(&run.run_id, Metric::new("loss", 0.5, Some(1), None))
+        .await
+        .unwrap();
+    client
+        .log_param(&run.run_id, Param::from(("lr", "0.01")))
+        .await
+        .unwrap();
+    assert!(matches!(
+        client
+            .log_param(&run.run_id, Param::from(("lr", "0.1")))
+            .await,
+        Err(MLFlowError::ParamValueChanged(_))
+    ));
+    client
+        .log_batch(
+            &run.run_id,
+            vec![Metric::new("acc", 0.9, Some(1), None)],
+            vec![],
+            vec![("tag1", "value1").into()],
+        )
+        .await
+        .unwrap();
+    let dataset = Dataset::new("train", "abc123", "local", r#"{"uri": "/data/train.csv"}"#);
+    client
+        .log_inputs(&run.run_id, vec![DatasetInput::new(dataset)])
+        .await
+        .unwrap();
+
+    let finished = client
+        .update_run(
+            &run.run_id,
+            RunStatus::Finished,
+            Some(run.start_time.unwrap() + 1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(finished.status, RunStatus::Finished);
+
+    let fetched = client.get_run(&run.run_id).await.unwrap();
+    assert_eq!(fetched.info.status, RunStatus::Finished);
+    assert_eq!(fetched.data.param("lr"), Some("0.01"));
+    assert_eq!(fetched.inputs.dataset_inputs.len(), 1);
+    assert!(client
+        .list_artifacts(&run.run_id, None, None)
+        .await
+        .unwrap()
+        .files
+        .is_empty());
+    assert!(client.server_version().await.is_ok());
+    let history = client
+        .get_metric_history(&run.run_id, "loss", None)
+        .await
+        .unwrap();
+    assert_eq!(history.metrics.len(), 1);
+    assert_eq!(history.metrics[0].value, 0.5);
+    assert!(matches!(
+        client.get_run("does-not-exist").await,
+        Err(MLFlowError::RunNotFound(_))
+    ));
+
+    let runs = RunSearch::new(vec![&experiment_id])
+        .unwrap()
+        .with_max_results(1)
+        .search_stream(client.clone())
+        .collect()
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].info.run_id, run.run_id);
+
+    client.delete_run(&run.run_id).await.unwrap();
+    assert_eq!(
+        client
+            .get_run(&run.run_id)
+            .await
+            .unwrap()
+            .info
+            .lifecycle_stage,
+        Some(LifecycleStage::Deleted)
+    );
+    client.restore_run(&run.run_id).await.unwrap();
+    assert_eq!(
+        client
+            .get_run(&run.run_id)
+            .await
+            .unwrap()
+            .info
+            .lifecycle_stage,
+        Some(LifecycleStage::Active)
+    );
+}
@@ -0,0 +1,96 @@
+//! The fluent API keeps process wide state, so it is exercised from its own test binary.
+#![cfg(feature = "blocking")]
+
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use rstest::*;
+use testcontainers::core::ContainerPort::Tcp;
+use testcontainers::{
+    core::WaitFor, runners::SyncRunner, ContainerRequest, GenericImage, ImageExt,
+};
+
+use mlflow_rs::{fluent, ExperimentIdentifier, Filter, MLFLowRestClient, RunSearch, RunStatus};
+
+const MLFLOW_VERSION: &str = "2.18.0";
+const MLFLOW_DOCKER_IMAGE: &str = "ghcr.io/mlflow/mlflow";
+const MLFLOW_ENTRY_POINT: &str = "mlflow";
+
+#[fixture]
+fn mlflow_server_container() -> ContainerRequest<GenericImage> {
+    let docker_version = format!("v{}", MLFLOW_VERSION);
+    GenericImage::new(MLFLOW_DOCKER_IMAGE, docker_version.as_str())
+        .with_wait_for(WaitFor::seconds(5))
+        .with_entrypoint(MLFLOW_ENTRY_POINT)
+        .with_exposed_port(Tcp(5000))
+        .with_cmd(["server", "--host", "0.0.0.0", "--port", "5000"])
+}
+
+#[fixture]
+fn experiment_name() -> String {
+    let rand_string: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(5)
+        .map(char::from)
+        .collect();
+
+    format!("experiment-{}", rand_string)
+}
+
+#[rstest]
+fn test_fluent_api(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    fluent::set_tracking_uri(&url);
+    let experiment = fluent::set_experiment(&experiment_name).unwrap();
+    // setting an existing experiment reuses it
+    assert_eq!(
+        fluent::set_experiment(&experiment_name)
+            .unwrap()
+            .experiment_id(),
+        experiment.experiment_id()
+    );
+
+    let parent_id = fluent::start_run().unwrap();
+    assert!(fluent::start_run().is_err());
+    fluent::log_param("learning_rate", 0.01).unwrap();
+
+    let child_id = fluent::start_nested_run().unwrap();
+    fluent::log_metric("rmse", 0.4, None, None).unwrap();
+    fluent::end_run().unwrap();
+    assert_eq!(fluent::active_run_id(), Some(parent_id.clone()));
+    fluent::end_run_with_status(RunStatus::Killed).unwrap();
+    assert_eq!(fluent::active_run_id(), None);
+
+    let find = |run_id: &str| {
+        RunSearch::new(vec![experiment.experiment_id().unwrap()])
+            .unwrap()
+            .with_filter(Filter::attribute("run_id").eq(run_id).build().unwrap())
+            .with_rest_client(MLFLowRestClient::new(&url))
+            .search()
+            .next()
+            .unwrap()
+            .unwrap()
+    };
+
+    let parent = find(&parent_id);
+    assert_eq!(parent.status(), RunStatus::Killed);
+    assert_eq!(parent.data().param("learning_rate"), Some("0.01"));
+
+    let child = find(&child_id);
+    assert_eq!(child.status(), RunStatus::Finished);
+    assert_eq!(child.data().metric("rmse"), Some(0.4));
+    assert_eq!(
+        child.data().tag(mlflow_rs::tags::MLFLOW_PARENT_RUN_ID),
+        Some(parent_id.as_str())
+    );
+
+    // logging without an active run starts one
+    fluent::log_metric("loss", 1.0, None, None).unwrap();
+    assert!(fluent::active_run_id().is_some());
+    fluent::end_run().unwrap();
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "blocking")]
+
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use rstest::*;
@@ -8,7 +10,10 @@ use testcontainers::{
 
 use mlflow_rs::ExperimentIdentifierType::{ById, ByName};
 use mlflow_rs::{
-    ExperimentBuilder, ExperimentIdentifier, ExperimentLoader, MLFLowRestClient, MLFlowError,
+    tags, ExperimentBuilder, ExperimentIdentifier, ExperimentLoader, ExperimentSearch, Filter,
+    LifecycleStage, MLFLowRestClient, MLFlowError, Metric, ModelVersionBuilder, ModelVersionLoader,
+    ModelVersionStage, Param, RegisteredModelBuilder, RegisteredModelLoader, RunBuilder,
+    RunIdentifier, RunSearch, RunStatus, RunViewType,
 };
 
 const MLFLOW_VERSION: &str = "2.18.0";
@@ -154,3 +159,735 @@ fn test_get_by_name_not_found(
         MLFlowError::ExperimentNotFound(s) if s == "experiment was not found"
     ));
 }
+
+#[rstest]
+fn test_create_and_finish_run(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    let mut run = RunBuilder::new(experiment_id.clone())
+        .unwrap()
+        .with_run_name("test-run")
+        .with_tag(("tag1", "value1"))
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    assert!(!run.run_id().is_empty());
+    assert_eq!(run.info().experiment_id, experiment_id);
+    assert_eq!(run.status(), RunStatus::Running);
+    assert!(run.start_time().is_some());
+    assert!(run.end_time().is_none());
+
+    run.finish(RunStatus::Finished).unwrap();
+    assert_eq!(run.status(), RunStatus::Finished);
+    assert!(run.end_time().is_some());
+}
+
+#[rstest]
+fn test_log_metric(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    assert!(run.log_metric("loss", 0.25, Some(1), None).is_ok());
+    assert!(run.log_metric("loss", f64::NAN, Some(2), None).is_ok());
+    assert!(run.log_metric("loss", f64::INFINITY, Some(3), None).is_ok());
+}
+
+#[rstest]
+fn test_log_param(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    assert!(run.log_param("learning_rate", 0.01).is_ok());
+    // re-logging the same value is allowed by the server
+    assert!(run.log_param("learning_rate", 0.01).is_ok());
+    assert!(matches!(
+        run.log_param("learning_rate", 0.1).err().unwrap(),
+        MLFlowError::ParamValueChanged(_)
+    ));
+}
+
+#[rstest]
+fn test_log_batch(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    // large enough to require several requests
+    let metrics = (0..1500)
+        .map(|i| Metric::new("loss", 1.0 / (i + 1) as f64, Some(i), None))
+        .collect();
+    let params = (0..150)
+        .map(|i| Param::from((format!("param{}", i).as_str(), "value")))
+        .collect();
+
+    assert!(run
+        .log_batch(metrics, params, vec![("tag1", "value1").into()])
+        .is_ok());
+}
+
+#[rstest]
+fn test_log_artifacts(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("nested")).unwrap();
+    std::fs::write(dir.path().join("model.txt"), "weights").unwrap();
+    std::fs::write(dir.path().join("nested/config.json"), "{}").unwrap();
+
+    assert!(run.log_artifact(dir.path().join("model.txt"), None).is_ok());
+    assert!(run.log_artifacts(dir.path(), Some("bundle")).is_ok());
+}
+
+#[rstest]
+fn test_list_and_download_artifacts(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let source = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(source.path().join("nested")).unwrap();
+    std::fs::write(source.path().join("model.txt"), "weights").unwrap();
+    std::fs::write(source.path().join("nested/config.json"), "{}").unwrap();
+    run.log_artifacts(source.path(), Some("bundle")).unwrap();
+
+    let root = run.list_artifacts(None).unwrap();
+    assert_eq!(root.len(), 1);
+    assert_eq!(root[0].path, "bundle");
+    assert!(root[0].is_dir);
+
+    let dest = tempfile::tempdir().unwrap();
+    let file = run
+        .download_artifact("bundle/model.txt", dest.path())
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(file).unwrap(), "weights");
+
+    let dir = run.download_artifact("bundle", dest.path()).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(dir.join("nested").join("config.json")).unwrap(),
+        "{}"
+    );
+}
+
+#[rstest]
+fn test_registered_model_lifecycle(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+    let model_name = format!("model-{}", experiment_name);
+    let renamed = format!("{}-renamed", model_name);
+
+    let client = MLFLowRestClient::new(url);
+    let mut model = RegisteredModelBuilder::new(&model_name)
+        .unwrap()
+        .with_description("test model")
+        .with_tag(("team", "ml"))
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    assert_eq!(model.name, model_name);
+    assert_eq!(model.tags.len(), 1);
+
+    model.set_tag(("stage", "dev")).unwrap();
+    model.delete_tag("team").unwrap();
+    model.rename(&renamed).unwrap();
+    assert_eq!(model.name, renamed);
+
+    let loaded = RegisteredModelLoader::default()
+        .with_client(client.clone())
+        .load(&renamed)
+        .unwrap();
+    assert_eq!(loaded.description, Some("test model".to_string()));
+    assert_eq!(loaded.tags, vec![("stage", "dev").into()]);
+
+    loaded.delete().unwrap();
+    assert!(matches!(
+        RegisteredModelLoader::default()
+            .with_client(client.clone())
+            .load(&renamed)
+            .err()
+            .unwrap(),
+        MLFlowError::RegisteredModelNotFound(_)
+    ));
+}
+
+#[rstest]
+fn test_model_version_lifecycle(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+    let model_name = format!("model-{}", experiment_name);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    RegisteredModelBuilder::new(&model_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let mut version = ModelVersionBuilder::new(
+        &model_name,
+        format!("{}/model", run.info().artifact_uri.clone().unwrap()),
+    )
+    .unwrap()
+    .with_run_id(run.run_id())
+    .with_tag(("validated", "false"))
+    .with_rest_client(client.clone())
+    .build()
+    .unwrap();
+    assert_eq!(version.version, "1");
+    assert_eq!(version.current_stage, ModelVersionStage::None);
+
+    version
+        .transition_stage(ModelVersionStage::Staging, false)
+        .unwrap();
+    assert_eq!(version.current_stage, ModelVersionStage::Staging);
+    version.set_tag(("validated", "true")).unwrap();
+    version.delete_tag("validated").unwrap();
+
+    let loaded = ModelVersionLoader::default()
+        .with_client(client.clone())
+        .load(&model_name, "1")
+        .unwrap();
+    assert_eq!(loaded.current_stage, ModelVersionStage::Staging);
+    assert!(loaded.tags.is_empty());
+
+    loaded.delete().unwrap();
+    assert!(matches!(
+        ModelVersionLoader::default()
+            .with_client(client.clone())
+            .load(&model_name, "1")
+            .err()
+            .unwrap(),
+        MLFlowError::ModelVersionNotFound(_)
+    ));
+}
+
+#[rstest]
+fn test_search_runs(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    for rmse in [0.9, 0.3, 0.6, 0.1, 0.7] {
+        let run = RunBuilder::new(&experiment_id)
+            .unwrap()
+            .with_rest_client(client.clone())
+            .build()
+            .unwrap();
+        run.log_metric("rmse", rmse, None, None).unwrap();
+    }
+
+    // a page size of 2 forces the iterator to follow page tokens
+    let runs: Vec<f64> = RunSearch::new(vec![experiment_id.clone()])
+        .unwrap()
+        .with_filter(Filter::metric("rmse").lt(0.8).build().unwrap())
+        .with_order_by(vec!["metrics.rmse ASC"])
+        .with_max_results(2)
+        .with_rest_client(client.clone())
+        .search()
+        .map(|r| r.unwrap().data().metric("rmse").unwrap())
+        .collect();
+    assert_eq!(runs, vec![0.1, 0.3, 0.6, 0.7]);
+}
+
+#[rstest]
+fn test_search_experiments(mlflow_server_container: ContainerRequest<GenericImage>) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    for i in 0..3 {
+        ExperimentBuilder::new(format!("search-nlp-{}", i))
+            .unwrap()
+            .with_tag(("team", "nlp"))
+            .with_rest_client(client.clone())
+            .build()
+            .unwrap();
+    }
+    ExperimentBuilder::new("search-cv")
+        .unwrap()
+        .with_tag(("team", "cv"))
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let names: Vec<String> = ExperimentSearch::default()
+        .with_filter("tags.team = 'nlp'")
+        .with_order_by(vec!["name DESC"])
+        .with_max_results(1)
+        .with_rest_client(client.clone())
+        .search()
+        .map(|e| e.unwrap().name)
+        .collect();
+    assert_eq!(names, vec!["search-nlp-2", "search-nlp-1", "search-nlp-0"]);
+
+    // includes the server's tag-less "Default" experiment
+    assert_eq!(
+        ExperimentSearch::default()
+            .with_rest_client(client.clone())
+            .search()
+            .count(),
+        5
+    );
+}
+
+#[rstest]
+fn test_experiment_lifecycle(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let mut experiment = ExperimentBuilder::new(&experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    let new_name = format!("{}-renamed", experiment_name);
+    experiment.rename(&new_name).unwrap();
+    let loaded = ExperimentLoader::default()
+        .with_client(client.clone())
+        .load(ById(experiment_id.clone()))
+        .unwrap();
+    assert_eq!(loaded.name, new_name);
+
+    experiment.delete().unwrap();
+    let loaded = ExperimentLoader::default()
+        .with_client(client.clone())
+        .load(ById(experiment_id.clone()))
+        .unwrap();
+    assert_eq!(loaded.lifecycle_stage, Some(LifecycleStage::Deleted));
+
+    experiment.restore().unwrap();
+    let loaded = ExperimentLoader::default()
+        .with_client(client.clone())
+        .load(ById(experiment_id))
+        .unwrap();
+    assert_eq!(loaded.lifecycle_stage, Some(LifecycleStage::Active));
+}
+
+#[rstest]
+fn test_run_tags(mlflow_server_container: ContainerRequest<GenericImage>, experiment_name: String) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    let run = RunBuilder::new(&experiment_id)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    assert!(run.data().tag(tags::MLFLOW_SOURCE_NAME).is_some());
+
+    run.set_tag("team", "nlp").unwrap();
+    run.set_tag("stale", "yes").unwrap();
+    run.delete_tag("stale").unwrap();
+
+    let found = RunSearch::new(vec![&experiment_id])
+        .unwrap()
+        .with_filter(Filter::tag("team").eq("nlp").build().unwrap())
+        .with_rest_client(client.clone())
+        .search()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.run_id(), run.run_id());
+    assert_eq!(found.data().tag("team"), Some("nlp"));
+    assert_eq!(found.data().tag("stale"), None);
+}
+
+#[rstest]
+fn test_nested_runs(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    let parent = RunBuilder::new(&experiment_id)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let mut child_ids: Vec<String> = (0..3)
+        .map(|_| {
+            RunBuilder::new(&experiment_id)
+                .unwrap()
+                .with_parent(parent.run_id())
+                .with_rest_client(client.clone())
+                .build()
+                .unwrap()
+                .run_id()
+        })
+        .collect();
+    // an unrelated run in the same experiment
+    RunBuilder::new(&experiment_id)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let mut children: Vec<String> = parent
+        .children()
+        .unwrap()
+        .iter()
+        .map(|r| r.run_id())
+        .collect();
+    children.sort();
+    child_ids.sort();
+    assert_eq!(children, child_ids);
+}
+
+#[rstest]
+fn test_active_run(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    let status_of = |run_id: &str| {
+        RunSearch::new(vec![&experiment_id])
+            .unwrap()
+            .with_filter(Filter::attribute("run_id").eq(run_id).build().unwrap())
+            .with_rest_client(client.clone())
+            .search()
+            .next()
+            .unwrap()
+            .unwrap()
+            .status()
+    };
+
+    let finished_id = {
+        let run = RunBuilder::new(&experiment_id)
+            .unwrap()
+            .with_rest_client(client.clone())
+            .start()
+            .unwrap();
+        run.log_metric("rmse", 0.4, None, None).unwrap();
+        run.run_id()
+    };
+    assert_eq!(status_of(&finished_id), RunStatus::Finished);
+
+    let run = RunBuilder::new(&experiment_id)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .start()
+        .unwrap();
+    let failed_id = run.run_id();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+        let _run = run;
+        panic!("training diverged");
+    }));
+    assert!(result.is_err());
+    assert_eq!(status_of(&failed_id), RunStatus::Failed);
+
+    let run = RunBuilder::new(&experiment_id)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .start()
+        .unwrap();
+    let killed_id = run.run_id();
+    run.end(RunStatus::Killed).unwrap();
+    assert_eq!(status_of(&killed_id), RunStatus::Killed);
+}
+
+#[rstest]
+fn test_metric_history(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let run = RunBuilder::new(experiment.experiment_id().unwrap())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    let metrics: Vec<Metric> = (0..1500)
+        .map(|step| Metric::new("loss", 1.0 / (step + 1) as f64, Some(step), None))
+        .collect();
+    run.log_batch(metrics, vec![], vec![]).unwrap();
+    run.log_metric("accuracy", 0.9, None, None).unwrap();
+
+    let mut history = run.get_metric_history("loss").unwrap();
+    history.sort_by_key(|m| m.step);
+    assert_eq!(history.len(), 1500);
+    assert_eq!(history[0].step, 0);
+    assert_eq!(history[0].value, 1.0);
+    assert_eq!(history[1499].step, 1499);
+    assert!(history.iter().all(|m| m.key == "loss"));
+
+    assert!(run.get_metric_history("missing").unwrap().is_empty());
+}
+
+#[rstest]
+fn test_server_error_codes(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(&experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        ExperimentBuilder::new(&experiment_name)
+            .unwrap()
+            .with_rest_client(client.clone())
+            .build(),
+        Err(MLFlowError::ResourceAlreadyExists(_))
+    ));
+
+    assert!(experiment.experiment_id().is_some());
+    assert!(matches!(
+        RunBuilder::new("does-not-exist")
+            .unwrap()
+            .with_rest_client(client.clone())
+            .build(),
+        Err(MLFlowError::ResourceDoesNotExist(_)) | Err(MLFlowError::InvalidParameterValue(_))
+    ));
+}
+
+#[rstest]
+fn test_experiment_get_or_create(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let created = ExperimentBuilder::new(&experiment_name)
+        .unwrap()
+        .with_tag(("team", "nlp"))
+        .with_rest_client(client.clone())
+        .get_or_create()
+        .unwrap();
+    let existing = ExperimentBuilder::new(&experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .get_or_create()
+        .unwrap();
+
+    assert_eq!(created.experiment_id(), existing.experiment_id());
+    assert_eq!(existing.name, experiment_name);
+    assert_eq!(existing.tags.len(), 1);
+}
+
+#[rstest]
+fn test_delete_and_restore_run(
+    mlflow_server_container: ContainerRequest<GenericImage>,
+    experiment_name: String,
+) {
+    let container = mlflow_server_container.start().unwrap();
+    let host_port = container.get_host_port_ipv4(Tcp(5000)).unwrap();
+    let url = format!("http://localhost:{}", host_port);
+
+    let client = MLFLowRestClient::new(url);
+    let experiment = ExperimentBuilder::new(experiment_name)
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let experiment_id = experiment.experiment_id().unwrap();
+
+    let mut run = RunBuilder::new(experiment_id.clone())
+        .unwrap()
+        .with_rest_client(client.clone())
+        .build()
+        .unwrap();
+    let search = |view_type| {
+        RunSearch::new(vec![experiment_id.as_str()])
+            .unwrap()
+            .with_run_view_type(view_type)
+            .with_rest_client(client.clone())
+            .search()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .len()
+    };
+
+    run.delete().unwrap();
+    assert_eq!(run.info().lifecycle_stage, Some(LifecycleStage::Deleted));
+    assert_eq!(search(RunViewType::ActiveOnly), 0);
+    assert_eq!(search(RunViewType::DeletedOnly), 1);
+
+    run.restore().unwrap();
+    assert_eq!(search(RunViewType::ActiveOnly), 1);
+    assert_eq!(search(RunViewType::All), 1);
+}